@@ -1,5 +1,6 @@
 use {
     crate::{
+        char::CharExt,
         decoration::{Decoration, DecorationType},
         layout::{BlockElement, WrappedElement},
         selection::Affinity,
@@ -10,6 +11,7 @@ use {
         token::TokenKind,
         Line, Selection, Token,
     },
+    makepad_regex::SearchOptions,
     makepad_widgets::*,
     std::fmt::Write,
     std::{mem, slice::Iter},
@@ -37,6 +39,7 @@ live_design! {
         delimiter_highlight: #f,
         error_decoration: #f00,
         warning_decoration: #0f0,
+        search_match_decoration: #e5c07b,
     }
 
     DrawIndentGuide = {{DrawIndentGuide}} {
@@ -161,6 +164,10 @@ live_design! {
         }
 
 
+        draw_composition: {
+            color: #3a
+        }
+
         draw_cursor_bg: {
             instance focus: 0.0
             fn pixel(self) -> vec4 {
@@ -230,8 +237,12 @@ pub struct CodeEditor {
     #[live] draw_selection: DrawSelection,
     #[live] draw_cursor: DrawColor,
     #[live] draw_cursor_bg: DrawColor,
+    #[live] draw_composition: DrawColor,
     #[live] draw_bg: DrawColor,
     #[rust(KeepCursorInView::Off)] keep_cursor_in_view: KeepCursorInView,
+    #[rust] composition: TextCompositionEvent,
+    // where the preedit is inserted while it is drawn, the text after it moves right
+    #[rust] composition_position: Option<Position>,
     #[rust] last_cursor_screen_pos: Option<DVec2>,
 
     #[rust] cell_size: DVec2,
@@ -342,6 +353,11 @@ impl CodeEditor {
             last_added_selection.cursor.affinity,
         );
         let cursor_pos = dvec2(cursor_x, cursor_y) * self.cell_size;
+        self.composition_position = if self.composition.is_empty() {
+            None
+        } else {
+            Some(last_added_selection.cursor.position)
+        };
         self.last_cursor_screen_pos = Some(cursor_pos - self.scroll_bars.get_scroll_pos());
         match self.keep_cursor_in_view {
             KeepCursorInView::Once | KeepCursorInView::Always(_, _) => {
@@ -477,6 +493,14 @@ impl CodeEditor {
         self.draw_indent_guide_layer(cx, session);
         self.draw_decoration_layer(cx, session);
         self.draw_selection_layer(cx, session);
+        self.draw_composition_layer(cx, cursor_pos);
+
+        if cx.has_key_focus(self.scroll_bars.area()) {
+            // place the input method candidate window at the cursor
+            let area_pos = self.scroll_bars.area().clipped_rect(cx).pos;
+            let ime_pos = cursor_pos - self.scroll_bars.get_scroll_pos() + self.viewport_rect.pos - area_pos;
+            cx.show_text_ime(self.scroll_bars.area(), ime_pos);
+        }
 
         // Get the last added selection.
         // Get the normalized cursor position. To go from normalized to screen position, multiply by
@@ -510,6 +534,12 @@ impl CodeEditor {
         self.redraw(cx);
     }
 
+    /// Scrolls to the cursor after the selection was changed from outside the editor
+    pub fn scroll_to_cursor(&mut self, cx: &mut Cx) {
+        self.keep_cursor_in_view = KeepCursorInView::JumpToPosition;
+        self.redraw(cx);
+    }
+
     pub fn reset_font_size(&mut self) {
         self.draw_gutter.text_style.font_size = 9.0;
         self.draw_text.text_style.font_size = 9.0;
@@ -572,6 +602,11 @@ impl CodeEditor {
         match event.hits(cx, self.scroll_bars.area()) {
             Hit::KeyFocusLost(_) => {
                 self.animator_play(cx, id!(focus.off));
+                if !self.composition.is_empty() {
+                    self.composition = TextCompositionEvent::default();
+                    self.redraw(cx);
+                }
+                cx.hide_text_ime();
             }
             Hit::KeyFocus(_) => {
                 self.animator_play(cx, id!(focus.on));
@@ -637,6 +672,102 @@ impl CodeEditor {
                     self.redraw(cx);
                 }
             }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyE,
+                modifiers: KeyModifiers { control, logo, .. },
+                ..
+            }) => {
+                if control || logo {
+                    // use the selection as the search pattern
+                    let pattern = session.copy();
+                    let _ = session.find(
+                        &pattern,
+                        SearchOptions {
+                            case_sensitive: true,
+                            ..SearchOptions::default()
+                        },
+                    );
+                    self.redraw(cx);
+                }
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyG,
+                modifiers:
+                    KeyModifiers {
+                        shift,
+                        control,
+                        logo,
+                        ..
+                    },
+                ..
+            }) => {
+                if control || logo {
+                    let found = if shift {
+                        session.select_prev_match()
+                    } else {
+                        session.select_next_match()
+                    };
+                    if found {
+                        keyboard_moved_cursor = true;
+                        self.redraw(cx);
+                    }
+                }
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyL,
+                modifiers:
+                    KeyModifiers {
+                        shift: true,
+                        control,
+                        logo,
+                        ..
+                    },
+                ..
+            }) => {
+                if (control || logo) && session.select_all_matches() {
+                    keyboard_moved_cursor = true;
+                    self.redraw(cx);
+                }
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyF,
+                modifiers:
+                    KeyModifiers {
+                        shift: true,
+                        control,
+                        logo,
+                        ..
+                    },
+                ..
+            }) => {
+                if control || logo {
+                    let pattern = session.copy();
+                    if !pattern.is_empty() {
+                        actions.push(CodeEditorAction::SearchWorkspace(
+                            pattern,
+                            SearchOptions {
+                                case_sensitive: true,
+                                ..SearchOptions::default()
+                            },
+                        ));
+                    }
+                }
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyF,
+                modifiers:
+                    KeyModifiers {
+                        shift: false,
+                        control,
+                        logo,
+                        ..
+                    },
+                ..
+            }) => {
+                if control || logo {
+                    actions.push(CodeEditorAction::Find(session.copy()));
+                }
+            }
             Hit::KeyDown(KeyEvent {
                 key_code: KeyCode::KeyA,
                 modifiers: KeyModifiers {control, logo, ..},
@@ -749,6 +880,7 @@ impl CodeEditor {
                 was_paste: false,
                 ..
             }) if input.len() > 0 => {
+                self.composition = TextCompositionEvent::default();
                 session.insert(input.into());
                 self.redraw(cx);
                 keyboard_moved_cursor = true;
@@ -821,6 +953,13 @@ impl CodeEditor {
                 keyboard_moved_cursor = true;
                 self.redraw(cx);
             }
+            Hit::ClipboardPaste(cp) => {
+                actions.push(CodeEditorAction::Paste(cp.item));
+            }
+            Hit::TextComposition(tc) => {
+                self.composition = tc;
+                self.redraw(cx);
+            }
             Hit::KeyDown(KeyEvent {
                 key_code: KeyCode::KeyZ,
                 modifiers:
@@ -920,6 +1059,10 @@ impl CodeEditor {
             Hit::FingerUp(_) => {
                 self.reset_cursor_blinker(cx);
                 self.keep_cursor_in_view = KeepCursorInView::Off;
+                let selected = session.copy();
+                if !selected.is_empty() {
+                    cx.clipboard_write(ClipboardSelection::Primary, vec![ClipboardItem::text(&selected)]);
+                }
             }
             Hit::FingerHoverIn(_) | Hit::FingerHoverOver(_) => {
                 cx.set_cursor(MouseCursor::Text);
@@ -1006,6 +1149,11 @@ impl CodeEditor {
             match element {
                 BlockElement::Line { line, .. } => {
                     self.draw_text.font_scale = line.scale();
+                    // the preedit takes up columns in front of the text after the cursor
+                    let mut composition_position = self
+                        .composition_position
+                        .filter(|position| position.line_index == line_index);
+                    let composition_column_count = self.composition.text.column_count();
                     let mut token_iter = line.tokens().iter().copied();
                     let mut token_slot = token_iter.next();
                     let mut row_index = 0;
@@ -1071,6 +1219,12 @@ impl CodeEditor {
                                         }
                                     }
                                     for grapheme in text_0.graphemes() {
+                                        if composition_position.is_some_and(|position| {
+                                            position.byte_index == byte_index
+                                        }) {
+                                            column_index += composition_column_count;
+                                            composition_position = None;
+                                        }
                                         let (x, y) = line
                                             .grid_to_normalized_position(row_index, column_index);
                                         self.draw_text.draw_abs(
@@ -1117,6 +1271,62 @@ impl CodeEditor {
         }
     }
 
+    // the input method preedit is drawn as if it was inserted at the cursor until it gets
+    // committed, draw_text_layer leaves room for it
+    fn draw_composition_layer(&mut self, cx: &mut Cx2d<'_>, cursor_pos: DVec2) {
+        if self.composition.is_empty() {
+            return;
+        }
+        let pos = cursor_pos + self.viewport_rect.pos;
+        // the column each char starts at, wide chars take two
+        let mut columns = vec![0];
+        for char in self.composition.text.chars() {
+            columns.push(columns.last().unwrap() + char.column_count());
+        }
+        let len = columns.len() - 1;
+        let column_rect = |start: usize, end: usize, cell_size: DVec2| Rect {
+            pos: pos + dvec2(columns[start] as f64 * cell_size.x, 0.0),
+            size: dvec2((columns[end] - columns[start]) as f64 * cell_size.x, cell_size.y),
+        };
+
+        self.draw_composition.new_draw_call(cx);
+        self.draw_composition.draw_abs(cx, column_rect(0, len, self.cell_size));
+
+        self.draw_text.new_draw_call(cx);
+        self.draw_text.font_scale = 1.0;
+        self.draw_text.outline = 0.0;
+        self.draw_text.color = self.token_colors.identifier;
+        let mut char_index = 0;
+        for grapheme in self.composition.text.graphemes() {
+            self.draw_text.draw_abs(
+                cx,
+                pos + dvec2(columns[char_index] as f64 * self.cell_size.x, 0.0),
+                grapheme,
+            );
+            char_index += grapheme.chars().count();
+        }
+
+        let bg_color = self.draw_composition.color;
+        let fg_color = self.token_colors.identifier;
+        self.draw_composition.new_draw_call(cx);
+        for segment in &self.composition.segments {
+            if segment.style == TextCompositionStyle::Highlight {
+                self.draw_composition.color = vec4(fg_color.x, fg_color.y, fg_color.z, 0.25);
+                self.draw_composition.draw_abs(cx, column_rect(segment.start.min(len), segment.end.min(len), self.cell_size));
+            }
+        }
+        self.draw_composition.color = fg_color;
+        let mut underline = column_rect(0, len, self.cell_size);
+        underline.pos.y += underline.size.y - 1.0;
+        underline.size.y = 1.0;
+        self.draw_composition.draw_abs(cx, underline);
+        let cursor = self.composition.cursor.min(len);
+        let mut caret = column_rect(cursor, cursor, self.cell_size);
+        caret.size.x = 1.5;
+        self.draw_composition.draw_abs(cx, caret);
+        self.draw_composition.color = bg_color;
+    }
+
     fn draw_indent_guide_layer(&mut self, cx: &mut Cx2d<'_>, session: &Session) {
         let mut origin_y = session.layout().line(self.line_start).y();
         for element in session
@@ -1156,8 +1366,17 @@ impl CodeEditor {
     }
 
     fn draw_decoration_layer(&mut self, cx: &mut Cx2d<'_>, session: &Session) {
+        self.draw_decorations(cx, session, &session.document().decorations());
+        self.draw_decorations(cx, session, &session.search_matches());
+    }
+
+    fn draw_decorations(
+        &mut self,
+        cx: &mut Cx2d<'_>,
+        session: &Session,
+        decorations: &[Decoration],
+    ) {
         let mut active_decoration = None;
-        let decorations = session.document().decorations();
         let mut decorations = decorations.iter();
         while decorations.as_slice().first().map_or(false, |decoration| {
             decoration.end().line_index < self.line_start
@@ -1416,9 +1635,15 @@ impl CodeEditor {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, DefaultNone)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, DefaultNone)]
 pub enum CodeEditorAction {
     TextDidChange,
+    /// Non-text clipboard content like an image, text pastes are applied directly
+    Paste(ClipboardItem),
+    /// Open the find bar, with the selected text if any
+    Find(String),
+    /// Search the whole workspace for the given pattern
+    SearchWorkspace(String, SearchOptions),
     None
 }
 
@@ -1592,6 +1817,9 @@ impl<'a> DrawDecorationLayer<'a> {
             match self.active_decoration.as_mut().unwrap().decoration.ty {
                 DecorationType::Warning => self.code_editor.token_colors.warning_decoration,
                 DecorationType::Error => self.code_editor.token_colors.error_decoration,
+                DecorationType::SearchMatch => {
+                    self.code_editor.token_colors.search_match_decoration
+                }
             };
 
         self.code_editor.draw_decoration.draw_abs(
@@ -1902,6 +2130,8 @@ struct TokenColors {
     error_decoration: Vec4,
     #[live]
     warning_decoration: Vec4,
+    #[live]
+    search_match_decoration: Vec4,
 }
 
 #[derive(Live, LiveHook, LiveRegister)]
@@ -4,6 +4,7 @@ use {
         decoration::{Decoration, DecorationSet},
         history::{EditKind, History},
         inlays::{BlockInlay, InlineInlay},
+        language,
         iter::IteratorExt,
         selection::SelectionSet,
         session::SessionId,
@@ -11,7 +12,7 @@ use {
         str::StrExt,
         text::{Change, Drift, Edit, Length, Position, Text},
         token::{Token, TokenKind},
-        tokenizer::Tokenizer,
+        tokenizer::{LanguageTokenizer, Tokenizer},
     },
     std::{
         cell::{Ref, RefCell},
@@ -29,6 +30,16 @@ pub struct Document(Rc<DocumentInner>);
 
 impl Document {
     pub fn new(text: Text, decorations: DecorationSet) -> Self {
+        Self::with_language(text, decorations, language::rust())
+    }
+
+    /// A document that is tokenized as `language`, see `language::for_path` to pick one by file
+    /// extension
+    pub fn with_language(
+        text: Text,
+        decorations: DecorationSet,
+        language: Rc<dyn LanguageTokenizer>,
+    ) -> Self {
         let line_count = text.as_lines().len();
         let tokens: Vec<_> = (0..line_count)
             .map(|line| tokenize(&text.as_lines()[line]).collect::<Vec<_>>())
@@ -41,7 +52,7 @@ impl Document {
                 inline_inlays: (0..line_count).map(|_| Vec::new()).collect(),
                 block_inlays: Vec::new(),
             }),
-            tokenizer: RefCell::new(Tokenizer::new(line_count)),
+            tokenizer: RefCell::new(Tokenizer::with_language(language, line_count)),
             decorations: RefCell::new(decorations),
             edit_senders: RefCell::new(HashMap::new()),
         }));
@@ -53,6 +64,20 @@ impl Document {
         inner
     }
 
+    pub fn language(&self) -> Rc<dyn LanguageTokenizer> {
+        self.0.tokenizer.borrow().language().clone()
+    }
+
+    /// Tokenizes the whole document again as `language`
+    pub fn set_language(&self, language: Rc<dyn LanguageTokenizer>) {
+        let mut tokenizer = self.0.tokenizer.borrow_mut();
+        tokenizer.set_language(language);
+        tokenizer.update(
+            self.0.history.borrow().as_text(),
+            &mut self.0.layout.borrow_mut().tokens,
+        );
+    }
+
     pub fn as_text(&self) -> Ref<'_, Text> {
         Ref::map(self.0.history.borrow(), |history| history.as_text())
     }
@@ -174,6 +199,40 @@ impl Document {
         self.update_after_edit(origin_id, None, &edits);
     }
 
+    /// Replaces each range with its text as a single undo group, without autoindent. The
+    /// ranges have to be sorted and can't overlap.
+    pub fn replace_ranges(
+        &self,
+        session_id: SessionId,
+        selections: &SelectionSet,
+        replacements: Vec<(Position, Length, Text)>,
+    ) {
+        let mut history = self.0.history.borrow_mut();
+        history.push_or_extend_group(session_id, EditKind::Other, selections);
+        let mut edits = Vec::new();
+        // back to front, so the earlier positions are still valid
+        for (start, length, text) in replacements.into_iter().rev() {
+            if length != Length::zero() {
+                let edit = Edit {
+                    change: Change::Delete(start, length),
+                    drift: Drift::Before,
+                };
+                edits.push(edit.clone());
+                history.apply_edit(edit);
+            }
+            if !text.is_empty() {
+                let edit = Edit {
+                    change: Change::Insert(start, text),
+                    drift: Drift::Before,
+                };
+                edits.push(edit.clone());
+                history.apply_edit(edit);
+            }
+        }
+        drop(history);
+        self.update_after_edit(session_id, None, &edits);
+    }
+
     pub fn add_decoration(&mut self, decoration: Decoration) {
         self.0.decorations.borrow_mut().add_decoration(decoration);
     }
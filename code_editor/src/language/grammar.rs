@@ -0,0 +1,240 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{Cursor, LanguageTokenizer, LineState},
+    Token,
+};
+
+const MODE_INITIAL: u32 = 0;
+const MODE_BLOCK_COMMENT: u32 = 1;
+// inside a string that continues on the next line, the mode is this plus the index of its quote
+const MODE_STRING: u32 = 2;
+
+/// A tokenizer for languages that are mostly identifiers, numbers, strings and comments, which
+/// is enough for the C family and most data formats. It is a table so adding a language is just
+/// another constant, see the ones in `language`.
+#[derive(Clone, Copy, Debug)]
+pub struct Grammar {
+    pub line_comments: &'static [&'static str],
+    pub block_comment: Option<(&'static str, &'static str)>,
+    /// Tried in order, so put `"""` before `"`
+    pub quotes: &'static [Quote],
+    pub branch_keywords: &'static [&'static str],
+    pub loop_keywords: &'static [&'static str],
+    pub other_keywords: &'static [&'static str],
+    pub typenames: &'static [&'static str],
+    pub constants: &'static [&'static str],
+    /// Chars other than letters, digits and `_` that are part of identifiers
+    pub identifier_chars: &'static str,
+    /// A string followed by a `:` is a key, as in json
+    pub string_keys: bool,
+    /// A line starting with `[` is a section header, as in toml
+    pub section_headers: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Quote {
+    pub open: &'static str,
+    pub close: &'static str,
+    pub escape: Option<char>,
+    pub multiline: bool,
+}
+
+impl Quote {
+    pub const fn new(open: &'static str, close: &'static str, escape: Option<char>) -> Self {
+        Self {
+            open,
+            close,
+            escape,
+            multiline: false,
+        }
+    }
+
+    pub const fn multiline(open: &'static str, close: &'static str, escape: Option<char>) -> Self {
+        Self {
+            open,
+            close,
+            escape,
+            multiline: true,
+        }
+    }
+}
+
+impl Grammar {
+    fn is_identifier_start(&self, cursor: &Cursor<'_>) -> bool {
+        let char = cursor.peek(0);
+        if char.is_alphabetic() || char == '_' {
+            return true;
+        }
+        // so that `-` in css is part of `-webkit-box` but not of `-1px` or `a - b`
+        self.identifier_chars.contains(char) && {
+            let next = cursor.peek(1);
+            char != '-' || next.is_alphabetic() || next == '-' || next == '_'
+        }
+    }
+
+    fn is_identifier_continue(&self, char: char) -> bool {
+        char.is_alphanumeric() || char == '_' || self.identifier_chars.contains(char)
+    }
+
+    fn next(&self, mode: u32, cursor: &mut Cursor<'_>) -> (u32, TokenKind) {
+        match mode {
+            MODE_BLOCK_COMMENT if self.block_comment.is_some() => (self.block_comment_tail(cursor), TokenKind::Comment),
+            _ if mode >= MODE_STRING && ((mode - MODE_STRING) as usize) < self.quotes.len() => {
+                let index = (mode - MODE_STRING) as usize;
+                (self.string_tail(index, cursor), TokenKind::String)
+            }
+            _ => self.initial(cursor),
+        }
+    }
+
+    fn initial(&self, cursor: &mut Cursor<'_>) -> (u32, TokenKind) {
+        let start = cursor.index();
+        let char = cursor.peek(0);
+        if char.is_whitespace() {
+            cursor.skip_while(|char| char.is_whitespace());
+            return (MODE_INITIAL, TokenKind::Whitespace);
+        }
+        if self
+            .line_comments
+            .iter()
+            .any(|prefix| cursor.rest().starts_with(prefix))
+        {
+            cursor.skip_to_end();
+            return (MODE_INITIAL, TokenKind::Comment);
+        }
+        if let Some((open, _)) = self.block_comment {
+            if cursor.skip_str(open) {
+                return (self.block_comment_tail(cursor), TokenKind::Comment);
+            }
+        }
+        if self.section_headers && char == '[' && cursor.string()[..start].trim().is_empty() {
+            cursor.skip_while(|char| char != ']');
+            cursor.skip_while(|char| char == ']');
+            return (MODE_INITIAL, TokenKind::Typename);
+        }
+        for (index, quote) in self.quotes.iter().enumerate() {
+            if cursor.skip_str(quote.open) {
+                let mode = self.string_tail(index, cursor);
+                let is_key = self.string_keys
+                    && mode == MODE_INITIAL
+                    && cursor.rest().trim_start().starts_with(':');
+                return (
+                    mode,
+                    if is_key {
+                        TokenKind::Identifier
+                    } else {
+                        TokenKind::String
+                    },
+                );
+            }
+        }
+        if char.is_ascii_digit() || char == '.' && cursor.peek(1).is_ascii_digit() {
+            self.number(cursor);
+            return (MODE_INITIAL, TokenKind::Number);
+        }
+        if self.is_identifier_start(cursor) {
+            cursor.skip(1);
+            cursor.skip_while(|char| self.is_identifier_continue(char));
+            let string = &cursor.string()[start..cursor.index()];
+            return (MODE_INITIAL, self.classify(string, cursor));
+        }
+        cursor.skip(1);
+        (
+            MODE_INITIAL,
+            match char {
+                '(' | ')' | '[' | ']' | '{' | '}' => TokenKind::Delimiter,
+                char if char.is_ascii_punctuation() => TokenKind::Punctuator,
+                _ => TokenKind::Unknown,
+            },
+        )
+    }
+
+    fn classify(&self, string: &str, cursor: &Cursor<'_>) -> TokenKind {
+        if self.branch_keywords.contains(&string) {
+            TokenKind::BranchKeyword
+        } else if self.loop_keywords.contains(&string) {
+            TokenKind::LoopKeyword
+        } else if self.other_keywords.contains(&string) {
+            TokenKind::OtherKeyword
+        } else if self.typenames.contains(&string) {
+            TokenKind::Typename
+        } else if self.constants.contains(&string) {
+            TokenKind::Constant
+        } else if cursor.peek(0) == '(' {
+            TokenKind::Function
+        } else if string.starts_with(char::is_uppercase) {
+            TokenKind::Typename
+        } else {
+            TokenKind::Identifier
+        }
+    }
+
+    fn number(&self, cursor: &mut Cursor<'_>) {
+        let start = cursor.index();
+        let mut prev = '\0';
+        loop {
+            let char = cursor.peek(0);
+            // in `0x1e-1` the `e` is a hex digit, only look at this literal so `0x1 + 1e-1` keeps
+            // its exponent
+            let is_exponent_sign =
+                (char == '+' || char == '-') && (prev == 'e' || prev == 'E') && {
+                    let literal = &cursor.string()[start..cursor.index()];
+                    !literal.starts_with("0x") && !literal.starts_with("0X")
+                };
+            if char.is_alphanumeric() || char == '_' || char == '.' || is_exponent_sign {
+                cursor.skip(1);
+                prev = char;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn block_comment_tail(&self, cursor: &mut Cursor<'_>) -> u32 {
+        let (_, close) = self.block_comment.unwrap();
+        match cursor.rest().find(close) {
+            Some(index) => {
+                cursor.skip_str(&cursor.rest()[..index + close.len()]);
+                MODE_INITIAL
+            }
+            None => {
+                cursor.skip_to_end();
+                MODE_BLOCK_COMMENT
+            }
+        }
+    }
+
+    fn string_tail(&self, index: usize, cursor: &mut Cursor<'_>) -> u32 {
+        let quote = &self.quotes[index];
+        loop {
+            if cursor.skip_str(quote.close) {
+                return MODE_INITIAL;
+            }
+            if cursor.is_at_end() {
+                return if quote.multiline {
+                    MODE_STRING + index as u32
+                } else {
+                    MODE_INITIAL
+                };
+            }
+            if quote.escape == Some(cursor.peek(0)) {
+                cursor.skip(1);
+            }
+            cursor.skip(1);
+        }
+    }
+}
+
+impl LanguageTokenizer for Grammar {
+    fn tokenize_line(&self, state: LineState, line: &str, tokens: &mut Vec<Token>) -> LineState {
+        let mut cursor = Cursor::new(line);
+        let mut mode = state.mode;
+        while !cursor.is_at_end() {
+            let start = cursor.index();
+            let (next_mode, kind) = self.next(mode, &mut cursor);
+            mode = next_mode;
+            cursor.push_token(start, kind, tokens);
+        }
+        LineState { mode, value: 0 }
+    }
+}
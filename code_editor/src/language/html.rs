@@ -0,0 +1,233 @@
+use crate::{
+    language::{CSS, JAVASCRIPT},
+    token::TokenKind,
+    tokenizer::{Cursor, LanguageTokenizer, LineState},
+    Token,
+};
+
+const MODE_TEXT: u32 = 0;
+// inside a tag, the value is one of the `TAG_` constants
+const MODE_TAG: u32 = 1;
+const MODE_COMMENT: u32 = 2;
+// inside a quoted attribute value, the value is the quote char shifted left by 2, or'd with the
+// `TAG_` constant of the tag
+const MODE_ATTRIBUTE_STRING: u32 = 3;
+// inside a <script> or <style> element, the value is the mode of the embedded tokenizer
+const MODE_SCRIPT: u32 = 4;
+const MODE_STYLE: u32 = 5;
+
+const TAG_OTHER: u32 = 0;
+const TAG_SCRIPT: u32 = 1;
+const TAG_STYLE: u32 = 2;
+
+/// Html, with the contents of `<script>` and `<style>` tokenized as javascript and css
+#[derive(Debug)]
+pub struct HtmlTokenizer;
+
+impl HtmlTokenizer {
+    fn text(&self, cursor: &mut Cursor<'_>) -> (LineState, TokenKind) {
+        let text = LineState::default();
+        if cursor.skip_str("<!--") {
+            return (self.comment_tail(cursor), TokenKind::Comment);
+        }
+        match (cursor.peek(0), cursor.peek(1)) {
+            ('<', next) if next.is_alphabetic() || next == '/' || next == '!' => {
+                cursor.skip(1);
+                let closing = cursor.skip_str("/") || cursor.skip_str("!");
+                let start = cursor.index();
+                cursor.skip_while(|char| char.is_alphanumeric() || char == '-' || char == ':');
+                let name = &cursor.string()[start..cursor.index()];
+                let tag = match name.to_ascii_lowercase().as_str() {
+                    "script" if !closing => TAG_SCRIPT,
+                    "style" if !closing => TAG_STYLE,
+                    _ => TAG_OTHER,
+                };
+                (
+                    LineState {
+                        mode: MODE_TAG,
+                        value: tag,
+                    },
+                    TokenKind::Typename,
+                )
+            }
+            ('&', _) => {
+                cursor.skip(1);
+                let rest = cursor.rest();
+                match rest.find(';') {
+                    Some(index)
+                        if index <= 32
+                            && rest[..index]
+                                .chars()
+                                .all(|char| char.is_alphanumeric() || char == '#') =>
+                    {
+                        cursor.skip_str(&rest[..index + 1]);
+                        (text, TokenKind::Constant)
+                    }
+                    _ => (text, TokenKind::Unknown),
+                }
+            }
+            (char, _) if char.is_whitespace() => {
+                cursor.skip_while(|char| char.is_whitespace());
+                (text, TokenKind::Whitespace)
+            }
+            _ => {
+                cursor.skip(1);
+                cursor.skip_while(|char| !char.is_whitespace() && char != '<' && char != '&');
+                (text, TokenKind::Unknown)
+            }
+        }
+    }
+
+    fn tag(&self, tag: u32, cursor: &mut Cursor<'_>) -> (LineState, TokenKind) {
+        let in_tag = LineState {
+            mode: MODE_TAG,
+            value: tag,
+        };
+        match cursor.peek(0) {
+            '>' => {
+                cursor.skip(1);
+                let mode = match tag {
+                    TAG_SCRIPT => MODE_SCRIPT,
+                    TAG_STYLE => MODE_STYLE,
+                    _ => MODE_TEXT,
+                };
+                (LineState { mode, value: 0 }, TokenKind::Punctuator)
+            }
+            '/' if cursor.peek(1) == '>' => {
+                cursor.skip(2);
+                (LineState::default(), TokenKind::Punctuator)
+            }
+            quote @ ('"' | '\'') => {
+                cursor.skip(1);
+                (self.attribute_string_tail(quote, tag, cursor), TokenKind::String)
+            }
+            '=' => {
+                cursor.skip(1);
+                (in_tag, TokenKind::Punctuator)
+            }
+            char if char.is_whitespace() => {
+                cursor.skip_while(|char| char.is_whitespace());
+                (in_tag, TokenKind::Whitespace)
+            }
+            _ => {
+                let unquoted_value = cursor.string()[..cursor.index()].ends_with('=');
+                cursor.skip(1);
+                cursor.skip_while(|char| {
+                    !char.is_whitespace() && !matches!(char, '>' | '=' | '"' | '\'' | '/')
+                });
+                (
+                    in_tag,
+                    if unquoted_value {
+                        TokenKind::String
+                    } else {
+                        TokenKind::Identifier
+                    },
+                )
+            }
+        }
+    }
+
+    fn attribute_string_tail(&self, quote: char, tag: u32, cursor: &mut Cursor<'_>) -> LineState {
+        match cursor.rest().find(quote) {
+            Some(index) => {
+                cursor.skip_str(&cursor.rest()[..index + 1]);
+                LineState {
+                    mode: MODE_TAG,
+                    value: tag,
+                }
+            }
+            None => {
+                cursor.skip_to_end();
+                LineState {
+                    mode: MODE_ATTRIBUTE_STRING,
+                    value: (quote as u32) << 2 | tag,
+                }
+            }
+        }
+    }
+
+    fn comment_tail(&self, cursor: &mut Cursor<'_>) -> LineState {
+        match cursor.rest().find("-->") {
+            Some(index) => {
+                cursor.skip_str(&cursor.rest()[..index + 3]);
+                LineState::default()
+            }
+            None => {
+                cursor.skip_to_end();
+                LineState {
+                    mode: MODE_COMMENT,
+                    value: 0,
+                }
+            }
+        }
+    }
+
+    // hands the line up to the closing tag to the embedded language
+    fn embedded(
+        &self,
+        state: LineState,
+        cursor: &mut Cursor<'_>,
+        tokens: &mut Vec<Token>,
+    ) -> LineState {
+        let (language, close) = match state.mode {
+            MODE_SCRIPT => (&JAVASCRIPT, "</script"),
+            _ => (&CSS, "</style"),
+        };
+        let rest = cursor.rest();
+        let end = rest.to_ascii_lowercase().find(close).unwrap_or(rest.len());
+        let inner = language.tokenize_line(
+            LineState {
+                mode: state.value,
+                value: 0,
+            },
+            &rest[..end],
+            tokens,
+        );
+        cursor.skip_str(&rest[..end]);
+        if cursor.is_at_end() {
+            LineState {
+                mode: state.mode,
+                value: inner.mode,
+            }
+        } else {
+            LineState::default()
+        }
+    }
+}
+
+impl LanguageTokenizer for HtmlTokenizer {
+    fn tokenize_line(&self, state: LineState, line: &str, tokens: &mut Vec<Token>) -> LineState {
+        let mut cursor = Cursor::new(line);
+        let mut state = state;
+        while !cursor.is_at_end() {
+            let start = cursor.index();
+            let kind = match state.mode {
+                MODE_SCRIPT | MODE_STYLE => {
+                    state = self.embedded(state, &mut cursor, tokens);
+                    continue;
+                }
+                MODE_TAG => {
+                    let (next_state, kind) = self.tag(state.value, &mut cursor);
+                    state = next_state;
+                    kind
+                }
+                MODE_COMMENT => {
+                    state = self.comment_tail(&mut cursor);
+                    TokenKind::Comment
+                }
+                MODE_ATTRIBUTE_STRING => {
+                    let quote = char::from_u32(state.value >> 2).unwrap_or('"');
+                    state = self.attribute_string_tail(quote, state.value & 3, &mut cursor);
+                    TokenKind::String
+                }
+                _ => {
+                    let (next_state, kind) = self.text(&mut cursor);
+                    state = next_state;
+                    kind
+                }
+            };
+            cursor.push_token(start, kind, tokens);
+        }
+        state
+    }
+}
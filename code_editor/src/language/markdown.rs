@@ -0,0 +1,188 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{Cursor, LanguageTokenizer, LineState},
+    Token,
+};
+
+const MODE_TEXT: u32 = 0;
+// inside a ``` block, the value is the number of backticks that opened it
+const MODE_CODE_BLOCK: u32 = 1;
+const MODE_HTML_COMMENT: u32 = 2;
+
+/// Markdown, as far as it shows in the editor: headings, code, emphasis, links, lists and quotes
+#[derive(Debug)]
+pub struct MarkdownTokenizer;
+
+impl MarkdownTokenizer {
+    fn code_block_line(&self, fence_len: u32, line: &str, tokens: &mut Vec<Token>) -> LineState {
+        let trimmed = line.trim_start();
+        let closes = trimmed.len() - trimmed.trim_start_matches('`').len() >= fence_len as usize
+            && trimmed.trim_start_matches('`').trim().is_empty();
+        if !line.is_empty() {
+            tokens.push(Token {
+                len: line.len(),
+                kind: TokenKind::String,
+            });
+        }
+        if closes {
+            LineState::default()
+        } else {
+            LineState {
+                mode: MODE_CODE_BLOCK,
+                value: fence_len,
+            }
+        }
+    }
+
+    fn html_comment_tail(&self, cursor: &mut Cursor<'_>) -> u32 {
+        match cursor.rest().find("-->") {
+            Some(index) => {
+                cursor.skip_str(&cursor.rest()[..index + 3]);
+                MODE_TEXT
+            }
+            None => {
+                cursor.skip_to_end();
+                MODE_HTML_COMMENT
+            }
+        }
+    }
+
+    // the things that only mean something at the start of a line
+    fn line_start(&self, cursor: &mut Cursor<'_>, tokens: &mut Vec<Token>) -> Option<LineState> {
+        let start = cursor.index();
+        cursor.skip_while(|char| char == ' ' || char == '\t');
+        cursor.push_token(start, TokenKind::Whitespace, tokens);
+        let rest = cursor.rest();
+        let fence_len = rest.len() - rest.trim_start_matches('`').len();
+        if fence_len >= 3 {
+            let start = cursor.index();
+            cursor.skip_to_end();
+            cursor.push_token(start, TokenKind::String, tokens);
+            return Some(LineState {
+                mode: MODE_CODE_BLOCK,
+                value: fence_len as u32,
+            });
+        }
+        let start = cursor.index();
+        if rest.starts_with('#') {
+            cursor.skip_to_end();
+            cursor.push_token(start, TokenKind::OtherKeyword, tokens);
+            return Some(LineState::default());
+        }
+        if rest.starts_with('>') {
+            cursor.skip_to_end();
+            cursor.push_token(start, TokenKind::Comment, tokens);
+            return Some(LineState::default());
+        }
+        let is_rule = rest.len() >= 3
+            && ["---", "***", "___"]
+                .iter()
+                .any(|rule| rest.trim_end().chars().all(|char| rule.starts_with(char)));
+        if is_rule {
+            cursor.skip_to_end();
+            cursor.push_token(start, TokenKind::Punctuator, tokens);
+            return Some(LineState::default());
+        }
+        let is_bullet = matches!(cursor.peek(0), '-' | '*' | '+') && cursor.peek(1) == ' ';
+        if is_bullet {
+            cursor.skip(1);
+        } else {
+            let digits = rest.len() - rest.trim_start_matches(|char: char| char.is_ascii_digit()).len();
+            let after = &rest[digits..];
+            if digits > 0 && (after.starts_with(". ") || after.starts_with(") ")) {
+                cursor.skip(digits + 1);
+            }
+        }
+        cursor.push_token(start, TokenKind::Punctuator, tokens);
+        None
+    }
+
+    fn inline(&self, cursor: &mut Cursor<'_>) -> (u32, TokenKind) {
+        let char = cursor.peek(0);
+        if cursor.skip_str("<!--") {
+            return (self.html_comment_tail(cursor), TokenKind::Comment);
+        }
+        match char {
+            '`' => {
+                let start = cursor.index();
+                cursor.skip_while(|char| char == '`');
+                let ticks = &cursor.string()[start..cursor.index()];
+                match cursor.rest().find(ticks) {
+                    Some(index) => cursor.skip_str(&cursor.rest()[..index + ticks.len()]),
+                    None => false,
+                };
+                (MODE_TEXT, TokenKind::String)
+            }
+            '*' | '_' => {
+                let start = cursor.index();
+                cursor.skip_while(|next| next == char);
+                let marks = &cursor.string()[start..cursor.index()];
+                if !cursor.peek(0).is_whitespace() {
+                    if let Some(index) = cursor.rest().find(marks) {
+                        cursor.skip_str(&cursor.rest()[..index + marks.len()]);
+                        return (MODE_TEXT, TokenKind::Constant);
+                    }
+                }
+                (MODE_TEXT, TokenKind::Punctuator)
+            }
+            '[' => {
+                match cursor.rest().find(']') {
+                    Some(index) => {
+                        cursor.skip_str(&cursor.rest()[..index + 1]);
+                    }
+                    None => {
+                        cursor.skip(1);
+                        return (MODE_TEXT, TokenKind::Punctuator);
+                    }
+                }
+                (MODE_TEXT, TokenKind::Function)
+            }
+            '(' if cursor.index() > 0 && cursor.string()[..cursor.index()].ends_with(']') => {
+                match cursor.rest().find(')') {
+                    Some(index) => cursor.skip_str(&cursor.rest()[..index + 1]),
+                    None => cursor.skip_str("("),
+                };
+                (MODE_TEXT, TokenKind::String)
+            }
+            char if char.is_whitespace() => {
+                cursor.skip_while(|char| char.is_whitespace());
+                (MODE_TEXT, TokenKind::Whitespace)
+            }
+            _ => {
+                cursor.skip(1);
+                cursor.skip_while(|char| {
+                    !char.is_whitespace() && !matches!(char, '`' | '*' | '_' | '[' | '(' | '<')
+                });
+                (MODE_TEXT, TokenKind::Unknown)
+            }
+        }
+    }
+}
+
+impl LanguageTokenizer for MarkdownTokenizer {
+    fn tokenize_line(&self, state: LineState, line: &str, tokens: &mut Vec<Token>) -> LineState {
+        if state.mode == MODE_CODE_BLOCK {
+            return self.code_block_line(state.value, line, tokens);
+        }
+        let mut cursor = Cursor::new(line);
+        let mut mode = state.mode;
+        if mode == MODE_TEXT {
+            if let Some(state) = self.line_start(&mut cursor, tokens) {
+                return state;
+            }
+        }
+        while !cursor.is_at_end() {
+            let start = cursor.index();
+            let kind = if mode == MODE_HTML_COMMENT {
+                mode = self.html_comment_tail(&mut cursor);
+                TokenKind::Comment
+            } else {
+                let (next_mode, kind) = self.inline(&mut cursor);
+                mode = next_mode;
+                kind
+            };
+            cursor.push_token(start, kind, tokens);
+        }
+        LineState { mode, value: 0 }
+    }
+}
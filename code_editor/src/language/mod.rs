@@ -0,0 +1,178 @@
+//! The languages the editor knows how to tokenize. Pick one for a file with `for_path`, anything
+//! it doesn't know is tokenized as plain text.
+
+mod grammar;
+mod html;
+mod markdown;
+mod rust;
+
+pub use self::{
+    grammar::{Grammar, Quote},
+    html::HtmlTokenizer,
+    markdown::MarkdownTokenizer,
+    rust::RustTokenizer,
+};
+
+use {
+    crate::{
+        str::StrExt,
+        token::TokenKind,
+        tokenizer::{LanguageTokenizer, LineState},
+        Token,
+    },
+    std::{path::Path, rc::Rc},
+};
+
+pub fn rust() -> Rc<dyn LanguageTokenizer> {
+    Rc::new(RustTokenizer)
+}
+
+pub fn plain() -> Rc<dyn LanguageTokenizer> {
+    Rc::new(PlainTokenizer)
+}
+
+/// The tokenizer for files with extension `extension`, without the dot and in any case
+pub fn for_extension(extension: &str) -> Rc<dyn LanguageTokenizer> {
+    match extension.to_ascii_lowercase().as_str() {
+        "rs" => rust(),
+        "toml" => Rc::new(TOML),
+        "md" | "markdown" => Rc::new(MarkdownTokenizer),
+        "js" | "mjs" | "cjs" | "ts" => Rc::new(JAVASCRIPT),
+        "html" | "htm" => Rc::new(HtmlTokenizer),
+        "json" => Rc::new(JSON),
+        "css" => Rc::new(CSS),
+        "glsl" | "vert" | "frag" | "wgsl" | "metal" | "hlsl" | "shader" => Rc::new(SHADER),
+        _ => plain(),
+    }
+}
+
+pub fn for_path(path: impl AsRef<Path>) -> Rc<dyn LanguageTokenizer> {
+    match path.as_ref().extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => for_extension(extension),
+        None => plain(),
+    }
+}
+
+/// Splits a line into whitespace and everything else
+#[derive(Debug)]
+pub struct PlainTokenizer;
+
+impl LanguageTokenizer for PlainTokenizer {
+    fn tokenize_line(&self, state: LineState, line: &str, tokens: &mut Vec<Token>) -> LineState {
+        tokens.extend(line.split_whitespace_boundaries().map(|string| Token {
+            len: string.len(),
+            kind: if string.chars().next().unwrap().is_whitespace() {
+                TokenKind::Whitespace
+            } else {
+                TokenKind::Unknown
+            },
+        }));
+        state
+    }
+}
+
+pub const TOML: Grammar = Grammar {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &[
+        Quote::multiline("\"\"\"", "\"\"\"", Some('\\')),
+        Quote::multiline("'''", "'''", None),
+        Quote::new("\"", "\"", Some('\\')),
+        Quote::new("'", "'", None),
+    ],
+    branch_keywords: &[],
+    loop_keywords: &[],
+    other_keywords: &[],
+    typenames: &[],
+    constants: &["true", "false", "inf", "nan"],
+    identifier_chars: "-",
+    string_keys: false,
+    section_headers: true,
+};
+
+pub const JSON: Grammar = Grammar {
+    line_comments: &[],
+    block_comment: None,
+    quotes: &[Quote::new("\"", "\"", Some('\\'))],
+    branch_keywords: &[],
+    loop_keywords: &[],
+    other_keywords: &[],
+    typenames: &[],
+    constants: &["true", "false", "null"],
+    identifier_chars: "",
+    string_keys: true,
+    section_headers: false,
+};
+
+/// JavaScript, and well enough for TypeScript
+pub const JAVASCRIPT: Grammar = Grammar {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &[
+        Quote::new("\"", "\"", Some('\\')),
+        Quote::new("'", "'", Some('\\')),
+        Quote::multiline("`", "`", Some('\\')),
+    ],
+    branch_keywords: &[
+        "if", "else", "switch", "case", "default", "return", "throw", "try", "catch", "finally",
+        "await", "yield",
+    ],
+    loop_keywords: &["for", "while", "do", "break", "continue"],
+    other_keywords: &[
+        "var", "let", "const", "function", "class", "extends", "new", "delete", "typeof",
+        "instanceof", "in", "of", "import", "export", "from", "as", "async", "this", "super",
+        "static", "get", "set", "void", "interface", "type", "enum", "implements", "public",
+        "private", "protected", "readonly", "declare", "namespace",
+    ],
+    typenames: &[
+        "string", "number", "boolean", "any", "unknown", "never", "object", "bigint", "symbol",
+    ],
+    constants: &["true", "false", "null", "undefined", "NaN", "Infinity"],
+    identifier_chars: "$",
+    string_keys: false,
+    section_headers: false,
+};
+
+pub const CSS: Grammar = Grammar {
+    line_comments: &[],
+    block_comment: Some(("/*", "*/")),
+    quotes: &[
+        Quote::new("\"", "\"", Some('\\')),
+        Quote::new("'", "'", Some('\\')),
+    ],
+    branch_keywords: &[],
+    loop_keywords: &[],
+    other_keywords: &["important", "media", "import", "keyframes", "font-face", "supports"],
+    typenames: &[],
+    constants: &["inherit", "initial", "unset", "none", "auto"],
+    identifier_chars: "-",
+    string_keys: false,
+    section_headers: false,
+};
+
+/// GLSL, WGSL, Metal and HLSL are close enough to share keywords, along with the shader
+/// language of `live_design!`
+pub const SHADER: Grammar = Grammar {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &[Quote::new("\"", "\"", Some('\\'))],
+    branch_keywords: &["if", "else", "switch", "case", "default", "return", "discard"],
+    loop_keywords: &["for", "while", "do", "loop", "break", "continue"],
+    other_keywords: &[
+        "fn", "let", "var", "const", "struct", "uniform", "instance", "varying", "texture",
+        "attribute", "in", "out", "inout", "layout", "precision", "highp", "mediump", "lowp",
+        "kernel", "vertex", "fragment", "device", "constant", "thread", "using", "namespace",
+        "cbuffer", "register", "self", "use",
+    ],
+    typenames: &[
+        "void", "bool", "int", "uint", "float", "half", "double", "vec2", "vec3", "vec4",
+        "ivec2", "ivec3", "ivec4", "uvec2", "uvec3", "uvec4", "bvec2", "bvec3", "bvec4", "mat2",
+        "mat3", "mat4", "sampler2D", "samplerCube", "f32", "f16", "i32", "u32", "vec2f", "vec3f",
+        "vec4f", "mat4x4f", "float2", "float3", "float4", "float4x4", "half2", "half3", "half4",
+        "texture2d", "sampler",
+    ],
+    constants: &["true", "false"],
+    identifier_chars: "",
+    string_keys: false,
+    section_headers: false,
+};
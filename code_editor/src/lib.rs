@@ -1,3 +1,4 @@
+pub use makepad_regex;
 pub use makepad_widgets;
 use makepad_widgets::*;
 
@@ -8,6 +9,7 @@ pub mod document;
 pub mod history;
 pub mod inlays;
 pub mod iter;
+pub mod language;
 pub mod layout;
 pub mod selection;
 pub mod session;
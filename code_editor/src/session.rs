@@ -1,6 +1,7 @@
 use {
     crate::{
         char::CharExt,
+        decoration::{Decoration, DecorationSet, DecorationType},
         document::Document,
         history::EditKind,
         layout::{BlockElement, Layout, WrappedElement},
@@ -11,6 +12,7 @@ use {
         wrap::WrapData,
         Selection, Settings,
     },
+    makepad_regex::{Regex, RegexError, SearchOptions},
     std::{
         cell::{Cell, Ref, RefCell},
         collections::HashSet,
@@ -30,6 +32,7 @@ pub struct Session {
     selection_state: RefCell<SelectionState>,
     wrap_column: Cell<Option<usize>>,
     fold_state: RefCell<FoldState>,
+    search_state: RefCell<SearchState>,
     edit_receiver: Receiver<(Option<SelectionSet>, Vec<Edit>)>,
 }
 
@@ -63,6 +66,11 @@ impl Session {
                 folded_lines: HashSet::new(),
                 unfolding_lines: HashSet::new(),
             }),
+            search_state: RefCell::new(SearchState {
+                regex: None,
+                options: SearchOptions::default(),
+                matches: DecorationSet::new(),
+            }),
             edit_receiver,
         };
         for line in 0..line_count {
@@ -113,6 +121,12 @@ impl Session {
         })
     }
 
+    pub fn search_matches(&self) -> Ref<'_, [Decoration]> {
+        Ref::map(self.search_state.borrow(), |search_state| {
+            search_state.matches.as_decorations()
+        })
+    }
+
     pub fn set_wrap_column(&self, wrap_column: Option<usize>) {
         if self.wrap_column.get() == wrap_column {
             return;
@@ -746,6 +760,110 @@ impl Session {
         );
     }
 
+    /// Highlights every match of `pattern` and returns how many there are. Matches don't span
+    /// lines, and are kept up to date as the text changes. An empty pattern clears the search.
+    pub fn find(&self, pattern: &str, options: SearchOptions) -> Result<usize, RegexError> {
+        let regex = if pattern.is_empty() {
+            None
+        } else {
+            Some(Regex::search(pattern, options)?)
+        };
+        let mut search_state = self.search_state.borrow_mut();
+        search_state.regex = regex;
+        search_state.options = options;
+        search_state.matches.clear();
+        for (line_index, line) in self.document.as_text().as_lines().iter().enumerate() {
+            search_state.scan_line(line_index, line);
+        }
+        Ok(search_state.matches.len())
+    }
+
+    pub fn clear_search(&self) {
+        let mut search_state = self.search_state.borrow_mut();
+        search_state.regex = None;
+        search_state.matches.clear();
+    }
+
+    /// Selects the first match after the last added selection, wrapping around at the end
+    pub fn select_next_match(&self) -> bool {
+        self.select_next_match_from(self.last_added_selection().end())
+    }
+
+    /// Selects the last match before the last added selection, wrapping around at the start
+    pub fn select_prev_match(&self) -> bool {
+        let position = self.last_added_selection().start();
+        let search_state = self.search_state.borrow();
+        let found = search_state
+            .matches
+            .iter()
+            .rev()
+            .find(|found| found.end() <= position)
+            .or(search_state.matches.last())
+            .copied();
+        drop(search_state);
+        match found {
+            Some(found) => {
+                self.select_matches(&[found]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn select_all_matches(&self) -> bool {
+        let matches = self.search_state.borrow().matches.to_vec();
+        if matches.is_empty() {
+            return false;
+        }
+        self.select_matches(&matches);
+        true
+    }
+
+    /// Replaces the match that is selected and selects the next one. If the selection isn't a
+    /// match, this only selects the next match. With a regex search `$1` in `replacement` is
+    /// the first group of the match.
+    pub fn replace(&self, replacement: &str) -> bool {
+        let selection = self.last_added_selection();
+        let selected = self
+            .search_state
+            .borrow()
+            .matches
+            .iter()
+            .find(|found| found.start() == selection.start() && found.end() == selection.end())
+            .copied();
+        let Some(found) = selected else {
+            self.select_next_match();
+            return false;
+        };
+        let replacements = self.expand_replacements(&[found], replacement);
+        let end = found.start() + replacements[0].2.length();
+        self.document.replace_ranges(
+            self.id,
+            &self.selection_state.borrow().selections,
+            replacements,
+        );
+        self.handle_changes();
+        self.select_next_match_from(end);
+        true
+    }
+
+    /// Replaces every match as a single undo step and returns how many were replaced
+    pub fn replace_all(&self, replacement: &str) -> usize {
+        let matches = self.search_state.borrow().matches.to_vec();
+        if matches.is_empty() {
+            return 0;
+        }
+        let replacements = self.expand_replacements(&matches, replacement);
+        self.document.replace_ranges(
+            self.id,
+            &self.selection_state.borrow().selections,
+            replacements,
+        );
+        self.handle_changes();
+        self.document.force_new_group();
+        matches.len()
+    }
+
     pub fn copy(&self) -> String {
         let mut string = String::new();
         for selection in &self.selection_state.borrow().selections {
@@ -779,7 +897,7 @@ impl Session {
             .redo(self.id, &self.selection_state.borrow().selections)
     }
 
-    pub fn handle_changes(&mut self) {
+    pub fn handle_changes(&self) {
         while let Ok((selections, edits)) = self.edit_receiver.try_recv() {
             self.update_after_edit(selections, &edits);
         }
@@ -886,6 +1004,110 @@ impl Session {
         }
         drop(selection_state);
         self.update_highlighted_delimiter_positions();
+        self.update_search_matches(edits);
+    }
+
+    fn last_added_selection(&self) -> Selection {
+        let selection_state = self.selection_state.borrow();
+        let selections = selection_state.selections.as_selections();
+        selection_state
+            .last_added_selection_index
+            .and_then(|index| selections.get(index))
+            .or(selections.last())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn select_next_match_from(&self, position: Position) -> bool {
+        let search_state = self.search_state.borrow();
+        let found = search_state
+            .matches
+            .iter()
+            .find(|found| found.start() >= position)
+            .or(search_state.matches.first())
+            .copied();
+        drop(search_state);
+        match found {
+            Some(found) => {
+                self.select_matches(&[found]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn select_matches(&self, matches: &[Decoration]) {
+        let mut selection_state = self.selection_state.borrow_mut();
+        selection_state.mode = SelectionMode::Simple;
+        let mut selections = SelectionSet::new();
+        let mut last_added_selection_index = 0;
+        for (index, found) in matches.iter().enumerate() {
+            let selection = Selection {
+                anchor: found.start(),
+                cursor: Cursor {
+                    position: found.end(),
+                    affinity: Affinity::Before,
+                    preferred_column_index: None,
+                },
+            };
+            if index == 0 {
+                selections.set_selection(selection);
+            } else {
+                last_added_selection_index = selections.add_selection(selection);
+            }
+        }
+        selection_state.selections = selections;
+        selection_state.last_added_selection_index = Some(last_added_selection_index);
+        selection_state.injected_char_stack.clear();
+        drop(selection_state);
+        self.update_highlighted_delimiter_positions();
+        self.document().force_new_group();
+    }
+
+    fn expand_replacements(
+        &self,
+        matches: &[Decoration],
+        replacement: &str,
+    ) -> Vec<(Position, Length, Text)> {
+        let search_state = self.search_state.borrow();
+        let regex = search_state.regex.as_ref().unwrap();
+        let text = self.document.as_text();
+        matches
+            .iter()
+            .map(|found| {
+                // a plain text search has no groups, so a `$` in the replacement is just a `$`
+                if !search_state.options.regex {
+                    return (found.start(), found.length(), Text::from(replacement));
+                }
+                let line = &text.as_lines()[found.start().line_index];
+                let expanded = match regex.captures_at(line, found.start().byte_index) {
+                    Some(captures) => captures.expand(replacement),
+                    None => replacement.to_string(),
+                };
+                (found.start(), found.length(), Text::from(expanded))
+            })
+            .collect()
+    }
+
+    // only the lines the edits touched are scanned again, the other matches move along
+    fn update_search_matches(&self, edits: &[Edit]) {
+        let mut search_state = self.search_state.borrow_mut();
+        if search_state.regex.is_none() {
+            return;
+        }
+        let mut dirty_lines = Vec::new();
+        for edit in edits {
+            search_state.apply_edit(edit, &mut dirty_lines);
+        }
+        dirty_lines.sort_unstable();
+        dirty_lines.dedup();
+        let text = self.document.as_text();
+        let lines = text.as_lines();
+        for line_index in dirty_lines {
+            if let Some(line) = lines.get(line_index) {
+                search_state.scan_line(line_index, line);
+            }
+        }
     }
 
     fn update_y(&self) {
@@ -1014,6 +1236,61 @@ struct SelectionState {
     highlighted_delimiter_positions: HashSet<Position>,
 }
 
+#[derive(Debug)]
+struct SearchState {
+    regex: Option<Regex>,
+    options: SearchOptions,
+    matches: DecorationSet,
+}
+
+impl SearchState {
+    fn scan_line(&mut self, line_index: usize, line: &str) {
+        let Some(regex) = &self.regex else {
+            return;
+        };
+        for found in regex.find_iter(line) {
+            if found.is_empty() {
+                continue;
+            }
+            self.matches.add_decoration(Decoration::new(
+                0,
+                Position {
+                    line_index,
+                    byte_index: found.start,
+                },
+                Position {
+                    line_index,
+                    byte_index: found.end,
+                },
+                DecorationType::SearchMatch,
+            ));
+        }
+    }
+
+    // drops the matches on the lines the edit touches and moves the rest. `dirty_lines` are the
+    // lines that need a rescan, they are moved along as well
+    fn apply_edit(&mut self, edit: &Edit, dirty_lines: &mut Vec<usize>) {
+        let (line_index, removed_line_count, inserted_line_count) = match edit.change {
+            Change::Insert(point, ref text) => (point.line_index, 0, text.length().line_count),
+            Change::Delete(start, length) => (start.line_index, length.line_count, 0),
+        };
+        let last_line_index = line_index + removed_line_count;
+        self.matches.retain(|found| {
+            let found_line_index = found.start().line_index;
+            found_line_index < line_index || found_line_index > last_line_index
+        });
+        self.matches.apply_edit(edit);
+        for dirty_line in dirty_lines.iter_mut() {
+            if *dirty_line > last_line_index {
+                *dirty_line = *dirty_line - removed_line_count + inserted_line_count;
+            } else if *dirty_line > line_index {
+                *dirty_line = line_index;
+            }
+        }
+        dirty_lines.extend(line_index..=line_index + inserted_line_count);
+    }
+}
+
 #[derive(Debug)]
 struct FoldState {
     folding_lines: HashSet<usize>,
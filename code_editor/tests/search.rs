@@ -0,0 +1,170 @@
+use makepad_code_editor::{
+    decoration::DecorationSet,
+    makepad_regex::SearchOptions,
+    selection::Affinity,
+    session::SelectionMode,
+    text::{Position, Text},
+    Document, Session,
+};
+
+fn session(text: &str) -> Session {
+    Session::new(Document::new(Text::from(text), DecorationSet::new()))
+}
+
+fn text(session: &Session) -> String {
+    session.document().as_text().to_string()
+}
+
+fn matches(session: &Session) -> Vec<(usize, usize, usize)> {
+    session
+        .search_matches()
+        .iter()
+        .map(|found| {
+            (
+                found.start().line_index,
+                found.start().byte_index,
+                found.end().byte_index,
+            )
+        })
+        .collect()
+}
+
+fn selections(session: &Session) -> Vec<(Position, Position)> {
+    session
+        .selections()
+        .iter()
+        .map(|selection| (selection.start(), selection.end()))
+        .collect()
+}
+
+fn position(line_index: usize, byte_index: usize) -> Position {
+    Position {
+        line_index,
+        byte_index,
+    }
+}
+
+fn insert_at(session: &Session, at: Position, text: &str) {
+    session.set_selection(at, Affinity::Before, SelectionMode::Simple);
+    session.insert(Text::from(text));
+    session.handle_changes();
+}
+
+const CASE_SENSITIVE: SearchOptions = SearchOptions {
+    case_sensitive: true,
+    whole_word: false,
+    regex: false,
+};
+
+#[test]
+fn find_options() {
+    let session = session("Foo foo\nfood FOO");
+    assert_eq!(session.find("foo", SearchOptions::default()), Ok(4));
+    assert_eq!(session.find("foo", CASE_SENSITIVE), Ok(2));
+    assert_eq!(matches(&session), vec![(0, 4, 7), (1, 0, 3)]);
+    let whole_word = SearchOptions {
+        whole_word: true,
+        ..SearchOptions::default()
+    };
+    assert_eq!(session.find("foo", whole_word), Ok(3));
+    let regex = SearchOptions {
+        regex: true,
+        ..CASE_SENSITIVE
+    };
+    assert_eq!(session.find("f.o", regex), Ok(2));
+    // special characters are literal unless it is a regex search
+    assert_eq!(session.find("f.o", CASE_SENSITIVE), Ok(0));
+    assert!(session.find("(", regex).is_err());
+    assert_eq!(session.find("", CASE_SENSITIVE), Ok(0));
+}
+
+#[test]
+fn matches_follow_edits() {
+    let session = session("one foo\ntwo\nthree foo");
+    session.find("foo", CASE_SENSITIVE).unwrap();
+    assert_eq!(matches(&session), vec![(0, 4, 7), (2, 6, 9)]);
+
+    // new lines move the matches below them and are searched themselves
+    insert_at(&session, position(1, 0), "foo\n\n");
+    assert_eq!(matches(&session), vec![(0, 4, 7), (1, 0, 3), (4, 6, 9)]);
+
+    // typing inside a match breaks it
+    insert_at(&session, position(0, 5), "x");
+    assert_eq!(matches(&session), vec![(1, 0, 3), (4, 6, 9)]);
+
+    // joining lines finds the match that spans the join
+    let session = self::session("f\noo");
+    session.find("foo", CASE_SENSITIVE).unwrap();
+    assert_eq!(matches(&session), vec![]);
+    session.set_selection(position(1, 0), Affinity::Before, SelectionMode::Simple);
+    session.backspace();
+    session.handle_changes();
+    assert_eq!(text(&session), "foo");
+    assert_eq!(matches(&session), vec![(0, 0, 3)]);
+
+    session.undo();
+    session.handle_changes();
+    assert_eq!(text(&session), "f\noo");
+    assert_eq!(matches(&session), vec![]);
+}
+
+#[test]
+fn select_matches() {
+    let session = session("x x\nx");
+    session.find("x", CASE_SENSITIVE).unwrap();
+    assert!(session.select_next_match());
+    assert_eq!(selections(&session), vec![(position(0, 0), position(0, 1))]);
+    assert!(session.select_next_match());
+    assert_eq!(selections(&session), vec![(position(0, 2), position(0, 3))]);
+    assert!(session.select_next_match());
+    assert!(session.select_next_match());
+    // wrapped around
+    assert_eq!(selections(&session), vec![(position(0, 0), position(0, 1))]);
+    assert!(session.select_prev_match());
+    assert_eq!(selections(&session), vec![(position(1, 0), position(1, 1))]);
+    assert!(session.select_all_matches());
+    assert_eq!(selections(&session).len(), 3);
+
+    session.find("y", CASE_SENSITIVE).unwrap();
+    assert!(!session.select_next_match());
+    assert!(!session.select_all_matches());
+}
+
+#[test]
+fn replace() {
+    let session = session("x x x");
+    session.find("x", CASE_SENSITIVE).unwrap();
+    // the selection is not a match yet, so this only selects the first one
+    assert!(!session.replace("yy"));
+    assert_eq!(selections(&session), vec![(position(0, 0), position(0, 1))]);
+    assert!(session.replace("yy"));
+    assert_eq!(text(&session), "yy x x");
+    assert_eq!(selections(&session), vec![(position(0, 3), position(0, 4))]);
+    assert_eq!(matches(&session), vec![(0, 3, 4), (0, 5, 6)]);
+}
+
+#[test]
+fn replace_is_literal_without_regex() {
+    let session = session("price a1");
+    session.find("a1", CASE_SENSITIVE).unwrap();
+    assert_eq!(session.replace_all("$1 ${0}"), 1);
+    assert_eq!(text(&session), "price $1 ${0}");
+}
+
+#[test]
+fn replace_all_is_one_undo_step() {
+    let session = session("a1 b22\nc333");
+    let regex = SearchOptions {
+        regex: true,
+        ..CASE_SENSITIVE
+    };
+    assert_eq!(session.find("[a-z](\\d+)", regex), Ok(3));
+    assert_eq!(session.replace_all("<$1>"), 3);
+    assert_eq!(text(&session), "<1> <22>\n<333>");
+    assert_eq!(matches(&session), vec![]);
+
+    assert!(session.undo());
+    session.handle_changes();
+    assert_eq!(text(&session), "a1 b22\nc333");
+    assert_eq!(matches(&session).len(), 3);
+}
@@ -0,0 +1,192 @@
+use makepad_code_editor::{
+    language,
+    token::TokenKind::{
+        self, Comment, Constant, Delimiter, Function, Identifier, Number, OtherKeyword,
+        Punctuator, Typename, Unknown, Whitespace,
+    },
+    tokenizer::{Cursor, LineState},
+};
+
+// Tokenizes `lines` one after the other, like the editor does, and pairs every token with its text
+fn tokenize(extension: &str, lines: &[&str]) -> Vec<Vec<(String, TokenKind)>> {
+    let language = language::for_extension(extension);
+    let mut state = LineState::default();
+    let mut result = Vec::new();
+    for line in lines {
+        let mut tokens = Vec::new();
+        state = language.tokenize_line(state, line, &mut tokens);
+        let mut start = 0;
+        let mut line_tokens = Vec::new();
+        for token in tokens {
+            line_tokens.push((line[start..start + token.len].to_string(), token.kind));
+            start += token.len;
+        }
+        assert_eq!(start, line.len(), "tokens don't cover {:?}", line);
+        result.push(line_tokens);
+    }
+    result
+}
+
+fn line(tokens: &[(&str, TokenKind)]) -> Vec<(String, TokenKind)> {
+    tokens
+        .iter()
+        .map(|(text, kind)| (text.to_string(), *kind))
+        .collect()
+}
+
+#[test]
+fn cursor_stops_at_end() {
+    let mut cursor = Cursor::new("ab");
+    cursor.skip_while(|_| true);
+    assert!(cursor.is_at_end());
+    assert!(!cursor.skip_if(|char| char == '\0'));
+    assert_eq!(cursor.peek(0), '\0');
+}
+
+#[test]
+fn rust() {
+    assert_eq!(
+        tokenize("rs", &["fn main() {", "/* a", "b */ x"]),
+        vec![
+            line(&[
+                ("fn", OtherKeyword),
+                (" ", Whitespace),
+                ("main", Function),
+                ("(", Delimiter),
+                (")", Delimiter),
+                (" ", Whitespace),
+                ("{", Delimiter),
+            ]),
+            line(&[("/* a", Comment)]),
+            line(&[("b */", Comment), (" ", Whitespace), ("x", Identifier)]),
+        ]
+    );
+}
+
+#[test]
+fn toml() {
+    assert_eq!(
+        tokenize("toml", &["[package", "name = \"\"\"a", "b\"\"\" # c"]),
+        vec![
+            line(&[("[package", Typename)]),
+            line(&[
+                ("name", Identifier),
+                (" ", Whitespace),
+                ("=", Punctuator),
+                (" ", Whitespace),
+                ("\"\"\"a", TokenKind::String),
+            ]),
+            line(&[("b\"\"\"", TokenKind::String), (" ", Whitespace), ("# c", Comment)]),
+        ]
+    );
+}
+
+#[test]
+fn json() {
+    assert_eq!(
+        tokenize("json", &["{\"a\": [1e-3, null]}"]),
+        vec![line(&[
+            ("{", Delimiter),
+            ("\"a\"", Identifier),
+            (":", Punctuator),
+            (" ", Whitespace),
+            ("[", Delimiter),
+            ("1e-3", Number),
+            (",", Punctuator),
+            (" ", Whitespace),
+            ("null", Constant),
+            ("]", Delimiter),
+            ("}", Delimiter),
+        ])]
+    );
+}
+
+#[test]
+fn javascript_numbers() {
+    assert_eq!(
+        tokenize("js", &["0x1e-1 + 1e-1"]),
+        vec![line(&[
+            ("0x1e", Number),
+            ("-", Punctuator),
+            ("1", Number),
+            (" ", Whitespace),
+            ("+", Punctuator),
+            (" ", Whitespace),
+            ("1e-1", Number),
+        ])]
+    );
+}
+
+#[test]
+fn css() {
+    assert_eq!(
+        tokenize("css", &["a { -webkit-box: -1px }"]),
+        vec![line(&[
+            ("a", Identifier),
+            (" ", Whitespace),
+            ("{", Delimiter),
+            (" ", Whitespace),
+            ("-webkit-box", Identifier),
+            (":", Punctuator),
+            (" ", Whitespace),
+            ("-", Punctuator),
+            ("1px", Number),
+            (" ", Whitespace),
+            ("}", Delimiter),
+        ])]
+    );
+}
+
+#[test]
+fn shader() {
+    assert_eq!(
+        tokenize("glsl", &["vec4 c = f(1.0);"]),
+        vec![line(&[
+            ("vec4", Typename),
+            (" ", Whitespace),
+            ("c", Identifier),
+            (" ", Whitespace),
+            ("=", Punctuator),
+            (" ", Whitespace),
+            ("f", Function),
+            ("(", Delimiter),
+            ("1.0", Number),
+            (")", Delimiter),
+            (";", Punctuator),
+        ])]
+    );
+}
+
+#[test]
+fn markdown() {
+    let lines = tokenize("md", &["text ends here", "```", "let x", "```", "more"]);
+    assert_eq!(
+        lines[0],
+        line(&[
+            ("text", Unknown),
+            (" ", Whitespace),
+            ("ends", Unknown),
+            (" ", Whitespace),
+            ("here", Unknown),
+        ])
+    );
+    assert_eq!(lines[2], line(&[("let x", TokenKind::String)]));
+    assert_eq!(lines[4], line(&[("more", Unknown)]));
+}
+
+#[test]
+fn html() {
+    let lines = tokenize("html", &["<a href=x", "title='b'>text", "<script>var a</script>"]);
+    assert_eq!(
+        lines[0],
+        line(&[
+            ("<a", Typename),
+            (" ", Whitespace),
+            ("href", Identifier),
+            ("=", Punctuator),
+            ("x", TokenKind::String),
+        ])
+    );
+    assert_eq!(lines[1].last(), Some(&("text".to_string(), Unknown)));
+    assert_eq!(lines[2][2], ("var".to_string(), OtherKeyword));
+}
@@ -4,6 +4,7 @@ pub use {
         collections::VecDeque,
         hash::{Hash, Hasher},
         rc::Rc,
+        ops::Range,
         cell::RefCell,
         io::prelude::*,
         fs::File,
@@ -151,6 +152,82 @@ impl CxFontsAtlas {
     pub fn get_internal_font_atlas_texture_id(&self) -> Texture {
         self.texture.clone()
     }
+    
+    // Returns the first font in the fallback chain that has a glyph for c. If none of them do,
+    // the primary font is used so the missing glyph still renders as that font's notdef box.
+    pub fn font_for_char(&self, font_ids: &[usize], c: char) -> Option<usize> {
+        fallback_font_for_char(font_ids, c, &| font_id, c | self.has_glyph(font_id, c))
+    }
+    
+    // Splits text into runs that can each be shaped with a single font from the fallback chain.
+    // Whitespace stays in the current run when its font covers it, and joiners, variation
+    // selectors and combining marks always stay with the character they modify, so that
+    // sequences like emoji ZWJ sequences reach the shaper in one piece.
+    pub fn font_runs(&self, font_ids: &[usize], text: &str) -> Vec<(usize, Range<usize>)> {
+        fallback_font_runs(font_ids, text, &| font_id, c | self.has_glyph(font_id, c))
+    }
+    
+    fn has_glyph(&self, font_id: usize, c: char) -> bool {
+        self.fonts[font_id].as_ref().is_some_and( | cxfont | cxfont.has_glyph(c))
+    }
+    
+    // The logical advance of c at font_size, measured in the font that the fallback chain picks for it.
+    pub fn char_advance(&mut self, font_ids: &[usize], c: char, font_size: f64) -> f64 {
+        let font_id = if let Some(font_id) = self.font_for_char(font_ids, c) {font_id} else {return 0.0};
+        if let Some(cxfont) = self.fonts[font_id].as_mut() {
+            let units_per_em = cxfont.ttf_font.units_per_em;
+            if let Some(glyph) = cxfont.get_glyph(c) {
+                return glyph.horizontal_metrics.advance_width * font_size * 96.0 / (72.0 * units_per_em)
+            }
+        }
+        0.0
+    }
+}
+
+// font_for_char and font_runs with the glyph lookup passed in, so they work on any set of fonts
+fn fallback_font_for_char(font_ids: &[usize], c: char, has_glyph: &dyn Fn(usize, char) -> bool) -> Option<usize> {
+    let primary = *font_ids.first() ?;
+    for &font_id in font_ids {
+        if has_glyph(font_id, c) {
+            return Some(font_id)
+        }
+    }
+    Some(primary)
+}
+
+fn fallback_font_runs(font_ids: &[usize], text: &str, has_glyph: &dyn Fn(usize, char) -> bool) -> Vec<(usize, Range<usize>)> {
+    let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+    for (index, c) in text.char_indices() {
+        let end = index + c.len_utf8();
+        if let Some((run_font_id, run_range)) = runs.last_mut() {
+            if is_cluster_continuation(c) || c.is_whitespace() && has_glyph(*run_font_id, c) {
+                run_range.end = end;
+                continue;
+            }
+        }
+        let font_id = if let Some(font_id) = fallback_font_for_char(font_ids, c, has_glyph) {font_id} else {break};
+        match runs.last_mut() {
+            Some((run_font_id, run_range)) if *run_font_id == font_id => run_range.end = end,
+            _ => runs.push((font_id, index..end))
+        }
+    }
+    runs
+}
+
+fn is_cluster_continuation(c: char) -> bool {
+    match c {
+        '\u{200c}' | '\u{200d}' // zero width (non-)joiner
+        | '\u{fe00}'..='\u{fe0f}' // variation selectors
+        | '\u{e0100}'..='\u{e01ef}'
+        | '\u{0300}'..='\u{036f}' // combining diacritical marks
+        | '\u{1ab0}'..='\u{1aff}'
+        | '\u{1dc0}'..='\u{1dff}'
+        | '\u{20d0}'..='\u{20ff}'
+        | '\u{fe20}'..='\u{fe2f}'
+        | '\u{1f3fb}'..='\u{1f3ff}' // emoji skin tone modifiers
+        | '\u{e0020}'..='\u{e007f}' => true, // emoji tag sequences
+        _ => false
+    }
 }
 
 impl DrawTrapezoidVector {
@@ -329,7 +406,15 @@ pub struct CxFont {
 
 pub struct ShapeCache {
     pub keys: VecDeque<(Direction, Rc<str>)>,
-    pub glyph_ids: HashMap<(Direction, Rc<str>), Vec<usize>>,
+    pub glyphs: HashMap<(Direction, Rc<str>), Vec<ShapedGlyph>>,
+}
+
+// A glyph as rustybuzz shaped it, cluster is the byte offset in the shaped string of the first
+// character the glyph was made from.
+#[derive(Clone, Copy, Debug)]
+pub struct ShapedGlyph {
+    pub glyph_id: usize,
+    pub cluster: usize,
 }
 
 impl ShapeCache {
@@ -339,14 +424,15 @@ impl ShapeCache {
     pub fn new() -> Self {
         Self {
             keys: VecDeque::new(),
-            glyph_ids: HashMap::new(),
+            glyphs: HashMap::new(),
         }
     }
 
     // If there is an entry for the given key in the cache, returns the corresponding list of
-    // glyph indices for that key. Otherwise, uses the given UnicodeBuffer and OwnedFace to
-    // compute the list of glyph indices for the key, inserts that in the cache and then returns
-    // the corresponding list.
+    // shaped glyphs for that key. Otherwise, uses the given UnicodeBuffer and OwnedFace to
+    // compute the list of shaped glyphs for the key, inserts that in the cache and then returns
+    // the corresponding list. The glyphs are in visual order, so for a right to left key the
+    // clusters count down.
     //
     // This method takes a UnicodeBuffer by value, and then returns the same buffer by value. This
     // is necessary because rustybuzz::shape consumes the UnicodeBuffer and then returns a
@@ -356,16 +442,16 @@ impl ShapeCache {
     //
     // Note that owned_font_face should be the same as the CxFont to which this cache belongs,
     // otherwise you will not get correct results.
-    pub fn get_or_compute_glyph_ids(
+    pub fn get_or_compute_glyphs(
         &mut self, 
         key: (Direction, &str),
         mut rustybuzz_buffer: UnicodeBuffer,
         owned_font_face: &crate::owned_font_face::OwnedFace
-    ) -> (&[usize], UnicodeBuffer) {
-        if !self.glyph_ids.contains_key(&key as &dyn ShapeCacheKey) {
+    ) -> (&[ShapedGlyph], UnicodeBuffer) {
+        if !self.glyphs.contains_key(&key as &dyn ShapeCacheKey) {
             if self.keys.len() == Self::MAX_SIZE {
                 for run in self.keys.drain(..Self::MAX_SIZE / 2) {
-                    self.glyph_ids.remove(&run);
+                    self.glyphs.remove(&run);
                 }
             }
 
@@ -373,14 +459,17 @@ impl ShapeCache {
             rustybuzz_buffer.set_direction(direction);
             rustybuzz_buffer.push_str(string);
             let glyph_buffer = owned_font_face.with_ref( | face | rustybuzz::shape(face, &[], rustybuzz_buffer));
-            let glyph_ids: Vec<_> = glyph_buffer.glyph_infos().iter().map( | glyph | glyph.glyph_id as usize).collect();
+            let glyphs: Vec<_> = glyph_buffer.glyph_infos().iter().map( | glyph | ShapedGlyph {
+                glyph_id: glyph.glyph_id as usize,
+                cluster: glyph.cluster as usize
+            }).collect();
             rustybuzz_buffer = glyph_buffer.clear();
 
             let owned_string: Rc<str> = string.into();
             self.keys.push_back((direction, owned_string.clone()));
-            self.glyph_ids.insert((direction, owned_string), glyph_ids);
+            self.glyphs.insert((direction, owned_string), glyphs);
         }
-        (&self.glyph_ids[&key as &dyn ShapeCacheKey], rustybuzz_buffer)
+        (&self.glyphs[&key as &dyn ShapeCacheKey], rustybuzz_buffer)
     }
 }
 
@@ -508,15 +597,74 @@ impl CxFont {
         self.atlas_pages.len() - 1
     }
 
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.owned_font_face.with_ref(|face| face.glyph_index(c).is_some())
+    }
+
     pub fn get_glyph(&mut self, c:char)->Option<&Glyph>{
-        if c < '\u{10000}' {
-            Some(self.get_glyph_by_id(self.owned_font_face.with_ref(|face| face.glyph_index(c))?.0 as usize).unwrap())
-        } else {
-            None
-        }
+        Some(self.get_glyph_by_id(self.owned_font_face.with_ref(|face| face.glyph_index(c))?.0 as usize).unwrap())
     }
 
     pub fn get_glyph_by_id(&mut self, id: usize) -> makepad_vector::ttf_parser::Result<&Glyph> {
         self.owned_font_face.with_ref(|face| self.ttf_font.get_glyph_by_id(face, id))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // 0 is a latin font, 1 an emoji font and 2 a cjk font with its own wide space
+    fn has_glyph(font_id: usize, c: char) -> bool {
+        match font_id {
+            0 => c.is_ascii(),
+            1 => matches!(c, '👩' | '💻' | '👍'),
+            2 => matches!(c, '漢' | '字' | '\u{3000}'),
+            _ => false
+        }
+    }
+    
+    fn runs(text: &str) -> Vec<(usize, Range<usize>)> {
+        fallback_font_runs(&[0, 1, 2], text, &has_glyph)
+    }
+    
+    #[test]
+    fn zwj_emoji_sequence_stays_in_one_run() {
+        // the joiner and the skin tone are in none of the fonts, but stay with their emoji
+        assert_eq!(runs("hi 👩\u{200d}💻!"), vec![(0, 0..3), (1, 3..14), (0, 14..15)]);
+        assert_eq!(runs("👍\u{1f3fd}"), vec![(1, 0..8)]);
+    }
+    
+    #[test]
+    fn combining_marks_stay_with_their_base() {
+        assert_eq!(runs("ae\u{301}b"), vec![(0, 0..5)]);
+        assert_eq!(runs("漢\u{301}a"), vec![(2, 0..5), (0, 5..6)]);
+    }
+    
+    #[test]
+    fn whitespace_between_runs_in_different_fonts() {
+        // the cjk font has no regular space, so it gets one of its own run
+        assert_eq!(runs("漢 字"), vec![(2, 0..3), (0, 3..4), (2, 4..7)]);
+        assert_eq!(runs("漢\u{3000}字"), vec![(2, 0..9)]);
+        // a space the run's font covers stays in the run
+        assert_eq!(runs("a 漢"), vec![(0, 0..2), (2, 2..5)]);
+    }
+    
+    #[test]
+    fn missing_glyphs_fall_back_to_the_primary_font() {
+        assert_eq!(fallback_font_for_char(&[0, 1, 2], '\u{e000}', &has_glyph), Some(0));
+        assert_eq!(fallback_font_for_char(&[2, 1], '💻', &has_glyph), Some(1));
+        assert_eq!(fallback_font_for_char(&[], 'a', &has_glyph), None);
+        assert_eq!(fallback_font_runs(&[], "a", &has_glyph), vec![]);
+    }
+    
+    #[test]
+    fn cluster_continuations() {
+        for c in ['\u{200d}', '\u{200c}', '\u{fe0f}', '\u{301}', '\u{1f3fd}', '\u{e0067}'] {
+            assert!(is_cluster_continuation(c), "{:?}", c);
+        }
+        for c in ['a', ' ', '👩', '漢'] {
+            assert!(!is_cluster_continuation(c), "{:?}", c);
+        }
+    }
+}
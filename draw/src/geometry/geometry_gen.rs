@@ -32,9 +32,22 @@ impl LiveHook for GeometryQuad2D {
     }
 }
 
+impl GeometryQuad2D {
+    fn push_geometry_fields(fields: &mut Vec<GeometryField>) {
+        fields.push(GeometryField {id: live_id!(geom_pos), ty: ShaderTy::Vec2});
+    }
+    
+    /// Lets draw shaders that use this geometry be validated without creating one.
+    pub fn register_geometry_fields(cx: &mut Cx) {
+        let mut fields = Vec::new();
+        Self::push_geometry_fields(&mut fields);
+        cx.draw_shaders.geometry_fields.insert(LiveType::of::<Self>(), fields);
+    }
+}
+
 impl GeometryFields for GeometryQuad2D {
     fn geometry_fields(&self, fields: &mut Vec<GeometryField>) {
-        fields.push(GeometryField {id: live_id!(geom_pos), ty: ShaderTy::Vec2});
+        Self::push_geometry_fields(fields);
     }
     
     fn get_geometry_id(&self) -> Option<GeometryId> {
@@ -13,10 +13,11 @@ pub use {
         turtle::{Walk, Layout},
         draw_list_2d::{ManyInstances, DrawList2d, RedrawingApi},
         geometry::GeometryQuad2D,
-        makepad_vector::trapezoidator::Trapezoidator,
-        makepad_vector::geometry::{AffineTransformation, Transform, Vector, Point},
+        makepad_vector::trapezoidator::{Trapezoidator, FillRule},
+        makepad_vector::geometry::{AffineTransformation, Transform, Vector, Point, Rectangle},
         makepad_vector::internal_iter::*,
         makepad_vector::path::{PathIterator, PathCommand},
+        makepad_vector::svg::{parse_svg, parse_svg_path, SvgPaint},
     }
 };
 
@@ -37,9 +38,26 @@ pub struct CxIconEntry {
 
 struct CxIconPathCommands {
     bounds: Rect,
+    fill_rule: FillRule,
     path: Vec<PathCommand>
 }
 
+/// A parsed svg file, drawn as one path per layer. Every layer has its own atlas slot so it can be
+/// painted with its own color or gradient, and all of them together form the monochrome icon.
+pub struct CxIconSvg {
+    /// The tight bounds of all layers
+    pub bounds: Rect,
+    /// The area the document means to show, when it has a viewBox or a size
+    pub view_box: Option<Rect>,
+    pub layers: Vec<CxIconSvgLayer>,
+}
+
+pub struct CxIconSvgLayer {
+    pub path_hash: CxIconPathHash,
+    pub paint: SvgPaint,
+    pub opacity: f32,
+}
+
 impl<'a> InternalIterator for &CxIconPathCommands {
     type Item = PathCommand;
     fn for_each<F>(self, f: &mut F) -> bool
@@ -66,6 +84,7 @@ pub struct CxIconAtlas {
     pub clear_buffer: bool,
     svg_deps: HashMap<String, CxIconPathHash>,
     paths: HashMap<CxIconPathHash, CxIconPathCommands>,
+    svgs: HashMap<CxIconPathHash, CxIconSvg>,
     entries: HashMap<CxIconEntryHash, CxIconEntry>,
     alloc: CxIconAtlasAlloc
 }
@@ -110,6 +129,7 @@ impl CxIconAtlas {
             entries: HashMap::new(),
             svg_deps: HashMap::new(),
             paths: HashMap::new(),
+            svgs: HashMap::new(),
             alloc: CxIconAtlasAlloc {
                 texture_size: DVec2 {x: 2048.0, y: 2048.0},
                 xpos: 0.0,
@@ -123,38 +143,10 @@ impl CxIconAtlas {
     pub fn parse_and_cache_path(&mut self, path_hash: CxIconPathHash, path: &[u8]) -> Option<(CxIconPathHash, Rect)> {
         match parse_svg_path(path) {
             Ok(path) => {
-                let mut min = dvec2(f64::INFINITY, f64::INFINITY);
-                let mut max = dvec2(-f64::INFINITY, -f64::INFINITY);
-                fn bound(p: &Point, min: &mut DVec2, max: &mut DVec2) {
-                    if p.x < min.x {min.x = p.x}
-                    if p.y < min.y {min.y = p.y}
-                    if p.x > max.x {max.x = p.x}
-                    if p.y > max.y {max.y = p.y}
-                }
-                for cmd in &path {
-                    match cmd {
-                        PathCommand::MoveTo(p) => {bound(p, &mut min, &mut max)},
-                        PathCommand::LineTo(p) => {bound(p, &mut min, &mut max)},
-                        PathCommand::ArcTo(e, r, _, _, _) => {
-                            // TODO: this is pretty rough
-                            bound(&Point{x: e.x + r.x, y: e.y + r.y}, &mut min, &mut max);
-                            bound(&Point{x: e.x - r.x, y: e.y - r.y}, &mut min, &mut max);
-                        },
-                        PathCommand::QuadraticTo(p1, p) => {
-                            bound(p1, &mut min, &mut max);
-                            bound(p, &mut min, &mut max);
-                        },
-                        PathCommand::CubicTo(p1, p2, p) => {
-                            bound(p1, &mut min, &mut max);
-                            bound(p2, &mut min, &mut max);
-                            bound(p, &mut min, &mut max);
-                        },
-                        PathCommand::Close => ()
-                    }
-                }
-                let bounds = Rect {pos: min, size: max - min};
+                let bounds = path_bounds(&path);
                 self.paths.insert(path_hash, CxIconPathCommands {
                     bounds,
+                    fill_rule: FillRule::NonZero,
                     path
                 });
                 return Some((path_hash, bounds));
@@ -166,43 +158,57 @@ impl CxIconAtlas {
         }
     }
     
+    pub fn parse_and_cache_svg(&mut self, path_hash: CxIconPathHash, data: &str) -> Option<(CxIconPathHash, Rect)> {
+        match parse_svg(data) {
+            Ok(document) => {
+                let bounds = to_rect(document.bounds()?);
+                let mut layers = Vec::new();
+                for (index, layer) in document.layers.into_iter().enumerate() {
+                    let layer_hash = CxIconPathHash(path_hash.0.id_append(LiveId(index as u64)));
+                    self.paths.insert(layer_hash, CxIconPathCommands {
+                        bounds: path_bounds(&layer.path),
+                        fill_rule: layer.fill_rule,
+                        path: layer.path
+                    });
+                    layers.push(CxIconSvgLayer {
+                        path_hash: layer_hash,
+                        paint: layer.paint,
+                        opacity: layer.opacity
+                    });
+                }
+                self.svgs.insert(path_hash, CxIconSvg {
+                    bounds,
+                    view_box: document.view_box.map(to_rect),
+                    layers
+                });
+                Some((path_hash, bounds))
+            }
+            Err(e) => {
+                log!("Error in SVG file {}", e);
+                None
+            }
+        }
+    }
+    
+    /// The layers of an svg file returned by `get_icon_bounds`, or None for a single path
+    pub fn get_svg(&self, path_hash: CxIconPathHash) -> Option<&CxIconSvg> {
+        self.svgs.get(&path_hash)
+    }
+    
     pub fn get_icon_bounds(&mut self, cx: &Cx, path_str: &Rc<String>, svg_dep: &Rc<String>) -> Option<(CxIconPathHash, Rect)> {
         if svg_dep.len() != 0 {
             // alright so. lets see if we have a path hash
             if let Some(path_hash) = self.svg_deps.get(svg_dep.as_str()) {
-                if let Some(path) = self.paths.get(&path_hash) {
-                    return Some((*path_hash, path.bounds))
+                if let Some(svg) = self.svgs.get(&path_hash) {
+                    return Some((*path_hash, svg.bounds))
                 }
                 return None
             }
             let path_hash = CxIconPathHash(LiveId(self.svg_deps.len() as u64));
             self.svg_deps.insert(svg_dep.as_str().to_string(), path_hash);
-            // lets parse the path range out of the svg file
             match cx.get_dependency(svg_dep.as_str()) {
                 Ok(data)=>{
-                    fn find_path_str(data:&[u8])->Option<&[u8]>{
-                        let pat = "path d=\"".as_bytes();
-                        'outer:for i in 0..data.len(){
-                            for j in 0..pat.len(){
-                                if data[i+j] != pat[j]{
-                                    continue 'outer;
-                                }
-                            }
-                            for k in i+pat.len()..data.len(){
-                                if data[k] == '\"' as u8{
-                                    return Some(&data[i+pat.len()..k])
-                                }
-                            }
-                            return None
-                        }
-                        None
-                    }
-                    if let Some(data) = find_path_str(&data){
-                        return self.parse_and_cache_path(path_hash, data)
-                    }
-                    println!("No SVG path tag found in svg file {}",path_str);
-                    return None
-                    
+                    return self.parse_and_cache_svg(path_hash, &String::from_utf8_lossy(&data))
                 }
                 Err(_err)=>{
                     println!("Error in SVG file {}: {}",path_str, _err);
@@ -273,6 +279,46 @@ impl CxIconAtlasAlloc {
     }
 }
 
+fn to_rect(rect: Rectangle) -> Rect {
+    Rect {
+        pos: dvec2(rect.p_min.x, rect.p_min.y),
+        size: dvec2(rect.p_max.x - rect.p_min.x, rect.p_max.y - rect.p_min.y)
+    }
+}
+
+fn path_bounds(path: &[PathCommand]) -> Rect {
+    let mut min = dvec2(f64::INFINITY, f64::INFINITY);
+    let mut max = dvec2(-f64::INFINITY, -f64::INFINITY);
+    fn bound(p: &Point, min: &mut DVec2, max: &mut DVec2) {
+        if p.x < min.x {min.x = p.x}
+        if p.y < min.y {min.y = p.y}
+        if p.x > max.x {max.x = p.x}
+        if p.y > max.y {max.y = p.y}
+    }
+    for cmd in path {
+        match cmd {
+            PathCommand::MoveTo(p) => {bound(p, &mut min, &mut max)},
+            PathCommand::LineTo(p) => {bound(p, &mut min, &mut max)},
+            PathCommand::ArcTo(e, r, _, _, _) => {
+                // TODO: this is pretty rough
+                bound(&Point{x: e.x + r.x, y: e.y + r.y}, &mut min, &mut max);
+                bound(&Point{x: e.x - r.x, y: e.y - r.y}, &mut min, &mut max);
+            },
+            PathCommand::QuadraticTo(p1, p) => {
+                bound(p1, &mut min, &mut max);
+                bound(p, &mut min, &mut max);
+            },
+            PathCommand::CubicTo(p1, p2, p) => {
+                bound(p1, &mut min, &mut max);
+                bound(p2, &mut min, &mut max);
+                bound(p, &mut min, &mut max);
+            },
+            PathCommand::Close => ()
+        }
+    }
+    Rect {pos: min, size: max - min}
+}
+
 #[derive(Clone)]
 pub struct CxIconAtlasRc(pub Rc<RefCell<CxIconAtlas >>);
 
@@ -294,6 +340,7 @@ impl CxIconAtlas {
 impl DrawTrapezoidVector {
     // atlas drawing function used by CxAfterDraw
     fn draw_vector(&mut self, entry: &CxIconEntry, path: &CxIconPathCommands, many: &mut ManyInstances) {
+        self.trapezoidator.fill_rule = path.fill_rule;
         let trapezoids = {
             let mut trapezoids = Vec::new();
             //log_str(&format!("Serializing char {} {} {} {}", glyphtc.tx1 , cx.fonts_atlas.texture_size.x ,todo.subpixel_x_fract ,atlas_page.dpi_factor));
@@ -411,8 +458,17 @@ impl<'a> Cx2d<'a> {
             if let Some(mut many) = self.begin_many_instances(&draw_atlas.draw_trapezoid.draw_vars) {
                 for todo in atlas_todo {
                     let entry = atlas.entries.get(&todo).unwrap();
-                    let path = atlas.paths.get(&entry.path_hash).unwrap();
-                    draw_atlas.draw_trapezoid.draw_vector(entry, path, &mut many);
+                    // a whole svg file draws all its layers into one slot
+                    if let Some(svg) = atlas.svgs.get(&entry.path_hash) {
+                        for layer in &svg.layers {
+                            let path = atlas.paths.get(&layer.path_hash).unwrap();
+                            draw_atlas.draw_trapezoid.draw_vector(entry, path, &mut many);
+                        }
+                    }
+                    else {
+                        let path = atlas.paths.get(&entry.path_hash).unwrap();
+                        draw_atlas.draw_trapezoid.draw_vector(entry, path, &mut many);
+                    }
                 }
                 
                 self.end_many_instances(many);
@@ -424,274 +480,3 @@ impl<'a> Cx2d<'a> {
     
     
 }
-
-fn parse_svg_path(path: &[u8]) -> Result<Vec<PathCommand>, String> {
-    #[derive(Debug)]
-    enum Cmd {
-        Unknown,
-        Move(bool),
-        Hor(bool),
-        Vert(bool),
-        Line(bool),
-        Arc(bool),
-        Cubic(bool),
-        Quadratic(bool),
-        Close
-    }
-    impl Default for Cmd {fn default() -> Self {Self::Unknown}}
-    
-    #[derive(Default)]
-    struct ParseState {
-        cmd: Cmd,
-        expect_nums: usize,
-        chain: bool,
-        nums: [f64; 7],
-        num_count: usize,
-        last_pt: Point,
-        out: Vec<PathCommand>,
-        num_state: Option<NumState>
-    }
-    
-    #[derive(Debug)]
-    struct NumState {
-        num: f64,
-        mul: f64,
-        has_dot: bool,
-    }
-    
-    impl NumState {
-        fn new_pos(v: f64) -> Self {Self {num: v, mul: 1.0, has_dot: false}}
-        fn new_min() -> Self {Self {num: 0.0, mul: -1.0, has_dot: false}}
-        fn finalize(self) -> f64 {self.num * self.mul}
-        fn add_digit(&mut self, digit: f64) {
-            self.num *= 10.0;
-            self.num += digit;
-            if self.has_dot {
-                self.mul *= 0.1;
-            }
-        }
-    }
-    
-    impl ParseState {
-        fn next_cmd(&mut self, cmd: Cmd) -> Result<(), String> {
-            self.finalize_cmd() ?;
-            self.chain = false;
-            self.expect_nums = match cmd {
-                Cmd::Unknown => panic!(),
-                Cmd::Move(_) => 2,
-                Cmd::Hor(_) => 1,
-                Cmd::Vert(_) => 1,
-                Cmd::Line(_) => 2,
-                Cmd::Cubic(_) => 6,
-                Cmd::Arc(_) => 7,
-                Cmd::Quadratic(_) => 4,
-                Cmd::Close => 0
-            };
-            self.cmd = cmd;
-            Ok(())
-        }
-        
-        fn add_min(&mut self) -> Result<(), String> {
-            if self.num_state.is_some() {
-                self.finalize_num();
-            }
-            if self.expect_nums == self.num_count {
-                self.finalize_cmd() ?;
-            }
-            if self.expect_nums == 0 {
-                return Err(format!("Unexpected minus"));
-            }
-            self.num_state = Some(NumState::new_min());
-            Ok(())
-        }
-        
-        fn add_digit(&mut self, digit: f64) -> Result<(), String> {
-            if let Some(num_state) = &mut self.num_state {
-                num_state.add_digit(digit);
-            }
-            else {
-                if self.expect_nums == self.num_count {
-                    self.finalize_cmd() ?;
-                }
-                if self.expect_nums == 0 {
-                    return Err(format!("Unexpected digit"));
-                }
-                self.num_state = Some(NumState::new_pos(digit))
-            }
-            Ok(())
-        }
-        
-        fn add_dot(&mut self) -> Result<(), String> {
-            if let Some(num_state) = &mut self.num_state {
-                if num_state.has_dot {
-                    self.finalize_num();
-                    self.add_digit(0.0) ?;
-                    self.add_dot() ?;
-                    return Ok(());
-                }
-                num_state.has_dot = true;
-            }
-            else {
-                self.add_digit(0.0) ?;
-                self.add_dot() ?;
-            }
-            Ok(())
-        }
-        
-        fn finalize_num(&mut self) {
-            if let Some(num_state) = self.num_state.take() {
-                self.nums[self.num_count] = num_state.finalize();
-                self.num_count += 1;
-            }
-        }
-        
-        fn whitespace(&mut self) -> Result<(), String> {
-            self.finalize_num();
-            if self.expect_nums == self.num_count {
-                self.finalize_cmd() ?;
-            }
-            Ok(())
-        }
-        
-        fn finalize_cmd(&mut self) -> Result<(), String> {
-            self.finalize_num();
-            if self.chain && self.num_count == 0 {
-                return Ok(())
-            }
-            if self.expect_nums != self.num_count {
-                return Err(format!("SVG Path command {:?} expected {} points, got {}", self.cmd, self.expect_nums, self.num_count));
-            }
-            match self.cmd {
-                Cmd::Unknown => (),
-                Cmd::Move(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[0], y: self.nums[1]};
-                    }
-                    else {
-                        self.last_pt += Vector {x: self.nums[0], y: self.nums[1]};
-                    }
-                    self.out.push(PathCommand::MoveTo(self.last_pt));
-                },
-                Cmd::Hor(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[0], y: self.last_pt.y};
-                    }
-                    else {
-                        self.last_pt += Vector {x: self.nums[0], y: 0.0};
-                    }
-                    self.out.push(PathCommand::LineTo(self.last_pt));
-                }
-                Cmd::Vert(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.last_pt.x, y: self.nums[0]};
-                    }
-                    else {
-                        self.last_pt += Vector {x: 0.0, y: self.nums[0]};
-                    }
-                    self.out.push(PathCommand::LineTo(self.last_pt));
-                }
-                Cmd::Line(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[0], y: self.nums[1]};
-                    }
-                    else {
-                        self.last_pt += Vector {x: self.nums[0], y: self.nums[1]};
-                    }
-                    self.out.push(PathCommand::LineTo(self.last_pt));
-                },
-                Cmd::Cubic(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[4], y: self.nums[5]};
-                        self.out.push(PathCommand::CubicTo(
-                            Point {x: self.nums[0], y: self.nums[1]},
-                            Point {x: self.nums[2], y: self.nums[3]},
-                            self.last_pt,
-                        ));
-                    } else {
-                        self.out.push(PathCommand::CubicTo(
-                            self.last_pt + Vector {x: self.nums[0], y: self.nums[1]},
-                            self.last_pt + Vector {x: self.nums[2], y: self.nums[3]},
-                            self.last_pt + Vector {x: self.nums[4], y: self.nums[5]},
-                        ));
-                        self.last_pt += Vector {x: self.nums[4], y: self.nums[5]};
-                    }
-                },
-                Cmd::Arc(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[5], y: self.nums[6]};
-                        self.out.push(PathCommand::ArcTo(
-                            self.last_pt,
-                            Point {x: self.nums[0], y: self.nums[1]},
-                            self.nums[2],
-                            self.nums[3] != 0.0,
-                            self.nums[4] != 0.0,
-                        ));
-                    }
-                    else {
-                        self.out.push(PathCommand::ArcTo(
-                            self.last_pt + Vector {x: self.nums[5], y: self.nums[6]},
-                            Point {x: self.nums[0], y: self.nums[1]},
-                            self.nums[2],
-                            self.nums[3] != 0.0,
-                            self.nums[4] != 0.0,
-                        ));
-                        self.last_pt += Vector {x: self.nums[5], y: self.nums[6]};
-                    }
-                },
-                Cmd::Quadratic(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[2], y: self.nums[3]};
-                        self.out.push(PathCommand::QuadraticTo(
-                            Point {x: self.nums[0], y: self.nums[1]},
-                            self.last_pt
-                        ));
-                    }
-                    else {
-                        self.out.push(PathCommand::QuadraticTo(
-                            self.last_pt + Vector {x: self.nums[0], y: self.nums[1]},
-                            self.last_pt + Vector {x: self.nums[2], y: self.nums[3]},
-                        ));
-                        self.last_pt += Vector {x: self.nums[2], y: self.nums[3]};
-                    }
-                }
-                Cmd::Close => {
-                    self.out.push(PathCommand::Close);
-                }
-            }
-            self.num_count = 0;
-            self.chain = true;
-            Ok(())
-        }
-    }
-    
-    let mut state = ParseState::default();
-    for i in 0..path.len() {
-        match path[i] {
-            b'M' => state.next_cmd(Cmd::Move(true)) ?,
-            b'm' => state.next_cmd(Cmd::Move(false)) ?,
-            b'Q' => state.next_cmd(Cmd::Quadratic(true)) ?,
-            b'q' => state.next_cmd(Cmd::Quadratic(false)) ?,
-            b'C' => state.next_cmd(Cmd::Cubic(true)) ?,
-            b'c' => state.next_cmd(Cmd::Cubic(false)) ?,
-            b'H' => state.next_cmd(Cmd::Hor(true)) ?,
-            b'h' => state.next_cmd(Cmd::Hor(false)) ?,
-            b'V' => state.next_cmd(Cmd::Vert(true)) ?,
-            b'v' => state.next_cmd(Cmd::Vert(false)) ?,
-            b'L' => state.next_cmd(Cmd::Line(true)) ?,
-            b'l' => state.next_cmd(Cmd::Line(false)) ?,
-            b'A' => state.next_cmd(Cmd::Arc(true)) ?,
-            b'a' => state.next_cmd(Cmd::Arc(false)) ?,
-            b'Z' | b'z' => state.next_cmd(Cmd::Close) ?,
-            b'-' => state.add_min() ?,
-            b'0'..=b'9' => state.add_digit((path[i] - b'0') as f64) ?,
-            b'.' => state.add_dot() ?,
-            b',' | b' ' | b'\r' | b'\n' | b'\t' => state.whitespace() ?,
-            x => {
-                return Err(format!("Unexpected character {} - {}", x, x as char))
-            }
-        }
-    }
-    state.finalize_cmd() ?;
-    
-    Ok(state.out)
-}
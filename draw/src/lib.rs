@@ -49,9 +49,11 @@ pub use crate::{
     shader::{
         //draw_shape::{DrawShape, Shape, Fill},
         draw_icon::DrawIcon,
+        draw_svg::DrawSvg,
         draw_quad::DrawQuad,
         draw_line::DrawLine,
         draw_text::DrawText,
+        draw_rich_text::{DrawRichText, RichText, RichTextSpan, RichTextFont},
         draw_color::DrawColor,
     },
     geometry::{
@@ -64,9 +66,12 @@ pub fn live_design(cx: &mut Cx) {
     crate::shader::draw_quad::live_design(cx);
     crate::shader::draw_color::live_design(cx);
     crate::shader::draw_icon::live_design(cx);
+    crate::shader::draw_svg::live_design(cx);
     crate::shader::draw_text::live_design(cx);
+    crate::shader::draw_rich_text::live_design(cx);
     crate::shader::draw_line::live_design(cx);
     crate::geometry::geometry_gen::live_design(cx);
+    GeometryQuad2D::register_geometry_fields(cx);
     crate::shader::std::live_design(cx);
     crate::shader::draw_trapezoid::live_design(cx);
 }
\ No newline at end of file
@@ -0,0 +1,506 @@
+use {
+    std::ops::Range,
+    crate::{
+        makepad_platform::*,
+        turtle::{Walk, Size, Align},
+        font_atlas::CxFontsAtlas,
+        shader::{
+            draw_text::{DrawText, TextGlyph, TextStyle, TextWrap},
+            draw_color::DrawColor,
+        },
+        cx_2d::Cx2d
+    },
+};
+
+live_design!{
+    DrawRichText = {{DrawRichText}} {}
+}
+
+/// Which of the text styles of a `DrawRichText` a span is drawn in
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RichTextFont {
+    #[default]
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+    Code
+}
+
+/// A styled range of a `RichText`, `range` is in bytes
+#[derive(Clone, Debug, Default)]
+pub struct RichTextSpan {
+    pub range: Range<usize>,
+    pub font: RichTextFont,
+    /// Replaces the color of the `DrawText`
+    pub color: Option<Vec4>,
+    pub underline: bool,
+    pub background: Option<Vec4>,
+    pub link: Option<LiveId>,
+}
+
+impl RichTextSpan {
+    pub fn with_font(mut self, font: RichTextFont) -> Self {
+        self.font = font;
+        self
+    }
+
+    pub fn with_color(mut self, color: Vec4) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn with_underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn with_background(mut self, color: Vec4) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Makes the span a link, which is underlined
+    pub fn with_link(mut self, link: LiveId) -> Self {
+        self.link = Some(link);
+        self.underline = true;
+        self
+    }
+}
+
+/// Text with styled spans. Text outside of any span is drawn in the style of the `DrawText`,
+/// where spans overlap the last one wins.
+#[derive(Clone, Debug, Default)]
+pub struct RichText {
+    pub text: String,
+    pub spans: Vec<RichTextSpan>,
+}
+
+impl RichText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends unstyled text
+    pub fn push_str(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    /// Appends text drawn in the style of span, whatever range it had is replaced
+    pub fn push_span(&mut self, text: &str, span: RichTextSpan) {
+        let start = self.text.len();
+        self.text.push_str(text);
+        self.spans.push(RichTextSpan {range: start..self.text.len(), ..span});
+    }
+
+    /// The index of the span the byte at offset is styled by
+    pub fn span_at(&self, offset: usize) -> Option<usize> {
+        self.spans.iter().rposition( | span | span.range.contains(&offset))
+    }
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct DrawRichText {
+    /// Draws the glyphs, its text style and color are the ones of unstyled text
+    #[live] pub draw_text: DrawText,
+    #[live] pub draw_background: DrawColor,
+    #[live] pub draw_underline: DrawColor,
+    #[live] pub bold: TextStyle,
+    #[live] pub italic: TextStyle,
+    #[live] pub bold_italic: TextStyle,
+    #[live] pub code: TextStyle,
+    #[live(1.0)] pub underline_width: f64,
+    #[live(1.5)] pub underline_offset: f64,
+    // the char ranges of the spans that were drawn last, for hit testing
+    #[rust] drawn_spans: Vec<(Range<usize>, usize)>,
+}
+
+struct RichChar {
+    byte: usize,
+    c: char,
+    span: Option<usize>,
+    advance: f64,
+}
+
+struct RichLine {
+    // the chars of the line, without the newline that ends it
+    chars: Range<usize>,
+    width: f64,
+    // the y of the line, and its baseline relative to that
+    ypos: f64,
+    baseline: f64,
+    height: f64,
+}
+
+impl DrawRichText {
+    fn span_text_style(&self, rich: &RichText, span: Option<usize>) -> &TextStyle {
+        match span.map(| span | rich.spans[span].font).unwrap_or_default() {
+            RichTextFont::Regular => &self.draw_text.text_style,
+            RichTextFont::Bold => &self.bold,
+            RichTextFont::Italic => &self.italic,
+            RichTextFont::BoldItalic => &self.bold_italic,
+            RichTextFont::Code => &self.code,
+        }
+    }
+
+    fn measure_chars(&self, rich: &RichText, fonts_atlas: &mut CxFontsAtlas) -> Vec<RichChar> {
+        let mut chars = Vec::new();
+        let mut font_ids = Vec::new();
+        let mut font_ids_span = None;
+        for (byte, c) in rich.text.char_indices() {
+            let span = rich.span_at(byte);
+            let text_style = self.span_text_style(rich, span);
+            if font_ids_span != Some(span) {
+                font_ids = text_style.font_ids();
+                font_ids_span = Some(span);
+            }
+            let advance = if c == '\n' || c == '\r' {
+                0.0
+            }
+            else {
+                fonts_atlas.char_advance(&font_ids, c, text_style.font_size) * self.draw_text.font_scale
+            };
+            chars.push(RichChar {byte, c, span, advance});
+        }
+        chars
+    }
+
+    fn line_metrics(&self, rich: &RichText, chars: &[RichChar], line: Range<usize>, ypos: f64) -> RichLine {
+        let font_scale = self.draw_text.font_scale;
+        let mut width = 0.0;
+        let mut baseline = 0.0f64;
+        let mut height = 0.0f64;
+        let mut spacing = 0.0f64;
+        let mut measure = | text_style: &TextStyle | {
+            baseline = baseline.max(text_style.font_size * text_style.top_drop * font_scale);
+            height = height.max(text_style.font_size * text_style.height_factor * font_scale);
+            spacing = spacing.max(text_style.font_size * text_style.height_factor * font_scale * text_style.line_spacing);
+        };
+        if line.is_empty() {
+            measure(self.span_text_style(rich, None));
+        }
+        let mut last_span = None;
+        for rc in &chars[line.clone()] {
+            if last_span != Some(rc.span) {
+                measure(self.span_text_style(rich, rc.span));
+                last_span = Some(rc.span);
+            }
+            width += rc.advance;
+        }
+        RichLine {chars: line, width, ypos, baseline, height: height.max(spacing)}
+    }
+
+    /// Draws the text with its spans styled, wrapped according to the `TextWrap` of the
+    /// `DrawText`. The text of a line is drawn style by style, bidi runs are reordered within
+    /// a style but not across them.
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk, align: Align, rich: &RichText) {
+        self.drawn_spans.clear();
+        if rich.text.is_empty() || self.draw_text.text_style.font.font_id.is_none() {
+            return
+        }
+        let fonts_atlas_rc = cx.fonts_atlas_rc.clone();
+        let mut fonts_atlas = fonts_atlas_rc.0.borrow_mut();
+        let fonts_atlas = &mut*fonts_atlas;
+
+        let chars = self.measure_chars(rich, fonts_atlas);
+        let eval_width = cx.turtle().eval_width(&walk, cx.turtle().layout().flow);
+        let eval_height = cx.turtle().eval_height(&walk, cx.turtle().layout().flow);
+
+        let wrap = if walk.width.is_fit() {TextWrap::Line} else {self.draw_text.wrap.clone()};
+        let mut line_ranges = break_lines(&chars, eval_width, matches!(wrap, TextWrap::Word));
+
+        // an ellipsis keeps the first line, and cuts it where it no longer fits with the dots
+        let mut ellipsis = None;
+        if let TextWrap::Ellipsis = wrap {
+            line_ranges.truncate(1);
+            let line = line_ranges[0].clone();
+            let line_width: f64 = chars[line.clone()].iter().map( | rc | rc.advance).sum();
+            if line_width > eval_width {
+                let mut width = 0.0;
+                let mut cut = line.start;
+                for (i, rc) in chars[line.clone()].iter().enumerate() {
+                    let text_style = self.span_text_style(rich, rc.span);
+                    let dots_width = fonts_atlas.char_advance(&text_style.font_ids(), '.', text_style.font_size) * self.draw_text.font_scale * 3.0;
+                    if width + rc.advance + dots_width > eval_width {
+                        break;
+                    }
+                    width += rc.advance;
+                    cut = line.start + i + 1;
+                }
+                line_ranges[0] = line.start..cut;
+                ellipsis = Some(if cut > line.start {chars[cut - 1].span} else {None});
+            }
+        }
+
+        let mut lines = Vec::new();
+        let mut ypos = 0.0;
+        for line in line_ranges {
+            let line = self.line_metrics(rich, &chars, line, ypos);
+            ypos += line.height;
+            lines.push(line);
+        }
+
+        let measured_width = lines.iter().fold(0.0f64, | w, line | w.max(line.width));
+        let measured_height = ypos;
+        let width = if walk.width.is_fit() {measured_width} else {eval_width};
+        let height = if walk.height.is_fit() {measured_height} else {eval_height};
+
+        // a right to left layout mirrors how the text is aligned in its rect
+        let align = if cx.turtle().layout().rtl {Align {x: 1.0 - align.x, y: align.y}} else {align};
+        let rect = cx.walk_turtle(Walk {
+            width: Size::Fixed(width),
+            height: Size::Fixed(height),
+            ..walk
+        });
+        let y_align = (height - measured_height) * align.y;
+
+        // this makes sure the backgrounds go behind the text
+        self.draw_background.append_to_draw_call(cx);
+
+        let regular_style = self.draw_text.text_style.clone();
+        let regular_color = self.draw_text.color;
+
+        let text_end = | i: usize | if i < chars.len() {chars[i].byte} else {rich.text.len()};
+        let mut paragraph = (None, unicode_bidi::Level::ltr());
+        for (line_index, line) in lines.iter().enumerate() {
+            // the direction comes from the whole paragraph, so the wrapped lines of a right to
+            // left paragraph that start with a latin word still run right to left
+            let line_start = text_end(line.chars.start);
+            let paragraph_start = rich.text[..line_start].rfind('\n').map_or(0, | i | i + 1);
+            if paragraph.0 != Some(paragraph_start) {
+                let paragraph_end = rich.text[line_start..].find('\n').map_or(rich.text.len(), | i | line_start + i);
+                paragraph = (Some(paragraph_start), self.draw_text.paragraph_level(&rich.text[paragraph_start..paragraph_end]));
+            }
+            let level = paragraph.1;
+            let line_x = rect.pos.x + align.x * (width - line.width);
+            let line_y = rect.pos.y + y_align + line.ypos;
+
+            // the line is drawn a span at a time, a right to left paragraph from the right
+            let mut pieces = Vec::new();
+            let mut piece_start = line.chars.start;
+            for i in line.chars.clone() {
+                if chars[i].span != chars[piece_start].span {
+                    pieces.push((piece_start..i, chars[piece_start].span));
+                    piece_start = i;
+                }
+            }
+            if piece_start < line.chars.end {
+                pieces.push((piece_start..line.chars.end, chars[piece_start].span));
+            }
+            if line_index == 0 {
+                if let Some(span) = ellipsis {
+                    pieces.push((line.chars.end..line.chars.end, span));
+                }
+            }
+
+            let mut xpos = 0.0;
+            for (piece, span) in pieces {
+                let is_ellipsis = piece.is_empty();
+                let text = if is_ellipsis {"..."} else {&rich.text[text_end(piece.start)..text_end(piece.end)]};
+                let text_style = self.span_text_style(rich, span).clone();
+                let piece_width = if is_ellipsis {
+                    fonts_atlas.char_advance(&text_style.font_ids(), '.', text_style.font_size) * self.draw_text.font_scale * 3.0
+                }
+                else {
+                    chars[piece.clone()].iter().map( | rc | rc.advance).sum()
+                };
+                let piece_x = if level.is_rtl() {line_x + line.width - xpos - piece_width} else {line_x + xpos};
+                let piece_y = line_y + line.baseline - text_style.font_size * text_style.top_drop * self.draw_text.font_scale;
+                xpos += piece_width;
+
+                let span = span.map( | span | &rich.spans[span]);
+                let color = span.and_then( | span | span.color).unwrap_or(regular_color);
+                if let Some(background) = span.and_then( | span | span.background) {
+                    self.draw_background.color = background;
+                    self.draw_background.draw_abs(cx, Rect {
+                        pos: dvec2(piece_x, line_y),
+                        size: dvec2(piece_width, line.height)
+                    });
+                }
+
+                self.draw_text.text_style = text_style;
+                self.draw_text.color = color;
+                self.draw_text.draw_inner(cx, dvec2(piece_x, piece_y), text, piece.start, level, fonts_atlas);
+
+                if span.is_some_and( | span | span.underline) {
+                    self.draw_underline.color = color;
+                    self.draw_underline.draw_abs(cx, Rect {
+                        pos: dvec2(piece_x, line_y + line.baseline + self.underline_offset),
+                        size: dvec2(piece_width, self.underline_width)
+                    });
+                }
+            }
+        }
+
+        self.draw_text.text_style = regular_style;
+        self.draw_text.color = regular_color;
+        if self.draw_text.many_instances.is_some() {
+            self.draw_text.end_many_instances(cx)
+        }
+
+        self.drawn_spans = drawn_spans(rich);
+    }
+
+    /// The index of the span under pos in the text that was drawn last, so a click can follow
+    /// its link
+    pub fn span_at(&self, cx: &Cx, pos: DVec2) -> Option<usize> {
+        let height_factor = self.draw_text.text_style.height_factor * self.draw_text.font_scale;
+        glyph_span_at(&self.draw_text.get_glyphs(cx), &self.drawn_spans, pos, height_factor)
+    }
+
+    /// The link under pos, see `span_at`
+    pub fn link_at(&self, cx: &Cx, pos: DVec2, rich: &RichText) -> Option<LiveId> {
+        rich.spans.get(self.span_at(cx, pos) ?)?.link
+    }
+}
+
+// breaks the chars into lines, a line is wrapped between words or in a word that is wider
+// than the line on its own
+fn break_lines(chars: &[RichChar], eval_width: f64, wrap: bool) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    let mut line_width = 0.0;
+    let mut word_start = 0;
+    let mut word_width = 0.0;
+    for (i, rc) in chars.iter().enumerate() {
+        if rc.c == '\n' {
+            lines.push(line_start..i);
+            line_start = i + 1;
+            word_start = i + 1;
+            line_width = 0.0;
+            word_width = 0.0;
+            continue;
+        }
+        if i > 0 && !rc.c.is_whitespace() && chars[i - 1].c.is_whitespace() {
+            word_start = i;
+            word_width = 0.0;
+        }
+        if wrap && !rc.c.is_whitespace() && line_width + rc.advance >= eval_width && i > line_start {
+            if word_start > line_start {
+                // move the word to the next line
+                lines.push(line_start..word_start);
+                line_start = word_start;
+                line_width = word_width;
+            }
+            else {
+                // the word doesn't fit on a line at all, so it breaks here
+                lines.push(line_start..i);
+                line_start = i;
+                word_start = i;
+                line_width = 0.0;
+                word_width = 0.0;
+            }
+        }
+        line_width += rc.advance;
+        word_width += rc.advance;
+    }
+    lines.push(line_start..chars.len());
+    lines
+}
+
+// the char ranges of the spans, in the order they are in
+fn drawn_spans(rich: &RichText) -> Vec<(Range<usize>, usize)> {
+    rich.spans.iter().enumerate().map( | (index, span) | {
+        let start = char_boundary(&rich.text, span.range.start);
+        let end = char_boundary(&rich.text, span.range.end).max(start);
+        let char_start = rich.text[..start].chars().count();
+        let len = rich.text[start..end].chars().count();
+        (char_start..char_start + len, index)
+    }).collect()
+}
+
+// the span of the glyph under pos, where spans overlap the last one wins like it does when drawing
+fn glyph_span_at(glyphs: &[TextGlyph], drawn_spans: &[(Range<usize>, usize)], pos: DVec2, height_factor: f64) -> Option<usize> {
+    let glyph = glyphs.iter().find( | glyph | {
+        pos.x >= glyph.pos.x && pos.x < glyph.pos.x + glyph.advance
+            && pos.y >= glyph.pos.y && pos.y < glyph.pos.y + glyph.font_size * height_factor
+    }) ?;
+    drawn_spans.iter().rev()
+        .find( | (range, _) | range.contains(&glyph.char_offset))
+        .map( | (_, index) | *index)
+}
+
+// offset clamped to the text and moved back to the start of the char it is in, span ranges come
+// from the caller and can point anywhere
+fn char_boundary(text: &str, offset: usize) -> usize {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // every char 10 wide
+    fn chars(rich: &RichText) -> Vec<RichChar> {
+        rich.text.char_indices().map( | (byte, c) | {
+            RichChar {byte, c, span: rich.span_at(byte), advance: if c == '\n' {0.0} else {10.0}}
+        }).collect()
+    }
+    
+    fn glyph(x: f64, char_offset: usize) -> TextGlyph {
+        TextGlyph {pos: dvec2(x, 0.0), advance: 10.0, font_size: 10.0, char_offset, char_len: 1, rtl: false}
+    }
+    
+    #[test]
+    fn words_wrap_across_span_boundaries() {
+        let mut rich = RichText::new();
+        rich.push_str("one ");
+        rich.push_span("tw", RichTextSpan::default().with_font(RichTextFont::Bold));
+        rich.push_str("o three");
+        let chars = chars(&rich);
+        // "two" is one word even though it starts in a span and ends outside of it
+        assert_eq!(break_lines(&chars, 65.0, true), vec![0..4, 4..8, 8..13]);
+        assert_eq!(break_lines(&chars, 65.0, false), vec![0..13]);
+    }
+    
+    #[test]
+    fn words_wider_than_a_line_break_inside_their_span() {
+        let mut rich = RichText::new();
+        rich.push_span("abcdefgh", RichTextSpan::default().with_underline());
+        assert_eq!(break_lines(&chars(&rich), 35.0, true), vec![0..3, 3..6, 6..8]);
+    }
+    
+    #[test]
+    fn newlines_end_lines() {
+        let mut rich = RichText::new();
+        rich.push_str("ab\n");
+        rich.push_span("cd", RichTextSpan::default());
+        assert_eq!(break_lines(&chars(&rich), 100.0, true), vec![0..2, 3..5]);
+    }
+    
+    #[test]
+    fn drawn_spans_are_in_chars() {
+        let mut rich = RichText::new();
+        rich.push_str("é ");
+        rich.push_span("link", RichTextSpan::default().with_link(live_id!(home)));
+        // ranges that point past the text or into a char are moved to where they can be
+        rich.spans.push(RichTextSpan {range: 100..200, ..RichTextSpan::default()});
+        rich.spans.push(RichTextSpan {range: 1..2, ..RichTextSpan::default()});
+        assert_eq!(drawn_spans(&rich), vec![(2..6, 0), (6..6, 1), (0..1, 2)]);
+    }
+    
+    #[test]
+    fn links_are_hit_by_their_glyphs() {
+        let mut rich = RichText::new();
+        rich.push_str("go ");
+        rich.push_span("home", RichTextSpan::default().with_link(live_id!(home)));
+        rich.push_span("me", RichTextSpan::default().with_color(vec4(1.0, 0.0, 0.0, 1.0)));
+        rich.spans[1].range = 5..rich.text.len();
+        let glyphs: Vec<TextGlyph> = (0..rich.text.len()).map( | i | glyph(i as f64 * 10.0, i)).collect();
+        let drawn = drawn_spans(&rich);
+        let span_at = | x: f64, y: f64 | glyph_span_at(&glyphs, &drawn, dvec2(x, y), 1.3);
+        assert_eq!(span_at(5.0, 5.0), None);
+        assert_eq!(span_at(35.0, 5.0), Some(0));
+        assert_eq!(rich.spans[span_at(45.0, 5.0).unwrap()].link, Some(live_id!(home)));
+        // where spans overlap the last one wins
+        assert_eq!(span_at(55.0, 5.0), Some(1));
+        // below the glyphs and past the end
+        assert_eq!(span_at(35.0, 14.0), None);
+        assert_eq!(span_at(95.0, 5.0), None);
+        assert_eq!(rich.span_at(5), Some(1));
+    }
+}
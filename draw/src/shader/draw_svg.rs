@@ -0,0 +1,261 @@
+use {
+    crate::{
+        makepad_platform::*,
+        makepad_vector::svg::{SvgPaint, SvgStop},
+        geometry::GeometryQuad2D,
+        icon_atlas::{CxIconAtlas, CxIconArgs},
+        cx_2d::Cx2d,
+        turtle::{Walk, Size}
+    },
+};
+
+
+live_design!{
+
+    DrawSvg = {{DrawSvg}} {
+        color: #fff
+
+        uniform u_brightness: float
+        uniform u_curve: float
+
+        texture tex: texture2d
+        varying pos: vec2,
+        varying tex_coord1: vec2
+        varying clipped: vec2
+
+        fn clip_and_transform_vertex(self, rect_pos: vec2, rect_size: vec2) -> vec4 {
+            let clipped: vec2 = clamp(
+                self.geom_pos * rect_size + rect_pos,
+                self.draw_clip.xy,
+                self.draw_clip.zw
+            )
+            self.pos = (clipped - rect_pos) / rect_size
+
+            self.tex_coord1 = mix(
+                self.icon_t1.xy,
+                self.icon_t2.xy,
+                self.pos.xy
+            )
+
+            return self.camera_projection * (self.camera_view * (self.view_transform * vec4(
+                clipped.x,
+                clipped.y,
+                self.draw_depth + self.draw_zbias,
+                1.
+            )))
+        }
+
+        fn vertex(self) -> vec4 {
+            return self.clip_and_transform_vertex(self.rect_pos, self.rect_size)
+        }
+
+        // where this pixel is along the gradient, from 0 to 1
+        fn gradient_offset(self) -> float {
+            let p = self.pos * self.rect_size;
+            if self.paint_kind < 1.5 {
+                let d = self.gradient_to - self.gradient_from;
+                return clamp(dot(p - self.gradient_from, d) / max(dot(d, d), 0.0001), 0., 1.);
+            }
+            return clamp(length(p - self.gradient_from) / max(self.gradient_radius, 0.0001), 0., 1.);
+        }
+
+        fn get_color(self) -> vec4 {
+            if self.paint_kind < 0.5 {
+                return self.paint_color;
+            }
+            let t = self.gradient_offset();
+            let o = self.stop_offsets;
+            let col = self.stop_color0;
+            col = mix(col, self.stop_color1, clamp((t - o.x) / max(o.y - o.x, 0.0001), 0., 1.));
+            col = mix(col, self.stop_color2, clamp((t - o.y) / max(o.z - o.y, 0.0001), 0., 1.));
+            col = mix(col, self.stop_color3, clamp((t - o.z) / max(o.w - o.z, 0.0001), 0., 1.));
+            return col;
+        }
+
+        fn pixel(self) -> vec4 {
+            let s = sample2d_rt(self.tex, self.tex_coord1.xy).x;
+            s = pow(s, self.u_curve);
+            let col = self.get_color();
+            let a = s * col.a * self.opacity;
+            return vec4(col.rgb * self.u_brightness * a, a);
+        }
+    }
+}
+
+/// Draws an svg file in color, layer by layer, with the fills, strokes, opacities and gradients
+/// of the file. `color` is used for the parts that are painted with `currentColor`.
+///
+/// Gradients with more than 4 stops are resampled to 4.
+#[derive(Live, LiveRegister)]
+#[repr(C)]
+pub struct DrawSvg {
+    #[live(1.0)] pub brightness: f32,
+    #[live(1.0)] pub curve: f32,
+    #[live(0.5)] pub linearize: f32,
+
+    #[live] pub svg_file: LiveDependency,
+    #[live] pub translate: DVec2,
+    #[live(1.0)] pub scale: f64,
+
+    #[live] pub geometry: GeometryQuad2D,
+    #[deref] pub draw_vars: DrawVars,
+    #[calc] pub rect_pos: Vec2,
+    #[calc] pub rect_size: Vec2,
+    #[calc] pub draw_clip: Vec4,
+    #[live(1.0)] pub draw_depth: f32,
+
+    #[live] pub color: Vec4,
+    #[calc] pub icon_t1: Vec2,
+    #[calc] pub icon_t2: Vec2,
+
+    #[calc] pub paint_kind: f32,
+    #[calc] pub paint_color: Vec4,
+    #[calc] pub opacity: f32,
+    #[calc] pub gradient_from: Vec2,
+    #[calc] pub gradient_to: Vec2,
+    #[calc] pub gradient_radius: f32,
+    #[calc] pub stop_offsets: Vec4,
+    #[calc] pub stop_color0: Vec4,
+    #[calc] pub stop_color1: Vec4,
+    #[calc] pub stop_color2: Vec4,
+    #[calc] pub stop_color3: Vec4,
+}
+
+impl LiveHook for DrawSvg{
+    fn before_apply(&mut self, cx: &mut Cx, apply_from: ApplyFrom, index: usize, nodes: &[LiveNode]){
+        self.draw_vars.before_apply_init_shader(cx, apply_from, index, nodes, &self.geometry);
+    }
+    fn after_apply(&mut self, cx: &mut Cx, apply_from: ApplyFrom, index: usize, nodes: &[LiveNode]) {
+        self.draw_vars.after_apply_update_self(cx, apply_from, index, nodes, &self.geometry);
+    }
+}
+
+impl DrawSvg {
+
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, mut walk: Walk) {
+        let icon_atlas_rc = cx.icon_atlas_rc.clone();
+        let mut icon_atlas = icon_atlas_rc.0.borrow_mut();
+        let icon_atlas = &mut*icon_atlas;
+
+        let no_path = Default::default();
+        let Some((svg_hash, _)) = icon_atlas.get_icon_bounds(cx, &no_path, self.svg_file.as_ref()) else {
+            return
+        };
+        let Some(svg) = icon_atlas.get_svg(svg_hash) else {
+            return
+        };
+        // unlike DrawIcon, the padding the file has around its content is kept
+        let bounds = svg.view_box.unwrap_or(svg.bounds);
+        let layers: Vec<_> = svg.layers.iter().map( | layer | (layer.path_hash, layer.paint.clone(), layer.opacity)).collect();
+
+        let width_is_fit = walk.width.is_fit();
+        let height_is_fit = walk.height.is_fit();
+        let peek_rect = cx.peek_walk_turtle(walk);
+        let mut scale = 1.0;
+
+        if width_is_fit {
+            if !height_is_fit {
+                scale = peek_rect.size.y / bounds.size.y
+            };
+            walk.width = Size::Fixed(bounds.size.x * self.scale * scale);
+        }
+        if height_is_fit {
+            if !width_is_fit {
+                scale = peek_rect.size.x / bounds.size.x
+            };
+            walk.height = Size::Fixed(bounds.size.y * self.scale * scale);
+        }
+        if !width_is_fit && !height_is_fit {
+            scale = (peek_rect.size.y / bounds.size.y).min(peek_rect.size.x / bounds.size.x);
+        }
+        let rect = cx.walk_turtle(walk);
+        if rect.is_nan(){
+            return
+        }
+
+        let dpi_factor = cx.current_dpi_factor();
+        let dpi_pos = rect.pos * dpi_factor;
+        let snapped_pos = dpi_pos.floor();
+        let snapped_size = (rect.size * dpi_factor).ceil() + dvec2(1.0, 1.0);
+        let subpixel = dvec2(
+            ((dpi_pos.x - snapped_pos.x) * 8.0).floor() / 8.0,
+            ((dpi_pos.y - snapped_pos.y) * 8.0).floor() / 8.0
+        );
+        let args = CxIconArgs {
+            linearize: self.linearize as f64,
+            size: snapped_size,
+            scale: self.scale * scale * dpi_factor,
+            translate: self.translate - bounds.pos,
+            subpixel
+        };
+        // maps a point of the file to the local space of the rect, where the shader does its
+        // gradients
+        let to_local = | x: f64, y: f64 | -> Vec2 {
+            (((dvec2(x, y) + args.translate) * args.scale + args.subpixel) / dpi_factor).into()
+        };
+
+        self.rect_pos = (snapped_pos / dpi_factor).into();
+        self.rect_size = (snapped_size / dpi_factor).into();
+
+        self.update_draw_call_vars(icon_atlas);
+        let Some(mut many) = cx.begin_many_aligned_instances(&self.draw_vars) else {
+            return
+        };
+        for (path_hash, paint, opacity) in layers {
+            let slot = icon_atlas.get_icon_slot(args.clone(), path_hash);
+            self.icon_t1 = slot.t1;
+            self.icon_t2 = slot.t2;
+            self.opacity = opacity;
+            match paint {
+                SvgPaint::Color(color) => {
+                    self.paint_kind = 0.0;
+                    self.paint_color = vec4(color.r, color.g, color.b, color.a);
+                }
+                SvgPaint::CurrentColor => {
+                    self.paint_kind = 0.0;
+                    self.paint_color = self.color;
+                }
+                SvgPaint::LinearGradient {from, to, stops} => {
+                    self.paint_kind = 1.0;
+                    self.gradient_from = to_local(from.x, from.y);
+                    self.gradient_to = to_local(to.x, to.y);
+                    self.set_stops(&stops);
+                }
+                SvgPaint::RadialGradient {center, radius, stops} => {
+                    self.paint_kind = 2.0;
+                    self.gradient_from = to_local(center.x, center.y);
+                    self.gradient_radius = (radius * args.scale / dpi_factor) as f32;
+                    self.set_stops(&stops);
+                }
+            }
+            many.instances.extend_from_slice(self.draw_vars.as_slice());
+        }
+        let new_area = cx.end_many_instances(many);
+        self.draw_vars.area = cx.update_area_refs(self.draw_vars.area, new_area);
+    }
+
+    fn set_stops(&mut self, stops: &[SvgStop]) {
+        // the shader blends between 4 stops, so longer gradients are sampled at 4 of theirs
+        let pick = | index: usize | -> SvgStop {
+            if stops.len() <= 4 {
+                stops[index.min(stops.len() - 1)]
+            }
+            else {
+                stops[index * (stops.len() - 1) / 3]
+            }
+        };
+        let stops = [pick(0), pick(1), pick(2), pick(3)];
+        self.stop_offsets = vec4(stops[0].offset, stops[1].offset, stops[2].offset, stops[3].offset);
+        let color = | stop: SvgStop | vec4(stop.color.r, stop.color.g, stop.color.b, stop.color.a);
+        self.stop_color0 = color(stops[0]);
+        self.stop_color1 = color(stops[1]);
+        self.stop_color2 = color(stops[2]);
+        self.stop_color3 = color(stops[3]);
+    }
+
+    pub fn update_draw_call_vars(&mut self, atlas: &CxIconAtlas) {
+        self.draw_vars.texture_slots[0] = Some(atlas.texture.clone());
+        self.draw_vars.user_uniforms[0] = self.brightness;
+        self.draw_vars.user_uniforms[1] = self.curve;
+    }
+}
@@ -2,7 +2,7 @@ use {
     crate::{
         makepad_platform::*,
         turtle::{Walk, Size, Align},
-        font_atlas::{CxFontsAtlasTodo, CxFont, CxFontsAtlas, Font},
+        font_atlas::{CxFontsAtlasTodo, CxFontsAtlas, Font, ShapedGlyph},
         draw_list_2d::ManyInstances,
         geometry::GeometryQuad2D,
         cx_2d::Cx2d
@@ -72,6 +72,9 @@ live_design!{
 #[live_ignore]
 pub struct TextStyle {
     #[live()] pub font: Font,
+    /// Fonts that are tried in order for characters the primary font has no glyph for,
+    /// for instance `font_fallbacks: [{path: dep("crate://self/resources/NotoSansCJK.otf")}]`
+    #[live] pub font_fallbacks: Vec<Font>,
     #[live(9.0)] pub font_size: f64,
     #[live(1.0)] pub brightness: f32,
     #[live(0.6)] pub curve: f32,
@@ -80,6 +83,15 @@ pub struct TextStyle {
     #[live(1.3)] pub height_factor: f64,
 }
 
+impl TextStyle {
+    /// The primary font followed by the fallback fonts, in lookup order.
+    pub fn font_ids(&self) -> Vec<usize> {
+        self.font.font_id.into_iter()
+            .chain(self.font_fallbacks.iter().filter_map( | font | font.font_id))
+            .collect()
+    }
+}
+
 #[derive(Clone, Live, LiveHook)]
 #[live_ignore]
 pub enum TextWrap {
@@ -88,6 +100,134 @@ pub enum TextWrap {
     Line
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum TextDirection {
+    // every paragraph takes the direction of its first strong character (UAX #9, P2 and P3)
+    #[pick] Auto,
+    Ltr,
+    Rtl
+}
+
+// The direction of the first strong character of a paragraph. What is inside an isolate doesn't
+// count, up to its matching PDI or the end of the paragraph (UAX #9, P2)
+fn first_strong_level(paragraph: &str) -> Option<unicode_bidi::Level> {
+    use unicode_bidi::BidiClass;
+    let mut isolates = 0usize;
+    for c in paragraph.chars() {
+        match unicode_bidi::bidi_class(c) {
+            BidiClass::LRI | BidiClass::RLI | BidiClass::FSI => isolates += 1,
+            BidiClass::PDI => isolates = isolates.saturating_sub(1),
+            BidiClass::L if isolates == 0 => return Some(unicode_bidi::Level::ltr()),
+            BidiClass::R | BidiClass::AL if isolates == 0 => return Some(unicode_bidi::Level::rtl()),
+            _ => ()
+        }
+    }
+    None
+}
+
+// Maps byte offsets in a string to char offsets. Shaping hands out byte offsets that mostly move
+// a little at a time, so this counts from the last lookup instead of from the start.
+struct CharOffsets<'a> {
+    text: &'a str,
+    byte: usize,
+    char: usize,
+    base: usize,
+}
+
+impl<'a> CharOffsets<'a> {
+    fn new(text: &'a str, base: usize) -> Self {
+        Self {text, byte: 0, char: 0, base}
+    }
+    
+    fn get(&mut self, byte: usize) -> usize {
+        if byte >= self.byte {
+            self.char += self.text[self.byte..byte].chars().count();
+        }
+        else {
+            self.char -= self.text[byte..self.byte].chars().count();
+        }
+        self.byte = byte;
+        self.base + self.char
+    }
+}
+
+// The byte offset where the cluster of glyphs[index] ends. The glyphs are in visual order, so
+// the clusters count up in a left to right run and down in a right to left one.
+fn shaped_cluster_end(glyphs: &[ShapedGlyph], index: usize, rtl: bool, text_len: usize) -> usize {
+    let cluster = glyphs[index].cluster;
+    let next = if rtl {
+        glyphs[..index].iter().rev().find( | glyph | glyph.cluster > cluster)
+    }
+    else {
+        glyphs[index + 1..].iter().find( | glyph | glyph.cluster > cluster)
+    };
+    next.map_or(text_len, | glyph | glyph.cluster)
+}
+
+/// A glyph of the drawn text, as read back from the instance buffer. Glyphs are in visual
+/// order, `char_offset` is the logical position of the first character they were shaped from.
+#[derive(Clone, Copy, Debug)]
+pub struct TextGlyph {
+    pub pos: DVec2,
+    pub advance: f64,
+    pub font_size: f64,
+    pub char_offset: usize,
+    /// The number of characters the glyph was shaped from, more than one for a ligature
+    pub char_len: usize,
+    pub rtl: bool,
+}
+
+fn glyphs_cursor_pos(glyphs: &[TextGlyph], pos: f32, index: usize) -> Option<DVec2> {
+    // the glyph that was shaped from the character at index, or the logically last one
+    // when index is past the end
+    let glyph = glyphs.iter()
+        .filter( | glyph | glyph.char_offset <= index)
+        .max_by_key( | glyph | glyph.char_offset)
+        .or_else( || glyphs.iter().min_by_key( | glyph | glyph.char_offset)) ?;
+    
+    // the characters of a ligature share its advance
+    let t = if index >= glyph.char_offset + glyph.char_len {
+        1.0
+    }
+    else {
+        (index.saturating_sub(glyph.char_offset) as f64 + pos as f64) / glyph.char_len.max(1) as f64
+    };
+    let x = if glyph.rtl {
+        glyph.pos.x + glyph.advance * (1.0 - t)
+    }
+    else {
+        glyph.pos.x + glyph.advance * t
+    };
+    Some(dvec2(x, glyph.pos.y))
+}
+
+fn glyphs_visual_cursor_step(glyphs: &[TextGlyph], index: usize, left: bool) -> Option<usize> {
+    let cursor = glyphs_cursor_pos(glyphs, 0.0, index) ?;
+    
+    let mut best: Option<(f64, usize)> = None;
+    for glyph in glyphs {
+        if (glyph.pos.y - cursor.y).abs() > 0.5 {
+            continue;
+        }
+        // both edges of a glyph are cursor stops, for the logical offsets on either side of it.
+        // At a run boundary such an offset is drawn at the edge of the glyph of the other run,
+        // so the stops are ranked by where the cursor ends up
+        for offset in [glyph.char_offset, glyph.char_offset + glyph.char_len] {
+            let pos = if let Some(pos) = glyphs_cursor_pos(glyphs, 0.0, offset) {pos} else {continue};
+            if (pos.y - cursor.y).abs() > 0.5 {
+                continue;
+            }
+            let ahead = if left {pos.x < cursor.x - 0.5} else {pos.x > cursor.x + 0.5};
+            let closer = if let Some((best_x, _)) = best {if left {pos.x > best_x} else {pos.x < best_x}} else {true};
+            if ahead && closer && offset != index {
+                best = Some((pos.x, offset));
+            }
+        }
+    }
+    best.map( | (_, offset) | offset)
+}
+
 struct WordIterator<'a> {
     char_iter: Option<std::str::CharIndices<'a >>,
     eval_width: f64,
@@ -96,7 +236,8 @@ struct WordIterator<'a> {
     last_is_whitespace: bool,
     last_char: char,
     last_index: usize,
-    font_size_total: f64,
+    font_size: f64,
+    font_scale: f64,
 }
 
 struct WordIteratorItem {
@@ -107,7 +248,7 @@ struct WordIteratorItem {
 }
 
 impl<'a> WordIterator<'a> {
-    fn new(char_iter: std::str::CharIndices<'a>, eval_width: f64, font_size_total: f64) -> Self {
+    fn new(char_iter: std::str::CharIndices<'a>, eval_width: f64, font_size: f64, font_scale: f64) -> Self {
         Self {
             eval_width,
             char_iter: Some(char_iter),
@@ -116,10 +257,11 @@ impl<'a> WordIterator<'a> {
             word_start: 0,
             last_char: '\0',
             last_index: 0,
-            font_size_total
+            font_size,
+            font_scale
         }
     }
-    fn next_word(&mut self, font: &mut CxFont) -> Option<WordIteratorItem> {
+    fn next_word(&mut self, fonts_atlas: &mut CxFontsAtlas, font_ids: &[usize]) -> Option<WordIteratorItem> {
         if let Some(char_iter) = &mut self.char_iter {
             while let Some((i, c)) = char_iter.next() {
                 self.last_index = i;
@@ -131,9 +273,7 @@ impl<'a> WordIterator<'a> {
                     with_newline: false
                 };
                 
-                let adv = if let Some(glyph) = font.get_glyph(c) {
-                    glyph.horizontal_metrics.advance_width * self.font_size_total
-                }else {0.0};
+                let adv = fonts_atlas.char_advance(font_ids, c, self.font_size) * self.font_scale;
                 
                 if c == '\r' {
                     continue;
@@ -202,6 +342,7 @@ pub struct DrawText {
     #[live] pub geometry: GeometryQuad2D,
     #[live] pub text_style: TextStyle,
     #[live] pub wrap: TextWrap,
+    #[live] pub direction: TextDirection,
     #[live(1.0)] pub font_scale: f64,
     #[live(1.0)] pub draw_depth: f32,
     
@@ -217,6 +358,9 @@ pub struct DrawText {
     #[calc] pub delta: Vec2,
     #[calc] pub font_size: f32,
     #[calc] pub advance: f32,
+    #[calc] pub char_offset: f32,
+    #[calc] pub char_len: f32,
+    #[calc] pub rtl: f32,
 }
 
 impl LiveHook for DrawText {
@@ -231,21 +375,24 @@ impl LiveHook for DrawText {
 impl DrawText {
     
     pub fn draw(&mut self, cx: &mut Cx2d, pos: DVec2, val: &str) {
-        self.draw_inner(cx, pos, val, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
+        let level = self.paragraph_level(val);
+        self.draw_inner(cx, pos, val, 0, level, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
         if self.many_instances.is_some() {
             self.end_many_instances(cx)
         }
     }
     
     pub fn draw_rel(&mut self, cx: &mut Cx2d, pos: DVec2, val: &str) {
-        self.draw_inner(cx, pos + cx.turtle().origin(), val, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
+        let level = self.paragraph_level(val);
+        self.draw_inner(cx, pos + cx.turtle().origin(), val, 0, level, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
         if self.many_instances.is_some() {
             self.end_many_instances(cx)
         }
     }
     
     pub fn draw_abs(&mut self, cx: &mut Cx2d, pos: DVec2, val: &str) {
-        self.draw_inner(cx, pos, val, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
+        let level = self.paragraph_level(val);
+        self.draw_inner(cx, pos, val, 0, level, &mut *cx.fonts_atlas_rc.clone().0.borrow_mut());
         if self.many_instances.is_some() {
             self.end_many_instances(cx)
         }
@@ -280,7 +427,18 @@ impl DrawText {
         self.draw_vars.user_uniforms[1] = self.text_style.curve;
     }
     
-    fn draw_inner(&mut self, cx: &mut Cx2d, pos: DVec2, chunk: &str, fonts_atlas: &mut CxFontsAtlas) {
+    /// The embedding level of a paragraph, from `direction` or from its first strong character
+    pub fn paragraph_level(&self, paragraph: &str) -> unicode_bidi::Level {
+        match self.direction {
+            TextDirection::Ltr => unicode_bidi::Level::ltr(),
+            TextDirection::Rtl => unicode_bidi::Level::rtl(),
+            TextDirection::Auto => first_strong_level(paragraph).unwrap_or_else(unicode_bidi::Level::ltr)
+        }
+    }
+    
+    // char_offset is the logical offset of chunk in the text the caller is drawing, so the
+    // instances can be mapped back to it by the cursor and selection queries
+    pub(crate) fn draw_inner(&mut self, cx: &mut Cx2d, pos: DVec2, chunk: &str, char_offset: usize, level: unicode_bidi::Level, fonts_atlas: &mut CxFontsAtlas) {
         if !self.draw_vars.can_instance()
             || pos.x.is_nan()
             || pos.y.is_nan()
@@ -289,9 +447,11 @@ impl DrawText {
         }
         //self.draw_clip = cx.turtle().draw_clip().into();
         //let in_many = self.many_instances.is_some();
-        let font_id = self.text_style.font.font_id.unwrap();
+        let font_ids: Vec<usize> = self.text_style.font_ids().into_iter()
+            .filter( | font_id | fonts_atlas.fonts[*font_id].is_some())
+            .collect();
         
-        if fonts_atlas.fonts[font_id].is_none() {
+        if font_ids.is_empty() {
             return
         }
         
@@ -306,20 +466,8 @@ impl DrawText {
             self.begin_many_instances_internal(cx, fonts_atlas);
         }
         
-        let cxfont = fonts_atlas.fonts[font_id].as_mut().unwrap();
         let dpi_factor = cx.current_dpi_factor();
         
-        let atlas_page_id = cxfont.get_atlas_page_id(dpi_factor, self.text_style.font_size);
-        
-        let font = &mut cxfont.ttf_font;
-        let owned_font_face = &cxfont.owned_font_face;
-        
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * font.units_per_em);
-        let font_size_pixels = font_size_logical * dpi_factor;
-        
-        let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
-        
-        let mi = if let Some(mi) = &mut self.many_instances {mi} else {return};
         let zbias_step = 0.00001;
         let mut char_depth = self.draw_depth;
         
@@ -329,108 +477,136 @@ impl DrawText {
         // (see http://www.unicode.org/reports/tr9/#Basic_Display_Algorithm),
         // as implemented by `unicode_bidi`, to slice the text into substrings
         // that can be individually shaped, then assembled visually.
-        let bidi_info = unicode_bidi::BidiInfo::new(chunk, None);
+        let bidi_info = unicode_bidi::BidiInfo::new(chunk, Some(level));
+        let mut char_offsets = CharOffsets::new(chunk, char_offset);
         
-        // NOTE(eddyb) the caller of `draw_inner` has already processed the text,
-        // such that `chunk` won't contain e.g. any `\n`.
-        if bidi_info.paragraphs.len() == 1 {
-            let runs_with_level_and_range = {
-                let para = &bidi_info.paragraphs[0];
-                // Split `chunk` into "runs" (that differ in their LTR/RTL "level").
-                let (adjusted_levels, runs) = bidi_info.visual_runs(para, para.range.clone());
-                runs.into_iter().map(move | run_range | (adjusted_levels[run_range.start], run_range))
-            };
+        for para in &bidi_info.paragraphs {
+            // Split the paragraph into "runs" (that differ in their LTR/RTL "level"), in visual order.
+            let (adjusted_levels, runs) = bidi_info.visual_runs(para, para.range.clone());
             
-            for (run_level, run_range) in runs_with_level_and_range {
+            for run_range in runs {
+                let run_level = adjusted_levels[run_range.start];
                 // FIXME(eddyb) UBA/`unicode_bidi` only offers a LTR/RTL distinction,
                 // even if `rustybuzz` has vertical `Direction`s as well.
-                let (glyph_ids, new_rustybuzz_buffer) = cxfont
-                    .shape_cache
-                    .get_or_compute_glyph_ids(
-                    (
-                            if run_level.is_rtl() {
-                                rustybuzz::Direction::RightToLeft
-                            } else {
-                                rustybuzz::Direction::LeftToRight
-                            },
-                            &bidi_info.text[run_range]
-                        ),
-                        rustybuzz_buffer,
-                        owned_font_face
-                    );
-                rustybuzz_buffer = new_rustybuzz_buffer;
-                for &glyph_id in glyph_ids {
-                    let glyph = owned_font_face.with_ref(|face| font.get_glyph_by_id(face, glyph_id).unwrap());
-                    
-                    let advance = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                let direction = if run_level.is_rtl() {
+                    rustybuzz::Direction::RightToLeft
+                } else {
+                    rustybuzz::Direction::LeftToRight
+                };
+                // Each bidi run is split further by which font in the fallback chain covers
+                // its characters. Those font runs are in logical order, so a right-to-left
+                // run has to emit them back to front.
+                let run_start = run_range.start;
+                let run_text = &bidi_info.text[run_range];
+                let mut font_runs = fonts_atlas.font_runs(&font_ids, run_text);
+                if run_level.is_rtl() {
+                    font_runs.reverse();
+                }
+                
+                for (font_id, font_run_range) in font_runs {
+                    let cxfont = fonts_atlas.fonts[font_id].as_mut().unwrap();
+                    let atlas_page_id = cxfont.get_atlas_page_id(dpi_factor, self.text_style.font_size);
                     
-                    // snap width/height to pixel granularity
-                    let w = ((glyph.bounds.p_max.x - glyph.bounds.p_min.x) * font_size_pixels).ceil() + 1.0;
-                    let h = ((glyph.bounds.p_max.y - glyph.bounds.p_min.y) * font_size_pixels).ceil() + 1.0;
+                    let font = &mut cxfont.ttf_font;
+                    let owned_font_face = &cxfont.owned_font_face;
                     
-                    // this one needs pixel snapping
-                    let min_pos_x = walk_x + font_size_logical * glyph.bounds.p_min.x;
-                    let min_pos_y = pos.y - font_size_logical * glyph.bounds.p_min.y + self.text_style.font_size * self.text_style.top_drop;
+                    let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * font.units_per_em);
+                    let font_size_pixels = font_size_logical * dpi_factor;
                     
-                    // compute subpixel shift
-                    let subpixel_x_fract = min_pos_x - (min_pos_x * dpi_factor).floor() / dpi_factor;
-                    let subpixel_y_fract = min_pos_y - (min_pos_y * dpi_factor).floor() / dpi_factor;
-                    // scale and snap it
-                    // only use a subpixel id for small fonts
-                    let subpixel_id = if self.text_style.font_size>32.0 {
-                        0
-                    }
-                    else { // subtle 64 index subpixel id
-                        ((subpixel_y_fract * dpi_factor * 7.0) as usize) << 3 |
-                        (subpixel_x_fract * dpi_factor * 7.0) as usize
-                    };
+                    let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
                     
-                    let subpixel_map = if let Some(tc) = atlas_page.atlas_glyphs.get_mut(&glyph_id){
-                        tc
-                    }
-                    else{
-                        atlas_page.atlas_glyphs.insert(glyph_id, [None; crate::font_atlas::ATLAS_SUBPIXEL_SLOTS]);
-                        atlas_page.atlas_glyphs.get_mut(&glyph_id).unwrap()
-                    };
+                    let mi = if let Some(mi) = &mut self.many_instances {mi} else {return};
                     
-                    let tc = if let Some(tc) = &subpixel_map[subpixel_id]{
-                        tc
-                    }
-                    else {
-                        // see if we can fit it
-                        // allocate slot
-                        fonts_atlas.alloc.todo.push(CxFontsAtlasTodo {
-                            subpixel_x_fract,
-                            subpixel_y_fract,
-                            font_id,
-                            atlas_page_id,
-                            glyph_id,
-                            subpixel_id
-                        });
+                    let font_run_start = run_start + font_run_range.start;
+                    let font_run_end = font_run_range.len();
+                    let (glyphs, new_rustybuzz_buffer) = cxfont
+                        .shape_cache
+                        .get_or_compute_glyphs(
+                        (direction, &run_text[font_run_range]),
+                        rustybuzz_buffer,
+                        owned_font_face
+                    );
+                    rustybuzz_buffer = new_rustybuzz_buffer;
+                    for (glyph_index, shaped) in glyphs.iter().enumerate() {
+                        let glyph_id = shaped.glyph_id;
+                        let cluster_end = shaped_cluster_end(glyphs, glyph_index, run_level.is_rtl(), font_run_end);
+                        let glyph = owned_font_face.with_ref(|face| font.get_glyph_by_id(face, glyph_id).unwrap());
                         
-                        subpixel_map[subpixel_id] = Some(
-                            fonts_atlas.alloc.alloc_atlas_glyph(w, h)
-                        );
-                        subpixel_map[subpixel_id].as_ref().unwrap()
-                    };
-                    
-                    let delta_x = font_size_logical * self.font_scale * glyph.bounds.p_min.x - subpixel_x_fract;
-                    let delta_y = -font_size_logical * self.font_scale * glyph.bounds.p_min.y + self.text_style.font_size * self.font_scale * self.text_style.top_drop - subpixel_y_fract;
-                    // give the callback a chance to do things
-                    //et scaled_min_pos_x = walk_x + delta_x;
-                    //let scaled_min_pos_y = pos.y - delta_y;
-                    self.font_t1 = tc.t1;
-                    self.font_t2 = tc.t2;
-                    self.rect_pos = dvec2(walk_x + delta_x, pos.y + delta_y).into();
-                    self.rect_size = dvec2(w * self.font_scale / dpi_factor, h * self.font_scale / dpi_factor).into();
-                    self.char_depth = char_depth;
-                    self.delta.x = delta_x as f32;
-                    self.delta.y = delta_y as f32;
-                    self.font_size = self.text_style.font_size as f32;
-                    self.advance = advance as f32; //char_offset as f32;
-                    char_depth += zbias_step;
-                    mi.instances.extend_from_slice(self.draw_vars.as_slice());
-                    walk_x += advance;
+                        let advance = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                        
+                        // snap width/height to pixel granularity
+                        let w = ((glyph.bounds.p_max.x - glyph.bounds.p_min.x) * font_size_pixels).ceil() + 1.0;
+                        let h = ((glyph.bounds.p_max.y - glyph.bounds.p_min.y) * font_size_pixels).ceil() + 1.0;
+                        
+                        // this one needs pixel snapping
+                        let min_pos_x = walk_x + font_size_logical * glyph.bounds.p_min.x;
+                        let min_pos_y = pos.y - font_size_logical * glyph.bounds.p_min.y + self.text_style.font_size * self.text_style.top_drop;
+                        
+                        // compute subpixel shift
+                        let subpixel_x_fract = min_pos_x - (min_pos_x * dpi_factor).floor() / dpi_factor;
+                        let subpixel_y_fract = min_pos_y - (min_pos_y * dpi_factor).floor() / dpi_factor;
+                        // scale and snap it
+                        // only use a subpixel id for small fonts
+                        let subpixel_id = if self.text_style.font_size>32.0 {
+                            0
+                        }
+                        else { // subtle 64 index subpixel id
+                            ((subpixel_y_fract * dpi_factor * 7.0) as usize) << 3 |
+                            (subpixel_x_fract * dpi_factor * 7.0) as usize
+                        };
+                        
+                        let subpixel_map = if let Some(tc) = atlas_page.atlas_glyphs.get_mut(&glyph_id){
+                            tc
+                        }
+                        else{
+                            atlas_page.atlas_glyphs.insert(glyph_id, [None; crate::font_atlas::ATLAS_SUBPIXEL_SLOTS]);
+                            atlas_page.atlas_glyphs.get_mut(&glyph_id).unwrap()
+                        };
+                        
+                        let tc = if let Some(tc) = &subpixel_map[subpixel_id]{
+                            tc
+                        }
+                        else {
+                            // see if we can fit it
+                            // allocate slot
+                            fonts_atlas.alloc.todo.push(CxFontsAtlasTodo {
+                                subpixel_x_fract,
+                                subpixel_y_fract,
+                                font_id,
+                                atlas_page_id,
+                                glyph_id,
+                                subpixel_id
+                            });
+                            
+                            subpixel_map[subpixel_id] = Some(
+                                fonts_atlas.alloc.alloc_atlas_glyph(w, h)
+                            );
+                            subpixel_map[subpixel_id].as_ref().unwrap()
+                        };
+                        
+                        let delta_x = font_size_logical * self.font_scale * glyph.bounds.p_min.x - subpixel_x_fract;
+                        let delta_y = -font_size_logical * self.font_scale * glyph.bounds.p_min.y + self.text_style.font_size * self.font_scale * self.text_style.top_drop - subpixel_y_fract;
+                        // give the callback a chance to do things
+                        //et scaled_min_pos_x = walk_x + delta_x;
+                        //let scaled_min_pos_y = pos.y - delta_y;
+                        self.font_t1 = tc.t1;
+                        self.font_t2 = tc.t2;
+                        self.rect_pos = dvec2(walk_x + delta_x, pos.y + delta_y).into();
+                        self.rect_size = dvec2(w * self.font_scale / dpi_factor, h * self.font_scale / dpi_factor).into();
+                        self.char_depth = char_depth;
+                        self.delta.x = delta_x as f32;
+                        self.delta.y = delta_y as f32;
+                        self.font_size = self.text_style.font_size as f32;
+                        self.advance = advance as f32;
+                        let char_start = char_offsets.get(font_run_start + shaped.cluster);
+                        let char_end = char_offsets.get(font_run_start + cluster_end);
+                        self.char_offset = char_start as f32;
+                        self.char_len = (char_end - char_start) as f32;
+                        self.rtl = if run_level.is_rtl() {1.0} else {0.0};
+                        char_depth += zbias_step;
+                        mi.instances.extend_from_slice(self.draw_vars.as_slice());
+                        walk_x += advance;
+                    }
                 }
             }
         }
@@ -442,35 +618,32 @@ impl DrawText {
     
     fn compute_geom_inner(&self, cx: &Cx2d, walk: Walk, text: &str, fonts_atlas: &mut CxFontsAtlas) -> Option<TextGeom> {
         // we include the align factor and the width/height
-        let font_id = self.text_style.font.font_id.unwrap();
+        let font_ids: Vec<usize> = self.text_style.font_ids().into_iter()
+            .filter( | font_id | fonts_atlas.fonts[*font_id].is_some())
+            .collect();
         
-        if fonts_atlas.fonts[font_id].is_none() {
+        if font_ids.is_empty() {
             return None
         }
         
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
+        let font_size = self.text_style.font_size;
         let line_height = self.text_style.font_size * self.text_style.height_factor * self.font_scale;
-        let eval_width = cx.turtle().eval_width(walk.width, walk.margin, cx.turtle().layout().flow);
-        let eval_height = cx.turtle().eval_height(walk.height, walk.margin, cx.turtle().layout().flow);
+        let eval_width = cx.turtle().eval_width(&walk, cx.turtle().layout().flow);
+        let eval_height = cx.turtle().eval_height(&walk, cx.turtle().layout().flow);
         
         match if walk.width.is_fit() {&TextWrap::Line}else {&self.wrap} {
             TextWrap::Ellipsis => {
-                let ellip_width = if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph('.') {
-                    glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale
-                }
-                else {
-                    0.0
-                };
+                let ellip_width = fonts_atlas.char_advance(&font_ids, '.', font_size) * self.font_scale;
                 
                 let mut measured_width = 0.0;
                 let mut ellip_pt = None;
-                for (i, c) in text.chars().enumerate() {
+                for (i, c) in text.char_indices() {
                     
                     if measured_width + ellip_width * 3.0 < eval_width {
                         ellip_pt = Some((i, measured_width, 3));
                     }
-                    if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph(c) {
-                        let adv = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    let adv = fonts_atlas.char_advance(&font_ids, c, font_size) * self.font_scale;
+                    if adv > 0.0 {
                         // ok so now what.
                         if measured_width + adv >= eval_width { // we have to drop back to ellip_pt
                             // if we don't have an ellip_pt, set it to 0
@@ -506,8 +679,8 @@ impl DrawText {
                 let mut measured_width = 0.0;
                 let mut measured_height = line_height;
                 
-                let mut iter = WordIterator::new(text.char_indices(), eval_width, font_size_logical * self.font_scale);
-                while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+                let mut iter = WordIterator::new(text.char_indices(), eval_width, font_size, self.font_scale);
+                while let Some(word) = iter.next_word(fonts_atlas, &font_ids) {
                     if measured_width + word.width >= eval_width {
                         measured_height += line_height * self.text_style.line_spacing;
                         measured_width = word.width;
@@ -539,10 +712,7 @@ impl DrawText {
                     if c == '\n' {
                         measured_height += line_height * self.text_style.line_spacing;
                     }
-                    if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph(c) {
-                        let adv = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
-                        measured_width += adv;
-                    }
+                    measured_width += fonts_atlas.char_advance(&font_ids, c, font_size) * self.font_scale;
                     if measured_width > max_width {
                         max_width = measured_width;
                     }
@@ -560,10 +730,10 @@ impl DrawText {
     
     
     pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk, align: Align, text: &str) {
-        let font_id = if let Some(font_id) = self.text_style.font.font_id{font_id}else{
+        if self.text_style.font.font_id.is_none() {
             //log!("Draw text without font");
             return
-        };
+        }
         let fonts_atlas_rc = cx.fonts_atlas_rc.clone();
         let mut fonts_atlas = fonts_atlas_rc.0.borrow_mut();
         let fonts_atlas = &mut*fonts_atlas;
@@ -576,6 +746,8 @@ impl DrawText {
         //if !in_many {
         //    self.begin_many_instances_internal(cx, fonts_atlas);
         //}
+        // a right to left layout mirrors how the text is aligned in its rect as well
+        let align = if cx.turtle().layout().rtl {Align {x: 1.0 - align.x, y: align.y}} else {align};
         if let Some(geom) = self.compute_geom_inner(cx, walk, text, fonts_atlas) {
             let height = if walk.height.is_fit() {
                 geom.measured_height
@@ -586,23 +758,31 @@ impl DrawText {
             
             match if walk.width.is_fit() {&TextWrap::Line}else {&self.wrap} {
                 TextWrap::Ellipsis => {
+                    let level = self.paragraph_level(text);
                     // otherwise we should check the ellipsis
                     if let Some((ellip, at_x, dots)) = geom.ellip_pt {
                         // ok so how do we draw this
                         let rect = cx.walk_turtle(Walk {
-                            abs_pos: walk.abs_pos,
-                            margin: walk.margin,
                             width: Size::Fixed(geom.eval_width),
-                            height: Size::Fixed(height)
+                            height: Size::Fixed(height),
+                            ..walk
                         });
-                        
-                        self.draw_inner(cx, rect.pos + dvec2(0.0, y_align), &text[0..ellip], fonts_atlas);
-                        self.draw_inner(cx, rect.pos + dvec2(at_x, y_align), &"..."[0..dots], fonts_atlas);
+                        let ellip_offset = text[0..ellip].chars().count();
+                        if level.is_rtl() {
+                            // a right to left paragraph is cut off on the left
+                            let font_ids = self.text_style.font_ids();
+                            let dots_width = fonts_atlas.char_advance(&font_ids, '.', self.text_style.font_size) * self.font_scale * dots as f64;
+                            let x = geom.eval_width - at_x;
+                            self.draw_inner(cx, rect.pos + dvec2(x, y_align), &text[0..ellip], 0, level, fonts_atlas);
+                            self.draw_inner(cx, rect.pos + dvec2(x - dots_width, y_align), &"..."[0..dots], ellip_offset, level, fonts_atlas);
+                        }
+                        else {
+                            self.draw_inner(cx, rect.pos + dvec2(0.0, y_align), &text[0..ellip], 0, level, fonts_atlas);
+                            self.draw_inner(cx, rect.pos + dvec2(at_x, y_align), &"..."[0..dots], ellip_offset, level, fonts_atlas);
+                        }
                     }
                     else { // we might have space to h-align
                         let rect = cx.walk_turtle(Walk {
-                            abs_pos: walk.abs_pos,
-                            margin: walk.margin,
                             width: Size::Fixed(geom.eval_width),
                             height: Size::Fixed(
                                 if walk.height.is_fit() {
@@ -610,52 +790,96 @@ impl DrawText {
                                 } else {
                                     geom.eval_height
                                 }
-                            )
+                            ),
+                            ..walk
                         });
                         let x_align = (geom.eval_width - geom.measured_width) * align.x;
-                        self.draw_inner(cx, rect.pos + dvec2(x_align, y_align), text, fonts_atlas);
+                        self.draw_inner(cx, rect.pos + dvec2(x_align, y_align), text, 0, level, fonts_atlas);
                     }
                 }
                 TextWrap::Word => {
-                    let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
+                    let font_ids = self.text_style.font_ids();
                     let line_height = self.text_style.font_size * self.text_style.height_factor * self.font_scale;
                     
                     let rect = cx.walk_turtle(Walk {
-                        abs_pos: walk.abs_pos,
-                        margin: walk.margin,
                         width: Size::Fixed(geom.eval_width),
-                        height: Size::Fixed(geom.measured_height)
+                        height: Size::Fixed(geom.measured_height),
+                        ..walk
                     });
-                    let mut pos = dvec2(0.0, 0.0);
+                    // break the text into lines first, so every line is drawn as a whole and its
+                    // bidi runs can be reordered across the words
+                    let mut lines = Vec::new();
+                    let mut line_start = None;
+                    let mut line_end = 0;
+                    let mut line_width = 0.0;
+                    let mut ypos = 0.0;
                     
-                    let mut iter = WordIterator::new(text.char_indices(), geom.eval_width, font_size_logical * self.font_scale);
-                    while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
-                        if pos.x + word.width >= geom.eval_width {
-                            pos.y += line_height * self.text_style.line_spacing;
-                            pos.x = 0.0;
+                    let mut iter = WordIterator::new(text.char_indices(), geom.eval_width, self.text_style.font_size, self.font_scale);
+                    while let Some(word) = iter.next_word(fonts_atlas, &font_ids) {
+                        if line_width + word.width >= geom.eval_width {
+                            if let Some(start) = line_start.take() {
+                                lines.push((start, line_end, line_width, ypos));
+                            }
+                            ypos += line_height * self.text_style.line_spacing;
+                            line_width = 0.0;
                         }
-                        self.draw_inner(cx, rect.pos + pos, &text[word.start..word.end], fonts_atlas);
-                        pos.x += word.width;
+                        line_start.get_or_insert(word.start);
+                        line_end = word.end;
+                        line_width += word.width;
                         
                         if word.with_newline {
-                            pos.y += line_height * self.text_style.line_spacing;
-                            pos.x = 0.0;
+                            if let Some(start) = line_start.take() {
+                                lines.push((start, line_end, line_width, ypos));
+                            }
+                            ypos += line_height * self.text_style.line_spacing;
+                            line_width = 0.0;
                         }
                     }
+                    if let Some(start) = line_start {
+                        lines.push((start, line_end, line_width, ypos));
+                    }
+                    
+                    let mut char_offsets = CharOffsets::new(text, 0);
+                    let mut level = self.paragraph_level(text.split('\n').next().unwrap_or(""));
+                    for (start, end, width, ypos) in lines {
+                        // the line after a newline starts with it, and with a new paragraph
+                        let start = if text[start..end].starts_with('\n') {
+                            level = self.paragraph_level(text[start + 1..].split('\n').next().unwrap_or(""));
+                            start + 1
+                        }
+                        else {
+                            start
+                        };
+                        // right to left paragraphs line up on the right
+                        let xpos = if level.is_rtl() {geom.eval_width - width} else {0.0};
+                        let char_offset = char_offsets.get(start);
+                        self.draw_inner(cx, rect.pos + dvec2(xpos, ypos), &text[start..end], char_offset, level, fonts_atlas);
+                    }
                 }
                 TextWrap::Line => {
+                    let font_ids = self.text_style.font_ids();
                     let line_height = self.text_style.font_size * self.text_style.height_factor * self.font_scale;
                     // lets just output it and walk it
                     let rect = cx.walk_turtle(Walk {
-                        abs_pos: walk.abs_pos,
-                        margin: walk.margin,
                         width: Size::Fixed(geom.measured_width),
-                        height: Size::Fixed(height)
+                        height: Size::Fixed(height),
+                        ..walk
                     });
                     // lets do our y alignment
                     let mut ypos = 0.0;
+                    let mut char_offset = 0;
                     for line in text.split('\n') {
-                        self.draw_inner(cx, rect.pos + dvec2(0.0, y_align + ypos), line, fonts_atlas);
+                        let level = self.paragraph_level(line);
+                        // right to left paragraphs line up on the right
+                        let xpos = if level.is_rtl() {
+                            let width: f64 = line.chars().map( | c | fonts_atlas.char_advance(&font_ids, c, self.text_style.font_size)).sum();
+                            geom.measured_width - width * self.font_scale
+                        }
+                        else {
+                            0.0
+                        };
+                        self.draw_inner(cx, rect.pos + dvec2(xpos, y_align + ypos), line, char_offset, level, fonts_atlas);
+                        char_offset += line.chars().count() + 1;
                         ypos += line_height * self.text_style.line_spacing;
                     }
                     
@@ -667,124 +891,116 @@ impl DrawText {
         }
     }
     
-    pub fn closest_offset(&self, cx: &Cx, pos: DVec2) -> Option<usize> {
+    /// The drawn glyphs in visual order, read back from the instances
+    pub fn get_glyphs(&self, cx: &Cx) -> Vec<TextGlyph> {
         let area = &self.draw_vars.area;
         
         if !area.is_valid(cx) {
-            return None
+            return Vec::new()
         }
-
-        let line_spacing = self.get_line_spacing();
+        
         let rect_pos = area.get_read_ref(cx, live_id!(rect_pos), ShaderTy::Vec2).unwrap();
         let delta = area.get_read_ref(cx, live_id!(delta), ShaderTy::Vec2).unwrap();
         let advance = area.get_read_ref(cx, live_id!(advance), ShaderTy::Float).unwrap();
-
-        let mut last_y = None;
-        for i in 0..rect_pos.repeat {
+        let font_size = area.get_read_ref(cx, live_id!(font_size), ShaderTy::Float).unwrap();
+        let char_offset = area.get_read_ref(cx, live_id!(char_offset), ShaderTy::Float).unwrap();
+        let char_len = area.get_read_ref(cx, live_id!(char_len), ShaderTy::Float).unwrap();
+        let rtl = area.get_read_ref(cx, live_id!(rtl), ShaderTy::Float).unwrap();
+        
+        (0..rect_pos.repeat).map( | i | {
             let index = rect_pos.stride * i;
-            let x = rect_pos.buffer[index + 0] as f64 - delta.buffer[index + 0] as f64;
-            let y = rect_pos.buffer[index + 1] - delta.buffer[index + 1];
-            if last_y.is_none() {last_y = Some(y)}
-            let advance = advance.buffer[index + 0] as f64;
-            if i > 0 && y > last_y.unwrap() && pos.y < last_y.unwrap() as f64 + line_spacing as f64 {
-                return Some(i - 1)
-            }
-            if pos.x < x + advance * 0.5 && pos.y < y as f64 + line_spacing as f64 {
-                return Some(i)
+            TextGlyph {
+                pos: dvec2(
+                    (rect_pos.buffer[index + 0] - delta.buffer[index + 0]) as f64,
+                    (rect_pos.buffer[index + 1] - delta.buffer[index + 1]) as f64
+                ),
+                advance: advance.buffer[index + 0] as f64,
+                font_size: font_size.buffer[index + 0] as f64,
+                char_offset: char_offset.buffer[index + 0] as usize,
+                char_len: char_len.buffer[index + 0] as usize,
+                rtl: rtl.buffer[index + 0] > 0.5
             }
-            last_y = Some(y)
-        }
-        return Some(rect_pos.repeat);
-        
+        }).collect()
     }
     
-    pub fn get_selection_rects(&self, cx: &Cx, start: usize, end: usize, shift: DVec2, pad: DVec2) -> Vec<Rect> {
+    pub fn closest_offset(&self, cx: &Cx, pos: DVec2) -> Option<usize> {
         let area = &self.draw_vars.area;
         
         if !area.is_valid(cx) {
-            return Vec::new();
+            return None
         }
         
-        let rect_pos = area.get_read_ref(cx, live_id!(rect_pos), ShaderTy::Vec2).unwrap();
-        let delta = area.get_read_ref(cx, live_id!(delta), ShaderTy::Vec2).unwrap();
-        let advance = area.get_read_ref(cx, live_id!(advance), ShaderTy::Float).unwrap();
+        let line_spacing = self.get_line_spacing();
+        let glyphs = self.get_glyphs(cx);
         
-        if rect_pos.repeat == 0 || start >= rect_pos.repeat{
-            return Vec::new();
+        let mut last: Option<&TextGlyph> = None;
+        for glyph in &glyphs {
+            if let Some(last) = last {
+                if glyph.pos.y > last.pos.y && pos.y < last.pos.y + line_spacing {
+                    return Some(last.char_offset)
+                }
+            }
+            if pos.x < glyph.pos.x + glyph.advance * 0.5 && pos.y < glyph.pos.y + line_spacing {
+                // the left half of a right to left glyph comes after it
+                return Some(if glyph.rtl {glyph.char_offset + glyph.char_len} else {glyph.char_offset})
+            }
+            last = Some(glyph);
         }
-        // alright now we go and walk from start to end and collect our selection rects
-        
-        let index = start * rect_pos.stride;
-        let start_x = rect_pos.buffer[index + 0] - delta.buffer[index + 0]; // + advance.buffer[index + 0] * pos;
-        let start_y = rect_pos.buffer[index + 1] - delta.buffer[index + 1];
+        Some(match last {
+            Some(last) if last.rtl => last.char_offset,
+            Some(last) => last.char_offset + last.char_len,
+            None => 0
+        })
+    }
+    
+    pub fn get_selection_rects(&self, cx: &Cx, start: usize, end: usize, shift: DVec2, pad: DVec2) -> Vec<Rect> {
         let line_spacing = self.get_line_spacing();
-        let mut last_y = start_y;
-        let mut min_x = start_x;
-        let mut last_x = start_x;
-        let mut last_advance = advance.buffer[index + 0];
+        let glyphs = self.get_glyphs(cx);
+        
+        // selected glyphs that sit next to each other on a line make up one rect, in mixed
+        // direction text a selection can fall apart into several of them on the same line
         let mut out = Vec::new();
-        for index in start..end {
-            if index >= rect_pos.repeat{
-                break;
+        let mut span: Option<(f64, f64, f64)> = None;
+        for glyph in &glyphs {
+            let selected = glyph.char_offset >= start && glyph.char_offset < end;
+            if let Some((min_x, max_x, y)) = span {
+                if !selected || glyph.pos.y > y {
+                    out.push(Rect {
+                        pos: dvec2(min_x, y) + shift,
+                        size: dvec2(max_x - min_x, line_spacing) + pad
+                    });
+                    span = None;
+                }
             }
-            let index = index * rect_pos.stride;
-            let end_x = rect_pos.buffer[index + 0] - delta.buffer[index + 0];
-            let end_y = rect_pos.buffer[index + 1] - delta.buffer[index + 1];
-            last_advance = advance.buffer[index + 0];
-            if end_y > last_y { // emit rect
-                out.push(Rect {
-                    pos: dvec2(min_x as f64, last_y as f64) + shift,
-                    size: dvec2((last_x - min_x + last_advance) as f64, line_spacing) + pad
+            if selected {
+                span = Some(match span {
+                    Some((min_x, _, y)) => (min_x, glyph.pos.x + glyph.advance, y),
+                    None => (glyph.pos.x, glyph.pos.x + glyph.advance, glyph.pos.y)
                 });
-                min_x = end_x;
-                last_y = end_y;
             }
-            last_x = end_x;
         }
-        out.push(Rect {
-            pos: dvec2(min_x as f64, last_y as f64) + shift,
-            size: dvec2((last_x - min_x + last_advance) as f64, line_spacing) + pad
-        });
+        if let Some((min_x, max_x, y)) = span {
+            out.push(Rect {
+                pos: dvec2(min_x, y) + shift,
+                size: dvec2(max_x - min_x, line_spacing) + pad
+            });
+        }
         out
     }
     
-    
     pub fn get_char_count(&self, cx: &Cx) -> usize {
-        let area = &self.draw_vars.area;
-        if !area.is_valid(cx) {
-            return 0
-        }
-        let rect_pos = area.get_read_ref(cx, live_id!(rect_pos), ShaderTy::Vec2).unwrap();
-        rect_pos.repeat
+        self.get_glyphs(cx).iter().map( | glyph | glyph.char_offset + glyph.char_len).max().unwrap_or(0)
     }
     
     pub fn get_cursor_pos(&self, cx: &Cx, pos: f32, index: usize) -> Option<DVec2> {
-        let area = &self.draw_vars.area;
-        
-        if !area.is_valid(cx) {
-            return None
-        }
-        
-        let rect_pos = area.get_read_ref(cx, live_id!(rect_pos), ShaderTy::Vec2).unwrap();
-        let delta = area.get_read_ref(cx, live_id!(delta), ShaderTy::Vec2).unwrap();
-        let advance = area.get_read_ref(cx, live_id!(advance), ShaderTy::Float).unwrap();
-        
-        if rect_pos.repeat == 0 {
-            return None
-        }
-        if index >= rect_pos.repeat {
-            // lets get the last one and advance
-            let index = (rect_pos.repeat - 1) * rect_pos.stride;
-            let x = rect_pos.buffer[index + 0] - delta.buffer[index + 0] + advance.buffer[index + 0];
-            let y = rect_pos.buffer[index + 1] - delta.buffer[index + 1];
-            Some(dvec2(x as f64, y as f64))
-        }
-        else {
-            let index = index * rect_pos.stride;
-            let x = rect_pos.buffer[index + 0] - delta.buffer[index + 0] + advance.buffer[index + 0] * pos;
-            let y = rect_pos.buffer[index + 1] - delta.buffer[index + 1];
-            Some(dvec2(x as f64, y as f64))
-        }
+        glyphs_cursor_pos(&self.get_glyphs(cx), pos, index)
+    }
+    
+    /// Where the cursor at index goes when it moves one step to the left or to the right on
+    /// its line, which in mixed direction text is not the logical neighbour. Returns None at
+    /// the end of the line.
+    pub fn get_visual_cursor_step(&self, cx: &Cx, index: usize, left: bool) -> Option<usize> {
+        glyphs_visual_cursor_step(&self.get_glyphs(cx), index, left)
     }
     
     pub fn get_line_spacing(&self) -> f64 {
@@ -814,4 +1030,77 @@ impl DrawText {
             y: self.text_style.line_spacing
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn glyph(x: f64, char_offset: usize, rtl: bool) -> TextGlyph {
+        TextGlyph {pos: dvec2(x, 0.0), advance: 10.0, font_size: 10.0, char_offset, char_len: 1, rtl}
+    }
+    
+    fn steps(glyphs: &[TextGlyph], mut index: usize, left: bool) -> Vec<usize> {
+        let mut out = vec![index];
+        while let Some(next) = glyphs_visual_cursor_step(glyphs, index, left) {
+            index = next;
+            out.push(index);
+        }
+        out
+    }
+    
+    #[test]
+    fn first_strong_character_sets_the_paragraph_level() {
+        assert_eq!(first_strong_level("abc אבג"), Some(unicode_bidi::Level::ltr()));
+        assert_eq!(first_strong_level("אבג abc"), Some(unicode_bidi::Level::rtl()));
+        assert_eq!(first_strong_level("123, بيت abc"), Some(unicode_bidi::Level::rtl()));
+        assert_eq!(first_strong_level("123 !"), None);
+    }
+    
+    #[test]
+    fn isolates_do_not_set_the_paragraph_level() {
+        // RLI abc PDI, then hebrew
+        assert_eq!(first_strong_level("\u{2067}abc\u{2069} אבג"), Some(unicode_bidi::Level::rtl()));
+        // nested isolates only end at the outer PDI
+        assert_eq!(first_strong_level("\u{2066}\u{2068}א\u{2069}b\u{2069}c"), Some(unicode_bidi::Level::ltr()));
+        // an isolate without its PDI runs to the end of the paragraph
+        assert_eq!(first_strong_level("\u{2068}אבג"), None);
+        // a PDI that matches nothing is ignored
+        assert_eq!(first_strong_level("\u{2069}abc"), Some(unicode_bidi::Level::ltr()));
+    }
+    
+    // "ab" then "CD" right to left then "e", drawn as a b D C e
+    fn ltr_paragraph() -> Vec<TextGlyph> {
+        vec![glyph(0.0, 0, false), glyph(10.0, 1, false), glyph(20.0, 3, true), glyph(30.0, 2, true), glyph(40.0, 4, false)]
+    }
+    
+    // "AB" right to left, then "cd", then "E", drawn as E c d B A
+    fn rtl_paragraph() -> Vec<TextGlyph> {
+        vec![glyph(0.0, 4, true), glyph(10.0, 2, false), glyph(20.0, 3, false), glyph(30.0, 1, true), glyph(40.0, 0, true)]
+    }
+    
+    #[test]
+    fn cursor_positions_in_mixed_paragraphs() {
+        let x = | glyphs: &[TextGlyph], index | glyphs_cursor_pos(glyphs, 0.0, index).unwrap().x;
+        let ltr = ltr_paragraph();
+        assert_eq!((0..=5).map( | i | x(&ltr, i)).collect::<Vec<_>>(), vec![0.0, 10.0, 40.0, 30.0, 40.0, 50.0]);
+        let rtl = rtl_paragraph();
+        assert_eq!((0..=5).map( | i | x(&rtl, i)).collect::<Vec<_>>(), vec![50.0, 40.0, 10.0, 20.0, 10.0, 0.0]);
+    }
+    
+    #[test]
+    fn visual_cursor_steps_cross_run_boundaries() {
+        let ltr = ltr_paragraph();
+        assert_eq!(steps(&ltr, 0, false), vec![0, 1, 3, 2, 5]);
+        assert_eq!(steps(&ltr, 5, true), vec![5, 2, 3, 1, 0]);
+        let rtl = rtl_paragraph();
+        assert_eq!(steps(&rtl, 0, true), vec![0, 1, 3, 4, 5]);
+        assert_eq!(steps(&rtl, 5, false), vec![5, 4, 3, 1, 0]);
+    }
+    
+    #[test]
+    fn visual_cursor_steps_stay_on_their_line() {
+        let glyphs = vec![glyph(0.0, 0, false), glyph(10.0, 1, false), TextGlyph {pos: dvec2(0.0, 20.0), ..glyph(0.0, 2, false)}];
+        assert_eq!(glyphs_visual_cursor_step(&glyphs, 1, false), None);
+        assert_eq!(glyphs_visual_cursor_step(&glyphs, 2, true), None);
+    }
+}
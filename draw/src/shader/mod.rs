@@ -1,8 +1,10 @@
 pub mod draw_color;
 pub mod draw_quad;
 pub mod draw_icon;
+pub mod draw_svg;
 pub mod draw_line;
 //pub mod draw_shape;
 pub mod draw_text;
+pub mod draw_rich_text;
 pub mod std;
 pub mod draw_trapezoid;
@@ -89,6 +89,48 @@ live_design!{
         }
     }
     
+    Yuv = {
+        // the planar yuv texture formats keep their chroma rows below the luma in one single
+        // channel texture, `size` is the width and height of the image in pixels
+        
+        fn to_rgb(y: float, u: float, v: float) -> vec4 { // bt.601 video range
+            let l = 1.164 * (y - 0.0625);
+            return vec4(
+                l + 1.596 * (v - 0.5),
+                l - 0.392 * (u - 0.5) - 0.813 * (v - 0.5),
+                l + 2.017 * (u - 0.5),
+                1.0
+            );
+        }
+        
+        fn sample_nv12(tex: texture2D, pos: vec2, size: vec2) -> vec4 {
+            let height = size.y * 1.5;
+            let p = clamp(floor(pos * size), vec2(0.0, 0.0), size - 1.0);
+            let y = sample2d(tex, vec2(p.x + 0.5, p.y + 0.5) / vec2(size.x, height)).x;
+            // every 2x2 block of pixels shares an interleaved u,v pair
+            let c = floor(p * 0.5);
+            let u = sample2d(tex, vec2(c.x * 2.0 + 0.5, size.y + c.y + 0.5) / vec2(size.x, height)).x;
+            let v = sample2d(tex, vec2(c.x * 2.0 + 1.5, size.y + c.y + 0.5) / vec2(size.x, height)).x;
+            return Yuv::to_rgb(y, u, v);
+        }
+        
+        fn sample_i420_plane(tex: texture2D, i: float, size: vec2) -> float {
+            // rows of the u and v planes are half as wide as the texture so they wrap around
+            let row = floor((i + 0.5) / size.x);
+            return sample2d(tex, vec2(i - row * size.x + 0.5, size.y + row + 0.5) / vec2(size.x, size.y * 1.5)).x;
+        }
+        
+        fn sample_i420(tex: texture2D, pos: vec2, size: vec2) -> vec4 {
+            let p = clamp(floor(pos * size), vec2(0.0, 0.0), size - 1.0);
+            let y = sample2d(tex, vec2(p.x + 0.5, p.y + 0.5) / vec2(size.x, size.y * 1.5)).x;
+            let c = floor(p * 0.5);
+            let i = c.y * size.x * 0.5 + c.x;
+            let u = Yuv::sample_i420_plane(tex, i, size);
+            let v = Yuv::sample_i420_plane(tex, i + size.x * size.y * 0.25, size);
+            return Yuv::to_rgb(y, u, v);
+        }
+    }
+    
     Sdf2d = struct {
         field pos: vec2
         field result: vec4
@@ -14,7 +14,13 @@ pub struct Layout {
     #[live] pub padding: Padding,
     #[live] pub align: Align,
     #[live] pub flow: Flow,
-    #[live] pub spacing: f64
+    #[live] pub spacing: f64,
+    /// The space between the rows of `RightWrap`, the columns of `DownWrap` and the rows of `Grid`
+    #[live] pub line_spacing: f64,
+    #[live] pub grid_columns: GridTracks,
+    #[live] pub grid_rows: GridTracks,
+    /// Lays the children out right to left: the flow and the alignment are mirrored horizontally
+    #[live] pub rtl: bool,
 }
 
 impl Default for Layout{
@@ -26,7 +32,11 @@ impl Default for Layout{
             padding: Padding::default(),
             align: Align{x:0.0,y:0.0},
             flow: Flow::Right,
-            spacing: 0.0
+            spacing: 0.0,
+            line_spacing: 0.0,
+            grid_columns: GridTracks::default(),
+            grid_rows: GridTracks::default(),
+            rtl: false,
         }
     }
 }
@@ -38,6 +48,12 @@ pub struct Walk {
     #[live] pub margin: Margin,
     #[live] pub width: Size,
     #[live] pub height: Size,
+    /// Bounds the evaluated width, whatever `width` is
+    #[live] pub min_width: Option<f64>,
+    #[live] pub max_width: Option<f64>,
+    /// Bounds the evaluated height, whatever `height` is
+    #[live] pub min_height: Option<f64>,
+    #[live] pub max_height: Option<f64>,
 }
 
 #[derive(Clone, Copy, Default, Debug, Live, LiveHook, LiveRegister)]
@@ -76,13 +92,44 @@ pub enum Flow {
     Down,
     //Left,
     //Up,
-    Overlay
+    Overlay,
+    /// Like `Right`, but starts a new row when the next item doesn't fit in the width that is left
+    RightWrap,
+    /// Like `Down`, but starts a new column when the next item doesn't fit in the height that is left
+    DownWrap,
+    /// Places the items cell by cell, row by row, in the tracks of `grid_columns` and `grid_rows`.
+    /// `Fill` items take the size their cell has when they are walked, which for `Fit` tracks is
+    /// the size of the largest item walked into them before.
+    Grid
 }
 
+/// The most tracks `grid_columns` or `grid_rows` can define. Rows past the defined ones are `Fit`.
+pub const GRID_MAX_TRACKS: usize = 16;
+
+/// The size of a column or row of a grid. `Fr` tracks share the space the other tracks leave, like
+/// `fr` in css.
+#[derive(Copy, Clone, Debug, Live)]
+#[live_ignore]
+pub enum GridTrack {
+    #[pick] Fit,
+    #[live(100.0)] Fixed(f64),
+    #[live(1.0)] Fr(f64),
+}
+
+/// The tracks of a grid, set from the dsl as an array like `[Fixed(100), Fr(1.0), Fit]`, or as a
+/// number of equally sized columns or rows like `3`
+#[derive(Copy, Clone, Debug, Default, Live, LiveRegister)]
+#[live_ignore]
+pub struct GridTracks {
+    #[rust] tracks: [GridTrack; GRID_MAX_TRACKS],
+    #[rust] len: usize,
+}
+
+/// `Fill` takes the space that is left; fills that share it get parts in proportion to their weight.
 #[derive(Copy, Clone, Debug, Live)]
 #[live_ignore]
 pub enum Size {
-    #[pick] Fill,
+    #[pick(1.0)] Fill(f64),
     #[live(200.0)] Fixed(f64),
     Fit,
     All
@@ -92,8 +139,7 @@ pub enum Size {
 pub enum DeferWalk{
     Unresolved{
         defer_index: usize,
-        margin: Margin,
-        other_axis: Size,
+        walk: Walk,
         pos: DVec2
     },
     Resolved(Walk)
@@ -113,6 +159,10 @@ pub enum AlignEntry{
 pub struct TurtleWalk {
     align_start: usize,
     defer_index: usize,
+    // the line of a wrapping flow or the cell of a grid the walk went into, none when it was
+    // absolutely positioned
+    slot: Option<usize>,
+    abs: bool,
     rect: Rect,
 }
 
@@ -122,7 +172,8 @@ pub struct Turtle {
     layout: Layout,
     align_start: usize,
     turtle_walks_start: usize,
-    defer_count: usize,
+    // the fills along the flow whose size is only known when the turtle has been walked
+    defer_walks: Vec<Walk>,
     shift: DVec2,
     pos: DVec2,
     origin: DVec2,
@@ -130,7 +181,15 @@ pub struct Turtle {
     height: f64,
     width_used: f64,
     height_used: f64,
-    guard_area: Area
+    guard_area: Area,
+    // the line of a wrapping flow we are on, the walk that started it and its size across the flow
+    wrap_line: usize,
+    wrap_line_start: usize,
+    wrap_line_size: f64,
+    // the cell of a grid we are on, and the largest item seen in each column and row so far
+    grid_cell: usize,
+    grid_columns_used: Vec<f64>,
+    grid_rows_used: Vec<f64>,
 }
 
 impl<'a> Cx2d<'a> {
@@ -151,12 +210,9 @@ impl<'a> Cx2d<'a> {
             return None
         }
         let turtle = self.turtles.last_mut().unwrap();
-        let defer_index = turtle.defer_count;
+        let defer_index = turtle.defer_walks.len();
         let pos = turtle.pos;
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk_size(&walk);
         let margin_size = walk.margin.size();
         match turtle.layout.flow {
             Flow::Right if walk.width.is_fill() => {
@@ -164,11 +220,10 @@ impl<'a> Cx2d<'a> {
                 turtle.pos.x += margin_size.x + spacing.x;
                 turtle.update_width_max(turtle.pos.x, 0.0);
                 turtle.update_height_max(turtle.pos.y, size.y + margin_size.y);
-                turtle.defer_count += 1;
+                turtle.defer_walks.push(walk);
                 Some(DeferWalk::Unresolved{
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.height,
+                    walk,
                     pos: pos + spacing
                 })
             },
@@ -177,11 +232,10 @@ impl<'a> Cx2d<'a> {
                 turtle.pos.y += margin_size.y + spacing.y;
                 turtle.update_width_max(turtle.pos.x, size.x + margin_size.x);
                 turtle.update_height_max(turtle.pos.y, 0.0);
-                turtle.defer_count += 1;
+                turtle.defer_walks.push(walk);
                 Some(DeferWalk::Unresolved {
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.width,
+                    walk,
                     pos: pos + spacing
                 })
             },
@@ -199,7 +253,7 @@ impl<'a> Cx2d<'a> {
             layout,
             align_start: self.align_list.len() - 1,
             turtle_walks_start: self.turtle_walks.len(),
-            defer_count: 0,
+            defer_walks: Vec::new(),
             pos: DVec2 {
                 x: layout.padding.left,
                 y: layout.padding.top
@@ -211,6 +265,12 @@ impl<'a> Cx2d<'a> {
             width_used: layout.padding.left,
             height_used: layout.padding.top,
             guard_area: Area::Empty,
+            wrap_line: 0,
+            wrap_line_start: self.turtle_walks.len(),
+            wrap_line_size: 0.0,
+            grid_cell: 0,
+            grid_columns_used: Vec::new(),
+            grid_rows_used: Vec::new(),
         };
         self.turtles.push(turtle);
     }
@@ -246,8 +306,8 @@ impl<'a> Cx2d<'a> {
                 parent.pos + parent.child_spacing(self.turtle_walks.len()) 
             };
             
-            let w = parent.eval_width(walk.width, walk.margin, parent.layout.flow);
-            let h = parent.eval_height(walk.height, walk.margin, parent.layout.flow);
+            let w = parent.eval_width(&walk, parent.layout.flow);
+            let h = parent.eval_height(&walk, parent.layout.flow);
             
             // figure out new clipping rect
             let (x0, x1) = if layout.clip_x {
@@ -273,8 +333,8 @@ impl<'a> Cx2d<'a> {
         }
         else {
             let o = DVec2 {x: walk.margin.left, y: walk.margin.top};
-            let w = walk.width.fixed_or_nan();
-            let h = walk.height.fixed_or_nan();
+            let w = walk.clamp_width(walk.width.fixed_or_nan());
+            let h = walk.clamp_height(walk.height.fixed_or_nan());
             
             (o, w, h, (dvec2(o.x, o.y), dvec2(o.x + w, o.y + h)))
         };
@@ -284,7 +344,7 @@ impl<'a> Cx2d<'a> {
             layout,
             align_start: self.align_list.len()-1,
             turtle_walks_start: self.turtle_walks.len(),
-            defer_count: 0,
+            defer_walks: Vec::new(),
             pos: DVec2 {
                 x: origin.x + layout.padding.left,
                 y: origin.y + layout.padding.top
@@ -296,6 +356,12 @@ impl<'a> Cx2d<'a> {
             width_used: layout.padding.left,
             height_used: layout.padding.top,
             guard_area,
+            wrap_line: 0,
+            wrap_line_start: self.turtle_walks.len(),
+            wrap_line_size: 0.0,
+            grid_cell: 0,
+            grid_columns_used: Vec::new(),
+            grid_rows_used: Vec::new(),
         };
         
         self.turtles.push(turtle);
@@ -315,11 +381,23 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn end_turtle_with_guard(&mut self, guard_area: Area) -> Rect {
-        let turtle = self.turtles.pop().unwrap();
+        let mut turtle = self.turtles.pop().unwrap();
         if guard_area != turtle.guard_area {
             panic!("End turtle guard area misaligned!, begin/end pair not matched begin {:?} end {:?}", turtle.guard_area, guard_area)
         }
         
+        // a grid is as big as its tracks, which are only known now that everything is walked
+        let grid_sizes = if let Flow::Grid = turtle.layout.flow {
+            let column_sizes = turtle.grid_column_sizes();
+            let row_sizes = turtle.grid_row_sizes();
+            turtle.width_used = turtle.layout.padding.left + track_sizes_total(&column_sizes, turtle.layout.spacing);
+            turtle.height_used = turtle.layout.padding.top + track_sizes_total(&row_sizes, turtle.layout.line_spacing);
+            Some((column_sizes, row_sizes))
+        }
+        else {
+            None
+        };
+        
         // computed height
         let w = if turtle.width.is_nan() {
             Size::Fixed(turtle.width_used + turtle.layout.padding.right - turtle.layout.scroll.x)
@@ -337,15 +415,15 @@ impl<'a> Cx2d<'a> {
         
         match turtle.layout.flow {
             Flow::Right => {
-                if turtle.defer_count > 0 {
-                    let left = turtle.width_left();
-                    let part = left / turtle.defer_count as f64;
+                if !turtle.defer_walks.is_empty() {
+                    let offsets = turtle.defer_offsets();
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
-                        let shift_x = walk.defer_index as f64 * part;
+                        let shift_x = offsets[walk.defer_index];
                         let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
+                        let shift_x = turtle.rtl_shift_x(&self.turtle_walks[i], shift_x, w.fixed_or_zero());
                         self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
                     }
                 }
@@ -356,20 +434,21 @@ impl<'a> Cx2d<'a> {
                         let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
+                        let shift_x = turtle.rtl_shift_x(&self.turtle_walks[i], shift_x, w.fixed_or_zero());
                         self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
                     }
                 }
             },
             Flow::Down => {
-                if turtle.defer_count > 0 {
-                    let left = turtle.height_left();
-                    let part = left / turtle.defer_count as f64;
+                if !turtle.defer_walks.is_empty() {
+                    let offsets = turtle.defer_offsets();
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
                         let shift_x = turtle.layout.align.x * (turtle.padded_width_or_used() - walk.rect.size.x);
-                        let shift_y = walk.defer_index as f64 * part;
+                        let shift_y = offsets[walk.defer_index];
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
+                        let shift_x = turtle.rtl_shift_x(&self.turtle_walks[i], shift_x, w.fixed_or_zero());
                         self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
                     }
                 }
@@ -380,6 +459,7 @@ impl<'a> Cx2d<'a> {
                         let shift_y = turtle.layout.align.y * turtle.height_left();
                         let align_start = walk.align_start;
                         let align_end = self.get_turtle_walk_align_end(i);
+                        let shift_x = turtle.rtl_shift_x(&self.turtle_walks[i], shift_x, w.fixed_or_zero());
                         self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
                     }
                 }
@@ -391,6 +471,44 @@ impl<'a> Cx2d<'a> {
                     let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
                     let align_start = walk.align_start;
                     let align_end = self.get_turtle_walk_align_end(i);
+                    let shift_x = turtle.rtl_shift_x(&self.turtle_walks[i], shift_x, w.fixed_or_zero());
+                    self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                }
+            }
+            Flow::RightWrap | Flow::DownWrap => {
+                let mut line_begin = turtle.turtle_walks_start;
+                while line_begin < self.turtle_walks.len() {
+                    let line_end = line_begin + wrap_line_len(&self.turtle_walks[line_begin..]);
+                    let shifts = turtle.wrap_line_shifts(&self.turtle_walks[line_begin..line_end]);
+                    for (i, shift) in (line_begin..line_end).zip(shifts) {
+                        let walk = &self.turtle_walks[i];
+                        let align_start = walk.align_start;
+                        let align_end = self.get_turtle_walk_align_end(i);
+                        let shift_x = turtle.rtl_shift_x(walk, shift.x, w.fixed_or_zero());
+                        self.move_align_list(shift_x, shift.y, align_start, align_end, false, turtle.shift);
+                    }
+                    line_begin = line_end;
+                }
+            }
+            Flow::Grid => {
+                // everything was walked into a cell as big as the tracks were at the time, so
+                // move it into its final cell and align it there
+                let (column_sizes, row_sizes) = grid_sizes.unwrap();
+                for i in turtle.turtle_walks_start..self.turtle_walks.len() {
+                    let walk = &self.turtle_walks[i];
+                    let (shift_x, shift_y) = if let Some(cell) = walk.slot {
+                        let cell = turtle.grid_cell_rect(cell, &column_sizes, &row_sizes);
+                        (
+                            cell.pos.x + turtle.layout.align.x * (cell.size.x - walk.rect.size.x) - walk.rect.pos.x,
+                            cell.pos.y + turtle.layout.align.y * (cell.size.y - walk.rect.size.y) - walk.rect.pos.y
+                        )
+                    }
+                    else {
+                        (0.0, 0.0)
+                    };
+                    let align_start = walk.align_start;
+                    let align_end = self.get_turtle_walk_align_end(i);
+                    let shift_x = turtle.rtl_shift_x(&self.turtle_walks[i], shift_x, w.fixed_or_zero());
                     self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
                 }
             }
@@ -442,24 +560,26 @@ impl<'a> Cx2d<'a> {
     }
     
      fn walk_turtle_move(&mut self, walk: Walk, align_start: usize) -> Rect {
+        if walk.abs_pos.is_none() {
+            self.wrap_turtle_line(walk, align_start);
+        }
         
         let turtle = self.turtles.last_mut().unwrap();
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk_size(&walk);
         
         if let Some(pos) = walk.abs_pos {
             self.turtle_walks.push(TurtleWalk {
                 align_start,
                 defer_index: 0,
+                slot: None,
+                abs: true,
                 rect: Rect {pos, size: size + walk.margin.size()}
             });
             
             match turtle.layout.flow {
                 Flow::Right=>turtle.update_height_max(pos.y, size.y + walk.margin.size().y),
                 Flow::Down=>turtle.update_width_max(pos.x, size.x + walk.margin.size().x),
-                Flow::Overlay => { // do not walk
+                Flow::Overlay | Flow::RightWrap | Flow::DownWrap | Flow::Grid => { // do not walk
                     turtle.update_width_max(pos.x, size.x);
                     turtle.update_height_max(pos.y,size.y);
                 }
@@ -498,12 +618,36 @@ impl<'a> Cx2d<'a> {
                     turtle.update_width_max(turtle.pos.x, size.x);
                     turtle.update_height_max(turtle.pos.y,size.y);
                 }
+                Flow::RightWrap => {
+                    turtle.pos.x = pos.x + size.x + margin_size.x + spacing.x;
+                    turtle.wrap_line_size = turtle.wrap_line_size.max(size.y + margin_size.y);
+                    turtle.update_width_max(turtle.pos.x, 0.0);
+                    turtle.update_height_max(turtle.pos.y, size.y + margin_size.y);
+                },
+                Flow::DownWrap => {
+                    turtle.pos.y = pos.y + size.y + margin_size.y + spacing.y;
+                    turtle.wrap_line_size = turtle.wrap_line_size.max(size.x + margin_size.x);
+                    turtle.update_width_max(turtle.pos.x, size.x + margin_size.x);
+                    turtle.update_height_max(turtle.pos.y, 0.0);
+                },
+                Flow::Grid => {
+                    turtle.grid_walk_cell(size + margin_size);
+                    turtle.update_width_max(pos.x, size.x + margin_size.x);
+                    turtle.update_height_max(pos.y, size.y + margin_size.y);
+                }
             };
             
+            let slot = match turtle.layout.flow {
+                Flow::RightWrap | Flow::DownWrap => Some(turtle.wrap_line),
+                Flow::Grid => Some(turtle.grid_cell - 1),
+                Flow::Right | Flow::Down | Flow::Overlay => None
+            };
             self.turtle_walks.push(TurtleWalk {
                 align_start,
-                defer_index: turtle.defer_count,
-                rect: Rect {pos, size: size + margin_size}
+                defer_index: turtle.defer_walks.len(),
+                slot,
+                abs: false,
+                rect: Rect {pos: pos + spacing, size: size + margin_size}
             });
             Rect {pos: pos + walk.margin.left_top() + spacing, size}
         }
@@ -514,14 +658,14 @@ impl<'a> Cx2d<'a> {
             return Rect::default()
         }
         let turtle = self.turtles.last().unwrap();
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk_size(&walk);
         
         if let Some(pos) = walk.abs_pos {
             Rect {pos: pos + walk.margin.left_top(), size}
         }
+        else if let Some(pos) = turtle.wrap_line_pos(size + walk.margin.size(), self.turtle_walks.len()) {
+            Rect {pos: pos + walk.margin.left_top(), size}
+        }
         else {
             let spacing = turtle.child_spacing(self.turtle_walks.len());
             let pos = turtle.pos;
@@ -529,6 +673,23 @@ impl<'a> Cx2d<'a> {
         }
     }
     
+    // starts a new line of a wrapping flow when the walk doesn't fit on the current one, and moves
+    // what was already drawn for it along to the start of that line
+    fn wrap_turtle_line(&mut self, walk: Walk, align_start: usize) {
+        let walks_len = self.turtle_walks.len();
+        let turtle = self.turtles.last_mut().unwrap();
+        let size = turtle.eval_walk_size(&walk);
+        if let Some(pos) = turtle.wrap_line_pos(size + walk.margin.size(), walks_len) {
+            let shift = pos - (turtle.pos + turtle.child_spacing(walks_len));
+            turtle.pos = pos;
+            turtle.wrap_line += 1;
+            turtle.wrap_line_start = walks_len;
+            turtle.wrap_line_size = 0.0;
+            let align_end = self.align_list.len();
+            self.move_align_list(shift.x, shift.y, align_start, align_end, false, dvec2(0.0, 0.0));
+        }
+    }
+    
     fn move_align_list(&mut self, dx: f64, dy: f64, align_start: usize, align_end: usize, shift_clip: bool, turtle_shift:DVec2) {
         //let current_dpi_factor = self.current_dpi_factor();
         let dx = if dx.is_nan() {0.0}else {dx} + turtle_shift.x;
@@ -723,8 +884,20 @@ impl Turtle {
         self.pos = pos
     }
     
+    // the shift that puts a walk at its mirrored position in a right to left layout, where width
+    // is the final width of the turtle
+    fn rtl_shift_x(&self, walk: &TurtleWalk, shift_x: f64, width: f64) -> f64 {
+        if !self.layout.rtl || walk.abs {
+            return shift_x
+        }
+        let shift_x = if shift_x.is_nan() {0.0} else {shift_x};
+        let left = self.origin.x + self.layout.padding.left;
+        let right = self.origin.x + width - self.layout.padding.right;
+        left + right - (walk.rect.pos.x + shift_x) - walk.rect.size.x - walk.rect.pos.x
+    }
+    
     fn child_spacing(&self, walks_len: usize) -> DVec2 {
-        if self.turtle_walks_start < walks_len || self.defer_count > 0 {
+        if self.turtle_walks_start < walks_len || !self.defer_walks.is_empty() {
             match self.layout.flow {
                 Flow::Right => {
                     dvec2(self.layout.spacing, 0.0)
@@ -732,7 +905,13 @@ impl Turtle {
                 Flow::Down => {
                     dvec2(0.0, self.layout.spacing)
                 }
-                Flow::Overlay => {
+                Flow::RightWrap if self.wrap_line_start < walks_len => {
+                    dvec2(self.layout.spacing, 0.0)
+                }
+                Flow::DownWrap if self.wrap_line_start < walks_len => {
+                    dvec2(0.0, self.layout.spacing)
+                }
+                Flow::Overlay | Flow::RightWrap | Flow::DownWrap | Flow::Grid => {
                     dvec2(0.0, 0.0)
                 }
             }
@@ -742,6 +921,146 @@ impl Turtle {
         }
     }
     
+    // where the next line of a wrapping flow starts, if an item of `size` (margin included)
+    // doesn't fit on the current one. A line always takes at least one item.
+    fn wrap_line_pos(&self, size: DVec2, walks_len: usize) -> Option<DVec2> {
+        if self.wrap_line_start >= walks_len {
+            return None
+        }
+        let pos = self.pos + self.child_spacing(walks_len);
+        match self.layout.flow {
+            Flow::RightWrap => {
+                let end = self.origin.x + self.width - self.layout.padding.right;
+                if end.is_nan() || pos.x + size.x <= end {
+                    return None
+                }
+                Some(dvec2(
+                    self.origin.x + self.layout.padding.left,
+                    self.pos.y + self.wrap_line_size + self.layout.line_spacing
+                ))
+            }
+            Flow::DownWrap => {
+                let end = self.origin.y + self.height - self.layout.padding.bottom;
+                if end.is_nan() || pos.y + size.y <= end {
+                    return None
+                }
+                Some(dvec2(
+                    self.pos.x + self.wrap_line_size + self.layout.line_spacing,
+                    self.origin.y + self.layout.padding.top
+                ))
+            }
+            _ => None
+        }
+    }
+    
+    // how far each walk of a wrap line moves. Every line is aligned on its own along the flow,
+    // the lines together across it, and absolutely positioned walks stay where they are
+    fn wrap_line_shifts(&self, line: &[TurtleWalk]) -> Vec<DVec2> {
+        let right = matches!(self.layout.flow, Flow::RightWrap);
+        let (line_space, block_shift) = if right {
+            (self.padded_width_or_used(), dvec2(0.0, self.layout.align.y * self.height_left()))
+        }
+        else {
+            (self.padded_height_or_used(), dvec2(self.layout.align.x * self.width_left(), 0.0))
+        };
+        let placed = line.iter().filter( | w | w.slot.is_some());
+        let line_min = placed.clone().fold(DVec2 {x: f64::INFINITY, y: f64::INFINITY}, | a, w | dvec2(a.x.min(w.rect.pos.x), a.y.min(w.rect.pos.y)));
+        let line_max = placed.fold(DVec2 {x: f64::NEG_INFINITY, y: f64::NEG_INFINITY}, | a, w | {
+            dvec2(a.x.max(w.rect.pos.x + w.rect.size.x), a.y.max(w.rect.pos.y + w.rect.size.y))
+        });
+        let line_size = line_max - line_min;
+        line.iter().map( | walk | {
+            if walk.slot.is_none() {
+                dvec2(0.0, 0.0)
+            }
+            else if right {
+                dvec2(
+                    self.layout.align.x * (line_space - line_size.x),
+                    block_shift.y + self.layout.align.y * (line_size.y - walk.rect.size.y)
+                )
+            }
+            else {
+                dvec2(
+                    block_shift.x + self.layout.align.x * (line_size.x - walk.rect.size.x),
+                    self.layout.align.y * (line_space - line_size.y)
+                )
+            }
+        }).collect()
+    }
+    
+    fn grid_column_count(&self) -> usize {
+        self.layout.grid_columns.len().max(1)
+    }
+    
+    fn grid_column_sizes(&self) -> Vec<f64> {
+        // without columns a grid is a single column as wide as the turtle
+        let columns = if self.layout.grid_columns.is_empty() {
+            GridTracks::equal(1)
+        }
+        else {
+            self.layout.grid_columns
+        };
+        grid_track_sizes(
+            &columns,
+            &self.grid_columns_used,
+            self.grid_column_count(),
+            self.width - self.layout.padding.width(),
+            self.layout.spacing
+        )
+    }
+    
+    fn grid_row_sizes(&self) -> Vec<f64> {
+        let count = self.layout.grid_rows.len()
+            .max(self.grid_rows_used.len())
+            .max(self.grid_cell / self.grid_column_count() + 1);
+        grid_track_sizes(
+            &self.layout.grid_rows,
+            &self.grid_rows_used,
+            count,
+            self.height - self.layout.padding.height(),
+            self.layout.line_spacing
+        )
+    }
+    
+    fn grid_cell_rect(&self, cell: usize, column_sizes: &[f64], row_sizes: &[f64]) -> Rect {
+        let columns = self.grid_column_count();
+        let (column, row) = (cell % columns, cell / columns);
+        let offset = | sizes: &[f64], index: usize, spacing: f64 | {
+            sizes[..index.min(sizes.len())].iter().sum::<f64>() + spacing * index as f64
+        };
+        Rect {
+            pos: self.origin + self.layout.padding.left_top() + dvec2(
+                offset(column_sizes, column, self.layout.spacing),
+                offset(row_sizes, row, self.layout.line_spacing)
+            ),
+            size: dvec2(
+                column_sizes.get(column).copied().unwrap_or(0.0),
+                row_sizes.get(row).copied().unwrap_or(0.0)
+            )
+        }
+    }
+    
+    // the cell the next item of a grid goes into, with the tracks as far as they are known
+    fn grid_current_cell_rect(&self) -> Rect {
+        self.grid_cell_rect(self.grid_cell, &self.grid_column_sizes(), &self.grid_row_sizes())
+    }
+    
+    // records an item of `size` (margin included) in the current cell and moves on to the next
+    fn grid_walk_cell(&mut self, size: DVec2) {
+        let columns = self.grid_column_count();
+        let (column, row) = (self.grid_cell % columns, self.grid_cell / columns);
+        if self.grid_columns_used.len() <= column {
+            self.grid_columns_used.resize(column + 1, 0.0);
+        }
+        if self.grid_rows_used.len() <= row {
+            self.grid_rows_used.resize(row + 1, 0.0);
+        }
+        self.grid_columns_used[column] = self.grid_columns_used[column].max(size.x);
+        self.grid_rows_used[row] = self.grid_rows_used[row].max(size.y);
+        self.grid_cell += 1;
+        self.pos = self.grid_current_cell_rect().pos;
+    }
+    
     pub fn rect_is_visible(&self, geom: Rect) -> bool {
         let view = Rect {pos: self.origin + self.layout.scroll, size: dvec2(self.width, self.height)};
         return view.intersects(geom)
@@ -766,48 +1085,128 @@ impl Turtle {
         self.layout.scroll
     }
     
-    pub fn eval_width(&self, width: Size, margin: Margin, flow: Flow) -> f64 {
-        return match width {
+    pub fn eval_walk_size(&self, walk: &Walk) -> DVec2 {
+        dvec2(
+            self.eval_width(walk, self.layout.flow),
+            self.eval_height(walk, self.layout.flow)
+        )
+    }
+    
+    pub fn eval_width(&self, walk: &Walk, flow: Flow) -> f64 {
+        let margin = walk.margin;
+        walk.clamp_width(match walk.width {
             Size::Fit => std::f64::NAN,
             Size::Fixed(v) => max_zero_keep_nan(v),
-            Size::Fill => {
+            Size::Fill(_) => {
                 match flow {
                     Flow::Right => {
                         max_zero_keep_nan(self.width_left() - margin.width())
                     },
-                    Flow::Down | Flow::Overlay => {
+                    Flow::Grid => {
+                        max_zero_keep_nan(self.grid_current_cell_rect().size.x - margin.width())
+                    },
+                    Flow::Down | Flow::Overlay | Flow::RightWrap | Flow::DownWrap => {
                         let r = max_zero_keep_nan(self.width - self.layout.padding.width() - margin.width());
                         if r.is_nan() {
-                            return self.width_used - margin.width() - self.layout.padding.right
+                            self.width_used - margin.width() - self.layout.padding.right
+                        }
+                        else {
+                            r
                         }
-                        return r
                     }
                 }
             },
             Size::All=>self.width
-        }
+        })
     }
     
-    pub fn eval_height(&self, height: Size, margin: Margin, flow: Flow) -> f64 {
-        return match height {
+    pub fn eval_height(&self, walk: &Walk, flow: Flow) -> f64 {
+        let margin = walk.margin;
+        walk.clamp_height(match walk.height {
             Size::Fit => std::f64::NAN,
             Size::Fixed(v) => max_zero_keep_nan(v),
-            Size::Fill => {
+            Size::Fill(_) => {
                 match flow {
-                    Flow::Right | Flow::Overlay => {
+                    Flow::Right | Flow::Overlay | Flow::RightWrap | Flow::DownWrap => {
                         let r = max_zero_keep_nan(self.height - self.layout.padding.height() - margin.height());
                         if r.is_nan() {
-                            return self.height_used - margin.height() - self.layout.padding.bottom
+                            self.height_used - margin.height() - self.layout.padding.bottom
+                        }
+                        else {
+                            r
                         }
-                        return r
                     }
                     Flow::Down => {
                         max_zero_keep_nan(self.height_left() - margin.height())
                     }
+                    Flow::Grid => {
+                        max_zero_keep_nan(self.grid_current_cell_rect().size.y - margin.height())
+                    }
                 }
             }
             Size::All=>self.height
+        })
+    }
+    
+    // the sizes of the deferred fills: the space left along the flow, shared by weight. Fills that
+    // would get less than their minimum or more than their maximum get that instead, and the rest
+    // is shared again between the others.
+    fn defer_sizes(&self) -> Vec<f64> {
+        let (left, fills): (f64, Vec<(f64, Option<f64>, Option<f64>)>) = match self.layout.flow {
+            Flow::Right => (
+                self.width_left(),
+                self.defer_walks.iter().map( | walk | (walk.width.fill_weight(), walk.min_width, walk.max_width)).collect()
+            ),
+            Flow::Down => (
+                self.height_left(),
+                self.defer_walks.iter().map( | walk | (walk.height.fill_weight(), walk.min_height, walk.max_height)).collect()
+            ),
+            Flow::Overlay | Flow::RightWrap | Flow::DownWrap | Flow::Grid => return Vec::new()
+        };
+        let mut sizes = vec![f64::NAN; fills.len()];
+        if left.is_nan() {
+            return sizes
+        }
+        let mut bounded = vec![false; fills.len()];
+        loop {
+            let mut free = left;
+            let mut weight = 0.0;
+            for (i, (fill_weight, _, _)) in fills.iter().enumerate() {
+                if bounded[i] {
+                    free -= sizes[i];
+                }
+                else {
+                    weight += fill_weight;
+                }
+            }
+            let mut any_bounded = false;
+            for (i, (fill_weight, min, max)) in fills.iter().enumerate() {
+                if bounded[i] {
+                    continue;
+                }
+                let size = if weight > 0.0 {free.max(0.0) * fill_weight / weight} else {0.0};
+                sizes[i] = clamp_size(size, *min, *max);
+                if sizes[i] != size {
+                    bounded[i] = true;
+                    any_bounded = true;
+                }
+            }
+            if !any_bounded {
+                return sizes
+            }
+        }
+    }
+    
+    // how far along the flow each deferred fill starts from where it was deferred, followed by
+    // how far everything walked after the last one has to move
+    fn defer_offsets(&self) -> Vec<f64> {
+        let mut offset = 0.0;
+        let mut offsets = vec![0.0];
+        for size in self.defer_sizes() {
+            offset += size;
+            offsets.push(offset);
         }
+        offsets
     }
     
     pub fn rect(&self) -> Rect {
@@ -882,30 +1281,27 @@ impl DeferWalk {
     pub fn resolve(&mut self, cx: &Cx2d) -> Walk {
         match self{
             Self::Resolved(walk)=>{*walk},
-            Self::Unresolved{pos, defer_index, margin, other_axis}=>{
+            Self::Unresolved{pos, defer_index, walk}=>{
                 let turtle = cx.turtles.last().unwrap();
+                let offsets = turtle.defer_offsets();
+                let (offset, size) = (offsets[*defer_index], offsets[*defer_index + 1] - offsets[*defer_index]);
                 let walk = match turtle.layout.flow {
                     Flow::Right => {
-                        let left = turtle.width_left();
-                        let part = left / turtle.defer_count as f64;
                         Walk {
-                            abs_pos: Some(*pos + dvec2(part * *defer_index as f64, 0.)),
-                            margin: *margin,
-                            width: Size::Fixed(part),
-                            height: *other_axis
+                            abs_pos: Some(*pos + dvec2(offset, 0.)),
+                            width: Size::Fixed(size),
+                            ..*walk
                         }
                     },
                     Flow::Down => {
-                        let left = turtle.height_left();
-                        let part = left / turtle.defer_count as f64;
                         Walk {
-                            abs_pos: Some(*pos + dvec2(0., part * *defer_index as f64)),
-                            margin: *margin,
-                            height: Size::Fixed(part),
-                            width: *other_axis
+                            abs_pos: Some(*pos + dvec2(0., offset)),
+                            height: Size::Fixed(size),
+                            ..*walk
                         }
                     }
-                    Flow::Overlay => panic!()
+                    // only Right and Down defer
+                    Flow::Overlay | Flow::RightWrap | Flow::DownWrap | Flow::Grid => panic!()
                 };
                 *self = DeferWalk::Resolved(walk);
                 walk
@@ -936,7 +1332,40 @@ impl Layout {
             ..Self::default()
         }
     }
+    
+    pub fn flow_right_wrap() -> Self {
+        Self {
+            flow: Flow::RightWrap,
+            ..Self::default()
+        }
+    }
+    
+    pub fn flow_down_wrap() -> Self {
+        Self {
+            flow: Flow::DownWrap,
+            ..Self::default()
+        }
+    }
+    
+    pub fn grid(columns: &[GridTrack], rows: &[GridTrack]) -> Self {
+        Self {
+            flow: Flow::Grid,
+            grid_columns: GridTracks::new(columns),
+            grid_rows: GridTracks::new(rows),
+            ..Self::default()
+        }
+    }
+    
+    pub fn with_line_spacing(mut self, v: f64) -> Self {
+        self.line_spacing = v;
+        self
+    }
 
+    pub fn with_rtl(mut self, v: bool) -> Self {
+        self.rtl = v;
+        self
+    }
+    
     pub fn with_scroll(mut self, v: DVec2) -> Self {
         self.scroll = v;
         self
@@ -996,6 +1425,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(0.0),
             height: Size::Fixed(0.0),
+            ..Self::default()
         }
     }
     
@@ -1005,6 +1435,7 @@ impl Walk {
             margin: Margin::default(),
             width: w,
             height: h,
+            ..Self::default()
         }
     }
 
@@ -1014,6 +1445,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(rect.size.x),
             height: Size::Fixed(rect.size.y),
+            ..Self::default()
         }
     }
     
@@ -1023,6 +1455,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(w),
             height: Size::Fixed(h),
+            ..Self::default()
         }
     }
         
@@ -1032,6 +1465,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(size.x),
             height: Size::Fixed(size.y),
+            ..Self::default()
         }
     }
     
@@ -1041,6 +1475,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fit,
             height: Size::Fit,
+            ..Self::default()
         }
     }
     
@@ -1048,8 +1483,9 @@ impl Walk {
         Self {
             abs_pos: None,
             margin: Margin::default(),
-            width: Size::Fill,
-            height: Size::Fill,
+            width: Size::fill(),
+            height: Size::fill(),
+            ..Self::default()
         }
     }
     
@@ -1057,8 +1493,9 @@ impl Walk {
         Self {
             abs_pos: None,
             margin: Margin::default(),
-            width: Size::Fill,
+            width: Size::fill(),
             height: Size::Fit,
+            ..Self::default()
         }
     }
     
@@ -1096,6 +1533,36 @@ impl Walk {
         self
     }
     
+    pub fn with_min_width(mut self, v: f64) -> Self {
+        self.min_width = Some(v);
+        self
+    }
+    
+    pub fn with_max_width(mut self, v: f64) -> Self {
+        self.max_width = Some(v);
+        self
+    }
+    
+    pub fn with_min_height(mut self, v: f64) -> Self {
+        self.min_height = Some(v);
+        self
+    }
+    
+    pub fn with_max_height(mut self, v: f64) -> Self {
+        self.max_height = Some(v);
+        self
+    }
+    
+    /// Bounds `width` by `min_width` and `max_width`, unless it is NaN, meaning it is not known yet
+    pub fn clamp_width(&self, width: f64) -> f64 {
+        clamp_size(width, self.min_width, self.max_width)
+    }
+    
+    /// Bounds `height` by `min_height` and `max_height`, unless it is NaN
+    pub fn clamp_height(&self, height: f64) -> f64 {
+        clamp_size(height, self.min_height, self.max_height)
+    }
+    
     pub fn with_add_padding(mut self, v: Padding) -> Self {
         self.margin.top += v.top;
         self.margin.left += v.left;
@@ -1172,6 +1639,11 @@ impl LiveHook for Size {
                 *self = Self::Fixed(*v as f64);
                 Some(index + 1)
             }
+            // a plain `Fill` has a weight of 1
+            LiveValue::BareEnum(live_id!(Fill)) => {
+                *self = Self::fill();
+                Some(index + 1)
+            }
             _ => None
         }
     }
@@ -1179,11 +1651,23 @@ impl LiveHook for Size {
 
 impl Default for Size {
     fn default() -> Self {
-        Size::Fill
+        Size::Fill(1.0)
     }
 }
 
 impl Size {
+    pub fn fill() -> Self {
+        Self::Fill(1.0)
+    }
+    
+    /// The weight of a `Fill`, 0 for the other sizes
+    pub fn fill_weight(&self) -> f64 {
+        match self {
+            Self::Fill(weight) => weight.max(0.0),
+            _ => 0.0
+        }
+    }
+    
     pub fn fixed_or_zero(&self) -> f64 {
         match self {
             Self::Fixed(v) => *v,
@@ -1214,12 +1698,153 @@ impl Size {
     
     pub fn is_fill(&self) -> bool {
         match self {
-            Self::Fill => true,
+            Self::Fill(_) => true,
             _ => false
         }
     }
 }
 
+impl Default for GridTrack {
+    fn default() -> Self {
+        GridTrack::Fit
+    }
+}
+
+impl LiveHook for GridTrack {
+    fn skip_apply(&mut self, _cx: &mut Cx, _apply_from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> Option<usize> {
+        match &nodes[index].value {
+            LiveValue::Float64(v) => {
+                *self = Self::Fixed(*v);
+                Some(index + 1)
+            }
+            LiveValue::Int64(v) => {
+                *self = Self::Fixed(*v as f64);
+                Some(index + 1)
+            }
+            _ => None
+        }
+    }
+}
+
+impl LiveHook for GridTracks {
+    fn skip_apply(&mut self, cx: &mut Cx, apply_from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> Option<usize> {
+        match &nodes[index].value {
+            LiveValue::Array => {
+                self.len = 0;
+                let mut index = index + 1;
+                while !nodes[index].is_close() {
+                    let mut track = GridTrack::default();
+                    let track_index = index;
+                    index = track.apply(cx, apply_from, index, nodes);
+                    if self.len == GRID_MAX_TRACKS {
+                        cx.apply_error(live_error_origin!(), track_index, nodes, format!("a grid can have at most {} columns or rows", GRID_MAX_TRACKS));
+                    }
+                    else {
+                        self.tracks[self.len] = track;
+                        self.len += 1;
+                    }
+                }
+                Some(index + 1)
+            }
+            LiveValue::Int64(v) => {
+                *self = Self::equal((*v).max(0) as usize);
+                Some(index + 1)
+            }
+            _ => None
+        }
+    }
+}
+
+impl GridTracks {
+    pub fn new(tracks: &[GridTrack]) -> Self {
+        let mut ret = Self::default();
+        for track in tracks.iter().take(GRID_MAX_TRACKS) {
+            ret.tracks[ret.len] = *track;
+            ret.len += 1;
+        }
+        ret
+    }
+    
+    /// `count` tracks that share the space equally
+    pub fn equal(count: usize) -> Self {
+        Self {
+            tracks: [GridTrack::Fr(1.0); GRID_MAX_TRACKS],
+            len: count.min(GRID_MAX_TRACKS)
+        }
+    }
+    
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    /// The track at `index`, tracks past the defined ones are `Fit`
+    pub fn track(&self, index: usize) -> GridTrack {
+        if index < self.len {
+            self.tracks[index]
+        }
+        else {
+            GridTrack::Fit
+        }
+    }
+}
+
+// sizes the tracks of a grid. Fit tracks are as big as the largest item walked into them so far,
+// and fractional tracks share what the others leave of `available`, or act like fit tracks when
+// the grid is sized to its content
+fn grid_track_sizes(tracks: &GridTracks, used: &[f64], count: usize, available: f64, spacing: f64) -> Vec<f64> {
+    let used = | index: usize | used.get(index).copied().unwrap_or(0.0);
+    let mut sizes = Vec::with_capacity(count);
+    let mut taken = 0.0;
+    let mut fr_total = 0.0;
+    for i in 0..count {
+        let size = match tracks.track(i) {
+            GridTrack::Fit => used(i),
+            GridTrack::Fixed(v) => v.max(0.0),
+            GridTrack::Fr(fr) => {
+                fr_total += fr.max(0.0);
+                0.0
+            }
+        };
+        taken += size;
+        sizes.push(size);
+    }
+    let left = max_zero_keep_nan(available - taken - spacing * count.saturating_sub(1) as f64);
+    for (i, size) in sizes.iter_mut().enumerate() {
+        if let GridTrack::Fr(fr) = tracks.track(i) {
+            *size = if left.is_nan() || fr_total <= 0.0 {
+                used(i)
+            }
+            else {
+                left * fr.max(0.0) / fr_total
+            };
+        }
+    }
+    sizes
+}
+
+// how many walks the wrap line starting at the first one has. Absolutely positioned walks have no
+// line, so they belong to the one they were walked in instead of splitting it
+fn wrap_line_len(walks: &[TurtleWalk]) -> usize {
+    let slot = walks.iter().find_map( | walk | walk.slot);
+    walks.iter().take_while( | walk | walk.slot.is_none() || walk.slot == slot).count()
+}
+
+fn track_sizes_total(sizes: &[f64], spacing: f64) -> f64 {
+    sizes.iter().sum::<f64>() + spacing * sizes.len().saturating_sub(1) as f64
+}
+
+fn clamp_size(v: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+    if v.is_nan() {
+        return v
+    }
+    let v = if let Some(max) = max {v.min(max)} else {v};
+    if let Some(min) = min {v.max(min)} else {v}
+}
+
 fn max_zero_keep_nan(v: f64) -> f64 {
     if v.is_nan() {
         v
@@ -1228,3 +1853,151 @@ fn max_zero_keep_nan(v: f64) -> f64 {
         f64::max(v, 0.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn turtle(layout: Layout, width: f64, height: f64) -> Turtle {
+        Turtle {layout, width, height, ..Turtle::default()}
+    }
+    
+    fn walk(slot: Option<usize>, pos: DVec2, size: DVec2) -> TurtleWalk {
+        TurtleWalk {slot, abs: slot.is_none(), rect: Rect {pos, size}, ..TurtleWalk::default()}
+    }
+    
+    #[test]
+    fn grid_fr_columns_share_what_the_others_leave() {
+        let mut layout = Layout::grid(&[GridTrack::Fixed(50.0), GridTrack::Fr(1.0), GridTrack::Fr(2.0), GridTrack::Fit], &[]);
+        layout.spacing = 10.0;
+        let mut t = turtle(layout, 280.0, f64::NAN);
+        t.grid_columns_used = vec![0.0, 0.0, 0.0, 20.0];
+        // 280 - 50 - 20 - 3 * 10 spacing leaves 180 for 3 fr
+        assert_eq!(t.grid_column_sizes(), vec![50.0, 60.0, 120.0, 20.0]);
+    }
+    
+    #[test]
+    fn grid_fr_columns_fit_their_content_without_a_width() {
+        let layout = Layout::grid(&[GridTrack::Fixed(50.0), GridTrack::Fr(1.0), GridTrack::Fr(2.0), GridTrack::Fit], &[]);
+        let mut t = turtle(layout, f64::NAN, f64::NAN);
+        t.grid_columns_used = vec![0.0, 30.0, 40.0, 20.0];
+        assert_eq!(t.grid_column_sizes(), vec![50.0, 30.0, 40.0, 20.0]);
+    }
+    
+    #[test]
+    fn grid_without_columns_is_one_column_as_wide_as_the_turtle() {
+        let layout = Layout::grid(&[], &[]).with_padding_all(5.0);
+        assert_eq!(turtle(layout, 100.0, f64::NAN).grid_column_sizes(), vec![90.0]);
+    }
+    
+    #[test]
+    fn grid_rows_grow_with_the_cells() {
+        let layout = Layout::grid(&[GridTrack::Fr(1.0), GridTrack::Fr(1.0)], &[GridTrack::Fixed(15.0)]);
+        let mut t = turtle(layout, 100.0, f64::NAN);
+        t.grid_rows_used = vec![10.0, 20.0];
+        t.grid_cell = 5;
+        // the first row is fixed, the ones past the defined tracks fit, the third has nothing yet
+        assert_eq!(t.grid_row_sizes(), vec![15.0, 20.0, 0.0]);
+    }
+    
+    #[test]
+    fn grid_cell_rect_adds_padding_and_spacing() {
+        let mut layout = Layout::grid(&[GridTrack::Fit, GridTrack::Fit], &[]).with_padding_left(5.0).with_padding_top(7.0);
+        layout.spacing = 10.0;
+        layout.line_spacing = 4.0;
+        let mut t = turtle(layout, f64::NAN, f64::NAN);
+        t.origin = dvec2(100.0, 200.0);
+        let (columns, rows) = ([30.0, 40.0], [20.0, 25.0]);
+        assert_eq!(t.grid_cell_rect(0, &columns, &rows), Rect {pos: dvec2(105.0, 207.0), size: dvec2(30.0, 20.0)});
+        assert_eq!(t.grid_cell_rect(3, &columns, &rows), Rect {pos: dvec2(145.0, 231.0), size: dvec2(40.0, 25.0)});
+        // a row that isn't sized yet starts after the others and is empty
+        assert_eq!(t.grid_cell_rect(4, &columns, &rows), Rect {pos: dvec2(105.0, 260.0), size: dvec2(30.0, 0.0)});
+    }
+    
+    #[test]
+    fn absolute_walks_do_not_split_a_wrap_line() {
+        let at = | slot | walk(slot, dvec2(0.0, 0.0), dvec2(10.0, 10.0));
+        let walks = [at(Some(0)), at(None), at(Some(0)), at(Some(1)), at(None)];
+        assert_eq!(wrap_line_len(&walks), 3);
+        assert_eq!(wrap_line_len(&walks[3..]), 2);
+        assert_eq!(wrap_line_len(&[at(None), at(Some(1)), at(Some(2))]), 2);
+    }
+    
+    #[test]
+    fn wrap_lines_align_on_their_own() {
+        let layout = Layout {align: Align {x: 0.5, y: 1.0}, ..Layout::flow_right_wrap()};
+        let mut t = turtle(layout, 100.0, 50.0);
+        t.height_used = 30.0;
+        let line = [
+            walk(Some(0), dvec2(0.0, 0.0), dvec2(20.0, 10.0)),
+            walk(None, dvec2(70.0, 5.0), dvec2(5.0, 5.0)),
+            walk(Some(0), dvec2(30.0, 0.0), dvec2(30.0, 20.0)),
+        ];
+        // the line is 60 wide in 100 and the block of lines moves down the 20 that is left
+        assert_eq!(t.wrap_line_shifts(&line), vec![dvec2(20.0, 30.0), dvec2(0.0, 0.0), dvec2(20.0, 20.0)]);
+    }
+    
+    #[test]
+    fn down_wrap_lines_align_on_their_own() {
+        let layout = Layout {align: Align {x: 1.0, y: 0.5}, ..Layout::flow_down_wrap()};
+        let mut t = turtle(layout, 40.0, 100.0);
+        t.width_used = 40.0;
+        let line = [
+            walk(Some(1), dvec2(0.0, 0.0), dvec2(10.0, 30.0)),
+            walk(Some(1), dvec2(0.0, 30.0), dvec2(20.0, 30.0)),
+        ];
+        assert_eq!(t.wrap_line_shifts(&line), vec![dvec2(10.0, 20.0), dvec2(0.0, 20.0)]);
+    }
+    
+    fn fill(weight: f64) -> Walk {
+        Walk::size(Size::Fill(weight), Size::Fill(weight))
+    }
+    
+    #[test]
+    fn deferred_fills_share_what_is_left_by_weight() {
+        let mut t = turtle(Layout::flow_right(), 300.0, 100.0);
+        t.width_used = 60.0;
+        t.defer_walks = vec![fill(1.0), fill(2.0)];
+        assert_eq!(t.defer_sizes(), vec![80.0, 160.0]);
+        assert_eq!(t.defer_offsets(), vec![0.0, 80.0, 240.0]);
+    }
+    
+    #[test]
+    fn deferred_fills_share_the_height_flowing_down() {
+        let mut t = turtle(Layout::flow_down().with_padding_bottom(10.0), 300.0, 100.0);
+        t.height_used = 30.0;
+        t.defer_walks = vec![fill(3.0), fill(1.0)];
+        assert_eq!(t.defer_sizes(), vec![45.0, 15.0]);
+    }
+    
+    #[test]
+    fn clamped_fills_leave_the_rest_to_the_others() {
+        let mut t = turtle(Layout::flow_right(), 290.0, 100.0);
+        t.defer_walks = vec![fill(1.0).with_max_width(50.0), fill(1.0), fill(2.0)];
+        // 72.5 is more than the first may take, the 240 left is shared again
+        assert_eq!(t.defer_sizes(), vec![50.0, 80.0, 160.0]);
+        
+        let mut t = turtle(Layout::flow_right(), 200.0, 100.0);
+        t.defer_walks = vec![fill(1.0).with_min_width(150.0), fill(1.0)];
+        assert_eq!(t.defer_sizes(), vec![150.0, 50.0]);
+    }
+    
+    #[test]
+    fn fills_without_a_size_to_share_stay_unknown() {
+        let mut t = turtle(Layout::flow_right(), f64::NAN, 100.0);
+        t.defer_walks = vec![fill(1.0), fill(2.0)];
+        assert!(t.defer_sizes().iter().all( | size | size.is_nan()));
+    }
+    
+    #[test]
+    fn eval_size_clamps_to_min_and_max() {
+        let t = turtle(Layout::flow_down().with_padding_all(10.0), 100.0, 100.0);
+        assert_eq!(t.eval_width(&Walk::fixed(300.0, 10.0).with_max_width(200.0), Flow::Down), 200.0);
+        // a fill across the flow takes the padded width, then gets clamped
+        assert_eq!(t.eval_width(&fill(1.0), Flow::Down), 80.0);
+        assert_eq!(t.eval_width(&fill(1.0).with_min_width(150.0), Flow::Down), 150.0);
+        assert_eq!(t.eval_height(&fill(1.0).with_max_height(30.0).with_margin_top(5.0), Flow::Down), 30.0);
+        // fit stays unknown whatever the bounds
+        assert!(t.eval_width(&Walk::fit().with_min_width(10.0), Flow::Down).is_nan());
+    }
+}
@@ -39,6 +39,11 @@ impl AffineTransformation {
     pub fn translate(self, v: Vector) -> AffineTransformation {
         AffineTransformation::new(self.xy, self.z + v)
     }
+
+    /// Returns the transformation that applies `other` first, and then `self`.
+    pub fn compose(self, other: AffineTransformation) -> AffineTransformation {
+        AffineTransformation::new(self.xy.compose(other.xy), self.transform_vector(other.z) + self.z)
+    }
 }
 
 impl Transformation for AffineTransformation {
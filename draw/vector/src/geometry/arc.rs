@@ -1,6 +1,6 @@
 use std::f64::consts::{PI, TAU};
 
-use crate::geometry::{Point, Transform, Transformation};
+use crate::geometry::{CubicSegment, Point, Transform, Transformation};
 use crate::internal_iter::InternalIterator;
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -208,6 +208,45 @@ impl Arc {
         ))
     }
 
+    /// Approximates `self` with cubic bezier curve segments, one for every quarter turn or less.
+    /// Unlike an arc, these stay exact under any affine transformation.
+    pub fn to_cubics(&self) -> Vec<CubicSegment> {
+        if self.sweep_angle == 0.0 {
+            // the arc is a line, or nothing at all
+            return vec![CubicSegment::new(self.from, self.from, self.to, self.to)];
+        }
+        let x_axis_rotation_radians = self.x_axis_rotation * (PI / 180.0);
+        let (sin_rotation, cos_rotation) = x_axis_rotation_radians.sin_cos();
+        let map = |x: f64, y: f64| Point {
+            x: cos_rotation * self.rx * x - sin_rotation * self.ry * y + self.center.x,
+            y: sin_rotation * self.rx * x + cos_rotation * self.ry * y + self.center.y,
+        };
+        let count = (self.sweep_angle.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+        let delta = self.sweep_angle / count as f64;
+        let k = 4.0 / 3.0 * (delta / 4.0).tan();
+        let mut cubics = Vec::with_capacity(count);
+        let mut p0 = self.from;
+        for index in 0..count {
+            let angle_0 = self.start_angle + delta * index as f64;
+            let angle_1 = angle_0 + delta;
+            let (sin_0, cos_0) = angle_0.sin_cos();
+            let (sin_1, cos_1) = angle_1.sin_cos();
+            let p3 = if index == count - 1 {
+                self.to
+            } else {
+                map(cos_1, sin_1)
+            };
+            cubics.push(CubicSegment::new(
+                p0,
+                map(cos_0 - k * sin_0, sin_0 + k * cos_0),
+                map(cos_1 + k * sin_1, sin_1 - k * cos_1),
+                p3,
+            ));
+            p0 = p3;
+        }
+        cubics
+    }
+
     pub fn linearize(self, epsilon: f64) -> Linearize {
         Linearize {
             segment: self,
@@ -0,0 +1,902 @@
+use crate::geometry::{
+    AffineTransformation, Arc, LinearTransformation, Point, Rectangle, Transform, Vector,
+};
+use crate::path::PathCommand;
+use crate::svg::path::parse_svg_path;
+use crate::svg::stroke::{stroke_path, LineCap, LineJoin, StrokeStyle};
+use crate::svg::style::{
+    parse_declarations, parse_length, parse_numbers, parse_transform, StyleSheet, SvgColor,
+};
+use crate::svg::xml::{parse_xml, XmlElement};
+use crate::trapezoidator::FillRule;
+use std::collections::HashMap;
+
+/// An SVG document flattened into a display list: every fill and every stroke becomes one
+/// layer, in painting order, with its path already in document space.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SvgDocument {
+    /// The area of the document that is meant to be shown, from `viewBox`, or else from `width`
+    /// and `height`.
+    pub view_box: Option<Rectangle>,
+    pub layers: Vec<SvgLayer>,
+}
+
+impl SvgDocument {
+    /// The tight bounds of all layers, taking control points into account.
+    pub fn bounds(&self) -> Option<Rectangle> {
+        self.layers
+            .iter()
+            .filter_map(|layer| path_bounds(&layer.path))
+            .reduce(union)
+    }
+}
+
+/// One filled shape of a document. Strokes are converted into the outline they cover.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgLayer {
+    /// The outline of the layer in document space, without arcs.
+    pub path: Vec<PathCommand>,
+    pub fill_rule: FillRule,
+    pub paint: SvgPaint,
+    /// The opacity of the layer, including the `opacity` of all the groups it is in.
+    pub opacity: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SvgPaint {
+    Color(SvgColor),
+    /// The color the document is drawn with, which is up to the user of the document.
+    CurrentColor,
+    /// A gradient along the line from `from` to `to`, in document space.
+    LinearGradient {
+        from: Point,
+        to: Point,
+        stops: Vec<SvgStop>,
+    },
+    /// A gradient from `center` outwards, in document space.
+    RadialGradient {
+        center: Point,
+        radius: f64,
+        stops: Vec<SvgStop>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SvgStop {
+    pub offset: f32,
+    pub color: SvgColor,
+}
+
+/// Parses an SVG document into a display list.
+///
+/// The supported subset is the one icons use: `path`, `rect`, `circle`, `ellipse`, `line`,
+/// `polyline` and `polygon` shapes, grouped by `g` and reused by `use`, filled and stroked with
+/// colors and linear or radial gradients, styled by attributes, `style` attributes and simple
+/// `<style>` sheets. Clip paths, masks, patterns, filters and text are ignored.
+pub fn parse_svg(data: &str) -> Result<SvgDocument, String> {
+    let root = parse_xml(data)?;
+    if root.local_name() != "svg" {
+        return Err(format!(
+            "expected <svg> as root element, got <{}>",
+            root.name
+        ));
+    }
+
+    let mut ids = HashMap::new();
+    let mut style_sheet = StyleSheet::default();
+    collect_definitions(&root, &mut ids, &mut style_sheet);
+
+    let view_box = match root.attribute("viewBox").map(parse_numbers) {
+        Some(numbers) if numbers.len() == 4 => Some(Rectangle::new(
+            Point::new(numbers[0], numbers[1]),
+            Point::new(numbers[0] + numbers[2], numbers[1] + numbers[3]),
+        )),
+        _ => {
+            let width = root.attribute("width").and_then(parse_length);
+            let height = root.attribute("height").and_then(parse_length);
+            match (width, height) {
+                (Some(width), Some(height))
+                    if !root.attribute("width").unwrap().ends_with('%')
+                        && !root.attribute("height").unwrap().ends_with('%') =>
+                {
+                    Some(Rectangle::new(Point::origin(), Point::new(width, height)))
+                }
+                _ => None,
+            }
+        }
+    };
+
+    let mut builder = Builder {
+        ids,
+        style_sheet,
+        viewport: view_box
+            .map(|view_box| view_box.p_max - view_box.p_min)
+            .unwrap_or(Vector::new(100.0, 100.0)),
+        layers: Vec::new(),
+        use_depth: 0,
+    };
+    let style = builder.style(&root, &Style::default());
+    if style.display {
+        let opacity = style.opacity;
+        for child in &root.children {
+            builder.walk(child, AffineTransformation::identity(), &style, opacity)?;
+        }
+    }
+    Ok(SvgDocument {
+        view_box,
+        layers: builder.layers,
+    })
+}
+
+fn collect_definitions<'a>(
+    element: &'a XmlElement,
+    ids: &mut HashMap<&'a str, &'a XmlElement>,
+    style_sheet: &mut StyleSheet,
+) {
+    if let Some(id) = element.attribute("id") {
+        ids.entry(id).or_insert(element);
+    }
+    if element.local_name() == "style" {
+        style_sheet.extend(StyleSheet::parse(&element.text));
+    }
+    for child in &element.children {
+        collect_definitions(child, ids, style_sheet);
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+    Diagonal,
+}
+
+// uses can refer to groups that contain uses, so this keeps a cycle from recursing forever
+const MAX_USE_DEPTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Paint {
+    None,
+    Color(SvgColor),
+    CurrentColor,
+    Url(String, Option<Box<Paint>>),
+}
+
+impl Paint {
+    fn parse(value: &str) -> Option<Paint> {
+        let value = value.trim();
+        if let Some(rest) = value.strip_prefix("url(") {
+            let (url, fallback) = rest.split_once(')')?;
+            let id = url.trim().trim_matches(|char| char == '"' || char == '\'');
+            let fallback = match fallback.trim() {
+                "" => None,
+                fallback => Some(Box::new(Paint::parse(fallback)?)),
+            };
+            return Some(Paint::Url(id.trim_start_matches('#').to_string(), fallback));
+        }
+        match value {
+            "none" => Some(Paint::None),
+            "currentColor" | "currentcolor" => Some(Paint::CurrentColor),
+            _ => SvgColor::parse(value).map(Paint::Color),
+        }
+    }
+}
+
+// the computed style of an element; everything but `display` and `opacity` is inherited
+#[derive(Clone, Debug)]
+struct Style {
+    fill: Paint,
+    fill_opacity: f32,
+    fill_rule: FillRule,
+    stroke_paint: Paint,
+    stroke_opacity: f32,
+    stroke: StrokeStyle,
+    // the color `currentColor` refers to, when the document sets one
+    color: Option<SvgColor>,
+    visible: bool,
+    display: bool,
+    opacity: f32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fill: Paint::Color(SvgColor::BLACK),
+            fill_opacity: 1.0,
+            fill_rule: FillRule::NonZero,
+            stroke_paint: Paint::None,
+            stroke_opacity: 1.0,
+            stroke: StrokeStyle::default(),
+            color: None,
+            visible: true,
+            display: true,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Style {
+    fn set(&mut self, name: &str, value: &str) {
+        let value = value.trim();
+        if value == "inherit" {
+            return;
+        }
+        let opacity = |value: &str| parse_length(value).map(|value| value.clamp(0.0, 1.0) as f32);
+        match name {
+            "fill" => {
+                if let Some(paint) = Paint::parse(value) {
+                    self.fill = paint;
+                }
+            }
+            "fill-opacity" => self.fill_opacity = opacity(value).unwrap_or(self.fill_opacity),
+            "fill-rule" => {
+                self.fill_rule = match value {
+                    "evenodd" => FillRule::EvenOdd,
+                    _ => FillRule::NonZero,
+                }
+            }
+            "stroke" => {
+                if let Some(paint) = Paint::parse(value) {
+                    self.stroke_paint = paint;
+                }
+            }
+            "stroke-opacity" => self.stroke_opacity = opacity(value).unwrap_or(self.stroke_opacity),
+            "stroke-width" => self.stroke.width = parse_length(value).unwrap_or(self.stroke.width),
+            "stroke-linejoin" => {
+                self.stroke.line_join = match value {
+                    "round" => LineJoin::Round,
+                    "bevel" => LineJoin::Bevel,
+                    _ => LineJoin::Miter,
+                }
+            }
+            "stroke-linecap" => {
+                self.stroke.line_cap = match value {
+                    "round" => LineCap::Round,
+                    "square" => LineCap::Square,
+                    _ => LineCap::Butt,
+                }
+            }
+            "stroke-miterlimit" => {
+                self.stroke.miter_limit = parse_length(value).unwrap_or(self.stroke.miter_limit)
+            }
+            "color" => self.color = SvgColor::parse(value).or(self.color),
+            "visibility" => self.visible = value == "visible",
+            "display" => self.display = value != "none",
+            "opacity" => self.opacity = opacity(value).unwrap_or(self.opacity),
+            _ => {}
+        }
+    }
+}
+
+struct Builder<'a> {
+    ids: HashMap<&'a str, &'a XmlElement>,
+    style_sheet: StyleSheet,
+    viewport: Vector,
+    layers: Vec<SvgLayer>,
+    use_depth: usize,
+}
+
+impl<'a> Builder<'a> {
+    // presentation attributes lose to style sheets, which lose to the style attribute
+    fn style(&self, element: &XmlElement, parent: &Style) -> Style {
+        let mut style = Style {
+            display: true,
+            opacity: 1.0,
+            ..parent.clone()
+        };
+        for (name, value) in &element.attributes {
+            style.set(name, value);
+        }
+        for body in self.style_sheet.matching(element) {
+            for (name, value) in parse_declarations(body) {
+                style.set(name, value);
+            }
+        }
+        if let Some(declarations) = element.attribute("style") {
+            for (name, value) in parse_declarations(declarations) {
+                style.set(name, value);
+            }
+        }
+        style
+    }
+
+    fn walk(
+        &mut self,
+        element: &'a XmlElement,
+        transform: AffineTransformation,
+        parent: &Style,
+        opacity: f32,
+    ) -> Result<(), String> {
+        let style = self.style(element, parent);
+        if !style.display {
+            return Ok(());
+        }
+        let opacity = opacity * style.opacity;
+        let mut transform = match element.attribute("transform") {
+            Some(value) => transform.compose(parse_transform(value)?),
+            None => transform,
+        };
+        match element.local_name() {
+            "g" | "a" | "switch" => {
+                for child in &element.children {
+                    self.walk(child, transform, &style, opacity)?;
+                }
+            }
+            "svg" => {
+                transform = transform.compose(AffineTransformation::translation(Vector::new(
+                    self.length(element, "x", Axis::X),
+                    self.length(element, "y", Axis::Y),
+                )));
+                for child in &element.children {
+                    self.walk(child, transform, &style, opacity)?;
+                }
+            }
+            "use" => {
+                let Some(target) = element
+                    .attribute("href")
+                    .or_else(|| element.attribute("xlink:href"))
+                    .and_then(|href| href.strip_prefix('#'))
+                    .and_then(|id| self.ids.get(id).copied())
+                else {
+                    return Ok(());
+                };
+                if self.use_depth == MAX_USE_DEPTH {
+                    return Err("too many nested <use> elements".to_string());
+                }
+                transform = transform.compose(AffineTransformation::translation(Vector::new(
+                    self.length(element, "x", Axis::X),
+                    self.length(element, "y", Axis::Y),
+                )));
+                self.use_depth += 1;
+                let result = if target.local_name() == "symbol" {
+                    let symbol_style = self.style(target, &style);
+                    target.children.iter().try_for_each(|child| {
+                        self.walk(
+                            child,
+                            transform,
+                            &symbol_style,
+                            opacity * symbol_style.opacity,
+                        )
+                    })
+                } else {
+                    self.walk(target, transform, &style, opacity)
+                };
+                self.use_depth -= 1;
+                result?;
+            }
+            _ => {
+                if let Some(path) = self.shape(element)? {
+                    if style.visible {
+                        self.emit(&path, transform, &style, opacity);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // a length attribute, with percentages relative to the size of the viewport along `axis`
+    fn length(&self, element: &XmlElement, name: &str, axis: Axis) -> f64 {
+        element
+            .attribute(name)
+            .map_or(0.0, |value| self.resolve_length(value, axis))
+    }
+
+    fn resolve_length(&self, value: &str, axis: Axis) -> f64 {
+        let length = parse_length(value).unwrap_or(0.0);
+        if !value.trim_end().ends_with('%') {
+            return length;
+        }
+        length
+            * match axis {
+                Axis::X => self.viewport.x,
+                Axis::Y => self.viewport.y,
+                Axis::Diagonal => self.viewport.length() / 2f64.sqrt(),
+            }
+    }
+
+    fn shape(&self, element: &XmlElement) -> Result<Option<Vec<PathCommand>>, String> {
+        use Axis::*;
+        let length = |name: &str, axis: Axis| self.length(element, name, axis);
+        let path = match element.local_name() {
+            "path" => match element.attribute("d") {
+                Some(d) => parse_svg_path(d.as_bytes())?,
+                None => return Ok(None),
+            },
+            "rect" => {
+                let (x, y) = (length("x", X), length("y", Y));
+                let (width, height) = (length("width", X), length("height", Y));
+                if width <= 0.0 || height <= 0.0 {
+                    return Ok(None);
+                }
+                let (mut rx, mut ry) = (length("rx", X), length("ry", Y));
+                match (
+                    element.attribute("rx").is_some(),
+                    element.attribute("ry").is_some(),
+                ) {
+                    (true, false) => ry = rx,
+                    (false, true) => rx = ry,
+                    _ => {}
+                }
+                let (rx, ry) = (rx.clamp(0.0, width / 2.0), ry.clamp(0.0, height / 2.0));
+                if rx == 0.0 || ry == 0.0 {
+                    vec![
+                        PathCommand::MoveTo(Point::new(x, y)),
+                        PathCommand::LineTo(Point::new(x + width, y)),
+                        PathCommand::LineTo(Point::new(x + width, y + height)),
+                        PathCommand::LineTo(Point::new(x, y + height)),
+                        PathCommand::Close,
+                    ]
+                } else {
+                    let radius = Point::new(rx, ry);
+                    let arc = |to: Point| PathCommand::ArcTo(to, radius, 0.0, false, true);
+                    vec![
+                        PathCommand::MoveTo(Point::new(x + rx, y)),
+                        PathCommand::LineTo(Point::new(x + width - rx, y)),
+                        arc(Point::new(x + width, y + ry)),
+                        PathCommand::LineTo(Point::new(x + width, y + height - ry)),
+                        arc(Point::new(x + width - rx, y + height)),
+                        PathCommand::LineTo(Point::new(x + rx, y + height)),
+                        arc(Point::new(x, y + height - ry)),
+                        PathCommand::LineTo(Point::new(x, y + ry)),
+                        arc(Point::new(x + rx, y)),
+                        PathCommand::Close,
+                    ]
+                }
+            }
+            "circle" | "ellipse" => {
+                let center = Point::new(length("cx", X), length("cy", Y));
+                let (rx, ry) = if element.local_name() == "circle" {
+                    let r = length("r", Diagonal);
+                    (r, r)
+                } else {
+                    (length("rx", X), length("ry", Y))
+                };
+                if rx <= 0.0 || ry <= 0.0 {
+                    return Ok(None);
+                }
+                let radius = Point::new(rx, ry);
+                let arc = |to: Point| PathCommand::ArcTo(to, radius, 0.0, false, true);
+                vec![
+                    PathCommand::MoveTo(center + Vector::new(rx, 0.0)),
+                    arc(center + Vector::new(0.0, ry)),
+                    arc(center + Vector::new(-rx, 0.0)),
+                    arc(center + Vector::new(0.0, -ry)),
+                    arc(center + Vector::new(rx, 0.0)),
+                    PathCommand::Close,
+                ]
+            }
+            "line" => vec![
+                PathCommand::MoveTo(Point::new(length("x1", X), length("y1", Y))),
+                PathCommand::LineTo(Point::new(length("x2", X), length("y2", Y))),
+            ],
+            "polyline" | "polygon" => {
+                let numbers = parse_numbers(element.attribute("points").unwrap_or(""));
+                let mut points = numbers
+                    .chunks_exact(2)
+                    .map(|pair| Point::new(pair[0], pair[1]));
+                let Some(first) = points.next() else {
+                    return Ok(None);
+                };
+                let mut path = vec![PathCommand::MoveTo(first)];
+                path.extend(points.map(PathCommand::LineTo));
+                if element.local_name() == "polygon" {
+                    path.push(PathCommand::Close);
+                }
+                path
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(arcs_to_cubics(&path)))
+    }
+
+    fn emit(
+        &mut self,
+        path: &[PathCommand],
+        transform: AffineTransformation,
+        style: &Style,
+        opacity: f32,
+    ) {
+        let Some(bounds) = path_bounds(path) else {
+            return;
+        };
+        let has_area = bounds.p_min.x < bounds.p_max.x && bounds.p_min.y < bounds.p_max.y;
+        let fill = has_area
+            .then(|| self.paint(&style.fill, style, bounds, transform))
+            .flatten();
+        if let Some(paint) = fill {
+            self.layers.push(SvgLayer {
+                path: path
+                    .iter()
+                    .map(|command| command.transform(&transform))
+                    .collect(),
+                fill_rule: style.fill_rule,
+                paint,
+                opacity: opacity * style.fill_opacity,
+            });
+        }
+        if style.stroke.width <= 0.0 {
+            return;
+        }
+        if let Some(paint) = self.paint(&style.stroke_paint, style, bounds, transform) {
+            // flatten finely enough that the outline stays smooth once it is transformed
+            let scale = determinant(transform.xy).abs().sqrt().max(1e-6);
+            let outline = stroke_path(path, &style.stroke, 0.01 / scale);
+            self.layers.push(SvgLayer {
+                path: outline
+                    .iter()
+                    .map(|command| command.transform(&transform))
+                    .collect(),
+                fill_rule: FillRule::NonZero,
+                paint,
+                opacity: opacity * style.stroke_opacity,
+            });
+        }
+    }
+
+    fn paint(
+        &self,
+        paint: &Paint,
+        style: &Style,
+        bounds: Rectangle,
+        transform: AffineTransformation,
+    ) -> Option<SvgPaint> {
+        match paint {
+            Paint::None => None,
+            Paint::Color(color) => Some(SvgPaint::Color(*color)),
+            Paint::CurrentColor => {
+                Some(style.color.map_or(SvgPaint::CurrentColor, SvgPaint::Color))
+            }
+            Paint::Url(id, fallback) => {
+                let gradient = self
+                    .ids
+                    .get(id.as_str())
+                    .and_then(|element| self.gradient(element, bounds, transform));
+                match (gradient, fallback) {
+                    (Some(gradient), _) => gradient,
+                    (None, Some(fallback)) => self.paint(fallback, style, bounds, transform),
+                    (None, None) => None,
+                }
+            }
+        }
+    }
+
+    // the outer option is whether there is a gradient, the inner one whether it paints anything
+    fn gradient(
+        &self,
+        element: &'a XmlElement,
+        bounds: Rectangle,
+        transform: AffineTransformation,
+    ) -> Option<Option<SvgPaint>> {
+        let name = element.local_name();
+        if name != "linearGradient" && name != "radialGradient" {
+            return None;
+        }
+        // gradients inherit attributes and stops from the gradients they link to
+        let mut chain = vec![element];
+        while chain.len() < MAX_USE_DEPTH {
+            let Some(next) = chain
+                .last()
+                .unwrap()
+                .attribute("href")
+                .or_else(|| chain.last().unwrap().attribute("xlink:href"))
+                .and_then(|href| href.strip_prefix('#'))
+                .and_then(|id| self.ids.get(id).copied())
+            else {
+                break;
+            };
+            chain.push(next);
+        }
+        let attribute = |name: &str| chain.iter().find_map(|element| element.attribute(name));
+
+        let stops = chain
+            .iter()
+            .map(|element| self.stops(element))
+            .find(|stops| !stops.is_empty())
+            .unwrap_or_default();
+        match stops.len() {
+            0 => return Some(None),
+            1 => return Some(Some(SvgPaint::Color(stops[0].color))),
+            _ => {}
+        }
+
+        let user_space = attribute("gradientUnits") == Some("userSpaceOnUse");
+        let mut gradient_transform = if user_space {
+            transform
+        } else {
+            let size = bounds.p_max - bounds.p_min;
+            transform
+                .compose(AffineTransformation::translation(bounds.p_min.to_vector()))
+                .compose(AffineTransformation::scaling(size))
+        };
+        if let Some(value) = attribute("gradientTransform") {
+            gradient_transform = gradient_transform.compose(parse_transform(value).ok()?);
+        }
+        // bounding box units are fractions of the box, so percentages are too
+        let coordinate = |name: &str, default: &str, axis: Axis| -> f64 {
+            let value = attribute(name).unwrap_or(default);
+            if user_space {
+                self.resolve_length(value, axis)
+            } else {
+                parse_length(value).unwrap_or(0.0)
+            }
+        };
+        Some(Some(if name == "linearGradient" {
+            SvgPaint::LinearGradient {
+                from: Point::new(
+                    coordinate("x1", "0%", Axis::X),
+                    coordinate("y1", "0%", Axis::Y),
+                )
+                .transform(&gradient_transform),
+                to: Point::new(
+                    coordinate("x2", "100%", Axis::X),
+                    coordinate("y2", "0%", Axis::Y),
+                )
+                .transform(&gradient_transform),
+                stops,
+            }
+        } else {
+            SvgPaint::RadialGradient {
+                center: Point::new(
+                    coordinate("cx", "50%", Axis::X),
+                    coordinate("cy", "50%", Axis::Y),
+                )
+                .transform(&gradient_transform),
+                radius: coordinate("r", "50%", Axis::Diagonal)
+                    * determinant(gradient_transform.xy).abs().sqrt(),
+                stops,
+            }
+        }))
+    }
+
+    fn stops(&self, gradient: &XmlElement) -> Vec<SvgStop> {
+        let mut stops: Vec<SvgStop> = Vec::new();
+        for element in gradient
+            .children
+            .iter()
+            .filter(|child| child.local_name() == "stop")
+        {
+            let mut color = SvgColor::BLACK;
+            let mut opacity = 1.0;
+            let mut set = |name: &str, value: &str| match name {
+                "stop-color" => color = SvgColor::parse(value).unwrap_or(color),
+                "stop-opacity" => {
+                    opacity = parse_length(value).map_or(opacity, |value| value.clamp(0.0, 1.0))
+                }
+                _ => {}
+            };
+            for (name, value) in &element.attributes {
+                set(name, value);
+            }
+            for body in self.style_sheet.matching(element) {
+                parse_declarations(body).for_each(|(name, value)| set(name, value));
+            }
+            if let Some(declarations) = element.attribute("style") {
+                parse_declarations(declarations).for_each(|(name, value)| set(name, value));
+            }
+            let offset = element
+                .attribute("offset")
+                .and_then(parse_length)
+                .unwrap_or(0.0)
+                .clamp(0.0, 1.0) as f32;
+            // offsets can not go back
+            let offset = stops.last().map_or(offset, |last| offset.max(last.offset));
+            stops.push(SvgStop {
+                offset,
+                color: color.with_alpha(color.a * opacity as f32),
+            });
+        }
+        stops
+    }
+}
+
+fn determinant(t: LinearTransformation) -> f64 {
+    t.x.x * t.y.y - t.x.y * t.y.x
+}
+
+// arcs don't survive non-uniform scaling and skewing, cubics do
+fn arcs_to_cubics(path: &[PathCommand]) -> Vec<PathCommand> {
+    let mut out = Vec::with_capacity(path.len());
+    let mut first = Point::origin();
+    let mut last = Point::origin();
+    for command in path {
+        match *command {
+            PathCommand::MoveTo(p) => {
+                first = p;
+                last = p;
+                out.push(*command);
+            }
+            PathCommand::LineTo(p)
+            | PathCommand::QuadraticTo(_, p)
+            | PathCommand::CubicTo(_, _, p) => {
+                last = p;
+                out.push(*command);
+            }
+            PathCommand::ArcTo(p, radius, x_axis_rotation, large_arc, sweep) => {
+                let arc = Arc::new(last, p, radius, x_axis_rotation, large_arc, sweep);
+                out.extend(
+                    arc.to_cubics()
+                        .into_iter()
+                        .map(|cubic| PathCommand::CubicTo(cubic.p1, cubic.p2, cubic.p3)),
+                );
+                last = p;
+            }
+            PathCommand::Close => {
+                last = first;
+                out.push(*command);
+            }
+        }
+    }
+    out
+}
+
+fn path_bounds(path: &[PathCommand]) -> Option<Rectangle> {
+    let mut points = path.iter().flat_map(|command| match *command {
+        PathCommand::MoveTo(p) | PathCommand::LineTo(p) => vec![p],
+        PathCommand::ArcTo(p, ..) => vec![p],
+        PathCommand::QuadraticTo(p1, p) => vec![p1, p],
+        PathCommand::CubicTo(p1, p2, p) => vec![p1, p2, p],
+        PathCommand::Close => vec![],
+    });
+    let first = points.next()?;
+    Some(points.fold(Rectangle::new(first, first), |bounds, p| {
+        union(bounds, Rectangle::new(p, p))
+    }))
+}
+
+fn union(a: Rectangle, b: Rectangle) -> Rectangle {
+    Rectangle::new(
+        Point::new(a.p_min.x.min(b.p_min.x), a.p_min.y.min(b.p_min.y)),
+        Point::new(a.p_max.x.max(b.p_max.x), a.p_max.y.max(b.p_max.y)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::internal_iter::InternalIterator;
+    use crate::path::PathIterator;
+    use crate::trapezoidator::Trapezoidator;
+
+    fn area(layer: &SvgLayer) -> f64 {
+        let mut trapezoidator = Trapezoidator::new();
+        trapezoidator.fill_rule = layer.fill_rule;
+        let mut area = 0.0;
+        trapezoidator
+            .trapezoidate(layer.path.iter().cloned().linearize(0.001))
+            .unwrap()
+            .for_each(&mut |trapezoid| {
+                let width = (trapezoid.xs[1] - trapezoid.xs[0]) as f64;
+                let height = (trapezoid.ys[2] - trapezoid.ys[0] + trapezoid.ys[3] - trapezoid.ys[1])
+                    as f64
+                    / 2.0;
+                area += width * height;
+                true
+            });
+        area
+    }
+
+    #[test]
+    fn shapes_and_colors() {
+        let document = parse_svg(
+            r##"<?xml version="1.0"?>
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+                <style>.accent { fill: #ff0000 } /* comment */</style>
+                <rect width="10" height="10" fill="blue"/>
+                <circle class="accent" cx="12" cy="12" r="5"/>
+                <g fill="currentColor" opacity="0.5" transform="translate(10 0)">
+                    <path d="M0 0h4v4H0z" style="fill-opacity: .5"/>
+                </g>
+            </svg>"##,
+        )
+        .unwrap();
+        assert_eq!(
+            document.view_box,
+            Some(Rectangle::new(Point::new(0.0, 0.0), Point::new(24.0, 24.0)))
+        );
+        assert_eq!(document.layers.len(), 3);
+        assert_eq!(
+            document.layers[0].paint,
+            SvgPaint::Color(SvgColor::rgb(0, 0, 255))
+        );
+        assert!((area(&document.layers[0]) - 100.0).abs() < 1e-3);
+        assert_eq!(
+            document.layers[1].paint,
+            SvgPaint::Color(SvgColor::rgb(255, 0, 0))
+        );
+        assert!((area(&document.layers[1]) - 25.0 * std::f64::consts::PI).abs() < 0.05);
+        assert_eq!(document.layers[2].paint, SvgPaint::CurrentColor);
+        assert_eq!(document.layers[2].opacity, 0.25);
+        assert_eq!(
+            path_bounds(&document.layers[2].path),
+            Some(Rectangle::new(Point::new(10.0, 0.0), Point::new(14.0, 4.0)))
+        );
+    }
+
+    #[test]
+    fn fill_rules() {
+        let rings = r#"M0 0h10v10H0z M2 2h6v6H2z"#;
+        let document = parse_svg(&format!(
+            r#"<svg><path d="{rings}"/><path d="{rings}" fill-rule="evenodd"/></svg>"#
+        ))
+        .unwrap();
+        assert!((area(&document.layers[0]) - 100.0).abs() < 1e-3);
+        assert!((area(&document.layers[1]) - 64.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn strokes() {
+        let document = parse_svg(
+            r#"<svg viewBox="0 0 20 20">
+                <rect x="5" y="5" width="10" height="10" fill="none" stroke="black" stroke-width="2"/>
+                <line x1="0" y1="0" x2="10" y2="0" stroke="black" stroke-linecap="square"/>
+            </svg>"#,
+        )
+        .unwrap();
+        assert_eq!(document.layers.len(), 2);
+        // the band from 4 to 16 minus the hole from 6 to 14
+        assert!((area(&document.layers[0]) - 80.0).abs() < 1e-3);
+        // square caps add half the width at both ends
+        assert!((area(&document.layers[1]) - 11.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn gradients_and_uses() {
+        let document = parse_svg(
+            r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 10 10">
+                <defs>
+                    <linearGradient id="base">
+                        <stop offset="0" stop-color="#000"/>
+                        <stop offset="100%" stop-color="#fff" stop-opacity="0.5"/>
+                    </linearGradient>
+                    <linearGradient id="vertical" xlink:href="#base" x2="0" y2="1"/>
+                    <rect id="box" x="2" y="2" width="4" height="4" fill="url(#vertical)"/>
+                </defs>
+                <use xlink:href="#box" x="1"/>
+                <rect width="1" height="1" fill="url(#missing) green"/>
+            </svg>"##,
+        )
+        .unwrap();
+        assert_eq!(document.layers.len(), 2);
+        assert_eq!(
+            document.layers[0].paint,
+            SvgPaint::LinearGradient {
+                from: Point::new(3.0, 2.0),
+                to: Point::new(3.0, 6.0),
+                stops: vec![
+                    SvgStop {
+                        offset: 0.0,
+                        color: SvgColor::BLACK
+                    },
+                    SvgStop {
+                        offset: 1.0,
+                        color: SvgColor::rgb(255, 255, 255).with_alpha(0.5)
+                    },
+                ],
+            }
+        );
+        assert_eq!(
+            document.layers[1].paint,
+            SvgPaint::Color(SvgColor::rgb(0, 128, 0))
+        );
+    }
+
+    #[test]
+    fn path_syntax() {
+        let path = parse_svg_path(b"M1e1-5.5.5.5L+2,2S4 4 5 5z").unwrap();
+        assert_eq!(
+            path,
+            [
+                PathCommand::MoveTo(Point::new(10.0, -5.5)),
+                PathCommand::LineTo(Point::new(0.5, 0.5)),
+                PathCommand::LineTo(Point::new(2.0, 2.0)),
+                PathCommand::CubicTo(
+                    Point::new(2.0, 2.0),
+                    Point::new(4.0, 4.0),
+                    Point::new(5.0, 5.0)
+                ),
+                PathCommand::Close,
+            ]
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! A parser for the subset of SVG that icons use, which turns a document into a list of filled
+//! paths with their paint.
+
+mod document;
+mod path;
+mod stroke;
+mod style;
+mod xml;
+
+pub use self::document::{parse_svg, SvgDocument, SvgLayer, SvgPaint, SvgStop};
+pub use self::path::parse_svg_path;
+pub use self::stroke::{stroke_path, LineCap, LineJoin, StrokeStyle};
+pub use self::style::SvgColor;
+pub use self::xml::{parse_xml, XmlElement};
@@ -0,0 +1,347 @@
+use crate::geometry::{Point, Vector};
+use crate::path::PathCommand;
+
+/// Parses the `d` attribute of an SVG `<path>` element.
+pub fn parse_svg_path(path: &[u8]) -> Result<Vec<PathCommand>, String> {
+    #[derive(Debug, Default)]
+    enum Cmd {
+        #[default]
+        Unknown,
+        Move(bool),
+        Hor(bool),
+        Vert(bool),
+        Line(bool),
+        Arc(bool),
+        Cubic(bool),
+        SmoothCubic(bool),
+        Quadratic(bool),
+        SmoothQuadratic(bool),
+        Close,
+    }
+
+    #[derive(Default)]
+    struct ParseState {
+        cmd: Cmd,
+        expect_nums: usize,
+        chain: bool,
+        nums: [f64; 7],
+        num_count: usize,
+        first_pt: Point,
+        last_pt: Point,
+        // the second control point of the last curve, for the smooth curves to reflect
+        last_ctrl: Option<Point>,
+        out: Vec<PathCommand>,
+        num_state: Option<NumState>,
+    }
+
+    #[derive(Debug)]
+    struct NumState {
+        num: f64,
+        mul: f64,
+        has_dot: bool,
+        // the sign, the value and whether there were any digits yet
+        exp: Option<(f64, i32, bool)>,
+    }
+
+    impl NumState {
+        fn new_pos(v: f64) -> Self {
+            Self {
+                num: v,
+                mul: 1.0,
+                has_dot: false,
+                exp: None,
+            }
+        }
+        fn new_min() -> Self {
+            Self {
+                num: 0.0,
+                mul: -1.0,
+                has_dot: false,
+                exp: None,
+            }
+        }
+        fn finalize(self) -> f64 {
+            match self.exp {
+                Some((sign, exp, _)) => self.num * self.mul * 10f64.powi(sign as i32 * exp),
+                None => self.num * self.mul,
+            }
+        }
+        fn add_digit(&mut self, digit: f64) {
+            if let Some((_, exp, has_digits)) = &mut self.exp {
+                *exp = *exp * 10 + digit as i32;
+                *has_digits = true;
+                return;
+            }
+            self.num *= 10.0;
+            self.num += digit;
+            if self.has_dot {
+                self.mul *= 0.1;
+            }
+        }
+    }
+
+    impl ParseState {
+        fn next_cmd(&mut self, cmd: Cmd) -> Result<(), String> {
+            self.finalize_cmd()?;
+            self.chain = false;
+            self.expect_nums = match cmd {
+                Cmd::Unknown => panic!(),
+                Cmd::Move(_) => 2,
+                Cmd::Hor(_) => 1,
+                Cmd::Vert(_) => 1,
+                Cmd::Line(_) => 2,
+                Cmd::Cubic(_) => 6,
+                Cmd::SmoothCubic(_) => 4,
+                Cmd::Arc(_) => 7,
+                Cmd::Quadratic(_) => 4,
+                Cmd::SmoothQuadratic(_) => 2,
+                Cmd::Close => 0,
+            };
+            self.cmd = cmd;
+            Ok(())
+        }
+
+        fn add_min(&mut self) -> Result<(), String> {
+            if let Some(NumState {
+                exp: Some((sign, _, false)),
+                ..
+            }) = &mut self.num_state
+            {
+                *sign = -1.0;
+                return Ok(());
+            }
+            if self.num_state.is_some() {
+                self.finalize_num();
+            }
+            if self.expect_nums == self.num_count {
+                self.finalize_cmd()?;
+            }
+            if self.expect_nums == 0 {
+                return Err("Unexpected minus".to_string());
+            }
+            self.num_state = Some(NumState::new_min());
+            Ok(())
+        }
+
+        fn add_plus(&mut self) -> Result<(), String> {
+            match &self.num_state {
+                Some(NumState {
+                    exp: Some((_, _, false)),
+                    ..
+                }) => Ok(()),
+                _ => self.whitespace(),
+            }
+        }
+
+        fn add_exp(&mut self) -> Result<(), String> {
+            match &mut self.num_state {
+                Some(num_state) if num_state.exp.is_none() => {
+                    num_state.exp = Some((1.0, 0, false));
+                    Ok(())
+                }
+                _ => Err("Unexpected exponent".to_string()),
+            }
+        }
+
+        fn add_digit(&mut self, digit: f64) -> Result<(), String> {
+            if let Some(num_state) = &mut self.num_state {
+                num_state.add_digit(digit);
+            } else {
+                if self.expect_nums == self.num_count {
+                    self.finalize_cmd()?;
+                }
+                if self.expect_nums == 0 {
+                    return Err("Unexpected digit".to_string());
+                }
+                self.num_state = Some(NumState::new_pos(digit))
+            }
+            Ok(())
+        }
+
+        fn add_dot(&mut self) -> Result<(), String> {
+            if let Some(num_state) = &mut self.num_state {
+                if num_state.has_dot || num_state.exp.is_some() {
+                    self.finalize_num();
+                    self.add_digit(0.0)?;
+                    self.add_dot()?;
+                    return Ok(());
+                }
+                num_state.has_dot = true;
+            } else {
+                self.add_digit(0.0)?;
+                self.add_dot()?;
+            }
+            Ok(())
+        }
+
+        fn finalize_num(&mut self) {
+            if let Some(num_state) = self.num_state.take() {
+                self.nums[self.num_count] = num_state.finalize();
+                self.num_count += 1;
+            }
+        }
+
+        fn whitespace(&mut self) -> Result<(), String> {
+            self.finalize_num();
+            if self.expect_nums == self.num_count {
+                self.finalize_cmd()?;
+            }
+            Ok(())
+        }
+
+        fn point(&self, abs: bool, x: f64, y: f64) -> Point {
+            if abs {
+                Point { x, y }
+            } else {
+                self.last_pt + Vector { x, y }
+            }
+        }
+
+        // the first control point of a smooth curve is the last one mirrored around the start
+        fn reflected_ctrl(&self) -> Point {
+            match self.last_ctrl {
+                Some(ctrl) => self.last_pt + (self.last_pt - ctrl),
+                None => self.last_pt,
+            }
+        }
+
+        fn finalize_cmd(&mut self) -> Result<(), String> {
+            self.finalize_num();
+            if self.chain && self.num_count == 0 {
+                return Ok(());
+            }
+            if self.expect_nums != self.num_count {
+                return Err(format!(
+                    "SVG Path command {:?} expected {} points, got {}",
+                    self.cmd, self.expect_nums, self.num_count
+                ));
+            }
+            let nums = self.nums;
+            let mut ctrl = None;
+            match self.cmd {
+                Cmd::Unknown => (),
+                Cmd::Move(abs) => {
+                    self.last_pt = self.point(abs, nums[0], nums[1]);
+                    self.first_pt = self.last_pt;
+                    self.out.push(PathCommand::MoveTo(self.last_pt));
+                    // more coordinate pairs after a move are lines
+                    self.cmd = Cmd::Line(abs);
+                }
+                Cmd::Hor(abs) => {
+                    self.last_pt = if abs {
+                        Point {
+                            x: nums[0],
+                            y: self.last_pt.y,
+                        }
+                    } else {
+                        self.last_pt + Vector { x: nums[0], y: 0.0 }
+                    };
+                    self.out.push(PathCommand::LineTo(self.last_pt));
+                }
+                Cmd::Vert(abs) => {
+                    self.last_pt = if abs {
+                        Point {
+                            x: self.last_pt.x,
+                            y: nums[0],
+                        }
+                    } else {
+                        self.last_pt + Vector { x: 0.0, y: nums[0] }
+                    };
+                    self.out.push(PathCommand::LineTo(self.last_pt));
+                }
+                Cmd::Line(abs) => {
+                    self.last_pt = self.point(abs, nums[0], nums[1]);
+                    self.out.push(PathCommand::LineTo(self.last_pt));
+                }
+                Cmd::Cubic(abs) => {
+                    let p1 = self.point(abs, nums[0], nums[1]);
+                    let p2 = self.point(abs, nums[2], nums[3]);
+                    self.last_pt = self.point(abs, nums[4], nums[5]);
+                    self.out.push(PathCommand::CubicTo(p1, p2, self.last_pt));
+                    ctrl = Some(p2);
+                }
+                Cmd::SmoothCubic(abs) => {
+                    let p1 = match self.out.last() {
+                        Some(PathCommand::CubicTo(..)) => self.reflected_ctrl(),
+                        _ => self.last_pt,
+                    };
+                    let p2 = self.point(abs, nums[0], nums[1]);
+                    self.last_pt = self.point(abs, nums[2], nums[3]);
+                    self.out.push(PathCommand::CubicTo(p1, p2, self.last_pt));
+                    ctrl = Some(p2);
+                }
+                Cmd::Arc(abs) => {
+                    self.last_pt = self.point(abs, nums[5], nums[6]);
+                    self.out.push(PathCommand::ArcTo(
+                        self.last_pt,
+                        Point {
+                            x: nums[0],
+                            y: nums[1],
+                        },
+                        nums[2],
+                        nums[3] != 0.0,
+                        nums[4] != 0.0,
+                    ));
+                }
+                Cmd::Quadratic(abs) => {
+                    let p1 = self.point(abs, nums[0], nums[1]);
+                    self.last_pt = self.point(abs, nums[2], nums[3]);
+                    self.out.push(PathCommand::QuadraticTo(p1, self.last_pt));
+                    ctrl = Some(p1);
+                }
+                Cmd::SmoothQuadratic(abs) => {
+                    let p1 = match self.out.last() {
+                        Some(PathCommand::QuadraticTo(..)) => self.reflected_ctrl(),
+                        _ => self.last_pt,
+                    };
+                    self.last_pt = self.point(abs, nums[0], nums[1]);
+                    self.out.push(PathCommand::QuadraticTo(p1, self.last_pt));
+                    ctrl = Some(p1);
+                }
+                Cmd::Close => {
+                    self.out.push(PathCommand::Close);
+                    self.last_pt = self.first_pt;
+                }
+            }
+            self.last_ctrl = ctrl;
+            self.num_count = 0;
+            self.chain = true;
+            Ok(())
+        }
+    }
+
+    let mut state = ParseState::default();
+    for &byte in path {
+        match byte {
+            b'M' => state.next_cmd(Cmd::Move(true))?,
+            b'm' => state.next_cmd(Cmd::Move(false))?,
+            b'Q' => state.next_cmd(Cmd::Quadratic(true))?,
+            b'q' => state.next_cmd(Cmd::Quadratic(false))?,
+            b'T' => state.next_cmd(Cmd::SmoothQuadratic(true))?,
+            b't' => state.next_cmd(Cmd::SmoothQuadratic(false))?,
+            b'C' => state.next_cmd(Cmd::Cubic(true))?,
+            b'c' => state.next_cmd(Cmd::Cubic(false))?,
+            b'S' => state.next_cmd(Cmd::SmoothCubic(true))?,
+            b's' => state.next_cmd(Cmd::SmoothCubic(false))?,
+            b'H' => state.next_cmd(Cmd::Hor(true))?,
+            b'h' => state.next_cmd(Cmd::Hor(false))?,
+            b'V' => state.next_cmd(Cmd::Vert(true))?,
+            b'v' => state.next_cmd(Cmd::Vert(false))?,
+            b'L' => state.next_cmd(Cmd::Line(true))?,
+            b'l' => state.next_cmd(Cmd::Line(false))?,
+            b'A' => state.next_cmd(Cmd::Arc(true))?,
+            b'a' => state.next_cmd(Cmd::Arc(false))?,
+            b'Z' | b'z' => state.next_cmd(Cmd::Close)?,
+            b'e' | b'E' => state.add_exp()?,
+            b'-' => state.add_min()?,
+            b'+' => state.add_plus()?,
+            b'0'..=b'9' => state.add_digit((byte - b'0') as f64)?,
+            b'.' => state.add_dot()?,
+            b',' | b' ' | b'\r' | b'\n' | b'\t' => state.whitespace()?,
+            x => return Err(format!("Unexpected character {} - {}", x, x as char)),
+        }
+    }
+    state.finalize_cmd()?;
+
+    Ok(state.out)
+}
@@ -0,0 +1,152 @@
+use crate::geometry::Point;
+use crate::internal_iter::InternalIterator;
+use crate::path::{LinePathCommand, PathCommand, PathIterator};
+use bender_clipper::{Clipper, Operation};
+use bender_geometry::linear_path::Command;
+use bender_offsetter::{CapKind, JoinKind, Options};
+
+/// How the outline of a stroke is drawn, in the units of the path it strokes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f64,
+    pub line_join: LineJoin,
+    pub line_cap: LineCap,
+    /// The longest a miter join can be, as a multiple of the stroke width, before it is beveled
+    pub miter_limit: f64,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            line_join: LineJoin::Miter,
+            line_cap: LineCap::Butt,
+            miter_limit: 4.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+/// Returns the outline of the stroke of `path` as closed contours that don't intersect, which
+/// cover the stroke when filled with the non-zero fill rule. Curves are flattened with tolerance `tolerance`.
+pub fn stroke_path(path: &[PathCommand], style: &StrokeStyle, tolerance: f64) -> Vec<PathCommand> {
+    let distance = (style.width / 2.0) as f32;
+    let options = Options {
+        join_kind: match style.line_join {
+            LineJoin::Miter => JoinKind::Miter,
+            LineJoin::Round => JoinKind::Round,
+            LineJoin::Bevel => JoinKind::Bevel,
+        },
+        cap_kind: match style.line_cap {
+            LineCap::Butt => CapKind::Butt,
+            LineCap::Round => CapKind::Round,
+            LineCap::Square => CapKind::Square,
+        },
+        // bender measures the miter against the whole width, svg against half of it
+        miter_limit: (style.miter_limit / 2.0) as f32,
+        arc_tolerance: tolerance as f32,
+    };
+
+    let mut subpaths: Vec<(Vec<bender_geometry::Point>, bool)> = Vec::new();
+    path.iter()
+        .cloned()
+        .linearize(tolerance)
+        .for_each(&mut |command| {
+            match command {
+                LinePathCommand::MoveTo(p) => subpaths.push((vec![to_bender(p)], false)),
+                LinePathCommand::LineTo(p) => {
+                    if subpaths.is_empty() {
+                        subpaths.push((Vec::new(), false));
+                    }
+                    let (points, _) = subpaths.last_mut().unwrap();
+                    let p = to_bender(p);
+                    if points.last() != Some(&p) {
+                        points.push(p);
+                    }
+                }
+                LinePathCommand::Close => {
+                    if let Some((points, closed)) = subpaths.last_mut() {
+                        *closed = true;
+                        let first = points.first().copied();
+                        // a new subpath starts where the closed one started
+                        let restart = first.map(|first| (vec![first], false));
+                        if points.len() > 1 && points.last() == first.as_ref() {
+                            points.pop();
+                        }
+                        subpaths.extend(restart);
+                    }
+                }
+            }
+            true
+        });
+
+    let mut polygons = Vec::new();
+    for (points, closed) in subpaths {
+        if closed && points.len() >= 3 {
+            let polygon = bender_geometry::Polygon { vertices: points };
+            polygons.push(bender_offsetter::offset_polygon(
+                &polygon, distance, options,
+            ));
+            let reversed = bender_geometry::Polygon {
+                vertices: Iterator::collect(polygon.vertices.iter().rev().copied()),
+            };
+            polygons.push(bender_offsetter::offset_polygon(
+                &reversed, distance, options,
+            ));
+        } else if points.len() >= 2 {
+            let polyline = bender_geometry::Polyline { vertices: points };
+            polygons.push(bender_offsetter::offset_polyline(
+                &polyline, distance, options,
+            ));
+        }
+    }
+
+    // the offset polygons loop back on themselves at inner joins, and overlap each other, which
+    // the trapezoidator can't handle, so merge them into a clean outline first
+    let mut outline = Vec::new();
+    bender_internal_iter::InternalIterator::for_each(
+        Clipper::new().clip_polygons(
+            Operation::Union,
+            &polygons,
+            &[],
+            bender_clipper::Options::default(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+        ),
+        &mut |command| {
+            outline.push(match command {
+                Command::MoveTo(p) => PathCommand::MoveTo(from_bender(p)),
+                Command::LineTo(p) => PathCommand::LineTo(from_bender(p)),
+                Command::Close => PathCommand::Close,
+            });
+            true
+        },
+    );
+    outline
+}
+
+fn to_bender(p: Point) -> bender_geometry::Point {
+    bender_geometry::Point::new(p.x as f32, p.y as f32)
+}
+
+fn from_bender(p: bender_geometry::Point) -> Point {
+    Point::new(p.x() as f64, p.y() as f64)
+}
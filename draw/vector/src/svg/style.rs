@@ -0,0 +1,293 @@
+use crate::geometry::{AffineTransformation, LinearTransformation, Vector};
+use crate::svg::xml::XmlElement;
+
+/// A color with straight (not premultiplied) alpha, each component from 0 to 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SvgColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl SvgColor {
+    pub const BLACK: SvgColor = SvgColor::rgb(0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self {
+            r: r as f32 / 255.0,
+            g: g as f32 / 255.0,
+            b: b as f32 / 255.0,
+            a: 1.0,
+        }
+    }
+
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Parses the color syntaxes SVG files use: `#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()`
+    /// and the common color names.
+    pub fn parse(value: &str) -> Option<SvgColor> {
+        let value = value.trim();
+        if let Some(hex) = value.strip_prefix('#') {
+            let digit = |index: usize| u8::from_str_radix(hex.get(index..index + 1)?, 16).ok();
+            let byte = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+            return match hex.len() {
+                3 => Some(SvgColor::rgb(
+                    digit(0)? * 17,
+                    digit(1)? * 17,
+                    digit(2)? * 17,
+                )),
+                6 => Some(SvgColor::rgb(byte(0)?, byte(2)?, byte(4)?)),
+                8 => Some(
+                    SvgColor::rgb(byte(0)?, byte(2)?, byte(4)?).with_alpha(byte(6)? as f32 / 255.0),
+                ),
+                _ => None,
+            };
+        }
+        let lower = value.to_ascii_lowercase();
+        if let Some(args) = lower
+            .strip_prefix("rgba(")
+            .or_else(|| lower.strip_prefix("rgb("))
+            .and_then(|args| args.strip_suffix(')'))
+        {
+            let args: Vec<&str> = args
+                .split(|char: char| char == ',' || char == '/' || char.is_whitespace())
+                .filter(|arg| !arg.is_empty())
+                .collect();
+            if args.len() < 3 {
+                return None;
+            }
+            let channel = |arg: &str| -> Option<f32> {
+                match arg.strip_suffix('%') {
+                    Some(percent) => percent.parse::<f32>().ok().map(|value| value / 100.0),
+                    None => arg.parse::<f32>().ok().map(|value| value / 255.0),
+                }
+            };
+            let alpha = match args.get(3) {
+                Some(arg) => match arg.strip_suffix('%') {
+                    Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+                    None => arg.parse::<f32>().ok()?,
+                },
+                None => 1.0,
+            };
+            return Some(SvgColor {
+                r: channel(args[0])?.clamp(0.0, 1.0),
+                g: channel(args[1])?.clamp(0.0, 1.0),
+                b: channel(args[2])?.clamp(0.0, 1.0),
+                a: alpha.clamp(0.0, 1.0),
+            });
+        }
+        Some(match lower.as_str() {
+            "black" => SvgColor::rgb(0, 0, 0),
+            "white" => SvgColor::rgb(255, 255, 255),
+            "red" => SvgColor::rgb(255, 0, 0),
+            "green" => SvgColor::rgb(0, 128, 0),
+            "lime" => SvgColor::rgb(0, 255, 0),
+            "blue" => SvgColor::rgb(0, 0, 255),
+            "yellow" => SvgColor::rgb(255, 255, 0),
+            "cyan" | "aqua" => SvgColor::rgb(0, 255, 255),
+            "magenta" | "fuchsia" => SvgColor::rgb(255, 0, 255),
+            "gray" | "grey" => SvgColor::rgb(128, 128, 128),
+            "silver" => SvgColor::rgb(192, 192, 192),
+            "maroon" => SvgColor::rgb(128, 0, 0),
+            "olive" => SvgColor::rgb(128, 128, 0),
+            "purple" => SvgColor::rgb(128, 0, 128),
+            "teal" => SvgColor::rgb(0, 128, 128),
+            "navy" => SvgColor::rgb(0, 0, 128),
+            "orange" => SvgColor::rgb(255, 165, 0),
+            "pink" => SvgColor::rgb(255, 192, 203),
+            "brown" => SvgColor::rgb(165, 42, 42),
+            "transparent" => SvgColor::BLACK.with_alpha(0.0),
+            _ => return None,
+        })
+    }
+}
+
+/// Parses a `transform` attribute, a list of `matrix`, `translate`, `scale`, `rotate`, `skewX`
+/// and `skewY`.
+pub fn parse_transform(value: &str) -> Result<AffineTransformation, String> {
+    let mut transform = AffineTransformation::identity();
+    let mut rest = value.trim();
+    while !rest.is_empty() {
+        let open = rest
+            .find('(')
+            .ok_or_else(|| format!("expected ( in transform {}", value))?;
+        let close = rest
+            .find(')')
+            .ok_or_else(|| format!("expected ) in transform {}", value))?;
+        let name = rest[..open].trim().trim_start_matches(',').trim();
+        let args = parse_numbers(&rest[open + 1..close]);
+        let arg = |index: usize| args.get(index).copied().unwrap_or(0.0);
+        let next = match (name, args.len()) {
+            ("matrix", 6) => AffineTransformation::new(
+                LinearTransformation::new(Vector::new(arg(0), arg(1)), Vector::new(arg(2), arg(3))),
+                Vector::new(arg(4), arg(5)),
+            ),
+            ("translate", 1 | 2) => AffineTransformation::translation(Vector::new(arg(0), arg(1))),
+            ("scale", 1) => AffineTransformation::uniform_scaling(arg(0)),
+            ("scale", 2) => AffineTransformation::scaling(Vector::new(arg(0), arg(1))),
+            ("rotate", 1 | 3) => {
+                let (sin, cos) = arg(0).to_radians().sin_cos();
+                let rotation = AffineTransformation::new(
+                    LinearTransformation::new(Vector::new(cos, sin), Vector::new(-sin, cos)),
+                    Vector::zero(),
+                );
+                let center = Vector::new(arg(1), arg(2));
+                AffineTransformation::translation(center)
+                    .compose(rotation)
+                    .compose(AffineTransformation::translation(-center))
+            }
+            ("skewX", 1) => AffineTransformation::new(
+                LinearTransformation::new(
+                    Vector::new(1.0, 0.0),
+                    Vector::new(arg(0).to_radians().tan(), 1.0),
+                ),
+                Vector::zero(),
+            ),
+            ("skewY", 1) => AffineTransformation::new(
+                LinearTransformation::new(
+                    Vector::new(1.0, arg(0).to_radians().tan()),
+                    Vector::new(0.0, 1.0),
+                ),
+                Vector::zero(),
+            ),
+            _ => return Err(format!("unsupported transform {}", &rest[..=close])),
+        };
+        transform = transform.compose(next);
+        rest = rest[close + 1..].trim_start();
+    }
+    Ok(transform)
+}
+
+/// Parses a list of numbers separated by commas and/or whitespace, or by nothing when the next
+/// one starts with a sign, as in `10-5`.
+pub fn parse_numbers(value: &str) -> Vec<f64> {
+    let mut numbers = Vec::new();
+    let bytes = value.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        if !matches!(bytes[index], b'0'..=b'9' | b'-' | b'+' | b'.') {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        index += 1;
+        let mut has_dot = bytes[start] == b'.';
+        while index < bytes.len() {
+            match bytes[index] {
+                b'0'..=b'9' => {}
+                b'.' if !has_dot => has_dot = true,
+                b'e' | b'E' => {
+                    if matches!(bytes.get(index + 1), Some(b'-' | b'+')) {
+                        index += 1;
+                    }
+                    has_dot = true;
+                }
+                _ => break,
+            }
+            index += 1;
+        }
+        if let Ok(number) = value[start..index].parse() {
+            numbers.push(number);
+        }
+    }
+    numbers
+}
+
+/// Parses a length, ignoring its unit. Percentages are returned as fractions.
+pub fn parse_length(value: &str) -> Option<f64> {
+    let value = value.trim();
+    if let Some(percent) = value.strip_suffix('%') {
+        return percent
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .map(|value| value / 100.0);
+    }
+    let len = value
+        .trim_end_matches(|char: char| char.is_ascii_alphabetic())
+        .len();
+    value[..len].parse().ok()
+}
+
+/// The declarations of a `style` attribute or a css rule, as name value pairs.
+pub fn parse_declarations(style: &str) -> impl Iterator<Item = (&str, &str)> {
+    style.split(';').filter_map(|declaration| {
+        let (name, value) = declaration.split_once(':')?;
+        let value = value.trim().trim_end_matches("!important").trim();
+        Some((name.trim(), value))
+    })
+}
+
+/// The rules of the `<style>` elements in a document. Only simple selectors are supported: a
+/// tag name, a `.class`, an `#id` or `*`, and lists of those.
+#[derive(Clone, Debug, Default)]
+pub struct StyleSheet {
+    rules: Vec<(String, String)>,
+}
+
+impl StyleSheet {
+    pub fn parse(css: &str) -> StyleSheet {
+        let mut rules = Vec::new();
+        let mut rest = strip_css_comments(css);
+        while let Some(open) = rest.find('{') {
+            let Some(close) = rest[open..].find('}').map(|close| open + close) else {
+                break;
+            };
+            let body = rest[open + 1..close].to_string();
+            for selector in rest[..open].split(',') {
+                let selector = selector.trim();
+                if !selector.is_empty() && !selector.starts_with('@') {
+                    rules.push((selector.to_string(), body.clone()));
+                }
+            }
+            rest = rest[close + 1..].to_string();
+        }
+        StyleSheet { rules }
+    }
+
+    pub fn extend(&mut self, other: StyleSheet) {
+        self.rules.extend(other.rules);
+    }
+
+    /// The declaration blocks that apply to `element`, in document order
+    pub fn matching<'a>(&'a self, element: &'a XmlElement) -> impl Iterator<Item = &'a str> {
+        self.rules.iter().filter_map(move |(selector, body)| {
+            let matches = match selector.as_bytes()[0] {
+                b'*' => selector == "*",
+                b'.' => element.attribute("class").is_some_and(|classes| {
+                    classes
+                        .split_whitespace()
+                        .any(|class| class == &selector[1..])
+                }),
+                b'#' => element.attribute("id") == Some(&selector[1..]),
+                _ => match selector.split_once('.') {
+                    Some((name, class)) => {
+                        element.local_name() == name
+                            && element.attribute("class").is_some_and(|classes| {
+                                classes.split_whitespace().any(|item| item == class)
+                            })
+                    }
+                    None => element.local_name() == selector,
+                },
+            };
+            matches.then_some(body.as_str())
+        })
+    }
+}
+
+fn strip_css_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = match rest[start..].find("*/") {
+            Some(end) => &rest[start + end + 2..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
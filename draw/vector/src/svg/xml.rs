@@ -0,0 +1,234 @@
+/// An element of an XML document. Text is only kept for the elements that need it, such as
+/// `<style>`; comments, processing instructions and doctypes are skipped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct XmlElement {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlElement>,
+    pub text: String,
+}
+
+impl XmlElement {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The name without its namespace prefix, so `svg:path` is `path`
+    pub fn local_name(&self) -> &str {
+        local_name(&self.name)
+    }
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Parses an XML document and returns its root element.
+pub fn parse_xml(data: &str) -> Result<XmlElement, String> {
+    let mut parser = Parser { data, index: 0 };
+    let mut stack: Vec<XmlElement> = vec![XmlElement::default()];
+    loop {
+        let text_start = parser.index;
+        let Some(offset) = parser.rest().find('<') else {
+            break;
+        };
+        parser.index += offset;
+        stack
+            .last_mut()
+            .unwrap()
+            .text
+            .push_str(&decode_entities(&data[text_start..parser.index]));
+        if parser.skip("<!--") {
+            parser.skip_past("-->")?;
+        } else if parser.skip("<![CDATA[") {
+            let start = parser.index;
+            parser.skip_past("]]>")?;
+            stack
+                .last_mut()
+                .unwrap()
+                .text
+                .push_str(&data[start..parser.index - 3]);
+        } else if parser.skip("<?") {
+            parser.skip_past("?>")?;
+        } else if parser.skip("<!") {
+            parser.skip_doctype()?;
+        } else if parser.skip("</") {
+            let name = parser.name();
+            parser.skip_whitespace();
+            if !parser.skip(">") {
+                return Err(parser.error("expected > after closing tag"));
+            }
+            let element = stack.pop().unwrap();
+            if element.name != name || stack.is_empty() {
+                return Err(parser.error(&format!("unexpected closing tag </{}>", name)));
+            }
+            stack.last_mut().unwrap().children.push(element);
+        } else {
+            parser.index += 1;
+            let mut element = XmlElement {
+                name: parser.name().to_string(),
+                ..XmlElement::default()
+            };
+            if element.name.is_empty() {
+                return Err(parser.error("expected a tag name"));
+            }
+            loop {
+                parser.skip_whitespace();
+                if parser.skip("/>") {
+                    stack.last_mut().unwrap().children.push(element);
+                    break;
+                }
+                if parser.skip(">") {
+                    stack.push(element);
+                    break;
+                }
+                let key = parser.name().to_string();
+                if key.is_empty() {
+                    return Err(parser.error("expected an attribute name"));
+                }
+                parser.skip_whitespace();
+                if !parser.skip("=") {
+                    return Err(parser.error("expected = after attribute name"));
+                }
+                parser.skip_whitespace();
+                let value = parser.quoted()?;
+                element.attributes.push((key, decode_entities(value)));
+            }
+        }
+    }
+    if stack.len() != 1 {
+        return Err(format!("unclosed tag <{}>", stack.last().unwrap().name));
+    }
+    let mut document = stack.pop().unwrap();
+    match document.children.len() {
+        0 => Err("no root element".to_string()),
+        _ => Ok(document.children.remove(0)),
+    }
+}
+
+struct Parser<'a> {
+    data: &'a str,
+    index: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.data[self.index..]
+    }
+
+    fn error(&self, message: &str) -> String {
+        let line = self.data[..self.index].matches('\n').count() + 1;
+        format!("{} on line {}", message, line)
+    }
+
+    fn skip(&mut self, prefix: &str) -> bool {
+        if self.rest().starts_with(prefix) {
+            self.index += prefix.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<(), String> {
+        match self.rest().find(end) {
+            Some(offset) => {
+                self.index += offset + end.len();
+                Ok(())
+            }
+            None => Err(self.error(&format!("expected {}", end))),
+        }
+    }
+
+    // a doctype can have an internal subset in brackets, which can contain >
+    fn skip_doctype(&mut self) -> Result<(), String> {
+        let mut depth = 0;
+        for (offset, char) in self.rest().char_indices() {
+            match char {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                '>' if depth == 0 => {
+                    self.index += offset + 1;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        Err(self.error("unclosed <!"))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.index += rest.len() - rest.trim_start().len();
+    }
+
+    fn name(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .find(|char: char| char.is_whitespace() || matches!(char, '>' | '/' | '=' | '<'))
+            .unwrap_or(rest.len());
+        self.index += len;
+        &rest[..len]
+    }
+
+    fn quoted(&mut self) -> Result<&'a str, String> {
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.error("expected a quoted attribute value")),
+        };
+        self.index += 1;
+        let rest = self.rest();
+        match rest.find(quote) {
+            Some(len) => {
+                self.index += len + 1;
+                Ok(&rest[..len])
+            }
+            None => Err(self.error("unclosed attribute value")),
+        }
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find('&') {
+        out.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let entity = rest.find(';').map(|end| &rest[1..end]);
+        let decoded = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(decimal) = entity.strip_prefix('#') {
+                    decimal.parse().ok()
+                } else {
+                    None
+                };
+                code.and_then(char::from_u32)
+            }
+        });
+        match (entity, decoded) {
+            (Some(entity), Some(char)) => {
+                out.push(char);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
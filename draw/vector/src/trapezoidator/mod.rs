@@ -10,10 +10,32 @@ use std::ops::Range;
 /// should define a set of closed contours.
 #[derive(Clone, Debug, Default)]
 pub struct Trapezoidator {
+    /// Which regions count as inside, for the contours passed to the next `trapezoidate`.
+    pub fill_rule: FillRule,
     event_queue: BinaryHeap<Event>,
     active_segments: Vec<ActiveSegment>,
 }
 
+/// The rule that decides whether a region is inside, given the winding number of the contours
+/// around it.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum FillRule {
+    /// A region is inside if its winding number is not zero.
+    #[default]
+    NonZero,
+    /// A region is inside if its winding number is odd.
+    EvenOdd,
+}
+
+impl FillRule {
+    fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
 impl Trapezoidator {
     /// Creates a new trapezoidator.
     pub fn new() -> Trapezoidator {
@@ -220,13 +242,14 @@ impl Trapezoidator {
         } else {
             self.active_segments[incident_segment_range.end - 1].upper_region
         };
+        let fill_rule = self.fill_rule;
         self.active_segments.splice(
             incident_segment_range.end..incident_segment_range.end,
             Iterator::map(right_segments.iter(), |right_segment| {
                 let upper_region = {
                     let winding = lower_region.winding + right_segment.winding;
                     Region {
-                        is_inside: winding != 0,
+                        is_inside: fill_rule.is_inside(winding),
                         winding,
                     }
                 };
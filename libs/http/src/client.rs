@@ -0,0 +1,396 @@
+// a small blocking HTTP/1.1 client. It speaks plain TCP itself and hands the socket
+// to a pluggable TLS connector for https, so we dont need to pull in a TLS stack here.
+
+use std::net::TcpStream;
+use std::io::{Read, Write, BufRead, BufReader};
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub trait HttpStream: Read + Write + Send {}
+impl<T: Read + Write + Send> HttpStream for T {}
+
+/// Wraps a connected `TcpStream` in a TLS session for the given host
+pub type TlsConnector = fn(host: &str, stream: TcpStream) -> Result<Box<dyn HttpStream>, String>;
+
+static TLS_CONNECTOR: Mutex<Option<TlsConnector>> = Mutex::new(None);
+
+pub fn set_tls_connector(connector: TlsConnector) {
+    *TLS_CONNECTOR.lock().unwrap() = Some(connector);
+}
+
+pub fn tls_connector() -> Option<TlsConnector> {
+    *TLS_CONNECTOR.lock().unwrap()
+}
+
+pub const MAX_REDIRECTS: usize = 10;
+const READ_CHUNK_SIZE: usize = 65536;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpUrl {
+    pub is_secure: bool,
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+impl HttpUrl {
+    pub fn parse(url: &str) -> Result<HttpUrl, String> {
+        let (is_secure, rest) = if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        }
+        else if let Some(rest) = url.strip_prefix("ws://") {
+            (false, rest)
+        }
+        else if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        }
+        else if let Some(rest) = url.strip_prefix("wss://") {
+            (true, rest)
+        }
+        else {
+            return Err(format!("Unsupported url scheme: {}", url))
+        };
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(pos) if rest.as_bytes()[pos] == b'?' => (&rest[..pos], format!("/{}", &rest[pos..])),
+            Some(pos) => (&rest[..pos], rest[pos..].to_string()),
+            None => (rest, "/".to_string())
+        };
+        // strip a fragment, it never goes over the wire
+        let path = match path.find('#') {
+            Some(pos) => path[..pos].to_string(),
+            None => path
+        };
+        // strip userinfo
+        let authority = match authority.rfind('@') {
+            Some(pos) => &authority[pos + 1..],
+            None => authority
+        };
+        let default_port = if is_secure {443} else {80};
+        let (host, port) = if authority.starts_with('[') { // ipv6 literal
+            match authority.find(']') {
+                Some(end) => {
+                    let host = &authority[1..end];
+                    match authority[end + 1..].strip_prefix(':') {
+                        Some(port) => (host, port.parse().map_err( | _ | format!("Invalid port in url: {}", url)) ?),
+                        None => (host, default_port)
+                    }
+                }
+                None => return Err(format!("Invalid host in url: {}", url))
+            }
+        }
+        else {
+            match authority.rfind(':') {
+                Some(pos) => (&authority[..pos], authority[pos + 1..].parse().map_err( | _ | format!("Invalid port in url: {}", url)) ?),
+                None => (authority, default_port)
+            }
+        };
+        if host.is_empty() {
+            return Err(format!("No host in url: {}", url))
+        }
+        Ok(HttpUrl {
+            is_secure,
+            host: host.to_string(),
+            port,
+            path
+        })
+    }
+
+    /// The value for the `Host` header, which leaves out the port if its the default one
+    pub fn host_header(&self) -> String {
+        let host = if self.host.contains(':') {format!("[{}]", self.host)} else {self.host.clone()};
+        if self.port == if self.is_secure {443} else {80} {
+            host
+        }
+        else {
+            format!("{}:{}", host, self.port)
+        }
+    }
+
+    /// Resolves a `Location` header value against this url
+    pub fn join(&self, location: &str) -> Result<HttpUrl, String> {
+        if location.contains("://") {
+            return HttpUrl::parse(location)
+        }
+        let scheme = if self.is_secure {"https"} else {"http"};
+        if let Some(rest) = location.strip_prefix("//") {
+            return HttpUrl::parse(&format!("{}://{}", scheme, rest))
+        }
+        let mut url = self.clone();
+        if location.starts_with('/') {
+            url.path = location.to_string();
+        }
+        else if location.starts_with('?') {
+            let base = self.path.split('?').next().unwrap_or("/");
+            url.path = format!("{}{}", base, location);
+        }
+        else {
+            let base = self.path.split('?').next().unwrap_or("/");
+            let dir = match base.rfind('/') {
+                Some(pos) => &base[..pos + 1],
+                None => "/"
+            };
+            url.path = format!("{}{}", dir, location);
+        }
+        Ok(url)
+    }
+
+    pub fn connect(&self) -> Result<Box<dyn HttpStream>, String> {
+        self.connect_with_read_timeout(None)
+    }
+
+    /// Connects with a read timeout on the underlying socket, so a long lived connection
+    /// can wake up periodically even when its wrapped in TLS
+    pub fn connect_with_read_timeout(&self, read_timeout: Option<Duration>) -> Result<Box<dyn HttpStream>, String> {
+        use std::net::ToSocketAddrs;
+        let addrs = (self.host.as_str(), self.port).to_socket_addrs().map_err( | e | format!("Cannot resolve {}: {}", self.host, e)) ?;
+        let mut last_error = format!("Cannot resolve {}", self.host);
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                Ok(stream) => {
+                    let _ = stream.set_nodelay(true);
+                    let _ = stream.set_read_timeout(read_timeout);
+                    if self.is_secure {
+                        return match tls_connector() {
+                            Some(connector) => connector(&self.host, stream),
+                            None => Err(format!("No TLS connector registered, cannot connect to {}", self.host))
+                        }
+                    }
+                    return Ok(Box::new(stream))
+                }
+                Err(e) => last_error = format!("Cannot connect to {}:{}: {}", self.host, self.port, e)
+            }
+        }
+        Err(last_error)
+    }
+}
+
+pub struct HttpClientRequest<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<&'a [u8]>,
+}
+
+#[derive(Debug)]
+pub struct HttpClientResponse {
+    /// The url the response came from, after following redirects
+    pub url: String,
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpClientResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find( | (k, _) | k.eq_ignore_ascii_case(name)).map( | (_, v) | v.as_str())
+    }
+}
+
+pub enum HttpClientEvent<'a> {
+    Progress {loaded: u64, total: Option<u64>},
+    Redirect {url: &'a str},
+}
+
+/// Runs a request to completion, following redirects. The callback receives body progress
+/// as it arrives, and is the place to hand partial results back to a UI thread.
+pub fn http_client_request(request: &HttpClientRequest, mut callback: impl FnMut(HttpClientEvent)) -> Result<HttpClientResponse, String> {
+    let mut url = HttpUrl::parse(request.url) ?;
+    let mut method = request.method.to_string();
+    let mut body = request.body;
+    let mut headers = request.headers.clone();
+    for _ in 0..=MAX_REDIRECTS {
+        let stream = url.connect() ?;
+        let response = send_and_receive(stream, &url, &method, &headers, body, &mut callback) ?;
+        match response.status_code {
+            301 | 302 | 303 | 307 | 308 => {
+                let location = match response.header("location") {
+                    Some(location) => location.trim().to_string(),
+                    None => return Ok(response)
+                };
+                let next_url = url.join(&location) ?;
+                // credentials are meant for the origin they were given for, dont leak them to another one
+                if next_url.is_secure != url.is_secure || next_url.host != url.host || next_url.port != url.port {
+                    headers.retain( | (k, _) | !is_credential_header(k));
+                }
+                url = next_url;
+                // 303 always turns into a GET, and browsers do the same for 301/302 POSTs
+                if response.status_code == 303 || (response.status_code <= 302 && method == "POST") {
+                    if method != "HEAD" {
+                        method = "GET".to_string();
+                    }
+                    body = None;
+                }
+                let url_string = format!("{}://{}{}", if url.is_secure {"https"} else {"http"}, url.host_header(), url.path);
+                callback(HttpClientEvent::Redirect {url: &url_string});
+            }
+            _ => return Ok(response)
+        }
+    }
+    Err(format!("Too many redirects for {}", request.url))
+}
+
+fn is_credential_header(name: &str) -> bool {
+    name.eq_ignore_ascii_case("authorization") || name.eq_ignore_ascii_case("proxy-authorization") || name.eq_ignore_ascii_case("cookie")
+}
+
+fn send_and_receive(
+    mut stream: Box<dyn HttpStream>,
+    url: &HttpUrl,
+    method: &str,
+    headers: &[(String, String)],
+    body: Option<&[u8]>,
+    callback: &mut impl FnMut(HttpClientEvent)
+) -> Result<HttpClientResponse, String> {
+    let mut head = format!("{} {} HTTP/1.1\r\nHost: {}\r\n", method, url.path, url.host_header());
+    let has_header = | name: &str | headers.iter().any( | (k, _) | k.eq_ignore_ascii_case(name));
+    for (key, value) in headers {
+        if key.eq_ignore_ascii_case("host") || key.eq_ignore_ascii_case("content-length") || key.eq_ignore_ascii_case("connection") {
+            continue;
+        }
+        head.push_str(&format!("{}: {}\r\n", key, value));
+    }
+    if !has_header("user-agent") {
+        head.push_str("User-Agent: makepad\r\n");
+    }
+    if !has_header("accept") {
+        head.push_str("Accept: */*\r\n");
+    }
+    if let Some(body) = body {
+        head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    else if method == "POST" || method == "PUT" || method == "PATCH" {
+        head.push_str("Content-Length: 0\r\n");
+    }
+    head.push_str("Connection: close\r\n\r\n");
+
+    stream.write_all(head.as_bytes()).map_err( | e | format!("Cannot write request: {}", e)) ?;
+    if let Some(body) = body {
+        stream.write_all(body).map_err( | e | format!("Cannot write request body: {}", e)) ?;
+    }
+    stream.flush().map_err( | e | format!("Cannot write request: {}", e)) ?;
+
+    let mut reader = BufReader::new(stream);
+    let (status_code, headers) = loop {
+        let (status_code, headers) = read_response_head(&mut reader) ?;
+        // skip interim responses such as 100 Continue
+        if status_code >= 200 || status_code == 101 {
+            break (status_code, headers)
+        }
+    };
+    let header = | name: &str | headers.iter().find( | (k, _) | k.eq_ignore_ascii_case(name)).map( | (_, v) | v.trim());
+
+    let has_body = method != "HEAD" && status_code != 204 && status_code != 304;
+    let body = if !has_body {
+        Vec::new()
+    }
+    else if header("transfer-encoding").is_some_and( | v | v.to_ascii_lowercase().contains("chunked")) {
+        read_chunked_body(&mut reader, callback) ?
+    }
+    else if let Some(length) = header("content-length") {
+        let length: u64 = length.parse().map_err( | _ | format!("Invalid Content-Length: {}", length)) ?;
+        read_body(&mut reader, Some(length), callback) ?
+    }
+    else {
+        read_body(&mut reader, None, callback) ?
+    };
+
+    Ok(HttpClientResponse {
+        url: format!("{}://{}{}", if url.is_secure {"https"} else {"http"}, url.host_header(), url.path),
+        status_code,
+        headers,
+        body
+    })
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, String> {
+    let mut line = Vec::new();
+    reader.read_until(b'\n', &mut line).map_err( | e | format!("Cannot read response: {}", e)) ?;
+    if line.is_empty() {
+        return Err("Connection closed before response was complete".to_string())
+    }
+    while line.last() == Some(&b'\n') || line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    String::from_utf8(line).map_err( | _ | "Response header is not valid UTF-8".to_string())
+}
+
+fn read_response_head(reader: &mut impl BufRead) -> Result<(u16, Vec<(String, String)>), String> {
+    let status_line = read_line(reader) ?;
+    let mut parts = status_line.splitn(3, ' ');
+    let version = parts.next().unwrap_or("");
+    if !version.starts_with("HTTP/") {
+        return Err(format!("Invalid response status line: {}", status_line))
+    }
+    let status_code = parts.next().and_then( | v | v.parse().ok()).ok_or_else( || format!("Invalid response status line: {}", status_line)) ?;
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader) ?;
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() > 4096 { // some overflow protection
+            return Err("Too many response headers".to_string())
+        }
+        if let Some(pos) = line.find(':') {
+            headers.push((line[..pos].trim().to_string(), line[pos + 1..].trim().to_string()));
+        }
+    }
+    Ok((status_code, headers))
+}
+
+fn read_body(reader: &mut impl Read, length: Option<u64>, callback: &mut impl FnMut(HttpClientEvent)) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    if let Some(length) = length {
+        body.reserve(length.min(64 * 1024 * 1024) as usize);
+    }
+    let mut buf = vec![0u8; READ_CHUNK_SIZE];
+    loop {
+        let want = match length {
+            Some(length) => (length - body.len() as u64).min(READ_CHUNK_SIZE as u64) as usize,
+            None => READ_CHUNK_SIZE
+        };
+        if want == 0 {
+            break;
+        }
+        let n = reader.read(&mut buf[0..want]).map_err( | e | format!("Cannot read response body: {}", e)) ?;
+        if n == 0 {
+            if length.is_some() {
+                return Err("Connection closed before response body was complete".to_string())
+            }
+            break;
+        }
+        body.extend_from_slice(&buf[0..n]);
+        callback(HttpClientEvent::Progress {loaded: body.len() as u64, total: length});
+    }
+    Ok(body)
+}
+
+fn read_chunked_body(reader: &mut impl BufRead, callback: &mut impl FnMut(HttpClientEvent)) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    loop {
+        let line = read_line(reader) ?;
+        // chunk extensions come after a ;
+        let size = line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size, 16).map_err( | _ | format!("Invalid chunk size: {}", line)) ?;
+        if size == 0 {
+            // skip trailers
+            while !read_line(reader) ?.is_empty() {}
+            break;
+        }
+        // the size comes from the server, so the chunk is read in pieces instead of allocating it up front
+        let mut remaining = size;
+        let mut buf = vec![0u8; size.min(READ_CHUNK_SIZE)];
+        while remaining > 0 {
+            let want = remaining.min(READ_CHUNK_SIZE);
+            reader.read_exact(&mut buf[0..want]).map_err( | e | format!("Cannot read response chunk: {}", e)) ?;
+            body.extend_from_slice(&buf[0..want]);
+            remaining -= want;
+        }
+        if !read_line(reader) ?.is_empty() {
+            return Err("Missing newline after response chunk".to_string())
+        }
+        callback(HttpClientEvent::Progress {loaded: body.len() as u64, total: None});
+    }
+    Ok(body)
+}
@@ -1,4 +1,6 @@
  pub mod digest;
  pub mod utils;
  pub mod server;
+ pub mod client;
  pub mod websocket;
+ pub mod websocket_client;
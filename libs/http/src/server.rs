@@ -99,10 +99,12 @@ fn handle_post(http_server: HttpServer, mut tcp_stream: TcpStream, headers: Http
         return http_error_out(tcp_stream, 500);
     }
     let bytes_total = content_length as usize;
-    let mut body = Vec::new();
-    body.resize(bytes_total, 0u8);
+    let mut body = vec![0u8; bytes_total];
     
-    let mut bytes_left = bytes_total;
+    // the start of the body may already have been read along with the headers
+    let bytes_start = headers.body_start.len().min(bytes_total);
+    body[0..bytes_start].copy_from_slice(&headers.body_start[0..bytes_start]);
+    let mut bytes_left = bytes_total - bytes_start;
     while bytes_left > 0 {
         let buf = &mut body[(bytes_total - bytes_left)..bytes_total];
         let bytes_read = tcp_stream.read(buf);
@@ -202,7 +204,7 @@ fn handle_web_socket(http_server: HttpServer, mut tcp_stream: TcpStream, headers
                                 let _ = tx_socket.send(Vec::new());
                             };
                         },
-                        Ok(WebSocketMessage::Close) => {
+                        Ok(WebSocketMessage::Close{..}) => {
                             let _ = tcp_stream.shutdown(Shutdown::Both);
                         }
                         Err(e) => {
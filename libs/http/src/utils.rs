@@ -68,7 +68,9 @@ pub struct HttpServerHeaders {
     pub search: Option<String>,
     pub content_length: Option<u64>,
     pub accept_encoding: Option<String>,
-    pub sec_websocket_key: Option<String>
+    pub sec_websocket_key: Option<String>,
+    /// Bytes that arrived after the headers and were read along with them, a request body starts with these
+    pub body_start: Vec<u8>,
 }
 
 impl HttpServerHeaders {
@@ -82,7 +84,7 @@ impl HttpServerHeaders {
         let mut sec_websocket_key = None;
         let mut line = String::new();
         
-        while reader.read_line(&mut line).is_ok() { // TODO replace this with a non-line read
+        while reader.read_line(&mut line).is_ok_and( | n | n > 0) {
             if line == "\r\n" { // the newline
                 break;
             }
@@ -106,6 +108,8 @@ impl HttpServerHeaders {
         if lines.len() <2 {
             return None;
         }
+        // the reader may have read past the headers, hand those bytes on instead of dropping them
+        let body_start = reader.buffer().to_vec();
         let verb;
         let path;
         if let Some(v) = split_header_line(&lines[0], "GET ") {
@@ -139,7 +143,8 @@ impl HttpServerHeaders {
             lines,
             content_length,
             accept_encoding,
-            sec_websocket_key
+            sec_websocket_key,
+            body_start
         })
     }
 }
@@ -1,6 +1,7 @@
 use std::convert::TryInto;
 use crate::digest::{Sha1, base64_encode};
 use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 #[derive(Debug, PartialEq)]
 enum State {
@@ -35,9 +36,15 @@ pub struct WebSocket {
     mask_counter: usize,
     is_ping: bool,
     is_pong: bool,
-    is_partial: bool,
+    is_close: bool,
+    is_final: bool,
+    is_continuation: bool,
     is_text: bool,
     is_masked: bool,
+    // a message split over multiple frames is collected here until its final frame
+    fragments: Vec<u8>,
+    is_fragmented: bool,
+    is_fragmented_text: bool,
     state: State
 }
 
@@ -46,21 +53,42 @@ pub enum WebSocketMessage<'a> {
     Pong(&'a [u8]),
     Text(&'a str),
     Binary(&'a [u8]),
-    Close
+    Close{code: Option<u16>, reason: &'a str}
 }
 
 #[derive(Debug)]
 pub enum WebSocketError<'a> {
     OpcodeNotSupported(u8),
     TextNotUTF8(&'a [u8]),
+    UnexpectedContinuation,
+    ExpectedContinuation,
+    InvalidControlFrame,
+}
+
+impl<'a> WebSocketError<'a> {
+    /// The close code to send to the peer when failing the connection because of this error
+    pub fn close_code(&self) -> u16 {
+        match self {
+            Self::TextNotUTF8(_) => CLOSE_INVALID_DATA,
+            _ => CLOSE_PROTOCOL_ERROR
+        }
+    }
 }
 
 pub const PING_MESSAGE:[u8;2] = [128 | 9,0];
 pub const PONG_MESSAGE:[u8;2] = [128 | 10,0];
 
+pub const CLOSE_NORMAL: u16 = 1000;
+pub const CLOSE_GOING_AWAY: u16 = 1001;
+pub const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+pub const CLOSE_INVALID_DATA: u16 = 1007;
+
 pub enum MessageFormat {
     Binary,
-    Text
+    Text,
+    Close,
+    Ping,
+    Pong,
 }
 
 pub struct MessageHeader {
@@ -77,6 +105,9 @@ impl MessageHeader {
         match format {
             MessageFormat::Binary => data[0] = 128 | 2,
             MessageFormat::Text => data[0] = 128 | 1,
+            MessageFormat::Close => data[0] = 128 | 8,
+            MessageFormat::Ping => data[0] = 128 | 9,
+            MessageFormat::Pong => data[0] = 128 | 10,
         }
 
         if masked {
@@ -125,7 +156,7 @@ impl MessageHeader {
         if self.masked {
             match self.len {
                 6 => Some(&self.data[2..6]),
-                10 => Some(&self.data[6..10]),
+                8 => Some(&self.data[4..8]),
                 14 => Some(&self.data[10..14]),
                 _ => None
             }
@@ -134,11 +165,24 @@ impl MessageHeader {
         }
     }
 
-    // TODO Improve this using a proper random number generator
     fn random_byte() -> u8 {
-        let num = SystemTime::now().duration_since(UNIX_EPOCH).expect("duration_since failed").subsec_nanos();
-        num as u8
+        random_u64() as u8
+    }
+}
+
+static RANDOM_STATE: AtomicU64 = AtomicU64::new(0);
+
+// xorshift seeded from the clock. Masks only need to be unpredictable to intermediaries, not cryptographically strong
+pub fn random_u64() -> u64 {
+    let mut x = RANDOM_STATE.load(Ordering::Relaxed);
+    if x == 0 {
+        x = SystemTime::now().duration_since(UNIX_EPOCH).expect("duration_since failed").as_nanos() as u64 | 1;
     }
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    RANDOM_STATE.store(x, Ordering::Relaxed);
+    x
 }
 
 impl WebSocket {
@@ -153,25 +197,65 @@ impl WebSocket {
             mask_counter: 0,
             is_ping: false,
             is_pong: false,
+            is_close: false,
+            is_final: false,
+            is_continuation: false,
             is_masked: false,
-            is_partial: false,
             is_text: false,
+            fragments: Vec::new(),
+            is_fragmented: false,
+            is_fragmented_text: false,
             state: State::Opcode
         }
     }
     
-    pub fn create_upgrade_response(key: &str) -> String {
+    /// The `Sec-WebSocket-Accept` value that answers a `Sec-WebSocket-Key`
+    pub fn accept_key(key: &str) -> String {
         let to_hash = format!("{}258EAFA5-E914-47DA-95CA-C5AB0DC85B11", key);
         let mut sha1 = Sha1::new();
         sha1.update(to_hash.as_bytes());
         let out_bytes = sha1.finalise();
-        let base64 = base64_encode(&out_bytes);
+        base64_encode(&out_bytes)
+    }
+    
+    pub fn create_upgrade_response(key: &str) -> String {
         let response_ack = format!(
             "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
-            base64
+            Self::accept_key(key)
         );
         response_ack
     }
+    
+    /// A fresh random `Sec-WebSocket-Key` for a client handshake
+    pub fn create_key() -> String {
+        let mut bytes = [0u8; 16];
+        bytes[0..8].copy_from_slice(&random_u64().to_le_bytes());
+        bytes[8..16].copy_from_slice(&random_u64().to_le_bytes());
+        base64_encode(&bytes)
+    }
+    
+    pub fn create_upgrade_request(host: &str, path: &str, key: &str, headers: &[(String, String)]) -> String {
+        let mut request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n",
+            path, host, key
+        );
+        for (name, value) in headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        request.push_str("\r\n");
+        request
+    }
+    
+    pub fn build_close_message(code: u16, reason: &str, masked: bool) -> Vec<u8> {
+        let mut payload = code.to_be_bytes().to_vec();
+        // control frames cant be longer than 125 bytes
+        let mut reason_len = reason.len().min(123);
+        while !reason.is_char_boundary(reason_len) {
+            reason_len -= 1;
+        }
+        payload.extend_from_slice(&reason.as_bytes()[0..reason_len]);
+        Self::build_message(MessageHeader::from_len(payload.len(), MessageFormat::Close, masked), &payload)
+    }
 
     pub fn build_message(mut header: MessageHeader, data: &[u8])->Vec<u8>{
         let mut frame = header.as_slice().to_vec();
@@ -207,7 +291,9 @@ impl WebSocket {
             State::Opcode => {
                 self.is_ping = false;
                 self.is_pong = false;
-                self.is_partial = false;
+                self.is_close = false;
+                self.is_final = false;
+                self.is_continuation = false;
                 self.is_text = false;
                 self.is_masked = false;
             },
@@ -228,27 +314,42 @@ impl WebSocket {
                         break;
                     }
                     let opcode = self.head[0] & 15;
-                    if opcode <= 2 {
-                        self.is_partial = (self.head[0] & 128) != 0;
-                        self.is_text = opcode == 1;
+                    let is_final = (self.head[0] & 128) != 0;
+                    if opcode == 0 {
+                        if !self.is_fragmented {
+                            result(Err(WebSocketError::UnexpectedContinuation));
+                            break;
+                        }
+                        self.is_continuation = true;
+                        self.is_text = self.is_fragmented_text;
                         self.to_state(State::Len1);
                     }
-                    else if opcode == 8 {
-                        result(Ok(WebSocketMessage::Close));
-                        break;
-                    }
-                    else if opcode == 9 {
-                        self.is_ping = true;
+                    else if opcode <= 2 {
+                        if self.is_fragmented {
+                            result(Err(WebSocketError::ExpectedContinuation));
+                            break;
+                        }
+                        self.is_text = opcode == 1;
                         self.to_state(State::Len1);
                     }
-                    else if opcode == 10 {
-                        self.is_pong = true;
+                    else if (8..=10).contains(&opcode) {
+                        // control frames can be interleaved with fragments, but are never fragmented themselves
+                        if !is_final {
+                            result(Err(WebSocketError::InvalidControlFrame));
+                            break;
+                        }
                         self.to_state(State::Len1);
+                        match opcode {
+                            8 => self.is_close = true,
+                            9 => self.is_ping = true,
+                            _ => self.is_pong = true
+                        }
                     }
                     else {
                         result(Err(WebSocketError::OpcodeNotSupported(opcode)));
                         break;
                     }
+                    self.is_final = is_final;
                 },
                 State::Len1 => {
                     if self.parse_head(input) {
@@ -330,16 +431,50 @@ impl WebSocket {
                         else if self.is_pong {
                             result(Ok(WebSocketMessage::Pong(&self.data)));
                         }
-                        else if self.is_text{
-                            if let Ok(text) = std::str::from_utf8(&self.data){
-                                result(Ok(WebSocketMessage::Text(text)));
+                        else if self.is_close {
+                            let code = if self.data.len() >= 2 {
+                                Some(u16::from_be_bytes([self.data[0], self.data[1]]))
                             }
-                            else{
-                                result(Err(WebSocketError::TextNotUTF8(&self.data)))
+                            else {
+                                None
+                            };
+                            let reason = if self.data.len() > 2 {
+                                std::str::from_utf8(&self.data[2..]).unwrap_or("")
+                            }
+                            else {
+                                ""
+                            };
+                            result(Ok(WebSocketMessage::Close{code, reason}));
+                        }
+                        else if !self.is_final {
+                            // keep collecting until the final fragment arrives
+                            if !self.is_continuation {
+                                self.fragments.clear();
+                                self.is_fragmented = true;
+                                self.is_fragmented_text = self.is_text;
                             }
+                            self.fragments.extend_from_slice(&self.data);
                         }
-                        else{
-                            result(Ok(WebSocketMessage::Binary(&self.data)));
+                        else {
+                            let data = if self.is_continuation {
+                                self.fragments.extend_from_slice(&self.data);
+                                self.is_fragmented = false;
+                                &self.fragments
+                            }
+                            else {
+                                &self.data
+                            };
+                            if self.is_text {
+                                if let Ok(text) = std::str::from_utf8(data) {
+                                    result(Ok(WebSocketMessage::Text(text)));
+                                }
+                                else {
+                                    result(Err(WebSocketError::TextNotUTF8(data)))
+                                }
+                            }
+                            else {
+                                result(Ok(WebSocketMessage::Binary(data)));
+                            }
                         }
                         
                         self.to_state(State::Opcode);
@@ -0,0 +1,242 @@
+// a websocket client running on its own thread. Framing is shared with the server through WebSocket,
+// the connection itself goes through HttpUrl so wss:// uses the same TLS connector as https.
+
+use std::io::{Read, Write, ErrorKind};
+use std::sync::mpsc::{channel, Sender, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use crate::client::{HttpUrl, HttpStream};
+use crate::websocket::{
+    WebSocket,
+    WebSocketMessage,
+    MessageHeader,
+    MessageFormat,
+    CLOSE_NORMAL,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, PartialEq)]
+pub enum WebSocketClientEvent {
+    Opened,
+    Text(String),
+    Binary(Vec<u8>),
+    Closed{code: Option<u16>, reason: String},
+    Error(String),
+}
+
+enum WebSocketClientCommand {
+    Send(Vec<u8>),
+    Close{code: u16, reason: String},
+}
+
+pub struct WebSocketClient {
+    command_sender: Sender<WebSocketClientCommand>,
+}
+
+impl WebSocketClient {
+    /// Opens a connection in the background. Every incoming message, the close and any
+    /// error is delivered through `on_event` from the socket thread.
+    pub fn connect<F>(url: &str, headers: Vec<(String, String)>, on_event: F) -> WebSocketClient
+    where F: FnMut(WebSocketClientEvent) + Send + 'static {
+        let (command_sender, command_receiver) = channel();
+        let url = url.to_string();
+        std::thread::spawn(move || {
+            let mut on_event = on_event;
+            match handshake(&url, &headers) {
+                Ok((stream, leftover)) => {
+                    on_event(WebSocketClientEvent::Opened);
+                    run_socket(stream, leftover, command_receiver, &mut on_event);
+                }
+                Err(error) => on_event(WebSocketClientEvent::Error(error))
+            }
+        });
+        WebSocketClient {
+            command_sender
+        }
+    }
+
+    pub fn send_text(&self, text: &str) -> Result<(), String> {
+        let header = MessageHeader::from_len(text.len(), MessageFormat::Text, true);
+        self.send_frame(WebSocket::build_message(header, text.as_bytes()))
+    }
+
+    pub fn send_binary(&self, data: &[u8]) -> Result<(), String> {
+        let header = MessageHeader::from_len(data.len(), MessageFormat::Binary, true);
+        self.send_frame(WebSocket::build_message(header, data))
+    }
+
+    pub fn close(&self, code: u16, reason: &str) -> Result<(), String> {
+        self.command_sender.send(WebSocketClientCommand::Close{code, reason: reason.to_string()}).map_err( | _ | "WebSocket connection is closed".to_string())
+    }
+
+    fn send_frame(&self, frame: Vec<u8>) -> Result<(), String> {
+        self.command_sender.send(WebSocketClientCommand::Send(frame)).map_err( | _ | "WebSocket connection is closed".to_string())
+    }
+}
+
+fn handshake(url: &str, headers: &[(String, String)]) -> Result<(Box<dyn HttpStream>, Vec<u8>), String> {
+    let url = HttpUrl::parse(url) ?;
+    let mut stream = url.connect_with_read_timeout(Some(POLL_INTERVAL)) ?;
+    let key = WebSocket::create_key();
+    let request = WebSocket::create_upgrade_request(&url.host_header(), &url.path, &key, headers);
+    stream.write_all(request.as_bytes()).map_err( | e | format!("Cannot write websocket upgrade: {}", e)) ?;
+
+    // read until the end of the response head, whatever follows is already websocket data
+    let start = Instant::now();
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let head_end = loop {
+        if let Some(pos) = data.windows(4).position( | w | w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if data.len() > 65536 {
+            return Err("Websocket upgrade response too large".to_string())
+        }
+        match stream.read(&mut buf) {
+            Ok(0) => return Err("Connection closed during websocket upgrade".to_string()),
+            Ok(n) => data.extend_from_slice(&buf[0..n]),
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                if start.elapsed() > HANDSHAKE_TIMEOUT {
+                    return Err("Timeout during websocket upgrade".to_string())
+                }
+            }
+            Err(e) => return Err(format!("Cannot read websocket upgrade: {}", e))
+        }
+    };
+
+    let head = String::from_utf8_lossy(&data[0..head_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let status_line = lines.next().unwrap_or("");
+    if status_line.split(' ').nth(1) != Some("101") {
+        return Err(format!("Websocket upgrade refused: {}", status_line))
+    }
+    let mut accept = None;
+    for line in lines {
+        if let Some(pos) = line.find(':') {
+            if line[..pos].trim().eq_ignore_ascii_case("sec-websocket-accept") {
+                accept = Some(line[pos + 1..].trim().to_string());
+            }
+        }
+    }
+    if accept.as_deref() != Some(&WebSocket::accept_key(&key)) {
+        return Err("Websocket upgrade has an invalid Sec-WebSocket-Accept".to_string())
+    }
+    Ok((stream, data[head_end..].to_vec()))
+}
+
+fn run_socket(
+    mut stream: Box<dyn HttpStream>,
+    leftover: Vec<u8>,
+    command_receiver: Receiver<WebSocketClientCommand>,
+    on_event: &mut dyn FnMut(WebSocketClientEvent)
+) {
+    let mut web_socket = WebSocket::new();
+    // set once we've sent our close frame, we then wait for the peer to answer it
+    let mut close_sent: Option<Instant> = None;
+    let mut last_activity = Instant::now();
+    let mut buf = vec![0u8; 65536];
+    let mut input = leftover;
+    loop {
+        // write out anything queued up
+        loop {
+            let command = match command_receiver.try_recv() {
+                Ok(command) => command,
+                Err(TryRecvError::Empty) => break,
+                // the owner is gone, thats a normal close
+                Err(TryRecvError::Disconnected) => WebSocketClientCommand::Close{code: CLOSE_NORMAL, reason: String::new()}
+            };
+            if close_sent.is_some() {
+                break;
+            }
+            let frame = match command {
+                WebSocketClientCommand::Send(frame) => frame,
+                WebSocketClientCommand::Close{code, reason} => {
+                    close_sent = Some(Instant::now());
+                    WebSocket::build_close_message(code, &reason, true)
+                }
+            };
+            if let Err(e) = stream.write_all(&frame) {
+                on_event(WebSocketClientEvent::Error(format!("Cannot write to websocket: {}", e)));
+                return
+            }
+        }
+
+        if input.is_empty() {
+            match stream.read(&mut buf) {
+                Ok(0) => {
+                    on_event(WebSocketClientEvent::Closed{code: None, reason: String::new()});
+                    return
+                }
+                Ok(n) => input.extend_from_slice(&buf[0..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                    if let Some(close_sent) = close_sent {
+                        if close_sent.elapsed() > CLOSE_TIMEOUT {
+                            on_event(WebSocketClientEvent::Closed{code: None, reason: String::new()});
+                            return
+                        }
+                    }
+                    else if last_activity.elapsed() > KEEP_ALIVE_INTERVAL {
+                        last_activity = Instant::now();
+                        let ping = WebSocket::build_message(MessageHeader::from_len(0, MessageFormat::Ping, true), &[]);
+                        if stream.write_all(&ping).is_err() {
+                            on_event(WebSocketClientEvent::Closed{code: None, reason: String::new()});
+                            return
+                        }
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    on_event(WebSocketClientEvent::Error(format!("Cannot read from websocket: {}", e)));
+                    return
+                }
+            }
+        }
+        last_activity = Instant::now();
+
+        let mut reply = Vec::new();
+        let mut closed = None;
+        let mut failed = None;
+        web_socket.parse(&input, | result | {
+            if closed.is_some() || failed.is_some() {
+                return
+            }
+            match result {
+                Ok(WebSocketMessage::Ping(data)) => {
+                    reply.extend_from_slice(&WebSocket::build_message(MessageHeader::from_len(data.len(), MessageFormat::Pong, true), data));
+                }
+                Ok(WebSocketMessage::Pong(_)) => (),
+                Ok(WebSocketMessage::Text(text)) => on_event(WebSocketClientEvent::Text(text.to_string())),
+                Ok(WebSocketMessage::Binary(data)) => on_event(WebSocketClientEvent::Binary(data.to_vec())),
+                Ok(WebSocketMessage::Close{code, reason}) => {
+                    closed = Some((code, reason.to_string()));
+                }
+                Err(e) => {
+                    failed = Some((e.close_code(), format!("Websocket protocol error {:?}", e)));
+                }
+            }
+        });
+        input.clear();
+
+        if !reply.is_empty() && close_sent.is_none() && stream.write_all(&reply).is_err() {
+            on_event(WebSocketClientEvent::Closed{code: None, reason: String::new()});
+            return
+        }
+        if let Some((code, reason)) = closed {
+            // echo the close unless we started it
+            if close_sent.is_none() {
+                let _ = stream.write_all(&WebSocket::build_close_message(code.unwrap_or(CLOSE_NORMAL), "", true));
+            }
+            on_event(WebSocketClientEvent::Closed{code, reason});
+            return
+        }
+        if let Some((code, error)) = failed {
+            let _ = stream.write_all(&WebSocket::build_close_message(code, "", true));
+            on_event(WebSocketClientEvent::Error(error));
+            return
+        }
+    }
+}
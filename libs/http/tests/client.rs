@@ -0,0 +1,193 @@
+use makepad_http::server::*;
+use makepad_http::client::*;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::mpsc;
+
+// serves a couple of canned responses so we can exercise the client end to end
+fn start_test_server() -> u16 {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let (tx_request, rx_request) = mpsc::channel::<HttpServerRequest>();
+    start_http_server(HttpServer {
+        listen_address: SocketAddr::from(([127, 0, 0, 1], port)),
+        request: tx_request,
+        post_max_size: 1024 * 1024,
+    }).unwrap();
+    std::thread::spawn(move || {
+        while let Ok(request) = rx_request.recv() {
+            let (path, lines, response_sender, body) = match request {
+                HttpServerRequest::Get {headers, response_sender} => (headers.path, headers.lines, response_sender, Vec::new()),
+                HttpServerRequest::Post {headers, body, response} => (headers.path, headers.lines, response, body),
+                _ => continue
+            };
+            // sends you on to the /headers of the server on another port
+            if let Some(port) = path.strip_prefix("/redirect_to/") {
+                let header = format!("HTTP/1.1 302 Found\r\nLocation: http://127.0.0.1:{}/headers\r\nContent-Length: 0\r\n\r\n", port);
+                let _ = response_sender.send(HttpServerResponse {header, body: Vec::new()});
+                continue;
+            }
+            let (header, body) = match path.as_str() {
+                "/plain" => (
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\n".to_string(),
+                    b"hello".to_vec()
+                ),
+                "/chunked" => (
+                    "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n".to_string(),
+                    b"4\r\nmake\r\n3;ext=1\r\npad\r\n0\r\nX-Trailer: 1\r\n\r\n".to_vec()
+                ),
+                "/huge_chunk" => (
+                    "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n".to_string(),
+                    b"fffffffffffffff\r\nmakepad".to_vec()
+                ),
+                "/redirect" => (
+                    "HTTP/1.1 302 Found\r\nLocation: /plain\r\nContent-Length: 0\r\n\r\n".to_string(),
+                    Vec::new()
+                ),
+                "/redirect_headers" => (
+                    "HTTP/1.1 302 Found\r\nLocation: /headers\r\nContent-Length: 0\r\n\r\n".to_string(),
+                    Vec::new()
+                ),
+                "/headers" => {
+                    let lines = lines.concat();
+                    (format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", lines.len()), lines.into_bytes())
+                }
+                "/echo" => (
+                    format!("HTTP/1.1 201 Created\r\nContent-Length: {}\r\n\r\n", body.len()),
+                    body
+                ),
+                _ => (
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string(),
+                    Vec::new()
+                )
+            };
+            let _ = response_sender.send(HttpServerResponse {header, body});
+        }
+    });
+    port
+}
+
+fn get(port: u16, path: &str) -> HttpClientResponse {
+    let url = format!("http://127.0.0.1:{}{}", port, path);
+    http_client_request(&HttpClientRequest {
+        method: "GET",
+        url: &url,
+        headers: Vec::new(),
+        body: None,
+    }, | _ | {}).unwrap()
+}
+
+#[test]
+fn test_client() {
+    let port = start_test_server();
+
+    let response = get(port, "/plain");
+    assert_eq!(response.status_code, 200);
+    assert_eq!(response.header("content-type"), Some("text/plain"));
+    assert_eq!(response.body, b"hello");
+
+    let response = get(port, "/chunked");
+    assert_eq!(response.status_code, 200);
+    assert_eq!(response.body, b"makepad");
+
+    // a chunk size the server never delivers must fail instead of allocating it
+    let url = format!("http://127.0.0.1:{}/huge_chunk", port);
+    assert!(http_client_request(&HttpClientRequest {
+        method: "GET",
+        url: &url,
+        headers: Vec::new(),
+        body: None,
+    }, | _ | {}).is_err());
+
+    let response = get(port, "/redirect");
+    assert_eq!(response.status_code, 200);
+    assert!(response.url.ends_with("/plain"));
+    assert_eq!(response.body, b"hello");
+
+    let response = get(port, "/missing");
+    assert_eq!(response.status_code, 404);
+    assert!(response.body.is_empty());
+
+    let url = format!("http://127.0.0.1:{}/echo", port);
+    let mut progress = Vec::new();
+    let response = http_client_request(&HttpClientRequest {
+        method: "POST",
+        url: &url,
+        headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+        body: Some(b"{\"a\":1}"),
+    }, | event | if let HttpClientEvent::Progress {loaded, total} = event {
+        progress.push((loaded, total))
+    }).unwrap();
+    assert_eq!(response.status_code, 201);
+    assert_eq!(response.body, b"{\"a\":1}");
+    assert_eq!(progress.last(), Some(&(7, Some(7))));
+}
+
+#[test]
+fn test_redirect_credentials() {
+    let port = start_test_server();
+    let other_port = start_test_server();
+    let request_headers = | path: &str | {
+        let url = format!("http://127.0.0.1:{}{}", port, path);
+        let response = http_client_request(&HttpClientRequest {
+            method: "GET",
+            url: &url,
+            headers: vec![
+                ("Authorization".to_string(), "Bearer secret".to_string()),
+                ("Cookie".to_string(), "session=secret".to_string()),
+                ("X-Custom".to_string(), "kept".to_string()),
+            ],
+            body: None,
+        }, | _ | {}).unwrap();
+        assert_eq!(response.status_code, 200);
+        String::from_utf8(response.body).unwrap()
+    };
+
+    // a redirect on the same origin keeps the credentials
+    let lines = request_headers("/redirect_headers");
+    assert!(lines.contains("Authorization: Bearer secret"));
+    assert!(lines.contains("Cookie: session=secret"));
+
+    // a redirect to another port drops them, but keeps the other headers
+    let lines = request_headers(&format!("/redirect_to/{}", other_port));
+    assert!(!lines.contains("secret"));
+    assert!(lines.contains("X-Custom: kept"));
+}
+
+#[test]
+fn test_https_without_connector() {
+    let port = start_test_server();
+    let url = format!("https://127.0.0.1:{}/plain", port);
+    let result = http_client_request(&HttpClientRequest {
+        method: "GET",
+        url: &url,
+        headers: Vec::new(),
+        body: None,
+    }, | _ | {});
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_url() {
+    let url = HttpUrl::parse("https://user@example.com:8443/a/b?c=d#frag").unwrap();
+    assert!(url.is_secure);
+    assert_eq!(url.host, "example.com");
+    assert_eq!(url.port, 8443);
+    assert_eq!(url.path, "/a/b?c=d");
+    assert_eq!(url.host_header(), "example.com:8443");
+
+    let url = HttpUrl::parse("http://example.com?q").unwrap();
+    assert_eq!(url.port, 80);
+    assert_eq!(url.path, "/?q");
+    assert_eq!(url.host_header(), "example.com");
+
+    let url = HttpUrl::parse("http://[::1]:8080/").unwrap();
+    assert_eq!(url.host, "::1");
+    assert_eq!(url.host_header(), "[::1]:8080");
+
+    let base = HttpUrl::parse("http://example.com/a/b").unwrap();
+    assert_eq!(base.join("c").unwrap().path, "/a/c");
+    assert_eq!(base.join("/d").unwrap().path, "/d");
+    assert_eq!(base.join("//other.org/e").unwrap().host, "other.org");
+    assert!(base.join("https://other.org/").unwrap().is_secure);
+
+    assert!(HttpUrl::parse("ftp://example.com").is_err());
+}
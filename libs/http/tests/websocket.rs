@@ -0,0 +1,108 @@
+use makepad_http::server::*;
+use makepad_http::websocket::*;
+use makepad_http::websocket_client::*;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::mpsc;
+use std::time::Duration;
+
+fn frame(first: u8, data: &[u8]) -> Vec<u8> {
+    let mut frame = vec![first, data.len() as u8];
+    frame.extend_from_slice(data);
+    frame
+}
+
+fn parse_all(web_socket: &mut WebSocket, input: &[u8]) -> Vec<String> {
+    let mut out = Vec::new();
+    web_socket.parse(input, | result | out.push(match result {
+        Ok(WebSocketMessage::Ping(data)) => format!("ping {:?}", data),
+        Ok(WebSocketMessage::Pong(data)) => format!("pong {:?}", data),
+        Ok(WebSocketMessage::Text(text)) => format!("text {}", text),
+        Ok(WebSocketMessage::Binary(data)) => format!("binary {:?}", data),
+        Ok(WebSocketMessage::Close{code, reason}) => format!("close {:?} {}", code, reason),
+        Err(e) => format!("error {:?}", e)
+    }));
+    out
+}
+
+#[test]
+fn test_fragmented_messages() {
+    let mut web_socket = WebSocket::new();
+    let mut input = Vec::new();
+    input.extend(frame(1, b"frag"));
+    // a control frame in the middle of a fragmented message
+    input.extend(frame(128 | 9, b"p"));
+    input.extend(frame(0, b"men"));
+    input.extend(frame(128, b"ted"));
+    input.extend(frame(128 | 2, &[1, 2]));
+    // feed it one byte at a time to exercise the partial state handling
+    let mut out = Vec::new();
+    for byte in &input {
+        out.extend(parse_all(&mut web_socket, &[*byte]));
+    }
+    assert_eq!(out, vec!["ping [112]", "text fragmented", "binary [1, 2]"]);
+
+    let out = parse_all(&mut WebSocket::new(), &frame(128, b"x"));
+    assert_eq!(out, vec!["error UnexpectedContinuation"]);
+}
+
+#[test]
+fn test_close_and_masking() {
+    let close = WebSocket::build_close_message(CLOSE_PROTOCOL_ERROR, "bye", true);
+    let out = parse_all(&mut WebSocket::new(), &close);
+    assert_eq!(out, vec!["close Some(1002) bye"]);
+
+    let header = MessageHeader::from_len(300, MessageFormat::Binary, true);
+    let data: Vec<u8> = (0..300).map( | i | i as u8).collect();
+    let message = WebSocket::build_message(header, &data);
+    let out = parse_all(&mut WebSocket::new(), &message);
+    assert_eq!(out, vec![format!("binary {:?}", data)]);
+
+    let out = parse_all(&mut WebSocket::new(), &frame(128 | 8, &[]));
+    assert_eq!(out, vec!["close None "]);
+}
+
+#[test]
+fn test_client_echo() {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let (tx_request, rx_request) = mpsc::channel::<HttpServerRequest>();
+    start_http_server(HttpServer {
+        listen_address: SocketAddr::from(([127, 0, 0, 1], port)),
+        request: tx_request,
+        post_max_size: 1024,
+    }).unwrap();
+    // echo every binary message back
+    std::thread::spawn(move || {
+        while let Ok(request) = rx_request.recv() {
+            if let HttpServerRequest::BinaryMessage {response_sender, data, ..} = request {
+                let _ = response_sender.send(data);
+            }
+        }
+    });
+
+    let (tx_event, rx_event) = mpsc::channel();
+    let client = WebSocketClient::connect(&format!("ws://127.0.0.1:{}/", port), Vec::new(), move | event | {
+        let _ = tx_event.send(event);
+    });
+    let timeout = Duration::from_secs(10);
+    assert_eq!(rx_event.recv_timeout(timeout).unwrap(), WebSocketClientEvent::Opened);
+
+    client.send_binary(b"hello").unwrap();
+    assert_eq!(rx_event.recv_timeout(timeout).unwrap(), WebSocketClientEvent::Binary(b"hello".to_vec()));
+
+    let big = vec![7u8; 100000];
+    client.send_binary(&big).unwrap();
+    assert_eq!(rx_event.recv_timeout(timeout).unwrap(), WebSocketClientEvent::Binary(big));
+
+    client.close(CLOSE_NORMAL, "").unwrap();
+    assert!(matches!(rx_event.recv_timeout(timeout).unwrap(), WebSocketClientEvent::Closed{..}));
+}
+
+#[test]
+fn test_client_refused() {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let (tx_event, rx_event) = mpsc::channel();
+    let _client = WebSocketClient::connect(&format!("ws://127.0.0.1:{}/", port), Vec::new(), move | event | {
+        let _ = tx_event.send(event);
+    });
+    assert!(matches!(rx_event.recv_timeout(Duration::from_secs(10)).unwrap(), WebSocketClientEvent::Error(_)));
+}
@@ -0,0 +1,343 @@
+// image_formats::gif
+
+use crate::ImageBuffer;
+
+fn from_le16(src: &[u8]) -> u16 {
+    ((src[1] as u16) << 8) | (src[0] as u16)
+}
+
+/// What happens to the area of a frame before the next frame is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Disposal {
+    /// the frame stays in place
+    None,
+    /// the area of the frame is cleared to transparent
+    Background,
+    /// the area of the frame goes back to what it was before the frame was drawn
+    Previous,
+}
+
+/// A single image of a GIF, placed at `x`,`y` on the canvas. Transparent pixels have alpha 0.
+pub struct Frame {
+    pub x: usize,
+    pub y: usize,
+    pub image: ImageBuffer,
+    /// how long the frame is shown, in seconds
+    pub delay: f64,
+    pub disposal: Disposal,
+}
+
+/// All frames of a GIF, in the order they are shown.
+pub struct Animation {
+    pub width: usize,
+    pub height: usize,
+    /// how often the animation plays, 0 is forever
+    pub num_plays: usize,
+    pub frames: Vec<Frame>,
+}
+
+struct Reader<'a> {
+    src: &'a [u8],
+    sp: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if self.sp + n > self.src.len() {
+            return Err("GIF data truncated".to_string());
+        }
+        let bytes = &self.src[self.sp..self.sp + n];
+        self.sp += n;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(from_le16(self.bytes(2)?))
+    }
+
+    fn palette(&mut self, size: usize) -> Result<Vec<u32>, String> {
+        let bytes = self.bytes(size * 3)?;
+        Ok(bytes.chunks(3).map(|c| 0xFF000000 | ((c[0] as u32) << 16) | ((c[1] as u32) << 8) | (c[2] as u32)).collect())
+    }
+
+    // the data of extensions and images is split in blocks of at most 255 bytes
+    fn sub_blocks(&mut self) -> Result<Vec<u8>, String> {
+        let mut data = Vec::new();
+        loop {
+            let size = self.byte()? as usize;
+            if size == 0 {
+                return Ok(data);
+            }
+            data.extend_from_slice(self.bytes(size)?);
+        }
+    }
+}
+
+const MAX_CODES: usize = 4096;
+
+// the sizes in the file are 16 bits, without a cap a few bytes of it can ask for gigabytes
+const MAX_PIXELS: usize = 1 << 26;
+
+// every frame is composed on its own copy of the canvas, the animation stops at the frame
+// that goes over this
+const MAX_ANIMATION_PIXELS: usize = 1 << 28;
+
+// writes the string of `code` at `dp`, the table only knows the last byte of every string
+// so it is written back to front
+fn put_string(dst: &mut [u8], dp: usize, code: usize, prefix: &[u16], suffix: &[u8], length: &[u16]) -> usize {
+    let len = length[code] as usize;
+    let mut code = code;
+    for i in (0..len).rev() {
+        if dp + i < dst.len() {
+            dst[dp + i] = suffix[code];
+        }
+        code = prefix[code] as usize;
+    }
+    len
+}
+
+// returns how many pixels the data had, files that end early leave the rest of `dst` alone
+fn lzw_decode(src: &[u8], min_code_size: u8, dst: &mut [u8]) -> Result<usize, String> {
+    if (min_code_size == 0) || (min_code_size > 11) {
+        return Err("Invalid GIF code size".to_string());
+    }
+    let clear = 1usize << min_code_size;
+    let end = clear + 1;
+    let mut prefix = [0u16; MAX_CODES];
+    let mut suffix = [0u8; MAX_CODES];
+    let mut first = [0u8; MAX_CODES];
+    let mut length = [0u16; MAX_CODES];
+    for i in 0..clear {
+        suffix[i] = i as u8;
+        first[i] = i as u8;
+        length[i] = 1;
+    }
+    let mut next = end + 1;
+    let mut code_size = min_code_size as u32 + 1;
+    let mut previous: Option<usize> = None;
+    let mut bits: u32 = 0;
+    let mut nbits: u32 = 0;
+    let mut sp = 0;
+    let mut dp = 0;
+    while dp < dst.len() {
+        while nbits < code_size {
+            if sp == src.len() {
+                // a lot of files end the data early, the rest of the frame stays transparent
+                return Ok(dp.min(dst.len()));
+            }
+            bits |= (src[sp] as u32) << nbits;
+            sp += 1;
+            nbits += 8;
+        }
+        let code = (bits & ((1 << code_size) - 1)) as usize;
+        bits >>= code_size;
+        nbits -= code_size;
+        if code == clear {
+            next = end + 1;
+            code_size = min_code_size as u32 + 1;
+            previous = None;
+            continue;
+        }
+        if code == end {
+            break;
+        }
+        let prev = match previous {
+            None => {
+                if code > clear {
+                    return Err("Invalid GIF data".to_string());
+                }
+                dp += put_string(dst, dp, code, &prefix, &suffix, &length);
+                previous = Some(code);
+                continue;
+            },
+            Some(prev) => prev,
+        };
+        if code > next {
+            return Err("Invalid GIF data".to_string());
+        }
+        // a code that isn't in the table yet is the previous string followed by its own first byte,
+        // which is the entry added right here
+        let first_byte = if code < next { first[code] } else { first[prev] };
+        if next < MAX_CODES {
+            prefix[next] = prev as u16;
+            suffix[next] = first_byte;
+            first[next] = first[prev];
+            length[next] = length[prev] + 1;
+            next += 1;
+            if (next == (1 << code_size)) && (code_size < 12) {
+                code_size += 1;
+            }
+        }
+        else if code == next {
+            return Err("Invalid GIF data".to_string());
+        }
+        dp += put_string(dst, dp, code, &prefix, &suffix, &length);
+        previous = Some(code);
+    }
+    Ok(dp.min(dst.len()))
+}
+
+// interlaced images store every 8th row first, then the rows in between, down to every 2nd row
+fn interlaced_rows(height: usize) -> Vec<usize> {
+    let mut rows = Vec::with_capacity(height);
+    for &(start, step) in &[(0, 8), (4, 8), (2, 4), (1, 2)] {
+        rows.extend((start..height).step_by(step));
+    }
+    rows
+}
+
+pub fn test(src: &[u8]) -> Option<(usize, usize)> {
+    if (src.len() < 13) || ((&src[0..6] != b"GIF87a") && (&src[0..6] != b"GIF89a")) {
+        return None;
+    }
+    let width = from_le16(&src[6..8]) as usize;
+    let height = from_le16(&src[8..10]) as usize;
+    if (width == 0) || (height == 0) {
+        return None;
+    }
+    Some((width, height))
+}
+
+/// Decodes the first frame of a GIF.
+pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
+    let animation = decode_animation(src)?;
+    let mut image = ImageBuffer::new(animation.width, animation.height);
+    let frame = &animation.frames[0];
+    for y in 0..frame.image.height.min(image.height.saturating_sub(frame.y)) {
+        for x in 0..frame.image.width.min(image.width.saturating_sub(frame.x)) {
+            image.data[(frame.y + y) * image.width + frame.x + x] = frame.image.data[y * frame.image.width + x];
+        }
+    }
+    Ok(image)
+}
+
+/// Decodes all frames of a GIF, they still have to be composed on the canvas in order.
+pub fn decode_animation(src: &[u8]) -> Result<Animation, String> {
+    let (width, height) = test(src).ok_or_else(|| "Invalid GIF".to_string())?;
+    if width * height > MAX_PIXELS {
+        return Err("GIF too large".to_string());
+    }
+    let flags = src[10];
+    let mut reader = Reader {
+        src,
+        sp: 13,
+    };
+    let global_palette = if (flags & 0x80) != 0 {
+        Some(reader.palette(2 << (flags & 7))?)
+    }
+    else {
+        None
+    };
+    // without a NETSCAPE2.0 extension the animation plays once
+    let mut num_plays = 1;
+    let mut frames = Vec::new();
+    // the graphic control extension applies to the image that follows it
+    let mut delay = 0;
+    let mut disposal = Disposal::None;
+    let mut transparent = None;
+    let mut animation_pixels = 0;
+    loop {
+        let block = match reader.byte() {
+            Ok(block) => block,
+            Err(_) if !frames.is_empty() => break,
+            Err(err) => return Err(err),
+        };
+        match block {
+            0x21 => { // extension
+                let label = reader.byte()?;
+                let data = reader.sub_blocks()?;
+                match label {
+                    0xF9 if data.len() >= 4 => { // graphic control
+                        disposal = match (data[0] >> 2) & 7 {
+                            2 => Disposal::Background,
+                            3 => Disposal::Previous,
+                            _ => Disposal::None,
+                        };
+                        delay = from_le16(&data[1..3]);
+                        transparent = if (data[0] & 1) != 0 { Some(data[3]) } else { None };
+                    },
+                    0xFF if (data.len() >= 14) && ((&data[0..11] == b"NETSCAPE2.0") || (&data[0..11] == b"ANIMEXTS1.0")) && (data[11] == 1) => { // loop count
+                        // the loop count is how often the animation repeats after the first time
+                        let loops = from_le16(&data[12..14]) as usize;
+                        num_plays = if loops == 0 { 0 } else { loops + 1 };
+                    },
+                    _ => { },
+                }
+            },
+            0x2C => { // image
+                let x = reader.u16()? as usize;
+                let y = reader.u16()? as usize;
+                let frame_width = reader.u16()? as usize;
+                let frame_height = reader.u16()? as usize;
+                // only the part on the canvas is ever shown
+                if frame_width * frame_height > width * height {
+                    return Err("GIF frame larger than its canvas".to_string());
+                }
+                animation_pixels += width * height;
+                if animation_pixels > MAX_ANIMATION_PIXELS {
+                    break;
+                }
+                let flags = reader.byte()?;
+                let local_palette = if (flags & 0x80) != 0 {
+                    Some(reader.palette(2 << (flags & 7))?)
+                }
+                else {
+                    None
+                };
+                let palette = local_palette.as_ref().or(global_palette.as_ref()).ok_or_else(|| "GIF image without palette".to_string())?;
+                let min_code_size = reader.byte()?;
+                let data = reader.sub_blocks()?;
+                let mut indices = vec![0u8; frame_width * frame_height];
+                let num_pixels = lzw_decode(&data, min_code_size, &mut indices)?;
+                let rows = if (flags & 0x40) != 0 {
+                    interlaced_rows(frame_height)
+                }
+                else {
+                    (0..frame_height).collect()
+                };
+                let mut image = ImageBuffer::new(frame_width, frame_height);
+                for (i, row) in rows.into_iter().enumerate() {
+                    for x in 0..frame_width.min(num_pixels.saturating_sub(i * frame_width)) {
+                        let index = indices[i * frame_width + x];
+                        if Some(index) != transparent {
+                            image.data[row * frame_width + x] = palette.get(index as usize).copied().unwrap_or(0xFF000000);
+                        }
+                    }
+                }
+                frames.push(Frame {
+                    x,
+                    y,
+                    image,
+                    // browsers show frames without a delay, or a very short one, for a tenth of a second
+                    delay: if delay < 2 { 0.1 } else { delay as f64 / 100.0 },
+                    disposal,
+                });
+                delay = 0;
+                disposal = Disposal::None;
+                transparent = None;
+            },
+            0x3B => { // trailer
+                break;
+            },
+            _ => {
+                if frames.is_empty() {
+                    return Err("Invalid GIF block".to_string());
+                }
+                break;
+            },
+        }
+    }
+    if frames.is_empty() {
+        return Err("GIF without images".to_string());
+    }
+    Ok(Animation {
+        width,
+        height,
+        num_plays,
+        frames,
+    })
+}
@@ -362,6 +362,7 @@ fn unpack_progressive_refine_ac(reader: &mut Reader, coeffs: &mut[i32], acht: &T
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn unpack_block(reader: &mut Reader, coeffs: &mut [i32], dcht: &Table, acht: &Table, dc: &mut i32, start: u8, end: u8, shift: u8, refine: bool, eobrun: &mut usize) {
     if refine {
         if start == 0 {
@@ -384,6 +385,7 @@ fn unpack_block(reader: &mut Reader, coeffs: &mut [i32], dcht: &Table, acht: &Ta
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn unpack_macroblock(
     reader: &mut Reader,
     coeffs: &mut [i32],
@@ -403,10 +405,8 @@ fn unpack_macroblock(
     mask: u8
 ) {
     match itype {
-        TYPE_Y => {
-            if (mask & 1) != 0 {
-                unpack_block(reader, &mut coeffs[0..64], &dcht[dt[0]], &acht[at[0]], &mut dc[0], start, end, shift, refine, eobrun);
-            }
+        TYPE_Y if (mask & 1) != 0 => {
+            unpack_block(reader, &mut coeffs[0..64], &dcht[dt[0]], &acht[at[0]], &mut dc[0], start, end, shift, refine, eobrun);
         },
         TYPE_YUV420 => {
             if (mask & 1) != 0 {
@@ -911,17 +911,14 @@ pub fn test(src: &[u8]) -> Option<(usize, usize)> {
     while sp < src.len() {
         let marker = from_be16(&src[sp..sp + 2]);
         let length = from_be16(&src[sp + 2..sp + 4]) as usize;
-        match marker {
-            0xFFC0 | 0xFFC1 | 0xFFC2 => {
-                let width = from_be16(&src[sp + 5..sp + 7]) as usize;
-                let height = from_be16(&src[sp + 7..sp + 9]) as usize;
-                let components = src[sp + 9];
-                if (components == 1) || (components == 3) { // does not support RGBA or CMYK JPEGs
-                    return Some((width, height));
-                }
-                return None;
-            },
-            _ => {},
+        if (0xFFC0..=0xFFC2).contains(&marker) {
+            let height = from_be16(&src[sp + 5..sp + 7]) as usize;
+            let width = from_be16(&src[sp + 7..sp + 9]) as usize;
+            let components = src[sp + 9];
+            if (components == 1) || (components == 3) { // does not support RGBA or CMYK JPEGs
+                return Some((width, height));
+            }
+            return None;
         }
         sp += length + 2;
     }
@@ -962,7 +959,7 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
         let length = if marker != 0xFFD9 {from_be16(&src[sp + 2..sp + 4]) as usize} else {0};
         //println!("marker {:04X}, length {}",marker,length);
         match marker {
-            0xFFC0 | 0xFFC1 | 0xFFC2 => { // baseline sequential, extended sequential, progressive
+            0xFFC0..=0xFFC2 => { // baseline sequential, extended sequential, progressive
                 //println!("precision {}",src[sp + 4]);
                 if src[sp + 4] != 8 {
                     return Err("Invalid JPEG 2".to_string());
@@ -992,8 +989,8 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
                     let sw = ((samp[0] >> 4) * 8) as usize;
                     let sh = ((samp[0] & 15) * 8) as usize;
                     //println!("one macroblock = {}x{}",sw,sh);
-                    mbwidth = (width + sw - 1) / sw;
-                    mbheight = (height + sh - 1) / sh;
+                    mbwidth = width.div_ceil(sw);
+                    mbheight = height.div_ceil(sh);
                     //println!("{}x{} macroblocks ({}x{} pixels)",mbwidth,mbheight,mbwidth * sw,mbheight * sh);
                     cpmb = 128 + 64 * ((samp[0] >> 4) as usize) * ((samp[0] & 15) as usize);
                     itype = match samp[0] {
@@ -1007,8 +1004,8 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
                     };
                 }
                 else {
-                    mbwidth = (width + 7) / 8;
-                    mbheight = (height + 7) / 8;
+                    mbwidth = width.div_ceil(8);
+                    mbheight = height.div_ceil(8);
                     cpmb = 64;
                     itype = TYPE_Y;
                 }
@@ -1235,6 +1232,309 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
     Err("Invalid JPEG 11".to_string())
 }
 
-pub fn encode(_image: &ImageBuffer) -> Result<Vec<u8>, String> {
-    Err("not implemented yet".to_string())
+// zigzag position to position in the block
+const ZIGZAG: [usize; 64] = [0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20, 13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59, 52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63];
+
+// the example tables of the JPEG standard (Annex K)
+const LUMINANCE_QUANT: [u16; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61,
+    12, 12, 14, 19, 26, 58, 60, 55,
+    14, 13, 16, 24, 40, 57, 69, 56,
+    14, 17, 22, 29, 51, 87, 80, 62,
+    18, 22, 37, 56, 68, 109, 103, 77,
+    24, 35, 55, 64, 81, 104, 113, 92,
+    49, 64, 78, 87, 103, 121, 120, 101,
+    72, 92, 95, 98, 112, 100, 103, 99,
+];
+const CHROMINANCE_QUANT: [u16; 64] = [
+    17, 18, 24, 47, 99, 99, 99, 99,
+    18, 21, 26, 66, 99, 99, 99, 99,
+    24, 26, 56, 99, 99, 99, 99, 99,
+    47, 66, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+];
+const LUMINANCE_DC_BITS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const LUMINANCE_DC_VALUES: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const CHROMINANCE_DC_BITS: [u8; 16] = [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0];
+const CHROMINANCE_DC_VALUES: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const LUMINANCE_AC_BITS: [u8; 16] = [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7D];
+const LUMINANCE_AC_VALUES: [u8; 162] = [
+    0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07,
+    0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xA1, 0x08, 0x23, 0x42, 0xB1, 0xC1, 0x15, 0x52, 0xD1, 0xF0,
+    0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0A, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x25, 0x26, 0x27, 0x28,
+    0x29, 0x2A, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49,
+    0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69,
+    0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89,
+    0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7,
+    0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3, 0xC4, 0xC5,
+    0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xE1, 0xE2,
+    0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8,
+    0xF9, 0xFA,
+];
+const CHROMINANCE_AC_BITS: [u8; 16] = [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77];
+const CHROMINANCE_AC_VALUES: [u8; 162] = [
+    0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21, 0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61, 0x71,
+    0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91, 0xA1, 0xB1, 0xC1, 0x09, 0x23, 0x33, 0x52, 0xF0,
+    0x15, 0x62, 0x72, 0xD1, 0x0A, 0x16, 0x24, 0x34, 0xE1, 0x25, 0xF1, 0x17, 0x18, 0x19, 0x1A, 0x26,
+    0x27, 0x28, 0x29, 0x2A, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48,
+    0x49, 0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68,
+    0x69, 0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87,
+    0x88, 0x89, 0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5,
+    0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3,
+    0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA,
+    0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8,
+    0xF9, 0xFA,
+];
+
+/// How much the color of an image is reduced next to its brightness.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Subsampling {
+    /// full color resolution
+    YUV444,
+    /// half the horizontal color resolution
+    YUV422,
+    /// half the horizontal and vertical color resolution
+    YUV420,
+}
+
+/// Settings for `encode_with_options`.
+#[derive(Clone, Copy, Debug)]
+pub struct JpegOptions {
+    /// from 1 (smallest) to 100 (best)
+    pub quality: u8,
+    pub subsampling: Subsampling,
+}
+
+impl Default for JpegOptions {
+    fn default() -> Self {
+        JpegOptions {
+            quality: 90,
+            subsampling: Subsampling::YUV420,
+        }
+    }
+}
+
+// scales a table of the standard the way libjpeg does, 50 is the table itself
+fn scale_quant(table: &[u16; 64], quality: u8) -> [u16; 64] {
+    let quality = quality.clamp(1, 100) as u32;
+    let scale = if quality < 50 { 5000 / quality } else { 200 - quality * 2 };
+    let mut result = [0u16; 64];
+    for i in 0..64 {
+        result[i] = ((table[i] as u32 * scale + 50) / 100).clamp(1, 255) as u16;
+    }
+    result
+}
+
+// the code and length of every symbol of a huffman table
+fn huffman_codes(bits: &[u8; 16], values: &[u8]) -> [(u16, u8); 256] {
+    let mut codes = [(0u16, 0u8); 256];
+    let mut code: u16 = 0;
+    let mut k = 0;
+    for length in 1..17 {
+        for _ in 0..bits[length - 1] {
+            codes[values[k] as usize] = (code, length as u8);
+            code += 1;
+            k += 1;
+        }
+        code <<= 1;
+    }
+    codes
+}
+
+struct Writer {
+    dst: Vec<u8>,
+    bits: u32,
+    nbits: u32,
+}
+
+impl Writer {
+    fn put(&mut self, code: u32, length: u8) {
+        self.bits = (self.bits << length) | (code & ((1 << length) - 1));
+        self.nbits += length as u32;
+        while self.nbits >= 8 {
+            let byte = (self.bits >> (self.nbits - 8)) as u8;
+            self.dst.push(byte);
+            if byte == 0xFF {
+                self.dst.push(0x00);
+            }
+            self.nbits -= 8;
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.nbits > 0 {
+            self.put(0x7F, (8 - self.nbits) as u8);
+        }
+    }
+
+    fn marker(&mut self, marker: u16, data: &[u8]) {
+        self.dst.extend_from_slice(&marker.to_be_bytes());
+        self.dst.extend_from_slice(&((data.len() + 2) as u16).to_be_bytes());
+        self.dst.extend_from_slice(data);
+    }
+}
+
+// category and extra bits of a coefficient
+fn magnitude(value: i32) -> (u8, u32) {
+    let category = (32 - value.unsigned_abs().leading_zeros()) as u8;
+    let bits = if value < 0 { (value - 1) as u32 } else { value as u32 };
+    (category, bits)
+}
+
+fn fdct(block: &[f32; 64], cos: &[[f32; 8]; 8]) -> [f32; 64] {
+    let mut rows = [0f32; 64];
+    for y in 0..8 {
+        for u in 0..8 {
+            rows[y * 8 + u] = (0..8).map(|x| block[y * 8 + x] * cos[u][x]).sum();
+        }
+    }
+    let mut result = [0f32; 64];
+    for v in 0..8 {
+        for u in 0..8 {
+            result[v * 8 + u] = (0..8).map(|y| rows[y * 8 + u] * cos[v][y]).sum();
+        }
+    }
+    result
+}
+
+fn encode_block(writer: &mut Writer, block: &[f32; 64], cos: &[[f32; 8]; 8], quant: &[u16; 64], previous_dc: &mut i32, dc_codes: &[(u16, u8); 256], ac_codes: &[(u16, u8); 256]) {
+    let coeffs = fdct(block, cos);
+    let dc = (coeffs[0] / quant[0] as f32).round() as i32;
+    let (category, bits) = magnitude(dc - *previous_dc);
+    *previous_dc = dc;
+    let (code, length) = dc_codes[category as usize];
+    writer.put(code as u32, length);
+    writer.put(bits, category);
+    let mut run = 0;
+    for &i in &ZIGZAG[1..] {
+        // baseline AC coefficients go up to category 10
+        let value = ((coeffs[i] / quant[i] as f32).round() as i32).clamp(-1023, 1023);
+        if value == 0 {
+            run += 1;
+            continue;
+        }
+        while run >= 16 {
+            let (code, length) = ac_codes[0xF0];
+            writer.put(code as u32, length);
+            run -= 16;
+        }
+        let (category, bits) = magnitude(value);
+        let (code, length) = ac_codes[(run << 4) | category as usize];
+        writer.put(code as u32, length);
+        writer.put(bits, category);
+        run = 0;
+    }
+    if run > 0 {
+        let (code, length) = ac_codes[0x00];
+        writer.put(code as u32, length);
+    }
+}
+
+pub fn encode(image: &ImageBuffer) -> Result<Vec<u8>, String> {
+    encode_with_options(image, &JpegOptions::default())
+}
+
+/// Writes a baseline JPEG, alpha is left out.
+pub fn encode_with_options(image: &ImageBuffer, options: &JpegOptions) -> Result<Vec<u8>, String> {
+    let width = image.width;
+    let height = image.height;
+    if (width == 0) || (height == 0) || (width >= 65536) || (height >= 65536) || (image.data.len() != width * height) {
+        return Err("Invalid image size".to_string());
+    }
+    let (hs, vs) = match options.subsampling {
+        Subsampling::YUV444 => (1, 1),
+        Subsampling::YUV422 => (2, 1),
+        Subsampling::YUV420 => (2, 2),
+    };
+    let luminance_quant = scale_quant(&LUMINANCE_QUANT, options.quality);
+    let chrominance_quant = scale_quant(&CHROMINANCE_QUANT, options.quality);
+    let mut writer = Writer {
+        dst: vec![0xFF, 0xD8],
+        bits: 0,
+        nbits: 0,
+    };
+    writer.marker(0xFFE0, &[b'J', b'F', b'I', b'F', 0, 1, 1, 0, 0, 1, 0, 1, 0, 0]); // JFIF
+    for (id, quant) in [&luminance_quant, &chrominance_quant].iter().enumerate() {
+        let mut dqt = vec![id as u8];
+        dqt.extend(ZIGZAG.iter().map(|i| quant[*i] as u8));
+        writer.marker(0xFFDB, &dqt);
+    }
+    let mut sof = vec![8];
+    sof.extend_from_slice(&(height as u16).to_be_bytes());
+    sof.extend_from_slice(&(width as u16).to_be_bytes());
+    sof.extend_from_slice(&[3, 1, ((hs << 4) | vs) as u8, 0, 2, 0x11, 1, 3, 0x11, 1]);
+    writer.marker(0xFFC0, &sof);
+    for &(class_id, bits, values) in &[
+        (0x00u8, &LUMINANCE_DC_BITS, &LUMINANCE_DC_VALUES[..]),
+        (0x10, &LUMINANCE_AC_BITS, &LUMINANCE_AC_VALUES[..]),
+        (0x01, &CHROMINANCE_DC_BITS, &CHROMINANCE_DC_VALUES[..]),
+        (0x11, &CHROMINANCE_AC_BITS, &CHROMINANCE_AC_VALUES[..]),
+    ] {
+        let mut dht = vec![class_id];
+        dht.extend_from_slice(bits);
+        dht.extend_from_slice(values);
+        writer.marker(0xFFC4, &dht);
+    }
+    writer.marker(0xFFDA, &[3, 1, 0x00, 2, 0x11, 3, 0x11, 0, 63, 0]);
+
+    let luminance_dc = huffman_codes(&LUMINANCE_DC_BITS, &LUMINANCE_DC_VALUES);
+    let luminance_ac = huffman_codes(&LUMINANCE_AC_BITS, &LUMINANCE_AC_VALUES);
+    let chrominance_dc = huffman_codes(&CHROMINANCE_DC_BITS, &CHROMINANCE_DC_VALUES);
+    let chrominance_ac = huffman_codes(&CHROMINANCE_AC_BITS, &CHROMINANCE_AC_VALUES);
+    let mut cos = [[0f32; 8]; 8];
+    for (u, row) in cos.iter_mut().enumerate() {
+        let scale = if u == 0 { (0.125f32).sqrt() } else { 0.5 };
+        for (x, c) in row.iter_mut().enumerate() {
+            *c = scale * (((2 * x + 1) * u) as f32 * std::f32::consts::PI / 16.0).cos();
+        }
+    }
+
+    // YCbCr of the whole image, centered around 0
+    let mut planes = vec![vec![0f32; width * height]; 3];
+    for (i, c) in image.data.iter().enumerate() {
+        let r = ((c >> 16) & 255) as f32;
+        let g = ((c >> 8) & 255) as f32;
+        let b = (c & 255) as f32;
+        planes[0][i] = 0.299 * r + 0.587 * g + 0.114 * b - 128.0;
+        planes[1][i] = -0.168736 * r - 0.331264 * g + 0.5 * b;
+        planes[2][i] = 0.5 * r - 0.418688 * g - 0.081312 * b;
+    }
+    // the edges are repeated to fill the last macroblocks
+    let sample = |plane: &[f32], x: usize, y: usize| plane[y.min(height - 1) * width + x.min(width - 1)];
+    let mut previous_dc = [0i32; 3];
+    let mut block = [0f32; 64];
+    for my in 0..height.div_ceil(vs * 8) {
+        for mx in 0..width.div_ceil(hs * 8) {
+            for by in 0..vs {
+                for bx in 0..hs {
+                    for y in 0..8 {
+                        for x in 0..8 {
+                            block[y * 8 + x] = sample(&planes[0], (mx * hs + bx) * 8 + x, (my * vs + by) * 8 + y);
+                        }
+                    }
+                    encode_block(&mut writer, &block, &cos, &luminance_quant, &mut previous_dc[0], &luminance_dc, &luminance_ac);
+                }
+            }
+            for component in 1..3 {
+                for y in 0..8 {
+                    for x in 0..8 {
+                        let mut sum = 0.0;
+                        for sy in 0..vs {
+                            for sx in 0..hs {
+                                sum += sample(&planes[component], (mx * 8 + x) * hs + sx, (my * 8 + y) * vs + sy);
+                            }
+                        }
+                        block[y * 8 + x] = sum / (hs * vs) as f32;
+                    }
+                }
+                encode_block(&mut writer, &block, &cos, &chrominance_quant, &mut previous_dc[component], &chrominance_dc, &chrominance_ac);
+            }
+        }
+    }
+    writer.flush();
+    writer.dst.extend_from_slice(&[0xFF, 0xD9]);
+    Ok(writer.dst)
 }
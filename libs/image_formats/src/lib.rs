@@ -5,6 +5,7 @@
 mod image;
 pub use image::*;
 //pub mod bmp;
-//pub mod png;
+pub mod png;
 pub mod jpeg;
+pub mod gif;
 
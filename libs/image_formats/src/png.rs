@@ -492,6 +492,7 @@ fn make_c(c: u32, gamma: f32) -> u32 {
     (ua << 24) | (ur << 16) | (ug << 8) | ub
 }
 
+#[allow(clippy::too_many_arguments)]
 fn decode_pixels(dst: &mut [u32], src: &[u8], width: usize, height: usize, stride: usize, x0: usize, y0: usize, dx: usize, dy: usize, itype: u16, palette: &[u32; 256], gamma: f32) {
     let mut sp = 0;
     match itype {
@@ -809,12 +810,12 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
                     return Err("Invalid PNG".to_string());
                 }
                 match itype {
-                    TYPE_L1 => {stride = (width + 7) / 8; bpp = 1;},
-                    TYPE_C1 => {stride = (width + 7) / 8; bpp = 1; need_plte = true;},
-                    TYPE_L2 => {stride = (width + 3) / 4; bpp = 1;},
-                    TYPE_C2 => {stride = (width + 3) / 4; bpp = 1; need_plte = true;},
-                    TYPE_L4 => {stride = (width + 1) / 2; bpp = 1;},
-                    TYPE_C4 => {stride = (width + 1) / 2; bpp = 1; need_plte = true;},
+                    TYPE_L1 => {stride = width.div_ceil(8); bpp = 1;},
+                    TYPE_C1 => {stride = width.div_ceil(8); bpp = 1; need_plte = true;},
+                    TYPE_L2 => {stride = width.div_ceil(4); bpp = 1;},
+                    TYPE_C2 => {stride = width.div_ceil(4); bpp = 1; need_plte = true;},
+                    TYPE_L4 => {stride = width.div_ceil(2); bpp = 1;},
+                    TYPE_C4 => {stride = width.div_ceil(2); bpp = 1; need_plte = true;},
                     TYPE_L8 => {stride = width; bpp = 1;},
                     TYPE_RGB8 => {stride = width * 3; bpp = 3;},
                     TYPE_C8 => {stride = width; bpp = 1; need_plte = true;},
@@ -923,7 +924,12 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
             },
             0x74524E53 => { // tRNS
                 //println!("tRNS {}",chunk_length);
-                // transparency information
+                // transparency information, only palette images are supported
+                if (itype == TYPE_C1) || (itype == TYPE_C2) || (itype == TYPE_C4) || (itype == TYPE_C8) {
+                    for (i, color) in palette.iter_mut().take(chunk_length).enumerate() {
+                        *color = (*color & 0x00FFFFFF) | ((src[sp + i] as u32) << 24);
+                    }
+                }
                 sp += chunk_length;
             }
             0x7A545874 => { // zTXt
@@ -965,12 +971,12 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
             awidth[i] = (width + adx[i] - ax0[i] - 1) / adx[i];
             aheight[i] = (height + ady[i] - ay0[i] - 1) / ady[i];
             astride[i] = match itype {
-                TYPE_L1 => {(awidth[i] + 7) / 8},
-                TYPE_C1 => {(awidth[i] + 7) / 8},
-                TYPE_L2 => {(awidth[i] + 3) / 4},
-                TYPE_C2 => {(awidth[i] + 3) / 4},
-                TYPE_L4 => {(awidth[i] + 1) / 2},
-                TYPE_C4 => {(awidth[i] + 1) / 2},
+                TYPE_L1 => {awidth[i].div_ceil(8)},
+                TYPE_C1 => {awidth[i].div_ceil(8)},
+                TYPE_L2 => {awidth[i].div_ceil(4)},
+                TYPE_C2 => {awidth[i].div_ceil(4)},
+                TYPE_L4 => {awidth[i].div_ceil(2)},
+                TYPE_C4 => {awidth[i].div_ceil(2)},
                 TYPE_L8 => {awidth[i]},
                 TYPE_RGB8 => {awidth[i] * 3},
                 TYPE_C8 => {awidth[i]},
@@ -1039,6 +1045,204 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
     }
 }
 
-pub fn encode(_image: &ImageBuffer) -> Result<Vec<u8>, String> {
-    Err("not implemented".to_string())
+/// Settings for `encode_with_options`.
+#[derive(Clone, Copy, Debug)]
+pub struct PngOptions {
+    /// store images with at most 256 colors as a palette
+    pub palette: bool,
+    /// deflate level, from 0 (store) to 10 (smallest)
+    pub level: u8,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        PngOptions {
+            palette: true,
+            level: 6,
+        }
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if (c & 1) != 0 { 0xEDB88320 ^ (c >> 1) } else { c >> 1 };
+        }
+        *entry = c;
+    }
+    let mut crc = 0xFFFFFFFF;
+    for byte in data {
+        crc = table[((crc ^ *byte as u32) & 255) as usize] ^ (crc >> 8);
+    }
+    crc ^ 0xFFFFFFFF
+}
+
+fn push_be32(dst: &mut Vec<u8>, d: u32) {
+    dst.extend_from_slice(&d.to_be_bytes());
+}
+
+fn push_chunk(dst: &mut Vec<u8>, chunk_type: u32, data: &[u8]) {
+    push_be32(dst, data.len() as u32);
+    let start = dst.len();
+    push_be32(dst, chunk_type);
+    dst.extend_from_slice(data);
+    let crc = crc32(&dst[start..]);
+    push_be32(dst, crc);
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let d = a as i32 + b as i32 - c as i32;
+    let pa = (d - a as i32).abs();
+    let pb = (d - b as i32).abs();
+    let pc = (d - c as i32).abs();
+    if (pa <= pb) && (pa <= pc) {
+        a
+    }
+    else if pb <= pc {
+        b
+    }
+    else {
+        c
+    }
+}
+
+// filters every row with the filter that gives the smallest sum of absolute values, which
+// tends to deflate best. Palette data doesn't predict well, so it isn't filtered.
+fn filter(raw: &[u8], height: usize, stride: usize, bpp: usize, adaptive: bool) -> Vec<u8> {
+    let mut dst: Vec<u8> = Vec::with_capacity((stride + 1) * height);
+    let mut row: Vec<u8> = vec![0; stride];
+    let zero: Vec<u8> = vec![0; stride];
+    for y in 0..height {
+        let cur = &raw[y * stride..(y + 1) * stride];
+        let prev = if y >= 1 { &raw[(y - 1) * stride..y * stride] } else { &zero[..] };
+        if !adaptive {
+            dst.push(0);
+            dst.extend_from_slice(cur);
+            continue;
+        }
+        let mut best_ftype = 0;
+        let mut best_sum = usize::MAX;
+        let mut best_row: Vec<u8> = Vec::new();
+        for ftype in 0..5u8 {
+            let mut sum = 0;
+            for x in 0..stride {
+                let a = if x >= bpp { cur[x - bpp] } else { 0 };
+                let b = prev[x];
+                let c = if x >= bpp { prev[x - bpp] } else { 0 };
+                let d = cur[x].wrapping_sub(match ftype {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) >> 1) as u8,
+                    _ => paeth(a, b, c),
+                });
+                row[x] = d;
+                sum += (d as i8).unsigned_abs() as usize;
+            }
+            if sum < best_sum {
+                best_sum = sum;
+                best_ftype = ftype;
+                best_row.clear();
+                best_row.extend_from_slice(&row);
+            }
+        }
+        dst.push(best_ftype);
+        dst.extend_from_slice(&best_row);
+    }
+    dst
+}
+
+// the colors of the image if there are at most 256 of them, in order of first appearance
+fn find_palette(image: &ImageBuffer) -> Option<Vec<u32>> {
+    let mut palette: Vec<u32> = Vec::new();
+    let mut last = None;
+    for c in &image.data {
+        if last == Some(*c) {
+            continue;
+        }
+        last = Some(*c);
+        if !palette.contains(c) {
+            if palette.len() == 256 {
+                return None;
+            }
+            palette.push(*c);
+        }
+    }
+    Some(palette)
+}
+
+pub fn encode(image: &ImageBuffer) -> Result<Vec<u8>, String> {
+    encode_with_options(image, &PngOptions::default())
+}
+
+/// Writes RGB when every pixel is opaque and RGBA when not, or a palette of 1, 2, 4 or 8 bits
+/// per pixel when the options allow it and the image has few enough colors.
+pub fn encode_with_options(image: &ImageBuffer, options: &PngOptions) -> Result<Vec<u8>, String> {
+    let width = image.width;
+    let height = image.height;
+    if (width == 0) || (height == 0) || (width >= 65536) || (height >= 65536) || (image.data.len() != width * height) {
+        return Err("Invalid image size".to_string());
+    }
+    let has_alpha = image.data.iter().any(|c| (c >> 24) != 255);
+    let palette = if options.palette { find_palette(image) } else { None };
+    let mut dst: Vec<u8> = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+    let (itype, stride, bpp, raw) = if let Some(palette) = &palette {
+        let bits = match palette.len() {
+            0..=2 => 1,
+            3..=4 => 2,
+            5..=16 => 4,
+            _ => 8,
+        };
+        let stride = (width * bits).div_ceil(8);
+        let mut raw: Vec<u8> = vec![0; stride * height];
+        let mut index = 0;
+        for y in 0..height {
+            for x in 0..width {
+                let c = image.data[y * width + x];
+                if palette[index] != c {
+                    index = palette.iter().position(|p| *p == c).unwrap();
+                }
+                let bit = x * bits;
+                raw[y * stride + bit / 8] |= (index as u8) << (8 - bits - (bit & 7));
+            }
+        }
+        ((bits << 8) as u16 | 3, stride, 1, raw)
+    }
+    else if has_alpha {
+        let mut raw: Vec<u8> = Vec::with_capacity(width * height * 4);
+        for c in &image.data {
+            raw.extend_from_slice(&[(c >> 16) as u8, (c >> 8) as u8, *c as u8, (c >> 24) as u8]);
+        }
+        (TYPE_RGBA8, width * 4, 4, raw)
+    }
+    else {
+        let mut raw: Vec<u8> = Vec::with_capacity(width * height * 3);
+        for c in &image.data {
+            raw.extend_from_slice(&[(c >> 16) as u8, (c >> 8) as u8, *c as u8]);
+        }
+        (TYPE_RGB8, width * 3, 3, raw)
+    };
+    let mut ihdr: Vec<u8> = Vec::new();
+    push_be32(&mut ihdr, width as u32);
+    push_be32(&mut ihdr, height as u32);
+    ihdr.extend_from_slice(&itype.to_be_bytes());
+    ihdr.extend_from_slice(&[0, 0, 0]); // compression, filter, no interlace
+    push_chunk(&mut dst, 0x49484452, &ihdr); // IHDR
+    if let Some(palette) = &palette {
+        let plte: Vec<u8> = palette.iter().flat_map(|c| [(c >> 16) as u8, (c >> 8) as u8, *c as u8]).collect();
+        push_chunk(&mut dst, 0x504C5445, &plte); // PLTE
+        if has_alpha {
+            // the alphas of the palette entries, the ones left out are opaque
+            let count = palette.iter().rposition(|c| (c >> 24) != 255).unwrap() + 1;
+            let trns: Vec<u8> = palette[..count].iter().map(|c| (c >> 24) as u8).collect();
+            push_chunk(&mut dst, 0x74524E53, &trns); // tRNS
+        }
+    }
+    let filtered = filter(&raw, height, stride, bpp, palette.is_none());
+    let zipped = makepad_miniz::compress_to_vec_zlib(&filtered, options.level);
+    push_chunk(&mut dst, 0x49444154, &zipped); // IDAT
+    push_chunk(&mut dst, 0x49454E44, &[]); // IEND
+    Ok(dst)
 }
\ No newline at end of file
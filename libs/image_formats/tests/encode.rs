@@ -0,0 +1,111 @@
+use makepad_image_formats::{jpeg, png, ImageBuffer};
+
+// a smooth gradient with a few hard edges, opaque unless `alpha` is set
+fn test_image(width: usize, height: usize, alpha: bool) -> ImageBuffer {
+    let mut image = ImageBuffer::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let r = (x * 255 / width) as u32;
+            let g = (y * 255 / height) as u32;
+            let b = if (x / 16 + y / 16) % 2 == 0 { 200 } else { 40 };
+            let a = if alpha { ((x + y) * 255 / (width + height)) as u32 } else { 255 };
+            image.data[y * width + x] = (a << 24) | (r << 16) | (g << 8) | b;
+        }
+    }
+    image
+}
+
+fn max_channel_error(a: &ImageBuffer, b: &ImageBuffer) -> u32 {
+    a.data.iter().zip(&b.data).map(|(a, b)| {
+        (0..4).map(|i| ((a >> (i * 8)) & 255).abs_diff((b >> (i * 8)) & 255)).max().unwrap()
+    }).max().unwrap()
+}
+
+fn mean_color_error(a: &ImageBuffer, b: &ImageBuffer) -> f64 {
+    let sum: u32 = a.data.iter().zip(&b.data).map(|(a, b)| {
+        (0..3).map(|i| ((a >> (i * 8)) & 255).abs_diff((b >> (i * 8)) & 255)).sum::<u32>()
+    }).sum();
+    sum as f64 / (a.data.len() * 3) as f64
+}
+
+#[test]
+fn png_rgb() {
+    let image = test_image(67, 45, false);
+    let decoded = png::decode(&png::encode(&image).unwrap()).unwrap();
+    assert_eq!((decoded.width, decoded.height), (67, 45));
+    assert_eq!(decoded.data, image.data);
+}
+
+#[test]
+fn png_rgba() {
+    let image = test_image(33, 70, true);
+    let data = png::encode(&image).unwrap();
+    let decoded = png::decode(&data).unwrap();
+    assert_eq!(decoded.data, image.data);
+}
+
+#[test]
+fn png_palette() {
+    for colors in [2u32, 3, 7, 16, 100, 256] {
+        let mut image = ImageBuffer::new(129, 93);
+        let mut seed = 1u32;
+        for c in &mut image.data {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let index = (seed >> 16) % colors;
+            // the first color is transparent
+            let a = if index == 0 { 0 } else { 255 };
+            *c = (a << 24) | (index << 16) | ((255 - index) << 8) | (index / 2);
+        }
+        let data = png::encode(&image).unwrap();
+        let decoded = png::decode(&data).unwrap();
+        assert_eq!(decoded.data, image.data, "{} colors", colors);
+        let full = png::encode_with_options(&image, &png::PngOptions {palette: false, level: 6}).unwrap();
+        assert!(data.len() < full.len(), "{} colors", colors);
+    }
+}
+
+#[test]
+fn png_levels() {
+    let image = test_image(64, 64, false);
+    let stored = png::encode_with_options(&image, &png::PngOptions {palette: false, level: 0}).unwrap();
+    let compressed = png::encode_with_options(&image, &png::PngOptions {palette: false, level: 10}).unwrap();
+    assert!(compressed.len() < stored.len());
+    assert_eq!(png::decode(&stored).unwrap().data, image.data);
+    assert_eq!(png::decode(&compressed).unwrap().data, image.data);
+}
+
+#[test]
+fn jpeg_subsampling() {
+    let image = test_image(83, 61, false);
+    for subsampling in [jpeg::Subsampling::YUV444, jpeg::Subsampling::YUV422, jpeg::Subsampling::YUV420] {
+        let data = jpeg::encode_with_options(&image, &jpeg::JpegOptions {quality: 95, subsampling}).unwrap();
+        assert_eq!(jpeg::test(&data), Some((83, 61)));
+        let decoded = jpeg::decode(&data).unwrap();
+        assert_eq!((decoded.width, decoded.height), (83, 61));
+        let error = mean_color_error(&image, &decoded);
+        assert!(error < 4.0, "{:?} mean error {}", subsampling, error);
+    }
+}
+
+#[test]
+fn jpeg_quality() {
+    let image = test_image(64, 64, false);
+    let mut last_len = 0;
+    let mut last_error = f64::MAX;
+    for quality in [10, 50, 90, 100] {
+        let data = jpeg::encode_with_options(&image, &jpeg::JpegOptions {quality, subsampling: jpeg::Subsampling::YUV444}).unwrap();
+        let error = mean_color_error(&image, &jpeg::decode(&data).unwrap());
+        assert!(data.len() > last_len);
+        assert!(error <= last_error);
+        last_len = data.len();
+        last_error = error;
+    }
+    assert!(max_channel_error(&image, &jpeg::decode(&jpeg::encode(&image).unwrap()).unwrap()) < 64);
+}
+
+#[test]
+fn invalid_size() {
+    let image = ImageBuffer::new(0, 10);
+    assert!(png::encode(&image).is_err());
+    assert!(jpeg::encode(&image).is_err());
+}
@@ -0,0 +1,116 @@
+use makepad_image_formats::gif;
+
+// packs the LZW codes, each with the size in bits it is read with, least significant bit first
+fn pack(codes: &[(u32, u32)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut bits = 0u32;
+    let mut nbits = 0;
+    for &(code, size) in codes {
+        bits |= code << nbits;
+        nbits += size;
+        while nbits >= 8 {
+            data.push(bits as u8);
+            bits >>= 8;
+            nbits -= 8;
+        }
+    }
+    if nbits > 0 {
+        data.push(bits as u8);
+    }
+    data
+}
+
+// a GIF with one image of `width` x `height` and 8 colors, color `i` is red `i`
+fn gif(width: u16, height: u16, frame: (u16, u16), interlaced: bool, data: &[u8]) -> Vec<u8> {
+    let mut src = b"GIF89a".to_vec();
+    src.extend_from_slice(&width.to_le_bytes());
+    src.extend_from_slice(&height.to_le_bytes());
+    src.extend_from_slice(&[0x82, 0, 0]);
+    for i in 0..8 {
+        src.extend_from_slice(&[i, 0, 0]);
+    }
+    src.push(0x2C);
+    src.extend_from_slice(&[0, 0, 0, 0]);
+    src.extend_from_slice(&frame.0.to_le_bytes());
+    src.extend_from_slice(&frame.1.to_le_bytes());
+    src.push(if interlaced { 0x40 } else { 0 });
+    src.push(3);
+    for block in data.chunks(255) {
+        src.push(block.len() as u8);
+        src.extend_from_slice(block);
+    }
+    src.push(0);
+    src.push(0x3B);
+    src
+}
+
+// the palette index of every pixel, None where it is transparent
+fn indices(image: &makepad_image_formats::ImageBuffer) -> Vec<Option<u32>> {
+    image.data.iter().map(|c| if (c >> 24) == 0 { None } else { Some((c >> 16) & 255) }).collect()
+}
+
+// with a minimum code size of 3 the clear code is 8 and the end code 9, codes start out 4 bits
+const CLEAR: u32 = 8;
+const END: u32 = 9;
+
+#[test]
+fn lzw_codes_grow() {
+    // every code after the first adds an entry, after the one for code 15 codes are 5 bits
+    let mut codes = vec![(CLEAR, 4)];
+    codes.extend((0..7).map(|i| (i, 4)));
+    codes.push((7, 5));
+    // 10 is the entry for 0 1
+    codes.push((10, 5));
+    codes.push((END, 5));
+    let image = gif::decode(&gif(10, 1, (10, 1), false, &pack(&codes))).unwrap();
+    assert_eq!(indices(&image), [0, 1, 2, 3, 4, 5, 6, 7, 0, 1].map(Some));
+}
+
+#[test]
+fn lzw_kwkwk() {
+    // 10 isn't in the table yet when it is read, it is the previous string followed by its first byte
+    let codes = [(CLEAR, 4), (5, 4), (10, 4), (11, 4), (END, 4)];
+    let image = gif::decode(&gif(6, 1, (6, 1), false, &pack(&codes))).unwrap();
+    assert_eq!(indices(&image), [Some(5); 6]);
+}
+
+#[test]
+fn lzw_clear_code() {
+    // the clear code empties the table and starts over with 4 bit codes
+    let codes = [(CLEAR, 4), (1, 4), (2, 4), (CLEAR, 4), (3, 4), (4, 4), (10, 4), (END, 4)];
+    let image = gif::decode(&gif(6, 1, (6, 1), false, &pack(&codes))).unwrap();
+    assert_eq!(indices(&image), [1, 2, 3, 4, 3, 4].map(Some));
+
+    // so the entries from before it are gone
+    let codes = [(CLEAR, 4), (1, 4), (2, 4), (CLEAR, 4), (10, 4), (END, 4)];
+    assert!(gif::decode(&gif(4, 1, (4, 1), false, &pack(&codes))).is_err());
+}
+
+#[test]
+fn lzw_truncated() {
+    // data that ends early leaves the rest of the frame transparent
+    let codes = [(CLEAR, 4), (1, 4), (2, 4), (3, 4)];
+    let image = gif::decode(&gif(5, 1, (5, 1), false, &pack(&codes))).unwrap();
+    assert_eq!(indices(&image), [Some(1), Some(2), Some(3), None, None]);
+
+    // a file that ends in the middle of the first image is an error
+    let src = gif(5, 1, (5, 1), false, &pack(&codes));
+    assert!(gif::decode(&src[..src.len() - 4]).is_err());
+}
+
+#[test]
+fn interlaced() {
+    // the rows come in the order 0, 4, 2, 6, 1, 3, 5, 7
+    let mut codes = vec![(CLEAR, 4)];
+    codes.extend((0..8).map(|i| (i, if i < 7 { 4 } else { 5 })));
+    codes.push((END, 5));
+    let image = gif::decode(&gif(1, 8, (1, 8), true, &pack(&codes))).unwrap();
+    assert_eq!(indices(&image), [0, 4, 2, 5, 1, 6, 3, 7].map(Some));
+}
+
+#[test]
+fn sizes_are_capped() {
+    let codes = pack(&[(CLEAR, 4), (1, 4), (END, 4)]);
+    assert!(gif::decode(&gif(65535, 65535, (1, 1), false, &codes)).is_err());
+    assert!(gif::decode(&gif(4, 4, (65535, 65535), false, &codes)).is_err());
+}
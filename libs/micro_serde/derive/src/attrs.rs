@@ -0,0 +1,182 @@
+use proc_macro::{TokenStream, TokenTree};
+use makepad_micro_proc_macro::*;
+
+// the helper attributes the derives understand. json and ron use all of them,
+// bin is positional unless it has a bin_version, so it only looks at skip and default:
+// #[rename = "name"] the key of a field or the name of a variant
+// #[skip] the field is never written, reading it gives its default
+// #[default] / #[default(expr)] used when the key is missing
+// #[flatten] the fields of a struct (or a map in json) are written inline
+// #[tag = "type"] on an enum writes {"type":"Variant", ...fields}
+// #[tag = "t"] #[content = "c"] on an enum writes {"t":"Variant","c":...}
+// #[bin_version = 1] on a struct or enum makes bin self describing, with field ids and lengths
+// #[bin_id = 7] the id of a field or variant in that mode, a hash of the name otherwise
+
+pub struct FieldAttrs {
+    pub rename: Option<String>,
+    pub skip: bool,
+    pub default: bool,
+    pub default_expr: Option<TokenStream>,
+    pub flatten: bool,
+    pub bin_id: Option<u32>,
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self, TokenStream> {
+        let mut ret = Self {
+            rename: None,
+            skip: false,
+            default: false,
+            default_expr: None,
+            flatten: false,
+            bin_id: None,
+        };
+        for attr in attrs {
+            match attr.name.as_ref() {
+                "rename" => ret.rename = Some(string_arg(attr) ?),
+                "skip" => ret.skip = true,
+                "default" => {
+                    ret.default = true;
+                    ret.default_expr = attr.args.clone().filter( | args | !args.is_empty());
+                }
+                "flatten" => ret.flatten = true,
+                "bin_id" => ret.bin_id = Some(u32_arg(attr) ?),
+                _ => ()
+            }
+        }
+        if ret.skip && ret.flatten {
+            return Err(error("a field can't be both #[skip] and #[flatten]"))
+        }
+        Ok(ret)
+    }
+}
+
+pub struct ContainerAttrs {
+    pub tag: Option<String>,
+    pub content: Option<String>,
+    pub bin_version: Option<u32>,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self, TokenStream> {
+        let mut ret = Self {tag: None, content: None, bin_version: None};
+        for attr in attrs {
+            match attr.name.as_ref() {
+                "tag" => ret.tag = Some(string_arg(attr) ?),
+                "content" => ret.content = Some(string_arg(attr) ?),
+                "bin_version" => ret.bin_version = Some(u32_arg(attr) ?),
+                _ => ()
+            }
+        }
+        if ret.content.is_some() && ret.tag.is_none() {
+            return Err(error("#[content] needs a #[tag] as well"))
+        }
+        Ok(ret)
+    }
+}
+
+pub struct Field {
+    pub name: String,
+    pub ty: TokenStream,
+    pub attrs: FieldAttrs,
+}
+
+impl Field {
+    pub fn parse_all(fields: Vec<StructField>) -> Result<Vec<Field>, TokenStream> {
+        let mut ret = Vec::new();
+        for field in fields {
+            ret.push(Field {
+                attrs: FieldAttrs::parse(&field.attrs) ?,
+                name: field.name,
+                ty: field.ty,
+            });
+        }
+        if ret.iter().filter( | field | field.attrs.flatten).count() > 1 {
+            return Err(error("only one #[flatten] field is supported"))
+        }
+        Ok(ret)
+    }
+
+    /// The fields of a tuple struct or variant, named by their index
+    pub fn tuple(types: Vec<TokenStream>) -> Vec<Field> {
+        types.into_iter().enumerate().map( | (index, ty) | Field {
+            name: index.to_string(),
+            ty,
+            attrs: FieldAttrs {
+                rename: None,
+                skip: false,
+                default: false,
+                default_expr: None,
+                flatten: false,
+                bin_id: Some(index as u32),
+            }
+        }).collect()
+    }
+
+    pub fn is_option(&self) -> bool {
+        self.ty.clone().into_iter().next().unwrap().to_string() == "Option"
+    }
+
+    /// The key in ron, json also strips a leading _
+    pub fn key(&self) -> &str {
+        self.attrs.rename.as_ref().unwrap_or(&self.name)
+    }
+
+    /// The id of the field in versioned bin, fields without a #[bin_id] use a hash of their name
+    /// so that reordering fields keeps old data readable
+    pub fn bin_id(&self) -> u32 {
+        self.attrs.bin_id.unwrap_or_else( || bin_name_hash(&self.name))
+    }
+
+    pub fn default_value(&self, tb: &mut TokenBuilder) {
+        if let Some(expr) = &self.attrs.default_expr {
+            tb.add("(").stream(Some(expr.clone())).add(")");
+        }
+        else {
+            tb.add("std :: default :: Default :: default ( )");
+        }
+    }
+}
+
+pub fn has_written_fields(fields: &[Field]) -> bool {
+    fields.iter().any( | field | !field.attrs.skip)
+}
+
+pub fn has_flatten(fields: &[Field]) -> bool {
+    fields.iter().any( | field | field.attrs.flatten)
+}
+
+/// 32 bit fnv-1a
+pub fn bin_name_hash(name: &str) -> u32 {
+    let mut hash = 0x811c9dc5u32;
+    for b in name.bytes() {
+        hash ^= b as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+fn u32_arg(attr: &Attribute) -> Result<u32, TokenStream> {
+    if let Some(args) = &attr.args {
+        if let Some(TokenTree::Literal(lit)) = args.clone().into_iter().next() {
+            let lit = lit.to_string().replace('_', "");
+            let lit = lit.strip_suffix("u32").unwrap_or(&lit);
+            if let Ok(value) = lit.parse() {
+                return Ok(value)
+            }
+        }
+    }
+    Err(error(&format!("expected #[{} = <u32>]", attr.name)))
+}
+
+fn string_arg(attr: &Attribute) -> Result<String, TokenStream> {
+    if let Some(args) = &attr.args {
+        if let Some(TokenTree::Literal(lit)) = args.clone().into_iter().next() {
+            let lit = lit.to_string();
+            if let Some(value) = lit.strip_prefix('"').and_then( | v | v.strip_suffix('"')) {
+                return Ok(value.to_string())
+            }
+        }
+    }
+    Err(error(&format!("expected #[{} = \"...\"]", attr.name)))
+}
@@ -1,11 +1,147 @@
 use proc_macro::{TokenStream};
 use makepad_micro_proc_macro::*;
+use crate::attrs::*;
+
+// bin is positional, a skipped field isn't in the data and a flattened one is just nested
+fn de_bin_field(tb: &mut TokenBuilder, field: &Field) {
+    tb.ident(&field.name).add(":");
+    if field.attrs.skip {
+        field.default_value(tb);
+    }
+    else {
+        tb.add("DeBin :: de_bin ( o , d ) ?");
+    }
+    tb.add(",");
+}
+
+// versioned bin, see serde_bin.rs for the layout
+
+fn check_bin_ids(ids: impl Iterator<Item = u32>) -> Result<(), TokenStream> {
+    let mut seen = Vec::new();
+    for id in ids {
+        if seen.contains(&id) {
+            return Err(error(&format!("bin id {} is used twice, give one of them another #[bin_id]", id)))
+        }
+        seen.push(id);
+    }
+    Ok(())
+}
+
+#[derive(PartialEq)]
+enum VariantShape {
+    Tuple,
+    Named,
+    Bare,
+}
+
+fn bin_variant_fields(parser: &mut TokenParser) -> Result<(Vec<Field>, VariantShape), TokenStream> {
+    if let Some(types) = parser.eat_all_types() {
+        Ok((Field::tuple(types), VariantShape::Tuple))
+    }
+    else if let Some(fields) = parser.eat_all_struct_fields() {
+        Ok((Field::parse_all(fields) ?, VariantShape::Named))
+    }
+    else if parser.is_punct_alone(',') || parser.is_eot() {
+        Ok((Vec::new(), VariantShape::Bare))
+    }
+    else {
+        Err(parser.unexpected())
+    }
+}
+
+// tuple fields are called by their index, `self . 0` on a struct and `n0` in a variant
+fn bin_member(tb: &mut TokenBuilder, field: &Field) {
+    match field.name.parse::<usize>() {
+        Ok(index) => tb.unsuf_usize(index),
+        Err(_) => tb.ident(&field.name)
+    };
+}
+
+fn bin_binding(field: &Field) -> String {
+    match field.name.parse::<usize>() {
+        Ok(index) => format!("n{}", index),
+        Err(_) => field.name.clone()
+    }
+}
+
+fn ser_bin_versioned(tb: &mut TokenBuilder, version: u32, fields: &[Field], on_self: bool) {
+    let written = fields.iter().filter( | field | !field.attrs.skip).count();
+    tb.add("ser_bin_header ( s ,").suf_u32(version).add(",").suf_u32(written as u32).add(") ;");
+    for field in fields.iter().filter( | field | !field.attrs.skip) {
+        tb.add("ser_bin_field ( s ,").suf_u32(field.bin_id()).add(",");
+        if on_self {
+            tb.add("& self .");
+            bin_member(tb, field);
+        }
+        else {
+            tb.ident(&bin_binding(field));
+        }
+        tb.add(") ;");
+    }
+}
+
+// reads the fields into locals, `path` names the field in errors
+fn de_bin_versioned(tb: &mut TokenBuilder, fields: &[Field], path: Option<&str>) {
+    tb.add("let ( _ , __fields ) = de_bin_header ( o , d ) ? ;");
+    if !has_written_fields(fields) {
+        tb.add("for _ in 0 .. __fields { let ( _ , __end ) = de_bin_field_header ( o , d ) ? ; * o = __end ; }");
+        return
+    }
+    for field in fields.iter().filter( | field | !field.attrs.skip) {
+        tb.add("let mut").ident(&format!("_{}", field.name)).add("= None ;");
+    }
+    tb.add("for _ in 0 .. __fields {");
+    tb.add("let ( __id , __end ) = de_bin_field_header ( o , d ) ? ;");
+    tb.add("match __id {");
+    for field in fields.iter().filter( | field | !field.attrs.skip) {
+        let label = match path {
+            Some(path) => format!("{}.{}", path, field.name),
+            None => field.name.clone()
+        };
+        tb.suf_u32(field.bin_id()).add("=>").ident(&format!("_{}", field.name));
+        tb.add("= Some ( de_bin_field ( o , d , __end ,").string(&label).add(") ? ) ,");
+    }
+    tb.add("_ => * o = __end } }");
+}
+
+fn de_bin_versioned_value(tb: &mut TokenBuilder, field: &Field) {
+    if field.attrs.skip {
+        field.default_value(tb);
+    }
+    else {
+        tb.add("if let Some ( t ) =").ident(&format!("_{}", field.name)).add("{ t } else {");
+        field.default_value(tb);
+        tb.add("}");
+    }
+}
+
+// the constructor after de_bin_versioned, without the type or variant name
+fn de_bin_versioned_fields(tb: &mut TokenBuilder, fields: &[Field], tuple: bool) {
+    if tuple {
+        tb.add("(");
+        for field in fields {
+            de_bin_versioned_value(tb, field);
+            tb.add(",");
+        }
+        tb.add(")");
+    }
+    else {
+        tb.add("{");
+        for field in fields {
+            tb.ident(&field.name).add(":");
+            de_bin_versioned_value(tb, field);
+            tb.add(",");
+        }
+        tb.add("}");
+    }
+}
 
 pub fn derive_ser_bin_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
     
-    parser.eat_attributes();
+    let attrs = parser.eat_attributes();
+    let container_attrs = match ContainerAttrs::parse(&attrs) {Ok(attrs) => attrs, Err(err) => return err};
     parser.eat_ident("pub");
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
@@ -18,14 +154,32 @@ pub fn derive_ser_bin_impl(input: TokenStream) -> TokenStream {
             tb.add("SerBin for").ident(&name).stream(generic).stream(where_clause);
             tb.add("{ fn ser_bin ( & self , s : & mut Vec < u8 > ) {");
 
-            if let Some(types) = types{
+            if let Some(version) = container_attrs.bin_version {
+                let fields = if let Some(types) = types {
+                    Field::tuple(types)
+                }
+                else if let Some(fields) = parser.eat_all_struct_fields() {
+                    match Field::parse_all(fields) {Ok(fields) => fields, Err(err) => return err}
+                }
+                else {
+                    return parser.unexpected()
+                };
+                if let Err(err) = check_bin_ids(fields.iter().map( | field | field.bin_id())) {
+                    return err
+                }
+                ser_bin_versioned(&mut tb, version, &fields, true);
+            }
+            else if let Some(types) = types{
                 for i in 0..types.len(){
                      tb.add("self .").unsuf_usize(i).add(". ser_bin ( s ) ;");
                 }
             }
             else if let Some(fields) = parser.eat_all_struct_fields(){ 
+                let fields = match Field::parse_all(fields) {Ok(fields) => fields, Err(err) => return err};
                 for field in fields{
-                    tb.add("self .").ident(&field.name).add(". ser_bin ( s ) ;");
+                    if !field.attrs.skip {
+                        tb.add("self .").ident(&field.name).add(". ser_bin ( s ) ;");
+                    }
                 }
             }
             else{
@@ -49,11 +203,38 @@ pub fn derive_ser_bin_impl(input: TokenStream) -> TokenStream {
                 return parser.unexpected()
             }
             let mut index = 0;
+            let mut variant_ids = Vec::new();
             while !parser.eat_eot(){
-                parser.eat_attributes();
+                let variant_attrs = match FieldAttrs::parse(&parser.eat_attributes()) {Ok(attrs) => attrs, Err(err) => return err};
                 // parse ident
                 if let Some(variant) = parser.eat_any_ident(){
-                    if let Some(types) = parser.eat_all_types(){
+                    if let Some(version) = container_attrs.bin_version {
+                        let id = variant_attrs.bin_id.unwrap_or_else( || bin_name_hash(&variant));
+                        variant_ids.push(id);
+                        let (fields, shape) = match bin_variant_fields(&mut parser) {Ok(fields) => fields, Err(err) => return err};
+                        if let Err(err) = check_bin_ids(fields.iter().map( | field | field.bin_id())) {
+                            return err
+                        }
+                        tb.add("Self ::").ident(&variant);
+                        if shape == VariantShape::Tuple {
+                            tb.add("(");
+                            for field in fields.iter() {
+                                tb.ident(&bin_binding(field)).add(",");
+                            }
+                            tb.add(")");
+                        }
+                        else if shape == VariantShape::Named {
+                            tb.add("{");
+                            for field in fields.iter().filter( | field | !field.attrs.skip) {
+                                tb.ident(&field.name).add(",");
+                            }
+                            tb.add(".. }");
+                        }
+                        tb.add("=> {").suf_u32(id).add(". ser_bin ( s ) ;");
+                        ser_bin_versioned(&mut tb, version, &fields, false);
+                        tb.add("}");
+                    }
+                    else if let Some(types) = parser.eat_all_types(){
                         tb.add("Self ::").ident(&variant).add("(");
                         for i in 0..types.len(){
                             tb.ident(&format!("n{}", i)).add(",");
@@ -65,13 +246,18 @@ pub fn derive_ser_bin_impl(input: TokenStream) -> TokenStream {
                         tb.add("}");
                     }
                     else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
+                        let fields = match Field::parse_all(fields) {Ok(fields) => fields, Err(err) => return err};
                         tb.add("Self ::").ident(&variant).add("{");
                         for field in fields.iter(){
-                            tb.ident(&field.name).add(",");
+                            if !field.attrs.skip {
+                                tb.ident(&field.name).add(",");
+                            }
                         }
-                        tb.add("} => {").suf_u16(index).add(". ser_bin ( s ) ;");
+                        tb.add(".. } => {").suf_u16(index).add(". ser_bin ( s ) ;");
                         for field in fields{
-                            tb.ident(&field.name).add(". ser_bin ( s ) ;");
+                            if !field.attrs.skip {
+                                tb.ident(&field.name).add(". ser_bin ( s ) ;");
+                            }
                         }
                         tb.add("}");
                     }
@@ -89,6 +275,9 @@ pub fn derive_ser_bin_impl(input: TokenStream) -> TokenStream {
                     return parser.unexpected()
                 }
             }
+            if let Err(err) = check_bin_ids(variant_ids.into_iter()) {
+                return err
+            }
             tb.add("} } } ;");
             return tb.end();
         }
@@ -100,7 +289,8 @@ pub fn derive_de_bin_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
     
-    parser.eat_attributes();
+    let attrs = parser.eat_attributes();
+    let container_attrs = match ContainerAttrs::parse(&attrs) {Ok(attrs) => attrs, Err(err) => return err};
     parser.eat_ident("pub");
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
@@ -112,8 +302,26 @@ pub fn derive_de_bin_impl(input: TokenStream) -> TokenStream {
             tb.add("DeBin for").ident(&name).stream(generic).stream(where_clause);
             tb.add("{ fn de_bin ( o : & mut usize , d : & [ u8 ] )");
             tb.add("-> std :: result :: Result < Self , DeBinErr > { ");
-            tb.add("std :: result :: Result :: Ok ( Self");
 
+            if container_attrs.bin_version.is_some() {
+                let tuple = types.is_some();
+                let fields = if let Some(types) = types {
+                    Field::tuple(types)
+                }
+                else if let Some(fields) = parser.eat_all_struct_fields() {
+                    match Field::parse_all(fields) {Ok(fields) => fields, Err(err) => return err}
+                }
+                else {
+                    return parser.unexpected()
+                };
+                de_bin_versioned(&mut tb, &fields, None);
+                tb.add("std :: result :: Result :: Ok ( Self");
+                de_bin_versioned_fields(&mut tb, &fields, tuple);
+                tb.add(") } } ;");
+                return tb.end();
+            }
+
+            tb.add("std :: result :: Result :: Ok ( Self");
             if let Some(types) = types{
                 tb.add("(");
                 for _ in 0..types.len(){
@@ -122,9 +330,10 @@ pub fn derive_de_bin_impl(input: TokenStream) -> TokenStream {
                 tb.add(")");
             }
             else if let Some(fields) = parser.eat_all_struct_fields(){ 
+                let fields = match Field::parse_all(fields) {Ok(fields) => fields, Err(err) => return err};
                 tb.add("{");
                 for field in fields{
-                    tb.ident(&field.name).add(": DeBin :: de_bin ( o , d ) ? ,");
+                    de_bin_field(&mut tb, &field);
                 }
                 tb.add("}");
             }
@@ -144,7 +353,12 @@ pub fn derive_de_bin_impl(input: TokenStream) -> TokenStream {
             tb.add("DeBin for").ident(&name).stream(generic).stream(where_clause);
             tb.add("{ fn de_bin ( o : & mut usize , d : & [ u8 ] )");
             tb.add("-> std :: result :: Result < Self , DeBinErr > {");
-            tb.add("let id : u16 = DeBin :: de_bin ( o , d ) ? ;");
+            if container_attrs.bin_version.is_some() {
+                tb.add("let id : u32 = DeBin :: de_bin ( o , d ) ? ;");
+            }
+            else {
+                tb.add("let id : u16 = DeBin :: de_bin ( o , d ) ? ;");
+            }
             tb.add("match id {");
             
             if !parser.open_brace(){
@@ -153,8 +367,20 @@ pub fn derive_de_bin_impl(input: TokenStream) -> TokenStream {
             let mut index = 0;
             while !parser.eat_eot(){
                 // parse ident
-                parser.eat_attributes();
+                let variant_attrs = match FieldAttrs::parse(&parser.eat_attributes()) {Ok(attrs) => attrs, Err(err) => return err};
                 if let Some(variant) = parser.eat_any_ident(){
+                    if container_attrs.bin_version.is_some() {
+                        let (fields, shape) = match bin_variant_fields(&mut parser) {Ok(fields) => fields, Err(err) => return err};
+                        tb.suf_u32(variant_attrs.bin_id.unwrap_or_else( || bin_name_hash(&variant))).add("=> {");
+                        de_bin_versioned(&mut tb, &fields, Some(&variant));
+                        tb.add("std :: result :: Result :: Ok ( Self ::").ident(&variant);
+                        if shape != VariantShape::Bare {
+                            de_bin_versioned_fields(&mut tb, &fields, shape == VariantShape::Tuple);
+                        }
+                        tb.add(") }");
+                        parser.eat_punct_alone(',');
+                        continue;
+                    }
                     tb.suf_u16(index as u16).add("=> {");
                     tb.add("std :: result :: Result :: Ok ( Self ::");
                     if let Some(types) = parser.eat_all_types(){
@@ -165,9 +391,10 @@ pub fn derive_de_bin_impl(input: TokenStream) -> TokenStream {
                         tb.add(")");
                     }
                     else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
+                        let fields = match Field::parse_all(fields) {Ok(fields) => fields, Err(err) => return err};
                         tb.ident(&variant).add("{");
                         for field in fields.iter(){
-                            tb.ident(&field.name).add(": DeBin :: de_bin ( o , d ) ? ,");
+                            de_bin_field(&mut tb, field);
                         }
                         tb.add("}");
                     }
@@ -186,7 +413,7 @@ pub fn derive_de_bin_impl(input: TokenStream) -> TokenStream {
                     return parser.unexpected()
                 }
             } 
-            tb.add("_ => std :: result :: Result :: Err ( DeBinErr { o : * o , l :");
+            tb.add("_ => std :: result :: Result :: Err ( DeBinErr { field : None , o : * o , l :");
             tb.unsuf_usize(1).add(", s : d . len ( ) , msg : ").string(&name).add(". to_string ( ) } )");
             tb.add("} } } ;");
             return tb.end();
@@ -1,16 +1,159 @@
 use proc_macro::TokenStream;
 use makepad_micro_proc_macro::*;
+use crate::attrs::*;
+
+// a leading _ is stripped from the key so fields like _type can be read. this only happens
+// where it always did, reading structs and writing enum variants, to keep the wire format
+fn json_key(field: &Field, strip: bool) -> String {
+    if let Some(rename) = &field.attrs.rename {
+        return rename.clone()
+    }
+    if strip {
+        field.name.strip_prefix('_').unwrap_or(&field.name).to_string()
+    }
+    else {
+        field.name.clone()
+    }
+}
+
+// writes named fields into an object that is already open, __first tracks the commas.
+// on a struct the fields are read through self, on a variant they are bound by reference
+fn ser_json_fields(tb: &mut TokenBuilder, fields: &[Field], on_self: bool) {
+    let value = | tb: &mut TokenBuilder, field: &Field, by_ref: bool | {
+        if on_self {
+            if by_ref {
+                tb.add("&");
+            }
+            tb.add("self .");
+        }
+        tb.ident(&field.name);
+    };
+    for field in fields {
+        if field.attrs.skip {
+            continue;
+        }
+        if field.attrs.flatten {
+            tb.add("s . flatten ( d ,");
+            value(tb, field, true);
+            tb.add(", & mut __first ) ;");
+        }
+        else if field.is_option() {
+            tb.add("if let Some ( t ) =");
+            value(tb, field, true);
+            tb.add("{ if ! __first { s . conl ( ) ; } __first = false ;");
+            tb.add("s . field ( d + 1 ,").string(&json_key(field, !on_self)).add(") ;");
+            tb.add("t . ser_json ( d + 1 , s ) ; }");
+        }
+        else {
+            tb.add("if ! __first { s . conl ( ) ; } __first = false ;");
+            tb.add("s . field ( d + 1 ,").string(&json_key(field, !on_self)).add(") ;");
+            value(tb, field, false);
+            tb.add(". ser_json ( d + 1 , s ) ;");
+        }
+    }
+}
+
+fn ser_json_tuple_body(tb: &mut TokenBuilder, len: usize) {
+    tb.add("s . out . push (").chr('[').add(") ;");
+    for i in 0..len {
+        tb.ident(&format!("n{}", i)).add(". ser_json ( d , s ) ;");
+        if i != len - 1 {
+            tb.add("s . out . push (").chr(',').add(") ;");
+        }
+    }
+    tb.add("s . out . push (").chr(']').add(") ;");
+}
+
+fn ser_json_struct_body(tb: &mut TokenBuilder, fields: &[Field]) {
+    tb.add("s . st_pre ( ) ;");
+    if has_written_fields(fields) {
+        tb.add("let mut __first = true ;");
+    }
+    ser_json_fields(tb, fields, false);
+    tb.add("s . st_post ( d ) ;");
+}
+
+// reads an object into named fields and ends with the Self { .. } or Self :: Variant { .. } expression
+fn de_json_fields(tb: &mut TokenBuilder, fields: &[Field], variant: Option<&str>) {
+    let strip = variant.is_none();
+    tb.add("s . curly_open ( i ) ? ;");
+    for field in fields {
+        if !field.attrs.skip && !field.attrs.flatten {
+            tb.add("let mut").ident(&format!("_{}", field.name)).add("= None ;");
+        }
+    }
+    let flatten = has_flatten(fields);
+    if flatten {
+        tb.add("let mut __flatten = String :: new ( ) ;");
+    }
+    tb.add("while let Some ( _ ) = s . next_str ( ) {");
+    tb.add("match s . strbuf . as_ref ( ) {");
+    for field in fields {
+        if !field.attrs.skip && !field.attrs.flatten {
+            tb.string(&json_key(field, strip)).add("=> { s . next_colon ( i ) ? ;");
+            tb.ident(&format!("_{}", field.name)).add("= Some ( DeJson :: de_json ( s , i ) ? ) ; } ,");
+        }
+    }
+    if flatten {
+        tb.add("_ => s . capture_field ( i , & mut __flatten ) ?");
+    }
+    else {
+        tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
+    }
+    tb.add("} ; s . eat_comma_curly ( i ) ? ;");
+    tb.add("} ; s . curly_close ( i ) ? ;");
+
+    tb.add("Self");
+    if let Some(variant) = variant {
+        tb.add("::").ident(variant);
+    }
+    tb.add("{");
+    for field in fields {
+        tb.ident(&field.name).add(":");
+        if field.attrs.skip {
+            field.default_value(tb);
+        }
+        else if field.attrs.flatten {
+            tb.add("DeJsonState :: de_captured_fields ( & __flatten ) ?");
+        }
+        else {
+            tb.add("if let Some ( t ) =").ident(&format!("_{}", field.name)).add("{ t } else {");
+            if field.attrs.default {
+                field.default_value(tb);
+            }
+            else if field.is_option() {
+                tb.add("None");
+            }
+            else {
+                tb.add("return std :: result :: Result :: Err ( s . err_nf (").string(&json_key(field, strip)).add(") )");
+            }
+            tb.add("}");
+        }
+        tb.add(",");
+    }
+    tb.add("}");
+}
+
+fn de_json_tuple_body(tb: &mut TokenBuilder, variant: &str, len: usize) {
+    tb.add("s . block_open ( i ) ? ;");
+    tb.add("let r = Self ::").ident(variant).add("(");
+    for _ in 0..len {
+        tb.add("{ let r = DeJson :: de_json ( s , i ) ? ; s . eat_comma_block ( i ) ? ; r } ,");
+    }
+    tb.add(") ;");
+    tb.add("s . block_close ( i ) ? ; r");
+}
 
 pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
 
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    
-    parser.eat_attributes();
+
+    let attrs = parser.eat_attributes();
     parser.eat_ident("pub");
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
-            
+
             let generic = parser.eat_generic();
             let types = parser.eat_all_types();
             let where_clause = parser.eat_where_clause(Some("SerJson"));
@@ -18,7 +161,7 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
             tb.add("impl").stream(generic.clone());
             tb.add("SerJson for").ident(&name).stream(generic).stream(where_clause);
             tb.add("{ fn ser_json ( & self , d : usize , s : & mut SerJsonState ) {");
-            
+
             if let Some(types) = types{
                 tb.add("s . out . push (").chr('[').add(") ;");
                 for i in 0..types.len(){
@@ -30,27 +173,12 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
                 tb.add("s . out . push (").chr(']').add(") ;");
             }
             else if let Some(fields) = parser.eat_all_struct_fields(){
-                let fields_len = fields.len();
+                let fields = match Field::parse_all(fields) {Ok(fields) => fields, Err(err) => return err};
                 tb.add("s . st_pre ( ) ;");
-                // named struct
-                for (i, field) in fields.into_iter().enumerate() {
-                    if field.ty.into_iter().next().unwrap().to_string() == "Option"{
-                        tb.add("if let Some ( t ) = ").add("& self .").ident(&field.name).add("{");
-                        tb.add("s . field ( d + 1 ,").string(&field.name).add(") ;");
-                        tb.add("t . ser_json ( d + 1 , s ) ;");
-                        if i != fields_len - 1 {
-                            tb.add("s . conl ( ) ;");
-                        }
-                        tb.add("} ;");
-                    }
-                    else{
-                        tb.add("s . field ( d + 1 ,").string(&field.name).add(" ) ;");
-                        tb.add("self .").ident(&field.name).add(". ser_json ( d + 1 , s ) ;");
-                        if i != fields_len - 1 {
-                            tb.add("s . conl ( ) ;");
-                        }
-                    }
+                if has_written_fields(&fields) {
+                    tb.add("let mut __first = true ;");
                 }
+                ser_json_fields(&mut tb, &fields, true);
                 tb.add("s . st_post ( d ) ;");
             }
             else{
@@ -62,80 +190,103 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
     }
     else if parser.eat_ident("enum"){
         if let Some(name) = parser.eat_any_ident(){
+            let container_attrs = match ContainerAttrs::parse(&attrs) {Ok(attrs) => attrs, Err(err) => return err};
             let generic = parser.eat_generic();
             let where_clause = parser.eat_where_clause(Some("SerJson"));
 
             tb.add("impl").stream(generic.clone());
             tb.add("SerJson for").ident(&name).stream(generic).stream(where_clause);
             tb.add("{ fn ser_json ( & self , d : usize , s : & mut SerJsonState ) {");
-            tb.add("s . out . push (").chr('{').add(") ;");
+            if container_attrs.tag.is_none() {
+                tb.add("s . out . push (").chr('{').add(") ;");
+            }
             tb.add("match self {");
-            
+
             if !parser.open_brace(){
                 return parser.unexpected()
             }
 
             while !parser.eat_eot(){
                 // parse ident
-                parser.eat_attributes();
+                let variant_attrs = match FieldAttrs::parse(&parser.eat_attributes()) {Ok(attrs) => attrs, Err(err) => return err};
                 if let Some(variant) = parser.eat_any_ident(){
+                    let variant_name = variant_attrs.rename.clone().unwrap_or_else( || variant.clone());
                     if let Some(types) = parser.eat_all_types(){
-                        
                         tb.add("Self ::").ident(&variant).add("(");
                         for i in 0..types.len(){
                             tb.ident(&format!("n{}", i)).add(",");
                         }
                         tb.add(") => {");
-                        tb.add("s . label (").string(&variant).add(") ;");
-                        tb.add("s . out . push (").chr(':').add(") ;");
-                        tb.add("s . out . push (").chr('[').add(") ;");
-                        
-                        for i in 0..types.len(){
-                            tb.ident(&format!("n{}", i)).add(". ser_json ( d , s ) ;");
-                            if i != types.len() - 1{
-                                tb.add("s . out . push (").chr(',').add(") ;");
+                        match (&container_attrs.tag, &container_attrs.content) {
+                            (None, _) => {
+                                tb.add("s . label (").string(&variant_name).add(") ;");
+                                tb.add("s . out . push (").chr(':').add(") ;");
+                                ser_json_tuple_body(&mut tb, types.len());
+                            }
+                            (Some(tag), None) => {
+                                if types.len() != 1 {
+                                    return error("internally tagged enums only support struct, newtype and unit variants")
+                                }
+                                tb.add("s . st_pre ( ) ; s . field ( d + 1 ,").string(tag).add(") ;");
+                                tb.add("s . label (").string(&variant_name).add(") ;");
+                                tb.add("let mut __first = false ; s . flatten ( d , n0 , & mut __first ) ;");
+                                tb.add("s . st_post ( d ) ;");
+                            }
+                            (Some(tag), Some(content)) => {
+                                tb.add("s . st_pre ( ) ; s . field ( d + 1 ,").string(tag).add(") ;");
+                                tb.add("s . label (").string(&variant_name).add(") ;");
+                                tb.add("s . conl ( ) ; s . field ( d + 1 ,").string(content).add(") ;");
+                                ser_json_tuple_body(&mut tb, types.len());
+                                tb.add("s . st_post ( d ) ;");
                             }
                         }
-                        tb.add("s . out . push (").chr(']').add(") ;");
                         tb.add("}");
                     }
                     else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
+                        let fields = match Field::parse_all(fields) {Ok(fields) => fields, Err(err) => return err};
                         tb.add("Self ::").ident(&variant).add("{");
                         for field in fields.iter(){
-                            tb.ident(&field.name).add(",");
+                            if !field.attrs.skip {
+                                tb.ident(&field.name).add(",");
+                            }
                         }
-                        tb.add("} => {");
-                        
-                        tb.add("s . label (").string(&variant).add(") ;");
-                        tb.add("s . out . push (").chr(':').add(") ;");
-                        tb.add("s . st_pre ( ) ;");
-
-                        let fields_len = fields.len();
-                        for (i, field) in fields.into_iter().enumerate() {
-                            let field_strip = if let Some(v) = field.name.strip_prefix("_"){v}else{&field.name};
-                            if field.ty.into_iter().next().unwrap().to_string() == "Option"{
-                                tb.add("if let Some ( t ) = ").ident(&field.name).add("{");
-                                tb.add("s . field ( d + 1 ,").string(&field_strip).add(") ;");
-                                tb.add("t . ser_json ( d + 1 , s ) ;");
-                                if i != fields_len - 1 {
-                                    tb.add("s . conl ( ) ;");
-                                }
-                                tb.add("} ;");
+                        tb.add(".. } => {");
+                        match (&container_attrs.tag, &container_attrs.content) {
+                            (None, _) => {
+                                tb.add("s . label (").string(&variant_name).add(") ;");
+                                tb.add("s . out . push (").chr(':').add(") ;");
+                                ser_json_struct_body(&mut tb, &fields);
                             }
-                            else{
-                                tb.add("s . field ( d + 1 ,").string(&field_strip).add(" ) ;");
-                                tb.ident(&field.name).add(". ser_json ( d + 1 , s ) ;");
-                                if i != fields_len - 1 {
-                                    tb.add("s . conl ( ) ;");
+                            (Some(tag), None) => {
+                                tb.add("s . st_pre ( ) ; s . field ( d + 1 ,").string(tag).add(") ;");
+                                tb.add("s . label (").string(&variant_name).add(") ;");
+                                if has_written_fields(&fields) {
+                                    tb.add("let mut __first = false ;");
                                 }
+                                ser_json_fields(&mut tb, &fields, false);
+                                tb.add("s . st_post ( d ) ;");
+                            }
+                            (Some(tag), Some(content)) => {
+                                tb.add("s . st_pre ( ) ; s . field ( d + 1 ,").string(tag).add(") ;");
+                                tb.add("s . label (").string(&variant_name).add(") ;");
+                                tb.add("s . conl ( ) ; s . field ( d + 1 ,").string(content).add(") ;");
+                                ser_json_struct_body(&mut tb, &fields);
+                                tb.add("s . st_post ( d ) ;");
                             }
                         }
-                        tb.add("s . st_post ( d ) ; }");
+                        tb.add("}");
                     }
                     else if parser.is_punct_alone(',') || parser.is_eot(){ // bare variant
                         tb.add("Self ::").ident(&variant).add("=> {");
-                        tb.add("s . label (").string(&variant).add(") ;");
-                        tb.add("s . out . push_str (").string(":[]").add(") ; }");
+                        if let Some(tag) = &container_attrs.tag {
+                            tb.add("s . st_pre ( ) ; s . field ( d + 1 ,").string(tag).add(") ;");
+                            tb.add("s . label (").string(&variant_name).add(") ;");
+                            tb.add("s . st_post ( d ) ; }");
+                        }
+                        else {
+                            tb.add("s . label (").string(&variant_name).add(") ;");
+                            tb.add("s . out . push_str (").string(":[]").add(") ; }");
+                        }
                     }
                     else{
                         return parser.unexpected();
@@ -147,7 +298,9 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
                 }
             }
             tb.add("}");
-            tb.add("s . out . push (").chr('}').add(") ;");
+            if container_attrs.tag.is_none() {
+                tb.add("s . out . push (").chr('}').add(") ;");
+            }
             tb.add("} } ;");
             return tb.end();
         }
@@ -158,8 +311,8 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
 pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    
-    parser.eat_attributes();
+
+    let attrs = parser.eat_attributes();
     parser.eat_ident("pub");
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
@@ -183,47 +336,23 @@ pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
                 tb.add("s . block_close ( i ) ? ;");
                 tb.add("std :: result :: Result :: Ok ( r )");
             }
-            else if let Some(fields) = parser.eat_all_struct_fields(){ 
-                tb.add("s . curly_open ( i ) ? ;");
-                for field in &fields{
-                    tb.add("let mut").ident(&format!("_{}",field.name)).add("= None ;");
-                }
-                tb.add("while let Some ( _ ) = s . next_str ( ) {");
-                tb.add("match s . strbuf . as_ref ( ) {");
-                for field in &fields{
-                    let field_strip = if let Some(v) = field.name.strip_prefix("_"){v}else{&field.name};
-                    tb.string(&field_strip).add("=> { s . next_colon ( i ) ? ;");
-                    tb.ident(&format!("_{}",field.name)).add("= Some (DeJson :: de_json ( s , i ) ? ) ; } ,");
-                }
-                tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-                tb.add("} ; s . eat_comma_curly ( i ) ? ;");
-                tb.add("} ; s . curly_close ( i ) ? ;");
-                
-                tb.add("std :: result :: Result :: Ok ( Self {");
-                for field in fields{
-                    tb.ident(&field.name).add(":");
-                    if field.ty.into_iter().next().unwrap().to_string() == "Option"{
-                        tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
-                        tb.add("{ t } else { None } ,");
-                    }
-                    else{
-                        tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
-                        tb.add("{ t } else { return Err ( s . err_nf (");
-                        tb.string(&field.name).add(") ) } ,");
-                    }
-                }
+            else if let Some(fields) = parser.eat_all_struct_fields(){
+                let fields = match Field::parse_all(fields) {Ok(fields) => fields, Err(err) => return err};
+                tb.add("std :: result :: Result :: Ok ( {");
+                de_json_fields(&mut tb, &fields, None);
                 tb.add("} )");
             }
             else{
                 return parser.unexpected()
             }
-            tb.add("} } ;"); 
+            tb.add("} } ;");
             return tb.end();
         }
     }
     else if parser.eat_ident("enum"){
-        
+
         if let Some(name) = parser.eat_any_ident(){
+            let container_attrs = match ContainerAttrs::parse(&attrs) {Ok(attrs) => attrs, Err(err) => return err};
             let generic = parser.eat_generic();
             let where_clause = parser.eat_where_clause(Some("DeJson"));
 
@@ -231,66 +360,77 @@ pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
             tb.add("DeJson for").ident(&name).stream(generic).stream(where_clause);
             tb.add("{ fn de_json ( s : & mut  DeJsonState , i : & mut std :: str :: Chars )");
             tb.add("-> std :: result :: Result < Self , DeJsonErr > { ");
-            tb.add("s . curly_open ( i ) ? ;");
-            tb.add("let _ = s . string ( i ) ? ;");
-            tb.add("s . colon ( i ) ? ;");
-            tb.add("let r = std :: result :: Result :: Ok ( match s . strbuf . as_ref ( ) {");
-            
+            if let Some(tag) = &container_attrs.tag {
+                // the tag can come after the rest, so buffer the object and read the variant from that
+                tb.add("s . curly_open ( i ) ? ;");
+                tb.add("let mut __tag = None ;");
+                tb.add("let mut __content = String :: new ( ) ;");
+                tb.add("while let Some ( _ ) = s . next_str ( ) {");
+                tb.add("if s . strbuf ==").string(tag).add("{");
+                tb.add("s . next_colon ( i ) ? ; __tag = Some ( s . as_string ( ) ? ) ; s . next_tok ( i ) ? ;");
+                tb.add("}");
+                if let Some(content) = &container_attrs.content {
+                    tb.add("else if s . strbuf ==").string(content).add("{");
+                    tb.add("s . next_colon ( i ) ? ; s . capture_value ( i , & mut __content ) ? ;");
+                    tb.add("}");
+                    tb.add("else { return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) ) }");
+                }
+                else {
+                    tb.add("else { s . capture_field ( i , & mut __content ) ? ; }");
+                }
+                tb.add("s . eat_comma_curly ( i ) ? ;");
+                tb.add("} s . curly_close ( i ) ? ;");
+                tb.add("let __tag = if let Some ( t ) = __tag { t } else {");
+                tb.add("return std :: result :: Result :: Err ( s . err_nf (").string(tag).add(") ) } ;");
+                if container_attrs.content.is_none() {
+                    tb.add("let __content = format ! (").string("{{{}}}").add(", __content ) ;");
+                }
+                tb.add("let ( mut __s , mut __i ) = DeJsonState :: from_captured ( & __content ) ? ;");
+                tb.add("# [ allow ( unused_variables ) ] let ( s , i ) = ( & mut __s , & mut __i ) ;");
+                tb.add("let r = std :: result :: Result :: Ok ( match __tag . as_ref ( ) {");
+            }
+            else {
+                tb.add("s . curly_open ( i ) ? ;");
+                tb.add("let _ = s . string ( i ) ? ;");
+                tb.add("s . colon ( i ) ? ;");
+                tb.add("let r = std :: result :: Result :: Ok ( match s . strbuf . as_ref ( ) {");
+            }
+
             if !parser.open_brace(){
                 return parser.unexpected()
             }
+            let internally_tagged = container_attrs.tag.is_some() && container_attrs.content.is_none();
             while !parser.eat_eot(){
                 // parse ident
-                parser.eat_attributes();
+                let variant_attrs = match FieldAttrs::parse(&parser.eat_attributes()) {Ok(attrs) => attrs, Err(err) => return err};
                 if let Some(variant) = parser.eat_any_ident(){
-                    tb.string(&variant).add("=> {");
+                    let variant_name = variant_attrs.rename.clone().unwrap_or_else( || variant.clone());
+                    tb.string(&variant_name).add("=> {");
                     if let Some(types) = parser.eat_all_types(){
-                        
-                        tb.add("s . block_open ( i ) ? ;");
-                        tb.add("let r = Self ::").ident(&variant).add("(");
-                        for _ in 0..types.len(){
-                            tb.add("{ let r = DeJson :: de_json ( s , i ) ? ; s . eat_comma_block ( i ) ? ; r } ,");
+                        if internally_tagged {
+                            if types.len() != 1 {
+                                return error("internally tagged enums only support struct, newtype and unit variants")
+                            }
+                            tb.add("Self ::").ident(&variant).add("( DeJson :: de_json ( s , i ) ? )");
+                        }
+                        else {
+                            de_json_tuple_body(&mut tb, &variant, types.len());
                         }
-                        tb.add(") ;");
-                        tb.add("s . block_close ( i ) ? ; r");
                     }
                     else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
-                        tb.add("s . curly_open ( i ) ? ;");
-                        for field in &fields{
-                            tb.add("let mut").ident(&format!("_{}",field.name)).add("= None ;");
-                        }
-                        tb.add("while let Some ( _ ) = s . next_str ( ) {");
-                        tb.add("match s . strbuf . as_ref ( ) {");
-                        for field in &fields{
-                            tb.string(&field.name).add("=> { s . next_colon ( i ) ? ;");
-                            tb.ident(&format!("_{}",field.name)).add("= Some (DeJson :: de_json ( s , i ) ? ) ; } ,");
-                        }
-                        tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-                        tb.add("} s . eat_comma_curly ( i ) ? ;");
-                        tb.add("} s . curly_close ( i ) ? ;");
-                        
-                        tb.add("Self ::").ident(&variant).add("{");
-                        for field in fields{
-                            tb.ident(&field.name).add(":");
-                            if field.ty.into_iter().next().unwrap().to_string() == "Option"{
-                                tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
-                                tb.add("{ t } else { None } ,");
-                            }
-                            else{
-                                tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
-                                tb.add("{ t } else { return Err ( s . err_nf (");
-                                tb.string(&field.name).add(") ) } ,");
-                            }
-                        }
-                        tb.add("}");
+                        let fields = match Field::parse_all(fields) {Ok(fields) => fields, Err(err) => return err};
+                        de_json_fields(&mut tb, &fields, Some(&variant));
                     }
                     else if parser.is_punct_alone(',') || parser.is_eot(){ // bare variant
-                        tb.add("s . block_open ( i ) ? ; s . block_close ( i ) ? ; Self ::").ident(&variant);
+                        if container_attrs.tag.is_none() {
+                            tb.add("s . block_open ( i ) ? ; s . block_close ( i ) ? ;");
+                        }
+                        tb.add("Self ::").ident(&variant);
                     }
                     else{
                         return parser.unexpected();
                     }
-                    
+
                     tb.add("}");
                     parser.eat_punct_alone(',');
                 }
@@ -298,8 +438,14 @@ pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
                     return parser.unexpected()
                 }
             }
-            tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-            tb.add("} ) ; s . curly_close ( i ) ? ; r } }");
+            if container_attrs.tag.is_some() {
+                tb.add("_ => return std :: result :: Result :: Err ( s . err_enum ( & __tag ) )");
+                tb.add("} ) ; r } }");
+            }
+            else {
+                tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
+                tb.add("} ) ; s . curly_close ( i ) ? ; r } }");
+            }
             return tb.end();
         }
     }
@@ -1,11 +1,143 @@
 use makepad_micro_proc_macro::*;
 use proc_macro::TokenStream;
+use crate::attrs::*;
+
+// writes named fields into a struct that is already open. on a struct the fields are read
+// through self, on a variant they are bound by reference
+fn ser_ron_fields(tb: &mut TokenBuilder, fields: &[Field], on_self: bool) {
+    let value = |tb: &mut TokenBuilder, field: &Field, by_ref: bool| {
+        if on_self {
+            if by_ref {
+                tb.add("&");
+            }
+            tb.add("self .");
+        }
+        tb.ident(&field.name);
+    };
+    for field in fields {
+        if field.attrs.skip {
+            continue;
+        }
+        if field.attrs.flatten {
+            tb.add("s . flatten ( d ,");
+            value(tb, field, true);
+            tb.add(") ;");
+        } else if field.is_option() {
+            tb.add("if let Some ( t ) =");
+            value(tb, field, true);
+            tb.add("{");
+            tb.add("s . field ( d + 1 ,").string(field.key()).add(") ;");
+            tb.add("t . ser_ron ( d + 1 , s ) ; s . conl ( ) ; }");
+        } else {
+            tb.add("s . field ( d + 1 ,").string(field.key()).add(") ;");
+            value(tb, field, false);
+            tb.add(". ser_ron ( d + 1 , s ) ; s . conl ( ) ;");
+        }
+    }
+}
+
+fn ser_ron_tuple_body(tb: &mut TokenBuilder, len: usize) {
+    tb.add("s . out . push (").chr('(').add(") ;");
+    for i in 0..len {
+        tb.ident(&format!("n{}", i)).add(". ser_ron ( d , s ) ;");
+        if i != len - 1 {
+            tb.add("s . out . push_str (").string(", ").add(") ;");
+        }
+    }
+    tb.add("s . out . push (").chr(')').add(") ;");
+}
+
+fn ser_ron_struct_body(tb: &mut TokenBuilder, fields: &[Field]) {
+    tb.add("s . st_pre ( ) ;");
+    ser_ron_fields(tb, fields, false);
+    tb.add("s . st_post ( d ) ;");
+}
+
+// the tag of a tagged enum, as the first field of a struct that is already open
+fn ser_ron_tag(tb: &mut TokenBuilder, tag: &str, variant_name: &str) {
+    tb.add("s . field ( d + 1 ,").string(tag).add(") ;");
+    tb.add("s . out . push_str (")
+        .string(&format!("\"{}\"", variant_name))
+        .add(") ; s . conl ( ) ;");
+}
+
+// reads a struct into named fields and ends with the Self ( .. ) or Self :: Variant ( .. ) expression
+fn de_ron_fields(tb: &mut TokenBuilder, fields: &[Field], variant: Option<&str>) {
+    tb.add("s . paren_open ( i ) ? ;");
+    for field in fields {
+        if !field.attrs.skip && !field.attrs.flatten {
+            tb.add("let mut")
+                .ident(&format!("_{}", field.name))
+                .add("= None ;");
+        }
+    }
+    let flatten = has_flatten(fields);
+    if flatten {
+        tb.add("let mut __flatten = String :: new ( ) ;");
+    }
+    tb.add("while let Some ( _ ) = s . next_ident ( ) {");
+    tb.add("match s . identbuf . as_ref ( ) {");
+    for field in fields {
+        if !field.attrs.skip && !field.attrs.flatten {
+            tb.string(field.key()).add("=> { s . next_colon ( i ) ? ;");
+            tb.ident(&format!("_{}", field.name))
+                .add("= Some ( DeRon :: de_ron ( s , i ) ? ) ; } ,");
+        }
+    }
+    if flatten {
+        tb.add("_ => s . capture_field ( i , & mut __flatten ) ?");
+    } else {
+        tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . identbuf ) )");
+    }
+    tb.add("} ; s . eat_comma_paren ( i ) ? ;");
+    tb.add("} ; s . paren_close ( i ) ? ;");
+
+    tb.add("Self");
+    if let Some(variant) = variant {
+        tb.add("::").ident(variant);
+    }
+    tb.add("{");
+    for field in fields {
+        tb.ident(&field.name).add(":");
+        if field.attrs.skip {
+            field.default_value(tb);
+        } else if field.attrs.flatten {
+            tb.add("DeRonState :: de_captured_fields ( & __flatten ) ?");
+        } else {
+            tb.add("if let Some ( t ) =")
+                .ident(&format!("_{}", field.name))
+                .add("{ t } else {");
+            if field.attrs.default {
+                field.default_value(tb);
+            } else if field.is_option() {
+                tb.add("None");
+            } else {
+                tb.add("return std :: result :: Result :: Err ( s . err_nf (")
+                    .string(field.key())
+                    .add(") )");
+            }
+            tb.add("}");
+        }
+        tb.add(",");
+    }
+    tb.add("}");
+}
+
+fn de_ron_tuple_body(tb: &mut TokenBuilder, variant: &str, len: usize) {
+    tb.add("s . paren_open ( i ) ? ;");
+    tb.add("let r = Self ::").ident(variant).add("(");
+    for _ in 0..len {
+        tb.add("{ let r = DeRon :: de_ron ( s , i ) ? ; s . eat_comma_paren ( i ) ? ; r } ,");
+    }
+    tb.add(") ;");
+    tb.add("s . paren_close ( i ) ? ; r");
+}
 
 pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
 
-    parser.eat_attributes();
+    let attrs = parser.eat_attributes();
     parser.eat_ident("pub");
     if parser.eat_ident("struct") {
         if let Some(name) = parser.eat_any_ident() {
@@ -31,26 +163,13 @@ pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
                 }
                 tb.add("s.out.push(").chr(')').add(");");
             } else if let Some(fields) = parser.eat_all_struct_fields() {
+                let fields = match Field::parse_all(fields) {
+                    Ok(fields) => fields,
+                    Err(err) => return err,
+                };
                 tb.add("s.st_pre( ) ;");
-
                 // named struct
-                for field in fields {
-                    if field.ty.into_iter().next().unwrap().to_string() == "Option" {
-                        tb.add("if let Some ( t ) = ")
-                            .add("& self .")
-                            .ident(&field.name)
-                            .add("{");
-                        tb.add("s.field ( d + 1 ,").string(&field.name).add(") ;");
-                        tb.add("t.ser_ron ( d + 1 , s ) ; s . conl ( ) ; } ;");
-                    } else {
-                        tb.add("s . field ( d + 1 ,")
-                            .string(&field.name)
-                            .add(" ) ;");
-                        tb.add("self .")
-                            .ident(&field.name)
-                            .add(". ser_ron ( d + 1 , s ) ; s . conl ( ) ;");
-                    }
-                }
+                ser_ron_fields(&mut tb, &fields, true);
                 tb.add("s . st_post ( d ) ;");
             } else {
                 return parser.unexpected();
@@ -60,6 +179,10 @@ pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
         }
     } else if parser.eat_ident("enum") {
         if let Some(name) = parser.eat_any_ident() {
+            let container_attrs = match ContainerAttrs::parse(&attrs) {
+                Ok(attrs) => attrs,
+                Err(err) => return err,
+            };
             let generic = parser.eat_generic();
             let where_clause = parser.eat_where_clause(Some("SerRon"));
 
@@ -77,55 +200,85 @@ pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
 
             while !parser.eat_eot() {
                 // parse ident
-                parser.eat_attributes();
+                let variant_attrs = match FieldAttrs::parse(&parser.eat_attributes()) {
+                    Ok(attrs) => attrs,
+                    Err(err) => return err,
+                };
                 if let Some(variant) = parser.eat_any_ident() {
+                    let variant_name = variant_attrs.rename.clone().unwrap_or_else(|| variant.clone());
                     if let Some(types) = parser.eat_all_types() {
                         tb.add("Self ::").ident(&variant).add("(");
                         for i in 0..types.len() {
                             tb.ident(&format!("n{}", i)).add(",");
                         }
                         tb.add(") => {");
-                        tb.add("s . out . push_str (").string(&variant).add(") ;");
-                        tb.add("s . out . push (").chr('(').add(") ;");
-
-                        for i in 0..types.len() {
-                            tb.ident(&format!("n{}", i)).add(". ser_ron ( d , s ) ;");
-                            if i != types.len() - 1 {
-                                tb.add("s . out . push_str (").string(", ").add(") ;");
+                        match (&container_attrs.tag, &container_attrs.content) {
+                            (None, _) => {
+                                tb.add("s . out . push_str (").string(&variant_name).add(") ;");
+                                ser_ron_tuple_body(&mut tb, types.len());
+                            }
+                            (Some(tag), None) => {
+                                if types.len() != 1 {
+                                    return error("internally tagged enums only support struct, newtype and unit variants");
+                                }
+                                tb.add("s . st_pre ( ) ;");
+                                ser_ron_tag(&mut tb, tag, &variant_name);
+                                tb.add("s . flatten ( d , n0 ) ; s . st_post ( d ) ;");
+                            }
+                            (Some(tag), Some(content)) => {
+                                tb.add("s . st_pre ( ) ;");
+                                ser_ron_tag(&mut tb, tag, &variant_name);
+                                tb.add("s . field ( d + 1 ,").string(content).add(") ;");
+                                tb.add("{ let d = d + 1 ;");
+                                ser_ron_tuple_body(&mut tb, types.len());
+                                tb.add("} s . conl ( ) ; s . st_post ( d ) ;");
                             }
                         }
-                        tb.add("s . out . push (").chr(')').add(") ;");
                         tb.add("}");
                     } else if let Some(fields) = parser.eat_all_struct_fields() {
                         // named variant
+                        let fields = match Field::parse_all(fields) {
+                            Ok(fields) => fields,
+                            Err(err) => return err,
+                        };
                         tb.add("Self ::").ident(&variant).add("{");
                         for field in fields.iter() {
-                            tb.ident(&field.name).add(",");
+                            if !field.attrs.skip {
+                                tb.ident(&field.name).add(",");
+                            }
                         }
-                        tb.add("} => {");
-
-                        tb.add("s . out . push_str (").string(&variant).add(") ;");
-                        tb.add("s . st_pre ( ) ;");
-
-                        for field in fields {
-                            if field.ty.into_iter().next().unwrap().to_string() == "Option" {
-                                tb.add("if ").ident(&field.name).add(". is_some ( ) {");
-                                tb.add("s . field ( d + 1 ,").string(&field.name).add(") ;");
-                                tb.ident(&field.name)
-                                    .add(" . ser_ron ( d + 1 , s ) ; s . conl ( ) ; } ;");
-                            } else {
-                                tb.add("s . field ( d + 1 ,")
-                                    .string(&field.name)
-                                    .add(" ) ;");
-                                tb.ident(&field.name)
-                                    .add(". ser_ron ( d + 1 , s ) ; s . conl ( ) ;");
+                        tb.add(".. } => {");
+                        match (&container_attrs.tag, &container_attrs.content) {
+                            (None, _) => {
+                                tb.add("s . out . push_str (").string(&variant_name).add(") ;");
+                                ser_ron_struct_body(&mut tb, &fields);
+                            }
+                            (Some(tag), None) => {
+                                tb.add("s . st_pre ( ) ;");
+                                ser_ron_tag(&mut tb, tag, &variant_name);
+                                ser_ron_fields(&mut tb, &fields, false);
+                                tb.add("s . st_post ( d ) ;");
+                            }
+                            (Some(tag), Some(content)) => {
+                                tb.add("s . st_pre ( ) ;");
+                                ser_ron_tag(&mut tb, tag, &variant_name);
+                                tb.add("s . field ( d + 1 ,").string(content).add(") ;");
+                                tb.add("{ let d = d + 1 ;");
+                                ser_ron_struct_body(&mut tb, &fields);
+                                tb.add("} s . conl ( ) ; s . st_post ( d ) ;");
                             }
                         }
-                        tb.add("s . st_post ( d ) ; }");
+                        tb.add("}");
                     } else if parser.is_punct_alone(',') || parser.is_eot() {
                         // bare variant
                         tb.add("Self ::").ident(&variant).add("=> {");
-                        tb.add("s . out . push_str (").string(&variant).add(") ; }");
+                        if let Some(tag) = &container_attrs.tag {
+                            tb.add("s . st_pre ( ) ;");
+                            ser_ron_tag(&mut tb, tag, &variant_name);
+                            tb.add("s . st_post ( d ) ; }");
+                        } else {
+                            tb.add("s . out . push_str (").string(&variant_name).add(") ; }");
+                        }
                     } else {
                         return parser.unexpected();
                     }
@@ -145,7 +298,7 @@ pub fn derive_ser_ron_impl(input: TokenStream) -> TokenStream {
 pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    parser.eat_attributes();
+    let attrs = parser.eat_attributes();
     parser.eat_ident("pub");
     if parser.eat_ident("struct") {
         if let Some(name) = parser.eat_any_ident() {
@@ -172,39 +325,12 @@ pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
                 tb.add("s . paren_close ( i ) ? ;");
                 tb.add("std :: result :: Result :: Ok ( r ) ");
             } else if let Some(fields) = parser.eat_all_struct_fields() {
-                tb.add("s . paren_open ( i ) ? ;");
-                for field in &fields {
-                    tb.add("let mut")
-                        .ident(&format!("_{}", field.name))
-                        .add("= None ;");
-                }
-                tb.add("while let Some ( _ ) = s . next_ident ( ) {");
-                tb.add("match s . identbuf . as_ref ( ) {");
-                for field in &fields {
-                    tb.string(&field.name).add("=> { s . next_colon ( i ) ? ;");
-                    tb.ident(&format!("_{}", field.name))
-                        .add("= Some ( DeRon :: de_ron ( s , i ) ? ) ; } ,");
-                }
-                tb.add(
-                    "_ => return std :: result :: Result :: Err ( s . err_exp ( & s . identbuf ) )",
-                );
-                tb.add("} ; s . eat_comma_paren ( i ) ? ;");
-                tb.add("} ; s . paren_close ( i ) ? ;");
-
-                tb.add("std :: result :: Result :: Ok ( Self {");
-                for field in fields {
-                    tb.ident(&field.name).add(":");
-                    if field.ty.into_iter().next().unwrap().to_string() == "Option" {
-                        tb.add("if let Some ( t ) =")
-                            .ident(&format!("_{}", field.name));
-                        tb.add("{ t } else { None } ,");
-                    } else {
-                        tb.add("if let Some ( t ) =")
-                            .ident(&format!("_{}", field.name));
-                        tb.add("{ t } else { return Err ( s . err_nf (");
-                        tb.string(&field.name).add(") ) } ,");
-                    }
-                }
+                let fields = match Field::parse_all(fields) {
+                    Ok(fields) => fields,
+                    Err(err) => return err,
+                };
+                tb.add("std :: result :: Result :: Ok ( {");
+                de_ron_fields(&mut tb, &fields, None);
                 tb.add("} )");
             } else {
                 return parser.unexpected();
@@ -214,6 +340,10 @@ pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
         }
     } else if parser.eat_ident("enum") {
         if let Some(name) = parser.eat_any_ident() {
+            let container_attrs = match ContainerAttrs::parse(&attrs) {
+                Ok(attrs) => attrs,
+                Err(err) => return err,
+            };
             let generic = parser.eat_generic();
             let where_clause = parser.eat_where_clause(Some("DeRon"));
 
@@ -224,59 +354,71 @@ pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
                 .stream(where_clause);
             tb.add("{ fn de_ron ( s : & mut  DeRonState , i : & mut std :: str :: Chars )");
             tb.add("-> std :: result :: Result < Self , DeRonErr > { ");
-            tb.add("s . ident ( i ) ? ;");
-            tb.add("std :: result :: Result :: Ok ( match s . identbuf . as_ref ( ) {");
+            if let Some(tag) = &container_attrs.tag {
+                // the tag can come after the rest, so buffer the struct and read the variant from that
+                tb.add("s . paren_open ( i ) ? ;");
+                tb.add("let mut __tag = None ;");
+                tb.add("let mut __content = String :: new ( ) ;");
+                tb.add("while let Some ( _ ) = s . next_ident ( ) {");
+                tb.add("if s . identbuf ==").string(tag).add("{");
+                tb.add("s . next_colon ( i ) ? ; __tag = Some ( s . as_string ( ) ? ) ; s . next_tok ( i ) ? ;");
+                tb.add("}");
+                if let Some(content) = &container_attrs.content {
+                    tb.add("else if s . identbuf ==").string(content).add("{");
+                    tb.add("s . next_colon ( i ) ? ; s . capture_value ( i , & mut __content ) ? ;");
+                    tb.add("}");
+                    tb.add("else { return std :: result :: Result :: Err ( s . err_exp ( & s . identbuf ) ) }");
+                } else {
+                    tb.add("else { s . capture_field ( i , & mut __content ) ? ; }");
+                }
+                tb.add("s . eat_comma_paren ( i ) ? ;");
+                tb.add("} s . paren_close ( i ) ? ;");
+                tb.add("let __tag = if let Some ( t ) = __tag { t } else {");
+                tb.add("return std :: result :: Result :: Err ( s . err_nf (")
+                    .string(tag)
+                    .add(") ) } ;");
+                if container_attrs.content.is_none() {
+                    tb.add("let __content = format ! (")
+                        .string("({})")
+                        .add(", __content ) ;");
+                }
+                tb.add("let ( mut __s , mut __i ) = DeRonState :: from_captured ( & __content ) ? ;");
+                tb.add("# [ allow ( unused_variables ) ] let ( s , i ) = ( & mut __s , & mut __i ) ;");
+                tb.add("std :: result :: Result :: Ok ( match __tag . as_ref ( ) {");
+            } else {
+                tb.add("s . ident ( i ) ? ;");
+                tb.add("std :: result :: Result :: Ok ( match s . identbuf . as_ref ( ) {");
+            }
 
             if !parser.open_brace() {
                 return parser.unexpected();
             }
+            let internally_tagged = container_attrs.tag.is_some() && container_attrs.content.is_none();
             while !parser.eat_eot() {
                 // parse ident
-                parser.eat_attributes();
+                let variant_attrs = match FieldAttrs::parse(&parser.eat_attributes()) {
+                    Ok(attrs) => attrs,
+                    Err(err) => return err,
+                };
                 if let Some(variant) = parser.eat_any_ident() {
-                    tb.string(&variant).add("=> {");
+                    let variant_name = variant_attrs.rename.clone().unwrap_or_else(|| variant.clone());
+                    tb.string(&variant_name).add("=> {");
                     if let Some(types) = parser.eat_all_types() {
-                        tb.add("s . paren_open ( i ) ? ;");
-                        tb.add("let r = Self ::").ident(&variant).add("(");
-                        for _ in 0..types.len() {
-                            tb.add("{ let r = DeRon :: de_ron ( s , i ) ? ; s . eat_comma_paren ( i ) ? ; r } ,");
+                        if internally_tagged {
+                            if types.len() != 1 {
+                                return error("internally tagged enums only support struct, newtype and unit variants");
+                            }
+                            tb.add("Self ::").ident(&variant).add("( DeRon :: de_ron ( s , i ) ? )");
+                        } else {
+                            de_ron_tuple_body(&mut tb, &variant, types.len());
                         }
-                        tb.add(") ;");
-                        tb.add("s . paren_close ( i ) ? ; r");
                     } else if let Some(fields) = parser.eat_all_struct_fields() {
                         // named variant
-                        tb.add("s . paren_open ( i ) ? ;");
-                        for field in &fields {
-                            tb.add("let mut")
-                                .ident(&format!("_{}", field.name))
-                                .add("= None ;");
-                        }
-                        tb.add("while let Some ( _ ) = s . next_ident ( ) {");
-                        tb.add("match s . identbuf . as_ref ( ) {");
-                        for field in &fields {
-                            tb.string(&field.name).add("=> { s . next_colon ( i ) ? ;");
-                            tb.ident(&format!("_{}", field.name))
-                                .add("= Some ( DeRon :: de_ron ( s , i ) ? ) ; } ,");
-                        }
-                        tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-                        tb.add("} ; s . eat_comma_paren ( i ) ? ;");
-                        tb.add("} ; s . paren_close ( i ) ? ;");
-
-                        tb.add("Self ::").ident(&variant).add("{");
-                        for field in fields {
-                            tb.ident(&field.name).add(":");
-                            if field.ty.into_iter().next().unwrap().to_string() == "Option" {
-                                tb.add("if let Some ( t ) =")
-                                    .ident(&format!("_{}", field.name));
-                                tb.add("{ t } else { None } ,");
-                            } else {
-                                tb.add("if let Some ( t ) =")
-                                    .ident(&format!("_{}", field.name));
-                                tb.add("{ t } else { return Err ( s . err_nf (");
-                                tb.string(&field.name).add(") ) } ,");
-                            }
-                        }
-                        tb.add("}");
+                        let fields = match Field::parse_all(fields) {
+                            Ok(fields) => fields,
+                            Err(err) => return err,
+                        };
+                        de_ron_fields(&mut tb, &fields, Some(&variant));
                     } else if parser.is_punct_alone(',') || parser.is_eot() {
                         // bare variant
                         tb.add("Self ::").ident(&variant);
@@ -290,9 +432,13 @@ pub fn derive_de_ron_impl(input: TokenStream) -> TokenStream {
                     return parser.unexpected();
                 }
             }
-            tb.add(
-                "_ => return std :: result :: Result :: Err ( s . err_enum ( & s . identbuf ) )",
-            );
+            if container_attrs.tag.is_some() {
+                tb.add("_ => return std :: result :: Result :: Err ( s . err_enum ( & __tag ) )");
+            } else {
+                tb.add(
+                    "_ => return std :: result :: Result :: Err ( s . err_enum ( & s . identbuf ) )",
+                );
+            }
             tb.add("} ) } }");
             return tb.end();
         }
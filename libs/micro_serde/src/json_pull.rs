@@ -0,0 +1,288 @@
+use std::str::Chars;
+use crate::serde_json::*;
+
+/// What `JsonPullParser` found next. Object keys come as `Key` right before their value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonEvent {
+    ObjectStart,
+    ObjectEnd,
+    ArrayStart,
+    ArrayEnd,
+    Key(String),
+    Str(String),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum JsonContainer {
+    Object,
+    Array,
+}
+
+/// Walks a json document one token at a time without building it in memory, on top of the
+/// `DeJsonState` tokenizer. Documents that follow each other, like newline separated json,
+/// are read one after the other. Parts of the document can be read into a typed value
+/// with `read` or skipped with `skip_value`.
+pub struct JsonPullParser<'a> {
+    state: DeJsonState,
+    chars: Chars<'a>,
+    stack: Vec<JsonContainer>,
+    after_key: bool,
+    started: bool,
+    failed: bool,
+}
+
+impl<'a> JsonPullParser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            state: DeJsonState::default(),
+            chars: input.chars(),
+            stack: Vec::new(),
+            after_key: false,
+            started: false,
+            failed: false,
+        }
+    }
+
+    /// How many objects and arrays we are in
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn line(&self) -> usize {
+        self.state.line
+    }
+
+    /// The next event, or None at the end of the input
+    pub fn next_event(&mut self) -> Result<Option<JsonEvent>, DeJsonErr> {
+        if self.failed {
+            return Ok(None)
+        }
+        let result = self.next_event_inner();
+        if result.is_err() {
+            self.failed = true;
+        }
+        result
+    }
+
+    fn next_event_inner(&mut self) -> Result<Option<JsonEvent>, DeJsonErr> {
+        self.start() ?;
+        let s = &mut self.state;
+        let i = &mut self.chars;
+        match self.stack.last().copied() {
+            Some(JsonContainer::Object) if !self.after_key => {
+                return match s.tok {
+                    DeJsonTok::CurlyClose => {
+                        s.next_tok(i) ?;
+                        self.stack.pop();
+                        self.end_value() ?;
+                        Ok(Some(JsonEvent::ObjectEnd))
+                    }
+                    DeJsonTok::Str => {
+                        let key = s.as_string() ?;
+                        s.next_colon(i) ?;
+                        self.after_key = true;
+                        Ok(Some(JsonEvent::Key(key)))
+                    }
+                    _ => Err(s.err_token("key or }"))
+                }
+            }
+            Some(JsonContainer::Array) if s.tok == DeJsonTok::BlockClose => {
+                s.next_tok(i) ?;
+                self.stack.pop();
+                self.end_value() ?;
+                return Ok(Some(JsonEvent::ArrayEnd))
+            }
+            None if s.tok == DeJsonTok::Eof => return Ok(None),
+            _ => ()
+        }
+        self.after_key = false;
+        let event = match s.tok {
+            DeJsonTok::CurlyOpen => {
+                s.next_tok(i) ?;
+                self.stack.push(JsonContainer::Object);
+                return Ok(Some(JsonEvent::ObjectStart))
+            }
+            DeJsonTok::BlockOpen => {
+                s.next_tok(i) ?;
+                self.stack.push(JsonContainer::Array);
+                return Ok(Some(JsonEvent::ArrayStart))
+            }
+            DeJsonTok::Str => JsonEvent::Str(s.as_string() ?),
+            DeJsonTok::U64(v) => JsonEvent::U64(v),
+            DeJsonTok::I64(v) => JsonEvent::I64(v),
+            DeJsonTok::F64(v) => JsonEvent::F64(v),
+            DeJsonTok::Bool(v) => JsonEvent::Bool(v),
+            DeJsonTok::Null => JsonEvent::Null,
+            _ => return Err(s.err_token("value"))
+        };
+        s.next_tok(i) ?;
+        self.end_value() ?;
+        Ok(Some(event))
+    }
+
+    fn start(&mut self) -> Result<(), DeJsonErr> {
+        if !self.started {
+            self.started = true;
+            self.state.next(&mut self.chars);
+            self.state.next_tok(&mut self.chars) ?;
+        }
+        Ok(())
+    }
+
+    // eats the comma after a value, or leaves the closing bracket for the next event
+    fn end_value(&mut self) -> Result<(), DeJsonErr> {
+        match self.stack.last() {
+            Some(JsonContainer::Object) => self.state.eat_comma_curly(&mut self.chars),
+            Some(JsonContainer::Array) => self.state.eat_comma_block(&mut self.chars),
+            None => Ok(())
+        }
+    }
+
+    fn at_value(&self) -> bool {
+        match self.stack.last() {
+            Some(JsonContainer::Object) => self.after_key,
+            Some(JsonContainer::Array) => self.state.tok != DeJsonTok::BlockClose,
+            None => self.state.tok != DeJsonTok::Eof,
+        }
+    }
+
+    /// Deserializes the next value, which can be a whole object or array, into any `DeJson` type.
+    /// Has to be called where a value comes next: after a `Key`, in an array or at the top.
+    pub fn read<T: DeJson>(&mut self) -> Result<T, DeJsonErr> {
+        self.start() ?;
+        if self.failed || !self.at_value() {
+            return Err(self.state.err_token("value"))
+        }
+        self.after_key = false;
+        let result = T::de_json(&mut self.state, &mut self.chars).and_then( | value | {
+            self.end_value() ?;
+            Ok(value)
+        });
+        if result.is_err() {
+            self.failed = true;
+        }
+        result
+    }
+
+    /// Skips the next value including everything in it, same place rules as `read`
+    pub fn skip_value(&mut self) -> Result<(), DeJsonErr> {
+        self.start() ?;
+        if self.failed || !self.at_value() {
+            return Err(self.state.err_token("value"))
+        }
+        let depth = self.depth();
+        loop {
+            self.next_event() ?;
+            if self.depth() == depth {
+                return Ok(())
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for JsonPullParser<'a> {
+    type Item = Result<JsonEvent, DeJsonErr>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+/// Collects json that arrives in pieces, like a streamed http response, and hands out each
+/// top level value once it is complete. Values can follow each other directly or be separated
+/// by whitespace. Framing like the `data: ` of server sent events is up to the caller.
+#[derive(Default)]
+pub struct JsonChunkBuffer {
+    buf: String,
+    // how far we have scanned and the scanner state at that point
+    scanned: usize,
+    start: Option<usize>,
+    depth: usize,
+    in_string: bool,
+    escape: bool,
+}
+
+impl JsonChunkBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, chunk: &str) {
+        self.buf.push_str(chunk);
+    }
+
+    /// What has been pushed but isn't a complete value yet
+    pub fn pending(&self) -> &str {
+        &self.buf
+    }
+
+    /// The text of the next complete value, if there is one
+    pub fn next_text(&mut self) -> Option<String> {
+        while self.scanned < self.buf.len() {
+            let b = self.buf.as_bytes()[self.scanned];
+            let pos = self.scanned;
+            self.scanned += 1;
+            if self.in_string {
+                if self.escape {
+                    self.escape = false;
+                }
+                else if b == b'\\' {
+                    self.escape = true;
+                }
+                else if b == b'"' {
+                    self.in_string = false;
+                    if self.depth == 0 {
+                        return Some(self.take(pos + 1))
+                    }
+                }
+                continue;
+            }
+            let is_space = b == b' ' || b == b'\t' || b == b'\r' || b == b'\n';
+            match self.start {
+                None if !is_space => self.start = Some(pos),
+                // a bare number or true/false/null at the top ends at whitespace or the next value
+                Some(_) if self.depth == 0 && (is_space || b == b'{' || b == b'[' || b == b'"') => {
+                    return Some(self.take(pos))
+                }
+                _ => ()
+            }
+            match b {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' if self.depth > 0 => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        return Some(self.take(pos + 1))
+                    }
+                }
+                _ => ()
+            }
+        }
+        None
+    }
+
+    /// The next complete value, deserialized
+    pub fn next_value<T: DeJson>(&mut self) -> Option<Result<T, DeJsonErr >> {
+        self.next_text().map( | text | T::deserialize_json(&text))
+    }
+
+    /// Whatever is left as a value, for when the stream has ended and a bare number was last
+    pub fn finish(&mut self) -> Option<String> {
+        let rest = self.buf.trim();
+        let ret = if rest.is_empty() || self.depth > 0 || self.in_string {None} else {Some(rest.to_string())};
+        *self = Self::default();
+        ret
+    }
+
+    fn take(&mut self, end: usize) -> String {
+        let start = self.start.take().unwrap_or(0);
+        let text = self.buf[start..end].to_string();
+        self.buf.drain(..end);
+        self.scanned = 0;
+        text
+    }
+}
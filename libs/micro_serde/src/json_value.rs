@@ -0,0 +1,318 @@
+use std::ops::{Index, IndexMut};
+use std::str::Chars;
+use crate::serde_json::*;
+
+/// An untyped json document. Objects keep their keys in document order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum JsonValue {
+    #[default]
+    Null,
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+static NULL: JsonValue = JsonValue::Null;
+
+impl JsonValue {
+    pub fn parse(input: &str) -> Result<Self, DeJsonErr> {
+        Self::deserialize_json(input)
+    }
+
+    pub fn object() -> Self {
+        JsonValue::Object(Vec::new())
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        if let JsonValue::Bool(v) = self {Some(*v)} else {None}
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            JsonValue::U64(v) => Some(v),
+            JsonValue::I64(v) if v >= 0 => Some(v as u64),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            JsonValue::I64(v) => Some(v),
+            JsonValue::U64(v) if v <= i64::MAX as u64 => Some(v as i64),
+            _ => None
+        }
+    }
+
+    /// Any number as a float, integers are converted
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            JsonValue::U64(v) => Some(v as f64),
+            JsonValue::I64(v) => Some(v as f64),
+            JsonValue::F64(v) => Some(v),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        if let JsonValue::String(v) = self {Some(v)} else {None}
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue >> {
+        if let JsonValue::Array(v) = self {Some(v)} else {None}
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<JsonValue >> {
+        if let JsonValue::Array(v) = self {Some(v)} else {None}
+    }
+
+    pub fn as_object(&self) -> Option<&Vec<(String, JsonValue) >> {
+        if let JsonValue::Object(v) = self {Some(v)} else {None}
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut Vec<(String, JsonValue) >> {
+        if let JsonValue::Object(v) = self {Some(v)} else {None}
+    }
+
+    /// The value of a key of an object, the first one if the key is there more than once
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.as_object()?.iter().find( | (k, _) | k == key).map( | (_, v) | v)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        self.as_object_mut()?.iter_mut().find( | (k, _) | k == key).map( | (_, v) | v)
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&JsonValue> {
+        self.as_array()?.get(index)
+    }
+
+    /// Sets a key on an object, replacing the value if the key is there already.
+    /// Does nothing if this isn't an object.
+    pub fn insert(&mut self, key: &str, value: impl Into<JsonValue>) {
+        if let Some(object) = self.as_object_mut() {
+            let value = value.into();
+            if let Some((_, v)) = object.iter_mut().find( | (k, _) | k == key) {
+                *v = value;
+            }
+            else {
+                object.push((key.to_string(), value));
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            JsonValue::Array(v) => v.len(),
+            JsonValue::Object(v) => v.len(),
+            _ => 0
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Looks up a json pointer (RFC 6901) like `/choices/0/delta/content`, the empty pointer is the value itself
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        if pointer.is_empty() {
+            return Some(self)
+        }
+        let mut value = self;
+        for token in pointer.strip_prefix('/')?.split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            value = match value {
+                JsonValue::Object(_) => value.get(&token) ?,
+                JsonValue::Array(array) => array.get(parse_pointer_index(&token) ?) ?,
+                _ => return None
+            };
+        }
+        Some(value)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        if pointer.is_empty() {
+            return Some(self)
+        }
+        let mut value = self;
+        for token in pointer.strip_prefix('/')?.split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            value = match value {
+                JsonValue::Object(_) => value.get_mut(&token) ?,
+                JsonValue::Array(array) => array.get_mut(parse_pointer_index(&token) ?) ?,
+                _ => return None
+            };
+        }
+        Some(value)
+    }
+}
+
+// array indices in a pointer are plain decimals without leading zeros
+fn parse_pointer_index(token: &str) -> Option<usize> {
+    if token.len() > 1 && token.starts_with('0') {
+        return None
+    }
+    token.parse().ok()
+}
+
+/// Missing keys and indices give `Null` so lookups can be chained, like `value["choices"][0]["text"]`
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+    fn index(&self, key: &str) -> &JsonValue {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+    fn index(&self, index: usize) -> &JsonValue {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+/// Turns `Null` into an object and adds the key if it is missing, panics on other values
+impl IndexMut<&str> for JsonValue {
+    fn index_mut(&mut self, key: &str) -> &mut JsonValue {
+        if self.is_null() {
+            *self = JsonValue::object();
+        }
+        let object = self.as_object_mut().expect("JsonValue is not an object");
+        let index = if let Some(index) = object.iter().position( | (k, _) | k == key) {
+            index
+        }
+        else {
+            object.push((key.to_string(), JsonValue::Null));
+            object.len() - 1
+        };
+        &mut object[index].1
+    }
+}
+
+impl IndexMut<usize> for JsonValue {
+    fn index_mut(&mut self, index: usize) -> &mut JsonValue {
+        &mut self.as_array_mut().expect("JsonValue is not an array")[index]
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(v: bool) -> Self {JsonValue::Bool(v)}
+}
+
+impl From<u64> for JsonValue {
+    fn from(v: u64) -> Self {JsonValue::U64(v)}
+}
+
+impl From<u32> for JsonValue {
+    fn from(v: u32) -> Self {JsonValue::U64(v as u64)}
+}
+
+impl From<usize> for JsonValue {
+    fn from(v: usize) -> Self {JsonValue::U64(v as u64)}
+}
+
+impl From<i64> for JsonValue {
+    fn from(v: i64) -> Self {JsonValue::I64(v)}
+}
+
+impl From<i32> for JsonValue {
+    fn from(v: i32) -> Self {JsonValue::I64(v as i64)}
+}
+
+impl From<f64> for JsonValue {
+    fn from(v: f64) -> Self {JsonValue::F64(v)}
+}
+
+impl From<&str> for JsonValue {
+    fn from(v: &str) -> Self {JsonValue::String(v.to_string())}
+}
+
+impl From<String> for JsonValue {
+    fn from(v: String) -> Self {JsonValue::String(v)}
+}
+
+impl From<Vec<JsonValue >> for JsonValue {
+    fn from(v: Vec<JsonValue>) -> Self {JsonValue::Array(v)}
+}
+
+impl<T> From<Option<T >> for JsonValue where T: Into<JsonValue> {
+    fn from(v: Option<T>) -> Self {
+        v.map( | v | v.into()).unwrap_or(JsonValue::Null)
+    }
+}
+
+impl SerJson for JsonValue {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        match self {
+            JsonValue::Null => s.out.push_str("null"),
+            JsonValue::Bool(v) => v.ser_json(d, s),
+            JsonValue::U64(v) => v.ser_json(d, s),
+            JsonValue::I64(v) => v.ser_json(d, s),
+            JsonValue::F64(v) => {
+                // json has no nan or infinity, and we want floats to stay floats
+                if v.is_finite() {
+                    let start = s.out.len();
+                    v.ser_json(d, s);
+                    if !s.out[start..].contains('.') {
+                        s.out.push_str(".0");
+                    }
+                }
+                else {
+                    s.out.push_str("null");
+                }
+            }
+            JsonValue::String(v) => v.ser_json(d, s),
+            JsonValue::Array(v) => v.ser_json(d, s),
+            JsonValue::Object(v) => {
+                s.st_pre();
+                for (index, (key, value)) in v.iter().enumerate() {
+                    if index != 0 {
+                        s.conl();
+                    }
+                    s.indent(d + 1);
+                    key.ser_json(d + 1, s);
+                    s.out.push(':');
+                    value.ser_json(d + 1, s);
+                }
+                s.st_post(d);
+            }
+        }
+    }
+}
+
+impl DeJson for JsonValue {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        let value = match s.tok {
+            DeJsonTok::Null => JsonValue::Null,
+            DeJsonTok::Bool(v) => JsonValue::Bool(v),
+            DeJsonTok::U64(v) => JsonValue::U64(v),
+            DeJsonTok::I64(v) => JsonValue::I64(v),
+            DeJsonTok::F64(v) => JsonValue::F64(v),
+            DeJsonTok::Str => JsonValue::String(s.as_string() ?),
+            DeJsonTok::BlockOpen => {
+                return Ok(JsonValue::Array(DeJson::de_json(s, i) ?))
+            }
+            DeJsonTok::CurlyOpen => {
+                s.curly_open(i) ?;
+                let mut object = Vec::new();
+                while s.next_str().is_some() {
+                    let key = s.as_string() ?;
+                    s.next_colon(i) ?;
+                    object.push((key, DeJson::de_json(s, i) ?));
+                    s.eat_comma_curly(i) ?;
+                }
+                s.curly_close(i) ?;
+                return Ok(JsonValue::Object(object))
+            }
+            _ => return Err(s.err_token("value"))
+        };
+        s.next_tok(i) ?;
+        Ok(value)
+    }
+}
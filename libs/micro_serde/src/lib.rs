@@ -7,4 +7,10 @@ mod serde_json;
 pub use crate::serde_json::*;
 
 mod serde_ron;
-pub use crate::serde_ron::*;
\ No newline at end of file
+pub use crate::serde_ron::*;
+
+mod json_value;
+pub use crate::json_value::*;
+
+mod json_pull;
+pub use crate::json_pull::*;
\ No newline at end of file
@@ -33,6 +33,8 @@ pub trait DeBin:Sized {
 
 pub struct DeBinErr{
     pub msg: String,
+    /// The path to the field that failed, for types with a `#[bin_version]`
+    pub field: Option<String>,
     pub o: usize,
     pub l: usize,
     pub s: usize
@@ -41,6 +43,9 @@ pub struct DeBinErr{
 impl std::fmt::Display for DeBinErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Error deserializing {} ", self.msg)?;
+        if let Some(field) = &self.field {
+            write!(f, "in field {} ", field)?
+        }
         if self.l != 0 {
             write!(f, "while trying to read {} bytes ", self.l)?
         }
@@ -54,6 +59,64 @@ impl std::fmt::Debug for DeBinErr {
     }
 }
 
+impl DeBinErr {
+    /// Puts `name` in front of the field path, errors pass through every versioned type they are in
+    pub fn in_field(mut self, name: &str) -> Self {
+        self.field = Some(match self.field {
+            Some(inner) => format!("{}.{}", name, inner),
+            None => name.to_string()
+        });
+        self
+    }
+}
+
+// Types with a #[bin_version] are written self describing, so both sides of an ipc channel
+// can add and reorder fields without breaking each other:
+// version: u32, field count: u32, then for each field id: u32, length: u32 and the data.
+// Enum values start with the id of the variant, followed by its fields the same way.
+// Readers skip fields they don't know and default the ones that aren't there.
+
+/// Writes the header of a versioned struct or enum variant
+pub fn ser_bin_header(s: &mut Vec<u8>, version: u32, fields: u32) {
+    version.ser_bin(s);
+    fields.ser_bin(s);
+}
+
+/// Writes one field of a versioned struct with its id and length
+pub fn ser_bin_field<T: SerBin + ?Sized>(s: &mut Vec<u8>, id: u32, value: &T) {
+    id.ser_bin(s);
+    let len_pos = s.len();
+    0u32.ser_bin(s);
+    value.ser_bin(s);
+    let len = (s.len() - len_pos - 4) as u32;
+    s[len_pos..len_pos + 4].copy_from_slice(&len.to_le_bytes());
+}
+
+/// Reads the header of a versioned struct or enum variant, returns the version and the field count
+pub fn de_bin_header(o: &mut usize, d: &[u8]) -> Result<(u32, u32), DeBinErr> {
+    let version = u32::de_bin(o, d)?;
+    let fields = u32::de_bin(o, d)?;
+    Ok((version, fields))
+}
+
+/// Reads the id of the next field and returns it with the offset where the field ends
+pub fn de_bin_field_header(o: &mut usize, d: &[u8]) -> Result<(u32, usize), DeBinErr> {
+    let id = u32::de_bin(o, d)?;
+    let len = u32::de_bin(o, d)? as usize;
+    if *o + len > d.len() {
+        return Err(DeBinErr{field:None, o:*o, l:len, s:d.len(), msg:"field".to_string()})
+    }
+    Ok((id, *o + len))
+}
+
+/// Reads a field that ends at `end`. The value can't read past the field, and whatever
+/// it doesn't read, like fields a newer writer appended to a tuple, is skipped.
+pub fn de_bin_field<T: DeBin>(o: &mut usize, d: &[u8], end: usize, name: &str) -> Result<T, DeBinErr> {
+    let value = T::de_bin(o, &d[..end]).map_err( | e | e.in_field(name))?;
+    *o = end;
+    Ok(value)
+}
+
 macro_rules! impl_ser_de_bin_for {
     ($ty:ident) => {
         impl SerBin for $ty {
@@ -66,7 +129,7 @@ macro_rules! impl_ser_de_bin_for {
             fn de_bin(o:&mut usize, d:&[u8]) -> Result<$ty, DeBinErr> {
                 let l = std::mem::size_of::<$ty>();
                 if *o + l > d.len(){
-                    return Err(DeBinErr{o:*o, l, s:d.len(), msg:format!("{}", stringify!($ty))})
+                    return Err(DeBinErr{field:None, o:*o, l, s:d.len(), msg:format!("{}", stringify!($ty))})
                 }
                 let ret = $ty::from_le_bytes(d[*o..*o+l].try_into().unwrap());
                 *o += l;
@@ -95,7 +158,7 @@ impl DeBin for usize {
     fn de_bin(o:&mut usize, d:&[u8]) -> Result<usize, DeBinErr> {
         let l = std::mem::size_of::<u64>();
         if *o + l > d.len(){
-            return Err(DeBinErr{o:*o, l, s:d.len(), msg:"usize".to_string()})
+            return Err(DeBinErr{field:None, o:*o, l, s:d.len(), msg:"usize".to_string()})
         }
         let ret = u64::from_le_bytes(d[*o..*o+l].try_into().unwrap()) as usize;
         *o += l;
@@ -106,7 +169,7 @@ impl DeBin for usize {
 impl DeBin for u8 {
     fn de_bin(o:&mut usize, d:&[u8]) -> Result<u8,DeBinErr> {
         if *o + 1 > d.len(){
-            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:"u8".to_string()})
+            return Err(DeBinErr{field:None, o:*o, l:1, s:d.len(), msg:"u8".to_string()})
         } 
         let m = d[*o];
         *o += 1;
@@ -129,7 +192,7 @@ impl SerBin for bool {
 impl DeBin for bool {
     fn de_bin(o:&mut usize, d:&[u8]) -> Result<bool, DeBinErr> {
         if *o + 1 > d.len(){
-            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:"bool".to_string()})
+            return Err(DeBinErr{field:None, o:*o, l:1, s:d.len(), msg:"bool".to_string()})
         } 
         let m = d[*o];
         *o += 1;
@@ -149,7 +212,7 @@ impl DeBin for String {
     fn de_bin(o:&mut usize, d:&[u8])->Result<String, DeBinErr> {
         let len:u64 = DeBin::de_bin(o,d)?;
         if *o + (len as usize) > d.len(){
-            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:"String".to_string()})
+            return Err(DeBinErr{field:None, o:*o, l:1, s:d.len(), msg:"String".to_string()})
         } 
         let r = std::str::from_utf8(&d[*o..(*o+(len as usize))]).unwrap().to_string();
         *o += len as usize;
@@ -193,14 +256,14 @@ impl<T> SerBin for Option<T> where T: SerBin {
 impl<T> DeBin for Option<T> where T:DeBin{
     fn de_bin(o:&mut usize, d:&[u8])->Result<Option<T>, DeBinErr> {
         if *o + 1 > d.len(){
-            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:"Option<T>".to_string()})
+            return Err(DeBinErr{field:None, o:*o, l:1, s:d.len(), msg:"Option<T>".to_string()})
         } 
         let m = d[*o];
         *o += 1;
         Ok(match m {
             0 => None,
             1 => Some(DeBin::de_bin(o,d)?),
-            _ => return Err(DeBinErr{o:*o, l:0, s:d.len(), msg:"Option<T>".to_string()}),
+            _ => return Err(DeBinErr{field:None, o:*o, l:0, s:d.len(), msg:"Option<T>".to_string()}),
         })
     }
 }
@@ -223,14 +286,14 @@ impl<T, E> SerBin for Result<T, E> where T: SerBin, E: SerBin {
 impl<T, E> DeBin for Result<T, E> where T: DeBin, E: DeBin {
     fn de_bin(o: &mut usize, d: &[u8]) -> Result<Self, DeBinErr> {
         if *o + 1 > d.len() {
-            return Err(DeBinErr{o:*o, l:1, s:d.len(), msg:"Result<T, E>".to_string()});
+            return Err(DeBinErr{field:None, o:*o, l:1, s:d.len(), msg:"Result<T, E>".to_string()});
         }
         let m = d[*o];
         *o += 1;
         Ok(match m {
             0 => Ok(T::de_bin(o, d)?),
             1 => Err(E::de_bin(o, d)?),
-            _ => return Err(DeBinErr{o:*o, l:0, s:d.len(), msg:"Result<T, E>".to_string()}),
+            _ => return Err(DeBinErr{field:None, o:*o, l:0, s:d.len(), msg:"Result<T, E>".to_string()}),
         })
     }
 }
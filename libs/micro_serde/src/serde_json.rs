@@ -40,6 +40,21 @@ impl SerJsonState {
         self.out.push('}');
     }
     
+    /// Writes the fields of `value`, which has to serialize to an object, into the object being
+    /// written. Used for `#[flatten]`, `first` tracks whether a comma is needed
+    pub fn flatten<T: SerJson + ?Sized>(&mut self, d: usize, value: &T, first: &mut bool) {
+        let mut inner = SerJsonState {out: String::new()};
+        value.ser_json(d, &mut inner);
+        if let Some(fields) = inner.out.strip_prefix('{').and_then( | v | v.strip_suffix('}')) {
+            if !fields.is_empty() {
+                if !*first {
+                    self.conl();
+                }
+                *first = false;
+                self.out.push_str(fields);
+            }
+        }
+    }
 }
 
 pub trait SerJson {
@@ -323,6 +338,102 @@ impl DeJsonState {
         Err(self.err_token("string"))
     }
     
+    /// Copies the value at the current token to `out` as json and moves past it, for when
+    /// we don't know yet what to deserialize it as
+    pub fn capture_value(&mut self, i: &mut Chars, out: &mut String) -> Result<(), DeJsonErr> {
+        let mut depth = 0;
+        loop {
+            match self.tok {
+                DeJsonTok::Str => {
+                    let mut s = SerJsonState {out: String::new()};
+                    self.strbuf.ser_json(0, &mut s);
+                    out.push_str(&s.out);
+                }
+                DeJsonTok::U64(_) | DeJsonTok::I64(_) | DeJsonTok::F64(_) => out.push_str(&self.numbuf),
+                DeJsonTok::Bool(value) => out.push_str(if value {"true"} else {"false"}),
+                DeJsonTok::Null => out.push_str("null"),
+                DeJsonTok::Colon if depth > 0 => out.push(':'),
+                DeJsonTok::Comma if depth > 0 => out.push(','),
+                DeJsonTok::CurlyOpen => {
+                    out.push('{');
+                    depth += 1;
+                }
+                DeJsonTok::BlockOpen => {
+                    out.push('[');
+                    depth += 1;
+                }
+                DeJsonTok::CurlyClose if depth > 0 => {
+                    out.push('}');
+                    depth -= 1;
+                }
+                DeJsonTok::BlockClose if depth > 0 => {
+                    out.push(']');
+                    depth -= 1;
+                }
+                _ => return Err(self.err_token("value"))
+            }
+            self.next_tok(i) ?;
+            if depth == 0 {
+                return Ok(())
+            }
+        }
+    }
+    
+    /// Copies the key at the current token and its value to `out` as `"key":value`, comma
+    /// separated from what's already there. Collects the fields a `#[flatten]` field reads
+    pub fn capture_field(&mut self, i: &mut Chars, out: &mut String) -> Result<(), DeJsonErr> {
+        if !out.is_empty() {
+            out.push(',');
+        }
+        self.capture_value(i, out) ?;
+        self.colon(i) ?;
+        out.push(':');
+        self.capture_value(i, out)
+    }
+    
+    /// Starts reading json that was copied out with `capture_value`
+    pub fn from_captured(text: &str) -> Result<(DeJsonState, Chars<'_>), DeJsonErr> {
+        let mut state = DeJsonState::default();
+        let mut chars = text.chars();
+        state.next(&mut chars);
+        state.next_tok(&mut chars) ?;
+        Ok((state, chars))
+    }
+    
+    /// Deserializes fields collected with `capture_field` as an object of their own
+    pub fn de_captured_fields<T: DeJson>(fields: &str) -> Result<T, DeJsonErr> {
+        T::deserialize_json(&format!("{{{}}}", fields))
+    }
+    
+    // reads the XXXX after \u, and the low half of a surrogate pair when there is one
+    fn unicode_escape(&mut self, i: &mut Chars) -> Result<char, DeJsonErr> {
+        let high = self.hex4(i) ?;
+        if (0xD800..0xDC00).contains(&high) {
+            if self.cur == '\\' {
+                self.next(i);
+                if self.cur == 'u' {
+                    let low = self.hex4(i) ?;
+                    if (0xDC00..0xE000).contains(&low) {
+                        let value = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                        return char::from_u32(value).ok_or_else( || self.err_parse("unicode escape"))
+                    }
+                }
+            }
+            return Err(self.err_parse("unicode escape"))
+        }
+        char::from_u32(high).ok_or_else( || self.err_parse("unicode escape"))
+    }
+    
+    fn hex4(&mut self, i: &mut Chars) -> Result<u32, DeJsonErr> {
+        let mut value = 0;
+        for _ in 0..4 {
+            self.next(i);
+            value = value * 16 + self.cur.to_digit(16).ok_or_else( || self.err_parse("unicode escape")) ?;
+        }
+        self.next(i);
+        Ok(value)
+    }
+    
     pub fn next_tok(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
         while self.cur == '\n' || self.cur == '\r' || self.cur == '\t' || self.cur == ' ' {
             self.next(i);
@@ -376,13 +487,30 @@ impl DeJsonState {
                     self.numbuf.push(self.cur);
                     self.next(i);
                 }
+                let mut is_float = false;
                 if self.cur == '.' {
+                    is_float = true;
+                    self.numbuf.push(self.cur);
+                    self.next(i);
+                    while self.cur >= '0' && self.cur <= '9' {
+                        self.numbuf.push(self.cur);
+                        self.next(i);
+                    }
+                }
+                if self.cur == 'e' || self.cur == 'E' {
+                    is_float = true;
                     self.numbuf.push(self.cur);
                     self.next(i);
+                    if self.cur == '-' || self.cur == '+' {
+                        self.numbuf.push(self.cur);
+                        self.next(i);
+                    }
                     while self.cur >= '0' && self.cur <= '9' {
                         self.numbuf.push(self.cur);
                         self.next(i);
                     }
+                }
+                if is_float {
                     if let Ok(num) = self.numbuf.parse() {
                         self.tok = DeJsonTok::F64(num);
                         Ok(())
@@ -444,6 +572,13 @@ impl DeJsonState {
                             'r'=>self.strbuf.push('\r'),
                             't'=>self.strbuf.push('\t'),
                             '0'=>self.strbuf.push('\0'),
+                            'b'=>self.strbuf.push('\u{8}'),
+                            'f'=>self.strbuf.push('\u{c}'),
+                            'u'=>{
+                                let c = self.unicode_escape(i) ?;
+                                self.strbuf.push(c);
+                                continue;
+                            }
                             '\0'=>{
                                 return Err(self.err_parse("string"));
                             },
@@ -762,15 +897,14 @@ impl<K, V> SerJson for HashMap<K, V> where K: SerJson,
 V: SerJson {
     fn ser_json(&self, d: usize, s: &mut SerJsonState) {
         s.out.push('{');
-        let last = self.len() - 1;
         for (index, (k, v)) in self.iter().enumerate() {
+            if index != 0 {
+                s.conl();
+            }
             s.indent(d + 1);
             k.ser_json(d + 1, s);
             s.out.push(':');
             v.ser_json(d + 1, s);
-            if index != last{
-                s.conl();
-            }
         }
         s.indent(d);
         s.out.push('}');
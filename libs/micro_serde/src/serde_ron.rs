@@ -32,6 +32,15 @@ impl SerRonState {
         self.out.push(')');
     }
     
+    /// Writes the fields of `value`, which has to serialize to a struct, into the struct being
+    /// written at depth `d`. Used for `#[flatten]`
+    pub fn flatten<T: SerRon + ?Sized>(&mut self, d: usize, value: &T) {
+        let mut inner = SerRonState {out: String::new()};
+        value.ser_ron(d, &mut inner);
+        if let Some(fields) = inner.out.strip_prefix("(\n").and_then( | v | v.strip_suffix(')')) {
+            self.out.push_str(fields.trim_end_matches(' '));
+        }
+    }
 }
 
 pub trait SerRon {
@@ -349,6 +358,78 @@ impl DeRonState {
         Err(self.err_token("string"))
     }
     
+    /// Copies the value at the current token to `out` as ron and moves past it, for when
+    /// we don't know yet what to deserialize it as
+    pub fn capture_value(&mut self, i: &mut Chars, out: &mut String) -> Result<(), DeRonErr> {
+        let mut depth = 0;
+        loop {
+            let mut is_ident = false;
+            match self.tok {
+                DeRonTok::Ident => {
+                    out.push_str(&self.identbuf);
+                    is_ident = true;
+                }
+                DeRonTok::Str => {
+                    let mut s = SerRonState {out: String::new()};
+                    self.strbuf.ser_ron(0, &mut s);
+                    out.push_str(&s.out);
+                }
+                DeRonTok::U64(_) | DeRonTok::I64(_) | DeRonTok::F64(_) => out.push_str(&self.numbuf),
+                DeRonTok::Bool(value) => out.push_str(if value {"true"} else {"false"}),
+                DeRonTok::Char(value) => {
+                    out.push('\'');
+                    if value == '\'' || value == '\\' {
+                        out.push('\\');
+                    }
+                    out.push(value);
+                    out.push('\'');
+                }
+                DeRonTok::Colon if depth > 0 => out.push(':'),
+                DeRonTok::Comma if depth > 0 => out.push(','),
+                DeRonTok::ParenOpen | DeRonTok::BlockOpen | DeRonTok::CurlyOpen => {
+                    out.push(match self.tok {DeRonTok::ParenOpen => '(', DeRonTok::BlockOpen => '[', _ => '{'});
+                    depth += 1;
+                }
+                DeRonTok::ParenClose | DeRonTok::BlockClose | DeRonTok::CurlyClose if depth > 0 => {
+                    out.push(match self.tok {DeRonTok::ParenClose => ')', DeRonTok::BlockClose => ']', _ => '}'});
+                    depth -= 1;
+                }
+                _ => return Err(self.err_token("value"))
+            }
+            self.next_tok(i) ?;
+            // an ident followed by ( is an enum variant or a named struct
+            if depth == 0 && !(is_ident && self.tok == DeRonTok::ParenOpen) {
+                return Ok(())
+            }
+        }
+    }
+    
+    /// Copies the ident at the current token and its value to `out` as `key:value`, comma
+    /// separated from what's already there. Collects the fields a `#[flatten]` field reads
+    pub fn capture_field(&mut self, i: &mut Chars, out: &mut String) -> Result<(), DeRonErr> {
+        if !out.is_empty() {
+            out.push(',');
+        }
+        out.push_str(&self.identbuf);
+        self.next_colon(i) ?;
+        out.push(':');
+        self.capture_value(i, out)
+    }
+    
+    /// Starts reading ron that was copied out with `capture_value`
+    pub fn from_captured(text: &str) -> Result<(DeRonState, Chars<'_>), DeRonErr> {
+        let mut state = DeRonState::default();
+        let mut chars = text.chars();
+        state.next(&mut chars);
+        state.next_tok(&mut chars) ?;
+        Ok((state, chars))
+    }
+    
+    /// Deserializes fields collected with `capture_field` as a struct of their own
+    pub fn de_captured_fields<T: DeRon>(fields: &str) -> Result<T, DeRonErr> {
+        T::deserialize_ron(&format!("({})", fields))
+    }
+    
     pub fn next_tok(&mut self, i: &mut Chars) -> Result<(), DeRonErr> {
         loop {
             while self.cur == '\n' || self.cur == '\r' || self.cur == '\t' || self.cur == ' ' {
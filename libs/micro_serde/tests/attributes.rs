@@ -0,0 +1,126 @@
+use makepad_micro_serde::*;
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, Debug, PartialEq, Default)]
+struct Meta {
+    version: u32,
+    author: Option<String>,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, Debug, PartialEq)]
+struct Document {
+    #[rename = "docTitle"]
+    title: String,
+    #[skip]
+    cached_len: usize,
+    #[default(4)]
+    tab_width: u32,
+    #[default]
+    tags: Vec<String>,
+    notes: Option<String>,
+    #[flatten]
+    meta: Meta,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, Debug, PartialEq)]
+#[tag = "type"]
+enum Part {
+    Text {text: String},
+    #[rename = "image_url"]
+    Image {url: String, detail: Option<String>},
+    Meta(Meta),
+    Empty,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, Debug, PartialEq)]
+#[tag = "t"]
+#[content = "c"]
+enum Message {
+    Move(i32, i32),
+    Say {text: String},
+    Quit,
+}
+
+#[derive(SerJson, DeJson, Debug, PartialEq)]
+struct Underscored {
+    _type: String,
+}
+
+fn document() -> Document {
+    Document {
+        title: "notes".to_string(),
+        cached_len: 0,
+        tab_width: 2,
+        tags: vec!["a".to_string()],
+        notes: None,
+        meta: Meta {version: 3, author: Some("me".to_string())},
+    }
+}
+
+#[test]
+fn json_field_attributes() {
+    let json = document().serialize_json();
+    assert_eq!(json, r#"{"docTitle":"notes","tab_width":2,"tags":["a"],"version":3,"author":"me"}"#);
+    assert_eq!(Document::deserialize_json(&json).unwrap(), document());
+
+    let doc = Document::deserialize_json(r#"{"version":1,"docTitle":"x"}"#).unwrap();
+    assert_eq!(doc.tab_width, 4);
+    assert!(doc.tags.is_empty());
+    assert_eq!(doc.notes, None);
+    assert_eq!(doc.meta, Meta {version: 1, author: None});
+    assert!(Document::deserialize_json(r#"{"version":1}"#).is_err());
+}
+
+#[test]
+fn ron_field_attributes() {
+    let ron = document().serialize_ron();
+    assert_eq!(Document::deserialize_ron(&ron).unwrap(), document());
+    let doc = Document::deserialize_ron(r#"(docTitle: "x", version: 1)"#).unwrap();
+    assert_eq!(doc.tab_width, 4);
+    assert_eq!(doc.meta.version, 1);
+}
+
+#[test]
+fn bin_skips_fields() {
+    let mut doc = document();
+    doc.cached_len = 10;
+    let bin = doc.serialize_bin();
+    let back = Document::deserialize_bin(&bin).unwrap();
+    assert_eq!(back.cached_len, 0);
+    assert_eq!(back.meta, doc.meta);
+}
+
+#[test]
+fn internally_tagged_enum() {
+    let parts = vec![
+        Part::Text {text: "hi".to_string()},
+        Part::Image {url: "a.png".to_string(), detail: None},
+        Part::Meta(Meta {version: 1, author: None}),
+        Part::Empty,
+    ];
+    let json = parts.serialize_json();
+    assert_eq!(json, r#"[{"type":"Text","text":"hi"},{"type":"image_url","url":"a.png"},{"type":"Meta","version":1},{"type":"Empty"}]"#);
+    assert_eq!(Vec::<Part>::deserialize_json(&json).unwrap(), parts);
+    assert_eq!(Vec::<Part>::deserialize_ron(&parts.serialize_ron()).unwrap(), parts);
+    assert_eq!(Vec::<Part>::deserialize_bin(&parts.serialize_bin()).unwrap(), parts);
+    // the tag doesn't have to come first
+    assert_eq!(Part::deserialize_json(r#"{"text":"x","type":"Text"}"#).unwrap(), Part::Text {text: "x".to_string()});
+    assert!(Part::deserialize_json(r#"{"text":"x"}"#).is_err());
+}
+
+#[test]
+fn adjacently_tagged_enum() {
+    let messages = vec![Message::Move(1, -2), Message::Say {text: "hello".to_string()}, Message::Quit];
+    let json = messages.serialize_json();
+    assert_eq!(json, r#"[{"t":"Move","c":[1,-2]},{"t":"Say","c":{"text":"hello"}},{"t":"Quit"}]"#);
+    assert_eq!(Vec::<Message>::deserialize_json(&json).unwrap(), messages);
+    assert_eq!(Vec::<Message>::deserialize_ron(&messages.serialize_ron()).unwrap(), messages);
+    assert_eq!(Message::deserialize_json(r#"{"c":[3,4],"t":"Move"}"#).unwrap(), Message::Move(3, 4));
+}
+
+#[test]
+fn json_underscore_fields() {
+    // structs are written with their field names, but a leading _ is skipped when reading them
+    let value = Underscored {_type: "text".to_string()};
+    assert_eq!(value.serialize_json(), r#"{"_type":"text"}"#);
+    assert_eq!(Underscored::deserialize_json(r#"{"type":"text"}"#).unwrap(), value);
+}
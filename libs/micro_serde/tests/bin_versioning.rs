@@ -0,0 +1,84 @@
+use makepad_micro_serde::*;
+
+mod old {
+    use makepad_micro_serde::*;
+
+    #[derive(SerBin, DeBin, Debug, PartialEq, Default)]
+    #[bin_version = 1]
+    pub struct LogItem {
+        pub file: String,
+        pub line: u32,
+    }
+
+    #[derive(SerBin, DeBin, Debug, PartialEq)]
+    #[bin_version = 1]
+    pub enum Message {
+        Log(LogItem),
+        Ping,
+    }
+}
+
+mod new {
+    use makepad_micro_serde::*;
+
+    // fields reordered, one added and one renamed with its old id kept
+    #[derive(SerBin, DeBin, Debug, PartialEq)]
+    #[bin_version = 2]
+    pub struct LogItem {
+        #[default(7)]
+        pub column: u32,
+        #[bin_id = 400234023]
+        pub line_start: u32,
+        pub file: String,
+        #[skip]
+        pub cached: bool,
+    }
+
+    impl Default for LogItem {
+        fn default() -> Self {
+            Self {column: 7, line_start: 0, file: String::new(), cached: false}
+        }
+    }
+
+    #[derive(SerBin, DeBin, Debug, PartialEq)]
+    #[bin_version = 2]
+    pub enum Message {
+        Ping,
+        Log(LogItem, Option<String>),
+        Quit {code: i32},
+    }
+}
+
+#[test]
+fn versioned_round_trip() {
+    let item = new::LogItem {column: 1, line_start: 2, file: "a.rs".to_string(), cached: true};
+    let back = new::LogItem::deserialize_bin(&item.serialize_bin()).unwrap();
+    assert_eq!(back, new::LogItem {cached: false, ..item});
+    let messages = vec![new::Message::Ping, new::Message::Quit {code: -1}];
+    assert_eq!(Vec::<new::Message>::deserialize_bin(&messages.serialize_bin()).unwrap(), messages);
+}
+
+#[test]
+fn old_data_in_new_reader() {
+    let old = old::Message::Log(old::LogItem {file: "a.rs".to_string(), line: 5});
+    let new = new::Message::deserialize_bin(&old.serialize_bin()).unwrap();
+    assert_eq!(new, new::Message::Log(new::LogItem {column: 7, line_start: 5, file: "a.rs".to_string(), cached: false}, None));
+}
+
+#[test]
+fn new_data_in_old_reader() {
+    let new = vec![new::Message::Log(new::LogItem {column: 3, line_start: 4, file: "b.rs".to_string(), cached: false}, Some("x".to_string())), new::Message::Ping];
+    let old = Vec::<old::Message>::deserialize_bin(&new.serialize_bin()).unwrap();
+    assert_eq!(old, vec![old::Message::Log(old::LogItem {file: "b.rs".to_string(), line: 4}), old::Message::Ping]);
+    assert!(old::Message::deserialize_bin(&new::Message::Quit {code: 0}.serialize_bin()).is_err());
+}
+
+#[test]
+fn errors_name_the_field() {
+    let mut bin = new::Message::Log(new::LogItem {column: 3, line_start: 4, file: "b.rs".to_string(), cached: false}, None).serialize_bin();
+    // cut the string in the file field short
+    let at = bin.windows(4).position( | w | w == b"b.rs").unwrap();
+    bin[at - 8] = 40;
+    let err = new::Message::deserialize_bin(&bin).unwrap_err();
+    assert_eq!(err.field.as_deref(), Some("Log.0.file"));
+}
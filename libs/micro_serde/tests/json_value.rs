@@ -0,0 +1,82 @@
+use makepad_micro_serde::*;
+
+#[derive(DeJson, Debug, PartialEq)]
+struct Item {
+    id: u32,
+    name: String,
+}
+
+#[test]
+fn value_indexing_and_pointers() {
+    let value = JsonValue::parse(r#"{"choices":[{"delta":{"content":"hi é😀"}}],"n":-3,"t":1.5e2,"a/b":true}"#).unwrap();
+    assert_eq!(value["choices"][0]["delta"]["content"].as_str(), Some("hi é😀"));
+    assert_eq!(value.pointer("/choices/0/delta/content"), Some(&JsonValue::String("hi é😀".to_string())));
+    assert_eq!(value.pointer("/a~1b").and_then( | v | v.as_bool()), Some(true));
+    assert_eq!(value["n"].as_i64(), Some(-3));
+    assert_eq!(value["t"].as_f64(), Some(150.0));
+    assert!(value["missing"][3].is_null());
+    assert_eq!(value.pointer("/choices/01"), None);
+}
+
+#[test]
+fn value_round_trip() {
+    let mut value = JsonValue::object();
+    value["model"] = "m".into();
+    value["stream"] = true.into();
+    value["temperature"] = 1.0.into();
+    value["messages"] = JsonValue::Array(vec![JsonValue::Null, 2u32.into()]);
+    let json = value.serialize_json();
+    assert_eq!(json, r#"{"model":"m","stream":true,"temperature":1.0,"messages":[null,2]}"#);
+    assert_eq!(JsonValue::parse(&json).unwrap(), value);
+}
+
+#[test]
+fn pull_parser_events() {
+    let events: Result<Vec<_>, _> = JsonPullParser::new(r#"{"a":[1,{"b":null}],"c":"x"}"#).collect();
+    assert_eq!(events.unwrap(), vec![
+        JsonEvent::ObjectStart,
+        JsonEvent::Key("a".to_string()),
+        JsonEvent::ArrayStart,
+        JsonEvent::U64(1),
+        JsonEvent::ObjectStart,
+        JsonEvent::Key("b".to_string()),
+        JsonEvent::Null,
+        JsonEvent::ObjectEnd,
+        JsonEvent::ArrayEnd,
+        JsonEvent::Key("c".to_string()),
+        JsonEvent::Str("x".to_string()),
+        JsonEvent::ObjectEnd,
+    ]);
+    assert!(JsonPullParser::new(r#"{"a" 1}"#).any( | e | e.is_err()));
+}
+
+#[test]
+fn pull_parser_read_and_skip() {
+    let mut parser = JsonPullParser::new(r#"{"meta":{"big":[1,2,3]},"items":[{"id":1,"name":"a"},{"id":2,"name":"b"}]}"#);
+    let mut items = Vec::new();
+    while let Some(event) = parser.next_event().unwrap() {
+        match event {
+            JsonEvent::Key(key) if key == "meta" => parser.skip_value().unwrap(),
+            JsonEvent::ArrayStart => while let Ok(item) = parser.read::<Item>() {
+                items.push(item);
+            },
+            _ => ()
+        }
+    }
+    assert_eq!(items, vec![Item {id: 1, name: "a".to_string()}, Item {id: 2, name: "b".to_string()}]);
+}
+
+#[test]
+fn chunk_buffer() {
+    let mut buffer = JsonChunkBuffer::new();
+    buffer.push(r#"{"a":"}"#);
+    assert_eq!(buffer.next_text(), None);
+    buffer.push("\"}\n[1,");
+    assert_eq!(buffer.next_text().as_deref(), Some(r#"{"a":"}"}"#));
+    assert_eq!(buffer.next_text(), None);
+    buffer.push("2] 3 \"s\"");
+    assert_eq!(buffer.next_value::<Vec<u32>>().unwrap().unwrap(), vec![1, 2]);
+    assert_eq!(buffer.next_text().as_deref(), Some("3"));
+    assert_eq!(buffer.next_text().as_deref(), Some("\"s\""));
+    assert_eq!(buffer.finish(), None);
+}
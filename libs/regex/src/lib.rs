@@ -0,0 +1,8 @@
+mod parse;
+mod regex;
+mod search;
+
+pub use crate::{
+    regex::{Captures, FindIter, Match, Regex, RegexError},
+    search::SearchOptions,
+};
@@ -0,0 +1,339 @@
+use crate::regex::RegexError;
+
+#[derive(Clone, Debug)]
+pub enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    LineStart,
+    LineEnd,
+    WordBoundary,
+    NotWordBoundary,
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct Class {
+    pub negated: bool,
+    pub items: Vec<ClassItem>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl ClassItem {
+    pub fn matches(self, char: char, case_insensitive: bool) -> bool {
+        match self {
+            ClassItem::Range(start, end) => {
+                if (start..=end).contains(&char) {
+                    return true;
+                }
+                case_insensitive
+                    && (char.to_lowercase().any(|char| (start..=end).contains(&char))
+                        || char.to_uppercase().any(|char| (start..=end).contains(&char)))
+            }
+            ClassItem::Digit(yes) => char.is_ascii_digit() == yes,
+            ClassItem::Word(yes) => is_word_char(char) == yes,
+            ClassItem::Space(yes) => char.is_whitespace() == yes,
+        }
+    }
+}
+
+impl Class {
+    pub fn matches(&self, char: char, case_insensitive: bool) -> bool {
+        self.items
+            .iter()
+            .any(|item| item.matches(char, case_insensitive))
+            != self.negated
+    }
+}
+
+pub fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+/// Parses a pattern into a tree, returns the tree and the number of capture groups
+/// (not counting the whole match)
+pub fn parse(pattern: &str) -> Result<(Node, usize), RegexError> {
+    let mut parser = Parser {
+        chars: pattern.char_indices().collect(),
+        index: 0,
+        group_count: 0,
+        len: pattern.len(),
+    };
+    let node = parser.parse_alt()?;
+    if let Some(&(offset, _)) = parser.chars.get(parser.index) {
+        // the only way to stop early is an unopened )
+        return Err(RegexError::new("unmatched )", offset));
+    }
+    Ok((node, parser.group_count))
+}
+
+struct Parser {
+    chars: Vec<(usize, char)>,
+    index: usize,
+    group_count: usize,
+    len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).map(|&(_, char)| char)
+    }
+
+    fn offset(&self) -> usize {
+        self.chars
+            .get(self.index)
+            .map_or(self.len, |&(offset, _)| offset)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.index += 1;
+        Some(char)
+    }
+
+    fn eat(&mut self, char: char) -> bool {
+        if self.peek() == Some(char) {
+            self.index += 1;
+            return true;
+        }
+        false
+    }
+
+    fn parse_alt(&mut self) -> Result<Node, RegexError> {
+        let mut alts = vec![self.parse_concat()?];
+        while self.eat('|') {
+            alts.push(self.parse_concat()?);
+        }
+        Ok(if alts.len() == 1 {
+            alts.pop().unwrap()
+        } else {
+            Node::Alt(alts)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = Vec::new();
+        while let Some(char) = self.peek() {
+            if char == '|' || char == ')' {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            nodes.push(self.parse_repeat(atom)?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap(),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn parse_repeat(&mut self, atom: Node) -> Result<Node, RegexError> {
+        let mut node = atom;
+        loop {
+            let offset = self.offset();
+            let (min, max) = match self.peek() {
+                Some('{') => match self.parse_counts()? {
+                    Some(counts) => counts,
+                    None => return Ok(node),
+                },
+                Some(char @ ('*' | '+' | '?')) => {
+                    self.index += 1;
+                    match char {
+                        '*' => (0, None),
+                        '+' => (1, None),
+                        _ => (0, Some(1)),
+                    }
+                }
+                _ => return Ok(node),
+            };
+            if matches!(
+                node,
+                Node::Empty
+                    | Node::LineStart
+                    | Node::LineEnd
+                    | Node::WordBoundary
+                    | Node::NotWordBoundary
+                    | Node::Repeat { .. }
+            ) {
+                return Err(RegexError::new("nothing to repeat", offset));
+            }
+            let greedy = !self.eat('?');
+            node = Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+                greedy,
+            };
+        }
+    }
+
+    // {n}, {n,} or {n,m}. anything else is a literal {, like most engines do
+    fn parse_counts(&mut self) -> Result<Option<(usize, Option<usize>)>, RegexError> {
+        let start = self.index;
+        let offset = self.offset();
+        self.index += 1;
+        let min = self.parse_number();
+        let max = if self.eat(',') {
+            self.parse_number()
+        } else {
+            min
+        };
+        match min {
+            Some(min) if self.eat('}') => {
+                if max.is_some_and(|max| max < min) {
+                    return Err(RegexError::new("repeat counts are out of order", offset));
+                }
+                Ok(Some((min, max)))
+            }
+            _ => {
+                self.index = start;
+                Ok(None)
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<usize> {
+        let mut number = None;
+        while let Some(digit) = self.peek().and_then(|char| char.to_digit(10)) {
+            self.index += 1;
+            number = Some(number.unwrap_or(0) * 10 + digit as usize);
+        }
+        number
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, RegexError> {
+        let offset = self.offset();
+        let char = self.next().unwrap();
+        Ok(match char {
+            '(' => {
+                let capture = if self.eat('?') {
+                    if !self.eat(':') {
+                        return Err(RegexError::new("only (?: groups are supported", offset));
+                    }
+                    None
+                } else {
+                    self.group_count += 1;
+                    Some(self.group_count)
+                };
+                let node = self.parse_alt()?;
+                if !self.eat(')') {
+                    return Err(RegexError::new("unclosed (", offset));
+                }
+                Node::Group(Box::new(node), capture)
+            }
+            '[' => Node::Class(self.parse_class(offset)?),
+            '.' => Node::Any,
+            '^' => Node::LineStart,
+            '$' => Node::LineEnd,
+            '*' | '+' | '?' => return Err(RegexError::new("nothing to repeat", offset)),
+            '\\' => match self.parse_escape(offset)? {
+                Escape::Char(char) => Node::Char(char),
+                Escape::Class(item) => Node::Class(Class {
+                    negated: false,
+                    items: vec![item],
+                }),
+                Escape::WordBoundary => Node::WordBoundary,
+                Escape::NotWordBoundary => Node::NotWordBoundary,
+            },
+            char => Node::Char(char),
+        })
+    }
+
+    fn parse_escape(&mut self, offset: usize) -> Result<Escape, RegexError> {
+        let char = match self.next() {
+            Some(char) => char,
+            None => return Err(RegexError::new("pattern ends with \\", offset)),
+        };
+        Ok(match char {
+            'd' => Escape::Class(ClassItem::Digit(true)),
+            'D' => Escape::Class(ClassItem::Digit(false)),
+            'w' => Escape::Class(ClassItem::Word(true)),
+            'W' => Escape::Class(ClassItem::Word(false)),
+            's' => Escape::Class(ClassItem::Space(true)),
+            'S' => Escape::Class(ClassItem::Space(false)),
+            'b' => Escape::WordBoundary,
+            'B' => Escape::NotWordBoundary,
+            't' => Escape::Char('\t'),
+            'n' => Escape::Char('\n'),
+            'r' => Escape::Char('\r'),
+            '0' => Escape::Char('\0'),
+            char if char.is_ascii_alphanumeric() => {
+                return Err(RegexError::new(&format!("unknown escape \\{}", char), offset))
+            }
+            char => Escape::Char(char),
+        })
+    }
+
+    fn parse_class(&mut self, offset: usize) -> Result<Class, RegexError> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        // a ] right at the start is a literal
+        if self.eat(']') {
+            items.push(ClassItem::Range(']', ']'));
+        }
+        loop {
+            let char_offset = self.offset();
+            let start = match self.next() {
+                None => return Err(RegexError::new("unclosed [", offset)),
+                Some(']') => break,
+                Some('\\') => match self.parse_escape(char_offset)? {
+                    Escape::Char(char) => char,
+                    Escape::Class(item) => {
+                        items.push(item);
+                        continue;
+                    }
+                    Escape::WordBoundary => '\u{8}',
+                    Escape::NotWordBoundary => {
+                        return Err(RegexError::new("\\B in a class", char_offset))
+                    }
+                },
+                Some(char) => char,
+            };
+            // a - at the end or before ] is a literal
+            if self.peek() == Some('-')
+                && self.chars.get(self.index + 1).is_some_and(|&(_, char)| char != ']')
+            {
+                self.index += 1;
+                let end_offset = self.offset();
+                let end = match self.next() {
+                    Some('\\') => match self.parse_escape(end_offset)? {
+                        Escape::Char(char) => char,
+                        _ => return Err(RegexError::new("invalid class range", end_offset)),
+                    },
+                    Some(char) => char,
+                    None => return Err(RegexError::new("unclosed [", offset)),
+                };
+                if end < start {
+                    return Err(RegexError::new("class range is out of order", char_offset));
+                }
+                items.push(ClassItem::Range(start, end));
+            } else {
+                items.push(ClassItem::Range(start, start));
+            }
+        }
+        Ok(Class { negated, items })
+    }
+}
+
+enum Escape {
+    Char(char),
+    Class(ClassItem),
+    WordBoundary,
+    NotWordBoundary,
+}
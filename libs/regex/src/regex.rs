@@ -0,0 +1,376 @@
+use {
+    crate::parse::{self, is_word_char, Node},
+    std::fmt,
+};
+
+/// A backtracking regex, enough for searching code in an editor. It supports literals, `.`,
+/// classes like `[a-z_]` and `[^0-9]`, `\d \w \s` and their negations, `^ $ \b \B`, groups,
+/// `(?:...)`, `|`, and the greedy and lazy quantifiers `* + ? {n} {n,} {n,m}`.
+///
+/// Text is matched one line at a time, `^` and `$` are the start and end of the haystack.
+#[derive(Clone, Debug)]
+pub struct Regex {
+    node: Node,
+    group_count: usize,
+    case_insensitive: bool,
+}
+
+// patterns like (a*)*b can take exponential time on a line without a match, give up on that
+// start position instead of hanging the editor
+const MAX_STEPS: usize = 1 << 20;
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        let (node, group_count) = parse::parse(pattern)?;
+        Ok(Self {
+            node,
+            group_count,
+            case_insensitive: false,
+        })
+    }
+
+    /// A regex that matches `text` literally
+    pub fn literal(text: &str) -> Self {
+        Self {
+            node: Node::Concat(text.chars().map(Node::Char).collect()),
+            group_count: 0,
+            case_insensitive: false,
+        }
+    }
+
+    pub fn case_insensitive(self, case_insensitive: bool) -> Self {
+        Self {
+            case_insensitive,
+            ..self
+        }
+    }
+
+    /// Escapes the characters that have a meaning in a pattern
+    pub fn escape(text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for char in text.chars() {
+            if "\\.+*?()|[]{}^$".contains(char) {
+                escaped.push('\\');
+            }
+            escaped.push(char);
+        }
+        escaped
+    }
+
+    /// The number of capture groups, not counting the whole match
+    pub fn group_count(&self) -> usize {
+        self.group_count
+    }
+
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.find_at(haystack, 0).is_some()
+    }
+
+    /// The first match that starts at or after byte `start`
+    pub fn find_at(&self, haystack: &str, start: usize) -> Option<Match> {
+        self.captures_at(haystack, start)
+            .map(|captures| captures.get(0).unwrap())
+    }
+
+    pub fn captures_at<'a>(&self, haystack: &'a str, start: usize) -> Option<Captures<'a>> {
+        let mut matcher = Matcher {
+            haystack,
+            case_insensitive: self.case_insensitive,
+            slots: vec![None; (self.group_count + 1) * 2],
+            steps: 0,
+        };
+        let mut position = start;
+        loop {
+            matcher.steps = 0;
+            let mut end = None;
+            if matcher.match_node(&self.node, position, &mut |_, end_position| {
+                end = Some(end_position);
+                true
+            }) {
+                matcher.slots[0] = Some(position);
+                matcher.slots[1] = end;
+                return Some(Captures {
+                    haystack,
+                    slots: matcher.slots,
+                });
+            }
+            position += haystack[position..].chars().next()?.len_utf8();
+        }
+    }
+
+    /// All matches that don't overlap, empty matches right after another match are skipped
+    pub fn find_iter<'a, 'b>(&'a self, haystack: &'b str) -> FindIter<'a, 'b> {
+        FindIter {
+            regex: self,
+            haystack,
+            position: 0,
+            last_end: None,
+        }
+    }
+}
+
+pub struct FindIter<'a, 'b> {
+    regex: &'a Regex,
+    haystack: &'b str,
+    position: usize,
+    last_end: Option<usize>,
+}
+
+impl<'a, 'b> Iterator for FindIter<'a, 'b> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        loop {
+            if self.position > self.haystack.len() {
+                return None;
+            }
+            let found = self.regex.find_at(self.haystack, self.position)?;
+            if found.is_empty() {
+                // step over a char so we don't find the same empty match forever
+                self.position = found.end
+                    + self.haystack[found.end..]
+                        .chars()
+                        .next()
+                        .map_or(1, |char| char.len_utf8());
+                if self.last_end == Some(found.end) {
+                    continue;
+                }
+            } else {
+                self.position = found.end;
+            }
+            self.last_end = Some(found.end);
+            return Some(found);
+        }
+    }
+}
+
+/// A match as byte offsets into the haystack
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Match {
+    pub fn is_empty(self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn len(self) -> usize {
+        self.end - self.start
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Captures<'a> {
+    haystack: &'a str,
+    slots: Vec<Option<usize>>,
+}
+
+impl<'a> Captures<'a> {
+    /// Group `index`, 0 is the whole match
+    pub fn get(&self, index: usize) -> Option<Match> {
+        match (self.slots.get(index * 2)?, self.slots.get(index * 2 + 1)?) {
+            (Some(start), Some(end)) => Some(Match {
+                start: *start,
+                end: *end,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, index: usize) -> Option<&'a str> {
+        self.get(index)
+            .map(|found| &self.haystack[found.start..found.end])
+    }
+
+    /// Fills in `$1` or `${1}` with the text of a group, `$0` is the whole match and `$$` is a `$`.
+    /// Groups that didn't take part in the match are empty.
+    pub fn expand(&self, template: &str) -> String {
+        let mut out = String::new();
+        let mut rest = template;
+        while let Some(index) = rest.find('$') {
+            out.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                out.push('$');
+                rest = after;
+                continue;
+            }
+            let (digits, after) = match rest.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(close) => (&braced[..close], &braced[close + 1..]),
+                    None => ("", rest),
+                },
+                None => {
+                    let len = rest
+                        .find(|char: char| !char.is_ascii_digit())
+                        .unwrap_or(rest.len());
+                    (&rest[..len], &rest[len..])
+                }
+            };
+            match digits.parse::<usize>() {
+                Ok(group) => {
+                    out.push_str(self.text(group).unwrap_or(""));
+                    rest = after;
+                }
+                Err(_) => out.push('$'),
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegexError {
+    pub message: String,
+    /// Byte offset in the pattern
+    pub offset: usize,
+}
+
+impl RegexError {
+    pub(crate) fn new(message: &str, offset: usize) -> Self {
+        Self {
+            message: message.to_string(),
+            offset,
+        }
+    }
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for RegexError {}
+
+struct Matcher<'a> {
+    haystack: &'a str,
+    case_insensitive: bool,
+    slots: Vec<Option<usize>>,
+    steps: usize,
+}
+
+type Next<'n, 'a> = &'n mut dyn FnMut(&mut Matcher<'a>, usize) -> bool;
+
+impl<'a> Matcher<'a> {
+    fn char_at(&self, position: usize) -> Option<char> {
+        self.haystack[position..].chars().next()
+    }
+
+    fn is_word_boundary(&self, position: usize) -> bool {
+        let before = self.haystack[..position]
+            .chars()
+            .next_back()
+            .is_some_and(is_word_char);
+        let after = self.char_at(position).is_some_and(is_word_char);
+        before != after
+    }
+
+    fn chars_equal(&self, a: char, b: char) -> bool {
+        a == b || self.case_insensitive && a.to_lowercase().eq(b.to_lowercase())
+    }
+
+    // matches `node` at `position` and calls `next` with where it ended, trying the other
+    // ways `node` can match for as long as `next` fails
+    fn match_node(&mut self, node: &Node, position: usize, next: Next<'_, 'a>) -> bool {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            return false;
+        }
+        match node {
+            Node::Empty => next(self, position),
+            Node::Char(char) => match self.char_at(position) {
+                Some(found) if self.chars_equal(*char, found) => {
+                    next(self, position + found.len_utf8())
+                }
+                _ => false,
+            },
+            Node::Any => match self.char_at(position) {
+                Some(found) => next(self, position + found.len_utf8()),
+                None => false,
+            },
+            Node::Class(class) => match self.char_at(position) {
+                Some(found) if class.matches(found, self.case_insensitive) => {
+                    next(self, position + found.len_utf8())
+                }
+                _ => false,
+            },
+            Node::LineStart => position == 0 && next(self, position),
+            Node::LineEnd => position == self.haystack.len() && next(self, position),
+            Node::WordBoundary => self.is_word_boundary(position) && next(self, position),
+            Node::NotWordBoundary => !self.is_word_boundary(position) && next(self, position),
+            Node::Group(node, None) => self.match_node(node, position, next),
+            Node::Group(node, Some(index)) => {
+                let (start_slot, end_slot) = (index * 2, index * 2 + 1);
+                let old_start = self.slots[start_slot];
+                let old_end = self.slots[end_slot];
+                self.slots[start_slot] = Some(position);
+                if self.match_node(node, position, &mut |matcher, end| {
+                    let inner_end = matcher.slots[end_slot];
+                    matcher.slots[end_slot] = Some(end);
+                    if next(matcher, end) {
+                        return true;
+                    }
+                    matcher.slots[end_slot] = inner_end;
+                    false
+                }) {
+                    return true;
+                }
+                self.slots[start_slot] = old_start;
+                self.slots[end_slot] = old_end;
+                false
+            }
+            Node::Concat(nodes) => self.match_concat(nodes, position, next),
+            Node::Alt(alts) => alts
+                .iter()
+                .any(|alt| self.match_node(alt, position, &mut |matcher, end| next(matcher, end))),
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => self.match_repeat(node, *min, *max, *greedy, 0, position, next),
+        }
+    }
+
+    fn match_concat(&mut self, nodes: &[Node], position: usize, next: Next<'_, 'a>) -> bool {
+        match nodes.split_first() {
+            None => next(self, position),
+            Some((first, rest)) => self.match_node(first, position, &mut |matcher, end| {
+                matcher.match_concat(rest, end, next)
+            }),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn match_repeat(
+        &mut self,
+        node: &Node,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+        count: usize,
+        position: usize,
+        next: Next<'_, 'a>,
+    ) -> bool {
+        let done = count >= min;
+        if done && !greedy && next(self, position) {
+            return true;
+        }
+        if max.is_none_or(|max| count < max)
+            && self.match_node(node, position, &mut |matcher, end| {
+                // once the minimum is reached, an empty iteration can't make progress
+                if end == position && done {
+                    return false;
+                }
+                matcher.match_repeat(node, min, max, greedy, count + 1, end, next)
+            })
+        {
+            return true;
+        }
+        done && greedy && next(self, position)
+    }
+}
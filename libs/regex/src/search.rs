@@ -0,0 +1,25 @@
+use crate::regex::{Regex, RegexError};
+
+/// The toggles of a find bar
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// When false the pattern is plain text
+    pub regex: bool,
+}
+
+impl Regex {
+    /// Builds the regex for what was typed in a find bar
+    pub fn search(pattern: &str, options: SearchOptions) -> Result<Self, RegexError> {
+        let mut pattern = if options.regex {
+            pattern.to_string()
+        } else {
+            Regex::escape(pattern)
+        };
+        if options.whole_word {
+            pattern = format!("\\b(?:{})\\b", pattern);
+        }
+        Ok(Regex::new(&pattern)?.case_insensitive(!options.case_sensitive))
+    }
+}
@@ -0,0 +1,54 @@
+use makepad_regex::*;
+
+fn find_all(pattern: &str, haystack: &str) -> Vec<String> {
+    let regex = Regex::new(pattern).unwrap();
+    regex
+        .find_iter(haystack)
+        .map(|found| haystack[found.start..found.end].to_string())
+        .collect()
+}
+
+#[test]
+fn matches() {
+    assert_eq!(find_all("a+b", "aab ab b"), ["aab", "ab"]);
+    assert_eq!(find_all("[a-c]{2,3}", "abcd ab a"), ["abc", "ab"]);
+    assert_eq!(find_all("\\bfn\\b", "fn fnord fn"), ["fn", "fn"]);
+    assert_eq!(find_all("x|yz?", "xyyz"), ["x", "y", "yz"]);
+    assert_eq!(find_all("^\\s*//", "  // hi"), ["  //"]);
+    assert_eq!(find_all("a.*?b", "aXbaYb"), ["aXb", "aYb"]);
+    assert_eq!(find_all("a*", "baa"), ["", "aa"]);
+    assert_eq!(find_all("é+", "aéé"), ["éé"]);
+    assert!(!Regex::new("(a*)*b").unwrap().is_match(&"a".repeat(40)));
+}
+
+#[test]
+fn captures() {
+    let regex = Regex::new("(\\w+): (\\d+)?").unwrap();
+    let captures = regex.captures_at("x width: 10", 0).unwrap();
+    assert_eq!(captures.text(1), Some("width"));
+    assert_eq!(captures.expand("$2 ${1}s $$"), "10 widths $");
+    let captures = regex.captures_at("width: ", 0).unwrap();
+    assert_eq!(captures.expand("[$2]"), "[]");
+}
+
+#[test]
+fn search_options() {
+    let options = SearchOptions {
+        case_sensitive: false,
+        whole_word: true,
+        regex: false,
+    };
+    let regex = Regex::search("a.b", options).unwrap();
+    assert!(regex.is_match("x A.B y"));
+    assert!(!regex.is_match("aXb"));
+    assert!(!regex.is_match("xa.b"));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(Regex::new("ab(c").unwrap_err().offset, 2);
+    assert_eq!(Regex::new("a)").unwrap_err().offset, 1);
+    assert!(Regex::new("*a").is_err());
+    assert!(Regex::new("[z-a]").is_err());
+    assert!(Regex::new("\\q").is_err());
+}
@@ -174,6 +174,8 @@ where
     pub(crate) seen_headers:    bool,
     pub(crate) seen_trns:       bool,
     pub(crate) seen_iend:       bool,
+    // an fcTL chunk came before the IDAT chunks, the default image is the first frame of an APNG
+    pub(crate) seen_idat_fctl:  bool,
     pub(crate) current_frame:   usize
 }
 
@@ -217,6 +219,7 @@ impl<T: ZReaderTrait> PngDecoder<T> {
             seen_trns:       false,
             seen_headers:    false,
             seen_iend:       false,
+            seen_idat_fctl:  false,
             trns_bytes:      [0; 4],
             current_frame:   0
         }
@@ -317,6 +320,41 @@ impl<T: ZReaderTrait> PngDecoder<T> {
         self.frames.len() > self.current_frame
     }
 
+    /// Return the animation control chunk of an APNG
+    ///
+    /// # Returns
+    /// - `Some(actl)`: The number of frames and how often the animation plays
+    /// - `None`: The image is not animated or the headers haven't been decoded
+    pub const fn get_actl_info(&self) -> Option<ActlChunk> {
+        self.actl_info
+    }
+
+    /// Return the frame control information of the frame that was
+    /// decoded last by [`decode_into`](Self::decode_into)
+    ///
+    /// The pixels of an APNG frame are `width` x `height` and start at the beginning
+    /// of the output buffer, they have to be placed at `x_offset`,`y_offset` on the canvas
+    /// using the dispose and blend operations
+    pub fn get_frame_info(&self) -> Option<FrameInfo> {
+        self.frames.get(self.current_frame.checked_sub(1)?)?.fctl_info
+    }
+
+    /// Skip the default image of an APNG when it is not part of the animation
+    ///
+    /// Such an image has no fcTL chunk before its IDAT chunks, it is what
+    /// decoders that don't support APNG show. After this, [`decode_into`](Self::decode_into)
+    /// returns the first frame of the animation. Does nothing if the default image is the first frame.
+    pub fn skip_default_image(&mut self) -> Result<(), PngDecodeErrors> {
+        if !self.seen_headers {
+            self.decode_headers()?;
+        }
+        if self.current_frame == 0 && self.actl_info.is_some() && !self.seen_idat_fctl {
+            self.frames[0].fdat = vec![];
+            self.current_frame = 1;
+        }
+        Ok(())
+    }
+
     pub(crate) fn read_chunk_header(&mut self) -> Result<PngChunk, PngDecodeErrors> {
         // Format is length - chunk type - [data] -  crc chunk, load crc chunk now
         let chunk_length = self.stream.get_u32_be_err()? as usize;
@@ -170,7 +170,11 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                     if field.name == "abs_pos" ||
                       field.name == "margin" ||
                       field.name == "width" ||
-                      field.name == "height" {
+                      field.name == "height" ||
+                      field.name == "min_width" ||
+                      field.name == "max_width" ||
+                      field.name == "min_height" ||
+                      field.name == "max_height" {
                           return error_result(&format!("Name collision between walk splat and {}", field.name));
                       }
                 }
@@ -178,6 +182,10 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                 tb.add("        live_id!(margin)=>self.").ident(&field.name).add(".margin.apply(cx, apply_from, index, nodes),");
                 tb.add("        live_id!(width)=>self.").ident(&field.name).add(".width.apply(cx, apply_from, index, nodes),");
                 tb.add("        live_id!(height)=>self.").ident(&field.name).add(".height.apply(cx, apply_from, index, nodes),");
+                tb.add("        live_id!(min_width)=>self.").ident(&field.name).add(".min_width.apply(cx, apply_from, index, nodes),");
+                tb.add("        live_id!(max_width)=>self.").ident(&field.name).add(".max_width.apply(cx, apply_from, index, nodes),");
+                tb.add("        live_id!(min_height)=>self.").ident(&field.name).add(".min_height.apply(cx, apply_from, index, nodes),");
+                tb.add("        live_id!(max_height)=>self.").ident(&field.name).add(".max_height.apply(cx, apply_from, index, nodes),");
             }
             else if field.attrs.iter().any( | a | a.name == "layout") {
                 for field in &fields {
@@ -187,7 +195,11 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                       field.name == "padding" ||
                       field.name == "align" ||
                       field.name == "flow" ||
-                      field.name == "spacing"{
+                      field.name == "spacing" ||
+                      field.name == "line_spacing" ||
+                      field.name == "grid_columns" ||
+                      field.name == "grid_rows" ||
+                      field.name == "rtl"{
                           return error_result(&format!("Name collision between layout splat and {}", field.name));
                       }
                 }
@@ -198,6 +210,10 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                 tb.add("        live_id!(align)=>self.").ident(&field.name).add(".align.apply(cx, apply_from, index, nodes),");
                 tb.add("        live_id!(flow)=>self.").ident(&field.name).add(".flow.apply(cx, apply_from, index, nodes),");
                 tb.add("        live_id!(spacing)=>self.").ident(&field.name).add(".spacing.apply(cx, apply_from, index, nodes),");
+                tb.add("        live_id!(line_spacing)=>self.").ident(&field.name).add(".line_spacing.apply(cx, apply_from, index, nodes),");
+                tb.add("        live_id!(grid_columns)=>self.").ident(&field.name).add(".grid_columns.apply(cx, apply_from, index, nodes),");
+                tb.add("        live_id!(grid_rows)=>self.").ident(&field.name).add(".grid_rows.apply(cx, apply_from, index, nodes),");
+                tb.add("        live_id!(rtl)=>self.").ident(&field.name).add(".rtl.apply(cx, apply_from, index, nodes),");
             }
         }
         // Unknown value handling
@@ -80,14 +80,12 @@ impl<'a> StructAnalyser<'a> {
     fn analyse_field_def(&mut self, field_def: &StructFieldDef) -> Result<(), LiveError> {
         self.ty_checker().ty_check_ty_expr(&field_def.ty_expr) ?;
         // ok so. if this thing depends on structs, lets store them.
-        match field_def.ty_expr.ty.borrow().as_ref().unwrap() {
-            Ty::Struct(struct_ptr) => {
-                self.struct_def.struct_refs.borrow_mut().as_mut().unwrap().insert(*struct_ptr);
-            }
-            Ty::Array {..} => {
-                todo!();
-            }
-            _ => ()
+        let mut ty = field_def.ty_expr.ty.borrow().clone().unwrap();
+        while let Ty::Array {elem_ty, ..} = ty {
+            ty = elem_ty.as_ref().clone();
+        }
+        if let Ty::Struct(struct_ptr) = ty {
+            self.struct_def.struct_refs.borrow_mut().as_mut().unwrap().insert(struct_ptr);
         }
         Ok(())
     }
@@ -328,12 +326,12 @@ impl<'a> DrawShaderAnalyser<'a> {
             DrawShaderFieldKind::Texture {..} => {
                 let ty = self.ty_checker().ty_check_ty_expr(&decl.ty_expr) ?;
                 match ty {
-                    Ty::Texture2D | Ty::TextureOES => {}
+                    Ty::Texture2D | Ty::TextureOES | Ty::TextureCube => {}
                     _ => {
                         return Err(LiveError {
                             origin: live_error_origin!(),
                             span: decl.span.into(),
-                            message: String::from("texture must be either a texture2D or a textureOES (android)"),
+                            message: String::from("texture must be either a texture2D, a textureCube or a textureOES (android)"),
                         })
                     }
                 }
@@ -785,11 +783,19 @@ impl<'a> FnDefAnalyser<'a> {
             Stmt::For {
                 span,
                 ident,
-                ref from_expr,
-                ref to_expr,
-                ref step_expr,
+                ref shadow,
+                ref range,
                 ref block,
-            } => self.analyse_for_stmt(span, ident, from_expr, to_expr, step_expr, block),
+            } => self.analyse_for_stmt(span, ident, shadow, range, block),
+            Stmt::While {
+                span,
+                ref expr,
+                ref block,
+            } => self.analyse_while_stmt(span, Some(expr), block),
+            Stmt::Loop {
+                span,
+                ref block,
+            } => self.analyse_while_stmt(span, None, block),
             Stmt::If {
                 span,
                 ref expr,
@@ -841,11 +847,11 @@ impl<'a> FnDefAnalyser<'a> {
         &mut self,
         span: TokenSpan,
         ident: Ident,
-        from_expr: &Expr,
-        to_expr: &Expr,
-        step_expr: &Option<Expr>,
+        shadow: &Cell<Option<ScopeSymShadow >>,
+        range: &ForRange,
         block: &Block,
     ) -> Result<(), LiveError> {
+        let ForRange {from_expr, to_expr, step_expr} = range;
         
         self.ty_checker()
             .ty_check_expr_with_expected_ty(span, from_expr, &Ty::Float) ?;
@@ -899,12 +905,34 @@ impl<'a> FnDefAnalyser<'a> {
             self.dep_analyser().dep_analyse_expr(step_expr);
         }
         self.scopes.push_scope();
-        self.scopes.insert_sym(
+        shadow.set(Some(self.scopes.insert_sym(
             span,
             ident,
             Ty::Int,
             ScopeSymKind::Local,
-        );
+        )));
+        let was_inside_loop = self.is_inside_loop;
+        self.is_inside_loop = true;
+        self.analyse_block(block) ?;
+        self.is_inside_loop = was_inside_loop;
+        self.scopes.pop_scope();
+        Ok(())
+    }
+    
+    fn analyse_while_stmt(
+        &mut self,
+        span: TokenSpan,
+        expr: Option<&Expr>,
+        block: &Block,
+    ) -> Result<(), LiveError> {
+        if let Some(expr) = expr {
+            self.ty_checker()
+                .ty_check_expr_with_expected_ty(span, expr, &Ty::Bool) ?;
+            self.const_evaluator().try_const_eval_expr(expr);
+            self.const_gatherer().const_gather_expr(expr);
+            self.dep_analyser().dep_analyse_expr(expr);
+        }
+        self.scopes.push_scope();
         let was_inside_loop = self.is_inside_loop;
         self.is_inside_loop = true;
         self.analyse_block(block) ?;
@@ -1008,9 +1036,18 @@ impl<'a> FnDefAnalyser<'a> {
             }
             let expected_ty = self.ty_checker().ty_check_ty_expr(ty_expr) ?;
             if let Some(expr) = expr {
-                let actual_ty =
-                self.ty_checker()
-                    .ty_check_expr_with_expected_ty(span, expr, &expected_ty) ?;
+                let actual_ty = self.ty_checker().ty_check_array_cons_expr(expr) ?;
+                if actual_ty != expected_ty {
+                    return Err(LiveError {
+                        origin: live_error_origin!(),
+                        span:span.into(),
+                        message: format!(
+                            "can't match expected type `{}` with actual type `{}",
+                            expected_ty,
+                            actual_ty
+                        ),
+                    });
+                }
                 self.dep_analyser().dep_analyse_expr(expr);
                 actual_ty
             } else {
@@ -1018,7 +1055,7 @@ impl<'a> FnDefAnalyser<'a> {
             }
             
         } else if let Some(expr) = expr {
-            let ty = self.ty_checker().ty_check_expr(expr) ?;
+            let ty = self.ty_checker().ty_check_array_cons_expr(expr) ?;
             if ty == Ty::Void {
                 return Err(LiveError {
                     origin: live_error_origin!(),
@@ -1037,6 +1074,15 @@ impl<'a> FnDefAnalyser<'a> {
                 message: format!("can't infer type of variable `{}`", ident),
             });
         });
+        if let Some(Ty::Array {..}) = ty.borrow().as_ref() {
+            if !matches!(expr.as_ref().unwrap().kind, ExprKind::ArrayCons {..}) {
+                return Err(LiveError {
+                    origin: live_error_origin!(),
+                    span:span.into(),
+                    message: format!("array variable `{}` must be initialised with an array constructor", ident),
+                });
+            }
+        }
         let new_shadow = self.scopes.insert_sym(
             span,
             ident,
@@ -71,6 +71,10 @@ impl ConstEvaluator {
                 ref arg_exprs,
                 ..
             } => self.try_const_eval_all_call_expr(arg_exprs),
+            ExprKind::ArrayCons {
+                ref arg_exprs,
+                ..
+            } => self.try_const_eval_all_call_expr(arg_exprs),
             ExprKind::Var {
                 span,
                 ref kind,
@@ -76,6 +76,10 @@ impl<'a> ConstGatherer<'a> {
                 ref arg_exprs,
                 ..
             } => self.const_gather_all_call_expr(arg_exprs),
+            ExprKind::ArrayCons {
+                ref arg_exprs,
+                ..
+            } => self.const_gather_all_call_expr(arg_exprs),
             ExprKind::Field {
                 span,
                 ref expr,
@@ -70,6 +70,14 @@ impl<'a> DepAnalyser<'a> {
                 ty_lit,
                 ref arg_exprs,
             } => self.dep_analyse_cons_call_expr(span, ty_lit, arg_exprs),
+            ExprKind::ArrayCons {
+                ref arg_exprs,
+                ..
+            } => {
+                for arg_expr in arg_exprs {
+                    self.dep_analyse_expr(arg_expr);
+                }
+            }
             ExprKind::StructCons{
                 struct_ptr,
                 span,
@@ -240,8 +248,10 @@ impl<'a> DepAnalyser<'a> {
                     Some(Ty::Struct(struct_ptr))=>{
                         self.fn_def.struct_refs.borrow_mut().as_mut().unwrap().insert(*struct_ptr);
                     }
-                    Some(Ty::Array{..})=>{
-                        todo!();
+                    Some(Ty::Array{elem_ty, ..})=>{
+                        if let Ty::Struct(struct_ptr) = elem_ty.as_ref(){
+                            self.fn_def.struct_refs.borrow_mut().as_mut().unwrap().insert(*struct_ptr);
+                        }
                     }
                     _=>()
                 }
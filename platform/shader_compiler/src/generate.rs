@@ -12,7 +12,8 @@ use{
             TokenSpan
         },
         shader_ast::*,
-        shader_registry::ShaderRegistry
+        shader_registry::ShaderRegistry,
+        source_map::ShaderSourceMap,
     }
 };
 
@@ -77,6 +78,40 @@ pub trait BackendWriter {
     fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit);
     fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]);
     
+    // the hooks below default to the c-like syntax shared by glsl, hlsl and metal
+    
+    fn cond_expr_is_select(&self) -> bool {false}
+    fn assign_is_stmt(&self) -> bool {false}
+    fn inout_is_pointer(&self) -> bool {false}
+    
+    fn write_fn_def_name(&self, string: &mut String, ident: &dyn fmt::Display, return_ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, return_ty);
+    }
+    
+    fn write_fn_def_return_ty(&self, _string: &mut String, _return_ty: &Ty) {
+    }
+    
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
+    
+    fn write_for_header(&self, string: &mut String, ident: &dyn fmt::Display, from: i32, to: i32, step: i32) {
+        write!(
+            string,
+            "for (int {0} = {1}; {0} {2} {3}; {0} {4} {5}) ",
+            ident,
+            if from <= to {from} else {from - 1},
+            if from <= to {"<"} else {">="},
+            to,
+            if step > 0 {"+="} else {"-="},
+            step.abs()
+        ).unwrap();
+    }
+    
+    // while and loop both become an endless loop that breaks on the condition
+    fn write_loop_header(&self, string: &mut String) {
+        write!(string, "while (true) ").unwrap();
+    }
 }
 
 pub struct BlockGenerator<'a> {
@@ -122,7 +157,7 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
     if param_tys.len() == 1 {
         let param_ty = &param_tys[0];
         match param_ty {
-            Ty::Bool | Ty::Int | Ty::Float => {
+            Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => {
                 let mut sep = "";
                 for _ in 0..ty.slots() {
                     write!(string, "{}x", sep).unwrap();
@@ -199,17 +234,26 @@ impl<'a> BlockGenerator<'a> {
     
     fn generate_stmt(&mut self, stmt: &Stmt) {
         self.write_indent();
+        ShaderSourceMap::write_marker(self.string, Some(stmt.span()));
         match *stmt {
             Stmt::Break {span} => self.generate_break_stmt(span),
             Stmt::Continue {span} => self.generate_continue_stmt(span),
             Stmt::For {
                 span,
                 ident,
-                ref from_expr,
-                ref to_expr,
-                ref step_expr,
+                ref shadow,
+                ref range,
+                ref block,
+            } => self.generate_for_stmt(span, ident, shadow, range, block),
+            Stmt::While {
+                span,
+                ref expr,
                 ref block,
-            } => self.generate_for_stmt(span, ident, from_expr, to_expr, step_expr, block),
+            } => self.generate_loop_stmt(span, Some(expr), block),
+            Stmt::Loop {
+                span,
+                ref block,
+            } => self.generate_loop_stmt(span, None, block),
             Stmt::If {
                 span,
                 ref expr,
@@ -247,11 +291,11 @@ impl<'a> BlockGenerator<'a> {
         &mut self,
         _span: TokenSpan,
         ident: Ident,
-        from_expr: &Expr,
-        to_expr: &Expr,
-        step_expr: &Option<Expr>,
+        shadow: &Cell<Option<ScopeSymShadow >>,
+        range: &ForRange,
         block: &Block,
     ) {
+        let ForRange {from_expr, to_expr, step_expr} = range;
         let from = from_expr
             .const_val
             .borrow()
@@ -285,21 +329,35 @@ impl<'a> BlockGenerator<'a> {
         } else {
             -1
         };
-        write!(
-            self.string,
-            "for (int {0} = {1}; {0} {2} {3}; {0} {4} {5}) ",
-            ident,
-            if from <= to {from} else {from - 1},
-            if from <= to {"<"} else {">="},
-            to,
-            if step > 0 {"+="} else {"-="},
-            step.abs()
-        )
-            .unwrap();
+        self.backend_writer.write_for_header(self.string, &DisplayVarName(ident, shadow.get().unwrap()), from, to, step);
         self.generate_block(block);
         writeln!(self.string).unwrap();
     }
     
+    fn generate_loop_stmt(
+        &mut self,
+        _span: TokenSpan,
+        expr: Option<&Expr>,
+        block: &Block,
+    ) {
+        self.backend_writer.write_loop_header(self.string);
+        write!(self.string, "{{\n").unwrap();
+        self.indent_level += 1;
+        if let Some(expr) = expr {
+            self.write_indent();
+            write!(self.string, "if (!(").unwrap();
+            self.generate_expr(expr);
+            write!(self.string, ")) {{ break; }}\n").unwrap();
+        }
+        for stmt in &block.stmts {
+            self.generate_stmt(stmt);
+            writeln!(self.string).unwrap();
+        }
+        self.indent_level -= 1;
+        self.write_indent();
+        write!(self.string, "}}\n").unwrap();
+    }
+    
     fn generate_if_stmt(
         &mut self,
         _span: TokenSpan,
@@ -358,14 +416,22 @@ impl<'a> BlockGenerator<'a> {
         expr: &Option<Expr>,
         shadow: &Cell<Option<ScopeSymShadow >>
     ) {
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_let_decl(
             &mut self.string,
-            "",
-            false, 
-            false,
             &DisplayVarName(ident, shadow.get().unwrap()),
             ty.borrow().as_ref().unwrap()
         );
+        // not every target has array constructors, so we store the elements one by one
+        if let Some(Expr {kind: ExprKind::ArrayCons {arg_exprs, ..}, ..}) = expr {
+            write!(self.string, ";\n").unwrap();
+            for (index, arg_expr) in arg_exprs.iter().enumerate() {
+                self.write_indent();
+                write!(self.string, "{}[{}] = ", DisplayVarName(ident, shadow.get().unwrap()), index).unwrap();
+                self.generate_expr(arg_expr);
+                write!(self.string, ";\n").unwrap();
+            }
+            return
+        }
         if let Some(expr) = expr {
             write!(self.string, " = ").unwrap();
             self.generate_expr(expr);
@@ -388,6 +454,13 @@ impl<'a> BlockGenerator<'a> {
     }
     
     fn generate_expr_stmt(&mut self, _span: TokenSpan, expr: &Expr) {
+        if self.backend_writer.assign_is_stmt() {
+            // only assignments and calls are statements, the rest has to be discarded explicitly
+            let is_assign = matches!(expr.kind, ExprKind::Bin {op: BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign, ..});
+            if !is_assign && *expr.ty.borrow() != Some(Ty::Void) {
+                write!(self.string, "_ = ").unwrap();
+            }
+        }
         self.generate_expr(expr);
         writeln!(self.string, ";").unwrap();
     }
@@ -544,6 +617,8 @@ impl<'a> ExprGenerator<'a> {
                     ty_lit,
                     ref arg_exprs,
                 } => self.generate_cons_call_expr(span, ty_lit, arg_exprs),
+                // lowered by generate_let_stmt, the type checker rejects it anywhere else
+                ExprKind::ArrayCons {..} => panic!(),
                 ExprKind::StructCons {
                     struct_ptr,
                     span,
@@ -566,6 +641,16 @@ impl<'a> ExprGenerator<'a> {
         expr_if_true: &Expr,
         expr_if_false: &Expr,
     ) {
+        if self.backend_writer.cond_expr_is_select() {
+            write!(self.string, "select(").unwrap();
+            self.generate_expr(expr_if_false);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr_if_true);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr);
+            write!(self.string, ")").unwrap();
+            return
+        }
         write!(self.string, "(").unwrap();
         self.generate_expr(expr);
         write!(self.string, " ? ").unwrap();
@@ -650,6 +735,15 @@ impl<'a> ExprGenerator<'a> {
             }
         }
         
+        if self.backend_writer.assign_is_stmt() {
+            if let BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign = op {
+                self.generate_expr(left_expr);
+                write!(self.string, " {} ", op).unwrap();
+                self.generate_expr(right_expr);
+                return
+            }
+        }
+        
         write!(self.string, "(").unwrap();
         self.generate_expr(left_expr);
         write!(self.string, " {} ", op).unwrap();
@@ -711,7 +805,7 @@ impl<'a> ExprGenerator<'a> {
             )).unwrap();
             
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (index, arg_expr) in arg_exprs.iter().enumerate() {
                 // check if the args is a closure, ifso skip it
                 match arg_expr.ty.borrow().as_ref().unwrap(){
                     Ty::ClosureDef(_)=>{
@@ -721,7 +815,7 @@ impl<'a> ExprGenerator<'a> {
                 }
                 
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_arg_expr(fn_def, index, arg_exprs.len(), arg_expr);
                 sep = ", ";
             }
             // and now the closed over values
@@ -748,9 +842,9 @@ impl<'a> ExprGenerator<'a> {
         else {
             write!(self.string, "{}_{} (", fn_def.fn_ptr, fn_def.ident).unwrap();
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (index, arg_expr) in arg_exprs.iter().enumerate() {
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_arg_expr(fn_def, index, arg_exprs.len(), arg_expr);
                 sep = ", ";
             }
 
@@ -760,6 +854,25 @@ impl<'a> ExprGenerator<'a> {
         }
     }
     
+    fn generate_arg_expr(&mut self, fn_def: &FnDef, index: usize, arg_count: usize, arg_expr: &Expr) {
+        // draw shader methods get called without their self arg, so align the args to the end of the params
+        if self.backend_writer.inout_is_pointer() {
+            if let Some(param) = fn_def.params.get(index + fn_def.params.len() - arg_count) {
+                if param.is_inout {
+                    write!(self.string, "&").unwrap();
+                }
+            }
+        }
+        self.generate_expr(arg_expr);
+    }
+    
+    fn is_inout_param(&self, ident: Ident, shadow: ScopeSymShadow) -> bool {
+        if let Some(fn_def) = self.fn_def {
+            return fn_def.params.iter().any( | param | param.is_inout && param.ident == ident && param.shadow.get() == Some(shadow))
+        }
+        false
+    }
+    
     fn generate_field_expr(&mut self, _span: TokenSpan, expr: &Expr, field_ident: Ident, ty:&Ty) {
         match expr.ty.borrow().as_ref() {
             Some(Ty::DrawShader(_)) => {
@@ -922,11 +1035,13 @@ impl<'a> ExprGenerator<'a> {
     fn generate_var_expr(&mut self, _span: TokenSpan, kind: &Cell<Option<VarKind >>, _ty: &Option<Ty>) {
         // ok so we have a few varkinds
         match kind.get().unwrap() {
-            VarKind::Local {ident, shadow} => {
-                write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
-            }
-            VarKind::MutLocal {ident, shadow} => {
-                write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+            VarKind::Local {ident, shadow} | VarKind::MutLocal {ident, shadow} => {
+                if self.backend_writer.inout_is_pointer() && self.is_inout_param(ident, shadow) {
+                    write!(self.string, "(*{})", DisplayVarName(ident, shadow)).unwrap();
+                }
+                else {
+                    write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+                }
             }
             VarKind::LiveValue(value_node_ptr) => {
                 // this is a live value.. also prefix needed
@@ -959,12 +1074,9 @@ pub struct FnDefGenerator<'a> {
 
 impl<'a> FnDefGenerator<'a> {
     pub fn generate_fn_def(&mut self) {
-        
-        self.backend_writer.write_var_decl(
+        ShaderSourceMap::write_marker(self.string, Some(self.fn_def.span));
+        self.backend_writer.write_fn_def_name(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnName(self.fn_def.fn_ptr, self.fn_def.ident), // here we must expand IdentPath to something
             self.fn_def.return_ty.borrow().as_ref().unwrap()
         );
@@ -985,9 +1097,12 @@ impl<'a> FnDefGenerator<'a> {
             }
         }
         self.backend_writer.write_fn_def_hidden_params(self.string, self.fn_def.hidden_args.borrow().as_ref().unwrap(), sep);
-        write!(self.string, ") ").unwrap();
+        write!(self.string, ")").unwrap();
+        self.backend_writer.write_fn_def_return_ty(self.string, self.fn_def.return_ty.borrow().as_ref().unwrap());
+        write!(self.string, " ").unwrap();
         self.generate_block(&self.fn_def.block);
         writeln!(self.string).unwrap();
+        ShaderSourceMap::write_marker(self.string, None);
         //self.visited.insert(self.decl.ident_path);
     }
     
@@ -1076,12 +1191,9 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
     }
     
     pub fn generate_fn_def_with_closure_args(&mut self) {
-        
-        self.backend_writer.write_var_decl(
+        ShaderSourceMap::write_marker(self.string, Some(self.fn_def.span));
+        self.backend_writer.write_fn_def_name(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnNameWithClosureArgs(
                 self.closure_site_info.site_index,
                 self.call_def.fn_ptr,
@@ -1125,13 +1237,16 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        write!(self.string, ") ").unwrap();
+        write!(self.string, ")").unwrap();
+        self.backend_writer.write_fn_def_return_ty(self.string, self.fn_def.return_ty.borrow().as_ref().unwrap());
+        write!(self.string, " ").unwrap();
         // alright so here the block is generated.. however
         // we need to know the names and the closed-over-args passthrough
         self.generate_block(&self.fn_def.block);
         
         
         writeln!(self.string).unwrap();
+        ShaderSourceMap::write_marker(self.string, None);
         //self.visited.insert(self.decl.ident_path);
     }
     
@@ -1164,17 +1279,15 @@ pub struct ClosureDefGenerator<'a> {
 impl<'a> ClosureDefGenerator<'a> {
     pub fn generate_fn_def(&mut self) {
         
+        ShaderSourceMap::write_marker(self.string, Some(self.closure_def.span));
         let fn_param = &self.fn_def.params[self.closure_site_arg.param_index];
         
         let mut sep = "";
         
-        if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
+        let return_ty = if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
             
-            self.backend_writer.write_var_decl(
+            self.backend_writer.write_fn_def_name(
                 &mut self.string,
-                "",
-                false,
-                false,
                 &DisplayClosureName(self.call_def.fn_ptr, self.closure_site_arg.closure_def_index), // here we must expand IdentPath to something
                 return_ty.borrow().as_ref().unwrap(),
             );
@@ -1196,10 +1309,11 @@ impl<'a> ClosureDefGenerator<'a> {
                     sep = ", ";
                 }
             }
+            return_ty
         }
         else {
             panic!()
-        }
+        };
         
         for sym in self.closure_def.closed_over_syms.borrow().as_ref().unwrap() {
             if self.backend_writer.write_var_decl(
@@ -1219,7 +1333,9 @@ impl<'a> ClosureDefGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        writeln!(self.string, ") {{").unwrap();
+        write!(self.string, ")").unwrap();
+        self.backend_writer.write_fn_def_return_ty(self.string, return_ty.borrow().as_ref().unwrap());
+        writeln!(self.string, " {{").unwrap();
         
         match &self.closure_def.kind {
             ClosureDefKind::Expr(expr) => {
@@ -1233,6 +1349,7 @@ impl<'a> ClosureDefGenerator<'a> {
                 writeln!(self.string).unwrap();
             }
         }
+        ShaderSourceMap::write_marker(self.string, None);
         //self.visited.insert(self.decl.ident_path);
     }
     
@@ -12,11 +12,161 @@ use {
         generate::*,
         swizzle::Swizzle,
         shader_ast::*,
-        shader_registry::ShaderRegistry
+        shader_registry::ShaderRegistry,
+        source_map::ShaderSourceMap,
+        makepad_live_compiler::{
+            LiveError,
+            LiveErrorOrigin,
+            live_error_origin,
+            TokenSpan,
+        },
     }
 };
 
+/// GLSL ES 1.0 has no unsigned integers and only accepts for loops with a constant bound.
+/// Shaders that use either are rejected here, the generators have no way to report errors
+pub fn check_draw_shader(draw_shader_def: &DrawShaderDef, shader_registry: &ShaderRegistry) -> Result<(), LiveError> {
+    for field in &draw_shader_def.fields {
+        check_ty(field.span, field.ty_expr.ty.borrow().as_ref()) ?;
+    }
+    for struct_ptr in draw_shader_def.all_structs.borrow().iter() {
+        for field in &shader_registry.structs.get(struct_ptr).unwrap().fields {
+            check_ty(field.span, field.ty_expr.ty.borrow().as_ref()) ?;
+        }
+    }
+    for fn_ptr in draw_shader_def.all_fns.borrow().iter() {
+        let fn_def = shader_registry.all_fns.get(fn_ptr).unwrap();
+        check_ty(fn_def.span, fn_def.return_ty.borrow().as_ref()) ?;
+        for param in &fn_def.params {
+            check_ty(param.span, param.ty_expr.ty.borrow().as_ref()) ?;
+        }
+        check_block(&fn_def.block) ?;
+        for closure_def in &fn_def.closure_defs {
+            match &closure_def.kind {
+                ClosureDefKind::Expr(expr) => check_expr(expr) ?,
+                ClosureDefKind::Block(block) => check_block(block) ?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_error(span: TokenSpan, message: &str) -> LiveError {
+    LiveError {
+        origin: live_error_origin!(),
+        span: span.into(),
+        message: message.to_string()
+    }
+}
+
+fn check_ty(span: TokenSpan, ty: Option<&Ty>) -> Result<(), LiveError> {
+    match ty {
+        Some(Ty::Uint) => Err(check_error(span, "uint isn't supported by GLSL ES 1.0 (WebGL), use int")),
+        Some(Ty::Array {elem_ty, ..}) => check_ty(span, Some(elem_ty)),
+        _ => Ok(())
+    }
+}
+
+fn check_block(block: &Block) -> Result<(), LiveError> {
+    for stmt in &block.stmts {
+        check_stmt(stmt) ?;
+    }
+    Ok(())
+}
+
+fn check_stmt(stmt: &Stmt) -> Result<(), LiveError> {
+    match stmt {
+        Stmt::Break {..} | Stmt::Continue {..} => Ok(()),
+        Stmt::For {range, block, ..} => {
+            check_expr(&range.from_expr) ?;
+            check_expr(&range.to_expr) ?;
+            if let Some(step_expr) = &range.step_expr {
+                check_expr(step_expr) ?;
+            }
+            check_block(block)
+        }
+        Stmt::While {span, ..} | Stmt::Loop {span, ..} => {
+            Err(check_error(*span, "while and loop aren't supported by GLSL ES 1.0 (WebGL), use a for loop"))
+        }
+        Stmt::If {expr, block_if_true, block_if_false, ..} => {
+            check_expr(expr) ?;
+            check_block(block_if_true) ?;
+            if let Some(block_if_false) = block_if_false {
+                check_block(block_if_false) ?;
+            }
+            Ok(())
+        }
+        Stmt::Match {expr, matches, ..} => {
+            check_expr(expr) ?;
+            for m in matches {
+                check_block(&m.block) ?;
+            }
+            Ok(())
+        }
+        Stmt::Let {span, ty, expr, ..} => {
+            check_ty(*span, ty.borrow().as_ref()) ?;
+            if let Some(expr) = expr {
+                check_expr(expr) ?;
+            }
+            Ok(())
+        }
+        Stmt::Return {expr, ..} => {
+            if let Some(expr) = expr {
+                check_expr(expr) ?;
+            }
+            Ok(())
+        }
+        Stmt::Block {block, ..} => check_block(block),
+        Stmt::Expr {expr, ..} => check_expr(expr),
+    }
+}
+
+fn check_expr(expr: &Expr) -> Result<(), LiveError> {
+    check_ty(expr.span, expr.ty.borrow().as_ref()) ?;
+    match &expr.kind {
+        ExprKind::Cond {expr, expr_if_true, expr_if_false, ..} => {
+            check_expr(expr) ?;
+            check_expr(expr_if_true) ?;
+            check_expr(expr_if_false)
+        }
+        ExprKind::Bin {left_expr, right_expr, ..} => {
+            check_expr(left_expr) ?;
+            check_expr(right_expr)
+        }
+        ExprKind::Un {expr, ..} | ExprKind::Field {expr, ..} => check_expr(expr),
+        ExprKind::Index {expr, index_expr, ..} => {
+            check_expr(expr) ?;
+            check_expr(index_expr)
+        }
+        ExprKind::MethodCall {arg_exprs, ..}
+            | ExprKind::PlainCall {arg_exprs, ..}
+            | ExprKind::BuiltinCall {arg_exprs, ..}
+            | ExprKind::ConsCall {arg_exprs, ..}
+            | ExprKind::ArrayCons {arg_exprs, ..} => {
+            for arg_expr in arg_exprs {
+                check_expr(arg_expr) ?;
+            }
+            Ok(())
+        }
+        ExprKind::StructCons {args, ..} => {
+            for (_, arg_expr) in args {
+                check_expr(arg_expr) ?;
+            }
+            Ok(())
+        }
+        ExprKind::ClosureDef(_) | ExprKind::Var {..} | ExprKind::Lit {..} => Ok(())
+    }
+}
+
 pub fn generate_vertex_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    generate_vertex_shader_with_source_map(draw_shader_def, const_table, shader_registry).0
+}
+
+pub fn generate_pixel_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    generate_pixel_shader_with_source_map(draw_shader_def, const_table, shader_registry).0
+}
+
+pub fn generate_vertex_shader_with_source_map(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> (String, ShaderSourceMap) {
     let mut string = String::new();
     DrawShaderGenerator {
         draw_shader_def,
@@ -26,10 +176,11 @@ pub fn generate_vertex_shader(draw_shader_def: &DrawShaderDef, const_table: &Dra
         backend_writer: &GlslBackendWriter {shader_registry, const_table}
     }
     .generate_vertex_shader();
-    string
+    let source_map = ShaderSourceMap::strip_markers(&mut string);
+    (string, source_map)
 }
 
-pub fn generate_pixel_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+pub fn generate_pixel_shader_with_source_map(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> (String, ShaderSourceMap) {
     let mut string = String::new();
     DrawShaderGenerator {
         draw_shader_def,
@@ -39,7 +190,8 @@ pub fn generate_pixel_shader(draw_shader_def: &DrawShaderDef, const_table: &Draw
         backend_writer: &GlslBackendWriter {shader_registry, const_table}
     }
     .generate_pixel_shader();
-    string
+    let source_map = ShaderSourceMap::strip_markers(&mut string);
+    (string, source_map)
 }
 
 struct DrawShaderGenerator<'a> {
@@ -57,7 +209,7 @@ impl<'a> DrawShaderGenerator<'a> {
             "{}",
             match ty {
                 Ty::Bool => "false",
-                Ty::Int => "0",
+                Ty::Int | Ty::Uint => "0",
                 Ty::Float => "0.0",
                 Ty::Bvec2 => "bvec2(0)",
                 Ty::Bvec3 => "bvec3(0)",
@@ -212,6 +364,14 @@ impl<'a> DrawShaderGenerator<'a> {
         for (ty_lit, param_tys) in all_constructor_fns {
             generate_cons_fn(self.backend_writer, self.string, ty_lit, &param_tys);
         }
+        
+        // the 2d samplers live in the platform prelude
+        let uses_builtin = | ident | fn_deps.iter().any( | fn_ptr | {
+            self.shader_registry.all_fns.get(fn_ptr).unwrap().builtin_deps.borrow().as_ref().unwrap().contains(&Ident(ident))
+        });
+        if uses_builtin(live_id!(sampleCube)) {
+            writeln!(self.string, "vec4 sampleCube(samplerCube sampler, vec3 pos){{return textureCube(sampler, pos);}}").unwrap();
+        }
         write!(self.string, "\n").unwrap();
         for fn_iter in fn_deps.iter().rev() {
             let const_table_offset = self.const_table.offsets.get(fn_iter).cloned();
@@ -826,6 +986,11 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Int);
                 write!(string, " {}", ident).unwrap();
             }
+            Ty::Uint => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uint);
+                write!(string, " {}", ident).unwrap();
+            }
             Ty::Float => {
                 prefix(string, sep, is_inout);
                 self.write_ty_lit(string, TyLit::Float);
@@ -901,6 +1066,11 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::TextureOES);
                 write!(string, " {}", ident).unwrap();
             }
+            Ty::TextureCube => {
+                write!(string, "{}", sep).unwrap();
+                self.write_ty_lit(string, TyLit::TextureCube);
+                write!(string, " {}", ident).unwrap();
+            }
             Ty::Array {ref elem_ty, len} => {
                 self.write_var_decl(string, sep, is_inout, is_packed, ident, elem_ty);
                 write!(string, "[{}]", len).unwrap();
@@ -947,6 +1117,8 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
             match ty_lit {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                // unreachable, check_draw_shader rejects uint before generating
+                TyLit::Uint => "int",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bvec2",
                 TyLit::Bvec3 => "bvec3",
@@ -962,6 +1134,7 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
                 TyLit::Mat4 => "mat4",
                 TyLit::Texture2D => "sampler2D",
                 TyLit::TextureOES => "samplerExternalOES",
+                TyLit::TextureCube => "samplerCube",
             }
         )
             .unwrap();
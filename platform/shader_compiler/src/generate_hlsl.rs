@@ -11,7 +11,8 @@ use{
         },
         generate::*,
         shader_ast::*,
-        shader_registry::ShaderRegistry
+        shader_registry::ShaderRegistry,
+        source_map::ShaderSourceMap,
     }
 };
 
@@ -20,6 +21,10 @@ pub fn index_to_char(index: usize) -> char {
 }
 
 pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    generate_shader_with_source_map(draw_shader_def, const_table, shader_registry).0
+}
+
+pub fn generate_shader_with_source_map(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> (String, ShaderSourceMap) {
     let mut string = String::new();
     DrawShaderGenerator {
         draw_shader_def,
@@ -29,7 +34,8 @@ pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderC
         backend_writer: &HlslBackendWriter {shader_registry, draw_shader_def, const_table}
     }
     .generate_shader();
-    string
+    let source_map = ShaderSourceMap::strip_markers(&mut string);
+    (string, source_map)
 }
 
 struct DrawShaderGenerator<'a> {
@@ -50,20 +56,24 @@ impl<'a> DrawShaderGenerator<'a> {
             all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
         }
 
-        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
-            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d))) {
-                writeln!(self.string, "SamplerState default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;}};").unwrap();
-                writeln!(self.string, "float4 sample2d(Texture2D tex, float2 pos){{return tex.Sample(default_texture_sampler,pos);}}").unwrap();
-                break;
-            }
-
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d_rt))) {
-                writeln!(self.string, "SamplerState default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;}};").unwrap();
-                writeln!(self.string, "float4 sample2d_rt(Texture2D tex, float2 pos){{return tex.Sample(default_texture_sampler,pos);}}").unwrap();
-                break;
+        let uses_builtin = | ident | self.draw_shader_def.all_fns.borrow().iter().any( | fn_iter | {
+            self.shader_registry.all_fns.get(fn_iter).unwrap().builtin_deps.borrow().as_ref().unwrap().contains(&Ident(ident))
+        });
+        let samplers = [
+            (live_id!(sample2d), "float4 sample2d(Texture2D tex, float2 pos){return tex.Sample(default_texture_sampler,pos);}"),
+            (live_id!(sample2d_rt), "float4 sample2d_rt(Texture2D tex, float2 pos){return tex.Sample(default_texture_sampler,pos);}"),
+            (live_id!(sampleCube), "float4 sampleCube(TextureCube tex, float3 pos){return tex.Sample(default_texture_sampler,pos);}"),
+        ];
+        let mut has_sampler = false;
+        for (ident, sampler_fn) in samplers {
+            if uses_builtin(ident) {
+                if !has_sampler {
+                    writeln!(self.string, "SamplerState default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;}};").unwrap();
+                    has_sampler = true;
+                }
+                writeln!(self.string, "{}", sampler_fn).unwrap();
             }
-        };
+        }
         
         self.generate_struct_decls();
         let fields_as_uniform_blocks = self.draw_shader_def.fields_as_uniform_blocks();
@@ -193,9 +203,10 @@ impl<'a> DrawShaderGenerator<'a> {
         for field in &self.draw_shader_def.fields {
             match field.kind {
                 DrawShaderFieldKind::Texture {..} => {
-                    assert_ne!(*field.ty_expr.ty.borrow().as_ref().unwrap(), Ty::TextureOES, "TextureOES is only available on Android");
-                    assert_eq!(*field.ty_expr.ty.borrow().as_ref().unwrap(), Ty::Texture2D);
-                    write!(self.string, "Texture2D {}: register(t{});", DisplayDsIdent(field.ident), index).unwrap();
+                    let ty = field.ty_expr.ty.borrow().clone().unwrap();
+                    assert_ne!(ty, Ty::TextureOES, "TextureOES is only available on Android");
+                    self.write_var_decl(&DisplayDsIdent(field.ident), &ty);
+                    write!(self.string, ": register(t{});", index).unwrap();
                     index += 1;
                 }
                 _ => {}
@@ -467,7 +478,7 @@ impl<'a> DrawShaderGenerator<'a> {
     fn write_var_init(&mut self, ty: &Ty) {
         match ty {
             Ty::Bool => write!(self.string, "false").unwrap(),
-            Ty::Int => write!(self.string, "0").unwrap(),
+            Ty::Int | Ty::Uint => write!(self.string, "0").unwrap(),
             Ty::Float => write!(self.string, "0.0").unwrap(),
             Ty::Bvec2 => write!(self.string, "bool2(0,0)").unwrap(),
             Ty::Bvec3 => write!(self.string, "bool3(0,0,0)").unwrap(),
@@ -561,6 +572,11 @@ impl<'a> BackendWriter for HlslBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Int);
                 write!(string, " {}", ident).unwrap();
             }
+            Ty::Uint => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uint);
+                write!(string, " {}", ident).unwrap();
+            }
             Ty::Float => {
                 prefix(string, sep, is_inout);
                 self.write_ty_lit(string, TyLit::Float);
@@ -626,7 +642,17 @@ impl<'a> BackendWriter for HlslBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Mat4);
                 write!(string, " {}", ident).unwrap();
             }
-            Ty::Texture2D | Ty::TextureOES => panic!(), // TODO
+            Ty::Texture2D => {
+                write!(string, "{}", sep).unwrap();
+                self.write_ty_lit(string, TyLit::Texture2D);
+                write!(string, " {}", ident).unwrap();
+            }
+            Ty::TextureCube => {
+                write!(string, "{}", sep).unwrap();
+                self.write_ty_lit(string, TyLit::TextureCube);
+                write!(string, " {}", ident).unwrap();
+            }
+            Ty::TextureOES => panic!(), // TODO
             Ty::Array {ref elem_ty, len} => {
                 self.write_var_decl(string, sep, is_inout, is_packed, ident, elem_ty);
                 write!(string, "[{}]", len).unwrap();
@@ -788,6 +814,7 @@ impl<'a> BackendWriter for HlslBackendWriter<'a> {
             match ty_lit {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                TyLit::Uint => "uint",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bool2",
                 TyLit::Bvec3 => "bool3",
@@ -801,7 +828,9 @@ impl<'a> BackendWriter for HlslBackendWriter<'a> {
                 TyLit::Mat2 => "float2x2",
                 TyLit::Mat3 => "float3x3",
                 TyLit::Mat4 => "float4x4",
-                TyLit::Texture2D | TyLit::TextureOES => panic!(), // TODO
+                TyLit::Texture2D => "Texture2D",
+                TyLit::TextureCube => "TextureCube",
+                TyLit::TextureOES => panic!(), // TODO
             }
         )
             .unwrap();
@@ -10,12 +10,14 @@ use {
         shader_ast::*,
         generate::*,
         shader_registry::ShaderRegistry,
+        source_map::ShaderSourceMap,
     }
 };
 
 pub struct MetalGeneratedShader{
     pub mtlsl: String,
-    pub fields_as_uniform_blocks:BTreeMap<Ident, Vec<(usize, Ident) >>   
+    pub fields_as_uniform_blocks:BTreeMap<Ident, Vec<(usize, Ident) >>,
+    pub source_map: ShaderSourceMap,
 }
 
 pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderConstTable, shader_registry: &ShaderRegistry) -> MetalGeneratedShader {
@@ -30,9 +32,11 @@ pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table:&DrawShaderC
         backend_writer: &MetalBackendWriter {shader_registry, draw_shader_def, const_table}
     }
     .generate_shader();
+    let source_map = ShaderSourceMap::strip_markers(&mut string);
     MetalGeneratedShader{
         mtlsl:string, 
-        fields_as_uniform_blocks
+        fields_as_uniform_blocks,
+        source_map,
     }
 }
 
@@ -57,17 +61,19 @@ impl<'a> DrawShaderGenerator<'a> {
             all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
         }
         
-        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
-            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d))) {
-                writeln!(self.string, "float4 sample2d(texture2d<float> tex, float2 pos){{return tex.sample(sampler(mag_filter::nearest,min_filter::nearest),pos);}}").unwrap();
-                break;
-            }
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d_rt))) {
-                writeln!(self.string, "float4 sample2d_rt(texture2d<float> tex, float2 pos){{return tex.sample(sampler(mag_filter::nearest,min_filter::nearest),pos);}}").unwrap();
-                break;
+        let uses_builtin = | ident | self.draw_shader_def.all_fns.borrow().iter().any( | fn_iter | {
+            self.shader_registry.all_fns.get(fn_iter).unwrap().builtin_deps.borrow().as_ref().unwrap().contains(&Ident(ident))
+        });
+        let samplers = [
+            (live_id!(sample2d), "float4 sample2d(texture2d<float> tex, float2 pos){return tex.sample(sampler(mag_filter::nearest,min_filter::nearest),pos);}"),
+            (live_id!(sample2d_rt), "float4 sample2d_rt(texture2d<float> tex, float2 pos){return tex.sample(sampler(mag_filter::nearest,min_filter::nearest),pos);}"),
+            (live_id!(sampleCube), "float4 sampleCube(texturecube<float> tex, float3 pos){return tex.sample(sampler(mag_filter::nearest,min_filter::nearest),pos);}"),
+        ];
+        for (ident, sampler_fn) in samplers {
+            if uses_builtin(ident) {
+                writeln!(self.string, "{}", sampler_fn).unwrap();
             }
-        };
+        }
         
         self.generate_struct_defs();
         //let fields_as_uniform_blocks = self.draw_shader_def.fields_as_uniform_blocks();
@@ -166,10 +172,10 @@ impl<'a> DrawShaderGenerator<'a> {
         for field in &self.draw_shader_def.fields {
             match field.kind {
                 DrawShaderFieldKind::Texture {..} => {
-                    assert_ne!(*field.ty_expr.ty.borrow().as_ref().unwrap(), Ty::TextureOES, "TextureOES is only available on Android");
-                    assert_eq!(*field.ty_expr.ty.borrow().as_ref().unwrap(), Ty::Texture2D);
-                    write!(self.string, "    texture2d<float> ").unwrap();
-                    write!(self.string, "{}", &DisplayDsIdent(field.ident)).unwrap();
+                    let ty = field.ty_expr.ty.borrow().clone().unwrap();
+                    assert_ne!(ty, Ty::TextureOES, "TextureOES is only available on Android");
+                    write!(self.string, "    ").unwrap();
+                    self.write_var_decl(&DisplayDsIdent(field.ident), &ty);
                     write!(self.string, " [[texture({})]];", index).unwrap();
                     index += 1;
                 }
@@ -518,6 +524,11 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Int);
                 write!(string, " {}{}", ref_prefix, ident).unwrap();
             }
+            Ty::Uint => {
+                prefix(string, sep, is_inout);
+                self.write_ty_lit(string, TyLit::Uint);
+                write!(string, " {}{}", ref_prefix, ident).unwrap();
+            }
             Ty::Float => {
                 prefix(string, sep, is_inout);
                 self.write_ty_lit(string, TyLit::Float);
@@ -592,7 +603,23 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
                 self.write_ty_lit(string, TyLit::Mat4);
                 write!(string, " {}{}", ref_prefix, ident).unwrap();
             }
-            Ty::Texture2D | Ty::TextureOES => panic!(), // TODO
+            Ty::Texture2D => {
+                write!(string, "{}", sep).unwrap();
+                self.write_ty_lit(string, TyLit::Texture2D);
+                write!(string, " {}", ident).unwrap();
+            }
+            Ty::TextureCube => {
+                write!(string, "{}", sep).unwrap();
+                self.write_ty_lit(string, TyLit::TextureCube);
+                write!(string, " {}", ident).unwrap();
+            }
+            Ty::TextureOES => panic!(), // TODO
+            Ty::Array {ref elem_ty, len} if is_inout => {
+                // a reference to the whole array, not an array of references
+                write!(string, "{}thread ", sep).unwrap();
+                self.write_var_decl(string, "", false, is_packed, &format!("(&{})", ident), elem_ty);
+                write!(string, "[{}]", len).unwrap();
+            }
             Ty::Array {ref elem_ty, len} => {
                 self.write_var_decl(string, sep, is_inout, is_packed, ident, elem_ty);
                 write!(string, "[{}]", len).unwrap();
@@ -779,6 +806,7 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
             match ty_lit {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                TyLit::Uint => "uint",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bool2",
                 TyLit::Bvec3 => "bool3",
@@ -792,7 +820,9 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
                 TyLit::Mat2 => "float2x2",
                 TyLit::Mat3 => "float3x3",
                 TyLit::Mat4 => "float4x4",
-                TyLit::Texture2D | TyLit::TextureOES => panic!(), // TODO
+                TyLit::Texture2D => "texture2d<float>",
+                TyLit::TextureCube => "texturecube<float>",
+                TyLit::TextureOES => panic!(), // TODO
             }
         )
             .unwrap();
@@ -0,0 +1,856 @@
+use {
+    std::{
+        fmt::Write,
+        fmt,
+        cell::RefCell,
+        collections::{BTreeMap, BTreeSet}
+    },
+    crate::{
+        makepad_live_id::*,
+        shader_ast::*,
+        generate::*,
+        swizzle::Swizzle,
+        shader_registry::ShaderRegistry,
+        source_map::ShaderSourceMap,
+    }
+};
+
+/// A WGSL module with a `vertex_main` and a `fragment_main` entry point.
+///
+/// Bindings are assigned in a fixed order so a WebGPU backend can build its bind group layouts
+/// from the draw shader def alone. Group 0 holds the uniform tables, each an `array<vec4<f32>, N>`
+/// packed like `DrawShaderInputPacking::UniformsGLSL` and padded to whole vec4s: `live_table`,
+/// then `const_table`, then one `<block>_table` per entry of `fields_as_uniform_blocks`, skipping
+/// the empty ones. Group 1 holds the textures in field order followed by a single sampler.
+/// Geometries and instances come in as `packed_geometry_N` and `packed_instance_N` vertex
+/// attributes of up to 4 floats, in the slot order of the GLSL backend.
+pub struct WgslGeneratedShader {
+    pub wgsl: String,
+    pub fields_as_uniform_blocks: BTreeMap<Ident, Vec<(usize, Ident) >>,
+    pub geometry_slots: usize,
+    pub instance_slots: usize,
+    pub varying_slots: usize,
+    pub source_map: ShaderSourceMap,
+}
+
+pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> WgslGeneratedShader {
+    let mut string = String::new();
+    let fields_as_uniform_blocks = draw_shader_def.fields_as_uniform_blocks();
+    let backend_writer = WgslBackendWriter {
+        helper_fns: RefCell::new(BTreeMap::new())
+    };
+    let mut generator = DrawShaderGenerator {
+        draw_shader_def,
+        shader_registry,
+        const_table,
+        string: &mut string,
+        fields_as_uniform_blocks: &fields_as_uniform_blocks,
+        backend_writer: &backend_writer
+    };
+    generator.generate_shader();
+    let geometry_slots = generator.compute_packed_geometries_slots();
+    let instance_slots = generator.compute_packed_instances_slots();
+    let varying_slots = generator.compute_packed_varyings_slots();
+    let source_map = ShaderSourceMap::strip_markers(&mut string);
+    WgslGeneratedShader {
+        wgsl: string,
+        fields_as_uniform_blocks,
+        geometry_slots,
+        instance_slots,
+        varying_slots,
+        source_map,
+    }
+}
+
+struct DrawShaderGenerator<'a> {
+    draw_shader_def: &'a DrawShaderDef,
+    shader_registry: &'a ShaderRegistry,
+    string: &'a mut String,
+    fields_as_uniform_blocks: &'a BTreeMap<Ident, Vec<(usize, Ident) >>,
+    backend_writer: &'a WgslBackendWriter,
+    const_table: &'a DrawShaderConstTable
+}
+
+impl<'a> DrawShaderGenerator<'a> {
+    fn generate_shader(&mut self) {
+        let mut all_constructor_fns = BTreeSet::new();
+        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
+        }
+
+        self.generate_struct_defs();
+        self.generate_uniform_bindings();
+        self.generate_texture_bindings();
+        self.generate_private_vars();
+        self.generate_vertex_input_struct();
+        self.generate_vertex_output_struct();
+
+        for (ty_lit, ref param_tys) in all_constructor_fns {
+            self.generate_cons_fn(ty_lit, param_tys);
+        }
+
+        // the fns are generated into their own string first, that way we know which
+        // builtin helpers they need. WGSL doesn't care about declaration order
+        let mut fns = String::new();
+        let all_fns = self.draw_shader_def.all_fns.borrow();
+        for fn_iter in all_fns.iter().rev() {
+            let const_table_offset = self.const_table.offsets.get(fn_iter).cloned();
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            if fn_def.has_closure_args() {
+                for call_iter in all_fns.iter().rev() {
+                    // any function that depends on us, will have the closures we need
+                    let call_def = self.shader_registry.all_fns.get(call_iter).unwrap();
+                    if call_def.callees.borrow().as_ref().unwrap().contains(fn_iter) {
+                        FnDefWithClosureArgsGenerator::generate_fn_def_with_all_closures(
+                            &mut fns,
+                            self.shader_registry,
+                            fn_def,
+                            call_def,
+                            self.backend_writer,
+                            const_table_offset
+                        );
+                    }
+                }
+                continue
+            }
+            FnDefGenerator {
+                fn_def,
+                const_table_offset,
+                shader_registry: self.shader_registry,
+                backend_writer: self.backend_writer,
+                string: &mut fns,
+            }
+            .generate_fn_def()
+        }
+        for helper_fn in self.backend_writer.helper_fns.borrow().values() {
+            writeln!(self.string, "{}", helper_fn).unwrap();
+        }
+        self.string.push_str(&fns);
+
+        self.generate_vertex_main();
+        self.generate_fragment_main();
+    }
+
+    fn generate_struct_defs(&mut self) {
+        for struct_ptr in self.draw_shader_def.all_structs.borrow().iter().rev() {
+            let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+            writeln!(self.string, "struct {} {{", struct_ptr).unwrap();
+            for field in &struct_def.fields {
+                write!(self.string, "    ").unwrap();
+                self.write_var_decl(&DisplayStructField(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ",").unwrap();
+            }
+            writeln!(self.string, "}}").unwrap();
+        }
+    }
+
+    fn generate_uniform_bindings(&mut self) {
+        let mut binding = 0;
+        let live_slots = self.calc_live_slots();
+        if live_slots > 0 {
+            writeln!(self.string, "@group(0) @binding({}) var<uniform> live_table: array<vec4<f32>, {}>;", binding, (live_slots + 3) >> 2).unwrap();
+            binding += 1;
+        }
+        if !self.const_table.table.is_empty() {
+            writeln!(self.string, "@group(0) @binding({}) var<uniform> const_table: array<vec4<f32>, {}>;", binding, (self.const_table.table.len() + 3) >> 2).unwrap();
+            binding += 1;
+        }
+        for (ident, vec) in self.fields_as_uniform_blocks {
+            let slots = self.calc_uniform_block_slots(vec);
+            if slots > 0 {
+                writeln!(self.string, "@group(0) @binding({}) var<uniform> {}_table: array<vec4<f32>, {}>;", binding, ident, (slots + 3) >> 2).unwrap();
+                binding += 1;
+            }
+        }
+    }
+
+    fn generate_texture_bindings(&mut self) {
+        let mut binding = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Texture {..} = field.kind {
+                write!(self.string, "@group(1) @binding({}) var ", binding).unwrap();
+                self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ";").unwrap();
+                binding += 1;
+            }
+        }
+        if binding > 0 {
+            writeln!(self.string, "@group(1) @binding({}) var default_sampler: sampler;", binding).unwrap();
+        }
+    }
+
+    fn generate_private_vars(&mut self) {
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            write!(self.string, "var<private> ").unwrap();
+            self.write_var_decl(live_ref, ty);
+            writeln!(self.string, ";").unwrap();
+        }
+        for field in &self.draw_shader_def.fields {
+            match field.kind {
+                DrawShaderFieldKind::Texture {..} => (),
+                _ if field.ident.0.is_empty() => (),
+                _ => {
+                    write!(self.string, "var<private> ").unwrap();
+                    self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                    writeln!(self.string, ";").unwrap();
+                }
+            }
+        }
+    }
+
+    fn generate_vertex_input_struct(&mut self) {
+        let geometry_slots = self.compute_packed_geometries_slots();
+        let instance_slots = self.compute_packed_instances_slots();
+        if geometry_slots + instance_slots == 0 {
+            return
+        }
+        writeln!(self.string, "struct VertexInput {{").unwrap();
+        let mut location = 0;
+        self.generate_packed_var_decls("packed_geometry", geometry_slots, &mut location);
+        self.generate_packed_var_decls("packed_instance", instance_slots, &mut location);
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_vertex_output_struct(&mut self) {
+        writeln!(self.string, "struct VertexOutput {{").unwrap();
+        writeln!(self.string, "    @builtin(position) position: vec4<f32>,").unwrap();
+        let mut location = 0;
+        self.generate_packed_var_decls("packed_varying", self.compute_packed_varyings_slots(), &mut location);
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_packed_var_decls(&mut self, packed_var_name: &str, mut packed_vars_size: usize, location: &mut usize) {
+        let mut packed_var_index = 0;
+        while packed_vars_size > 0 {
+            let packed_var_size = packed_vars_size.min(4);
+            writeln!(
+                self.string,
+                "    @location({}) {}_{}: {},",
+                location,
+                packed_var_name,
+                packed_var_index,
+                match packed_var_size {
+                    1 => "f32",
+                    2 => "vec2<f32>",
+                    3 => "vec3<f32>",
+                    _ => "vec4<f32>",
+                }
+            ).unwrap();
+            packed_vars_size -= packed_var_size;
+            packed_var_index += 1;
+            *location += 1;
+        }
+    }
+
+    fn generate_cons_fn(&mut self, ty_lit: TyLit, param_tys: &[Ty]) {
+        let mut cons_name = format!("consfn_{}", ty_lit);
+        for param_ty in param_tys {
+            write!(cons_name, "_{}", param_ty).unwrap();
+        }
+        if !self.backend_writer.use_cons_fn(&cons_name) {
+            return
+        }
+        // only the matrix to matrix conversions need a function in WGSL
+        let dst_size = mat_size(&ty_lit.to_ty());
+        let src_size = mat_size(&param_tys[0]);
+        write!(self.string, "fn {}(x: ", cons_name).unwrap();
+        self.backend_writer.write_ty(self.string, &param_tys[0]);
+        write!(self.string, ") -> ").unwrap();
+        self.backend_writer.write_ty_lit(self.string, ty_lit);
+        writeln!(self.string, " {{").unwrap();
+        write!(self.string, "    return ").unwrap();
+        self.backend_writer.write_ty_lit(self.string, ty_lit);
+        write!(self.string, "(").unwrap();
+        let mut sep = "";
+        for col_index in 0..dst_size {
+            for row_index in 0..dst_size {
+                if row_index < src_size && col_index < src_size {
+                    write!(self.string, "{}x[{}][{}]", sep, col_index, row_index).unwrap();
+                }
+                else {
+                    write!(self.string, "{}{}", sep, if col_index == row_index {"1.0"} else {"0.0"}).unwrap();
+                }
+                sep = ", ";
+            }
+        }
+        writeln!(self.string, ");").unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_vertex_main(&mut self) {
+        let geometry_slots = self.compute_packed_geometries_slots();
+        let instance_slots = self.compute_packed_instances_slots();
+
+        writeln!(self.string, "@vertex").unwrap();
+        if geometry_slots + instance_slots > 0 {
+            writeln!(self.string, "fn vertex_main(input: VertexInput) -> VertexOutput {{").unwrap();
+        }
+        else {
+            writeln!(self.string, "fn vertex_main() -> VertexOutput {{").unwrap();
+        }
+        self.generate_uniform_unpack();
+
+        let mut offset = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Geometry {..} = field.kind {
+                let ty = field.ty_expr.ty.borrow();
+                self.generate_var_unpack(field.ident, ty.as_ref().unwrap(), "input.packed_geometry", geometry_slots, offset);
+                offset += ty.as_ref().unwrap().slots();
+            }
+        }
+        let mut offset = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Instance {..} = field.kind {
+                let ty = field.ty_expr.ty.borrow();
+                if !field.ident.0.is_empty() {
+                    self.generate_var_unpack(field.ident, ty.as_ref().unwrap(), "input.packed_instance", instance_slots, offset);
+                }
+                offset += ty.as_ref().unwrap().slots();
+            }
+        }
+
+        let vertex_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(vertex))).unwrap();
+        writeln!(self.string, "    var output: VertexOutput;").unwrap();
+        writeln!(self.string, "    output.position = {}();", DisplayFnName(vertex_def.fn_ptr, vertex_def.ident)).unwrap();
+
+        let varying_slots = self.compute_packed_varyings_slots();
+        let mut offset = 0;
+        for field in &self.draw_shader_def.fields {
+            if self.is_varying(field) {
+                let ty = field.ty_expr.ty.borrow();
+                self.generate_var_pack(field.ident, ty.as_ref().unwrap(), "output.packed_varying", varying_slots, offset);
+                offset += ty.as_ref().unwrap().slots();
+            }
+        }
+        writeln!(self.string, "    return output;").unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_fragment_main(&mut self) {
+        writeln!(self.string, "@fragment").unwrap();
+        writeln!(self.string, "fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {{").unwrap();
+        self.generate_uniform_unpack();
+
+        let varying_slots = self.compute_packed_varyings_slots();
+        let mut offset = 0;
+        for field in &self.draw_shader_def.fields {
+            if self.is_varying(field) {
+                let ty = field.ty_expr.ty.borrow();
+                self.generate_var_unpack(field.ident, ty.as_ref().unwrap(), "input.packed_varying", varying_slots, offset);
+                offset += ty.as_ref().unwrap().slots();
+            }
+        }
+
+        let pixel_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
+        writeln!(self.string, "    return {}();", DisplayFnName(pixel_def.fn_ptr, pixel_def.ident)).unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn is_varying(&self, field: &DrawShaderFieldDef) -> bool {
+        match &field.kind {
+            DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} => is_used_in_pixel_shader.get(),
+            DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} => is_used_in_pixel_shader.get(),
+            DrawShaderFieldKind::Varying {..} => true,
+            _ => false
+        }
+    }
+
+    fn generate_uniform_unpack(&mut self) {
+        let mut slots = 0;
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            write!(self.string, "    {} = ", live_ref).unwrap();
+            self.write_uniform_ty_unpack(ty, "live_table", slots);
+            writeln!(self.string, ";").unwrap();
+            slots += ty.slots();
+        }
+        for (ident, vec) in self.fields_as_uniform_blocks {
+            let table = format!("{}_table", ident);
+            let mut slots = 0;
+            for (index, _item) in vec {
+                let field = &self.draw_shader_def.fields[*index];
+                let ty = field.ty_expr.ty.borrow();
+                write!(self.string, "    {} = ", DisplayDsIdent(field.ident)).unwrap();
+                self.write_uniform_ty_unpack(ty.as_ref().unwrap(), &table, slots);
+                writeln!(self.string, ";").unwrap();
+                slots += ty.as_ref().unwrap().slots();
+            }
+        }
+    }
+
+    fn write_uniform_ty_unpack(&mut self, ty: &Ty, table: &str, s: usize) {
+        let slot = | s: usize | format!("{}[{}].{}", table, s >> 2, Swizzle::from_range(s & 3, (s & 3) + 1));
+        match ty {
+            Ty::Bool => write!(self.string, "{} > 0.5", slot(s)).unwrap(),
+            Ty::Int => write!(self.string, "i32({})", slot(s)).unwrap(),
+            Ty::Float | Ty::Enum(_) => write!(self.string, "{}", slot(s)).unwrap(),
+            _ => {
+                self.backend_writer.write_ty(self.string, ty);
+                write!(self.string, "(").unwrap();
+                for i in 0..ty.slots() {
+                    if i != 0 {
+                        write!(self.string, ", ").unwrap();
+                    }
+                    match ty {
+                        Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => write!(self.string, "{} > 0.5", slot(s + i)).unwrap(),
+                        Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => write!(self.string, "i32({})", slot(s + i)).unwrap(),
+                        _ => write!(self.string, "{}", slot(s + i)).unwrap()
+                    }
+                }
+                write!(self.string, ")").unwrap();
+            }
+        }
+    }
+
+    fn generate_var_unpack(&mut self, ident: Ident, ty: &Ty, packed_var_name: &str, packed_vars_size: usize, offset: usize) {
+        let slots = ty.slots();
+        // a vector that lives in a single packed var can be copied in one go
+        if mat_size(ty) == 0 && offset >> 2 == (offset + slots - 1) >> 2 {
+            let packed_var_size = (packed_vars_size - (offset & !3)).min(4);
+            write!(self.string, "    {} = {}_{}", DisplayDsIdent(ident), packed_var_name, offset >> 2).unwrap();
+            if packed_var_size != slots {
+                write!(self.string, ".{}", Swizzle::from_range(offset & 3, (offset & 3) + slots)).unwrap();
+            }
+            writeln!(self.string, ";").unwrap();
+            return
+        }
+        for i in 0..slots {
+            writeln!(
+                self.string,
+                "    {} = {};",
+                DisplaySlotRef(ident, ty, i),
+                DisplayPackedSlotRef(packed_var_name, packed_vars_size, offset + i)
+            ).unwrap();
+        }
+    }
+
+    fn generate_var_pack(&mut self, ident: Ident, ty: &Ty, packed_var_name: &str, packed_vars_size: usize, offset: usize) {
+        let slots = ty.slots();
+        // WGSL can't assign to a multi component swizzle, so only whole packed vars are copied in one go
+        if mat_size(ty) == 0 && offset & 3 == 0 && (packed_vars_size - offset).min(4) == slots {
+            writeln!(self.string, "    {}_{} = {};", packed_var_name, offset >> 2, DisplayDsIdent(ident)).unwrap();
+            return
+        }
+        for i in 0..slots {
+            writeln!(
+                self.string,
+                "    {} = {};",
+                DisplayPackedSlotRef(packed_var_name, packed_vars_size, offset + i),
+                DisplaySlotRef(ident, ty, i)
+            ).unwrap();
+        }
+    }
+
+    fn calc_live_slots(&self) -> usize {
+        let mut slots = 0;
+        for (_, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            slots += ty.slots();
+        }
+        slots
+    }
+
+    fn calc_uniform_block_slots(&self, vec: &[(usize, Ident)]) -> usize {
+        let mut slots = 0;
+        for (index, _item) in vec {
+            slots += self.draw_shader_def.fields[*index].ty_expr.ty.borrow().as_ref().unwrap().slots();
+        }
+        slots
+    }
+
+    fn compute_packed_geometries_slots(&self) -> usize {
+        let mut packed_attributes_size = 0;
+        for field in &self.draw_shader_def.fields {
+            packed_attributes_size += match field.kind {
+                DrawShaderFieldKind::Geometry {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                _ => 0,
+            }
+        }
+        packed_attributes_size
+    }
+
+    fn compute_packed_instances_slots(&self) -> usize {
+        let mut packed_instances_size = 0;
+        for field in &self.draw_shader_def.fields {
+            packed_instances_size += match field.kind {
+                DrawShaderFieldKind::Instance {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                _ => 0,
+            }
+        }
+        packed_instances_size
+    }
+
+    fn compute_packed_varyings_slots(&self) -> usize {
+        let mut packed_varyings_size = 0;
+        for field in &self.draw_shader_def.fields {
+            if self.is_varying(field) {
+                packed_varyings_size += field.ty_expr.ty.borrow().as_ref().unwrap().slots();
+            }
+        }
+        packed_varyings_size
+    }
+
+    fn write_var_decl(&mut self, ident: &dyn fmt::Display, ty: &Ty) {
+        self.backend_writer.write_var_decl(self.string, "", false, false, ident, ty);
+    }
+}
+
+fn mat_size(ty: &Ty) -> usize {
+    match ty {
+        Ty::Mat2 => 2,
+        Ty::Mat3 => 3,
+        Ty::Mat4 => 4,
+        _ => 0
+    }
+}
+
+fn vec_size(ty: &Ty) -> usize {
+    match ty {
+        Ty::Bvec2 | Ty::Ivec2 | Ty::Vec2 => 2,
+        Ty::Bvec3 | Ty::Ivec3 | Ty::Vec3 => 3,
+        Ty::Bvec4 | Ty::Ivec4 | Ty::Vec4 => 4,
+        _ => 0
+    }
+}
+
+/// One float slot of a draw shader field, matrices are walked column by column
+struct DisplaySlotRef<'a>(Ident, &'a Ty, usize);
+impl<'a> fmt::Display for DisplaySlotRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let size = mat_size(self.1);
+        if let Some(column) = self.2.checked_div(size) {
+            write!(f, "{}[{}][{}]", DisplayDsIdent(self.0), column, self.2 % size)
+        }
+        else if self.1.slots() > 1 {
+            write!(f, "{}.{}", DisplayDsIdent(self.0), Swizzle::from_range(self.2, self.2 + 1))
+        }
+        else {
+            write!(f, "{}", DisplayDsIdent(self.0))
+        }
+    }
+}
+
+/// One float slot of a packed attribute or varying, the last packed var can be smaller than a vec4
+struct DisplayPackedSlotRef<'a>(&'a str, usize, usize);
+impl<'a> fmt::Display for DisplayPackedSlotRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let index = self.2 >> 2;
+        if (self.1 - (index << 2)).min(4) > 1 {
+            write!(f, "{}_{}.{}", self.0, index, Swizzle::from_range(self.2 & 3, (self.2 & 3) + 1))
+        }
+        else {
+            write!(f, "{}_{}", self.0, index)
+        }
+    }
+}
+
+struct WgslBackendWriter {
+    // WGSL has no overloads for user fns, so the GLSL builtins it lacks get a helper per signature
+    pub helper_fns: RefCell<BTreeMap<String, String >>,
+}
+
+impl WgslBackendWriter {
+    fn write_ty(&self, string: &mut String, ty: &Ty) {
+        match ty {
+            Ty::Void => (),
+            Ty::Array {elem_ty, len} => {
+                write!(string, "array<").unwrap();
+                self.write_ty(string, elem_ty);
+                write!(string, ", {}>", len).unwrap();
+            }
+            Ty::Struct(struct_ptr) => {
+                write!(string, "{}", struct_ptr).unwrap();
+            }
+            Ty::Enum(_) => {
+                write!(string, "f32").unwrap();
+            }
+            Ty::Texture2D => {
+                self.write_ty_lit(string, TyLit::Texture2D);
+            }
+            Ty::TextureOES => {
+                self.write_ty_lit(string, TyLit::TextureOES);
+            }
+            Ty::TextureCube => {
+                self.write_ty_lit(string, TyLit::TextureCube);
+            }
+            _ => {
+                self.write_ty_lit(string, ty.maybe_ty_lit().unwrap());
+            }
+        }
+    }
+
+    fn ty_to_string(&self, ty: &Ty) -> String {
+        let mut string = String::new();
+        self.write_ty(&mut string, ty);
+        string
+    }
+
+    fn add_helper_fn(&self, name: &str, params: &[Ty], return_ty: &str, body: &str) {
+        if self.helper_fns.borrow().contains_key(name) {
+            return
+        }
+        let mut string = String::new();
+        write!(string, "fn {}(", name).unwrap();
+        for (index, param) in params.iter().enumerate() {
+            if index != 0 {
+                write!(string, ", ").unwrap();
+            }
+            write!(string, "x{}: {}", index, self.ty_to_string(param)).unwrap();
+        }
+        write!(string, ") -> {} {{\n    return {};\n}}", return_ty, body).unwrap();
+        self.helper_fns.borrow_mut().insert(name.to_string(), string);
+    }
+
+    fn helper_fn_name(ident: Ident, params: &[Ty]) -> String {
+        let mut name = format!("{}", ident);
+        for param in params {
+            write!(name, "_{}", param).unwrap();
+        }
+        name
+    }
+}
+
+impl BackendWriter for WgslBackendWriter {
+
+    fn get_struct_cons_type(&self) -> StructConsType {
+        StructConsType::Paren
+    }
+
+    fn needs_mul_fn_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn needs_unpack_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn enum_is_float(&self) -> bool {
+        true
+    }
+
+    fn const_table_is_vec4(&self) -> bool {
+        true
+    }
+
+    fn cond_expr_is_select(&self) -> bool {
+        true
+    }
+
+    fn assign_is_stmt(&self) -> bool {
+        true
+    }
+
+    fn inout_is_pointer(&self) -> bool {
+        true
+    }
+
+    fn use_cons_fn(&self, what: &str) -> bool {
+        matches!(
+            what,
+            "consfn_mat3_mat4"
+                | "consfn_mat2_mat4"
+                | "consfn_mat2_mat3"
+                | "consfn_mat4_mat3"
+                | "consfn_mat4_mat2"
+                | "consfn_mat3_mat2"
+        )
+    }
+
+    fn write_var_decl(
+        &self,
+        string: &mut String,
+        sep: &'static str,
+        is_inout: bool,
+        _is_packed: bool,
+        ident: &dyn fmt::Display,
+        ty: &Ty,
+    ) -> bool {
+        match ty {
+            Ty::DrawShader(_) | Ty::ClosureDef {..} | Ty::ClosureDecl => {
+                return false
+            }
+            Ty::Void => {
+                write!(string, "{}{}", sep, ident).unwrap();
+            }
+            _ => {
+                write!(string, "{}{}: ", sep, ident).unwrap();
+                if is_inout {
+                    write!(string, "ptr<function, ").unwrap();
+                    self.write_ty(string, ty);
+                    write!(string, ">").unwrap();
+                }
+                else {
+                    self.write_ty(string, ty);
+                }
+            }
+        }
+        true
+    }
+
+    fn write_fn_def_name(&self, string: &mut String, ident: &dyn fmt::Display, _return_ty: &Ty) {
+        write!(string, "fn {}", ident).unwrap();
+    }
+
+    fn write_fn_def_return_ty(&self, string: &mut String, return_ty: &Ty) {
+        if *return_ty != Ty::Void {
+            write!(string, " -> ").unwrap();
+            self.write_ty(string, return_ty);
+        }
+    }
+
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        write!(string, "var ").unwrap();
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
+
+    fn write_for_header(&self, string: &mut String, ident: &dyn fmt::Display, from: i32, to: i32, step: i32) {
+        write!(
+            string,
+            "for (var {0}: i32 = {1}; {0} {2} {3}; {0} {4} {5}) ",
+            ident,
+            if from <= to {from} else {from - 1},
+            if from <= to {"<"} else {">="},
+            to,
+            if step > 0 {"+="} else {"-="},
+            step.abs()
+        ).unwrap();
+    }
+
+    fn write_call_expr_hidden_args(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+
+    fn write_fn_def_hidden_params(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+
+    fn generate_live_value_prefix(&self, _string: &mut String) {
+    }
+
+    fn generate_draw_shader_field_expr(&self, string: &mut String, field_ident: Ident, _ty: &Ty) {
+        write!(string, "{}", &DisplayDsIdent(field_ident)).unwrap();
+    }
+
+    fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit) {
+        write!(
+            string,
+            "{}",
+            match ty_lit {
+                TyLit::Bool => "bool",
+                TyLit::Int => "i32",
+                TyLit::Uint => "u32",
+                TyLit::Float => "f32",
+                TyLit::Bvec2 => "vec2<bool>",
+                TyLit::Bvec3 => "vec3<bool>",
+                TyLit::Bvec4 => "vec4<bool>",
+                TyLit::Ivec2 => "vec2<i32>",
+                TyLit::Ivec3 => "vec3<i32>",
+                TyLit::Ivec4 => "vec4<i32>",
+                TyLit::Vec2 => "vec2<f32>",
+                TyLit::Vec3 => "vec3<f32>",
+                TyLit::Vec4 => "vec4<f32>",
+                TyLit::Mat2 => "mat2x2<f32>",
+                TyLit::Mat3 => "mat3x3<f32>",
+                TyLit::Mat4 => "mat4x4<f32>",
+                TyLit::Texture2D => "texture_2d<f32>",
+                TyLit::TextureOES => "texture_external",
+                TyLit::TextureCube => "texture_cube<f32>",
+            }
+        ).unwrap();
+    }
+
+    fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]) {
+        let arg_tys: Vec<Ty> = arg_exprs.iter().map( | arg_expr | arg_expr.ty.borrow().clone().unwrap()).collect();
+        match ident {
+            Ident(live_id!(atan)) if arg_tys.len() == 2 => {
+                write!(string, "atan2").unwrap();
+            }
+            Ident(live_id!(inversesqrt)) => {
+                write!(string, "inverseSqrt").unwrap();
+            }
+            Ident(live_id!(faceforward)) => {
+                write!(string, "faceForward").unwrap();
+            }
+            Ident(live_id!(dFdx)) => {
+                write!(string, "dpdx").unwrap();
+            }
+            Ident(live_id!(dFdy)) => {
+                write!(string, "dpdy").unwrap();
+            }
+            Ident(live_id!(sample2d)) | Ident(live_id!(sample2d_rt)) => {
+                // textures are sampled in the same orientation as the metal backend does
+                self.add_helper_fn(
+                    &format!("{}", ident),
+                    &[Ty::Texture2D, Ty::Vec2],
+                    "vec4<f32>",
+                    "textureSampleLevel(x0, default_sampler, x1, 0.0)"
+                );
+                write!(string, "{}", ident).unwrap();
+            }
+            Ident(live_id!(sample2dOES)) => {
+                self.add_helper_fn(
+                    "sample2dOES",
+                    &[Ty::TextureOES, Ty::Vec2],
+                    "vec4<f32>",
+                    "textureSampleBaseClampToEdge(x0, default_sampler, x1)"
+                );
+                write!(string, "sample2dOES").unwrap();
+            }
+            Ident(live_id!(sampleCube)) => {
+                self.add_helper_fn(
+                    "sampleCube",
+                    &[Ty::TextureCube, Ty::Vec3],
+                    "vec4<f32>",
+                    "textureSampleLevel(x0, default_sampler, x1, 0.0)"
+                );
+                write!(string, "sampleCube").unwrap();
+            }
+            Ident(live_id!(mod)) => {
+                let name = Self::helper_fn_name(ident, &arg_tys);
+                self.add_helper_fn(&name, &arg_tys, &self.ty_to_string(&arg_tys[0]), "x0 - x1 * floor(x0 / x1)");
+                write!(string, "{}", name).unwrap();
+            }
+            Ident(live_id!(lessThan)) | Ident(live_id!(lessThanEqual)) |
+            Ident(live_id!(greaterThan)) | Ident(live_id!(greaterThanEqual)) |
+            Ident(live_id!(equal)) | Ident(live_id!(notEqual)) => {
+                let op = match ident {
+                    Ident(live_id!(lessThan)) => "<",
+                    Ident(live_id!(lessThanEqual)) => "<=",
+                    Ident(live_id!(greaterThan)) => ">",
+                    Ident(live_id!(greaterThanEqual)) => ">=",
+                    Ident(live_id!(equal)) => "==",
+                    _ => "!=",
+                };
+                let name = Self::helper_fn_name(ident, &arg_tys[0..1]);
+                let return_ty = format!("vec{}<bool>", vec_size(&arg_tys[0]));
+                self.add_helper_fn(&name, &arg_tys, &return_ty, &format!("x0 {} x1", op));
+                write!(string, "{}", name).unwrap();
+            }
+            Ident(live_id!(not)) => {
+                let name = Self::helper_fn_name(ident, &arg_tys);
+                self.add_helper_fn(&name, &arg_tys, &self.ty_to_string(&arg_tys[0]), "!x0");
+                write!(string, "{}", name).unwrap();
+            }
+            Ident(live_id!(matrixCompMult)) => {
+                let name = Self::helper_fn_name(ident, &arg_tys[0..1]);
+                let ty = self.ty_to_string(&arg_tys[0]);
+                let cols: Vec<String> = (0..mat_size(&arg_tys[0])).map( | i | format!("x0[{0}] * x1[{0}]", i)).collect();
+                self.add_helper_fn(&name, &arg_tys, &ty, &format!("{}({})", ty, cols.join(", ")));
+                write!(string, "{}", name).unwrap();
+            }
+            Ident(live_id!(clamp)) | Ident(live_id!(max)) | Ident(live_id!(min)) |
+            Ident(live_id!(step)) | Ident(live_id!(smoothstep)) | Ident(live_id!(pow))
+                if arg_tys.iter().any( | ty | *ty != arg_tys[0]) => {
+                // GLSL allows mixing scalars with vectors here, WGSL wants them splatted
+                let vec_ty = arg_tys.iter().find( | ty | vec_size(ty) > 0).unwrap().clone();
+                let vec_ty_str = self.ty_to_string(&vec_ty);
+                let name = Self::helper_fn_name(ident, &arg_tys);
+                let args: Vec<String> = arg_tys.iter().enumerate().map( | (index, ty) | {
+                    if vec_size(ty) == 0 {format!("{}(x{})", vec_ty_str, index)} else {format!("x{}", index)}
+                }).collect();
+                self.add_helper_fn(&name, &arg_tys, &vec_ty_str, &format!("{}({})", ident, args.join(", ")));
+                write!(string, "{}", name).unwrap();
+            }
+            _ => {
+                write!(string, "{}", ident).unwrap()
+            }
+        }
+    }
+}
@@ -0,0 +1,1277 @@
+// a tree walking interpreter over the analysed shader AST, so vertex and pixel functions
+// can be run (and unit tested) without a GPU. Values are copied in and out of inout params
+// and closures capture by value, which matches the code the generate_* backends emit.
+
+use {
+    std::{
+        cell::Cell,
+        collections::BTreeMap,
+        rc::Rc,
+    },
+    crate::{
+        makepad_live_compiler::*,
+        makepad_live_id::*,
+        makepad_math::*,
+        shader_ast::*,
+        shader_registry::ShaderRegistry,
+        swizzle::Swizzle,
+    }
+};
+
+/// A value inside the interpreter. Matrices are stored column major like glsl does
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShaderValue {
+    Void,
+    Bool(bool),
+    Int(i32),
+    Uint(u32),
+    Float(f32),
+    Bvec2([bool; 2]),
+    Bvec3([bool; 3]),
+    Bvec4([bool; 4]),
+    Ivec2([i32; 2]),
+    Ivec3([i32; 3]),
+    Ivec4([i32; 4]),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    Mat2([f32; 4]),
+    Mat3([f32; 9]),
+    Mat4(Mat4),
+    Array(Vec<ShaderValue>),
+    Struct(StructPtr, Vec<ShaderValue>),
+    Texture(Ident),
+}
+
+/// An rgba texture sampled with linear filtering and clamp to edge, row 0 is the top.
+/// Cube maps store their 6 faces as layers in +x -x +y -y +z -z order
+#[derive(Clone, Debug, Default)]
+pub struct ShaderTexture {
+    pub width: usize,
+    pub height: usize,
+    pub layers: usize,
+    pub pixels: Vec<Vec4>,
+}
+
+// the generators don't bound while loops, glsl rejects them. This only keeps a buggy shader from hanging the interpreter
+const MAX_LOOP_ITERATIONS: usize = 65536;
+
+pub struct ShaderInterpreter<'a> {
+    shader_registry: &'a ShaderRegistry,
+    live_registry: &'a LiveRegistry,
+    draw_shader_def: &'a DrawShaderDef,
+    fields: BTreeMap<LiveId, ShaderValue>,
+    textures: BTreeMap<LiveId, ShaderTexture>,
+}
+
+type Locals = BTreeMap<(Ident, ScopeSymShadow), ShaderValue>;
+
+#[derive(Clone)]
+struct ClosureValue<'a> {
+    def_fn: &'a FnDef,
+    closure_def_index: ClosureDefIndex,
+    captured: Rc<Locals>,
+    closures: Rc<BTreeMap<usize, ClosureValue<'a >> >,
+}
+
+struct Frame<'a> {
+    fn_def: &'a FnDef,
+    locals: Locals,
+    closures: Rc<BTreeMap<usize, ClosureValue<'a >> >,
+}
+
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(ShaderValue)
+}
+
+fn interpret_error(span: TokenSpan, message: String) -> LiveError {
+    LiveError {
+        origin: live_error_origin!(),
+        span: span.into(),
+        message
+    }
+}
+
+impl<'a> ShaderInterpreter<'a> {
+    /// all fields start out zeroed, except matrices which start out as identity
+    pub fn new(shader_registry: &'a ShaderRegistry, live_registry: &'a LiveRegistry, draw_shader_def: &'a DrawShaderDef) -> Self {
+        let mut interpreter = Self {
+            shader_registry,
+            live_registry,
+            draw_shader_def,
+            fields: BTreeMap::new(),
+            textures: BTreeMap::new(),
+        };
+        for field in &draw_shader_def.fields {
+            let value = match field.kind {
+                DrawShaderFieldKind::Texture {..} => ShaderValue::Texture(field.ident),
+                _ => match field.ty_expr.ty.borrow().as_ref().unwrap() {
+                    Ty::Mat2 => ShaderValue::Mat2([1.0, 0.0, 0.0, 1.0]),
+                    Ty::Mat3 => ShaderValue::Mat3([1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]),
+                    Ty::Mat4 => ShaderValue::Mat4(Mat4::default()),
+                    ty => interpreter.zero_value(ty)
+                }
+            };
+            interpreter.fields.insert(field.ident.0, value);
+        }
+        interpreter
+    }
+
+    pub fn set_field(&mut self, id: LiveId, value: impl Into<ShaderValue>) {
+        self.fields.insert(id, value.into());
+    }
+
+    pub fn field(&self, id: LiveId) -> Option<&ShaderValue> {
+        self.fields.get(&id)
+    }
+
+    pub fn set_texture(&mut self, id: LiveId, texture: ShaderTexture) {
+        self.textures.insert(id, texture);
+    }
+
+    /// runs `fn vertex`, varyings it writes stay readable through `field` and by `pixel`
+    pub fn vertex(&mut self) -> Result<Vec4, LiveError> {
+        self.call_method(live_id!(vertex), Vec::new()).map( | value | value.to_vec4().unwrap())
+    }
+
+    pub fn pixel(&mut self) -> Result<Vec4, LiveError> {
+        self.call_method(live_id!(pixel), Vec::new()).map( | value | value.to_vec4().unwrap())
+    }
+
+    /// calls a method on the draw shader, args exclude self
+    pub fn call_method(&mut self, id: LiveId, args: Vec<ShaderValue>) -> Result<ShaderValue, LiveError> {
+        let fn_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(id)).ok_or_else( || {
+            interpret_error(TokenSpan::default(), format!("method `{}` is not defined on the draw shader", id))
+        }) ?;
+        if fn_def.params.len() != args.len() + 1 {
+            return Err(interpret_error(fn_def.span, format!("method `{}` expects {} arguments, got {}", id, fn_def.params.len() - 1, args.len())))
+        }
+        let mut call_args = vec![None];
+        call_args.extend(args.into_iter().map(Some));
+        let (value, _) = self.call_fn(fn_def, call_args, BTreeMap::new()) ?;
+        Ok(value)
+    }
+
+    fn zero_value(&self, ty: &Ty) -> ShaderValue {
+        match ty {
+            Ty::Bool => ShaderValue::Bool(false),
+            Ty::Int => ShaderValue::Int(0),
+            Ty::Uint => ShaderValue::Uint(0),
+            Ty::Float | Ty::Enum(_) => ShaderValue::Float(0.0),
+            Ty::Bvec2 => ShaderValue::Bvec2([false; 2]),
+            Ty::Bvec3 => ShaderValue::Bvec3([false; 3]),
+            Ty::Bvec4 => ShaderValue::Bvec4([false; 4]),
+            Ty::Ivec2 => ShaderValue::Ivec2([0; 2]),
+            Ty::Ivec3 => ShaderValue::Ivec3([0; 3]),
+            Ty::Ivec4 => ShaderValue::Ivec4([0; 4]),
+            Ty::Vec2 => ShaderValue::Vec2(Vec2::default()),
+            Ty::Vec3 => ShaderValue::Vec3(Vec3::default()),
+            Ty::Vec4 => ShaderValue::Vec4(Vec4::default()),
+            Ty::Mat2 => ShaderValue::Mat2([0.0; 4]),
+            Ty::Mat3 => ShaderValue::Mat3([0.0; 9]),
+            Ty::Mat4 => ShaderValue::Mat4(Mat4 {v: [0.0; 16]}),
+            Ty::Array {elem_ty, len} => ShaderValue::Array(vec![self.zero_value(elem_ty); *len]),
+            Ty::Struct(struct_ptr) => {
+                let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+                ShaderValue::Struct(*struct_ptr, struct_def.fields.iter().map( | field | {
+                    self.zero_value(field.ty_expr.ty.borrow().as_ref().unwrap())
+                }).collect())
+            }
+            Ty::Void | Ty::Texture2D | Ty::TextureOES | Ty::TextureCube | Ty::DrawShader(_) | Ty::ClosureDecl | Ty::ClosureDef(_) => ShaderValue::Void
+        }
+    }
+
+    fn live_value(&self, value_ptr: ValuePtr, ty: &Ty, span: TokenSpan) -> Result<ShaderValue, LiveError> {
+        let (nodes, index) = self.live_registry.ptr_to_nodes_index(value_ptr.0);
+        let mut eval_index = if let LiveValue::Expr {..} = nodes[index].value {index + 1} else {index};
+        Ok(match (live_eval(self.live_registry, index, &mut eval_index, nodes) ?, ty) {
+            (LiveEval::Float64(v), Ty::Int) => ShaderValue::Int(v as i32),
+            (LiveEval::Float64(v), _) => ShaderValue::Float(v as f32),
+            (LiveEval::Int64(v), Ty::Int) => ShaderValue::Int(v as i32),
+            (LiveEval::Int64(v), _) => ShaderValue::Float(v as f32),
+            (LiveEval::Bool(v), _) => ShaderValue::Float(if v {1.0} else {0.0}),
+            (LiveEval::Vec2(v), _) => ShaderValue::Vec2(v),
+            (LiveEval::Vec3(v), _) => ShaderValue::Vec3(v),
+            (LiveEval::Vec4(v), _) => ShaderValue::Vec4(v),
+            (v, _) => return Err(interpret_error(span, format!("live value {:?} is not a shader value", v)))
+        })
+    }
+
+    // args are aligned with the params, None for the draw shader self and closure params
+    fn call_fn(&mut self, fn_def: &'a FnDef, args: Vec<Option<ShaderValue >>, closures: BTreeMap<usize, ClosureValue<'a >>) -> Result<(ShaderValue, Vec<(usize, ShaderValue)>), LiveError> {
+        let mut frame = Frame {
+            fn_def,
+            locals: BTreeMap::new(),
+            closures: Rc::new(closures),
+        };
+        for (param, arg) in fn_def.params.iter().zip(args) {
+            if let Some(arg) = arg {
+                frame.locals.insert((param.ident, param.shadow.get().unwrap()), arg);
+            }
+        }
+        let value = match self.exec_block(&mut frame, &fn_def.block) ? {
+            Flow::Return(value) => value,
+            _ => ShaderValue::Void
+        };
+        let mut inouts = Vec::new();
+        for (index, param) in fn_def.params.iter().enumerate() {
+            if param.is_inout {
+                if let Some(shadow) = param.shadow.get() {
+                    if let Some(value) = frame.locals.remove(&(param.ident, shadow)) {
+                        inouts.push((index, value));
+                    }
+                }
+            }
+        }
+        Ok((value, inouts))
+    }
+
+    fn exec_block(&mut self, frame: &mut Frame<'a>, block: &'a Block) -> Result<Flow, LiveError> {
+        for stmt in &block.stmts {
+            match self.exec_stmt(frame, stmt) ? {
+                Flow::Next => (),
+                flow => return Ok(flow)
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn exec_stmt(&mut self, frame: &mut Frame<'a>, stmt: &'a Stmt) -> Result<Flow, LiveError> {
+        match stmt {
+            Stmt::Break {..} => Ok(Flow::Break),
+            Stmt::Continue {..} => Ok(Flow::Continue),
+            Stmt::For {
+                ident,
+                shadow,
+                range,
+                block,
+                ..
+            } => {
+                let ForRange {from_expr, to_expr, step_expr} = &**range;
+                // same bounds as generate_for_stmt
+                let from = from_expr.const_val.borrow().as_ref().unwrap().as_ref().unwrap().to_int().unwrap();
+                let to = to_expr.const_val.borrow().as_ref().unwrap().as_ref().unwrap().to_int().unwrap();
+                let step = if let Some(step_expr) = step_expr {
+                    step_expr.const_val.borrow().as_ref().unwrap().as_ref().unwrap().to_int().unwrap()
+                }
+                else if from < to {1} else {-1};
+                let mut i = if from <= to {from} else {from - 1};
+                while if from <= to {i < to} else {i >= to} {
+                    frame.locals.insert((*ident, shadow.get().unwrap()), ShaderValue::Int(i));
+                    match self.exec_block(frame, block) ? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        _ => ()
+                    }
+                    i += step;
+                }
+                Ok(Flow::Next)
+            }
+            Stmt::While {span, expr, block} => self.exec_loop(frame, *span, Some(expr), block),
+            Stmt::Loop {span, block} => self.exec_loop(frame, *span, None, block),
+            Stmt::If {
+                expr,
+                block_if_true,
+                block_if_false,
+                ..
+            } => {
+                if self.eval_bool(frame, expr) ? {
+                    self.exec_block(frame, block_if_true)
+                }
+                else if let Some(block_if_false) = block_if_false {
+                    self.exec_block(frame, block_if_false)
+                }
+                else {
+                    Ok(Flow::Next)
+                }
+            }
+            Stmt::Match {expr, matches, ..} => {
+                let value = self.eval_expr(frame, expr) ?;
+                let value = value.to_comps().map( | comps | comps.v[0]).unwrap_or(0.0);
+                for match_item in matches {
+                    if (value - match_item.enum_value.get().unwrap() as f64).abs() < 0.5 {
+                        return self.exec_block(frame, &match_item.block)
+                    }
+                }
+                Ok(Flow::Next)
+            }
+            Stmt::Let {ty, ident, shadow, expr, ..} => {
+                let value = if let Some(expr) = expr {
+                    self.eval_expr(frame, expr) ?
+                }
+                else {
+                    self.zero_value(ty.borrow().as_ref().unwrap())
+                };
+                frame.locals.insert((*ident, shadow.get().unwrap()), value);
+                Ok(Flow::Next)
+            }
+            Stmt::Return {expr, ..} => {
+                Ok(Flow::Return(if let Some(expr) = expr {
+                    self.eval_expr(frame, expr) ?
+                }
+                else {
+                    ShaderValue::Void
+                }))
+            }
+            Stmt::Block {block, ..} => self.exec_block(frame, block),
+            Stmt::Expr {expr, ..} => {
+                self.eval_expr(frame, expr) ?;
+                Ok(Flow::Next)
+            }
+        }
+    }
+
+    fn exec_loop(&mut self, frame: &mut Frame<'a>, span: TokenSpan, expr: Option<&'a Expr>, block: &'a Block) -> Result<Flow, LiveError> {
+        for _ in 0..MAX_LOOP_ITERATIONS {
+            if let Some(expr) = expr {
+                if !self.eval_bool(frame, expr) ? {
+                    return Ok(Flow::Next)
+                }
+            }
+            match self.exec_block(frame, block) ? {
+                Flow::Break => return Ok(Flow::Next),
+                Flow::Return(value) => return Ok(Flow::Return(value)),
+                _ => ()
+            }
+        }
+        Err(interpret_error(span, format!("loop did not finish within {} iterations", MAX_LOOP_ITERATIONS)))
+    }
+
+    fn eval_bool(&mut self, frame: &mut Frame<'a>, expr: &'a Expr) -> Result<bool, LiveError> {
+        match self.eval_expr(frame, expr) ? {
+            ShaderValue::Bool(v) => Ok(v),
+            value => Err(interpret_error(expr.span, format!("expected a bool, got {:?}", value)))
+        }
+    }
+
+    fn eval_expr(&mut self, frame: &mut Frame<'a>, expr: &'a Expr) -> Result<ShaderValue, LiveError> {
+        if let Some(Some(val)) = expr.const_val.borrow().as_ref() {
+            return Ok(match val {
+                Val::Bool(v) => ShaderValue::Bool(*v),
+                Val::Int(v) => ShaderValue::Int(*v),
+                Val::Float(v) => ShaderValue::Float(*v),
+                Val::Vec4(v) => ShaderValue::Vec4(*v),
+            })
+        }
+        match &expr.kind {
+            ExprKind::Cond {expr: cond_expr, expr_if_true, expr_if_false, ..} => {
+                if self.eval_bool(frame, cond_expr) ? {
+                    self.eval_expr(frame, expr_if_true)
+                }
+                else {
+                    self.eval_expr(frame, expr_if_false)
+                }
+            }
+            ExprKind::Bin {span, op, left_expr, right_expr} => self.eval_bin_expr(frame, *span, *op, left_expr, right_expr),
+            ExprKind::Un {span, op, expr} => {
+                let value = self.eval_expr(frame, expr) ?;
+                match (op, &value) {
+                    (UnOp::Not, ShaderValue::Bool(v)) => Ok(ShaderValue::Bool(!v)),
+                    (UnOp::Neg, _) if value.to_comps().is_some() => {
+                        let comps = value.to_comps().unwrap();
+                        Ok(comps.map( | v | -v).to_value())
+                    }
+                    _ => Err(interpret_error(*span, format!("cannot apply {} to {:?}", op, value)))
+                }
+            }
+            ExprKind::Field {span, expr: base_expr, field_ident} => {
+                match base_expr.ty.borrow().as_ref().unwrap() {
+                    Ty::DrawShader(_) => self.fields.get(&field_ident.0).cloned().ok_or_else( || {
+                        interpret_error(*span, format!("field `{}` is not defined on the draw shader", field_ident))
+                    }),
+                    Ty::Struct(struct_ptr) => {
+                        let index = self.struct_field_index(*struct_ptr, *field_ident);
+                        match self.eval_expr(frame, base_expr) ? {
+                            ShaderValue::Struct(_, mut values) => Ok(values.swap_remove(index)),
+                            value => Err(interpret_error(*span, format!("expected a struct, got {:?}", value)))
+                        }
+                    }
+                    _ => {
+                        let value = self.eval_expr(frame, base_expr) ?;
+                        let comps = value.to_comps().unwrap();
+                        let swizzle = Swizzle::parse(*field_ident).unwrap();
+                        let v: Vec<f64> = swizzle.iter().map( | index | comps.v[*index]).collect();
+                        Ok(Comps::from_slice(ty_lit_with_len(comps.ty_lit, v.len()), &v).to_value())
+                    }
+                }
+            }
+            ExprKind::Index {span, expr: base_expr, index_expr} => {
+                let value = self.eval_expr(frame, base_expr) ?;
+                let index = self.eval_index(frame, index_expr) ?;
+                value.index(index).ok_or_else( || interpret_error(*span, format!("cannot index {:?} with {}", value, index)))
+            }
+            ExprKind::MethodCall {span, ident, arg_exprs, closure_site_index} => {
+                let fn_def = match arg_exprs[0].ty.borrow().as_ref().unwrap() {
+                    Ty::Struct(struct_ptr) => self.shader_registry.struct_method_decl_from_ident(
+                        self.shader_registry.structs.get(struct_ptr).unwrap(),
+                        *ident
+                    ),
+                    Ty::DrawShader(shader_ptr) => self.shader_registry.draw_shader_method_decl_from_ident(
+                        self.shader_registry.draw_shader_defs.get(shader_ptr).unwrap(),
+                        *ident
+                    ),
+                    _ => None
+                }.ok_or_else( || interpret_error(*span, format!("method `{}` not found", ident))) ?;
+                self.eval_call(frame, fn_def, arg_exprs, closure_site_index)
+            }
+            ExprKind::PlainCall {span, fn_ptr, arg_exprs, closure_site_index, param_index, ..} => {
+                if let Some(param_index) = param_index.get() {
+                    self.eval_closure_call(frame, *span, param_index, arg_exprs)
+                }
+                else {
+                    let fn_def = self.shader_registry.all_fns.get(&fn_ptr.unwrap()).unwrap();
+                    self.eval_call(frame, fn_def, arg_exprs, closure_site_index)
+                }
+            }
+            ExprKind::BuiltinCall {span, ident, arg_exprs} => {
+                let mut args = Vec::new();
+                for arg_expr in arg_exprs {
+                    args.push(self.eval_expr(frame, arg_expr) ?);
+                }
+                self.eval_builtin(*span, *ident, &args)
+            }
+            ExprKind::ClosureDef(_) => Ok(ShaderValue::Void),
+            ExprKind::ConsCall {span, ty_lit, arg_exprs} => {
+                let mut args = Vec::new();
+                for arg_expr in arg_exprs {
+                    args.push(self.eval_expr(frame, arg_expr) ?);
+                }
+                construct(*ty_lit, &args).ok_or_else( || interpret_error(*span, format!("cannot construct {} from {:?}", ty_lit, args)))
+            }
+            ExprKind::ArrayCons {arg_exprs, ..} => {
+                let mut values = Vec::new();
+                for arg_expr in arg_exprs {
+                    values.push(self.eval_expr(frame, arg_expr) ?);
+                }
+                Ok(ShaderValue::Array(values))
+            }
+            ExprKind::StructCons {struct_ptr, args, ..} => {
+                let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+                let mut values = Vec::new();
+                for field in &struct_def.fields {
+                    let (_, arg_expr) = args.iter().find( | (ident, _) | *ident == field.ident).unwrap();
+                    values.push(self.eval_expr(frame, arg_expr) ?);
+                }
+                Ok(ShaderValue::Struct(*struct_ptr, values))
+            }
+            ExprKind::Var {span, kind, ..} => {
+                match kind.get().unwrap() {
+                    VarKind::Local {ident, shadow} | VarKind::MutLocal {ident, shadow} => {
+                        if let Some(Ty::DrawShader(_)) = expr.ty.borrow().as_ref() {
+                            return Ok(ShaderValue::Void)
+                        }
+                        frame.locals.get(&(ident, shadow)).cloned().ok_or_else( || {
+                            interpret_error(*span, format!("variable `{}` is not set", ident))
+                        })
+                    }
+                    VarKind::LiveValue(value_ptr) => self.live_value(value_ptr, expr.ty.borrow().as_ref().unwrap(), *span)
+                }
+            }
+            ExprKind::Lit {lit, ..} => Ok(match lit {
+                Lit::Bool(v) => ShaderValue::Bool(*v),
+                Lit::Int(v) => ShaderValue::Int(*v),
+                Lit::Float(v) => ShaderValue::Float(*v),
+                Lit::Color(v) => ShaderValue::Vec4(Vec4::from_u32(*v)),
+            })
+        }
+    }
+
+    fn eval_index(&mut self, frame: &mut Frame<'a>, index_expr: &'a Expr) -> Result<usize, LiveError> {
+        match self.eval_expr(frame, index_expr) ? {
+            ShaderValue::Int(v) if v >= 0 => Ok(v as usize),
+            ShaderValue::Uint(v) => Ok(v as usize),
+            ShaderValue::Float(v) if v >= 0.0 => Ok(v as usize),
+            value => Err(interpret_error(index_expr.span, format!("invalid index {:?}", value)))
+        }
+    }
+
+    fn struct_field_index(&self, struct_ptr: StructPtr, field_ident: Ident) -> usize {
+        let struct_def = self.shader_registry.structs.get(&struct_ptr).unwrap();
+        struct_def.fields.iter().position( | field | field.ident == field_ident).unwrap()
+    }
+
+    fn eval_bin_expr(&mut self, frame: &mut Frame<'a>, span: TokenSpan, op: BinOp, left_expr: &'a Expr, right_expr: &'a Expr) -> Result<ShaderValue, LiveError> {
+        let arith_op = match op {
+            BinOp::Assign => {
+                let value = self.eval_expr(frame, right_expr) ?;
+                self.assign(frame, left_expr, value.clone()) ?;
+                return Ok(value)
+            }
+            BinOp::AddAssign => Some(BinOp::Add),
+            BinOp::SubAssign => Some(BinOp::Sub),
+            BinOp::MulAssign => Some(BinOp::Mul),
+            BinOp::DivAssign => Some(BinOp::Div),
+            BinOp::Or => {
+                return Ok(ShaderValue::Bool(self.eval_bool(frame, left_expr) ? || self.eval_bool(frame, right_expr) ?))
+            }
+            BinOp::And => {
+                return Ok(ShaderValue::Bool(self.eval_bool(frame, left_expr) ? && self.eval_bool(frame, right_expr) ?))
+            }
+            _ => None
+        };
+        let left = self.eval_expr(frame, left_expr) ?;
+        let right = self.eval_expr(frame, right_expr) ?;
+        if let Some(arith_op) = arith_op {
+            let value = binary_op(arith_op, &left, &right).ok_or_else( || {
+                interpret_error(span, format!("cannot apply {} to {:?} and {:?}", op, left, right))
+            }) ?;
+            self.assign(frame, left_expr, value.clone()) ?;
+            return Ok(value)
+        }
+        binary_op(op, &left, &right).ok_or_else( || {
+            interpret_error(span, format!("cannot apply {} to {:?} and {:?}", op, left, right))
+        })
+    }
+
+    fn assign(&mut self, frame: &mut Frame<'a>, expr: &'a Expr, value: ShaderValue) -> Result<(), LiveError> {
+        match &expr.kind {
+            ExprKind::Var {kind, ..} => {
+                if let Some(VarKind::Local {ident, shadow}) | Some(VarKind::MutLocal {ident, shadow}) = kind.get() {
+                    frame.locals.insert((ident, shadow), value);
+                    return Ok(())
+                }
+            }
+            ExprKind::Field {expr: base_expr, field_ident, ..} => {
+                match base_expr.ty.borrow().as_ref().unwrap() {
+                    Ty::DrawShader(_) => {
+                        self.fields.insert(field_ident.0, value);
+                        return Ok(())
+                    }
+                    Ty::Struct(struct_ptr) => {
+                        let index = self.struct_field_index(*struct_ptr, *field_ident);
+                        let mut base = self.eval_expr(frame, base_expr) ?;
+                        if let ShaderValue::Struct(_, values) = &mut base {
+                            values[index] = value;
+                        }
+                        return self.assign(frame, base_expr, base)
+                    }
+                    _ => {
+                        let base = self.eval_expr(frame, base_expr) ?;
+                        let mut comps = base.to_comps().unwrap();
+                        let value = value.to_comps().unwrap();
+                        for (i, index) in Swizzle::parse(*field_ident).unwrap().iter().enumerate() {
+                            comps.v[*index] = value.v[i];
+                        }
+                        return self.assign(frame, base_expr, comps.to_value())
+                    }
+                }
+            }
+            ExprKind::Index {span, expr: base_expr, index_expr} => {
+                let index = self.eval_index(frame, index_expr) ?;
+                let mut base = self.eval_expr(frame, base_expr) ?;
+                if !base.set_index(index, value) {
+                    return Err(interpret_error(*span, format!("cannot assign to index {} of {:?}", index, base)))
+                }
+                return self.assign(frame, base_expr, base)
+            }
+            _ => ()
+        }
+        Err(interpret_error(expr.span, "expression is not assignable".to_string()))
+    }
+
+    fn eval_call(&mut self, frame: &mut Frame<'a>, fn_def: &'a FnDef, arg_exprs: &'a [Expr], closure_site_index: &Cell<Option<usize >>) -> Result<ShaderValue, LiveError> {
+        // args line up with the end of the params, like generate_arg_expr
+        let offset = fn_def.params.len() - arg_exprs.len();
+        let mut args = vec![None; offset];
+        for arg_expr in arg_exprs {
+            args.push(match arg_expr.ty.borrow().as_ref().unwrap() {
+                Ty::ClosureDef(_) | Ty::DrawShader(_) => None,
+                _ => Some(self.eval_expr(frame, arg_expr) ?)
+            });
+        }
+        let mut closures = BTreeMap::new();
+        if let Some(closure_site_index) = closure_site_index.get() {
+            let closure_sites = frame.fn_def.closure_sites.borrow();
+            let captured = Rc::new(frame.locals.clone());
+            for closure_arg in &closure_sites.as_ref().unwrap()[closure_site_index].closure_args {
+                closures.insert(closure_arg.param_index, ClosureValue {
+                    def_fn: frame.fn_def,
+                    closure_def_index: closure_arg.closure_def_index,
+                    captured: captured.clone(),
+                    closures: frame.closures.clone(),
+                });
+            }
+        }
+        let (value, inouts) = self.call_fn(fn_def, args, closures) ?;
+        for (param_index, inout_value) in inouts {
+            self.assign(frame, &arg_exprs[param_index - offset], inout_value) ?;
+        }
+        Ok(value)
+    }
+
+    fn eval_closure_call(&mut self, frame: &mut Frame<'a>, span: TokenSpan, param_index: usize, arg_exprs: &'a [Expr]) -> Result<ShaderValue, LiveError> {
+        let closure = frame.closures.get(&param_index).cloned().ok_or_else( || {
+            interpret_error(span, "closure argument is not available here".to_string())
+        }) ?;
+        let closure_def = &closure.def_fn.closure_defs[closure.closure_def_index.0];
+        let mut locals = (*closure.captured).clone();
+        for (param, arg_expr) in closure_def.params.iter().zip(arg_exprs) {
+            let value = self.eval_expr(frame, arg_expr) ?;
+            locals.insert((param.ident, param.shadow.get().unwrap()), value);
+        }
+        let mut closure_frame = Frame {
+            fn_def: closure.def_fn,
+            locals,
+            closures: closure.closures.clone(),
+        };
+        match &closure_def.kind {
+            ClosureDefKind::Expr(expr) => self.eval_expr(&mut closure_frame, expr),
+            ClosureDefKind::Block(block) => match self.exec_block(&mut closure_frame, block) ? {
+                Flow::Return(value) => Ok(value),
+                _ => Ok(ShaderValue::Void)
+            }
+        }
+    }
+
+    fn eval_builtin(&self, span: TokenSpan, ident: Ident, args: &[ShaderValue]) -> Result<ShaderValue, LiveError> {
+        let err = || interpret_error(span, format!("builtin `{}` cannot be called with {:?}", ident, args));
+
+        if let live_id!(sample2d) | live_id!(sample2d_rt) | live_id!(sample2dOES) = ident.0 {
+            return match (&args[0], args[1].to_vec2()) {
+                (ShaderValue::Texture(tex), Some(pos)) => Ok(ShaderValue::Vec4(
+                    self.textures.get(&tex.0).map( | texture | texture.sample(pos)).unwrap_or_default()
+                )),
+                _ => Err(err())
+            }
+        }
+        if ident.0 == live_id!(sampleCube) {
+            return match (&args[0], args[1].to_vec3()) {
+                (ShaderValue::Texture(tex), Some(dir)) => Ok(ShaderValue::Vec4(
+                    self.textures.get(&tex.0).map( | texture | texture.sample_cube(dir)).unwrap_or_default()
+                )),
+                _ => Err(err())
+            }
+        }
+        let comps: Vec<Comps> = args.iter().map( | arg | arg.to_comps()).collect::<Option<_ >>().ok_or_else(err) ?;
+        let float = | v: f64 | v as f32;
+        let value = match (ident.0, comps.as_slice()) {
+            (live_id!(abs), [x]) => x.map( | v | v.abs()),
+            (live_id!(sign), [x]) => x.map( | v | if v > 0.0 {1.0} else if v < 0.0 {-1.0} else {0.0}),
+            (live_id!(acos), [x]) => x.map_f32(f32::acos),
+            (live_id!(asin), [x]) => x.map_f32(f32::asin),
+            (live_id!(atan), [x]) => x.map_f32(f32::atan),
+            (live_id!(atan), [y, x]) => Comps::zip2(y, x, | y, x | float(y).atan2(float(x)) as f64).ok_or_else(err) ?,
+            (live_id!(ceil), [x]) => x.map_f32(f32::ceil),
+            (live_id!(floor), [x]) => x.map_f32(f32::floor),
+            (live_id!(fract), [x]) => x.map_f32( | v | v - v.floor()),
+            (live_id!(cos), [x]) => x.map_f32(f32::cos),
+            (live_id!(sin), [x]) => x.map_f32(f32::sin),
+            (live_id!(tan), [x]) => x.map_f32(f32::tan),
+            (live_id!(degrees), [x]) => x.map_f32(f32::to_degrees),
+            (live_id!(radians), [x]) => x.map_f32(f32::to_radians),
+            (live_id!(exp), [x]) => x.map_f32(f32::exp),
+            (live_id!(exp2), [x]) => x.map_f32(f32::exp2),
+            (live_id!(log), [x]) => x.map_f32(f32::ln),
+            (live_id!(log2), [x]) => x.map_f32(f32::log2),
+            (live_id!(sqrt), [x]) => x.map_f32(f32::sqrt),
+            (live_id!(inversesqrt), [x]) => x.map_f32( | v | 1.0 / v.sqrt()),
+            // a single invocation has no neighbours to take derivatives against
+            (live_id!(dFdx), [x]) | (live_id!(dFdy), [x]) => x.map( | _ | 0.0),
+            (live_id!(pow), [x, y]) => Comps::zip2(x, y, | x, y | float(x).powf(float(y)) as f64).ok_or_else(err) ?,
+            (live_id!(max), [x, y]) => Comps::zip2(x, y, f64::max).ok_or_else(err) ?,
+            (live_id!(min), [x, y]) => Comps::zip2(x, y, f64::min).ok_or_else(err) ?,
+            (live_id!(mod), [x, y]) => Comps::zip2(x, y, | x, y | {
+                let (x, y) = (float(x), float(y));
+                (x - y * (x / y).floor()) as f64
+            }).ok_or_else(err) ?,
+            (live_id!(step), [edge, x]) => Comps::zip2(edge, x, | edge, x | if x < edge {0.0} else {1.0}).ok_or_else(err) ?,
+            (live_id!(clamp), [x, lo, hi]) => Comps::zip3(x, lo, hi, | x, lo, hi | x.max(lo).min(hi)).ok_or_else(err) ?,
+            (live_id!(mix), [x, y, a]) => Comps::zip3(x, y, a, | x, y, a | {
+                let (x, y, a) = (float(x), float(y), float(a));
+                (x * (1.0 - a) + y * a) as f64
+            }).ok_or_else(err) ?,
+            (live_id!(smoothstep), [e0, e1, x]) => Comps::zip3(e0, e1, x, | e0, e1, x | {
+                let t = ((float(x) - float(e0)) / (float(e1) - float(e0))).clamp(0.0, 1.0);
+                (t * t * (3.0 - 2.0 * t)) as f64
+            }).ok_or_else(err) ?,
+            (live_id!(length), [x]) => Comps::scalar(x.dot(x).sqrt()),
+            (live_id!(distance), [x, y]) => {
+                let d = Comps::zip2(x, y, | x, y | x - y).ok_or_else(err) ?;
+                Comps::scalar(d.dot(&d).sqrt())
+            }
+            (live_id!(dot), [x, y]) => Comps::scalar(x.dot(y)),
+            (live_id!(normalize), [x]) => {
+                let len = x.dot(x).sqrt() as f32;
+                x.map_f32( | v | v / len)
+            }
+            (live_id!(cross), [x, y]) => {
+                let (x, y) = (&x.v, &y.v);
+                Comps::from_slice(TyLit::Vec3, &[
+                    x[1] * y[2] - x[2] * y[1],
+                    x[2] * y[0] - x[0] * y[2],
+                    x[0] * y[1] - x[1] * y[0],
+                ])
+            }
+            (live_id!(faceforward), [n, i, nref]) => {
+                if nref.dot(i) < 0.0 {*n} else {n.map( | v | -v)}
+            }
+            (live_id!(reflect), [i, n]) => {
+                let d = n.dot(i);
+                Comps::zip2(i, n, | i, n | i - 2.0 * d * n).ok_or_else(err) ?
+            }
+            (live_id!(refract), [i, n, eta]) => {
+                let d = n.dot(i);
+                let eta = eta.v[0];
+                let k = 1.0 - eta * eta * (1.0 - d * d);
+                if k < 0.0 {
+                    i.map( | _ | 0.0)
+                }
+                else {
+                    Comps::zip2(i, n, | i, n | eta * i - (eta * d + k.sqrt()) * n).ok_or_else(err) ?
+                }
+            }
+            (live_id!(lessThan), [x, y]) => Comps::compare(x, y, | x, y | x < y),
+            (live_id!(lessThanEqual), [x, y]) => Comps::compare(x, y, | x, y | x <= y),
+            (live_id!(greaterThan), [x, y]) => Comps::compare(x, y, | x, y | x > y),
+            (live_id!(greaterThanEqual), [x, y]) => Comps::compare(x, y, | x, y | x >= y),
+            (live_id!(equal), [x, y]) => Comps::compare(x, y, | x, y | x == y),
+            (live_id!(notEqual), [x, y]) => Comps::compare(x, y, | x, y | x != y),
+            (live_id!(not), [x]) => x.map( | v | if v != 0.0 {0.0} else {1.0}),
+            (live_id!(all), [x]) => Comps::from_slice(TyLit::Bool, &[if x.v[0..x.len].iter().all( | v | *v != 0.0) {1.0} else {0.0}]),
+            (live_id!(any), [x]) => Comps::from_slice(TyLit::Bool, &[if x.v[0..x.len].iter().any( | v | *v != 0.0) {1.0} else {0.0}]),
+            (live_id!(matrixCompMult), [x, y]) => Comps::zip2(x, y, | x, y | x * y).ok_or_else(err) ?,
+            (live_id!(transpose), [x]) => x.transpose().ok_or_else(err) ?,
+            (live_id!(inverse), [x]) => x.inverse().ok_or_else(err) ?,
+            _ => return Err(err())
+        };
+        Ok(value.to_value())
+    }
+}
+
+impl ShaderTexture {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            layers: 1,
+            pixels: vec![Vec4::default(); width * height]
+        }
+    }
+
+    pub fn new_cube(size: usize) -> Self {
+        Self {
+            width: size,
+            height: size,
+            layers: 6,
+            pixels: vec![Vec4::default(); size * size * 6]
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Vec4 {
+        self.texel(x, y, 0)
+    }
+
+    pub fn texel(&self, x: usize, y: usize, layer: usize) -> Vec4 {
+        self.pixels[(layer * self.height + y) * self.width + x]
+    }
+
+    pub fn sample(&self, pos: Vec2) -> Vec4 {
+        self.sample_layer(pos, 0)
+    }
+
+    /// picks the face on the major axis of dir, with the face orientations of the gl spec
+    pub fn sample_cube(&self, dir: Vec3) -> Vec4 {
+        if self.layers < 6 {
+            return Vec4::default()
+        }
+        let (ax, ay, az) = (dir.x.abs(), dir.y.abs(), dir.z.abs());
+        let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+            if dir.x >= 0.0 {(0, -dir.z, -dir.y, ax)} else {(1, dir.z, -dir.y, ax)}
+        }
+        else if ay >= az {
+            if dir.y >= 0.0 {(2, dir.x, dir.z, ay)} else {(3, dir.x, -dir.z, ay)}
+        }
+        else if dir.z >= 0.0 {(4, dir.x, -dir.y, az)} else {(5, -dir.x, -dir.y, az)};
+        if ma == 0.0 {
+            return Vec4::default()
+        }
+        self.sample_layer(vec2((sc / ma + 1.0) * 0.5, (tc / ma + 1.0) * 0.5), face)
+    }
+
+    fn sample_layer(&self, pos: Vec2, layer: usize) -> Vec4 {
+        if self.width == 0 || self.height == 0 || layer >= self.layers {
+            return Vec4::default()
+        }
+        let x = pos.x * self.width as f32 - 0.5;
+        let y = pos.y * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let clamp_x = | x: f32 | (x.max(0.0) as usize).min(self.width - 1);
+        let clamp_y = | y: f32 | (y.max(0.0) as usize).min(self.height - 1);
+        let (ax, bx, ay, by) = (clamp_x(x0), clamp_x(x0 + 1.0), clamp_y(y0), clamp_y(y0 + 1.0));
+        let top = self.texel(ax, ay, layer) * (1.0 - fx) + self.texel(bx, ay, layer) * fx;
+        let bottom = self.texel(ax, by, layer) * (1.0 - fx) + self.texel(bx, by, layer) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+}
+
+impl ShaderValue {
+    pub fn to_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    pub fn to_int(&self) -> Option<i32> {
+        match self {
+            Self::Int(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    pub fn to_uint(&self) -> Option<u32> {
+        match self {
+            Self::Uint(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    pub fn to_float(&self) -> Option<f32> {
+        match self {
+            Self::Float(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    pub fn to_vec2(&self) -> Option<Vec2> {
+        match self {
+            Self::Vec2(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    pub fn to_vec3(&self) -> Option<Vec3> {
+        match self {
+            Self::Vec3(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    pub fn to_vec4(&self) -> Option<Vec4> {
+        match self {
+            Self::Vec4(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    pub fn to_mat4(&self) -> Option<Mat4> {
+        match self {
+            Self::Mat4(v) => Some(*v),
+            _ => None
+        }
+    }
+
+    fn to_comps(&self) -> Option<Comps> {
+        let bools = | ty_lit, v: &[bool] | Comps::from_slice(ty_lit, &v.iter().map( | v | if *v {1.0} else {0.0}).collect::<Vec<_ >>());
+        let ints = | ty_lit, v: &[i32] | Comps::from_slice(ty_lit, &v.iter().map( | v | *v as f64).collect::<Vec<_ >>());
+        let floats = | ty_lit, v: &[f32] | Comps::from_slice(ty_lit, &v.iter().map( | v | *v as f64).collect::<Vec<_ >>());
+        Some(match self {
+            Self::Bool(v) => bools(TyLit::Bool, &[*v]),
+            Self::Int(v) => ints(TyLit::Int, &[*v]),
+            Self::Uint(v) => Comps::from_slice(TyLit::Uint, &[*v as f64]),
+            Self::Float(v) => floats(TyLit::Float, &[*v]),
+            Self::Bvec2(v) => bools(TyLit::Bvec2, v),
+            Self::Bvec3(v) => bools(TyLit::Bvec3, v),
+            Self::Bvec4(v) => bools(TyLit::Bvec4, v),
+            Self::Ivec2(v) => ints(TyLit::Ivec2, v),
+            Self::Ivec3(v) => ints(TyLit::Ivec3, v),
+            Self::Ivec4(v) => ints(TyLit::Ivec4, v),
+            Self::Vec2(v) => floats(TyLit::Vec2, &[v.x, v.y]),
+            Self::Vec3(v) => floats(TyLit::Vec3, &[v.x, v.y, v.z]),
+            Self::Vec4(v) => floats(TyLit::Vec4, &[v.x, v.y, v.z, v.w]),
+            Self::Mat2(v) => floats(TyLit::Mat2, v),
+            Self::Mat3(v) => floats(TyLit::Mat3, v),
+            Self::Mat4(v) => floats(TyLit::Mat4, &v.v),
+            _ => return None
+        })
+    }
+
+    fn index(&self, index: usize) -> Option<ShaderValue> {
+        if let Self::Array(values) = self {
+            return values.get(index).cloned()
+        }
+        let comps = self.to_comps() ?;
+        let dim = mat_dim(comps.ty_lit);
+        if dim > 0 {
+            if index >= dim {
+                return None
+            }
+            let column = &comps.v[index * dim..(index + 1) * dim];
+            return Some(Comps::from_slice(ty_lit_with_len(TyLit::Float, dim), column).to_value())
+        }
+        if index >= comps.len {
+            return None
+        }
+        Some(Comps::from_slice(ty_lit_with_len(comps.ty_lit, 1), &[comps.v[index]]).to_value())
+    }
+
+    fn set_index(&mut self, index: usize, value: ShaderValue) -> bool {
+        if let Self::Array(values) = self {
+            if let Some(slot) = values.get_mut(index) {
+                *slot = value;
+                return true
+            }
+            return false
+        }
+        if let (Some(mut comps), Some(value)) = (self.to_comps(), value.to_comps()) {
+            let dim = mat_dim(comps.ty_lit);
+            let (start, len) = if dim > 0 {(index * dim, dim)} else {(index, 1)};
+            if start + len > comps.len || value.len != len {
+                return false
+            }
+            comps.v[start..start + len].copy_from_slice(&value.v[0..len]);
+            *self = comps.to_value();
+            return true
+        }
+        false
+    }
+}
+
+impl From<bool> for ShaderValue {fn from(v: bool) -> Self {Self::Bool(v)}}
+impl From<i32> for ShaderValue {fn from(v: i32) -> Self {Self::Int(v)}}
+impl From<u32> for ShaderValue {fn from(v: u32) -> Self {Self::Uint(v)}}
+impl From<f32> for ShaderValue {fn from(v: f32) -> Self {Self::Float(v)}}
+impl From<Vec2> for ShaderValue {fn from(v: Vec2) -> Self {Self::Vec2(v)}}
+impl From<Vec3> for ShaderValue {fn from(v: Vec3) -> Self {Self::Vec3(v)}}
+impl From<Vec4> for ShaderValue {fn from(v: Vec4) -> Self {Self::Vec4(v)}}
+impl From<Mat4> for ShaderValue {fn from(v: Mat4) -> Self {Self::Mat4(v)}}
+
+// the components of a scalar, vector or matrix value. f64 holds every i32 exactly,
+// float math is done in f32 to stay close to what the gpu computes
+#[derive(Clone, Copy, Debug)]
+struct Comps {
+    ty_lit: TyLit,
+    len: usize,
+    v: [f64; 16],
+}
+
+fn ty_lit_len(ty_lit: TyLit) -> usize {
+    match ty_lit {
+        TyLit::Bool | TyLit::Int | TyLit::Uint | TyLit::Float => 1,
+        TyLit::Bvec2 | TyLit::Ivec2 | TyLit::Vec2 => 2,
+        TyLit::Bvec3 | TyLit::Ivec3 | TyLit::Vec3 => 3,
+        TyLit::Bvec4 | TyLit::Ivec4 | TyLit::Vec4 | TyLit::Mat2 => 4,
+        TyLit::Mat3 => 9,
+        TyLit::Mat4 => 16,
+        TyLit::Texture2D | TyLit::TextureOES | TyLit::TextureCube => 0,
+    }
+}
+
+fn mat_dim(ty_lit: TyLit) -> usize {
+    match ty_lit {
+        TyLit::Mat2 => 2,
+        TyLit::Mat3 => 3,
+        TyLit::Mat4 => 4,
+        _ => 0
+    }
+}
+
+// the scalar or vector type with the same component kind as ty_lit
+fn ty_lit_with_len(ty_lit: TyLit, len: usize) -> TyLit {
+    match (ty_lit, len) {
+        (TyLit::Bool | TyLit::Bvec2 | TyLit::Bvec3 | TyLit::Bvec4, 1) => TyLit::Bool,
+        (TyLit::Bool | TyLit::Bvec2 | TyLit::Bvec3 | TyLit::Bvec4, 2) => TyLit::Bvec2,
+        (TyLit::Bool | TyLit::Bvec2 | TyLit::Bvec3 | TyLit::Bvec4, 3) => TyLit::Bvec3,
+        (TyLit::Bool | TyLit::Bvec2 | TyLit::Bvec3 | TyLit::Bvec4, _) => TyLit::Bvec4,
+        (TyLit::Int | TyLit::Ivec2 | TyLit::Ivec3 | TyLit::Ivec4, 1) => TyLit::Int,
+        (TyLit::Int | TyLit::Ivec2 | TyLit::Ivec3 | TyLit::Ivec4, 2) => TyLit::Ivec2,
+        (TyLit::Int | TyLit::Ivec2 | TyLit::Ivec3 | TyLit::Ivec4, 3) => TyLit::Ivec3,
+        (TyLit::Int | TyLit::Ivec2 | TyLit::Ivec3 | TyLit::Ivec4, _) => TyLit::Ivec4,
+        (TyLit::Uint, 1) => TyLit::Uint,
+        (_, 1) => TyLit::Float,
+        (_, 2) => TyLit::Vec2,
+        (_, 3) => TyLit::Vec3,
+        (_, _) => TyLit::Vec4,
+    }
+}
+
+fn is_float(ty_lit: TyLit) -> bool {
+    ty_lit_with_len(ty_lit, 1) == TyLit::Float
+}
+
+fn is_int(ty_lit: TyLit) -> bool {
+    ty_lit_with_len(ty_lit, 1) == TyLit::Int
+}
+
+fn is_integer(ty_lit: TyLit) -> bool {
+    is_int(ty_lit) || ty_lit == TyLit::Uint
+}
+
+impl Comps {
+    fn from_slice(ty_lit: TyLit, v: &[f64]) -> Self {
+        let mut comps = Self {ty_lit, len: ty_lit_len(ty_lit), v: [0.0; 16]};
+        comps.v[0..v.len()].copy_from_slice(v);
+        comps
+    }
+
+    fn scalar(v: f64) -> Self {
+        Self::from_slice(TyLit::Float, &[v as f32 as f64])
+    }
+
+    fn to_value(self) -> ShaderValue {
+        let v = &self.v;
+        let b = | i: usize | v[i] != 0.0;
+        let i = | i: usize | v[i] as i32;
+        let f = | i: usize | v[i] as f32;
+        match self.ty_lit {
+            TyLit::Bool => ShaderValue::Bool(b(0)),
+            TyLit::Int => ShaderValue::Int(i(0)),
+            TyLit::Uint => ShaderValue::Uint(v[0] as u32),
+            TyLit::Float => ShaderValue::Float(f(0)),
+            TyLit::Bvec2 => ShaderValue::Bvec2([b(0), b(1)]),
+            TyLit::Bvec3 => ShaderValue::Bvec3([b(0), b(1), b(2)]),
+            TyLit::Bvec4 => ShaderValue::Bvec4([b(0), b(1), b(2), b(3)]),
+            TyLit::Ivec2 => ShaderValue::Ivec2([i(0), i(1)]),
+            TyLit::Ivec3 => ShaderValue::Ivec3([i(0), i(1), i(2)]),
+            TyLit::Ivec4 => ShaderValue::Ivec4([i(0), i(1), i(2), i(3)]),
+            TyLit::Vec2 => ShaderValue::Vec2(Vec2 {x: f(0), y: f(1)}),
+            TyLit::Vec3 => ShaderValue::Vec3(Vec3 {x: f(0), y: f(1), z: f(2)}),
+            TyLit::Vec4 => ShaderValue::Vec4(Vec4 {x: f(0), y: f(1), z: f(2), w: f(3)}),
+            TyLit::Mat2 => ShaderValue::Mat2([f(0), f(1), f(2), f(3)]),
+            TyLit::Mat3 => ShaderValue::Mat3([f(0), f(1), f(2), f(3), f(4), f(5), f(6), f(7), f(8)]),
+            TyLit::Mat4 => {
+                let mut m = Mat4 {v: [0.0; 16]};
+                for (index, v) in m.v.iter_mut().enumerate() {
+                    *v = f(index);
+                }
+                ShaderValue::Mat4(m)
+            }
+            TyLit::Texture2D | TyLit::TextureOES | TyLit::TextureCube => ShaderValue::Void,
+        }
+    }
+
+    fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        let mut out = *self;
+        for v in &mut out.v[0..self.len] {
+            *v = f(*v);
+        }
+        out
+    }
+
+    fn map_f32(&self, f: impl Fn(f32) -> f32) -> Self {
+        self.map( | v | f(v as f32) as f64)
+    }
+
+    // combines components, a scalar is repeated to match the other side
+    fn zip2(a: &Self, b: &Self, f: impl Fn(f64, f64) -> f64) -> Option<Self> {
+        let out_ty = if a.len >= b.len {a.ty_lit} else {b.ty_lit};
+        let len = a.len.max(b.len);
+        if (a.len != len && a.len != 1) || (b.len != len && b.len != 1) {
+            return None
+        }
+        let mut out = Self {ty_lit: out_ty, len, v: [0.0; 16]};
+        for i in 0..len {
+            out.v[i] = f(a.v[if a.len == 1 {0} else {i}], b.v[if b.len == 1 {0} else {i}]);
+        }
+        Some(out.round())
+    }
+
+    fn zip3(a: &Self, b: &Self, c: &Self, f: impl Fn(f64, f64, f64) -> f64) -> Option<Self> {
+        let out_ty = [a, b, c].iter().max_by_key( | comps | comps.len).unwrap().ty_lit;
+        let len = a.len.max(b.len).max(c.len);
+        if [a, b, c].iter().any( | comps | comps.len != len && comps.len != 1) {
+            return None
+        }
+        let mut out = Self {ty_lit: out_ty, len, v: [0.0; 16]};
+        let get = | comps: &Self, i: usize | comps.v[if comps.len == 1 {0} else {i}];
+        for i in 0..len {
+            out.v[i] = f(get(a, i), get(b, i), get(c, i));
+        }
+        Some(out.round())
+    }
+
+    fn compare(a: &Self, b: &Self, f: impl Fn(f64, f64) -> bool) -> Self {
+        let mut out = Self {ty_lit: ty_lit_with_len(TyLit::Bool, a.len), len: a.len, v: [0.0; 16]};
+        for i in 0..a.len {
+            out.v[i] = if f(a.v[i], b.v[i]) {1.0} else {0.0};
+        }
+        out
+    }
+
+    // keeps float results at f32 precision and int results wrapped to i32 or u32
+    fn round(mut self) -> Self {
+        for v in &mut self.v[0..self.len] {
+            if is_float(self.ty_lit) {
+                *v = *v as f32 as f64;
+            }
+            else if is_int(self.ty_lit) {
+                *v = (*v as i64) as i32 as f64;
+            }
+            else if self.ty_lit == TyLit::Uint {
+                *v = (*v as i64) as u32 as f64;
+            }
+        }
+        self
+    }
+
+    fn dot(&self, other: &Self) -> f64 {
+        let mut sum = 0.0f32;
+        for i in 0..self.len.min(other.len) {
+            sum += self.v[i] as f32 * other.v[i] as f32;
+        }
+        sum as f64
+    }
+
+    fn at(&self, column: usize, row: usize) -> f64 {
+        self.v[column * mat_dim(self.ty_lit) + row]
+    }
+
+    fn transpose(&self) -> Option<Self> {
+        let dim = mat_dim(self.ty_lit);
+        if dim == 0 {
+            return None
+        }
+        let mut out = *self;
+        for column in 0..dim {
+            for row in 0..dim {
+                out.v[column * dim + row] = self.at(row, column);
+            }
+        }
+        Some(out)
+    }
+
+    #[allow(clippy::needless_range_loop)]
+    fn inverse(&self) -> Option<Self> {
+        // gauss-jordan on a row major copy
+        let dim = mat_dim(self.ty_lit);
+        if dim == 0 {
+            return None
+        }
+        let mut m = [[0.0f64; 8]; 4];
+        for row in 0..dim {
+            for column in 0..dim {
+                m[row][column] = self.at(column, row);
+                m[row][dim + column] = if row == column {1.0} else {0.0};
+            }
+        }
+        for column in 0..dim {
+            let pivot = (column..dim).max_by( | a, b | m[*a][column].abs().partial_cmp(&m[*b][column].abs()).unwrap()).unwrap();
+            if m[pivot][column] == 0.0 {
+                return None
+            }
+            m.swap(column, pivot);
+            let scale = m[column][column];
+            for v in &mut m[column][0..dim * 2] {
+                *v /= scale;
+            }
+            for row in 0..dim {
+                if row != column {
+                    let factor = m[row][column];
+                    for i in 0..dim * 2 {
+                        m[row][i] -= factor * m[column][i];
+                    }
+                }
+            }
+        }
+        let mut out = *self;
+        for row in 0..dim {
+            for column in 0..dim {
+                out.v[column * dim + row] = m[row][dim + column];
+            }
+        }
+        Some(out.round())
+    }
+}
+
+fn binary_op(op: BinOp, left: &ShaderValue, right: &ShaderValue) -> Option<ShaderValue> {
+    if let BinOp::Eq | BinOp::Ne = op {
+        return Some(ShaderValue::Bool((left == right) == matches!(op, BinOp::Eq)))
+    }
+    let (a, b) = (left.to_comps() ?, right.to_comps() ?);
+    let cmp = | f: fn(f64, f64) -> bool | Some(ShaderValue::Bool(f(a.v[0], b.v[0])));
+    let int = is_integer(a.ty_lit);
+    Some(match op {
+        BinOp::Lt => return cmp( | a, b | a < b),
+        BinOp::Le => return cmp( | a, b | a <= b),
+        BinOp::Gt => return cmp( | a, b | a > b),
+        BinOp::Ge => return cmp( | a, b | a >= b),
+        BinOp::Add => Comps::zip2(&a, &b, | a, b | a + b) ?,
+        BinOp::Sub => Comps::zip2(&a, &b, | a, b | a - b) ?,
+        BinOp::Mul => {
+            let (dim_a, dim_b) = (mat_dim(a.ty_lit), mat_dim(b.ty_lit));
+            if dim_a > 0 && dim_b > 0 {
+                // matrix * matrix
+                let mut out = a;
+                for column in 0..dim_a {
+                    for row in 0..dim_a {
+                        out.v[column * dim_a + row] = (0..dim_a).map( | k | a.at(k, row) * b.at(column, k)).sum();
+                    }
+                }
+                out.round()
+            }
+            else if dim_a > 0 && b.len == dim_a {
+                // matrix * column vector
+                let mut out = b;
+                for row in 0..dim_a {
+                    out.v[row] = (0..dim_a).map( | k | a.at(k, row) * b.v[k]).sum();
+                }
+                out.round()
+            }
+            else if dim_b > 0 && a.len == dim_b {
+                // row vector * matrix
+                let mut out = a;
+                for column in 0..dim_b {
+                    out.v[column] = (0..dim_b).map( | k | a.v[k] * b.at(column, k)).sum();
+                }
+                out.round()
+            }
+            else {
+                Comps::zip2(&a, &b, | a, b | a * b) ?
+            }
+        }
+        BinOp::Div if int => Comps::zip2(&a, &b, | a, b | if b == 0.0 {0.0} else {(a / b).trunc()}) ?,
+        BinOp::Div => Comps::zip2(&a, &b, | a, b | (a as f32 / b as f32) as f64) ?,
+        _ => return None
+    }.to_value())
+}
+
+// vector and matrix constructors, following the glsl rules for splatting and resizing
+fn construct(ty_lit: TyLit, args: &[ShaderValue]) -> Option<ShaderValue> {
+    let comps: Vec<Comps> = args.iter().map( | arg | arg.to_comps()).collect::<Option<_ >>() ?;
+    let len = ty_lit_len(ty_lit);
+    let dim = mat_dim(ty_lit);
+    let mut out = Comps {ty_lit, len, v: [0.0; 16]};
+    match comps.as_slice() {
+        [single] if single.len == 1 => {
+            if dim > 0 {
+                for i in 0..dim {
+                    out.v[i * dim + i] = single.v[0];
+                }
+            }
+            else {
+                for i in 0..len {
+                    out.v[i] = single.v[0];
+                }
+            }
+        }
+        [single] if dim > 0 && mat_dim(single.ty_lit) > 0 => {
+            let from_dim = mat_dim(single.ty_lit);
+            for column in 0..dim {
+                for row in 0..dim {
+                    out.v[column * dim + row] = if column < from_dim && row < from_dim {
+                        single.at(column, row)
+                    }
+                    else if column == row {1.0} else {0.0};
+                }
+            }
+        }
+        _ => {
+            let flat: Vec<f64> = comps.iter().flat_map( | comps | comps.v[0..comps.len].to_vec()).collect();
+            if flat.len() < len {
+                return None
+            }
+            out.v[0..len].copy_from_slice(&flat[0..len]);
+        }
+    }
+    if ty_lit_with_len(ty_lit, 1) == TyLit::Bool {
+        out = out.map( | v | if v != 0.0 {1.0} else {0.0});
+    }
+    else if is_integer(ty_lit) {
+        out = out.map(f64::trunc);
+    }
+    Some(out.round().to_value())
+}
@@ -65,6 +65,10 @@ impl<'a> LhsChecker<'a> {
                 span,
                 ..
             } => self.lhs_check_all_call_expr(span),
+            ExprKind::ArrayCons {
+                span,
+                ..
+            } => self.lhs_check_all_call_expr(span),
             ExprKind::Var {
                 span,
                 ref kind,
@@ -14,6 +14,7 @@ pub mod lhs_check;
 pub mod swizzle;
 pub mod util;
 pub mod generate;
+pub mod source_map;
 
 #[cfg(any(target_os = "android", target_os = "linux", target_arch = "wasm32"))]
 pub mod generate_glsl;
@@ -21,6 +22,8 @@ pub mod generate_glsl;
 pub mod generate_metal;
 #[cfg(any(target_os = "windows"))]
 pub mod generate_hlsl;
+pub mod generate_wgsl;
+pub mod interpret;
 
 pub use makepad_live_compiler;
 pub use makepad_live_compiler::makepad_math;
@@ -307,9 +307,17 @@ pub enum Stmt {
     For {
         span: TokenSpan,
         ident: Ident,
-        from_expr: Expr,
-        to_expr: Expr,
-        step_expr: Option<Expr>,
+        shadow: Cell<Option<ScopeSymShadow >>,
+        range: Box<ForRange>,
+        block: Box<Block>,
+    },
+    While {
+        span: TokenSpan,
+        expr: Expr,
+        block: Box<Block>,
+    },
+    Loop {
+        span: TokenSpan,
         block: Box<Block>,
     },
     If {
@@ -346,6 +354,32 @@ pub enum Stmt {
     },
 }
 
+/// The bounds of a `for` loop, they have to be constants
+#[derive(Clone, Debug)]
+pub struct ForRange {
+    pub from_expr: Expr,
+    pub to_expr: Expr,
+    pub step_expr: Option<Expr>,
+}
+
+impl Stmt {
+    pub fn span(&self) -> TokenSpan {
+        match self {
+            Self::Break {span}
+                | Self::Continue {span}
+                | Self::For {span, ..}
+                | Self::While {span, ..}
+                | Self::Loop {span, ..}
+                | Self::If {span, ..}
+                | Self::Match {span, ..}
+                | Self::Let {span, ..}
+                | Self::Return {span, ..}
+                | Self::Block {span, ..}
+                | Self::Expr {span, ..} => *span
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Match {
     pub span: TokenSpan,
@@ -419,6 +453,12 @@ pub enum ExprKind {
         ty_lit: TyLit,
         arg_exprs: Vec<Expr>,
     },
+    ArrayCons { // only valid as the initializer of a let, generators lower it to per element stores
+        span: TokenSpan,
+        elem_ty_lit: TyLit,
+        len: u32,
+        arg_exprs: Vec<Expr>,
+    },
     StructCons {
         struct_ptr: StructPtr,
         span: TokenSpan,
@@ -522,6 +562,7 @@ pub enum ShaderTy {
     Void,
     Bool,
     Int,
+    Uint,
     Float,
     Bvec2,
     Bvec3,
@@ -537,6 +578,7 @@ pub enum ShaderTy {
     Mat4,
     Texture2D,
     TextureOES,
+    TextureCube,
     Array {elem_ty: Rc<ShaderTy>, len: usize},
     Struct(StructPtr),
     Enum(LiveType),
@@ -552,6 +594,7 @@ pub type Ty = ShaderTy;
 pub enum TyLit {
     Bool,
     Int,
+    Uint,
     Float,
     Bvec2,
     Bvec3,
@@ -567,6 +610,7 @@ pub enum TyLit {
     Mat4,
     Texture2D,
     TextureOES,
+    TextureCube,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -716,6 +760,7 @@ impl StructDef {
 
 impl FnDef {
     
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         fn_ptr: FnPtr,
         span: TokenSpan,
@@ -977,6 +1022,7 @@ impl Ty {
             Ty::Void => None,
             Ty::Bool => Some(TyLit::Bool),
             Ty::Int => Some(TyLit::Int),
+            Ty::Uint => Some(TyLit::Uint),
             Ty::Float => Some(TyLit::Float),
             Ty::Bvec2 => Some(TyLit::Bvec2),
             Ty::Bvec3 => Some(TyLit::Bvec3),
@@ -992,6 +1038,7 @@ impl Ty {
             Ty::Mat4 => Some(TyLit::Mat4),
             Ty::Texture2D => Some(TyLit::Bool),
             Ty::TextureOES => Some(TyLit::Bool),
+            Ty::TextureCube => Some(TyLit::Bool),
             Ty::Array {..} => None,
             Ty::Struct(_) => None,
             Ty::Enum(_) => None,
@@ -1003,7 +1050,7 @@ impl Ty {
     
     pub fn is_scalar(&self) -> bool {
         match self {
-            Ty::Bool | Ty::Int | Ty::Float => true,
+            Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => true,
             _ => false,
         }
     }
@@ -1033,7 +1080,7 @@ impl Ty {
     pub fn slots(&self) -> usize {
         match self {
             Ty::Void => 0,
-            Ty::Bool | Ty::Int | Ty::Float => 1,
+            Ty::Bool | Ty::Int | Ty::Uint | Ty::Float => 1,
             Ty::Bvec2 | Ty::Ivec2 | Ty::Vec2 => 2,
             Ty::Bvec3 | Ty::Ivec3 | Ty::Vec3 => 3,
             Ty::Bvec4 | Ty::Ivec4 | Ty::Vec4 | Ty::Mat2 => 4,
@@ -1041,6 +1088,7 @@ impl Ty {
             Ty::Mat4 => 16,
             Ty::Texture2D {..} => panic!(),
             Ty::TextureOES {..} => panic!(),
+            Ty::TextureCube {..} => panic!(),
             Ty::Array {elem_ty, len} => elem_ty.slots() * len,
             Ty::Enum(_) => 1,
             Ty::Struct(_) => panic!(),
@@ -1058,6 +1106,7 @@ impl Ty {
                 Ty::Void => panic!(),
                 Ty::Bool => TyExprKind::Lit {ty_lit: TyLit::Bool},
                 Ty::Int => TyExprKind::Lit {ty_lit: TyLit::Int},
+                Ty::Uint => TyExprKind::Lit {ty_lit: TyLit::Uint},
                 Ty::Float => TyExprKind::Lit {ty_lit: TyLit::Float},
                 Ty::Bvec2 => TyExprKind::Lit {ty_lit: TyLit::Bvec2},
                 Ty::Bvec3 => TyExprKind::Lit {ty_lit: TyLit::Bvec3},
@@ -1073,6 +1122,7 @@ impl Ty {
                 Ty::Mat4 => TyExprKind::Lit {ty_lit: TyLit::Mat4},
                 Ty::Texture2D => TyExprKind::Lit {ty_lit: TyLit::Texture2D},
                 Ty::TextureOES => TyExprKind::Lit {ty_lit: TyLit::TextureOES},
+                Ty::TextureCube => TyExprKind::Lit {ty_lit: TyLit::TextureCube},
                 Ty::Array {elem_ty, len} => {
                     TyExprKind::Array {
                         elem_ty_expr: Box::new(elem_ty.to_ty_expr()),
@@ -1135,6 +1185,15 @@ impl Ty {
                 live_id!(vec4) => Self::Vec4,
                 live_id!(texture2d) => Self::Texture2D,
                 live_id!(textureOES) => Self::TextureOES,
+                live_id!(textureCube) => Self::TextureCube,
+                // GLSL ES 1.0 has no sampler3D, so there is nothing to bind one to on GL and WebGL
+                live_id!(texture3d) | live_id!(texture3D) => {
+                    return Err(LiveError {
+                        origin: live_error_origin!(),
+                        message: "texture3d isn't supported yet, GLSL ES 1.0 (WebGL) has no 3D textures".to_string(),
+                        span: nodes[index].origin.token_id().unwrap().into()
+                    })
+                }
                 _ => {
                     return Err(LiveError {
                         origin: live_error_origin!(),
@@ -1166,6 +1225,7 @@ impl fmt::Display for Ty {
             Ty::Void => write!(f, "void"),
             Ty::Bool => write!(f, "bool"),
             Ty::Int => write!(f, "int"),
+            Ty::Uint => write!(f, "uint"),
             Ty::Float => write!(f, "float"),
             Ty::Bvec2 => write!(f, "bvec2"),
             Ty::Bvec3 => write!(f, "bvec3"),
@@ -1181,6 +1241,7 @@ impl fmt::Display for Ty {
             Ty::Mat4 => write!(f, "mat4"),
             Ty::Texture2D => write!(f, "texture2D"),
             Ty::TextureOES => write!(f, "textureOES"),
+            Ty::TextureCube => write!(f, "textureCube"),
             Ty::Array {elem_ty, len} => write!(f, "{}[{}]", elem_ty, len),
             Ty::Struct(struct_ptr) => write!(f, "Struct:{:?}", struct_ptr),
             Ty::DrawShader(shader_ptr) => write!(f, "DrawShader:{:?}", shader_ptr),
@@ -1203,13 +1264,15 @@ impl TyLit {
             live_id!(float) => Some(TyLit::Float),
             live_id!(bool) => Some(TyLit::Bool),
             live_id!(int) => Some(TyLit::Int),
+            live_id!(uint) => Some(TyLit::Uint),
             live_id!(bvec2) => Some(TyLit::Bvec2),
             live_id!(bvec3) => Some(TyLit::Bvec3),
             live_id!(bvec4) => Some(TyLit::Bvec4),
-            live_id!(ivec2) => Some(TyLit::Ivec4),
-            live_id!(ivec3) => Some(TyLit::Ivec4),
+            live_id!(ivec2) => Some(TyLit::Ivec2),
+            live_id!(ivec3) => Some(TyLit::Ivec3),
             live_id!(ivec4) => Some(TyLit::Ivec4),
             live_id!(texture2D) => Some(TyLit::Texture2D),
+            live_id!(textureCube) => Some(TyLit::TextureCube),
             _ => None
         }
     }
@@ -1218,6 +1281,7 @@ impl TyLit {
         match self {
             TyLit::Bool => Ty::Bool,
             TyLit::Int => Ty::Int,
+            TyLit::Uint => Ty::Uint,
             TyLit::Float => Ty::Float,
             TyLit::Bvec2 => Ty::Bvec2,
             TyLit::Bvec3 => Ty::Bvec3,
@@ -1233,6 +1297,7 @@ impl TyLit {
             TyLit::Mat4 => Ty::Mat4,
             TyLit::Texture2D => Ty::Texture2D,
             TyLit::TextureOES => Ty::TextureOES,
+            TyLit::TextureCube => Ty::TextureCube,
         }
     }
     
@@ -1246,6 +1311,7 @@ impl fmt::Display for TyLit {
             match self {
                 TyLit::Bool => "bool",
                 TyLit::Int => "int",
+                TyLit::Uint => "uint",
                 TyLit::Float => "float",
                 TyLit::Bvec2 => "bvec2",
                 TyLit::Bvec3 => "bvec3",
@@ -1261,6 +1327,7 @@ impl fmt::Display for TyLit {
                 TyLit::Mat4 => "mat4",
                 TyLit::Texture2D => "texture2D",
                 TyLit::TextureOES => "textureOES",
+                TyLit::TextureCube => "textureCube",
             }
         )
     }
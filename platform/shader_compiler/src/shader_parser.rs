@@ -34,6 +34,7 @@ pub struct ShaderParser<'a> {
 }
 
 impl<'a> ShaderParser<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         live_registry: &'a LiveRegistry,
         shader_registry: &'a ShaderRegistry,
@@ -74,6 +75,11 @@ impl<'a> ShaderParser<'a> {
         self.token_with_span.token.clone()
     }
     
+    #[inline]
+    fn peek_next_token(&self) -> LiveToken {
+        self.tokens_with_span.clone().next().map( | t | t.token).unwrap_or(LiveToken::Eof)
+    }
+    
     #[inline]
     fn eat_token(&mut self) -> LiveToken {
         let token = self.peek_token();
@@ -182,7 +188,7 @@ impl<'a> ShaderParser<'a> {
     #[inline]
     fn expect_specific_ident(&mut self, specific_id: LiveId) -> Result<(), LiveError> {
         match self.peek_token() {
-            LiveToken::Ident(id) if id == id => {
+            LiveToken::Ident(id) if id == specific_id => {
                 self.skip_token();
                 Ok(())
             }
@@ -596,6 +602,8 @@ impl<'a> ShaderParser<'a> {
             LiveToken::Ident(live_id!(break)) => self.expect_break_stmt(),
             LiveToken::Ident(live_id!(continue)) => self.expect_continue_stmt(),
             LiveToken::Ident(live_id!(for)) => self.expect_for_stmt(),
+            LiveToken::Ident(live_id!(while)) => self.expect_while_stmt(),
+            LiveToken::Ident(live_id!(loop)) => self.expect_loop_stmt(),
             LiveToken::Ident(live_id!(if)) => self.expect_if_stmt(),
             LiveToken::Ident(live_id!(match)) => self.expect_match_stmt(),
             LiveToken::Ident(live_id!(let)) => self.expect_let_stmt(),
@@ -640,13 +648,33 @@ impl<'a> ShaderParser<'a> {
         Ok(span.end(self, | span | Stmt::For {
             span,
             ident,
-            from_expr,
-            to_expr,
-            step_expr,
+            shadow: Cell::new(None),
+            range: Box::new(ForRange {from_expr, to_expr, step_expr}),
+            block,
+        }))
+    }
+    
+    fn expect_while_stmt(&mut self) -> Result<Stmt, LiveError> {
+        let span = self.begin_span();
+        self.expect_token(LiveToken::Ident(live_id!(while))) ?;
+        let expr = self.expect_expr() ?;
+        let block = Box::new(self.expect_block() ?);
+        Ok(span.end(self, | span | Stmt::While {
+            span,
+            expr,
             block,
         }))
     }
     
+    fn expect_loop_stmt(&mut self) -> Result<Stmt, LiveError> {
+        let span = self.begin_span();
+        self.expect_token(LiveToken::Ident(live_id!(loop))) ?;
+        let block = Box::new(self.expect_block() ?);
+        Ok(span.end(self, | span | Stmt::Loop {
+            span,
+            block,
+        }))
+    }
     
     fn expect_if_stmt(&mut self) -> Result<Stmt, LiveError> {
         let span = self.begin_span();
@@ -1021,10 +1049,24 @@ impl<'a> ShaderParser<'a> {
                         })
                     }
                 }
-                LiveToken::Close(Delim::Bracket) => {
+                LiveToken::Open(Delim::Bracket) => {
                     self.skip_token();
                     let expr = Box::new(acc);
-                    let index_expr = Box::new(self.expect_expr() ?);
+                    // integer literals are floats everywhere else, but a literal index has to stay an int
+                    let index_expr = Box::new(if let (LiveToken::Int(v), LiveToken::Close(Delim::Bracket)) = (self.peek_token(), self.peek_next_token()) {
+                        let span = self.begin_span();
+                        self.skip_token();
+                        span.end(self, | span | Expr {
+                            span,
+                            ty: RefCell::new(None),
+                            const_val: RefCell::new(None),
+                            const_index: Cell::new(None),
+                            kind: ExprKind::Lit {span, lit: Lit::Int(v as i32)},
+                        })
+                    }
+                    else {
+                        self.expect_expr() ?
+                    });
                     self.expect_token(LiveToken::Close(Delim::Bracket)) ?;
                     acc = span.end(self, | span | Expr {
                         span,
@@ -1051,6 +1093,31 @@ impl<'a> ShaderParser<'a> {
             LiveToken::Ident(ident) => {
                 if let Some(ty_lit) = TyLit::from_id(ident) {
                     self.skip_token();
+                    // array constructor: vec4[3](a, b, c)
+                    if self.accept_token(LiveToken::Open(Delim::Bracket)) {
+                        let token = self.peek_token();
+                        let len = if let Some(Lit::Int(len)) = Lit::from_token(&token) {
+                            self.skip_token();
+                            len as u32
+                        }
+                        else {
+                            return Err(span.error(self, live_error_origin!(), format!("unexpected token `{}`", token)))
+                        };
+                        self.expect_token(LiveToken::Close(Delim::Bracket)) ?;
+                        let arg_exprs = self.expect_arg_exprs() ?;
+                        return Ok(span.end(self, | span | Expr {
+                            span,
+                            ty: RefCell::new(None),
+                            const_val: RefCell::new(None),
+                            const_index: Cell::new(None),
+                            kind: ExprKind::ArrayCons {
+                                span,
+                                elem_ty_lit: ty_lit,
+                                len,
+                                arg_exprs,
+                            },
+                        }))
+                    }
                     self.expect_token(LiveToken::Open(Delim::Paren)) ?;
                     let mut arg_exprs = Vec::new();
                     if !self.accept_token(LiveToken::Close(Delim::Paren)) {
@@ -0,0 +1,102 @@
+use {
+    std::fmt::Write,
+    crate::makepad_live_compiler::*,
+};
+
+// while generating, every statement is preceded by a marker holding the token id it came from.
+// The markers are taken out again before the source leaves the generator, that way the code that
+// assembles a shader from pieces doesn't have to keep track of line numbers.
+const MARKER_START: char = '\u{1}';
+const MARKER_END: char = '\u{2}';
+
+/// Maps the lines of a generated shader back to the DSL they were generated from.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShaderSourceMap {
+    lines: Vec<Option<TokenSpan >>,
+}
+
+impl ShaderSourceMap {
+    pub(crate) fn write_marker(string: &mut String, span: Option<TokenSpan>) {
+        string.push(MARKER_START);
+        if let Some(span) = span {
+            write!(string, "{:x}", span.token_id.to_bits()).unwrap();
+        }
+        string.push(MARKER_END);
+    }
+
+    pub(crate) fn strip_markers(source: &mut String) -> Self {
+        let mut out = String::with_capacity(source.len());
+        let mut lines = Vec::new();
+        let mut current = None;
+        for (index, line) in source.split('\n').enumerate() {
+            if index != 0 {
+                out.push('\n');
+            }
+            // a line belongs to the first statement that starts on it, or to the one it continues
+            let mut line_span = current;
+            let mut first = true;
+            let mut rest = line;
+            while let Some(start) = rest.find(MARKER_START) {
+                out.push_str(&rest[..start]);
+                let end = start + rest[start..].find(MARKER_END).unwrap();
+                current = u32::from_str_radix(&rest[start + 1..end], 16).ok().and_then(LiveTokenId::from_bits).map(TokenSpan::from);
+                if first {
+                    line_span = current;
+                    first = false;
+                }
+                rest = &rest[end + 1..];
+            }
+            out.push_str(rest);
+            lines.push(line_span);
+        }
+        *source = out;
+        Self {lines}
+    }
+
+    /// The span of the DSL statement a line was generated from, line numbers start at 1.
+    pub fn line_span(&self, line: usize) -> Option<TokenSpan> {
+        self.lines.get(line.checked_sub(1) ?).cloned().flatten()
+    }
+
+    /// Turns the messages of a platform shader compiler into errors on the DSL. `line_offset` is
+    /// the number of lines the platform put in front of the generated source. Messages that
+    /// don't point into a function body are left out.
+    pub fn map_compiler_log(&self, log: &str, line_offset: usize) -> Vec<LiveError> {
+        let mut errors = Vec::new();
+        for message in log.lines() {
+            if let Some(span) = compiler_log_line(message).and_then( | line | line.checked_sub(line_offset)).and_then( | line | self.line_span(line)) {
+                errors.push(LiveError {
+                    origin: live_error_origin!(),
+                    span: span.into(),
+                    message: message.trim().to_string()
+                });
+            }
+        }
+        errors
+    }
+}
+
+/// Finds the line number in a message of a platform shader compiler. Understands the GLSL
+/// compilers (`0:12(5): error`, `ERROR: 0:12: ...` and `0(12) : error`), fxc
+/// (`makepad_shader(12,5-9): error`) and the Metal compiler (`program_source:12:5: error`).
+pub fn compiler_log_line(message: &str) -> Option<usize> {
+    let message = message.trim_start();
+    let message = message.strip_prefix("ERROR:").or_else( || message.strip_prefix("WARNING:")).unwrap_or(message).trim_start();
+    if let Some(rest) = message.strip_prefix("program_source:") {
+        return leading_number(rest).map( | (line, _) | line)
+    }
+    if let Some((_, rest)) = leading_number(message) {
+        // glsl starts with the index of the source string
+        let rest = rest.strip_prefix(':').or_else( || rest.strip_prefix('(')) ?;
+        return leading_number(rest).map( | (line, _) | line)
+    }
+    // fxc puts the line and column behind the source name
+    let open = message.find('(') ?;
+    let (line, rest) = leading_number(&message[open + 1..]) ?;
+    if rest.starts_with(',') {Some(line)} else {None}
+}
+
+fn leading_number(s: &str) -> Option<(usize, &str)> {
+    let end = s.find( | c: char | !c.is_ascii_digit()).unwrap_or(s.len());
+    Some((s[..end].parse().ok() ?, &s[end..]))
+}
@@ -137,6 +137,11 @@ impl<'a> TyChecker<'a> {
                 ty_lit,
                 ref arg_exprs,
             } => self.ty_check_cons_call_expr(span, ty_lit, arg_exprs),
+            ExprKind::ArrayCons {span, ..} => Err(LiveError {
+                origin: live_error_origin!(),
+                span:span.into(),
+                message: String::from("array constructors can only be used to initialize a `let`"),
+            }),
             ExprKind::Field {
                 span,
                 ref expr,
@@ -203,6 +208,13 @@ impl<'a> TyChecker<'a> {
                 | BinOp::MulAssign
                 | BinOp::DivAssign => {
                 self.lhs_checker().lhs_check_expr(left_expr) ?;
+                if let Ty::Array {..} = left_ty {
+                    return Err(LiveError {
+                        origin: live_error_origin!(),
+                        span:span.into(),
+                        message: String::from("can't assign to an array as a whole, assign its elements instead"),
+                    });
+                }
             }
             _ => {}
         }
@@ -216,6 +228,7 @@ impl<'a> TyChecker<'a> {
             }
             BinOp::AddAssign | BinOp::SubAssign | BinOp::DivAssign => match (&left_ty, &right_ty) {
                 (Ty::Int, Ty::Int) => Some(Ty::Int),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Uint),
                 (Ty::Float, Ty::Float) => Some(Ty::Float),
                 (Ty::Ivec2, Ty::Int) => Some(Ty::Ivec2),
                 (Ty::Ivec2, Ty::Ivec2) => Some(Ty::Ivec2),
@@ -239,6 +252,7 @@ impl<'a> TyChecker<'a> {
             },
             BinOp::MulAssign => match (&left_ty, &right_ty) {
                 (Ty::Int, Ty::Int) => Some(Ty::Int),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Uint),
                 (Ty::Float, Ty::Float) => Some(Ty::Float),
                 (Ty::Ivec2, Ty::Int) => Some(Ty::Ivec2),
                 (Ty::Ivec2, Ty::Ivec2) => Some(Ty::Ivec2),
@@ -270,6 +284,7 @@ impl<'a> TyChecker<'a> {
             BinOp::Eq | BinOp::Ne => match (&left_ty, &right_ty) {
                 (Ty::Bool, Ty::Bool) => Some(Ty::Bool),
                 (Ty::Int, Ty::Int) => Some(Ty::Bool),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Bool),
                 (Ty::Float, Ty::Float) => Some(Ty::Bool),
                 (Ty::Ivec2, Ty::Ivec2) => Some(Ty::Bool),
                 (Ty::Ivec3, Ty::Ivec3) => Some(Ty::Bool),
@@ -284,11 +299,13 @@ impl<'a> TyChecker<'a> {
             },
             BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => match (&left_ty, &right_ty) {
                 (Ty::Int, Ty::Int) => Some(Ty::Bool),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Bool),
                 (Ty::Float, Ty::Float) => Some(Ty::Bool),
                 _ => None,
             },
             BinOp::Add | BinOp::Sub | BinOp::Div => match (&left_ty, &right_ty) {
                 (Ty::Int, Ty::Int) => Some(Ty::Int),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Uint),
                 (Ty::Float, Ty::Float) => Some(Ty::Float),
                 (Ty::Float, Ty::Vec2) => Some(Ty::Vec2),
                 (Ty::Float, Ty::Vec3) => Some(Ty::Vec3),
@@ -318,6 +335,7 @@ impl<'a> TyChecker<'a> {
             },
             BinOp::Mul => match (&left_ty, &right_ty) {
                 (Ty::Int, Ty::Int) => Some(Ty::Int),
+                (Ty::Uint, Ty::Uint) => Some(Ty::Uint),
                 (Ty::Float, Ty::Float) => Some(Ty::Float),
                 (Ty::Float, Ty::Vec2) => Some(Ty::Vec2),
                 (Ty::Float, Ty::Vec3) => Some(Ty::Vec3),
@@ -784,6 +802,7 @@ impl<'a> TyChecker<'a> {
             Ty::Mat2 => Ty::Vec2,
             Ty::Mat3 => Ty::Vec3,
             Ty::Mat4 => Ty::Vec4,
+            Ty::Array {ref elem_ty, ..} => elem_ty.as_ref().clone(),
             _ => {
                 return Err(LiveError {
                     origin: live_error_origin!(),
@@ -792,7 +811,7 @@ impl<'a> TyChecker<'a> {
                 })
             }
         };
-        if index_ty != Ty::Int {
+        if index_ty != Ty::Int && index_ty != Ty::Uint {
             return Err(LiveError {
                 origin: live_error_origin!(),
                 span:span.into(),
@@ -802,6 +821,42 @@ impl<'a> TyChecker<'a> {
         Ok(elem_ty)
     }
     
+    pub fn ty_check_array_cons_expr(&mut self, expr: &Expr) -> Result<Ty, LiveError> {
+        let (span, elem_ty_lit, len, arg_exprs) = if let ExprKind::ArrayCons {span, elem_ty_lit, len, ref arg_exprs} = expr.kind {
+            (span, elem_ty_lit, len as usize, arg_exprs)
+        }
+        else {
+            return self.ty_check_expr(expr)
+        };
+        let elem_ty = elem_ty_lit.to_ty();
+        if !(elem_ty.is_scalar() || elem_ty.is_vector() || elem_ty.is_matrix()) || len == 0 {
+            return Err(LiveError {
+                origin: live_error_origin!(),
+                span:span.into(),
+                message: format!("can't construct an array of type `{}[{}]`", elem_ty, len),
+            });
+        }
+        if arg_exprs.len() != len {
+            return Err(LiveError {
+                origin: live_error_origin!(),
+                span:span.into(),
+                message: format!(
+                    "wrong number of elements for array constructor `{}[{}]`: expected {}, got {}",
+                    elem_ty,
+                    len,
+                    len,
+                    arg_exprs.len()
+                ),
+            });
+        }
+        for arg_expr in arg_exprs {
+            self.ty_check_expr_with_expected_ty(arg_expr.span, arg_expr, &elem_ty) ?;
+        }
+        let ty = Ty::Array {elem_ty: Rc::new(elem_ty), len};
+        *expr.ty.borrow_mut() = Some(ty.clone());
+        Ok(ty)
+    }
+    
     #[allow(clippy::redundant_closure_call)]
     fn ty_check_cons_call_expr(
         &mut self,
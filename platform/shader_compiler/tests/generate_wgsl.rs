@@ -0,0 +1,270 @@
+// snapshot tests for the WGSL backend. The draw shaders below mirror the standard ones in
+// the draw crate, their instance fields are added the way draw_vars.rs does it.
+// run with MAKEPAD_UPDATE_SNAPSHOTS=1 to rewrite the files in tests/snapshots
+
+use makepad_shader_compiler::makepad_live_compiler::*;
+use makepad_shader_compiler::makepad_live_id::*;
+use makepad_shader_compiler::shader_ast::*;
+use makepad_shader_compiler::shader_registry::{ShaderRegistry, DrawShaderQuery};
+use makepad_shader_compiler::generate_wgsl;
+use makepad_shader_compiler::source_map::compiler_log_line;
+
+const SOURCE: &'static str = r#"
+    DrawQuad = {{DrawQuad}} {
+        varying pos: vec2
+
+        fn clip_and_transform_vertex(self, rect_pos:vec2, rect_size:vec2) -> vec4 {
+            let clipped: vec2 = clamp(
+                self.geom_pos * rect_size + rect_pos,
+                self.draw_clip.xy,
+                self.draw_clip.zw
+            )
+            self.pos = (clipped - rect_pos) / rect_size
+            return self.camera_projection * (self.camera_view * (self.view_transform * vec4(
+                clipped.x,
+                clipped.y,
+                self.draw_depth + self.draw_zbias,
+                1.
+            )))
+        }
+
+        fn vertex(self) -> vec4 {
+            return self.clip_and_transform_vertex(self.rect_pos, self.rect_size)
+        }
+
+        fn pixel(self) -> vec4 {
+            return #f0f
+        }
+    }
+
+    DrawColor = <DrawQuad> {
+        fn pixel(self) -> vec4 {
+            return vec4(self.color.rgb*self.color.a, self.color.a);
+        }
+    }
+
+    DrawText = {{DrawText}} {
+        uniform brightness: float
+        uniform curve: float
+
+        texture tex: texture2d
+
+        varying tex_coord1: vec2
+        varying clipped: vec2
+        varying pos: vec2
+
+        fn vertex(self) -> vec4 {
+            let min_pos = vec2(self.rect_pos.x, self.rect_pos.y)
+            let max_pos = vec2(self.rect_pos.x + self.rect_size.x, self.rect_pos.y - self.rect_size.y)
+
+            self.clipped = clamp(
+                mix(min_pos, max_pos, self.geom_pos),
+                self.draw_clip.xy,
+                self.draw_clip.zw
+            )
+
+            let normalized: vec2 = (self.clipped - min_pos) / vec2(self.rect_size.x, -self.rect_size.y)
+
+            self.tex_coord1 = mix(
+                self.font_t1.xy,
+                self.font_t2.xy,
+                normalized.xy
+            )
+            self.pos = normalized;
+            return self.camera_projection * (self.camera_view * (self.view_transform * vec4(
+                self.clipped.x,
+                self.clipped.y,
+                self.char_depth + self.draw_zbias,
+                1.
+            )))
+        }
+
+        fn get_color(self) -> vec4 {
+            return self.color;
+        }
+
+        fn blend_color(self, incol:vec4)->vec4{
+            return incol
+        }
+
+        fn pixel(self) -> vec4 {
+            let s = sample2d_rt(self.tex, self.tex_coord1.xy).x;
+            s = pow(s, self.curve);
+            let col = self.get_color();
+            return self.blend_color(vec4(s * col.rgb * self.brightness * col.a, s * col.a));
+        }
+    }
+
+    DrawLoops = <DrawQuad> {
+        fn pixel(self) -> vec4 {
+            let weights = float[3](0.25, 0.5, 0.25);
+            let acc = vec4(0.0);
+            let i = uint(0);
+            while (i < uint(3)) {
+                acc += weights[i] * vec4(self.pos, float(i) * 0.5, 1.0);
+                i += uint(1);
+            }
+            loop {
+                if acc.a <= 1.0 {
+                    break;
+                }
+                acc *= 0.5;
+            }
+            return acc * weights[1];
+        }
+    }
+"#;
+
+const DRAW_QUAD_INSTANCES: [(&'static str, Ty); 4] = [
+    ("rect_pos", Ty::Vec2),
+    ("rect_size", Ty::Vec2),
+    ("draw_clip", Ty::Vec4),
+    ("draw_depth", Ty::Float),
+];
+
+const DRAW_TEXT_INSTANCES: [(&'static str, Ty); 13] = [
+    ("color", Ty::Vec4),
+    ("font_t1", Ty::Vec2),
+    ("font_t2", Ty::Vec2),
+    ("rect_pos", Ty::Vec2),
+    ("rect_size", Ty::Vec2),
+    ("draw_clip", Ty::Vec4),
+    ("char_depth", Ty::Float),
+    ("delta", Ty::Vec2),
+    ("font_size", Ty::Float),
+    ("advance", Ty::Float),
+    ("char_offset", Ty::Float),
+    ("char_len", Ty::Float),
+    ("rtl", Ty::Float),
+];
+
+struct FakeType();
+
+fn compile_wgsl(name: &str, instances: &[(&str, Ty)], super_instances: &[(&str, Ty)]) -> generate_wgsl::WgslGeneratedShader {
+    compile_wgsl_with_registry(name, instances, super_instances).1
+}
+
+fn compile_wgsl_with_registry(name: &str, instances: &[(&str, Ty)], super_instances: &[(&str, Ty)]) -> (LiveRegistry, generate_wgsl::WgslGeneratedShader) {
+    let mut live_registry = LiveRegistry::default();
+    let mut shader_registry = ShaderRegistry::new();
+
+    let module_id = LiveModuleId::from_str("test").unwrap();
+    let live_type_infos = ["DrawQuad", "DrawText"].iter().map( | type_name | LiveTypeInfo {
+        live_type: std::any::TypeId::of::<FakeType>(),
+        type_name: LiveId::from_str_with_lut(type_name).unwrap(),
+        module_id,
+        live_ignore: true,
+        fields: Vec::new()
+    }).collect();
+    if let Err(err) = live_registry.register_live_file("test.rs", "", module_id, SOURCE.to_string(), live_type_infos, TextPos::default()) {
+        panic!("Cannot parse live file {}", err);
+    }
+    let mut errors = Vec::new();
+    live_registry.expand_all_documents(&mut errors);
+    assert!(errors.is_empty(), "Cannot expand live file {:?}", errors);
+
+    let draw_shader_ptr = DrawShaderPtr(live_registry.module_id_and_name_to_ptr(module_id, LiveId::from_str(name)).unwrap());
+    let result = shader_registry.analyse_draw_shader(&live_registry, draw_shader_ptr, | _, _, span, draw_shader_query, _, draw_shader_def | {
+        if let DrawShaderQuery::DrawShader = draw_shader_query {
+            draw_shader_def.add_geometry(live_id!(geom_pos), Ty::Vec2, span);
+            // fields of a deref'd draw shader get padded to an even number of slots
+            let mut slots = 0;
+            for (id, ty) in super_instances {
+                slots += ty.slots();
+                draw_shader_def.add_instance(LiveId::from_str_with_lut(id).unwrap(), ty.clone(), span, LiveFieldKind::Calc);
+            }
+            if slots % 2 == 1 {
+                draw_shader_def.add_instance(LiveId(0), Ty::Float, span, LiveFieldKind::Calc);
+            }
+            for (id, ty) in instances {
+                draw_shader_def.add_instance(LiveId::from_str_with_lut(id).unwrap(), ty.clone(), span, LiveFieldKind::Calc);
+            }
+        }
+    });
+    if let Err(err) = result {
+        panic!("Cannot analyse {} {}", name, live_registry.live_error_to_live_file_error(err));
+    }
+
+    let const_table = shader_registry.compute_const_table(draw_shader_ptr);
+    let draw_shader_def = shader_registry.draw_shader_defs.get(&draw_shader_ptr).unwrap();
+    let shader = generate_wgsl::generate_shader(draw_shader_def, &const_table, &shader_registry);
+    (live_registry, shader)
+}
+
+fn compare_snapshot(file: &str, output: &str) {
+    let path = format!("{}/tests/snapshots/{}", env!("CARGO_MANIFEST_DIR"), file);
+    if std::env::var("MAKEPAD_UPDATE_SNAPSHOTS").is_ok() {
+        std::fs::write(&path, output).unwrap();
+        return
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else( | _ | panic!("Cannot read snapshot {}", path));
+    if expected != output {
+        for (index, (a, b)) in expected.lines().zip(output.lines()).enumerate() {
+            if a != b {
+                println!("First difference on line {}\n  snapshot: {}\n  output:   {}", index + 1, a, b);
+                break
+            }
+        }
+        println!("########## ALL ##########\n{}\n########## END ##########", output);
+        panic!("WGSL output of {} changed", file);
+    }
+}
+
+#[test]
+fn draw_quad() {
+    let shader = compile_wgsl("DrawQuad", &DRAW_QUAD_INSTANCES, &[]);
+    assert_eq!(shader.geometry_slots, 2);
+    assert_eq!(shader.instance_slots, 9);
+    compare_snapshot("draw_quad.wgsl", &shader.wgsl);
+}
+
+#[test]
+fn draw_color() {
+    let shader = compile_wgsl("DrawColor", &[("color", Ty::Vec4)], &DRAW_QUAD_INSTANCES);
+    // 9 slots of DrawQuad, one padding slot and the color
+    assert_eq!(shader.instance_slots, 14);
+    compare_snapshot("draw_color.wgsl", &shader.wgsl);
+}
+
+#[test]
+fn draw_text() {
+    let shader = compile_wgsl("DrawText", &DRAW_TEXT_INSTANCES, &[]);
+    assert_eq!(shader.instance_slots, 24);
+    compare_snapshot("draw_text.wgsl", &shader.wgsl);
+}
+
+#[test]
+fn draw_loops() {
+    let shader = compile_wgsl("DrawLoops", &[], &DRAW_QUAD_INSTANCES);
+    compare_snapshot("draw_loops.wgsl", &shader.wgsl);
+}
+
+#[test]
+fn source_map() {
+    let (live_registry, shader) = compile_wgsl_with_registry("DrawLoops", &[], &DRAW_QUAD_INSTANCES);
+    let line = shader.wgsl.lines().position( | line | line.contains("return") && line.contains("weights")).unwrap() + 1;
+    let span = shader.source_map.line_span(line).unwrap();
+    let text_span = live_registry.token_id_to_span(span.token_id);
+    assert!(SOURCE.lines().nth(text_span.start.line as usize).unwrap().contains("return acc * weights[1]"));
+    // statements nested in blocks map to themselves, not to the statement around them
+    let line = shader.wgsl.lines().position( | line | line.trim() == "break;").unwrap() + 1;
+    let span = shader.source_map.line_span(line).unwrap();
+    assert!(SOURCE.lines().nth(live_registry.token_id_to_span(span.token_id).start.line as usize).unwrap().contains("break;"));
+    // lines outside of functions have no counterpart in the DSL
+    assert_eq!(shader.source_map.line_span(1), None);
+
+    let log = format!("0:{}(12): error: no matching function\nwarning: something unrelated", line + 3);
+    let errors = shader.source_map.map_compiler_log(&log, 3);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(live_registry.live_error_to_live_file_error(errors.into_iter().next().unwrap()).span, live_registry.token_id_to_span(span.token_id));
+}
+
+#[test]
+fn compiler_log_lines() {
+    assert_eq!(compiler_log_line("0:12(5): error: `foo' undeclared"), Some(12));
+    assert_eq!(compiler_log_line("ERROR: 0:34: 'foo' : undeclared identifier"), Some(34));
+    assert_eq!(compiler_log_line("0(56) : error C1008: undefined variable \"foo\""), Some(56));
+    assert_eq!(compiler_log_line("makepad_shader(78,5-9): error X3004: undeclared identifier 'foo'"), Some(78));
+    assert_eq!(compiler_log_line("program_source:90:5: error: use of undeclared identifier 'foo'"), Some(90));
+    assert_eq!(compiler_log_line("Shader compilation failed"), None);
+}
@@ -0,0 +1,292 @@
+// runs draw shaders on the cpu through the interpreter. The shaders below use the same
+// constructs as the ones in the draw and widgets crates: struct methods with inout self,
+// closures, loops, live constants and texture samples.
+
+use makepad_shader_compiler::makepad_live_compiler::*;
+use makepad_shader_compiler::makepad_live_id::*;
+use makepad_shader_compiler::makepad_math::*;
+use makepad_shader_compiler::shader_ast::*;
+use makepad_shader_compiler::shader_registry::{ShaderRegistry, DrawShaderQuery};
+use makepad_shader_compiler::interpret::*;
+use makepad_shader_compiler::generate_glsl;
+
+const SOURCE: &'static str = r#"
+    HALF = 0.5
+
+    Shape = struct {
+        field pos: vec2
+        field result: vec4
+        field dist: float
+
+        fn viewport(pos: vec2) -> Self {
+            return Self {
+                pos: pos
+                result: vec4(0.)
+                dist: 1e+20
+            };
+        }
+
+        fn circle(inout self, x: float, y: float, r: float) {
+            self.dist = length(self.pos - vec2(x, y)) - r;
+        }
+
+        fn fill(inout self, color: vec4) -> vec4 {
+            if self.dist <= 0.0 {
+                self.result = color;
+            }
+            self.dist = 1e+20;
+            return self.result;
+        }
+
+        fn apply(self, f: fn(d: float) -> float) -> float {
+            return f(self.dist);
+        }
+    }
+
+    DrawQuad = {{DrawQuad}} {
+        varying pos: vec2
+
+        fn vertex(self) -> vec4 {
+            let clipped: vec2 = self.geom_pos * self.rect_size + self.rect_pos;
+            self.pos = (clipped - self.rect_pos) / self.rect_size;
+            return self.camera_projection * vec4(clipped.x, clipped.y, 0.0, 1.);
+        }
+
+        fn pixel(self) -> vec4 {
+            let shape = Shape::viewport(self.pos * self.rect_size);
+            shape.circle(self.rect_size.x * HALF, self.rect_size.y * HALF, 5.0);
+            return shape.fill(#f00);
+        }
+    }
+
+    DrawMath = <DrawQuad> {
+        uniform scale: float
+
+        texture tex: texture2d
+
+        fn sum(self, n: float) -> float {
+            let total = 0.0;
+            for i in 0..4 {
+                if n <= float(i) {
+                    break;
+                }
+                total += float(i);
+            }
+            return total;
+        }
+
+        fn offset(self, d: float) -> float {
+            let shape = Shape::viewport(vec2(0.0));
+            shape.circle(3.0, 4.0, 0.0);
+            let extra = self.scale;
+            return shape.apply(| x | x * extra + d);
+        }
+
+        fn pixel(self) -> vec4 {
+            let c = sample2d(self.tex, self.pos);
+            return vec4(c.rgb * self.scale, smoothstep(0.0, 1.0, self.pos.x));
+        }
+    }
+
+    DrawArrays = <DrawQuad> {
+        fn palette(self, i: int) -> vec4 {
+            let pal = vec4[3](#f00, #0f0, #00f);
+            pal[2] = pal[2] * 0.5;
+            return pal[i] + pal[uint(i)];
+        }
+
+        fn count(self, n: float) -> float {
+            let i = 0.0;
+            while (i < n) {
+                i += 1.0;
+            }
+            return i;
+        }
+
+        fn halvings(self, n: float) -> float {
+            let steps = 0.0;
+            let v = n;
+            loop {
+                if v < 1.0 {
+                    break;
+                }
+                v = v / 2.0;
+                steps += 1.0;
+            }
+            return steps;
+        }
+
+        fn unsigned(self, a: uint, b: uint) -> uint {
+            return a / b + a * b;
+        }
+
+        fn forever(self) -> float {
+            loop {
+            }
+            return 0.0;
+        }
+
+        fn pixel(self) -> vec4 {
+            return vec4(self.count(1.0));
+        }
+    }
+
+    DrawCube = <DrawQuad> {
+        texture sky: textureCube
+
+        fn pixel(self) -> vec4 {
+            return sampleCube(self.sky, vec3(self.pos * 2.0 - 1.0, 1.0));
+        }
+    }
+"#;
+
+struct FakeType();
+
+struct Shader {
+    live_registry: LiveRegistry,
+    shader_registry: ShaderRegistry,
+    draw_shader_ptr: DrawShaderPtr,
+}
+
+impl Shader {
+    fn interpreter(&self) -> ShaderInterpreter {
+        let draw_shader_def = self.shader_registry.draw_shader_defs.get(&self.draw_shader_ptr).unwrap();
+        ShaderInterpreter::new(&self.shader_registry, &self.live_registry, draw_shader_def)
+    }
+}
+
+fn analyse(name: &str) -> Shader {
+    let mut live_registry = LiveRegistry::default();
+    let mut shader_registry = ShaderRegistry::new();
+
+    let module_id = LiveModuleId::from_str("test").unwrap();
+    let live_type_infos = vec![LiveTypeInfo {
+        live_type: std::any::TypeId::of::<FakeType>(),
+        type_name: LiveId::from_str_with_lut("DrawQuad").unwrap(),
+        module_id,
+        live_ignore: true,
+        fields: Vec::new()
+    }];
+    if let Err(err) = live_registry.register_live_file("test.rs", "", module_id, SOURCE.to_string(), live_type_infos, TextPos::default()) {
+        panic!("Cannot parse live file {}", err);
+    }
+    let mut errors = Vec::new();
+    live_registry.expand_all_documents(&mut errors);
+    assert!(errors.is_empty(), "Cannot expand live file {:?}", errors);
+
+    let draw_shader_ptr = DrawShaderPtr(live_registry.module_id_and_name_to_ptr(module_id, LiveId::from_str(name)).unwrap());
+    let result = shader_registry.analyse_draw_shader(&live_registry, draw_shader_ptr, | _, _, span, draw_shader_query, _, draw_shader_def | {
+        if let DrawShaderQuery::DrawShader = draw_shader_query {
+            draw_shader_def.add_geometry(live_id!(geom_pos), Ty::Vec2, span);
+            draw_shader_def.add_instance(live_id!(rect_pos), Ty::Vec2, span, LiveFieldKind::Calc);
+            draw_shader_def.add_instance(live_id!(rect_size), Ty::Vec2, span, LiveFieldKind::Calc);
+        }
+    });
+    if let Err(err) = result {
+        panic!("Cannot analyse {} {}", name, live_registry.live_error_to_live_file_error(err));
+    }
+    Shader {live_registry, shader_registry, draw_shader_ptr}
+}
+
+fn assert_vec4(a: Vec4, b: Vec4) {
+    let d = a - b;
+    assert!(d.x.abs() + d.y.abs() + d.z.abs() + d.w.abs() < 1e-5, "{:?} != {:?}", a, b);
+}
+
+#[test]
+fn vertex_and_varyings() {
+    let shader = analyse("DrawQuad");
+    let mut interp = shader.interpreter();
+    interp.set_field(live_id!(rect_pos), vec2(10.0, 20.0));
+    interp.set_field(live_id!(rect_size), vec2(100.0, 50.0));
+    interp.set_field(live_id!(geom_pos), vec2(0.5, 1.0));
+    // camera_projection starts out as identity
+    assert_vec4(interp.vertex().unwrap(), vec4(60.0, 70.0, 0.0, 1.0));
+    assert_eq!(interp.field(live_id!(pos)), Some(&ShaderValue::Vec2(vec2(0.5, 1.0))));
+
+    let mut projection = Mat4::default();
+    projection.v[0] = 2.0;
+    projection.v[12] = -1.0;
+    interp.set_field(live_id!(camera_projection), projection);
+    assert_vec4(interp.vertex().unwrap(), vec4(119.0, 70.0, 0.0, 1.0));
+}
+
+#[test]
+fn struct_methods() {
+    let shader = analyse("DrawQuad");
+    let mut interp = shader.interpreter();
+    interp.set_field(live_id!(rect_size), vec2(20.0, 20.0));
+    // the circle of radius 5 sits in the middle of the quad
+    interp.set_field(live_id!(pos), vec2(0.5, 0.6));
+    assert_vec4(interp.pixel().unwrap(), vec4(1.0, 0.0, 0.0, 1.0));
+    interp.set_field(live_id!(pos), vec2(0.9, 0.5));
+    assert_vec4(interp.pixel().unwrap(), vec4(0.0, 0.0, 0.0, 0.0));
+}
+
+#[test]
+fn loops_and_closures() {
+    let shader = analyse("DrawMath");
+    let mut interp = shader.interpreter();
+    assert_eq!(interp.call_method(live_id!(sum), vec![10.0.into()]).unwrap(), ShaderValue::Float(6.0));
+    assert_eq!(interp.call_method(live_id!(sum), vec![2.0.into()]).unwrap(), ShaderValue::Float(1.0));
+
+    interp.set_field(live_id!(scale), 2.0);
+    assert_eq!(interp.call_method(live_id!(offset), vec![1.0.into()]).unwrap(), ShaderValue::Float(11.0));
+    assert!(interp.call_method(live_id!(offset), vec![]).is_err());
+}
+
+#[test]
+fn texture_sampling() {
+    let shader = analyse("DrawMath");
+    let mut interp = shader.interpreter();
+    let mut texture = ShaderTexture::new(2, 1);
+    texture.pixels[0] = vec4(1.0, 0.0, 0.0, 1.0);
+    texture.pixels[1] = vec4(0.0, 0.0, 1.0, 1.0);
+    interp.set_texture(live_id!(tex), texture);
+    interp.set_field(live_id!(scale), 0.5);
+
+    interp.set_field(live_id!(pos), vec2(0.0, 0.5));
+    assert_vec4(interp.pixel().unwrap(), vec4(0.5, 0.0, 0.0, 0.0));
+    interp.set_field(live_id!(pos), vec2(0.5, 0.5));
+    assert_vec4(interp.pixel().unwrap(), vec4(0.25, 0.0, 0.25, 0.5));
+}
+
+#[test]
+fn arrays_and_while_loops() {
+    let shader = analyse("DrawArrays");
+    let mut interp = shader.interpreter();
+    assert_eq!(interp.call_method(live_id!(palette), vec![ShaderValue::Int(0)]).unwrap(), ShaderValue::Vec4(vec4(2.0, 0.0, 0.0, 2.0)));
+    assert_eq!(interp.call_method(live_id!(palette), vec![ShaderValue::Int(2)]).unwrap(), ShaderValue::Vec4(vec4(0.0, 0.0, 1.0, 1.0)));
+    assert!(interp.call_method(live_id!(palette), vec![ShaderValue::Int(3)]).is_err());
+
+    assert_eq!(interp.call_method(live_id!(count), vec![3.5.into()]).unwrap(), ShaderValue::Float(4.0));
+    assert_eq!(interp.call_method(live_id!(halvings), vec![8.0.into()]).unwrap(), ShaderValue::Float(4.0));
+    assert_eq!(interp.call_method(live_id!(unsigned), vec![7u32.into(), 2u32.into()]).unwrap(), ShaderValue::Uint(17));
+    // loops without a break are cut off instead of hanging the caller
+    assert!(interp.call_method(live_id!(forever), vec![]).is_err());
+}
+
+#[test]
+fn cube_sampling() {
+    let shader = analyse("DrawCube");
+    let mut interp = shader.interpreter();
+    let mut sky = ShaderTexture::new_cube(2);
+    // faces are stored in the order +x -x +y -y +z -z, the +z face is the fifth 2x2
+    for (i, color) in [vec4(1.0, 0.0, 0.0, 1.0), vec4(0.0, 1.0, 0.0, 1.0), vec4(0.0, 0.0, 1.0, 1.0), vec4(1.0, 1.0, 1.0, 1.0)].into_iter().enumerate() {
+        sky.pixels[16 + i] = color;
+    }
+    interp.set_texture(live_id!(sky), sky);
+
+    // straight ahead along +z lands in the middle of the face, between all four texels
+    interp.set_field(live_id!(pos), vec2(0.5, 0.5));
+    assert_vec4(interp.pixel().unwrap(), vec4(0.5, 0.5, 0.5, 1.0));
+    // the top left of the +z face is towards -x +y, which is row 0
+    interp.set_field(live_id!(pos), vec2(0.1, 0.9));
+    assert_vec4(interp.pixel().unwrap(), vec4(1.0, 0.0, 0.0, 1.0));
+
+    let draw_shader_def = shader.shader_registry.draw_shader_defs.get(&shader.draw_shader_ptr).unwrap();
+    let const_table = shader.shader_registry.compute_const_table(shader.draw_shader_ptr);
+    let pixel = generate_glsl::generate_pixel_shader(draw_shader_def, &const_table, &shader.shader_registry);
+    assert!(pixel.contains("uniform samplerCube"));
+    assert!(pixel.contains("vec4 sampleCube(samplerCube sampler, vec3 pos){return textureCube(sampler, pos);}"));
+}
@@ -29,6 +29,9 @@ macro_rules!app_main {
             
             cx.borrow_mut().init_websockets(std::option_env!("MAKEPAD_STUDIO_HTTP").unwrap_or(""));
             live_design(&mut *cx.borrow_mut());
+            if std::env::args().any(|v| v == "--validate-shaders") {
+                std::process::exit(cx.borrow_mut().validate_draw_shaders());
+            }
             cx.borrow_mut().init_cx_os();
             Cx::event_loop(cx);
         }
@@ -39,7 +39,7 @@ use {
         draw_list::CxDrawListPool,
         web_socket::WebSocket,
         pass::CxPassPool,
-        texture::{CxTexturePool,TextureFormat,Texture},
+        texture::{CxTexturePool,TextureFormat,Texture,CxTextureReadback},
         geometry::{
             Geometry,
             CxGeometryPool,
@@ -64,6 +64,7 @@ pub struct Cx {
     pub draw_lists: CxDrawListPool,
     pub draw_matrices: CxDrawMatrixPool,
     pub textures: CxTexturePool,
+    pub (crate) texture_readbacks: Vec<CxTextureReadback>,
     pub (crate) geometries: CxGeometryPool,
     pub (crate) start_time: Instant,
     pub (crate) geometries_refs: HashMap<GeometryFingerprint, Weak<Geometry >>,
@@ -223,6 +224,7 @@ impl Cx {
             draw_matrices: Default::default(),
             geometries: Default::default(),
             textures,
+            texture_readbacks: Default::default(),
             geometries_refs: Default::default(),
             
             draw_shaders: Default::default(),
@@ -15,7 +15,9 @@ use {
             Trigger,
             NextFrame,
             HttpRequest,
-            VideoSource
+            VideoSource,
+            ClipboardSelection,
+            ClipboardItem,
         },
         draw_list::DrawListId,
         window::WindowId,
@@ -24,7 +26,7 @@ use {
             Area, 
             //DrawListArea
         },
-        texture::Texture,
+        texture::{Texture, CxTextureReadback, TextureReadbackSource},
         macos_menu::MacosMenu,
         pass::{
             PassId,
@@ -72,6 +74,8 @@ pub enum CxOsOp {
     StartDragging(Vec<DragItem>),
     UpdateMacosMenu(MacosMenu),
     ShowClipboardActions(String),
+    ClipboardRead{request_id: LiveId, selection: ClipboardSelection, mime_types: Vec<String>},
+    ClipboardWrite{selection: ClipboardSelection, items: Vec<ClipboardItem>},
 
     HttpRequest{request_id: LiveId, request:HttpRequest},
 
@@ -148,6 +152,17 @@ impl Cx {
     pub fn show_clipboard_actions(&mut self, selected: String) {
         self.platform_ops.push(CxOsOp::ShowClipboardActions(selected));
     }
+    
+    /// Asynchronously reads the clipboard, answered with `Event::ClipboardRead` carrying the same request_id.
+    /// The mime types are in order of preference, an empty list takes text, then images, then file lists.
+    pub fn clipboard_read(&mut self, request_id: LiveId, selection: ClipboardSelection, mime_types: Vec<String>) {
+        self.platform_ops.push(CxOsOp::ClipboardRead{request_id, selection, mime_types});
+    }
+    
+    /// Puts the items on the clipboard, each one being an alternative representation of the same content
+    pub fn clipboard_write(&mut self, selection: ClipboardSelection, items: Vec<ClipboardItem>) {
+        self.platform_ops.push(CxOsOp::ClipboardWrite{selection, items});
+    }
 
     pub fn start_dragging(&mut self, items: Vec<DragItem>) {
         self.platform_ops.iter().for_each( | p | {
@@ -221,6 +236,19 @@ impl Cx {
         return 1.0;
     }
     
+    /// The window the area is drawn in, following render passes up to the one drawn into a window
+    pub fn window_id_of(&self, area: &Area) -> Option<WindowId> {
+        let mut pass_id_walk = self.draw_lists[area.draw_list_id()?].pass_id?;
+        for _ in 0..25 {
+            match self.passes[pass_id_walk].parent {
+                CxPassParent::Window(window_id) => return Some(window_id),
+                CxPassParent::Pass(next_pass_id) => pass_id_walk = next_pass_id,
+                _ => break
+            }
+        }
+        None
+    }
+    
     pub fn get_delegated_dpi_factor(&mut self, pass_id: PassId) -> f64 {
         let mut pass_id_walk = pass_id;
         for _ in 0..25 {
@@ -287,6 +315,28 @@ impl Cx {
         cxpass.paint_dirty = true;
     }
     
+    /// Repaints the pass and reads back what it drew, the pixels arrive as an
+    /// `Event::TextureReadback` with the same `request_id`.
+    pub fn capture_pass(&mut self, pass_id: PassId, request_id: LiveId) {
+        self.texture_readbacks.push(CxTextureReadback {
+            request_id,
+            source: TextureReadbackSource::Pass(pass_id),
+            rect: None,
+            response: None,
+        });
+        self.repaint_pass(pass_id);
+    }
+    
+    /// Takes a screenshot of the window, see `capture_pass`.
+    pub fn capture_window(&mut self, window_id: WindowId, request_id: LiveId) {
+        if let Some(main_pass_id) = self.windows[window_id].main_pass_id {
+            self.capture_pass(main_pass_id, request_id);
+        }
+        else {
+            crate::error!("capture_window: window has no pass to capture");
+        }
+    }
+    
     pub fn repaint_pass_and_child_passes(&mut self, pass_id: PassId) {
         let cxpass = &mut self.passes[pass_id];
         cxpass.paint_dirty = true;
@@ -8,11 +8,13 @@ use {
         },
     },
     crate::{
-        makepad_live_compiler::{LiveValue, LiveFieldKind, LiveNode, LivePtr, LiveNodeSliceApi},
+        makepad_live_compiler::{LiveValue, LiveFieldKind, LiveNode, LivePtr, LiveNodeSliceApi, LiveRegistry, LiveError, LiveFileId, LiveType},
+        makepad_shader_compiler::source_map::ShaderSourceMap,
         makepad_shader_compiler::*,
         makepad_live_id::*,
         live_traits::*,
-        draw_vars::DrawVars,
+        draw_vars::{DrawVars, add_draw_vars_instances},
+        geometry::GeometryField,
         os::CxOsDrawShader,
         cx::Cx
     }
@@ -69,6 +71,8 @@ pub struct CxDrawShaders {
     pub fingerprints: Vec<DrawShaderFingerprint>,
     pub error_set: HashSet<DrawShaderPtr>,
     pub error_fingerprints: Vec<Vec<LiveNode >>,
+    /// the fields of each geometry type, for draw shaders that are analysed without an instance
+    pub geometry_fields: HashMap<LiveType, Vec<GeometryField >>,
 }
 
 impl CxDrawShaders{
@@ -81,6 +85,81 @@ impl CxDrawShaders{
 }
 
 impl Cx {
+    /// Type checks every draw shader in the registered live files and generates it for the platform
+    /// backend without opening a window, errors are logged with their position in the DSL.
+    /// Returns the process exit code.
+    pub fn validate_draw_shaders(&mut self) -> i32 {
+        fn is_draw_shader(live_registry: &LiveRegistry, live_type: LiveType) -> bool {
+            if let Some(lti) = live_registry.live_type_infos.get(&live_type) {
+                for field in &lti.fields {
+                    if let LiveFieldKind::Deref = field.live_field_kind {
+                        let live_type = field.live_type_info.live_type;
+                        if live_type == LiveType::of::<DrawVars>() || is_draw_shader(live_registry, live_type) {
+                            return true
+                        }
+                    }
+                }
+            }
+            false
+        }
+        
+        self.live_expand();
+        
+        let live_registry_rc = self.live_registry.clone();
+        let live_registry = live_registry_rc.borrow();
+        let mut draw_shader_ptrs = Vec::new();
+        for (file_index, live_file) in live_registry.live_files.iter().enumerate() {
+            for (index, node) in live_file.expanded.nodes.iter().enumerate() {
+                if let LiveValue::Class {live_type, ..} = node.value {
+                    if is_draw_shader(&live_registry, live_type) {
+                        draw_shader_ptrs.push(DrawShaderPtr(LivePtr::from_index(LiveFileId::new(file_index), index, live_file.generation)));
+                    }
+                }
+            }
+        }
+        
+        // the same shader is used in many places, only check it once
+        let mut fingerprints = Vec::new();
+        let mut errors = 0;
+        for draw_shader_ptr in draw_shader_ptrs {
+            let fingerprint = DrawShaderFingerprint::from_ptr(self, draw_shader_ptr);
+            // a declaration without any shader code isn't drawn with
+            if fingerprint.is_empty() || fingerprints.contains(&fingerprint) {
+                continue
+            }
+            fingerprints.push(fingerprint);
+            
+            let geometry_fields = &self.draw_shaders.geometry_fields;
+            let result = self.shader_registry.analyse_draw_shader(&live_registry, draw_shader_ptr, | live_registry, shader_registry, span, draw_shader_query, live_type, draw_shader_def | {
+                match draw_shader_query {
+                    DrawShaderQuery::DrawShader => {
+                        add_draw_vars_instances(live_registry, shader_registry, live_type, draw_shader_def, span);
+                    }
+                    DrawShaderQuery::Geometry => {
+                        for field in geometry_fields.get(&live_type).into_iter().flatten() {
+                            draw_shader_def.add_geometry(field.id, field.ty.clone(), span);
+                        }
+                    }
+                }
+            });
+            match result {
+                Err(e) => {
+                    errors += 1;
+                    log_draw_shader_error(&live_registry, e);
+                }
+                Ok(()) => {
+                    let const_table = self.shader_registry.compute_const_table(draw_shader_ptr);
+                    let draw_shader_def = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr).unwrap();
+                    if !self.os_validate_draw_shader(draw_shader_def, &const_table, &live_registry) {
+                        errors += 1;
+                    }
+                }
+            }
+        }
+        log!("Validated {} draw shaders, {} with errors", fingerprints.len(), errors);
+        if errors > 0 {1} else {0}
+    }
+    
     pub fn flush_draw_shaders(&mut self) {
         self.draw_shaders.generation += 1;
         self.shader_registry.flush_registry();
@@ -92,6 +171,37 @@ impl Cx {
     }
 }
 
+/// Logs a shader error on the DSL, in the json format when cargo asked for it.
+pub fn log_draw_shader_error(live_registry: &LiveRegistry, err: LiveError) {
+    let err = live_registry.live_error_to_live_file_error(err);
+    if std::env::args().find(|v| v == "--message-format=json").is_some(){
+        crate::log::log_with_level(
+            &err.file,
+            err.span.start.line,
+            err.span.start.column,
+            err.span.end.line,
+            err.span.end.column,
+            err.message,
+            crate::log::LogLevel::Error
+        );
+    }
+    else{
+        log!("Error {}", err);
+    }
+}
+
+/// Reports the messages of a platform shader compiler on the DSL statements the failing lines
+/// were generated from. `line_offset` is the number of lines the platform put in front of the
+/// generated source. Returns false if none of the messages could be mapped.
+pub fn report_shader_compiler_log(live_registry: &LiveRegistry, source_map: &ShaderSourceMap, log: &str, line_offset: usize) -> bool {
+    let errors = source_map.map_compiler_log(log, line_offset);
+    let mapped = !errors.is_empty();
+    for err in errors {
+        log_draw_shader_error(live_registry, err);
+    }
+    mapped
+}
+
 impl Index<usize> for CxDrawShaders {
     type Output = CxDrawShader;
     fn index(&self, index: usize) -> &Self::Output {
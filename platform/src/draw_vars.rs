@@ -124,6 +124,65 @@ impl LiveApply for DrawVars {
 
 impl LiveHook for DrawVars {}
 
+fn live_type_to_shader_ty(live_type: LiveType) -> Option<ShaderTy> {
+    if live_type == LiveType::of::<f32>() {Some(ShaderTy::Float)}
+    else if live_type == LiveType::of::<Vec2>() {Some(ShaderTy::Vec2)}
+    else if live_type == LiveType::of::<Vec3>() {Some(ShaderTy::Vec3)}
+    else if live_type == LiveType::of::<Vec4>() {Some(ShaderTy::Vec4)}
+    else {None}
+}
+
+/// Adds the fields that come after the `DrawVars` deref of a draw shader struct as instances.
+pub fn add_draw_vars_instances(live_registry: &LiveRegistry, shader_registry: &ShaderRegistry, live_type: LiveType, draw_shader_def: &mut DrawShaderDef, span: TokenSpan) {
+    fn recur_expand(
+        live_registry: &LiveRegistry,
+        shader_registry: &ShaderRegistry,
+        level: usize,
+        after_draw_vars: &mut bool,
+        live_type: LiveType,
+        draw_shader_def: &mut DrawShaderDef,
+        span: TokenSpan
+    ) {
+        if let Some(lf) = live_registry.live_type_infos.get(&live_type) {
+            
+            let mut slots = 0;
+            for field in &lf.fields {
+                if let LiveFieldKind::Deref = field.live_field_kind {
+                    if field.live_type_info.live_type != LiveType::of::<DrawVars>() {
+                        recur_expand(live_registry, shader_registry, level + 1, after_draw_vars, field.live_type_info.live_type, draw_shader_def, span);
+                        continue
+                    }
+                    else{
+                        *after_draw_vars = true;
+                        continue
+                    }
+                }
+                if *after_draw_vars {
+                    // lets count sizes
+                    //
+                    let live_type = field.live_type_info.live_type;
+                    if shader_registry.enums.get(&live_type).is_some() {
+                        slots += 1;
+                        //draw_shader_def.enums
+                        
+                        draw_shader_def.add_instance(field.id, ShaderTy::Enum(live_type), span, field.live_field_kind);
+                    }
+                    else {
+                        let ty = live_type_to_shader_ty(live_type).expect("Please only put shader-understandable instance fields after draw_vars");
+                        slots += ty.slots();
+                        draw_shader_def.add_instance(field.id, ty, span, field.live_field_kind);
+                    }
+                }
+            }
+            // insert padding
+            if level >0 && slots % 2 == 1 {
+                draw_shader_def.add_instance(LiveId(0), ShaderTy::Float, span, LiveFieldKind::Calc);
+            }
+        }
+    }
+    recur_expand(live_registry, shader_registry, 0, &mut false, live_type, draw_shader_def, span);
+}
+
 impl DrawVars {
     
     pub fn set_texture(&mut self, slot: usize, texture: &Texture) {
@@ -190,14 +249,6 @@ impl DrawVars {
             if cx.draw_shaders.error_fingerprints.iter().find( | fp | **fp == fingerprint).is_some() {
                 return;
             }
-
-            fn live_type_to_shader_ty(live_type: LiveType) -> Option<ShaderTy> {
-                if live_type == LiveType::of::<f32>() {Some(ShaderTy::Float)}
-                else if live_type == LiveType::of::<Vec2>() {Some(ShaderTy::Vec2)}
-                else if live_type == LiveType::of::<Vec3>() {Some(ShaderTy::Vec3)}
-                else if live_type == LiveType::of::<Vec4>() {Some(ShaderTy::Vec4)}
-                else {None}
-            }
             // ok ! we have to compile it
             //let live_factories = &cx.live_factories;
             let live_registry_cp = cx.live_registry.clone();
@@ -206,53 +257,7 @@ impl DrawVars {
             let result = cx.shader_registry.analyse_draw_shader(&live_registry, draw_shader_ptr, | live_registry, shader_registry, span, draw_shader_query, live_type, draw_shader_def | {
                 match draw_shader_query {
                     DrawShaderQuery::DrawShader => {
-                        fn recur_expand(
-                            live_registry: &LiveRegistry,
-                            shader_registry: &ShaderRegistry,
-                            level: usize,
-                            after_draw_vars: &mut bool,
-                            live_type: LiveType,
-                            draw_shader_def: &mut DrawShaderDef,
-                            span: TokenSpan
-                        ) {
-                            if let Some(lf) = live_registry.live_type_infos.get(&live_type) {
-                                
-                                let mut slots = 0;
-                                for field in &lf.fields {
-                                    if let LiveFieldKind::Deref = field.live_field_kind {
-                                        if field.live_type_info.live_type != LiveType::of::<DrawVars>() {
-                                            recur_expand(live_registry, shader_registry, level + 1, after_draw_vars, field.live_type_info.live_type, draw_shader_def, span);
-                                            continue
-                                        }
-                                        else{
-                                            *after_draw_vars = true;
-                                            continue
-                                        }
-                                    }
-                                    if *after_draw_vars {
-                                        // lets count sizes
-                                        //
-                                        let live_type = field.live_type_info.live_type;
-                                        if shader_registry.enums.get(&live_type).is_some() {
-                                            slots += 1;
-                                            //draw_shader_def.enums
-                                            
-                                            draw_shader_def.add_instance(field.id, ShaderTy::Enum(live_type), span, field.live_field_kind);
-                                        }
-                                        else {
-                                            let ty = live_type_to_shader_ty(live_type).expect("Please only put shader-understandable instance fields after draw_vars");
-                                            slots += ty.slots();
-                                            draw_shader_def.add_instance(field.id, ty, span, field.live_field_kind);
-                                        }
-                                    }
-                                }
-                                // insert padding
-                                if level >0 && slots % 2 == 1 {
-                                    draw_shader_def.add_instance(LiveId(0), ShaderTy::Float, span, LiveFieldKind::Calc);
-                                }
-                            }
-                        }
-                        recur_expand(live_registry, shader_registry, 0, &mut false, live_type, draw_shader_def, span);
+                        add_draw_vars_instances(live_registry, shader_registry, live_type, draw_shader_def, span);
                     }
                     DrawShaderQuery::Geometry => {
                         if live_type == geometry_fields.live_type_check() {
@@ -274,21 +279,7 @@ impl DrawVars {
                     cx.draw_shaders.error_set.insert(draw_shader_ptr);
                     cx.draw_shaders.error_fingerprints.push(fingerprint);
                     // ok so. lets get the source for this file id
-                    let err = live_registry.live_error_to_live_file_error(e);
-                    if std::env::args().find(|v| v == "--message-format=json").is_some(){
-                        crate::log::log_with_level(
-                            &err.file,
-                            err.span.start.line,
-                            err.span.start.column,
-                            err.span.end.line,
-                            err.span.end.column,
-                            err.message,
-                            crate::log::LogLevel::Error
-                        );
-                    }
-                    else{
-                        log!("Error {}", err);
-                    }
+                    log_draw_shader_error(&live_registry, e);
                 }
                 Ok(()) => {
                     // OK! SO the shader parsed
@@ -457,8 +448,8 @@ impl DrawVars {
                             }
                         }
                         draw_call.instance_dirty = true;
+                        return
                     }
-                    return
                 }
                 for input in &sh.mapping.user_uniforms.inputs {
                     if input.id == id {
@@ -0,0 +1,111 @@
+use {
+    crate::{
+        makepad_live_id::LiveId,
+    },
+};
+
+pub const MIME_TEXT_PLAIN: &str = "text/plain";
+pub const MIME_TEXT_URI_LIST: &str = "text/uri-list";
+pub const MIME_IMAGE_PNG: &str = "image/png";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClipboardSelection {
+    /// The regular copy / paste clipboard
+    Clipboard,
+    /// The X11 'select to copy, middle click to paste' selection. Platforms without it ignore writes
+    Primary,
+}
+
+/// One representation of the clipboard contents. Text is always stored as utf8.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClipboardItem {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl ClipboardItem {
+    pub fn new(mime_type: &str, data: Vec<u8>) -> Self {
+        Self {mime_type: mime_type.to_string(), data}
+    }
+
+    pub fn text(text: &str) -> Self {
+        Self::new(MIME_TEXT_PLAIN, text.as_bytes().to_vec())
+    }
+
+    pub fn uri_list(uris: &[String]) -> Self {
+        let mut data = String::new();
+        for uri in uris {
+            data.push_str(uri);
+            data.push_str("\r\n");
+        }
+        Self::new(MIME_TEXT_URI_LIST, data.into_bytes())
+    }
+
+    pub fn png(data: Vec<u8>) -> Self {
+        Self::new(MIME_IMAGE_PNG, data)
+    }
+
+    /// Matches `text/plain` as well as its parameterised forms like `text/plain;charset=utf-8`
+    pub fn is_mime_type(&self, mime_type: &str) -> bool {
+        mime_type_matches(&self.mime_type, mime_type)
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.is_mime_type(MIME_TEXT_PLAIN)
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.mime_type.starts_with("image/")
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        if self.is_text() || self.is_mime_type(MIME_TEXT_URI_LIST) {
+            std::str::from_utf8(&self.data).ok()
+        }
+        else {
+            None
+        }
+    }
+
+    /// The entries of a `text/uri-list`, comment lines are skipped
+    pub fn uris(&self) -> Vec<String> {
+        if !self.is_mime_type(MIME_TEXT_URI_LIST) {
+            return Vec::new()
+        }
+        self.as_text().unwrap_or("").lines()
+            .map( | line | line.trim())
+            .filter( | line | !line.is_empty() && !line.starts_with('#'))
+            .map( | line | line.to_string())
+            .collect()
+    }
+}
+
+/// The mime types a read tries in order, an empty list takes text, then images, then file lists
+pub fn read_mime_types(mime_types: &[String]) -> Vec<String> {
+    if mime_types.is_empty() {
+        vec![MIME_TEXT_PLAIN.to_string(), MIME_IMAGE_PNG.to_string(), MIME_TEXT_URI_LIST.to_string()]
+    }
+    else {
+        mime_types.to_vec()
+    }
+}
+
+pub fn mime_type_matches(mime_type: &str, base: &str) -> bool {
+    let essence = mime_type.split(';').next().unwrap_or("").trim();
+    essence.eq_ignore_ascii_case(base.split(';').next().unwrap_or("").trim())
+}
+
+/// Sent to everyone in response to `Cx::clipboard_read`, match it up using the `request_id`
+#[derive(Clone, Debug)]
+pub struct ClipboardReadEvent {
+    pub request_id: LiveId,
+    pub selection: ClipboardSelection,
+    pub response: Result<ClipboardItem, String>,
+}
+
+/// A paste of clipboard contents that aren't text, like an image or a list of files.
+/// Text pastes keep arriving as `TextInput` with `was_paste` set.
+#[derive(Clone, Debug)]
+pub struct ClipboardPasteEvent {
+    pub item: ClipboardItem,
+}
@@ -16,6 +16,8 @@ use {
             drag_drop::*,
             network::*,
             video_playback::*,
+            clipboard::*,
+            texture_readback::*,
         },
         action::ActionsBuf,
         animator::Ease,
@@ -66,12 +68,17 @@ pub enum Event {
     KeyDown(KeyEvent),
     KeyUp(KeyEvent),
     TextInput(TextInputEvent),
+    TextComposition(TextCompositionEvent),
     TextCopy(TextClipboardEvent),
     TextCut(TextClipboardEvent),
+    ClipboardRead(ClipboardReadEvent),
+    ClipboardPaste(ClipboardPasteEvent),
     
     Drag(DragEvent),
     Drop(DropEvent),
     DragEnd,
+    /// the drag moved out of our windows, but goes on elsewhere
+    DragLeave,
     
     Actions(ActionsBuf),
     AudioDevices(AudioDevicesEvent),
@@ -85,6 +92,7 @@ pub enum Event {
     VideoPlaybackResourcesReleased(VideoPlaybackResourcesReleasedEvent),
     VideoDecodingError(VideoDecodingErrorEvent),
     TextureHandleReady(TextureHandleReadyEvent),
+    TextureReadback(TextureReadbackEvent),
  
     #[cfg(target_arch = "wasm32")]
     ToWasmMsg(ToWasmMsgEvent),
@@ -153,9 +161,14 @@ impl Event{
             45=>"TextureHandleReady",
             46=>"MouseLeave",
             47=>"Actions",
+            48=>"ClipboardRead",
+            49=>"ClipboardPaste",
+            50=>"TextComposition",
+            51=>"TextureReadback",
+            52=>"DragLeave",
                                                  
             #[cfg(target_arch = "wasm32")]
-            48=>"ToWasmMsg",
+            53=>"ToWasmMsg",
             _=>panic!()
         }
     }
@@ -218,9 +231,14 @@ impl Event{
             Self::TextureHandleReady(_)=>45,
             Self::MouseLeave(_)=>46,
             Self::Actions(_)=>47,
+            Self::ClipboardRead(_)=>48,
+            Self::ClipboardPaste(_)=>49,
+            Self::TextComposition(_)=>50,
+            Self::TextureReadback(_)=>51,
+            Self::DragLeave=>52,
                                      
             #[cfg(target_arch = "wasm32")]
-            Self::ToWasmMsg(_)=>48,
+            Self::ToWasmMsg(_)=>53,
         }
     }
 }
@@ -233,8 +251,10 @@ pub enum Hit{
     KeyUp(KeyEvent),
     Trigger(TriggerHitEvent),
     TextInput(TextInputEvent),
+    TextComposition(TextCompositionEvent),
     TextCopy(TextClipboardEvent),
     TextCut(TextClipboardEvent),
+    ClipboardPaste(ClipboardPasteEvent),
     
     FingerScroll(FingerScrollEvent),
     FingerDown(FingerDownEvent),
@@ -656,6 +656,11 @@ impl Event {
                     return Hit::TextInput(ti.clone())
                 }
             },
+            Event::TextComposition(tc) => {
+                if cx.keyboard.has_key_focus(area) {
+                    return Hit::TextComposition(tc.clone())
+                }
+            },
             Event::TextCopy(tc) => {
                 if cx.keyboard.has_key_focus(area) {
                     return Hit::TextCopy(tc.clone());
@@ -666,6 +671,11 @@ impl Event {
                     return Hit::TextCut(tc.clone());
                 }
             },
+            Event::ClipboardPaste(cp) => {
+                if cx.keyboard.has_key_focus(area) {
+                    return Hit::ClipboardPaste(cp.clone());
+                }
+            },
             Event::Scroll(e) => {
                 let digit_id = live_id!(mouse).into();
                 
@@ -120,6 +120,38 @@ pub struct TextInputEvent {
     pub was_paste: bool
 }
 
+#[derive(Clone, Copy, Debug, SerBin, DeBin, SerJson, DeJson, PartialEq)]
+pub enum TextCompositionStyle {
+    /// The part of the preedit that is still being composed
+    Underline,
+    /// The clause the input method is currently converting
+    Highlight,
+}
+
+#[derive(Clone, Debug, SerBin, DeBin, SerJson, DeJson, PartialEq)]
+pub struct TextCompositionSegment {
+    /// char range into the composition text
+    pub start: usize,
+    pub end: usize,
+    pub style: TextCompositionStyle,
+}
+
+/// The uncommitted preedit string of an input method. It replaces the previous composition,
+/// an empty `text` means the composition ended. The committed text arrives as a `TextInput`.
+#[derive(Clone, Debug, Default, SerBin, DeBin, SerJson, DeJson, PartialEq)]
+pub struct TextCompositionEvent {
+    pub text: String,
+    /// cursor position in chars
+    pub cursor: usize,
+    pub segments: Vec<TextCompositionSegment>,
+}
+
+impl TextCompositionEvent {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TextClipboardEvent {
     pub response: Rc<RefCell<Option<String>>>
@@ -6,6 +6,8 @@ pub mod xr;
 pub mod drag_drop;
 pub mod network;
 pub mod video_playback;
+pub mod clipboard;
+pub mod texture_readback;
 
 pub use event::*;
 pub use finger::*;
@@ -15,3 +17,5 @@ pub use xr::*;
 pub use drag_drop::*;
 pub use network::*;
 pub use video_playback::*;
+pub use clipboard::*;
+pub use texture_readback::*;
@@ -0,0 +1,28 @@
+use {
+    crate::{
+        makepad_live_id::LiveId,
+    },
+};
+
+/// Pixels read back from the gpu, rows go from top to bottom.
+#[derive(Clone, Debug)]
+pub enum TextureReadbackData {
+    BGRAu8 {width: usize, height: usize, data: Vec<u32>},
+    RGBAf32 {width: usize, height: usize, data: Vec<f32>},
+}
+
+impl TextureReadbackData {
+    pub fn width_height(&self) -> (usize, usize) {
+        match self {
+            Self::BGRAu8 {width, height, ..} => (*width, *height),
+            Self::RGBAf32 {width, height, ..} => (*width, *height),
+        }
+    }
+}
+
+/// The answer to `Texture::read_pixels`, `Cx::capture_pass` or `Cx::capture_window`.
+#[derive(Clone, Debug)]
+pub struct TextureReadbackEvent {
+    pub request_id: LiveId,
+    pub response: Result<TextureReadbackData, String>,
+}
@@ -62,6 +62,8 @@ pub use makepad_objc_sys;
 pub use ::makepad_windows as windows;
 
 pub use makepad_futures;
+
+pub use makepad_http;
  
 pub use {
     makepad_shader_compiler,
@@ -181,7 +183,17 @@ pub use {
             KeyEvent,
             KeyFocusEvent,
             TextInputEvent,
+            TextCompositionEvent,
+            TextCompositionSegment,
+            TextCompositionStyle,
             TextClipboardEvent,
+            ClipboardSelection,
+            ClipboardItem,
+            ClipboardReadEvent,
+            ClipboardPasteEvent,
+            MIME_TEXT_PLAIN,
+            MIME_TEXT_URI_LIST,
+            MIME_IMAGE_PNG,
             WindowCloseRequestedEvent,
             WindowClosedEvent,
             WindowDragQueryResponse,
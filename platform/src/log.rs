@@ -46,10 +46,12 @@ macro_rules!warning {
 }
 
 
-#[derive(Clone, PartialEq, Eq, Copy, Debug, SerBin, DeBin)]
+#[derive(Clone, PartialEq, Eq, Copy, Debug, Default, SerBin, DeBin)]
+#[bin_version = 1]
 pub enum LogLevel{
     Warning,
     Error,
+    #[default]
     Log,
     Wait,
     Panic,
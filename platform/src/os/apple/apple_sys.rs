@@ -147,6 +147,7 @@ extern {
     pub static NSPasteboardTypeFileURL: ObjcId;
     pub static NSPasteboardTypeURL: ObjcId;
     pub static NSPasteboardTypeString: ObjcId;
+    pub static NSPasteboardTypePNG: ObjcId;
 }
 
 #[link(name = "Vision", kind = "framework")]
@@ -582,6 +583,7 @@ pub struct MTLClearColor {
 pub enum MTLPixelFormat {
     //RGBA8Unorm = 70,
     R8Unorm = 10,
+    R16Unorm = 20,
     RG8Unorm   = 30,
     R32Float = 55,
     BGRA8Unorm = 80,
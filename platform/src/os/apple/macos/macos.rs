@@ -213,6 +213,7 @@ impl Cx {
                 }
             }
         }
+        self.call_texture_readback_events();
     }
     
     pub (crate) fn handle_networking_events(&mut self) {
@@ -421,7 +422,7 @@ impl Cx {
             }
         }
         
-        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || paint_dirty {
+        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || !self.texture_readbacks.is_empty() || paint_dirty {
             EventFlow::Poll
         } else {
             EventFlow::Wait
@@ -518,6 +519,17 @@ impl Cx {
                 CxOsOp::ShowClipboardActions(_request) => {
                     crate::log!("Show clipboard actions not supported yet");
                 }
+                CxOsOp::ClipboardRead{request_id, selection, mime_types} => {
+                    let response = get_macos_app_global().clipboard_read(&mime_types);
+                    self.call_event_handler(&Event::ClipboardRead(crate::event::ClipboardReadEvent {
+                        request_id,
+                        selection,
+                        response
+                    }));
+                }
+                CxOsOp::ClipboardWrite{selection, ..} => {
+                    Cx::log_clipboard_write_unsupported(selection);
+                }
                 /*CxOsOp::WebSocketOpen {request_id, request} => {
                     web_socket_open(request_id, request, self.os.network_response.sender.clone());
                 }
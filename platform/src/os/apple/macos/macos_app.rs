@@ -41,6 +41,12 @@ use {
             TextClipboardEvent,
             TimerEvent,
             KeyModifiers,
+            ClipboardItem,
+            MIME_TEXT_PLAIN,
+            MIME_TEXT_URI_LIST,
+            MIME_IMAGE_PNG,
+            read_mime_types,
+            mime_type_matches,
         },
         cursor::MouseCursor,
         macos_menu::{
@@ -628,6 +634,61 @@ impl MacosApp {
         }
     }
     
+    /// Reads the general pasteboard as the first of the mime types it has
+    pub fn clipboard_read(&self, mime_types: &[String]) -> Result<ClipboardItem, String> {
+        for mime_type in read_mime_types(mime_types) {
+            if mime_type_matches(&mime_type, MIME_TEXT_PLAIN) {
+                let nsstring: ObjcId = unsafe {msg_send![self.pasteboard, stringForType: NSPasteboardTypeString]};
+                if nsstring != nil {
+                    return Ok(ClipboardItem::text(&nsstring_to_string(nsstring)))
+                }
+            }
+            else if mime_type_matches(&mime_type, MIME_IMAGE_PNG) {
+                let data: ObjcId = unsafe {msg_send![self.pasteboard, dataForType: NSPasteboardTypePNG]};
+                if data != nil {
+                    let bytes: *const u8 = unsafe {msg_send![data, bytes]};
+                    let length: usize = unsafe {msg_send![data, length]};
+                    let data = unsafe {std::slice::from_raw_parts(bytes, length)}.to_vec();
+                    return Ok(ClipboardItem::png(data))
+                }
+            }
+            else if mime_type_matches(&mime_type, MIME_TEXT_URI_LIST) {
+                let uris = self.pasteboard_file_uris();
+                if !uris.is_empty() {
+                    return Ok(ClipboardItem::uri_list(&uris))
+                }
+            }
+        }
+        Err("Clipboard has no content in the requested formats".to_string())
+    }
+    
+    fn pasteboard_file_uris(&self) -> Vec<String> {
+        unsafe {
+            let class: ObjcId = msg_send![class!(NSURL), class];
+            let classes: ObjcId = msg_send![class!(NSArray), arrayWithObject: class];
+            let object: ObjcId = msg_send![class!(NSNumber), numberWithBool: true];
+            let options: ObjcId = msg_send![
+                class!(NSDictionary),
+                dictionaryWithObject: object
+                forKey: NSPasteboardURLReadingFileURLsOnlyKey
+            ];
+            let urls: ObjcId = msg_send![self.pasteboard, readObjectsForClasses: classes options: options];
+            if urls == nil {
+                return Vec::new()
+            }
+            let count: usize = msg_send![urls, count];
+            let mut uris = Vec::with_capacity(count);
+            for index in 0..count {
+                let url: ObjcId = msg_send![urls, objectAtIndex: index];
+                let string: ObjcId = msg_send![url, absoluteString];
+                if string != nil {
+                    uris.push(nsstring_to_string(string));
+                }
+            }
+            uris
+        }
+    }
+    
     pub fn start_timer(&mut self, timer_id: u64, interval: f64, repeats: bool) {
         unsafe {
             let pool: ObjcId = msg_send![class!(NSAutoreleasePool), new];
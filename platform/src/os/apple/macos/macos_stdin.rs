@@ -359,6 +359,9 @@ impl Cx {
                 CxOsOp::HttpRequest {request_id, request} => {
                     make_http_request(request_id, request, self.os.network_response.sender.clone());
                 },
+                CxOsOp::ClipboardRead{request_id, selection, ..} => {
+                    self.call_clipboard_read_unsupported(request_id, selection);
+                }
                 _ => ()
                 /*
                 CxOsOp::CloseWindow(_window_id) => {},
@@ -11,7 +11,10 @@ use {
         makepad_shader_compiler::{
             generate_metal,
             generate_metal::MetalGeneratedShader,
+            DrawShaderDef,
+            DrawShaderConstTable,
         },
+        makepad_live_compiler::LiveRegistry,
         makepad_math::*,
         makepad_live_id::*,
         os::{
@@ -23,6 +26,7 @@ use {
             cx_stdin::PresentableDraw,
         },
         draw_list::DrawListId,
+        draw_shader::report_shader_compiler_log,
         cx::Cx,
         pass::{PassClearColor, PassClearDepth, PassId},
         texture::{
@@ -30,6 +34,7 @@ use {
             Texture,
             TexturePixel,
             TextureFormat,
+            TextureUpdated,
         },
     },
     std::sync::{
@@ -521,7 +526,7 @@ impl Cx {
                     }
                 }
                 if cx_shader.os_shader_id.is_none() {
-                    if let Some(shp) = CxOsDrawShader::new(metal_cx, gen) {
+                    if let Some(shp) = CxOsDrawShader::new(metal_cx, gen, &self.live_registry.borrow()) {
                         cx_shader.os_shader_id = Some(self.draw_shaders.os_shaders.len());
                         self.draw_shaders.os_shaders.push(shp);
                     }
@@ -531,6 +536,18 @@ impl Cx {
         self.draw_shaders.compile_set.clear();
     }
     
+    /// Compiles the metal source of a shader without a window, metal only needs the device for it
+    pub (crate) fn os_validate_draw_shader(&self, draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, live_registry: &LiveRegistry) -> bool {
+        let shader = generate_metal::generate_shader(draw_shader_def, const_table, &self.shader_registry);
+        match get_default_metal_device() {
+            Some(device) => mtl_compile_library(device, &shader, live_registry).is_ok(),
+            None => {
+                crate::error!("Cannot get default metal device, the shaders were only generated");
+                true
+            }
+        }
+    }
+    
     #[cfg(target_os="macos")]
     pub fn share_texture_for_presentable_image(
         &mut self,
@@ -626,35 +643,13 @@ impl CxOsDrawShader {
     pub (crate) fn new(
         metal_cx: &MetalCx,
         shader: MetalGeneratedShader,
+        live_registry: &LiveRegistry,
     ) -> Option<Self> {
-        let options = RcObjcId::from_owned(unsafe {msg_send![class!(MTLCompileOptions), new]});
-        unsafe {
-            let _: () = msg_send![options.as_id(), setFastMathEnabled: YES];
+        let library = match mtl_compile_library(metal_cx.device, &shader, live_registry) {
+            Ok(library) => library,
+            Err(string) => panic!("{}", string)
         };
         
-        let mut error: ObjcId = nil;
-        
-        let library = RcObjcId::from_owned(match NonNull::new(unsafe {
-            msg_send![
-                metal_cx.device,
-                newLibraryWithSource: str_to_nsstring(&shader.mtlsl)
-                options: options
-                error: &mut error
-            ]
-        }) {
-            Some(library) => library,
-            None => {
-                let description: ObjcId = unsafe {msg_send![error, localizedDescription]};
-                let string = nsstring_to_string(description);
-                let mut out = format!("{}\n", string);
-                for (index, line) in shader.mtlsl.split("\n").enumerate() {
-                    out.push_str(&format!("{}: {}\n", index + 1, line));
-                }
-                crate::error!("{}", out);
-                panic!("{}", string);
-            }
-        });
-        
         let descriptor = RcObjcId::from_owned(NonNull::new(unsafe {
             msg_send![class!(MTLRenderPipelineDescriptor), new]
         }).unwrap());
@@ -813,6 +808,7 @@ fn texture_pixel_to_mtl_pixel(pix:&TexturePixel)-> MTLPixelFormat {
          TexturePixel::Ru8  => MTLPixelFormat::R8Unorm,
          TexturePixel::RGu8  => MTLPixelFormat::RG8Unorm,
          TexturePixel::Rf32  => MTLPixelFormat::R32Float,
+         TexturePixel::Ru16  => MTLPixelFormat::R16Unorm,
          TexturePixel::D32 => MTLPixelFormat::Depth32Float,
      }   
 }
@@ -837,10 +833,33 @@ impl CxTexture {
             let _: () = unsafe {msg_send![descriptor.as_id(), setWidth: alloc.width as u64]};
             let _: () = unsafe {msg_send![descriptor.as_id(), setHeight: alloc.height as u64]};
             let _: () = unsafe{msg_send![descriptor.as_id(), setPixelFormat: texture_pixel_to_mtl_pixel(&alloc.pixel)]};
+            if let TextureFormat::VecMipBGRAu8_32{max_level, ..} = &self.format{
+                // down to 1x1, or max_level like GL_TEXTURE_MAX_LEVEL
+                let levels = (usize::BITS - alloc.width.max(alloc.height).leading_zeros()) as usize;
+                let levels = max_level.map_or(levels, |max_level| levels.min(max_level + 1));
+                let _: () = unsafe {msg_send![descriptor.as_id(), setMipmapLevelCount: levels as u64]};
+            }
             let texture:ObjcId = unsafe{msg_send![metal_cx.device, newTextureWithDescriptor: descriptor]};
             self.os.texture = Some(RcObjcId::from_owned(NonNull::new(texture).unwrap()));
         }
-        if self.check_updated(){
+        let updated = self.check_updated();
+        if let TextureUpdated::Partial(rect) = updated{
+            let (data, bytes_per_pixel, row_length) = self.format.vec_pixel_data().unwrap();
+            for rect in self.format.vec_upload_rects(rect){
+                let region = MTLRegion {
+                    origin: MTLOrigin {x: rect.x as u64, y: rect.y as u64, z: 0},
+                    size: MTLSize {width: rect.width as u64, height: rect.height as u64, depth: 1}
+                };
+                let () = unsafe {msg_send![
+                    self.os.texture.as_ref().unwrap().as_id(),
+                    replaceRegion: region
+                    mipmapLevel: 0
+                    withBytes: data.add((rect.y * row_length + rect.x) * bytes_per_pixel) as *const std::ffi::c_void
+                    bytesPerRow: (row_length * bytes_per_pixel) as u64
+                ]};
+            }
+        }
+        else if updated == TextureUpdated::Full{
             fn update_data(texture:&Option<RcObjcId>, width: usize, height: usize, bpp: u64, data: *const std::ffi::c_void){
                 let region = MTLRegion {
                     origin: MTLOrigin {x: 0, y: 0, z: 0},
@@ -857,7 +876,8 @@ impl CxTexture {
             }
             
             match &self.format{
-                TextureFormat::VecBGRAu8_32{width, height, data}=>{
+                TextureFormat::VecBGRAu8_32{width, height, data} |
+                TextureFormat::VecMipBGRAu8_32{width, height, data, ..}=>{
                     update_data(&self.os.texture, *width, *height, 4,  data.as_ptr() as *const std::ffi::c_void);
                 }
                 TextureFormat::VecRGBAf32{width, height, data}=>{
@@ -872,9 +892,28 @@ impl CxTexture {
                 TextureFormat::VecRf32{width, height, data}=>{
                     update_data(&self.os.texture, *width, *height, 4,  data.as_ptr() as *const std::ffi::c_void);
                 }
+                TextureFormat::VecRu16{width, height, data}=>{
+                    update_data(&self.os.texture, *width, *height, 2,  data.as_ptr() as *const std::ffi::c_void);
+                }
+                TextureFormat::VecNV12{width, height, data} |
+                TextureFormat::VecI420{width, height, data}=>{
+                    update_data(&self.os.texture, *width, *height + *height / 2, 1,  data.as_ptr() as *const std::ffi::c_void);
+                }
                 _=>panic!()
             }
         }
+        // the smaller levels are made from level 0 again after every upload, partial ones too
+        if updated != TextureUpdated::Empty{
+            if let TextureFormat::VecMipBGRAu8_32{..} = &self.format{
+                unsafe{
+                    let command_buffer: ObjcId = msg_send![metal_cx.command_queue, commandBuffer];
+                    let encoder: ObjcId = msg_send![command_buffer, blitCommandEncoder];
+                    let () = msg_send![encoder, generateMipmapsForTexture: self.os.texture.as_ref().unwrap().as_id()];
+                    let () = msg_send![encoder, endEncoding];
+                    let () = msg_send![command_buffer, commit];
+                }
+            }
+        }
     }
     
     #[cfg(target_os = "macos")]
@@ -1042,6 +1081,38 @@ impl Drop for MetalRwLockGpuReadGuard {
     }
 }
 
+/// Compiles the metal source of a shader, errors are reported on the DSL
+fn mtl_compile_library(device: ObjcId, shader: &MetalGeneratedShader, live_registry: &LiveRegistry) -> Result<RcObjcId, String> {
+    let options = RcObjcId::from_owned(unsafe {msg_send![class!(MTLCompileOptions), new]});
+    unsafe {
+        let _: () = msg_send![options.as_id(), setFastMathEnabled: YES];
+    };
+    
+    let mut error: ObjcId = nil;
+    
+    match NonNull::new(unsafe {
+        msg_send![
+            device,
+            newLibraryWithSource: str_to_nsstring(&shader.mtlsl)
+            options: options
+            error: &mut error
+        ]
+    }) {
+        Some(library) => Ok(RcObjcId::from_owned(library)),
+        None => {
+            let description: ObjcId = unsafe {msg_send![error, localizedDescription]};
+            let string = nsstring_to_string(description);
+            let mut out = format!("{}\n", string);
+            for (index, line) in shader.mtlsl.split("\n").enumerate() {
+                out.push_str(&format!("{}: {}\n", index + 1, line));
+            }
+            crate::error!("{}", out);
+            report_shader_compiler_log(live_registry, &shader.source_map, &string, 0);
+            Err(string)
+        }
+    }
+}
+
 pub fn get_default_metal_device() -> Option<ObjcId> {
     unsafe {
         let dev = MTLCreateSystemDefaultDevice();
@@ -92,6 +92,7 @@ impl Cx {
                 }
             }
         }
+        self.call_texture_readback_events();
     }
 
     pub(crate) fn handle_networking_events(&mut self) {
@@ -200,7 +201,7 @@ impl Cx {
             }
         }
         
-        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || paint_dirty {
+        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || !self.texture_readbacks.is_empty() || paint_dirty {
             EventFlow::Poll
         } else {
             EventFlow::Wait
@@ -261,6 +262,12 @@ impl Cx {
                 CxOsOp::ShowClipboardActions(_request) => {
                     crate::log!("Show clipboard actions not supported yet");
                 }
+                CxOsOp::ClipboardRead{request_id, selection, ..} => {
+                    self.call_clipboard_read_unsupported(request_id, selection);
+                }
+                CxOsOp::ClipboardWrite{selection, ..} => {
+                    Cx::log_clipboard_write_unsupported(selection);
+                }
                 CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
                 CxOsOp::PauseVideoPlayback(_) => todo!(),
                 CxOsOp::ResumeVideoPlayback(_) => todo!(),
@@ -81,7 +81,7 @@ pub struct OsWebSocket{
 
 impl OsWebSocket{
 
-    pub fn send_message(&mut self, message:WebSocketMessage)->Result<(),()>{
+    pub fn send_message(&mut self, message:WebSocketMessage)->Result<(),String>{
         unsafe{
             let rx_sender = self.rx_sender.clone();
             let handler = objc_block!(move | error: ObjcId | {
@@ -3,6 +3,7 @@ use {
     std::time::{Instant},
     std::collections::{HashSet, HashMap},
     crate::{
+        makepad_live_id::LiveId,
         cx::Cx,
         pass::{
             PassId,
@@ -14,6 +15,9 @@ use {
             Event,
             KeyFocusEvent,
             NextFrameEvent,
+            TextureReadbackEvent,
+            ClipboardReadEvent,
+            ClipboardSelection,
         },
         studio::{AppToStudio,ProfileSampleEvent},
     }
@@ -196,6 +200,39 @@ impl Cx {
         self.call_event_handler(&Event::Draw(draw_event));
     }
 
+    // the backend answers the readbacks it supports during the repaint, the rest fail here
+    pub (crate) fn call_texture_readback_events(&mut self) {
+        if self.texture_readbacks.is_empty() {
+            return
+        }
+        let readbacks = std::mem::take(&mut self.texture_readbacks);
+        for readback in readbacks {
+            let response = readback.response.unwrap_or_else( || Err("Texture readback is not supported on this platform".to_string()));
+            self.call_event_handler(&Event::TextureReadback(TextureReadbackEvent {
+                request_id: readback.request_id,
+                response
+            }));
+        }
+    }
+
+    // for the backends that can't read the clipboard yet
+    #[allow(dead_code)]
+    pub (crate) fn call_clipboard_read_unsupported(&mut self, request_id: LiveId, selection: ClipboardSelection) {
+        self.call_event_handler(&Event::ClipboardRead(ClipboardReadEvent {
+            request_id,
+            selection,
+            response: Err("Clipboard reading is not supported on this platform".to_string())
+        }));
+    }
+
+    // only X11 has a primary selection, so elsewhere just the clipboard is missed
+    #[allow(dead_code)]
+    pub (crate) fn log_clipboard_write_unsupported(selection: ClipboardSelection) {
+        if selection == ClipboardSelection::Clipboard {
+            crate::log!("Clipboard write not supported yet");
+        }
+    }
+
     pub (crate) fn call_next_frame_event(&mut self, time: f64) {
         let mut set = HashSet::default();
         std::mem::swap(&mut set, &mut self.new_next_frames);
@@ -378,7 +378,7 @@ impl Cx {
             }
             self.handle_platform_ops();
             
-            if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 {
+            if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || !self.texture_readbacks.is_empty() {
                 if self.new_next_frames.len() != 0 {
                     self.call_next_frame_event(self.os.timers.time_now());
                 }
@@ -618,6 +618,8 @@ impl Cx {
             zbias_step,
         );
         
+        self.opengl_read_pass_pixels(pass_id, 0, self.os.display_size.x as usize, self.os.display_size.y as usize, self.os.display_size.y as usize);
+        
         //to_java.swap_buffers();
         //unsafe {
         //direct_app.drm.swap_buffers_and_wait(&direct_app.egl);
@@ -651,8 +653,7 @@ impl Cx {
                 }
             }
         }
-        
-        
+        self.opengl_handle_texture_readbacks();
     }
     
     fn handle_platform_ops(&mut self) -> EventFlow {
@@ -733,6 +734,9 @@ impl Cx {
                         android_jni::to_java_cleanup_video_playback_resources(env, video_id);
                     }
                 },
+                CxOsOp::ClipboardRead{request_id, selection, ..} => {
+                    self.call_clipboard_read_unsupported(request_id, selection);
+                }
                 _ => ()
             }
         }
@@ -16,7 +16,7 @@ pub type WebsocketIncomingMessageFn = Box<dyn FnMut(WebSocketMessage) + Send  +
 
 impl OsWebSocket{
 
-    pub fn send_message(&mut self, message:WebSocketMessage)->Result<(),()>{
+    pub fn send_message(&mut self, message:WebSocketMessage)->Result<(),String>{
         let frame = match &message{
             WebSocketMessage::String(data)=>{
                 let header = MessageHeader::from_len(data.len(), MessageFormat::Text, true);
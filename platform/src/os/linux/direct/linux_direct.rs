@@ -9,7 +9,8 @@ use {
     self::super::super::{
         gl_sys,
         select_timer::SelectTimers,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        http::make_http_request,
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi},
@@ -20,6 +21,7 @@ use {
             TimerEvent,
             Event,
             WindowGeom,
+            NetworkResponseChannel,
         },
         window::CxWindowPool,
         pass::CxPassParent,
@@ -180,19 +182,30 @@ impl Cx {
                         self.handle_media_signals();
                         self.call_event_handler(&Event::Signal);
                     }
+                    self.handle_networking_events();
                 }
                 else {
                     self.call_event_handler(&Event::Timer(e))
                 }
             }
         }
-        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 {
+        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || !self.texture_readbacks.is_empty() {
             EventFlow::Poll
         } else {
             EventFlow::Wait
         }
     }
     
+    pub(crate) fn handle_networking_events(&mut self) {
+        let mut out = Vec::new();
+        while let Ok(event) = self.os.network_response.receiver.try_recv() {
+            out.push(event);
+        }
+        if out.len()>0 {
+            self.call_event_handler(&Event::NetworkResponses(out))
+        }
+    }
+    
     pub fn draw_pass_to_fullscreen(
         &mut self,
         pass_id: PassId,
@@ -244,6 +257,8 @@ impl Cx {
             zbias_step,
         );
         
+        self.opengl_read_pass_pixels(pass_id, 0, direct_app.drm.width as usize, direct_app.drm.height as usize, direct_app.drm.height as usize);
+        
         unsafe {
             direct_app.drm.swap_buffers_and_wait(&direct_app.egl);
         }
@@ -268,6 +283,7 @@ impl Cx {
                 }
             }
         }
+        self.opengl_handle_texture_readbacks();
     }
     
     fn handle_platform_ops(&mut self, direct_app: &mut DirectApp) -> EventFlow {
@@ -297,6 +313,12 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     direct_app.timers.stop_timer(timer_id);
                 },
+                CxOsOp::HttpRequest{request_id, request} => {
+                    make_http_request(request_id, request, self.os.network_response.sender.clone());
+                },
+                CxOsOp::ClipboardRead{request_id, selection, ..} => {
+                    self.call_clipboard_read_unsupported(request_id, selection);
+                }
                 _ => ()
             }
         }
@@ -319,5 +341,6 @@ impl CxOsApi for Cx {
 #[derive(Default)]
 pub struct CxOs {
     pub (crate) media: CxLinuxMedia,
+    pub (crate) network_response: NetworkResponseChannel,
 }
 
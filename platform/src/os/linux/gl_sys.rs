@@ -1,5 +1,10 @@
+//! The GL entry points of the opengl backend, loaded at runtime by `load_with`. Every function
+//! is a raw call into the driver, it needs a current context and arguments that are valid for GL.
 #![allow(non_snake_case)]
 #![allow(non_upper_case_globals)]
+// the safety requirements of the functions are the ones of the GL spec
+#![allow(clippy::missing_safety_doc)]
+#![allow(clippy::too_many_arguments)]
 
 use std::mem;
 use std::os::raw;
@@ -25,6 +30,8 @@ pub const ARRAY_BUFFER: types::GLenum = 0x8892;
 pub const ELEMENT_ARRAY_BUFFER: types::GLenum = 0x8893;
 pub const TEXTURE0: types::GLenum = 0x84C0;
 pub const TEXTURE_2D: types::GLenum = 0x0DE1;
+pub const TEXTURE_CUBE_MAP: types::GLenum = 0x8513;
+pub const TEXTURE_CUBE_MAP_POSITIVE_X: types::GLenum = 0x8515;
 pub const TRIANGLES: types::GLenum = 0x0004;
 pub const UNSIGNED_INT: types::GLenum = 0x1405;
 pub const DEPTH_TEST: types::GLenum = 0x0B71;
@@ -55,7 +62,9 @@ pub const RGBA: types::GLenum = 0x1908;
 pub const BGRA: types::GLenum = 0x80E1;
 pub const RED: types::GLenum = 0x1903;
 pub const RG: types::GLenum =  0x8227;
+pub const R16: types::GLenum = 0x822A;
 pub const UNSIGNED_BYTE: types::GLenum = 0x1401;
+pub const UNSIGNED_SHORT: types::GLenum = 0x1403;
 pub const HALF_FLOAT: types::GLenum =  0x140B;
 pub const FLOAT: types::GLenum = 0x1406;
 
@@ -69,65 +78,68 @@ pub const PROGRAM_BINARY_LENGTH: types::GLenum = 0x8741;
 pub const NO_ERROR: types::GLenum = 0x0;
 pub const UNPACK_ALIGNMENT: types::GLenum = 0x0CF5;
 pub const UNPACK_ROW_LENGTH: types::GLenum = 0x0CF2;
+pub const PACK_ALIGNMENT: types::GLenum = 0x0D05;
 pub const TEXTURE_EXTERNAL_OES: types::GLenum = 0x8D65;
 
-#[inline] pub unsafe fn GenVertexArrays(n: types::GLsizei, arrays: *mut types::GLuint) -> () {mem::transmute::<_, extern "system" fn(types::GLsizei, *mut types::GLuint) -> ()>(storage::GenVertexArrays.f)(n, arrays)}
-#[inline] pub unsafe fn BindVertexArray(array: types::GLuint) -> () {mem::transmute::<_, extern "system" fn(types::GLuint) -> ()>(storage::BindVertexArray.f)(array)}
-#[inline] pub unsafe fn BindBuffer(target: types::GLenum, buffer: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLuint) -> ()>(storage::BindBuffer.f)(target, buffer) }
-#[inline] pub unsafe fn VertexAttribPointer(index: types::GLuint, size: types::GLint, type_: types::GLenum, normalized: types::GLboolean, stride: types::GLsizei, pointer: *const raw::c_void) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLint, types::GLenum, types::GLboolean, types::GLsizei, *const raw::c_void) -> ()>(storage::VertexAttribPointer.f)(index, size, type_, normalized, stride, pointer) }
-#[inline] pub unsafe fn EnableVertexAttribArray(index: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint) -> ()>(storage::EnableVertexAttribArray.f)(index) }
-#[inline] pub unsafe fn VertexAttribDivisor(index: types::GLuint, divisor: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLuint) -> ()>(storage::VertexAttribDivisor.f)(index, divisor) }
-#[inline] pub unsafe fn UseProgram(program: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint) -> ()>(storage::UseProgram.f)(program) }
-#[inline] pub unsafe fn ActiveTexture(texture: types::GLenum) -> () { mem::transmute::<_, extern "system" fn(types::GLenum) -> ()>(storage::ActiveTexture.f)(texture) }
-#[inline] pub unsafe fn BindTexture(target: types::GLenum, texture: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLuint) -> ()>(storage::BindTexture.f)(target, texture) }
-#[inline] pub unsafe fn DrawElementsInstanced(mode: types::GLenum, count: types::GLsizei, type_: types::GLenum, indices: *const raw::c_void, instancecount: types::GLsizei) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLsizei, types::GLenum, *const raw::c_void, types::GLsizei) -> ()>(storage::DrawElementsInstanced.f)(mode, count, type_, indices, instancecount) }
-#[inline] pub unsafe fn Enable(cap: types::GLenum) -> () { mem::transmute::<_, extern "system" fn(types::GLenum) -> ()>(storage::Enable.f)(cap) }
-#[inline] pub unsafe fn DepthFunc(func: types::GLenum) -> () { mem::transmute::<_, extern "system" fn(types::GLenum) -> ()>(storage::DepthFunc.f)(func) }
-#[inline] pub unsafe fn BlendEquationSeparate(modeRGB: types::GLenum, modeAlpha: types::GLenum) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLenum) -> ()>(storage::BlendEquationSeparate.f)(modeRGB, modeAlpha) }
-#[inline] pub unsafe fn BlendFuncSeparate(sfactorRGB: types::GLenum, dfactorRGB: types::GLenum, sfactorAlpha: types::GLenum, dfactorAlpha: types::GLenum) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLenum, types::GLenum, types::GLenum) -> ()>(storage::BlendFuncSeparate.f)(sfactorRGB, dfactorRGB, sfactorAlpha, dfactorAlpha) }
-#[inline] pub unsafe fn Viewport(x: types::GLint, y: types::GLint, width: types::GLsizei, height: types::GLsizei) -> () { mem::transmute::<_, extern "system" fn(types::GLint, types::GLint, types::GLsizei, types::GLsizei) -> ()>(storage::Viewport.f)(x, y, width, height) }
-#[inline] pub unsafe fn BindFramebuffer(target: types::GLenum, framebuffer: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLuint) -> ()>(storage::BindFramebuffer.f)(target, framebuffer) }
-#[inline] pub unsafe fn ClearDepthf(d: types::GLfloat) -> () { mem::transmute::<_, extern "system" fn(types::GLfloat) -> ()>(storage::ClearDepthf.f)(d) }
-#[inline] pub unsafe fn ClearColor(red: types::GLfloat, green: types::GLfloat, blue: types::GLfloat, alpha: types::GLfloat) -> () { mem::transmute::<_, extern "system" fn(types::GLfloat, types::GLfloat, types::GLfloat, types::GLfloat) -> ()>(storage::ClearColor.f)(red, green, blue, alpha) }
-#[inline] pub unsafe fn Clear(mask: types::GLbitfield) -> () { mem::transmute::<_, extern "system" fn(types::GLbitfield) -> ()>(storage::Clear.f)(mask) }
-#[inline] pub unsafe fn GenFramebuffers(n: types::GLsizei, framebuffers: *mut types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *mut types::GLuint) -> ()>(storage::GenFramebuffers.f)(n, framebuffers) }
-#[inline] pub unsafe fn GenRenderbuffers(n: types::GLsizei, renderbuffers: *mut types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *mut types::GLuint) -> ()>(storage::GenRenderbuffers.f)(n, renderbuffers) }
-#[inline] pub unsafe fn BindRenderbuffer(target: types::GLenum, renderbuffer: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLuint) -> ()>(storage::BindRenderbuffer.f)(target, renderbuffer) }
-#[inline] pub unsafe fn RenderbufferStorage(target: types::GLenum, internalformat: types::GLenum, width: types::GLsizei, height: types::GLsizei) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLenum, types::GLsizei, types::GLsizei) -> ()>(storage::RenderbufferStorage.f)(target, internalformat, width, height) }
-#[inline] pub unsafe fn Disable(cap: types::GLenum) -> () { mem::transmute::<_, extern "system" fn(types::GLenum) -> ()>(storage::Disable.f)(cap) }
-#[inline] pub unsafe fn FramebufferRenderbuffer(target: types::GLenum, attachment: types::GLenum, renderbuffertarget: types::GLenum, renderbuffer: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLenum, types::GLenum, types::GLuint) -> ()>(storage::FramebufferRenderbuffer.f)(target, attachment, renderbuffertarget, renderbuffer) }
-#[inline] pub unsafe fn FramebufferTexture2D(target: types::GLenum, attachment: types::GLenum, textarget: types::GLenum, texture: types::GLuint, level: types::GLint) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLenum, types::GLenum, types::GLuint, types::GLint) -> ()>(storage::FramebufferTexture2D.f)(target, attachment, textarget, texture, level) }
-#[inline] pub unsafe fn GetShaderiv(shader: types::GLuint, pname: types::GLenum, params: *mut types::GLint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLenum, *mut types::GLint) -> ()>(storage::GetShaderiv.f)(shader, pname, params) }
-#[inline] pub unsafe fn GetProgramiv(program: types::GLuint, pname: types::GLenum, params: *mut types::GLint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLenum, *mut types::GLint) -> ()>(storage::GetProgramiv.f)(program, pname, params) }
-#[inline] pub unsafe fn GetShaderInfoLog(shader: types::GLuint, bufSize: types::GLsizei, length: *mut types::GLsizei, infoLog: *mut types::GLchar) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLsizei, *mut types::GLsizei, *mut types::GLchar) -> ()>(storage::GetShaderInfoLog.f)(shader, bufSize, length, infoLog) }
-#[inline] pub unsafe fn GetProgramInfoLog(program: types::GLuint, bufSize: types::GLsizei, length: *mut types::GLsizei, infoLog: *mut types::GLchar) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLsizei, *mut types::GLsizei, *mut types::GLchar) -> ()>(storage::GetProgramInfoLog.f)(program, bufSize, length, infoLog) }
-#[inline] pub unsafe fn GetAttribLocation(program: types::GLuint, name: *const types::GLchar) -> types::GLint { mem::transmute::<_, extern "system" fn(types::GLuint, *const types::GLchar) -> types::GLint>(storage::GetAttribLocation.f)(program, name) }
-#[inline] pub unsafe fn GetUniformLocation(program: types::GLuint, name: *const types::GLchar) -> types::GLint { mem::transmute::<_, extern "system" fn(types::GLuint, *const types::GLchar) -> types::GLint>(storage::GetUniformLocation.f)(program, name) }
-#[inline] pub unsafe fn CreateShader(type_: types::GLenum) -> types::GLuint { mem::transmute::<_, extern "system" fn(types::GLenum) -> types::GLuint>(storage::CreateShader.f)(type_) }            
-#[inline] pub unsafe fn ShaderSource(shader: types::GLuint, count: types::GLsizei, string: *const *const types::GLchar, length: *const types::GLint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLsizei, *const *const types::GLchar, *const types::GLint) -> ()>(storage::ShaderSource.f)(shader, count, string, length) }
-#[inline] pub unsafe fn CompileShader(shader: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint) -> ()>(storage::CompileShader.f)(shader) }
-#[inline] pub unsafe fn CreateProgram() -> types::GLuint { mem::transmute::<_, extern "system" fn() -> types::GLuint>(storage::CreateProgram.f)() }
-#[inline] pub unsafe fn AttachShader(program: types::GLuint, shader: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLuint) -> ()>(storage::AttachShader.f)(program, shader) }
-#[inline] pub unsafe fn LinkProgram(program: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint) -> ()>(storage::LinkProgram.f)(program) }
-#[inline] pub unsafe fn DeleteShader(shader: types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLuint) -> ()>(storage::DeleteShader.f)(shader) }
-#[inline] pub unsafe fn Uniform1fv(location: types::GLint, count: types::GLsizei, value: *const types::GLfloat) -> () { mem::transmute::<_, extern "system" fn(types::GLint, types::GLsizei, *const types::GLfloat) -> ()>(storage::Uniform1fv.f)(location, count, value) }
-#[inline] pub unsafe fn GenTextures(n: types::GLsizei, textures: *mut types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *mut types::GLuint) -> ()>(storage::GenTextures.f)(n, textures) }
-#[inline] pub unsafe fn TexParameteri(target: types::GLenum, pname: types::GLenum, param: types::GLint) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLenum, types::GLint) -> ()>(storage::TexParameteri.f)(target, pname, param) }
-#[inline] pub unsafe fn TexImage2D(target: types::GLenum, level: types::GLint, internalformat: types::GLint, width: types::GLsizei, height: types::GLsizei, border: types::GLint, format: types::GLenum, type_: types::GLenum, pixels: *const raw::c_void) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLint, types::GLint, types::GLsizei, types::GLsizei, types::GLint, types::GLenum, types::GLenum, *const raw::c_void) -> ()>(storage::TexImage2D.f)(target, level, internalformat, width, height, border, format, type_, pixels) }
-#[inline] pub unsafe fn DeleteTextures(n: types::GLsizei, textures: *const types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *const types::GLuint) -> ()>(storage::DeleteTextures.f)(n, textures) }
-#[inline] pub unsafe fn GenBuffers(n: types::GLsizei, buffers: *mut types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *mut types::GLuint) -> ()>(storage::GenBuffers.f)(n, buffers) }
-#[inline] pub unsafe fn BufferData(target: types::GLenum, size: types::GLsizeiptr, data: *const raw::c_void, usage: types::GLenum) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLsizeiptr, *const raw::c_void, types::GLenum) -> ()>(storage::BufferData.f)(target, size, data, usage) }
-#[inline] pub unsafe fn Uniform1i(location: types::GLint, v0: types::GLint) -> () { mem::transmute::<_, extern "system" fn(types::GLint, types::GLint) -> ()>(storage::Uniform1i.f)(location, v0) }
-#[inline] pub unsafe fn GetError() -> types::GLenum { mem::transmute::<_, extern "system" fn() -> types::GLenum>(storage::GetError.f)() }
-#[inline] pub unsafe fn Finish() -> () { mem::transmute::<_, extern "system" fn() -> ()>(storage::Finish.f)() }
-#[inline] pub unsafe fn GetProgramBinary(program: types::GLuint, bufSize: types::GLsizei, length: *mut types::GLsizei, binaryFormat: *mut types::GLenum, binary: *mut raw::c_void) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLsizei, *mut types::GLsizei, *mut types::GLenum, *mut raw::c_void) -> ()>(storage::GetProgramBinary.f)(program, bufSize, length, binaryFormat, binary) }
-#[inline] pub unsafe fn ProgramBinary(program: types::GLuint, binaryFormat: types::GLenum, binary: *const raw::c_void, length: types::GLsizei) -> () { mem::transmute::<_, extern "system" fn(types::GLuint, types::GLenum, *const raw::c_void, types::GLsizei) -> ()>(storage::ProgramBinary.f)(program, binaryFormat, binary, length) }            
-#[inline] pub unsafe fn DeleteRenderbuffers(n: types::GLsizei, renderbuffers: *const types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *const types::GLuint) -> ()>(storage::DeleteRenderbuffers.f)(n, renderbuffers) }
-#[inline] pub unsafe fn DeleteBuffers(n: types::GLsizei, buffers: *const types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *const types::GLuint) -> ()>(storage::DeleteBuffers.f)(n, buffers) }
-#[inline] pub unsafe fn DeleteFramebuffers(n: types::GLsizei, framebuffers: *const types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *const types::GLuint) -> ()>(storage::DeleteFramebuffers.f)(n, framebuffers) }
-#[inline] pub unsafe fn DeleteVertexArrays(n: types::GLsizei, arrays: *const types::GLuint) -> () { mem::transmute::<_, extern "system" fn(types::GLsizei, *const types::GLuint) -> ()>(storage::DeleteVertexArrays.f)(n, arrays) }
-#[inline] pub unsafe fn GenerateMipmap(target: types::GLenum) -> () { mem::transmute::<_, extern "system" fn(types::GLenum) -> ()>( storage::GenerateMipmap.f)(target)}
-#[inline] pub unsafe fn PixelStorei(pname: types::GLenum, param: types::GLint) -> () { mem::transmute::<_, extern "system" fn(types::GLenum, types::GLint) -> ()>(storage::PixelStorei.f)(pname, param)}
+#[inline] pub unsafe fn GenVertexArrays(n: types::GLsizei, arrays: *mut types::GLuint) {mem::transmute::<*const raw::c_void, extern "system" fn(types::GLsizei, *mut types::GLuint)>(storage::GenVertexArrays.f)(n, arrays)}
+#[inline] pub unsafe fn BindVertexArray(array: types::GLuint) {mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint)>(storage::BindVertexArray.f)(array)}
+#[inline] pub unsafe fn BindBuffer(target: types::GLenum, buffer: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLuint)>(storage::BindBuffer.f)(target, buffer) }
+#[inline] pub unsafe fn VertexAttribPointer(index: types::GLuint, size: types::GLint, type_: types::GLenum, normalized: types::GLboolean, stride: types::GLsizei, pointer: *const raw::c_void) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, types::GLint, types::GLenum, types::GLboolean, types::GLsizei, *const raw::c_void)>(storage::VertexAttribPointer.f)(index, size, type_, normalized, stride, pointer) }
+#[inline] pub unsafe fn EnableVertexAttribArray(index: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint)>(storage::EnableVertexAttribArray.f)(index) }
+#[inline] pub unsafe fn VertexAttribDivisor(index: types::GLuint, divisor: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, types::GLuint)>(storage::VertexAttribDivisor.f)(index, divisor) }
+#[inline] pub unsafe fn UseProgram(program: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint)>(storage::UseProgram.f)(program) }
+#[inline] pub unsafe fn ActiveTexture(texture: types::GLenum) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum)>(storage::ActiveTexture.f)(texture) }
+#[inline] pub unsafe fn BindTexture(target: types::GLenum, texture: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLuint)>(storage::BindTexture.f)(target, texture) }
+#[inline] pub unsafe fn DrawElementsInstanced(mode: types::GLenum, count: types::GLsizei, type_: types::GLenum, indices: *const raw::c_void, instancecount: types::GLsizei) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLsizei, types::GLenum, *const raw::c_void, types::GLsizei)>(storage::DrawElementsInstanced.f)(mode, count, type_, indices, instancecount) }
+#[inline] pub unsafe fn Enable(cap: types::GLenum) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum)>(storage::Enable.f)(cap) }
+#[inline] pub unsafe fn DepthFunc(func: types::GLenum) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum)>(storage::DepthFunc.f)(func) }
+#[inline] pub unsafe fn BlendEquationSeparate(modeRGB: types::GLenum, modeAlpha: types::GLenum) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLenum)>(storage::BlendEquationSeparate.f)(modeRGB, modeAlpha) }
+#[inline] pub unsafe fn BlendFuncSeparate(sfactorRGB: types::GLenum, dfactorRGB: types::GLenum, sfactorAlpha: types::GLenum, dfactorAlpha: types::GLenum) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLenum, types::GLenum, types::GLenum)>(storage::BlendFuncSeparate.f)(sfactorRGB, dfactorRGB, sfactorAlpha, dfactorAlpha) }
+#[inline] pub unsafe fn Viewport(x: types::GLint, y: types::GLint, width: types::GLsizei, height: types::GLsizei) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLint, types::GLint, types::GLsizei, types::GLsizei)>(storage::Viewport.f)(x, y, width, height) }
+#[inline] pub unsafe fn BindFramebuffer(target: types::GLenum, framebuffer: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLuint)>(storage::BindFramebuffer.f)(target, framebuffer) }
+#[inline] pub unsafe fn ClearDepthf(d: types::GLfloat) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLfloat)>(storage::ClearDepthf.f)(d) }
+#[inline] pub unsafe fn ClearColor(red: types::GLfloat, green: types::GLfloat, blue: types::GLfloat, alpha: types::GLfloat) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLfloat, types::GLfloat, types::GLfloat, types::GLfloat)>(storage::ClearColor.f)(red, green, blue, alpha) }
+#[inline] pub unsafe fn Clear(mask: types::GLbitfield) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLbitfield)>(storage::Clear.f)(mask) }
+#[inline] pub unsafe fn GenFramebuffers(n: types::GLsizei, framebuffers: *mut types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLsizei, *mut types::GLuint)>(storage::GenFramebuffers.f)(n, framebuffers) }
+#[inline] pub unsafe fn GenRenderbuffers(n: types::GLsizei, renderbuffers: *mut types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLsizei, *mut types::GLuint)>(storage::GenRenderbuffers.f)(n, renderbuffers) }
+#[inline] pub unsafe fn BindRenderbuffer(target: types::GLenum, renderbuffer: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLuint)>(storage::BindRenderbuffer.f)(target, renderbuffer) }
+#[inline] pub unsafe fn RenderbufferStorage(target: types::GLenum, internalformat: types::GLenum, width: types::GLsizei, height: types::GLsizei) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLenum, types::GLsizei, types::GLsizei)>(storage::RenderbufferStorage.f)(target, internalformat, width, height) }
+#[inline] pub unsafe fn Disable(cap: types::GLenum) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum)>(storage::Disable.f)(cap) }
+#[inline] pub unsafe fn FramebufferRenderbuffer(target: types::GLenum, attachment: types::GLenum, renderbuffertarget: types::GLenum, renderbuffer: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLenum, types::GLenum, types::GLuint)>(storage::FramebufferRenderbuffer.f)(target, attachment, renderbuffertarget, renderbuffer) }
+#[inline] pub unsafe fn FramebufferTexture2D(target: types::GLenum, attachment: types::GLenum, textarget: types::GLenum, texture: types::GLuint, level: types::GLint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLenum, types::GLenum, types::GLuint, types::GLint)>(storage::FramebufferTexture2D.f)(target, attachment, textarget, texture, level) }
+#[inline] pub unsafe fn GetShaderiv(shader: types::GLuint, pname: types::GLenum, params: *mut types::GLint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, types::GLenum, *mut types::GLint)>(storage::GetShaderiv.f)(shader, pname, params) }
+#[inline] pub unsafe fn GetProgramiv(program: types::GLuint, pname: types::GLenum, params: *mut types::GLint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, types::GLenum, *mut types::GLint)>(storage::GetProgramiv.f)(program, pname, params) }
+#[inline] pub unsafe fn GetShaderInfoLog(shader: types::GLuint, bufSize: types::GLsizei, length: *mut types::GLsizei, infoLog: *mut types::GLchar) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, types::GLsizei, *mut types::GLsizei, *mut types::GLchar)>(storage::GetShaderInfoLog.f)(shader, bufSize, length, infoLog) }
+#[inline] pub unsafe fn GetProgramInfoLog(program: types::GLuint, bufSize: types::GLsizei, length: *mut types::GLsizei, infoLog: *mut types::GLchar) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, types::GLsizei, *mut types::GLsizei, *mut types::GLchar)>(storage::GetProgramInfoLog.f)(program, bufSize, length, infoLog) }
+#[inline] pub unsafe fn GetAttribLocation(program: types::GLuint, name: *const types::GLchar) -> types::GLint { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, *const types::GLchar) -> types::GLint>(storage::GetAttribLocation.f)(program, name) }
+#[inline] pub unsafe fn GetUniformLocation(program: types::GLuint, name: *const types::GLchar) -> types::GLint { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, *const types::GLchar) -> types::GLint>(storage::GetUniformLocation.f)(program, name) }
+#[inline] pub unsafe fn CreateShader(type_: types::GLenum) -> types::GLuint { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum) -> types::GLuint>(storage::CreateShader.f)(type_) }            
+#[inline] pub unsafe fn ShaderSource(shader: types::GLuint, count: types::GLsizei, string: *const *const types::GLchar, length: *const types::GLint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, types::GLsizei, *const *const types::GLchar, *const types::GLint)>(storage::ShaderSource.f)(shader, count, string, length) }
+#[inline] pub unsafe fn CompileShader(shader: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint)>(storage::CompileShader.f)(shader) }
+#[inline] pub unsafe fn CreateProgram() -> types::GLuint { mem::transmute::<*const raw::c_void, extern "system" fn() -> types::GLuint>(storage::CreateProgram.f)() }
+#[inline] pub unsafe fn AttachShader(program: types::GLuint, shader: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, types::GLuint)>(storage::AttachShader.f)(program, shader) }
+#[inline] pub unsafe fn LinkProgram(program: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint)>(storage::LinkProgram.f)(program) }
+#[inline] pub unsafe fn DeleteShader(shader: types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint)>(storage::DeleteShader.f)(shader) }
+#[inline] pub unsafe fn Uniform1fv(location: types::GLint, count: types::GLsizei, value: *const types::GLfloat) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLint, types::GLsizei, *const types::GLfloat)>(storage::Uniform1fv.f)(location, count, value) }
+#[inline] pub unsafe fn GenTextures(n: types::GLsizei, textures: *mut types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLsizei, *mut types::GLuint)>(storage::GenTextures.f)(n, textures) }
+#[inline] pub unsafe fn TexParameteri(target: types::GLenum, pname: types::GLenum, param: types::GLint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLenum, types::GLint)>(storage::TexParameteri.f)(target, pname, param) }
+#[inline] pub unsafe fn TexImage2D(target: types::GLenum, level: types::GLint, internalformat: types::GLint, width: types::GLsizei, height: types::GLsizei, border: types::GLint, format: types::GLenum, type_: types::GLenum, pixels: *const raw::c_void) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLint, types::GLint, types::GLsizei, types::GLsizei, types::GLint, types::GLenum, types::GLenum, *const raw::c_void)>(storage::TexImage2D.f)(target, level, internalformat, width, height, border, format, type_, pixels) }
+#[inline] pub unsafe fn TexSubImage2D(target: types::GLenum, level: types::GLint, xoffset: types::GLint, yoffset: types::GLint, width: types::GLsizei, height: types::GLsizei, format: types::GLenum, type_: types::GLenum, pixels: *const raw::c_void) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLint, types::GLint, types::GLint, types::GLsizei, types::GLsizei, types::GLenum, types::GLenum, *const raw::c_void)>(storage::TexSubImage2D.f)(target, level, xoffset, yoffset, width, height, format, type_, pixels) }
+#[inline] pub unsafe fn DeleteTextures(n: types::GLsizei, textures: *const types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLsizei, *const types::GLuint)>(storage::DeleteTextures.f)(n, textures) }
+#[inline] pub unsafe fn GenBuffers(n: types::GLsizei, buffers: *mut types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLsizei, *mut types::GLuint)>(storage::GenBuffers.f)(n, buffers) }
+#[inline] pub unsafe fn BufferData(target: types::GLenum, size: types::GLsizeiptr, data: *const raw::c_void, usage: types::GLenum) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLsizeiptr, *const raw::c_void, types::GLenum)>(storage::BufferData.f)(target, size, data, usage) }
+#[inline] pub unsafe fn Uniform1i(location: types::GLint, v0: types::GLint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLint, types::GLint)>(storage::Uniform1i.f)(location, v0) }
+#[inline] pub unsafe fn GetError() -> types::GLenum { mem::transmute::<*const raw::c_void, extern "system" fn() -> types::GLenum>(storage::GetError.f)() }
+#[inline] pub unsafe fn Finish() { mem::transmute::<*const raw::c_void, extern "system" fn()>(storage::Finish.f)() }
+#[inline] pub unsafe fn GetProgramBinary(program: types::GLuint, bufSize: types::GLsizei, length: *mut types::GLsizei, binaryFormat: *mut types::GLenum, binary: *mut raw::c_void) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, types::GLsizei, *mut types::GLsizei, *mut types::GLenum, *mut raw::c_void)>(storage::GetProgramBinary.f)(program, bufSize, length, binaryFormat, binary) }
+#[inline] pub unsafe fn ProgramBinary(program: types::GLuint, binaryFormat: types::GLenum, binary: *const raw::c_void, length: types::GLsizei) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLuint, types::GLenum, *const raw::c_void, types::GLsizei)>(storage::ProgramBinary.f)(program, binaryFormat, binary, length) }            
+#[inline] pub unsafe fn DeleteRenderbuffers(n: types::GLsizei, renderbuffers: *const types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLsizei, *const types::GLuint)>(storage::DeleteRenderbuffers.f)(n, renderbuffers) }
+#[inline] pub unsafe fn DeleteBuffers(n: types::GLsizei, buffers: *const types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLsizei, *const types::GLuint)>(storage::DeleteBuffers.f)(n, buffers) }
+#[inline] pub unsafe fn DeleteFramebuffers(n: types::GLsizei, framebuffers: *const types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLsizei, *const types::GLuint)>(storage::DeleteFramebuffers.f)(n, framebuffers) }
+#[inline] pub unsafe fn DeleteVertexArrays(n: types::GLsizei, arrays: *const types::GLuint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLsizei, *const types::GLuint)>(storage::DeleteVertexArrays.f)(n, arrays) }
+#[inline] pub unsafe fn GenerateMipmap(target: types::GLenum) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum)>( storage::GenerateMipmap.f)(target)}
+#[inline] pub unsafe fn PixelStorei(pname: types::GLenum, param: types::GLint) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLenum, types::GLint)>(storage::PixelStorei.f)(pname, param)}
+#[inline] pub unsafe fn ReadPixels(x: types::GLint, y: types::GLint, width: types::GLsizei, height: types::GLsizei, format: types::GLenum, type_: types::GLenum, pixels: *mut raw::c_void) { mem::transmute::<*const raw::c_void, extern "system" fn(types::GLint, types::GLint, types::GLsizei, types::GLsizei, types::GLenum, types::GLenum, *mut raw::c_void)>(storage::ReadPixels.f)(x, y, width, height, format, type_, pixels) }
 
 mod storage {
     use super::FnPtr;
@@ -175,6 +187,7 @@ mod storage {
     pub static mut GenTextures: FnPtr = FnPtr::default();
     pub static mut TexParameteri: FnPtr = FnPtr::default();
     pub static mut TexImage2D: FnPtr = FnPtr::default();
+    pub static mut TexSubImage2D: FnPtr = FnPtr::default();
     pub static mut DeleteTextures: FnPtr = FnPtr::default();
     pub static mut GenBuffers: FnPtr = FnPtr::default();
     pub static mut BufferData: FnPtr = FnPtr::default();
@@ -189,6 +202,7 @@ mod storage {
     pub static mut DeleteVertexArrays: FnPtr = FnPtr::default();
     pub static mut GenerateMipmap: FnPtr = FnPtr::default();
     pub static mut PixelStorei: FnPtr = FnPtr::default();
+    pub static mut ReadPixels: FnPtr = FnPtr::default();
 }
 
 pub unsafe fn load_with<F>(mut loadfn: F) where F: FnMut(&'static str) -> *const raw::c_void {
@@ -235,6 +249,7 @@ pub unsafe fn load_with<F>(mut loadfn: F) where F: FnMut(&'static str) -> *const
     storage::GenTextures = FnPtr::new(metaloadfn(&mut loadfn, "glGenTextures", &[]));
     storage::TexParameteri = FnPtr::new(metaloadfn(&mut loadfn, "glTexParameteri", &[]));
     storage::TexImage2D = FnPtr::new(metaloadfn(&mut loadfn, "glTexImage2D", &[]));
+    storage::TexSubImage2D = FnPtr::new(metaloadfn(&mut loadfn, "glTexSubImage2D", &[]));
     storage::DeleteTextures = FnPtr::new(metaloadfn(&mut loadfn, "glDeleteTextures", &[]));
     storage::GenBuffers = FnPtr::new(metaloadfn(&mut loadfn, "glGenBuffers", &["glGenBuffersARB"]));
     storage::BufferData = FnPtr::new(metaloadfn(&mut loadfn, "glBufferData", &["glBufferDataARB"]));
@@ -250,6 +265,7 @@ pub unsafe fn load_with<F>(mut loadfn: F) where F: FnMut(&'static str) -> *const
     storage::DeleteVertexArrays = FnPtr::new(metaloadfn(&mut loadfn, "glDeleteVertexArrays", &["glDeleteVertexArraysAPPLE", "glDeleteVertexArraysOES"]));
     storage::GenerateMipmap = FnPtr::new(metaloadfn(&mut loadfn, "glGenerateMipmap", &[]));
     storage::PixelStorei = FnPtr::new(metaloadfn(&mut loadfn, "glPixelStorei", &[]));
+    storage::ReadPixels = FnPtr::new(metaloadfn(&mut loadfn, "glReadPixels", &[]));
 }
 
 #[inline(never)]
@@ -0,0 +1,61 @@
+use {
+    std::sync::mpsc::Sender,
+    makepad_http::client::{http_client_request, HttpClientRequest, HttpClientEvent},
+    crate::{
+        makepad_live_id::*,
+        event::{
+            NetworkResponseItem,
+            NetworkResponse,
+            HttpRequest,
+            HttpResponse
+        },
+    }
+};
+
+pub fn make_http_request(request_id: LiveId, request: HttpRequest, networking_sender: Sender<NetworkResponseItem>) {
+    std::thread::spawn(move || {
+        let mut headers = Vec::new();
+        for (key, values) in request.headers.iter() {
+            for value in values {
+                headers.push((key.clone(), value.clone()));
+            }
+        }
+        let client_request = HttpClientRequest {
+            method: request.method.to_string(),
+            url: &request.url,
+            headers,
+            body: request.body.as_deref(),
+        };
+        let progress_sender = networking_sender.clone();
+        let result = http_client_request(&client_request, | event | {
+            if let HttpClientEvent::Progress {loaded, total} = event {
+                let _ = progress_sender.send(NetworkResponseItem {
+                    request_id,
+                    response: NetworkResponse::HttpProgress {
+                        loaded: loaded.min(u32::MAX as u64) as u32,
+                        total: total.unwrap_or(0).min(u32::MAX as u64) as u32
+                    }
+                });
+            }
+        });
+        let response = match result {
+            Ok(client_response) => {
+                let mut response = HttpResponse::new(
+                    request.metadata_id,
+                    client_response.status_code,
+                    "".to_string(),
+                    Some(client_response.body),
+                );
+                for (key, value) in client_response.headers {
+                    response.set_header(key, value);
+                }
+                NetworkResponse::HttpResponse(response)
+            }
+            Err(error) => NetworkResponse::HttpRequestError(error)
+        };
+        let _ = networking_sender.send(NetworkResponseItem {
+            request_id,
+            response
+        });
+    });
+}
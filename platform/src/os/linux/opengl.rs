@@ -9,14 +9,15 @@ use {
     self::super::gl_sys,
     crate::{
         makepad_live_id::*,
-        makepad_shader_compiler::generate_glsl,
+        makepad_live_compiler::LiveRegistry,
+        makepad_shader_compiler::{generate_glsl, source_map::ShaderSourceMap, DrawShaderDef, DrawShaderConstTable},
         cx::Cx,
-        texture::{Texture, TextureFormat, TexturePixel, CxTexture},
+        texture::{Texture, TextureId, TextureFormat, TexturePixel, TextureCategory, TextureRect, TextureReadbackSource, TextureUpdated, CxTexture},
         makepad_math::{Mat4, DVec2, Vec4},
-        pass::{PassClearColor, PassClearDepth, PassId},
+        pass::{PassClearColor, PassClearDepth, PassId, CxPassParent},
         draw_list::DrawListId,
-        draw_shader::{CxDrawShaderMapping, DrawShaderTextureInput},
-        event::{Event, TextureHandleReadyEvent}
+        draw_shader::{CxDrawShaderMapping, DrawShaderTextureInput, log_draw_shader_error, report_shader_compiler_log},
+        event::{Event, TextureHandleReadyEvent, TextureReadbackData}
     },
 };
 
@@ -66,7 +67,9 @@ impl Cx {
                         &shp.vertex,
                         &shp.pixel,
                         &sh.mapping,
-                        self.os_type.get_cache_dir().as_ref()
+                        self.os_type.get_cache_dir().as_ref(),
+                        &self.live_registry.borrow(),
+                        &shp.source_maps
                     ));
                 }
                 let shgl = shp.gl_shader.as_ref().unwrap();
@@ -194,6 +197,8 @@ impl Cx {
                         
                         if cxtexture.format.is_vec(){
                             cxtexture.update_vec_texture();
+                        } else if cxtexture.format.is_cube(){
+                            cxtexture.update_cube_texture();
                         } else if cxtexture.format.is_video() {
                             let is_initial_setup = cxtexture.setup_video_texture();
                             if is_initial_setup {
@@ -220,12 +225,14 @@ impl Cx {
                             // Video playback with SurfaceTexture requires TEXTURE_EXTERNAL_OES, for any other format we assume regular 2D textures
                             match cxtexture.format {
                                 TextureFormat::VideoRGB => gl_sys::BindTexture(gl_sys::TEXTURE_EXTERNAL_OES, texture),
+                                TextureFormat::VecCubeBGRAu8_32{..} => gl_sys::BindTexture(gl_sys::TEXTURE_CUBE_MAP, texture),
                                 _ => gl_sys::BindTexture(gl_sys::TEXTURE_2D, texture)     
                             }
                         }
                         else {
                             match cxtexture.format {
                                 TextureFormat::VideoRGB => gl_sys::BindTexture(gl_sys::TEXTURE_EXTERNAL_OES, 0),
+                                TextureFormat::VecCubeBGRAu8_32{..} => gl_sys::BindTexture(gl_sys::TEXTURE_CUBE_MAP, 0),
                                 _ => gl_sys::BindTexture(gl_sys::TEXTURE_2D, 0)     
                             }
                         }
@@ -456,13 +463,18 @@ impl Cx {
             if let Some(item) = self.draw_shaders.ptr_to_item.get(&draw_shader_ptr) {
                 let cx_shader = &mut self.draw_shaders.shaders[item.draw_shader_id];
                 let draw_shader_def = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr);
+                // shaders that don't pass are left without an os shader and aren't drawn
+                if let Err(err) = generate_glsl::check_draw_shader(draw_shader_def.as_ref().unwrap(), &self.shader_registry) {
+                    log_draw_shader_error(&self.live_registry.borrow(), err);
+                    continue;
+                }
                 
-                let vertex = generate_glsl::generate_vertex_shader(
+                let (vertex, vertex_source_map) = generate_glsl::generate_vertex_shader_with_source_map(
                     draw_shader_def.as_ref().unwrap(),
                     &cx_shader.mapping.const_table,
                     &self.shader_registry
                 );
-                let pixel = generate_glsl::generate_pixel_shader(
+                let (pixel, pixel_source_map) = generate_glsl::generate_pixel_shader_with_source_map(
                     draw_shader_def.as_ref().unwrap(),
                     &cx_shader.mapping.const_table,
                     &self.shader_registry
@@ -481,7 +493,7 @@ impl Cx {
                 }
                 
                 if cx_shader.os_shader_id.is_none() {
-                    let shp = CxOsDrawShader::new(&vertex, &pixel);
+                    let shp = CxOsDrawShader::new(&vertex, vertex_source_map, &pixel, pixel_source_map);
                     cx_shader.os_shader_id = Some(self.draw_shaders.os_shaders.len());
                     self.draw_shaders.os_shaders.push(shp);
                 }
@@ -489,6 +501,141 @@ impl Cx {
         }
         self.draw_shaders.compile_set.clear();
     }
+    
+    /// Without a window there is no GL context to compile with, so this only runs the checks
+    /// for what GLSL ES doesn't support and generates the source
+    pub (crate) fn os_validate_draw_shader(&self, draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, live_registry: &LiveRegistry) -> bool {
+        if let Err(err) = generate_glsl::check_draw_shader(draw_shader_def, &self.shader_registry) {
+            log_draw_shader_error(live_registry, err);
+            return false
+        }
+        generate_glsl::generate_vertex_shader(draw_shader_def, const_table, &self.shader_registry);
+        generate_glsl::generate_pixel_shader(draw_shader_def, const_table, &self.shader_registry);
+        true
+    }
+    
+    // call right after drawing a window pass, before the buffers are swapped. The pass
+    // covers the top `width` x `height` pixels of the framebuffer
+    pub (crate) fn opengl_read_pass_pixels(&mut self, pass_id: PassId, gl_framebuffer: u32, width: usize, height: usize, framebuffer_height: usize) {
+        for readback in &mut self.texture_readbacks {
+            if readback.response.is_none() && readback.source == TextureReadbackSource::Pass(pass_id) {
+                unsafe {
+                    gl_sys::BindFramebuffer(gl_sys::FRAMEBUFFER, gl_framebuffer);
+                    readback.response = Some(opengl_read_pixels(&TexturePixel::BGRAu8, width, height, Some(framebuffer_height), readback.rect));
+                }
+            }
+        }
+    }
+    
+    // call after the repaint, reads back the textures and sends out the events
+    pub (crate) fn opengl_handle_texture_readbacks(&mut self) {
+        if self.texture_readbacks.is_empty() {
+            return
+        }
+        let mut gl_framebuffer = None;
+        let mut readbacks = std::mem::take(&mut self.texture_readbacks);
+        for readback in &mut readbacks {
+            if readback.response.is_some() {
+                continue
+            }
+            let texture_id = match readback.source {
+                TextureReadbackSource::Texture(texture_id) => Ok(texture_id),
+                TextureReadbackSource::Pass(pass_id) => match self.passes[pass_id].parent {
+                    CxPassParent::Window(_) => Err("The window of the pass was not drawn".to_string()),
+                    _ => self.passes[pass_id].color_textures.first().map( | color_texture | color_texture.texture.texture_id())
+                        .ok_or_else( || "The pass has no color texture".to_string())
+                }
+            };
+            readback.response = Some(texture_id.and_then( | texture_id | self.opengl_read_texture_pixels(texture_id, readback.rect, &mut gl_framebuffer)));
+        }
+        if let Some(gl_framebuffer) = gl_framebuffer {
+            unsafe {
+                gl_sys::BindFramebuffer(gl_sys::FRAMEBUFFER, 0);
+                gl_sys::DeleteFramebuffers(1, &gl_framebuffer);
+            }
+        }
+        // requests made while handling these events go out after the next repaint
+        readbacks.append(&mut self.texture_readbacks);
+        self.texture_readbacks = readbacks;
+        self.call_texture_readback_events();
+    }
+    
+    fn opengl_read_texture_pixels(&self, texture_id: TextureId, rect: Option<TextureRect>, gl_framebuffer: &mut Option<u32>) -> Result<TextureReadbackData, String> {
+        let cxtexture = &self.textures[texture_id];
+        let (alloc, gl_texture) = match (&cxtexture.alloc, cxtexture.os.gl_texture) {
+            (Some(alloc), Some(gl_texture)) => (alloc, gl_texture),
+            _ => return Err("The texture has not been uploaded to the gpu".to_string())
+        };
+        if cxtexture.format.is_cube() {
+            return Err("Cube textures cannot be read back".to_string())
+        }
+        // render targets are drawn with the viewport at the bottom, vec textures are uploaded top row first
+        let flip_height = match alloc.category {
+            TextureCategory::Vec {..} => None,
+            TextureCategory::Render {..} => Some(alloc.height),
+            _ => return Err(format!("Cannot read back a texture of category {:?}", alloc.category))
+        };
+        unsafe {
+            if gl_framebuffer.is_none() {
+                let mut new_framebuffer = std::mem::MaybeUninit::uninit();
+                gl_sys::GenFramebuffers(1, new_framebuffer.as_mut_ptr());
+                *gl_framebuffer = Some(new_framebuffer.assume_init());
+            }
+            gl_sys::BindFramebuffer(gl_sys::FRAMEBUFFER, gl_framebuffer.unwrap());
+            gl_sys::FramebufferTexture2D(gl_sys::FRAMEBUFFER, gl_sys::COLOR_ATTACHMENT0, gl_sys::TEXTURE_2D, gl_texture, 0);
+            opengl_read_pixels(&alloc.pixel, alloc.width, alloc.height, flip_height, rect)
+        }
+    }
+}
+
+// reads from the bound framebuffer, the result always has its top row first. Framebuffers
+// that are drawn bottom up pass their height as `flip_height`
+unsafe fn opengl_read_pixels(pixel: &TexturePixel, width: usize, height: usize, flip_height: Option<usize>, rect: Option<TextureRect>) -> Result<TextureReadbackData, String> {
+    let rect = rect.unwrap_or(TextureRect {x: 0, y: 0, width, height});
+    if rect.width == 0 || rect.height == 0 || rect.x + rect.width > width || rect.y + rect.height > height {
+        return Err(format!("Readback rect {:?} is outside of the {}x{} texture", rect, width, height))
+    }
+    let flip_y = flip_height.is_some();
+    let y = if let Some(flip_height) = flip_height {flip_height - rect.y - rect.height} else {rect.y};
+    
+    while gl_sys::GetError() != 0 {}
+    gl_sys::PixelStorei(gl_sys::PACK_ALIGNMENT, 4);
+    let result = match pixel {
+        TexturePixel::BGRAu8 => {
+            let mut data = vec![0u32; rect.width * rect.height];
+            gl_sys::ReadPixels(rect.x as i32, y as i32, rect.width as i32, rect.height as i32, gl_sys::RGBA, gl_sys::UNSIGNED_BYTE, data.as_mut_ptr() as *mut _);
+            // RGBA bytes to the BGRA layout of VecBGRAu8_32
+            for pixel in &mut data {
+                *pixel = (*pixel & 0xff00ff00) | ((*pixel & 0xff) << 16) | ((*pixel >> 16) & 0xff);
+            }
+            if flip_y {
+                flip_rows(&mut data, rect.width);
+            }
+            TextureReadbackData::BGRAu8 {width: rect.width, height: rect.height, data}
+        }
+        TexturePixel::RGBAf16 | TexturePixel::RGBAf32 => {
+            let mut data = vec![0f32; rect.width * rect.height * 4];
+            gl_sys::ReadPixels(rect.x as i32, y as i32, rect.width as i32, rect.height as i32, gl_sys::RGBA, gl_sys::FLOAT, data.as_mut_ptr() as *mut _);
+            if flip_y {
+                flip_rows(&mut data, rect.width * 4);
+            }
+            TextureReadbackData::RGBAf32 {width: rect.width, height: rect.height, data}
+        }
+        _ => return Err(format!("Cannot read back a texture with {:?} pixels", pixel))
+    };
+    let error = gl_sys::GetError();
+    if error != 0 {
+        return Err(format!("glReadPixels failed with error {:x}", error))
+    }
+    Ok(result)
+}
+
+fn flip_rows<T>(data: &mut [T], row_len: usize) {
+    let rows = data.len() / row_len;
+    for row in 0..rows / 2 {
+        let (top, bottom) = data.split_at_mut((rows - row - 1) * row_len);
+        top[row * row_len..(row + 1) * row_len].swap_with_slice(&mut bottom[..row_len]);
+    }
 }
 
 
@@ -497,6 +644,17 @@ pub struct CxOsDrawShader {
     pub gl_shader: Option<GlShader>,
     pub vertex: String,
     pub pixel: String,
+    pub source_maps: GlShaderSourceMaps,
+}
+
+/// Maps the lines the GLSL compiler complains about back to the DSL, the offsets are
+/// the number of header lines in front of the generated vertex and pixel shader.
+#[derive(Clone, Default)]
+pub struct GlShaderSourceMaps {
+    pub vertex: ShaderSourceMap,
+    pub vertex_line_offset: usize,
+    pub pixel: ShaderSourceMap,
+    pub pixel_line_offset: usize,
 }
 
 #[derive(Clone)]
@@ -514,7 +672,7 @@ pub struct GlShader {
 }
 
 impl GlShader{
-    pub fn new(vertex: &str, pixel: &str, mapping: &CxDrawShaderMapping, cache_dir: Option<&String>)->Self{
+    pub fn new(vertex: &str, pixel: &str, mapping: &CxDrawShaderMapping, cache_dir: Option<&String>, live_registry: &LiveRegistry, source_maps: &GlShaderSourceMaps)->Self{
         unsafe fn read_cache(vertex:&str, pixel:&str, cache_dir:Option<&String>)->Option<gl_sys::GLuint>{ 
             if let Some(cache_dir) = cache_dir {
                 let shader_hash = live_id!(shader).str_append(&vertex).str_append(&pixel);
@@ -544,6 +702,7 @@ impl GlShader{
                 gl_sys::CompileShader(vs);
                 //println!("{}", Self::opengl_get_info_log(true, vs as usize, &vertex));
                 if let Some(error) = Self::opengl_has_shader_error(true, vs as usize, &vertex) {
+                    report_shader_compiler_log(live_registry, &source_maps.vertex, &error, source_maps.vertex_line_offset);
                     panic!("ERROR::SHADER::VERTEX::COMPILATION_FAILED\n{}", error);
                 }
                 let fs = gl_sys::CreateShader(gl_sys::FRAGMENT_SHADER);
@@ -551,6 +710,7 @@ impl GlShader{
                 gl_sys::CompileShader(fs);
                 //println!("{}", Self::opengl_get_info_log(true, fs as usize, &fragment));
                 if let Some(error) = Self::opengl_has_shader_error(true, fs as usize, &pixel) {
+                    report_shader_compiler_log(live_registry, &source_maps.pixel, &error, source_maps.pixel_line_offset);
                     panic!("ERROR::SHADER::FRAGMENT::COMPILATION_FAILED\n{}", error);
                 }
                 
@@ -736,7 +896,12 @@ impl GlShader{
 }
 
 impl CxOsDrawShader {
-    pub fn new(vertex: &str, pixel: &str) -> Self {
+    pub fn new(vertex: &str, vertex_source_map: ShaderSourceMap, pixel: &str, pixel_source_map: ShaderSourceMap) -> Self {
+        fn header_lines(full: &str, generated: &str) -> usize {
+            full.matches('\n').count() - generated.matches('\n').count()
+        }
+        let generated_vertex = vertex;
+        let generated_pixel = pixel;
         
         let vertex = format!("
             #version 100
@@ -766,6 +931,12 @@ impl CxOsDrawShader {
         
             // lets fetch the uniform positions for our uniforms
         CxOsDrawShader {
+            source_maps: GlShaderSourceMaps {
+                vertex: vertex_source_map,
+                vertex_line_offset: header_lines(&vertex, generated_vertex),
+                pixel: pixel_source_map,
+                pixel_line_offset: header_lines(&pixel, generated_pixel),
+            },
             vertex,
             pixel,
             gl_shader: None,
@@ -870,7 +1041,11 @@ impl CxTexture {
                 }
             }
         }
-        if self.check_updated(){
+        let updated = self.check_updated();
+        if let TextureUpdated::Partial(rect) = updated{
+            self.update_vec_texture_rect(rect);
+        }
+        else if updated == TextureUpdated::Full{
             unsafe{
                 gl_sys::BindTexture(gl_sys::TEXTURE_2D, self.os.gl_texture.unwrap());
                 gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_WRAP_S, gl_sys::CLAMP_TO_EDGE as i32);
@@ -978,6 +1153,40 @@ impl CxTexture {
                         data.as_ptr() as *const _
                     );
                 },
+                TextureFormat::VecRu16{width, height, data}=>unsafe{
+                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, gl_sys::NEAREST as i32);
+                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_sys::NEAREST as i32);
+                    gl_sys::PixelStorei(gl_sys::UNPACK_ALIGNMENT, 1);
+                    gl_sys::TexImage2D(
+                        gl_sys::TEXTURE_2D,
+                        0,
+                        gl_sys::R16 as i32,
+                        *width as i32,
+                        *height as i32,
+                        0,
+                        gl_sys::RED,
+                        gl_sys::UNSIGNED_SHORT,
+                        data.as_ptr() as *const _
+                    );
+                },
+                TextureFormat::VecNV12{width, height, data} |
+                TextureFormat::VecI420{width, height, data}=>unsafe{
+                    // the chroma planes are uploaded as rows below the luma, the shader picks them apart
+                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, gl_sys::NEAREST as i32);
+                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_sys::NEAREST as i32);
+                    gl_sys::PixelStorei(gl_sys::UNPACK_ALIGNMENT, 1);
+                    gl_sys::TexImage2D(
+                        gl_sys::TEXTURE_2D,
+                        0,
+                        gl_sys::RED as i32,
+                        *width as i32,
+                        (*height + *height / 2) as i32,
+                        0,
+                        gl_sys::RED,
+                        gl_sys::UNSIGNED_BYTE,
+                        data.as_ptr() as *const _
+                    );
+                },
                 _=>{panic!()}
             }
             unsafe{
@@ -985,6 +1194,80 @@ impl CxTexture {
             }
         }
     }
+    
+    pub fn update_cube_texture(&mut self) {
+        if self.alloc_vec() && self.os.gl_texture.is_none() {
+            unsafe {
+                let mut gl_texture = std::mem::MaybeUninit::uninit();
+                gl_sys::GenTextures(1, gl_texture.as_mut_ptr());
+                self.os.gl_texture = Some(gl_texture.assume_init());
+            }
+        }
+        if self.check_updated() == TextureUpdated::Empty {
+            return
+        }
+        let TextureFormat::VecCubeBGRAu8_32{size, data} = &self.format else {
+            return
+        };
+        unsafe {
+            gl_sys::BindTexture(gl_sys::TEXTURE_CUBE_MAP, self.os.gl_texture.unwrap());
+            gl_sys::TexParameteri(gl_sys::TEXTURE_CUBE_MAP, gl_sys::TEXTURE_WRAP_S, gl_sys::CLAMP_TO_EDGE as i32);
+            gl_sys::TexParameteri(gl_sys::TEXTURE_CUBE_MAP, gl_sys::TEXTURE_WRAP_T, gl_sys::CLAMP_TO_EDGE as i32);
+            gl_sys::TexParameteri(gl_sys::TEXTURE_CUBE_MAP, gl_sys::TEXTURE_MIN_FILTER, gl_sys::LINEAR as i32);
+            gl_sys::TexParameteri(gl_sys::TEXTURE_CUBE_MAP, gl_sys::TEXTURE_MAG_FILTER, gl_sys::LINEAR as i32);
+            // the face targets follow each other in the same +x -x +y -y +z -z order as the data
+            for face in 0..6 {
+                gl_sys::TexImage2D(
+                    gl_sys::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                    0,
+                    gl_sys::BGRA as i32,
+                    *size as i32,
+                    *size as i32,
+                    0,
+                    gl_sys::BGRA,
+                    gl_sys::UNSIGNED_BYTE,
+                    data[face * size * size..].as_ptr() as *const _
+                );
+            }
+            gl_sys::BindTexture(gl_sys::TEXTURE_CUBE_MAP, 0);
+        }
+    }
+    
+    fn update_vec_texture_rect(&self, rect: TextureRect) {
+        let (data, bytes_per_pixel, row_length) = self.format.vec_pixel_data().unwrap();
+        let (format, data_type) = match &self.format{
+            TextureFormat::VecBGRAu8_32{..} | TextureFormat::VecMipBGRAu8_32{..} => (gl_sys::BGRA, gl_sys::UNSIGNED_BYTE),
+            TextureFormat::VecRGBAf32{..} => (gl_sys::RGBA, gl_sys::FLOAT),
+            TextureFormat::VecRu8{..} | TextureFormat::VecNV12{..} | TextureFormat::VecI420{..} => (gl_sys::RED, gl_sys::UNSIGNED_BYTE),
+            TextureFormat::VecRGu8{..} => (gl_sys::RG, gl_sys::UNSIGNED_BYTE),
+            TextureFormat::VecRf32{..} => (gl_sys::RED, gl_sys::FLOAT),
+            TextureFormat::VecRu16{..} => (gl_sys::RED, gl_sys::UNSIGNED_SHORT),
+            _=>panic!()
+        };
+        unsafe{
+            gl_sys::BindTexture(gl_sys::TEXTURE_2D, self.os.gl_texture.unwrap());
+            gl_sys::PixelStorei(gl_sys::UNPACK_ALIGNMENT, 1);
+            gl_sys::PixelStorei(gl_sys::UNPACK_ROW_LENGTH, row_length as i32);
+            for rect in self.format.vec_upload_rects(rect){
+                gl_sys::TexSubImage2D(
+                    gl_sys::TEXTURE_2D,
+                    0,
+                    rect.x as i32,
+                    rect.y as i32,
+                    rect.width as i32,
+                    rect.height as i32,
+                    format,
+                    data_type,
+                    data.add((rect.y * row_length + rect.x) * bytes_per_pixel) as *const _
+                );
+            }
+            gl_sys::PixelStorei(gl_sys::UNPACK_ROW_LENGTH, 0);
+            if let TextureFormat::VecMipBGRAu8_32{..} = &self.format{
+                gl_sys::GenerateMipmap(gl_sys::TEXTURE_2D);
+            }
+            gl_sys::BindTexture(gl_sys::TEXTURE_2D, 0);
+        }
+    }
 
     pub fn setup_video_texture(&mut self) -> bool {
         while unsafe { gl_sys::GetError() } != 0 {}
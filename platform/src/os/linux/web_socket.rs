@@ -1,16 +1,40 @@
-
 use crate::event::HttpRequest;
 use crate::web_socket::{WebSocketMessage};
 use std::sync::mpsc::{Sender};
+use makepad_http::websocket_client::{WebSocketClient, WebSocketClientEvent};
+
 pub struct OsWebSocket{
+    client: WebSocketClient,
 }
 
 impl OsWebSocket{
-    pub fn send_message(&mut self, _message:WebSocketMessage)->Result<(),()>{
-        todo!();
+    pub fn send_message(&mut self, message:WebSocketMessage)->Result<(),String>{
+        match message{
+            WebSocketMessage::String(data)=>self.client.send_text(&data),
+            WebSocketMessage::Binary(data)=>self.client.send_binary(&data),
+            _=>Err("Only text and binary messages can be sent".to_string())
+        }
     }
                     
-    pub fn open(_request: HttpRequest, _rx_sender:Sender<WebSocketMessage>)->OsWebSocket{
-        todo!();
+    pub fn open(request: HttpRequest, rx_sender:Sender<WebSocketMessage>)->OsWebSocket{
+        let mut headers = Vec::new();
+        for (key, values) in request.headers.iter() {
+            for value in values {
+                headers.push((key.clone(), value.clone()));
+            }
+        }
+        let client = WebSocketClient::connect(&request.url, headers, move |event|{
+            let message = match event{
+                WebSocketClientEvent::Opened=>return,
+                WebSocketClientEvent::Text(data)=>WebSocketMessage::String(data),
+                WebSocketClientEvent::Binary(data)=>WebSocketMessage::Binary(data),
+                WebSocketClientEvent::Closed{..}=>WebSocketMessage::Closed,
+                WebSocketClientEvent::Error(error)=>WebSocketMessage::Error(error),
+            };
+            let _ = rx_sender.send(message);
+        });
+        OsWebSocket{
+            client
+        }
     }
-}
\ No newline at end of file
+}
@@ -9,14 +9,16 @@ use {
         egl_sys,
         x11::xlib_event::*,
         x11::xlib_app::*,
-        linux_media::CxLinuxMedia
+        x11::xlib_clipboard::*,
+        linux_media::CxLinuxMedia,
+        http::make_http_request,
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi}, 
         makepad_math::dvec2,
         makepad_live_id::*,
         thread::Signal,
-        event::Event,
+        event::{Event, NetworkResponseChannel, ClipboardReadEvent},
         pass::CxPassParent,
         cx::{Cx, OsType,LinuxWindowParams}, 
         os::cx_stdin::{PollTimers},
@@ -163,14 +165,24 @@ impl Cx {
             XlibEvent::TextInput(e) => {
                 self.call_event_handler(&Event::TextInput(e))
             }
+            XlibEvent::TextComposition(e) => {
+                self.call_event_handler(&Event::TextComposition(e))
+            }
             XlibEvent::Drag(e) => {
-                self.call_event_handler(&Event::Drag(e))
+                self.call_event_handler(&Event::Drag(e));
+                self.drag_drop.cycle_drag();
             }
             XlibEvent::Drop(e) => {
-                self.call_event_handler(&Event::Drop(e))
+                self.call_event_handler(&Event::Drop(e));
+                self.drag_drop.cycle_drag();
             }
             XlibEvent::DragEnd => {
-                self.call_event_handler(&Event::DragEnd)
+                self.call_event_handler(&Event::DragEnd);
+                self.drag_drop.cycle_drag();
+            }
+            XlibEvent::DragLeave => {
+                self.call_event_handler(&Event::DragLeave);
+                self.drag_drop.cycle_drag();
             }
             XlibEvent::KeyDown(e) => {
                 self.keyboard.process_key_down(e.clone());
@@ -186,6 +198,12 @@ impl Cx {
             XlibEvent::TextCut(e) => {
                 self.call_event_handler(&Event::TextCut(e))
             }
+            XlibEvent::ClipboardRead(e) => {
+                self.call_event_handler(&Event::ClipboardRead(e))
+            }
+            XlibEvent::ClipboardPaste(e) => {
+                self.call_event_handler(&Event::ClipboardPaste(e))
+            }
             XlibEvent::Timer(e) => {
                 //println!("TIMER! {:?}", std::time::Instant::now());
                 if e.timer_id == 0{
@@ -193,6 +211,7 @@ impl Cx {
                         self.handle_media_signals();
                         self.call_event_handler(&Event::Signal);
                     }
+                    self.handle_networking_events();
                 }
                 else{
                     self.call_event_handler(&Event::Timer(e))
@@ -200,7 +219,7 @@ impl Cx {
             }
         }
         
-        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || paint_dirty {
+        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || !self.texture_readbacks.is_empty() || paint_dirty {
             EventFlow::Poll
         } else {
             EventFlow::Wait
@@ -209,6 +228,13 @@ impl Cx {
     }
 
     pub(crate) fn handle_networking_events(&mut self) {
+        let mut out = Vec::new();
+        while let Ok(event) = self.os.network_response.receiver.try_recv() {
+            out.push(event);
+        }
+        if out.len()>0 {
+            self.call_event_handler(&Event::NetworkResponses(out))
+        }
     }
     
     pub (crate) fn handle_repaint(&mut self, opengl_windows: &mut Vec<OpenglWindow>) {
@@ -235,6 +261,7 @@ impl Cx {
                 }
             }
         }
+        self.opengl_handle_texture_readbacks();
     }
     
     fn handle_platform_ops(&mut self, opengl_windows: &mut Vec<OpenglWindow>, xlib_app: &mut XlibApp) -> EventFlow {
@@ -284,6 +311,31 @@ impl Cx {
                 },
                 CxOsOp::ShowClipboardActions(_) =>{
                 }
+                CxOsOp::ClipboardRead{request_id, selection, mime_types} => {
+                    if let Some(window) = xlib_app.any_window() {
+                        unsafe {xlib_app.clipboard_read(XlibClipboardRead {
+                            target: XlibClipboardTarget::Request(request_id),
+                            selection,
+                            mime_types,
+                            window,
+                            // CurrentTime
+                            time: 0
+                        })};
+                    }
+                    else {
+                        self.call_event_handler(&Event::ClipboardRead(ClipboardReadEvent {
+                            request_id,
+                            selection,
+                            response: Err("No window to read the clipboard with".to_string())
+                        }));
+                    }
+                }
+                CxOsOp::ClipboardWrite{selection, items} => {
+                    if let Some(window) = xlib_app.any_window() {
+                        // CurrentTime, as there is no input event to take the time from
+                        unsafe {xlib_app.clipboard.write(selection, items, window, 0)};
+                    }
+                }
                 CxOsOp::FullscreenWindow(_window_id) => {
                     todo!()
                 },
@@ -299,11 +351,17 @@ impl Cx {
                 CxOsOp::XrStopPresenting => {
                     //todo!()
                 },
-                CxOsOp::ShowTextIME(_area, _pos) => {
-                    //todo!()
+                CxOsOp::ShowTextIME(area, pos) => {
+                    let pos = area.clipped_rect(self).pos + pos;
+                    let window_id = self.window_id_of(&area);
+                    if let Some(window) = opengl_windows.iter_mut().find( | w | Some(w.window_id) == window_id) {
+                        window.xlib_window.show_ime(pos);
+                    }
                 }
                 CxOsOp::HideTextIME => {
-                    //todo!()
+                    opengl_windows.iter_mut().for_each( | w | {
+                        w.xlib_window.hide_ime();
+                    });
                 },
                 CxOsOp::SetCursor(cursor) => {
                     xlib_app.set_mouse_cursor(cursor);
@@ -314,12 +372,13 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     xlib_app.stop_timer(timer_id);
                 },
-                CxOsOp::StartDragging(_dragged_item) => {
+                CxOsOp::StartDragging(items) => {
+                    xlib_app.start_dragging(items);
                 },
                 CxOsOp::UpdateMacosMenu(_menu) => {
                 },
-                CxOsOp::HttpRequest{request_id:_, request:_} => {
-                    todo!()
+                CxOsOp::HttpRequest{request_id, request} => {
+                    make_http_request(request_id, request, self.os.network_response.sender.clone());
                 },
                 CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
                 CxOsOp::PauseVideoPlayback(_) => todo!(),
@@ -350,6 +409,7 @@ impl CxOsApi for Cx {
 pub struct CxOs {
     pub(crate) media: CxLinuxMedia,
     pub (crate) stdin_timers: PollTimers,
+    pub (crate) network_response: NetworkResponseChannel,
 
     // HACK(eddyb) generalize this to EGL, properly.
     pub(super) opengl_cx: Option<OpenglCx>,
@@ -19,6 +19,7 @@ use {
         cx_api::CxOsOp,
         cx::Cx,
         gl_sys,
+        os::linux::http::make_http_request,
     } 
 };
 
@@ -54,6 +55,13 @@ impl Cx {
                             width: (pass_rect.size.x * dpi_factor) as u32,
                             height: (pass_rect.size.y * dpi_factor) as u32,
                         };
+                        
+                        // the swapchain image can be larger than the window, the pass sits at its top
+                        if let Some(alloc) = &self.textures[current_image.image.texture_id()].alloc {
+                            let alloc_height = alloc.height;
+                            let gl_framebuffer = self.passes[pass_id].os.gl_framebuffer.unwrap();
+                            self.opengl_read_pass_pixels(pass_id, gl_framebuffer, presentable_draw.width as usize, presentable_draw.height as usize, alloc_height);
+                        }
 
                         // inform host that frame is ready
                         let _ = io::stdout().write_all(StdinToHost::DrawCompleteAndFlip(presentable_draw).to_json().as_bytes());
@@ -68,6 +76,7 @@ impl Cx {
                 }
             }
         }
+        self.opengl_handle_texture_readbacks();
     }
     
     pub fn stdin_event_loop(&mut self) {
@@ -263,6 +272,12 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     self.os.stdin_timers.timers.remove(&timer_id);
                 },
+                CxOsOp::HttpRequest{request_id, request} => {
+                    make_http_request(request_id, request, self.os.network_response.sender.clone());
+                },
+                CxOsOp::ClipboardRead{request_id, selection, ..} => {
+                    self.call_clipboard_read_unsupported(request_id, selection);
+                }
                 _ => ()
                 /*
                 CxOsOp::CloseWindow(_window_id) => {},
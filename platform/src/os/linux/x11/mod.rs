@@ -3,6 +3,8 @@ pub mod opengl_x11;
 pub mod xlib_app; 
 pub mod xlib_window;
 pub mod xlib_event;
+pub mod xlib_clipboard;
+pub mod xlib_drag_source;
 pub mod linux_x11; 
 pub mod linux_x11_stdin; 
 
@@ -3,6 +3,7 @@
 use std::os::raw::{
     c_int,
     c_uint,
+    c_ushort,
     c_short,
     c_long,
     c_ulong,
@@ -45,6 +46,10 @@ pub const CurrentTime: u32 = 0;
 pub const SelectionNotify: u32 = 31;
 pub const AnyPropertyType: u32 = 0;
 pub const SelectionRequest: u32 = 30;
+pub const SelectionClear: u32 = 29;
+pub const PropertyNotify: u32 = 28;
+pub const PropertyNewValue: i32 = 0;
+pub const PropertyDelete: i32 = 1;
 pub const PropModeReplace: u32 = 0;
 pub const DestroyNotify: u32 = 17;
 pub const ConfigureNotify: u32 = 22;
@@ -67,6 +72,8 @@ pub const CWEventMask: u32 = 2048;
 pub const SubstructureNotifyMask: u32 = 524288;
 pub const SubstructureRedirectMask: u32 = 1048576;
 pub const NoEventMask: u32 = 0;
+pub const GrabModeAsync: c_int = 1;
+pub const GrabSuccess: c_int = 0;
 
 pub const ExposureMask: u32 = 32768;
 pub const StructureNotifyMask: u32 = 131072;
@@ -80,14 +87,42 @@ pub const VisibilityChangeMask: u32 = 65536;
 pub const FocusChangeMask: u32 = 2097152;
 pub const EnterWindowMask: u32 = 16;
 pub const LeaveWindowMask: u32 = 32;
+pub const PropertyChangeMask: u32 = 4194304;
 pub const XBufferOverflow: i32 = -1;
 
+pub const XIMPreeditArea: u32 = 1;
+pub const XIMPreeditCallbacks: u32 = 2;
+pub const XIMPreeditPosition: u32 = 4;
 pub const XIMPreeditNothing: u32 = 8;
+pub const XIMPreeditNone: u32 = 16;
 pub const XIMStatusNothing: u32 = 1024;
-
-pub const XNInputStyle: &'static [u8; 11usize] = b"inputStyle\0";
-pub const XNClientWindow: &'static [u8; 13usize] = b"clientWindow\0";
-pub const XNFocusWindow: &'static [u8; 12usize] = b"focusWindow\0";
+pub const XIMStatusNone: u32 = 2048;
+
+pub const XIMReverse: u32 = 1;
+pub const XIMUnderline: u32 = 2;
+pub const XIMHighlight: u32 = 4;
+
+pub const XIMForwardChar: c_int = 0;
+pub const XIMBackwardChar: c_int = 1;
+pub const XIMAbsolutePosition: c_int = 10;
+pub const XIMDontChange: c_int = 11;
+
+pub const XLookupChars: c_int = 2;
+pub const XLookupBoth: c_int = 4;
+
+pub const FocusIn: u32 = 9;
+pub const FocusOut: u32 = 10;
+
+pub const XNInputStyle: &[u8; 11usize] = b"inputStyle\0";
+pub const XNClientWindow: &[u8; 13usize] = b"clientWindow\0";
+pub const XNFocusWindow: &[u8; 12usize] = b"focusWindow\0";
+pub const XNQueryInputStyle: &[u8; 16usize] = b"queryInputStyle\0";
+pub const XNPreeditAttributes: &[u8; 18usize] = b"preeditAttributes\0";
+pub const XNSpotLocation: &[u8; 13usize] = b"spotLocation\0";
+pub const XNPreeditStartCallback: &[u8; 21usize] = b"preeditStartCallback\0";
+pub const XNPreeditDoneCallback: &[u8; 20usize] = b"preeditDoneCallback\0";
+pub const XNPreeditDrawCallback: &[u8; 20usize] = b"preeditDrawCallback\0";
+pub const XNPreeditCaretCallback: &[u8; 21usize] = b"preeditCaretCallback\0";
 
 pub const Mod1Mask: u32 = 8;
 pub const ShiftMask: u32 = 1;
@@ -282,6 +317,10 @@ extern "C" {
     
     pub fn XFree(arg1: *mut c_void) -> c_int;
     
+    pub fn XDeleteProperty(arg1: *mut Display, arg2: Window, arg3: Atom) -> c_int;
+    
+    pub fn XSelectInput(arg1: *mut Display, arg2: Window, arg3: c_long) -> c_int;
+    
     pub fn XChangeProperty(
         arg1: *mut Display,
         arg2: Window,
@@ -353,6 +392,24 @@ extern "C" {
     
     pub fn XCreateIC(arg1: XIM, ...) -> XIC;
     
+    pub fn XDestroyIC(arg1: XIC);
+    
+    pub fn XGetIMValues(arg1: XIM, ...) -> *mut c_char;
+    
+    pub fn XSetICValues(arg1: XIC, ...) -> *mut c_char;
+    
+    pub fn XVaCreateNestedList(arg1: c_int, ...) -> *mut c_void;
+    
+    pub fn XSetICFocus(arg1: XIC);
+    
+    pub fn XUnsetICFocus(arg1: XIC);
+    
+    pub fn XFilterEvent(arg1: *mut XEvent, arg2: Window) -> c_int;
+    
+    pub fn XSetLocaleModifiers(arg1: *const c_char) -> *mut c_char;
+    
+    pub fn XSupportsLocale() -> c_int;
+    
     pub fn XDestroyWindow(arg1: *mut Display, arg2: Window) -> c_int;
     
     pub fn XIconifyWindow(
@@ -397,6 +454,31 @@ extern "C" {
     
     pub fn XUngrabPointer(arg1: *mut Display, arg2: Time) -> c_int;
     
+    pub fn XChangeActivePointerGrab(arg1: *mut Display, arg2: c_uint, arg3: Cursor, arg4: Time) -> c_int;
+    
+    pub fn XGrabPointer(
+        arg1: *mut Display,
+        arg2: Window,
+        arg3: c_int,
+        arg4: c_uint,
+        arg5: c_int,
+        arg6: c_int,
+        arg7: Window,
+        arg8: Cursor,
+        arg9: Time,
+    ) -> c_int;
+    
+    pub fn XTranslateCoordinates(
+        arg1: *mut Display,
+        arg2: Window,
+        arg3: Window,
+        arg4: c_int,
+        arg5: c_int,
+        arg6: *mut c_int,
+        arg7: *mut c_int,
+        arg8: *mut Window,
+    ) -> c_int;
+    
     pub fn XSetSelectionOwner(
         arg1: *mut Display,
         arg2: Atom,
@@ -448,6 +530,64 @@ pub struct _XIC {
     _unused: [u8; 0],
 }
 
+pub type XIMStyle = c_ulong;
+pub type XIMFeedback = c_ulong;
+pub type XICProc = Option<unsafe extern "C" fn(XIC, XPointer, XPointer) -> c_int>;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XIMStyles {
+    pub count_styles: c_ushort,
+    pub supported_styles: *mut XIMStyle,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XICCallback {
+    pub client_data: XPointer,
+    pub callback: XICProc,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union XIMTextString {
+    pub multi_byte: *mut c_char,
+    pub wide_char: *mut c_int,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct XIMText {
+    pub length: c_ushort,
+    pub feedback: *mut XIMFeedback,
+    pub encoding_is_wchar: c_int,
+    pub string: XIMTextString,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XIMPreeditDrawCallbackStruct {
+    pub caret: c_int,
+    pub chg_first: c_int,
+    pub chg_length: c_int,
+    pub text: *mut XIMText,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XIMPreeditCaretCallbackStruct {
+    pub position: c_int,
+    pub direction: c_int,
+    pub style: c_int,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct XPoint {
+    pub x: c_short,
+    pub y: c_short,
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct _XComposeStatus {
@@ -4,26 +4,36 @@ use {
         mem,
         rc::Rc,
         cell::{Cell, RefCell},
-        os::raw::{c_char, c_int, c_uint, c_ulong, c_void, c_uchar, c_long},
+        os::raw::{c_char, c_int, c_uint, c_ulong, c_long, c_void},
         ptr,
     },
     self::super::{
         x11_sys,
         xlib_event::XlibEvent,
         xlib_window::*,
+        xlib_clipboard::*,
+        xlib_drag_source::*,
         super::select_timer::SelectTimers,
+        super::libc_sys,
     },
     crate::{
         makepad_math::DVec2,
         event::*,
         cursor::MouseCursor,
         os::cx_native::EventFlow,
-        x11::x11_sys::XEvent
     },
 };
 
 static mut XLIB_APP: *mut XlibApp = 0 as *mut _;
 
+// our own timers live at the top of the id range, Cx counts its timer ids up from 1
+const CLIPBOARD_TIMER_ID: u64 = u64::MAX;
+// how often a pending clipboard read is checked for a timeout
+const CLIPBOARD_TIMER_INTERVAL: f64 = 1.0;
+const DRAG_FINISHED_TIMER_ID: u64 = u64::MAX - 1;
+// how long a drop target gets to send XdndFinished before we end the drag anyway
+const DRAG_FINISHED_TIMEOUT: f64 = 5.0;
+
 pub fn get_xlib_app_global() -> &'static mut XlibApp {
     unsafe {
         &mut *(XLIB_APP)
@@ -40,7 +50,8 @@ pub struct XlibApp {
     pub display: *mut x11_sys::Display,
     event_loop_running: bool,
     pub xim: x11_sys::XIM,
-    pub clipboard: String,
+    pub xim_style: c_ulong,
+    pub clipboard: XlibClipboard,
     pub display_fd: c_int,
     //pub signal_fds: [c_int; 2],
     pub window_map: HashMap<c_ulong, *mut XlibWindow>,
@@ -50,12 +61,14 @@ pub struct XlibApp {
     pub last_scroll_time: f64,
     pub last_click_time: f64,
     pub last_click_pos: (i32, i32),
+    pub last_click_window: c_ulong,
     pub event_callback: Option<Box<dyn FnMut(&mut XlibApp, XlibEvent) -> EventFlow >>,
     //pub free_timers: Vec<usize>,
     pub event_flow: EventFlow,
     pub current_cursor: MouseCursor,
     pub atoms: XlibAtoms,
     pub dnd: Dnd,
+    pub drag_source: XlibDragSource,
 }
 
 impl XlibApp {
@@ -63,7 +76,11 @@ impl XlibApp {
         unsafe {
             let display = x11_sys::XOpenDisplay(ptr::null());
             let display_fd = x11_sys::XConnectionNumber(display);
+            // input methods need the locale from the environment, otherwise we only get latin1
+            libc_sys::setlocale(libc_sys::LC_CTYPE, c"".as_ptr());
+            x11_sys::XSetLocaleModifiers(c"".as_ptr());
             let xim = x11_sys::XOpenIM(display, ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+            let xim_style = Self::choose_xim_style(xim);
             //let mut signal_fds = [0, 0];
             //libc_sys::pipe(signal_fds.as_mut_ptr());
             x11_sys::XrmInitialize();
@@ -72,21 +89,53 @@ impl XlibApp {
                 event_callback: Some(event_callback),
                 atoms: XlibAtoms::new(display),
                 xim,
+                xim_style,
                 display,
                 display_fd,
                 //signal_fds,
-                clipboard: String::new(),
+                clipboard: XlibClipboard::new(display),
                 last_scroll_time: 0.0,
                 last_click_time: 0.0,
                 last_click_pos: (0, 0),
+                last_click_window: 0,
                 window_map: HashMap::new(),
                 timers: SelectTimers::new(),
                 event_flow: EventFlow::Poll,
                 //free_timers: Vec::new(),
                 current_cursor: MouseCursor::Default,
                 dnd: Dnd::new(display),
+                drag_source: XlibDragSource::new(display),
+            }
+        }
+    }
+    
+    // prefer drawing the preedit ourselves, then a candidate window at our cursor, then whatever the IM does
+    unsafe fn choose_xim_style(xim: x11_sys::XIM) -> c_ulong {
+        let fallback = (x11_sys::XIMPreeditNothing | x11_sys::XIMStatusNothing) as c_ulong;
+        if xim.is_null() {
+            return fallback
+        }
+        let mut styles: *mut x11_sys::XIMStyles = ptr::null_mut();
+        let failed = x11_sys::XGetIMValues(
+            xim,
+            x11_sys::XNQueryInputStyle.as_ptr(),
+            &mut styles as *mut *mut x11_sys::XIMStyles,
+            ptr::null_mut() as *mut c_void
+        );
+        if !failed.is_null() || styles.is_null() {
+            return fallback
+        }
+        let supported = std::slice::from_raw_parts((*styles).supported_styles, (*styles).count_styles as usize).to_vec();
+        x11_sys::XFree(styles as *mut c_void);
+        for preedit in [x11_sys::XIMPreeditCallbacks, x11_sys::XIMPreeditPosition, x11_sys::XIMPreeditNothing] {
+            for status in [x11_sys::XIMStatusNothing, x11_sys::XIMStatusNone] {
+                let style = (preedit | status) as c_ulong;
+                if supported.contains(&style) {
+                    return style
+                }
             }
         }
+        fallback
     }
     
     pub unsafe fn event_loop_poll(&mut self) {
@@ -96,109 +145,27 @@ impl XlibApp {
             let mut event = mem::MaybeUninit::uninit();
             x11_sys::XNextEvent(self.display, event.as_mut_ptr());
             let mut event = event.assume_init();
+            // the input method eats the keys it uses for composing
+            if x11_sys::XFilterEvent(&mut event, 0) != 0 {
+                continue;
+            }
             match event.type_ as u32 {
                 x11_sys::SelectionNotify => {
                     let selection = event.xselection;
                     if selection.property == self.dnd.atoms.selection {
                         self.dnd.handle_selection_event(&selection);
                     } else {
-                        // first get the size of the thing
-                        let mut actual_type = mem::MaybeUninit::uninit();
-                        let mut actual_format = mem::MaybeUninit::uninit();
-                        let mut n_items = mem::MaybeUninit::uninit();
-                        let mut bytes_to_read = mem::MaybeUninit::uninit();
-                        let mut ret = mem::MaybeUninit::uninit();
-                        x11_sys::XGetWindowProperty(
-                            self.display,
-                            selection.requestor,
-                            selection.property,
-                            0,
-                            0,
-                            0,
-                            x11_sys::AnyPropertyType as c_ulong,
-                            actual_type.as_mut_ptr(),
-                            actual_format.as_mut_ptr(),
-                            n_items.as_mut_ptr(),
-                            bytes_to_read.as_mut_ptr(),
-                            ret.as_mut_ptr()
-                        );
-                        //let actual_type = actual_type.assume_init();
-                        //let actual_format = actual_format.assume_init();
-                        //let n_items = n_items.assume_init();
-                        let bytes_to_read = bytes_to_read.assume_init();
-                        //let mut ret = ret.assume_init();
-                        let mut bytes_after = mem::MaybeUninit::uninit();
-                        x11_sys::XGetWindowProperty(
-                            self.display,
-                            selection.requestor,
-                            selection.property,
-                            0,
-                            bytes_to_read as c_long,
-                            0,
-                            x11_sys::AnyPropertyType as c_ulong,
-                            actual_type.as_mut_ptr(),
-                            actual_format.as_mut_ptr(),
-                            n_items.as_mut_ptr(),
-                            bytes_after.as_mut_ptr(),
-                            ret.as_mut_ptr()
-                        );
-                        let ret = ret.assume_init();
-                        //let bytes_after = bytes_after.assume_init();
-                        if ret != ptr::null_mut() && bytes_to_read > 0 {
-                            let utf8_slice = std::slice::from_raw_parts::<u8>(ret as *const _ as *const u8, bytes_to_read as usize);
-                            if let Ok(utf8_string) = String::from_utf8(utf8_slice.to_vec()) {
-                                self.do_callback(XlibEvent::TextInput(TextInputEvent {
-                                    input: utf8_string,
-                                    was_paste: true,
-                                    replace_last: false
-                                }));
-                            }
-                            x11_sys::XFree(ret as *mut _ as *mut c_void);
-                        }
+                        self.clipboard.handle_selection_notify(&selection);
                     }
                 },
                 x11_sys::SelectionRequest => {
-                    let request = event.xselectionrequest;
-                    let mut response = x11_sys::XSelectionEvent {
-                        type_: x11_sys::SelectionNotify as i32,
-                        serial: 0,
-                        send_event: 0,
-                        display: self.display,
-                        requestor: request.requestor,
-                        selection: request.selection,
-                        target: request.target,
-                        time: request.time,
-                        property: request.property,
-                    };
-                    if request.target == self.atoms.targets {
-                        let mut targets = [self.atoms.utf8_string];
-                        x11_sys::XChangeProperty(
-                            self.display,
-                            request.requestor,
-                            request.property,
-                            4,
-                            32,
-                            x11_sys::PropModeReplace as i32,
-                            targets.as_mut() as *mut _ as *mut c_uchar,
-                            targets.len() as i32
-                        );
-                    }
-                    else if request.target == self.atoms.utf8_string {
-                        x11_sys::XChangeProperty(
-                            self.display,
-                            request.requestor,
-                            request.property,
-                            self.atoms.utf8_string,
-                            8,
-                            x11_sys::PropModeReplace as i32,
-                            self.clipboard.as_ptr() as *const _ as *const c_uchar,
-                            self.clipboard.len() as i32
-                        );
-                    }
-                    else {
-                        response.property = 0;
-                    }
-                    x11_sys::XSendEvent(self.display, request.requestor, 1, 0, &mut response as *mut _ as *mut x11_sys::XEvent);
+                    self.clipboard.handle_selection_request(&event.xselectionrequest);
+                },
+                x11_sys::SelectionClear => {
+                    self.clipboard.handle_selection_clear(&event.xselectionclear);
+                },
+                x11_sys::PropertyNotify => {
+                    self.clipboard.handle_property_notify(&event.xproperty);
                 },
                 x11_sys::DestroyNotify => { // our window got destroyed
                     let destroy_window = event.xdestroywindow;
@@ -218,6 +185,18 @@ impl XlibApp {
                         }
                     }
                 },
+                x11_sys::FocusIn => {
+                    if let Some(window_ptr) = self.window_map.get(&event.xfocus.window) {
+                        let window = &mut (**window_ptr);
+                        window.focus_ime();
+                    }
+                },
+                x11_sys::FocusOut => {
+                    if let Some(window_ptr) = self.window_map.get(&event.xfocus.window) {
+                        let window = &mut (**window_ptr);
+                        window.unfocus_ime();
+                    }
+                },
                 x11_sys::EnterNotify => {},
                 x11_sys::LeaveNotify => {
                     let crossing = event.xcrossing;
@@ -244,7 +223,10 @@ impl XlibApp {
                 },
                 x11_sys::MotionNotify => { // mousemove
                     let motion = event.xmotion;
-                    if let Some(window_ptr) = self.window_map.get(&motion.window) {
+                    if self.drag_source.is_dragging() {
+                        self.drag_motion(motion.x_root, motion.y_root, motion.time, motion.state);
+                    }
+                    else if let Some(window_ptr) = self.window_map.get(&motion.window) {
                         let window = &mut (**window_ptr);
                         let x = motion.x;
                         let y = motion.y;
@@ -399,22 +381,41 @@ impl XlibApp {
                                 }
                             }
                             else {
-                                window.send_mouse_down(button.button as usize, self.xkeystate_to_modifiers(button.state))
+                                window.send_mouse_down(button.button as usize, self.xkeystate_to_modifiers(button.state));
+                                // middle click pastes the primary selection at the spot we just clicked
+                                if button.button == 2 {
+                                    self.clipboard_read(XlibClipboardRead {
+                                        target: XlibClipboardTarget::Paste,
+                                        selection: ClipboardSelection::Primary,
+                                        mime_types: vec![MIME_TEXT_PLAIN.to_string()],
+                                        window: window.window.unwrap(),
+                                        time: button.time
+                                    });
+                                }
                             }
                         }
                     }
                     self.last_click_time = time_now;
                     self.last_click_pos = (button.x_root, button.y_root);
+                    self.last_click_window = button.window;
                 },
                 x11_sys::ButtonRelease => { // mouse up
                     let button = event.xbutton;
+                    if self.drag_source.is_dragging() {
+                        self.drag_release(button.x_root, button.y_root, button.time, button.state);
+                    }
                     if let Some(window_ptr) = self.window_map.get(&button.window) {
                         let window = &mut (**window_ptr);
                         window.send_mouse_up(button.button as usize, self.xkeystate_to_modifiers(button.state))
                     }
                 },
                 x11_sys::KeyPress => {
-                    if let Some(window_ptr) = self.window_map.get(&event.xkey.window) {
+                    if self.drag_source.is_dragging() {
+                        if self.xkeyevent_to_keycode(&mut event.xkey) == KeyCode::Escape {
+                            self.drag_cancel(event.xkey.time);
+                        }
+                    }
+                    else if let Some(window_ptr) = self.window_map.get(&event.xkey.window) {
                         let window = &mut (**window_ptr);
                         let block_text = if event.xkey.keycode != 0 {
                             let key_code = self.xkeyevent_to_keycode(&mut event.xkey);
@@ -423,24 +424,15 @@ impl XlibApp {
                             if modifiers.control || modifiers.logo {
                                 match key_code {
                                     KeyCode::KeyV => { // paste
-                                        // request the pasteable text from the other side
-                                        x11_sys::XConvertSelection(
-                                            self.display,
-                                            self.atoms.clipboard,
-                                            self.atoms.utf8_string,
-                                            self.atoms.clipboard,
-                                            window.window.unwrap(),
-                                            event.xkey.time
-                                        );
-                                        /*
-                                        self.do_callback(&mut vec![
-                                            Event::TextInput(TextInputEvent {
-                                                input: String::new(),
-                                                was_paste: true,
-                                                replace_last: false
-                                            })
-                                        ]);
-                                        */
+                                        // request the pasteable content from the other side, this arrives later as
+                                        // a TextInput or a ClipboardPaste event
+                                        self.clipboard_read(XlibClipboardRead {
+                                            target: XlibClipboardTarget::Paste,
+                                            selection: ClipboardSelection::Clipboard,
+                                            mime_types: Vec::new(),
+                                            window: window.window.unwrap(),
+                                            time: event.xkey.time
+                                        });
                                     }
                                     KeyCode::KeyC => {
                                         let response = Rc::new(RefCell::new(None));
@@ -449,7 +441,12 @@ impl XlibApp {
                                         }));
                                         let response = response.borrow();
                                         if let Some(response) = response.as_ref() {
-                                            self.copy_to_clipboard(response, &window, &event);
+                                            self.clipboard.write(
+                                                ClipboardSelection::Clipboard,
+                                                vec![ClipboardItem::text(response)],
+                                                window.window.unwrap(),
+                                                event.xkey.time
+                                            );
                                         }
                                     }
                                     KeyCode::KeyX => {
@@ -459,7 +456,12 @@ impl XlibApp {
                                         }));
                                         let response = response.borrow();
                                         if let Some(response) = response.as_ref() {
-                                            self.copy_to_clipboard(response, &window, &event);
+                                            self.clipboard.write(
+                                                ClipboardSelection::Clipboard,
+                                                vec![ClipboardItem::text(response)],
+                                                window.window.unwrap(),
+                                                event.xkey.time
+                                            );
                                         }
                                     }
                                     _ => ()
@@ -477,22 +479,37 @@ impl XlibApp {
                         }else {false};
                         
                         if !block_text {
-                            // decode the character
-                            let mut buffer = [0u8; 32];
+                            // decode the character, input methods can commit a whole sentence at once
+                            let mut buffer = vec![0u8; 32];
                             let mut keysym = mem::MaybeUninit::uninit();
-                            let mut status = mem::MaybeUninit::uninit();
-                            let count = x11_sys::Xutf8LookupString(
-                                window.xic.unwrap(),
-                                &mut event.xkey,
-                                buffer.as_mut_ptr() as *mut c_char,
-                                buffer.len() as c_int,
-                                keysym.as_mut_ptr(),
-                                status.as_mut_ptr(),
-                            );
-                            //let keysym = keysym.assume_init();
-                            let status = status.assume_init();
-                            if status != x11_sys::XBufferOverflow {
-                                let utf8 = std::str::from_utf8(&buffer[..count as usize]).unwrap_or("").to_string();
+                            let mut status = x11_sys::XLookupChars;
+                            let count = if let Some(xic) = window.xic {
+                                loop {
+                                    let count = x11_sys::Xutf8LookupString(
+                                        xic,
+                                        &mut event.xkey,
+                                        buffer.as_mut_ptr() as *mut c_char,
+                                        buffer.len() as c_int,
+                                        keysym.as_mut_ptr(),
+                                        &mut status,
+                                    );
+                                    if status != x11_sys::XBufferOverflow {
+                                        break count;
+                                    }
+                                    buffer.resize(count as usize + 1, 0);
+                                }
+                            }
+                            else {
+                                x11_sys::XLookupString(
+                                    &mut event.xkey,
+                                    buffer.as_mut_ptr() as *mut c_char,
+                                    buffer.len() as c_int,
+                                    keysym.as_mut_ptr(),
+                                    ptr::null_mut()
+                                )
+                            };
+                            if status == x11_sys::XLookupChars || status == x11_sys::XLookupBoth {
+                                let utf8 = String::from_utf8_lossy(&buffer[..count.max(0) as usize]).to_string();
                                 let char_code = utf8.chars().next().unwrap_or('\0');
                                 if char_code >= ' ' && char_code != 127 as char {
                                     self.do_callback(XlibEvent::TextInput(TextInputEvent {
@@ -529,6 +546,13 @@ impl XlibApp {
                         self.dnd.handle_leave_event(&event);
                     } else if event.message_type == self.dnd.atoms.position {
                         self.dnd.handle_position_event(&event);
+                    } else if event.message_type == self.drag_source.atoms.status {
+                        self.drag_source.handle_status(&event);
+                        self.set_drag_cursor(self.drag_source.response());
+                    } else if event.message_type == self.drag_source.atoms.finished {
+                        if self.drag_source.handle_finished(&event) {
+                            self.drag_end(x11_sys::CurrentTime as x11_sys::Time);
+                        }
                     }
                 },
                 x11_sys::Expose => {
@@ -541,6 +565,9 @@ impl XlibApp {
                 },
                 _ => {}
             }
+            for event in self.clipboard.take_events() {
+                self.do_callback(event);
+            }
         }
         self.do_callback(XlibEvent::Paint);
     }
@@ -557,30 +584,14 @@ impl XlibApp {
                         break;
                     }
                     EventFlow::Wait => {
-                        let time = self.time_now();
                         self.timers.update_timers(&mut timer_ids);
-                        for timer_id in &timer_ids{
-                            self.do_callback(
-                                XlibEvent::Timer(TimerEvent {
-                                    timer_id:*timer_id,
-                                    time: Some(time)
-                                })
-                            );
-                        }
+                        self.fire_timers(&timer_ids);
                         self.timers.select(self.display_fd);
                         self.event_flow = EventFlow::Poll;
                     }
                     EventFlow::Poll => { 
-                        let time = self.time_now();
                         self.timers.update_timers(&mut timer_ids);
-                        for timer_id in &timer_ids{
-                            self.do_callback(
-                                XlibEvent::Timer(TimerEvent {
-                                    timer_id:*timer_id,
-                                    time: Some(time)
-                                })
-                            );
-                        }
+                        self.fire_timers(&timer_ids);
                         self.event_loop_poll();
                     }
                 }
@@ -588,6 +599,43 @@ impl XlibApp {
         }
     }
     
+    unsafe fn fire_timers(&mut self, timer_ids: &[u64]) {
+        let time = self.time_now();
+        for timer_id in timer_ids {
+            if *timer_id == CLIPBOARD_TIMER_ID {
+                self.clipboard.check_timeout();
+                if !self.clipboard.is_reading() {
+                    self.timers.stop_timer(CLIPBOARD_TIMER_ID);
+                }
+                for event in self.clipboard.take_events() {
+                    self.do_callback(event);
+                }
+                continue
+            }
+            if *timer_id == DRAG_FINISHED_TIMER_ID {
+                // the drop target never said it was done with the data
+                if self.drag_source.is_finishing() {
+                    self.drag_end(x11_sys::CurrentTime as x11_sys::Time);
+                }
+                continue
+            }
+            self.do_callback(
+                XlibEvent::Timer(TimerEvent {
+                    timer_id: *timer_id,
+                    time: Some(time)
+                })
+            );
+        }
+    }
+    
+    /// Queues a clipboard read, and keeps a timer running to time it out while reads are pending
+    pub unsafe fn clipboard_read(&mut self, read: XlibClipboardRead) {
+        if !self.clipboard.is_reading() {
+            self.timers.start_timer(CLIPBOARD_TIMER_ID, CLIPBOARD_TIMER_INTERVAL, true);
+        }
+        self.clipboard.read(read);
+    }
+    
     pub fn do_callback(&mut self, event: XlibEvent) {
         if let Some(mut callback) = self.event_callback.take() {
             self.event_flow = callback(self, event);
@@ -600,7 +648,9 @@ impl XlibApp {
     
     pub fn terminate_event_loop(&mut self) {
         self.event_loop_running = false;
-        unsafe {x11_sys::XCloseIM(self.xim)};
+        if !self.xim.is_null() {
+            unsafe {x11_sys::XCloseIM(self.xim)};
+        }
         unsafe {x11_sys::XCloseDisplay(self.display)};
         self.display = ptr::null_mut();
     }
@@ -833,18 +883,139 @@ impl XlibApp {
             _ => KeyCode::Unknown,
         }
     }
-
-    unsafe fn copy_to_clipboard(&mut self, text: &String, window: &XlibWindow, event: &XEvent) {
-        // store the text on the clipboard
-        self.clipboard = text.clone();
-        // lets set the owner
-        x11_sys::XSetSelectionOwner(
-            self.display,
-            self.atoms.clipboard,
-            window.window.unwrap(),
-            event.xkey.time
-        );
-        x11_sys::XFlush(self.display);
+    
+    /// Starts dragging from the window that was last clicked, the button is still down
+    pub fn start_dragging(&mut self, items: Vec<DragItem>) {
+        let window = self.last_click_window;
+        if self.drag_source.is_dragging() {
+            return
+        }
+        // a drag that can't start ends right away, so the app doesn't wait for it
+        if !self.window_map.contains_key(&window) {
+            self.do_callback(XlibEvent::DragEnd);
+            return
+        }
+        unsafe {
+            let clipboard_items = drag_items_to_clipboard_items(&items);
+            let types = self.clipboard.targets(&clipboard_items);
+            self.clipboard.own(self.drag_source.atoms.selection, clipboard_items, window, x11_sys::CurrentTime as x11_sys::Time);
+            if !self.drag_source.start(items, window, types) {
+                self.do_callback(XlibEvent::DragEnd);
+            }
+        }
+    }
+    
+    /// Shows what a drop would do at the current position, the pointer is grabbed so this
+    /// goes through the grab instead of the window cursor
+    unsafe fn set_drag_cursor(&self, response: DragResponse) {
+        let cursor = match response {
+            DragResponse::None => self.load_first_cursor(&[b"dnd-no-drop\0", b"crossed_circle\0"]),
+            DragResponse::Copy => self.load_first_cursor(&[b"dnd-copy\0", b"copy\0"]),
+            DragResponse::Move => self.load_first_cursor(&[b"dnd-move\0", b"move\0"]),
+            DragResponse::Link => self.load_first_cursor(&[b"dnd-link\0", b"alias\0"]),
+        };
+        if let Some(cursor) = cursor {
+            x11_sys::XChangeActivePointerGrab(
+                self.display,
+                x11_sys::ButtonReleaseMask | x11_sys::PointerMotionMask,
+                cursor,
+                x11_sys::CurrentTime as x11_sys::Time
+            );
+            x11_sys::XFreeCursor(self.display, cursor);
+        }
+    }
+    
+    unsafe fn root_to_window_pos(&self, window: &XlibWindow, x_root: c_int, y_root: c_int) -> DVec2 {
+        let root = x11_sys::XRootWindow(self.display, x11_sys::XDefaultScreen(self.display));
+        let mut x = 0;
+        let mut y = 0;
+        let mut child = 0;
+        x11_sys::XTranslateCoordinates(self.display, root, window.window.unwrap(), x_root, y_root, &mut x, &mut y, &mut child);
+        DVec2 {x: x as f64, y: y as f64} / window.get_dpi_factor()
+    }
+    
+    unsafe fn send_internal_drag(&mut self, window: c_ulong, x_root: c_int, y_root: c_int, modifiers: KeyModifiers) -> DragResponse {
+        let window = if let Some(window_ptr) = self.window_map.get(&window) {&mut (**window_ptr)} else {return DragResponse::None};
+        let response = Rc::new(Cell::new(DragResponse::None));
+        window.do_callback(XlibEvent::Drag(DragEvent {
+            modifiers,
+            handled: Cell::new(false),
+            abs: self.root_to_window_pos(window, x_root, y_root),
+            items: self.drag_source.items.clone().unwrap(),
+            response: response.clone(),
+        }));
+        response.get()
+    }
+    
+    unsafe fn drag_motion(&mut self, x_root: c_int, y_root: c_int, time: x11_sys::Time, state: c_uint) {
+        if self.drag_source.is_finishing() {
+            return
+        }
+        let modifiers = self.xkeystate_to_modifiers(state);
+        self.drag_source.action = self.drag_source.action_for_modifiers(&modifiers);
+        let root = x11_sys::XRootWindow(self.display, x11_sys::XDefaultScreen(self.display));
+        let target = self.drag_source.find_target(root, x_root, y_root);
+        let internal = target.map(| target | target.window).filter(| window | self.window_map.contains_key(window));
+        // let the window we leave know the drag moved out of it
+        if let Some((window, _)) = self.drag_source.internal {
+            if Some(window) != internal {
+                self.drag_source.internal = None;
+                self.do_callback(XlibEvent::DragLeave);
+            }
+        }
+        if let Some(window) = internal {
+            // our own windows don't need the round trip through the X server
+            self.drag_source.update_target(None, x_root, y_root, time);
+            let response = self.send_internal_drag(window, x_root, y_root, modifiers);
+            self.drag_source.internal = Some((window, response));
+            self.set_drag_cursor(response);
+        }
+        else {
+            self.drag_source.update_target(target, x_root, y_root, time);
+            self.set_drag_cursor(self.drag_source.response());
+        }
+    }
+    
+    unsafe fn drag_release(&mut self, x_root: c_int, y_root: c_int, time: x11_sys::Time, state: c_uint) {
+        if self.drag_source.is_finishing() {
+            return
+        }
+        if let Some((window, response)) = self.drag_source.internal.take() {
+            if response != DragResponse::None {
+                if let Some(window_ptr) = self.window_map.get(&window) {
+                    let window = &mut (**window_ptr);
+                    window.do_callback(XlibEvent::Drop(DropEvent {
+                        modifiers: self.xkeystate_to_modifiers(state),
+                        handled: Cell::new(false),
+                        abs: self.root_to_window_pos(window, x_root, y_root),
+                        items: self.drag_source.items.clone().unwrap(),
+                    }));
+                }
+            }
+        }
+        // the target fetches the data from XdndSelection, which we keep owning after the drag
+        else if self.drag_source.drop(time) {
+            self.timers.start_timer(DRAG_FINISHED_TIMER_ID, DRAG_FINISHED_TIMEOUT, false);
+            return
+        }
+        self.drag_end(time);
+    }
+    
+    unsafe fn drag_cancel(&mut self, time: x11_sys::Time) {
+        self.drag_source.leave();
+        self.drag_source.internal = None;
+        self.drag_end(time);
+    }
+    
+    unsafe fn drag_end(&mut self, time: x11_sys::Time) {
+        self.timers.stop_timer(DRAG_FINISHED_TIMER_ID);
+        self.drag_source.end(time);
+        self.do_callback(XlibEvent::DragEnd);
+    }
+    
+    /// the window we own selections and receive conversions with when no particular one is involved
+    pub fn any_window(&self) -> Option<c_ulong> {
+        self.window_map.keys().next().cloned()
     }
 }
 
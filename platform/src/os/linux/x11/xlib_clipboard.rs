@@ -0,0 +1,580 @@
+use {
+    std::{
+        collections::{HashMap, VecDeque},
+        time::{Duration, Instant},
+        os::raw::{c_int, c_long, c_ulong, c_uchar, c_void},
+        ptr,
+    },
+    self::super::{
+        x11_sys,
+        xlib_event::XlibEvent,
+    },
+    crate::{
+        makepad_live_id::LiveId,
+        event::*,
+    },
+};
+
+// payloads bigger than this are sent in INCR chunks of this size
+const INCR_CHUNK_SIZE: usize = 256 * 1024;
+// an owner that doesn't answer within this time is given up on
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+// XA_ATOM
+const ATOM_TYPE: x11_sys::Atom = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub enum XlibClipboardTarget {
+    /// a ctrl+v or middle click, delivered to the key focus
+    Paste,
+    /// a Cx::clipboard_read, answered with a ClipboardRead event
+    Request(LiveId),
+}
+
+pub struct XlibClipboardRead {
+    pub target: XlibClipboardTarget,
+    pub selection: ClipboardSelection,
+    pub mime_types: Vec<String>,
+    pub window: x11_sys::Window,
+    pub time: x11_sys::Time,
+}
+
+enum ReadState {
+    Targets,
+    Convert {target: x11_sys::Atom, mime_type: String},
+    Incr {target: x11_sys::Atom, mime_type: String, data: Vec<u8>},
+}
+
+struct IncrSend {
+    requestor: x11_sys::Window,
+    property: x11_sys::Atom,
+    type_: x11_sys::Atom,
+    data: Vec<u8>,
+    offset: usize,
+    // the mask we had selected on the requestor before the transfer, restored afterwards
+    event_mask: c_long,
+}
+
+struct Owned {
+    window: x11_sys::Window,
+    items: Vec<ClipboardItem>,
+}
+
+/// Owns and reads the CLIPBOARD and PRIMARY selections. Reads are done one at a time
+/// (TARGETS negotiation, then the conversion, optionally in INCR chunks), finished reads
+/// come back out as XlibEvents through take_events.
+pub struct XlibClipboard {
+    display: *mut x11_sys::Display,
+    atoms: ClipboardAtoms,
+    owned: HashMap<x11_sys::Atom, Owned>,
+    reads: VecDeque<(XlibClipboardRead, Instant)>,
+    read_state: ReadState,
+    sends: Vec<IncrSend>,
+    events: Vec<XlibEvent>,
+}
+
+impl XlibClipboard {
+    pub fn new(display: *mut x11_sys::Display) -> Self {
+        Self {
+            display,
+            atoms: ClipboardAtoms::new(display),
+            owned: HashMap::new(),
+            reads: VecDeque::new(),
+            read_state: ReadState::Targets,
+            sends: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<XlibEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn selection_atom(&self, selection: ClipboardSelection) -> x11_sys::Atom {
+        match selection {
+            ClipboardSelection::Clipboard => self.atoms.clipboard,
+            ClipboardSelection::Primary => self.atoms.primary,
+        }
+    }
+
+    fn intern(&self, name: &str) -> x11_sys::Atom {
+        let name = format!("{}\0", name);
+        unsafe {x11_sys::XInternAtom(self.display, name.as_ptr() as *const _, 0)}
+    }
+
+    fn text_atoms(&self) -> [x11_sys::Atom; 5] {
+        [self.atoms.utf8_string, self.atoms.text_plain_utf8, self.atoms.text_plain, self.atoms.string, self.atoms.text]
+    }
+
+    /// the targets we can convert an item to, best first
+    fn item_targets(&self, item: &ClipboardItem) -> Vec<x11_sys::Atom> {
+        if item.is_text() {
+            self.text_atoms().to_vec()
+        }
+        else {
+            vec![self.intern(&item.mime_type)]
+        }
+    }
+
+    // Writing
+
+    /// # Safety
+    /// `window` must be a live window on our display.
+    pub unsafe fn write(&mut self, selection: ClipboardSelection, items: Vec<ClipboardItem>, window: x11_sys::Window, time: x11_sys::Time) {
+        let selection = self.selection_atom(selection);
+        self.own(selection, items, window, time);
+    }
+
+    /// Takes ownership of any selection, like XdndSelection, and serves the items on request
+    ///
+    /// # Safety
+    /// `window` must be a live window on our display.
+    pub unsafe fn own(&mut self, selection: x11_sys::Atom, items: Vec<ClipboardItem>, window: x11_sys::Window, time: x11_sys::Time) {
+        self.owned.insert(selection, Owned {window, items});
+        x11_sys::XSetSelectionOwner(self.display, selection, window, time);
+        x11_sys::XFlush(self.display);
+    }
+
+    /// All the targets the items can be converted to, best first
+    pub fn targets(&self, items: &[ClipboardItem]) -> Vec<x11_sys::Atom> {
+        let mut targets = Vec::new();
+        for item in items {
+            for target in self.item_targets(item) {
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+        }
+        targets
+    }
+
+    /// # Safety
+    /// The event has to come from our display.
+    pub unsafe fn handle_selection_clear(&mut self, event: &x11_sys::XSelectionClearEvent) {
+        // someone else took the selection, unless its just us moving it to another window
+        if self.owned.get(&event.selection).is_some_and( | owned | owned.window == event.window) {
+            self.owned.remove(&event.selection);
+        }
+    }
+
+    /// # Safety
+    /// The event has to come from our display, the reply is sent to its requestor.
+    pub unsafe fn handle_selection_request(&mut self, request: &x11_sys::XSelectionRequestEvent) {
+        // obsolete clients pass no property, we then use the target as the property
+        let property = if request.property == 0 {request.target} else {request.property};
+        let mut response = x11_sys::XSelectionEvent {
+            type_: x11_sys::SelectionNotify as i32,
+            serial: 0,
+            send_event: 0,
+            display: self.display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            time: request.time,
+            property,
+        };
+        if !self.serve_request(request, property) {
+            response.property = 0;
+        }
+        x11_sys::XSendEvent(self.display, request.requestor, 1, 0, &mut response as *mut _ as *mut x11_sys::XEvent);
+        x11_sys::XFlush(self.display);
+    }
+
+    unsafe fn serve_request(&mut self, request: &x11_sys::XSelectionRequestEvent, property: x11_sys::Atom) -> bool {
+        let items = if let Some(owned) = self.owned.get(&request.selection) {
+            &owned.items
+        }
+        else {
+            return false
+        };
+        if request.target == self.atoms.targets {
+            let mut targets: Vec<c_ulong> = vec![self.atoms.targets];
+            for item in items {
+                for target in self.item_targets(item) {
+                    if !targets.contains(&target) {
+                        targets.push(target);
+                    }
+                }
+            }
+            x11_sys::XChangeProperty(
+                self.display,
+                request.requestor,
+                property,
+                ATOM_TYPE,
+                32,
+                x11_sys::PropModeReplace as i32,
+                targets.as_ptr() as *const c_uchar,
+                targets.len() as i32
+            );
+            return true
+        }
+        let item = if let Some(item) = items.iter().find( | item | self.item_targets(item).contains(&request.target)) {
+            item
+        }
+        else {
+            return false
+        };
+        let (type_, data) = if request.target == self.atoms.string {
+            // STRING is latin1
+            let text = String::from_utf8_lossy(&item.data);
+            (request.target, text.chars().map( | c | if (c as u32) < 256 {c as u8} else {b'?'}).collect())
+        }
+        else if request.target == self.atoms.text {
+            (self.atoms.utf8_string, item.data.clone())
+        }
+        else {
+            (request.target, item.data.clone())
+        };
+
+        if data.len() > INCR_CHUNK_SIZE {
+            // too big for a single property, announce the size and hand it over in chunks as the
+            // requestor deletes the property
+            // the requestor can be one of our own windows, so add to the events we already select
+            // on it instead of replacing them
+            let event_mask = if let Some(send) = self.sends.iter().find( | send | send.requestor == request.requestor) {
+                send.event_mask
+            }
+            else {
+                let mut attributes: x11_sys::XWindowAttributes = std::mem::zeroed();
+                if x11_sys::XGetWindowAttributes(self.display, request.requestor, &mut attributes) != 0 {
+                    attributes.your_event_mask
+                }
+                else {
+                    x11_sys::NoEventMask as c_long
+                }
+            };
+            x11_sys::XSelectInput(self.display, request.requestor, event_mask | x11_sys::PropertyChangeMask as c_long);
+            let size = [data.len() as c_ulong];
+            x11_sys::XChangeProperty(
+                self.display,
+                request.requestor,
+                property,
+                self.atoms.incr,
+                32,
+                x11_sys::PropModeReplace as i32,
+                size.as_ptr() as *const c_uchar,
+                1
+            );
+            self.sends.retain( | send | send.requestor != request.requestor || send.property != property);
+            self.sends.push(IncrSend {
+                requestor: request.requestor,
+                property,
+                type_,
+                data,
+                offset: 0,
+                event_mask
+            });
+        }
+        else {
+            x11_sys::XChangeProperty(
+                self.display,
+                request.requestor,
+                property,
+                type_,
+                8,
+                x11_sys::PropModeReplace as i32,
+                data.as_ptr() as *const c_uchar,
+                data.len() as i32
+            );
+        }
+        true
+    }
+
+    // Reading
+
+    /// # Safety
+    /// The window in the read must be a live window on our display.
+    pub unsafe fn read(&mut self, read: XlibClipboardRead) {
+        self.check_timeout();
+        self.reads.push_back((read, Instant::now()));
+        if self.reads.len() == 1 {
+            self.start_read();
+        }
+    }
+
+    pub fn is_reading(&self) -> bool {
+        !self.reads.is_empty()
+    }
+
+    /// Gives up on an owner that never answered, the app calls this from a timer while is_reading
+    ///
+    /// # Safety
+    /// The display must still be open, this can start the next queued read.
+    pub unsafe fn check_timeout(&mut self) {
+        if let Some((_, started)) = self.reads.front() {
+            if started.elapsed() > READ_TIMEOUT {
+                self.finish_read(Err("Timeout reading the clipboard".to_string()));
+            }
+        }
+    }
+
+    unsafe fn start_read(&mut self) {
+        // queued reads only start timing out once the ones before them are done
+        if let Some((_, started)) = self.reads.front_mut() {
+            *started = Instant::now();
+        }
+        if let Some((read, _)) = self.reads.front() {
+            self.read_state = ReadState::Targets;
+            x11_sys::XConvertSelection(
+                self.display,
+                self.selection_atom(read.selection),
+                self.atoms.targets,
+                self.atoms.property,
+                read.window,
+                read.time
+            );
+            x11_sys::XFlush(self.display);
+        }
+    }
+
+    unsafe fn convert(&mut self, target: x11_sys::Atom, mime_type: String) {
+        let (read, _) = self.reads.front().unwrap();
+        x11_sys::XConvertSelection(
+            self.display,
+            self.selection_atom(read.selection),
+            target,
+            self.atoms.property,
+            read.window,
+            read.time
+        );
+        x11_sys::XFlush(self.display);
+        self.read_state = ReadState::Convert {target, mime_type};
+    }
+
+    /// picks the best of the offered targets for the mime types we asked for
+    fn choose_target(&self, mime_types: &[String], offered: &[x11_sys::Atom]) -> Option<(x11_sys::Atom, String)> {
+        for mime_type in mime_types {
+            let candidates = if mime_type_matches(mime_type, MIME_TEXT_PLAIN) {
+                self.text_atoms().to_vec()
+            }
+            else {
+                vec![self.intern(mime_type)]
+            };
+            if let Some(target) = candidates.iter().find( | target | offered.contains(target)) {
+                return Some((*target, mime_type.clone()))
+            }
+        }
+        None
+    }
+
+    /// # Safety
+    /// The event has to come from our display.
+    pub unsafe fn handle_selection_notify(&mut self, event: &x11_sys::XSelectionEvent) {
+        let mime_types = if let Some((read, _)) = self.reads.front() {
+            if event.requestor != read.window || event.selection != self.selection_atom(read.selection) {
+                return
+            }
+            read_mime_types(&read.mime_types)
+        }
+        else {
+            return
+        };
+        if event.property == 0 {
+            // the owner refused, or there is no owner at all
+            if let ReadState::Targets = self.read_state {
+                // owners that don't do TARGETS usually still do plain text
+                if mime_types.iter().any( | m | mime_type_matches(m, MIME_TEXT_PLAIN)) {
+                    self.convert(self.atoms.utf8_string, MIME_TEXT_PLAIN.to_string());
+                    return
+                }
+            }
+            self.finish_read(Err("Clipboard is empty or cannot be converted".to_string()));
+            return
+        }
+        let (type_, data) = self.read_property(event.requestor, event.property);
+        match std::mem::replace(&mut self.read_state, ReadState::Targets) {
+            ReadState::Targets => {
+                let offered = atoms_from_bytes(&data);
+                if let Some((target, mime_type)) = self.choose_target(&mime_types, &offered) {
+                    self.convert(target, mime_type);
+                }
+                else {
+                    self.finish_read(Err("Clipboard has no content in the requested formats".to_string()));
+                }
+            }
+            ReadState::Convert {target, mime_type} => {
+                if type_ == self.atoms.incr {
+                    // deleting the property (done in read_property) tells the owner to start sending
+                    self.read_state = ReadState::Incr {target, mime_type, data: Vec::new()};
+                }
+                else {
+                    self.finish_read(Ok(self.make_item(target, mime_type, data)));
+                }
+            }
+            ReadState::Incr {..} => {
+                self.finish_read(Err("Unexpected selection notify during INCR transfer".to_string()));
+            }
+        }
+    }
+
+    /// # Safety
+    /// The event has to come from our display.
+    pub unsafe fn handle_property_notify(&mut self, event: &x11_sys::XPropertyEvent) {
+        if event.state == x11_sys::PropertyDelete {
+            // the requestor consumed a chunk of an outgoing INCR transfer
+            if let Some(index) = self.sends.iter().position( | send | send.requestor == event.window && send.property == event.atom) {
+                let send = &mut self.sends[index];
+                let end = (send.offset + INCR_CHUNK_SIZE).min(send.data.len());
+                let chunk = &send.data[send.offset..end];
+                x11_sys::XChangeProperty(
+                    self.display,
+                    send.requestor,
+                    send.property,
+                    send.type_,
+                    8,
+                    x11_sys::PropModeReplace as i32,
+                    chunk.as_ptr() as *const c_uchar,
+                    chunk.len() as i32
+                );
+                // a zero length chunk marks the end
+                if chunk.is_empty() {
+                    let send = self.sends.remove(index);
+                    if !self.sends.iter().any( | other | other.requestor == send.requestor) {
+                        x11_sys::XSelectInput(self.display, send.requestor, send.event_mask);
+                    }
+                }
+                else {
+                    send.offset = end;
+                }
+                x11_sys::XFlush(self.display);
+            }
+        }
+        else if event.state == x11_sys::PropertyNewValue && event.atom == self.atoms.property {
+            // the owner put the next chunk of an incoming INCR transfer up
+            let window = if let Some((read, _)) = self.reads.front() {read.window} else {return};
+            if event.window != window || !matches!(self.read_state, ReadState::Incr {..}) {
+                return
+            }
+            let (_, chunk) = self.read_property(window, self.atoms.property);
+            if chunk.is_empty() {
+                if let ReadState::Incr {target, mime_type, data} = std::mem::replace(&mut self.read_state, ReadState::Targets) {
+                    self.finish_read(Ok(self.make_item(target, mime_type, data)));
+                }
+            }
+            else if let ReadState::Incr {data, ..} = &mut self.read_state {
+                data.extend_from_slice(&chunk);
+            }
+        }
+    }
+
+    fn make_item(&self, target: x11_sys::Atom, mime_type: String, mut data: Vec<u8>) -> ClipboardItem {
+        if !mime_type_matches(&mime_type, MIME_TEXT_PLAIN) {
+            return ClipboardItem::new(&mime_type, data)
+        }
+        if target == self.atoms.string {
+            data = data.iter().map( | b | *b as char).collect::<String>().into_bytes();
+        }
+        while data.last() == Some(&0) {
+            data.pop();
+        }
+        ClipboardItem::text(&String::from_utf8_lossy(&data))
+    }
+
+    unsafe fn finish_read(&mut self, response: Result<ClipboardItem, String>) {
+        let (read, _) = if let Some(read) = self.reads.pop_front() {read} else {return};
+        match read.target {
+            XlibClipboardTarget::Paste => if let Ok(item) = response {
+                if item.is_text() {
+                    self.events.push(XlibEvent::TextInput(TextInputEvent {
+                        input: item.as_text().unwrap_or("").to_string(),
+                        was_paste: true,
+                        replace_last: false
+                    }));
+                }
+                else {
+                    self.events.push(XlibEvent::ClipboardPaste(ClipboardPasteEvent {item}));
+                }
+            }
+            XlibClipboardTarget::Request(request_id) => {
+                self.events.push(XlibEvent::ClipboardRead(ClipboardReadEvent {
+                    request_id,
+                    selection: read.selection,
+                    response
+                }));
+            }
+        }
+        self.start_read();
+    }
+
+    /// reads and deletes a property, returning its type and contents
+    unsafe fn read_property(&self, window: x11_sys::Window, property: x11_sys::Atom) -> (x11_sys::Atom, Vec<u8>) {
+        let mut data = Vec::new();
+        let mut actual_type = 0;
+        let mut offset = 0;
+        loop {
+            let mut actual_format = 0;
+            let mut n_items = 0;
+            let mut bytes_after = 0;
+            let mut ret = ptr::null_mut();
+            // offset and length are in 32 bit units, the property is deleted once all of it is read
+            x11_sys::XGetWindowProperty(
+                self.display,
+                window,
+                property,
+                offset,
+                INCR_CHUNK_SIZE as c_long / 4,
+                1,
+                x11_sys::AnyPropertyType as c_ulong,
+                &mut actual_type,
+                &mut actual_format,
+                &mut n_items,
+                &mut bytes_after,
+                &mut ret
+            );
+            if ret.is_null() {
+                break;
+            }
+            // 32 bit items are handed out as longs
+            let item_size = match actual_format {
+                16 => 2,
+                32 => std::mem::size_of::<c_long>(),
+                _ => 1
+            };
+            let len = n_items as usize * item_size;
+            data.extend_from_slice(std::slice::from_raw_parts(ret as *const u8, len));
+            x11_sys::XFree(ret as *mut c_void);
+            if bytes_after == 0 {
+                break;
+            }
+            offset += (n_items as usize * actual_format as usize / 32) as c_long;
+        }
+        (actual_type, data)
+    }
+}
+
+fn atoms_from_bytes(data: &[u8]) -> Vec<x11_sys::Atom> {
+    data.chunks_exact(std::mem::size_of::<x11_sys::Atom>())
+        .map( | chunk | x11_sys::Atom::from_ne_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+struct ClipboardAtoms {
+    clipboard: x11_sys::Atom,
+    primary: x11_sys::Atom,
+    targets: x11_sys::Atom,
+    incr: x11_sys::Atom,
+    utf8_string: x11_sys::Atom,
+    string: x11_sys::Atom,
+    text: x11_sys::Atom,
+    text_plain: x11_sys::Atom,
+    text_plain_utf8: x11_sys::Atom,
+    // the property on our window conversions are delivered into
+    property: x11_sys::Atom,
+}
+
+impl ClipboardAtoms {
+    fn new(display: *mut x11_sys::Display) -> Self {
+        let intern = | name: &str | unsafe {x11_sys::XInternAtom(display, name.as_ptr() as *const _, 0 as c_int)};
+        Self {
+            clipboard: intern("CLIPBOARD\0"),
+            primary: intern("PRIMARY\0"),
+            targets: intern("TARGETS\0"),
+            incr: intern("INCR\0"),
+            utf8_string: intern("UTF8_STRING\0"),
+            string: intern("STRING\0"),
+            text: intern("TEXT\0"),
+            text_plain: intern("text/plain\0"),
+            text_plain_utf8: intern("text/plain;charset=utf-8\0"),
+            property: intern("MAKEPAD_SELECTION\0"),
+        }
+    }
+}
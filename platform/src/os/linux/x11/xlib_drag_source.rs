@@ -0,0 +1,449 @@
+use {
+    std::{
+        mem,
+        rc::Rc,
+        os::raw::{c_int, c_long, c_ulong, c_uchar, c_void},
+        ptr,
+    },
+    self::super::{
+        x11_sys,
+    },
+    crate::{
+        event::*,
+    },
+};
+
+// the XDND version we speak
+const XDND_VERSION: c_long = 5;
+// XA_ATOM and XA_WINDOW
+const ATOM_TYPE: x11_sys::Atom = 4;
+const WINDOW_TYPE: x11_sys::Atom = 33;
+
+/// An XdndAware window under the pointer. Messages go to the proxy, but are about the window.
+#[derive(Clone, Copy, PartialEq)]
+pub struct XlibDragTarget {
+    pub window: x11_sys::Window,
+    pub proxy: x11_sys::Window,
+    pub version: c_long,
+}
+
+struct ExternalTarget {
+    target: XlibDragTarget,
+    accepted: bool,
+    // the action the target said it will perform, which need not be the one we asked for
+    action: x11_sys::Atom,
+    // XDND wants us to wait for a status before sending the next position
+    waiting_for_status: bool,
+    pending_position: Option<(c_int, c_int, x11_sys::Time)>,
+}
+
+/// The source side of XDND: we own XdndSelection while dragging and tell the window under
+/// the pointer where we are. Dragging onto our own windows is short-circuited by XlibApp.
+pub struct XlibDragSource {
+    display: *mut x11_sys::Display,
+    pub atoms: DragSourceAtoms,
+    pub items: Option<Rc<Vec<DragItem >>>,
+    pub window: x11_sys::Window,
+    pub action: x11_sys::Atom,
+    /// the window of ours under the pointer and what it said about the drag
+    pub internal: Option<(x11_sys::Window, DragResponse)>,
+    types: Vec<x11_sys::Atom>,
+    external: Option<ExternalTarget>,
+    // the target we dropped on, until it sends XdndFinished
+    finishing: Option<XlibDragTarget>,
+}
+
+impl XlibDragSource {
+    /// # Safety
+    /// `display` must be an open display that outlives the drag source.
+    pub unsafe fn new(display: *mut x11_sys::Display) -> Self {
+        Self {
+            display,
+            atoms: DragSourceAtoms::new(display),
+            items: None,
+            window: 0,
+            action: 0,
+            internal: None,
+            types: Vec::new(),
+            external: None,
+            finishing: None,
+        }
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.items.is_some()
+    }
+
+    /// Whether we dropped on an external target and are waiting for it to finish
+    pub fn is_finishing(&self) -> bool {
+        self.finishing.is_some()
+    }
+
+    /// What the external target under the pointer will do with a drop
+    pub fn response(&self) -> DragResponse {
+        match &self.external {
+            Some(external) if external.accepted => {
+                if external.action == self.atoms.action_move {
+                    DragResponse::Move
+                }
+                else if external.action == self.atoms.action_link {
+                    DragResponse::Link
+                }
+                else {
+                    DragResponse::Copy
+                }
+            }
+            _ => DragResponse::None
+        }
+    }
+
+    /// Grabs the pointer so we keep getting motion while outside our windows. The items
+    /// must already be offered on XdndSelection with the given types.
+    ///
+    /// # Safety
+    /// `window` must be a live window on our display.
+    pub unsafe fn start(&mut self, items: Vec<DragItem>, window: x11_sys::Window, types: Vec<x11_sys::Atom>) -> bool {
+        let grab = x11_sys::XGrabPointer(
+            self.display,
+            window,
+            x11_sys::False as c_int,
+            x11_sys::ButtonReleaseMask | x11_sys::PointerMotionMask,
+            x11_sys::GrabModeAsync,
+            x11_sys::GrabModeAsync,
+            0,
+            0,
+            x11_sys::CurrentTime as x11_sys::Time,
+        );
+        if grab != x11_sys::GrabSuccess {
+            crate::error!("Could not grab the pointer to start dragging");
+            return false
+        }
+        if types.len() > 3 {
+            x11_sys::XChangeProperty(
+                self.display,
+                window,
+                self.atoms.type_list,
+                ATOM_TYPE,
+                32,
+                x11_sys::PropModeReplace as c_int,
+                types.as_ptr() as *const c_uchar,
+                types.len() as c_int
+            );
+        }
+        self.items = Some(Rc::new(items));
+        self.window = window;
+        self.action = self.atoms.action_copy;
+        self.types = types;
+        self.internal = None;
+        self.external = None;
+        self.finishing = None;
+        true
+    }
+
+    /// Ends the drag, the caller is responsible for dropping or leaving the target first
+    ///
+    /// # Safety
+    /// The display must still be open, this ungrabs the pointer.
+    pub unsafe fn end(&mut self, time: x11_sys::Time) {
+        x11_sys::XUngrabPointer(self.display, time);
+        x11_sys::XFlush(self.display);
+        self.items = None;
+        self.internal = None;
+        self.external = None;
+        self.finishing = None;
+    }
+
+    pub fn action_for_modifiers(&self, modifiers: &KeyModifiers) -> x11_sys::Atom {
+        if modifiers.control && modifiers.shift {
+            self.atoms.action_link
+        }
+        else if modifiers.shift {
+            self.atoms.action_move
+        }
+        else {
+            self.atoms.action_copy
+        }
+    }
+
+    /// Walks down from the root window to the first XdndAware window under the pointer
+    ///
+    /// # Safety
+    /// `root` must be the root window of our display.
+    pub unsafe fn find_target(&self, root: x11_sys::Window, x: c_int, y: c_int) -> Option<XlibDragTarget> {
+        let mut window = root;
+        loop {
+            let mut child = 0;
+            let mut child_x = 0;
+            let mut child_y = 0;
+            if x11_sys::XTranslateCoordinates(self.display, root, window, x, y, &mut child_x, &mut child_y, &mut child) == 0 {
+                return None
+            }
+            if child == 0 {
+                return None
+            }
+            window = child;
+            if let Some(version) = self.get_aware_version(window) {
+                let proxy = self.get_proxy(window).unwrap_or(window);
+                return Some(XlibDragTarget {window, proxy, version: version.min(XDND_VERSION)})
+            }
+        }
+    }
+
+    unsafe fn get_aware_version(&self, window: x11_sys::Window) -> Option<c_long> {
+        let atom = self.get_window_property(window, self.atoms.aware, ATOM_TYPE)?;
+        // XDND before version 3 is too different to bother with
+        if atom >= 3 {Some(atom as c_long)} else {None}
+    }
+
+    unsafe fn get_proxy(&self, window: x11_sys::Window) -> Option<x11_sys::Window> {
+        let proxy = self.get_window_property(window, self.atoms.proxy, WINDOW_TYPE)?;
+        // a valid proxy points at itself
+        if self.get_window_property(proxy, self.atoms.proxy, WINDOW_TYPE) == Some(proxy) {
+            Some(proxy)
+        }
+        else {
+            None
+        }
+    }
+
+    unsafe fn get_window_property(&self, window: x11_sys::Window, property: x11_sys::Atom, type_: x11_sys::Atom) -> Option<c_ulong> {
+        let mut actual_type = 0;
+        let mut actual_format = 0;
+        let mut nitems = 0;
+        let mut bytes_after = 0;
+        let mut prop = ptr::null_mut();
+        x11_sys::XGetWindowProperty(
+            self.display,
+            window,
+            property,
+            0,
+            1,
+            x11_sys::False as c_int,
+            type_,
+            &mut actual_type,
+            &mut actual_format,
+            &mut nitems,
+            &mut bytes_after,
+            &mut prop,
+        );
+        if prop.is_null() {
+            return None
+        }
+        let value = if actual_type == type_ && actual_format == 32 && nitems > 0 {
+            Some(*(prop as *const c_ulong))
+        }
+        else {
+            None
+        };
+        x11_sys::XFree(prop as *mut c_void);
+        value
+    }
+
+    /// Moves the drag over an external window, or over none of them
+    ///
+    /// # Safety
+    /// The target has to come from `find_target` on the same display.
+    pub unsafe fn update_target(&mut self, target: Option<XlibDragTarget>, x: c_int, y: c_int, time: x11_sys::Time) {
+        if self.external.as_ref().map(| e | e.target) != target {
+            self.leave();
+            if let Some(target) = target {
+                self.send_enter(&target);
+                self.external = Some(ExternalTarget {
+                    target,
+                    accepted: false,
+                    action: 0,
+                    waiting_for_status: false,
+                    pending_position: None,
+                });
+            }
+        }
+        if let Some(external) = &mut self.external {
+            if external.waiting_for_status {
+                external.pending_position = Some((x, y, time));
+            }
+            else {
+                external.waiting_for_status = true;
+                let target = external.target;
+                self.send_position(&target, x, y, time);
+            }
+        }
+    }
+
+    /// Sends XdndLeave to the current external target, if there is one
+    ///
+    /// # Safety
+    /// The display must still be open, this sends a client message.
+    pub unsafe fn leave(&mut self) {
+        if let Some(external) = self.external.take() {
+            self.send_message(&external.target, self.atoms.leave, [self.window as c_long, 0, 0, 0, 0]);
+        }
+    }
+
+    /// Drops on the external target if it wants the data, returns whether it did. The drag
+    /// then goes on until the target sends XdndFinished.
+    ///
+    /// # Safety
+    /// The display must still be open, this sends a client message.
+    pub unsafe fn drop(&mut self, time: x11_sys::Time) -> bool {
+        if let Some(external) = &self.external {
+            if external.accepted {
+                let target = external.target;
+                self.send_message(&target, self.atoms.drop, [self.window as c_long, 0, time as c_long, 0, 0]);
+                self.external = None;
+                self.finishing = Some(target);
+                return true
+            }
+        }
+        self.leave();
+        false
+    }
+
+    /// Returns whether this is the XdndFinished of the target we dropped on
+    ///
+    /// # Safety
+    /// `event` must be an XdndFinished message, its data is read as longs.
+    pub unsafe fn handle_finished(&self, event: &x11_sys::XClientMessageEvent) -> bool {
+        let window = event.data.l[0] as x11_sys::Window;
+        self.finishing.is_some_and( | target | target.window == window)
+    }
+
+    /// Takes in the XdndStatus of the external target
+    ///
+    /// # Safety
+    /// `event` must be an XdndStatus message, its data is read as longs.
+    pub unsafe fn handle_status(&mut self, event: &x11_sys::XClientMessageEvent) {
+        let window = event.data.l[0] as x11_sys::Window;
+        let external = if let Some(external) = &mut self.external {
+            external
+        }
+        else {
+            return
+        };
+        if external.target.window != window {
+            return
+        }
+        // a target that accepts without an action can't do anything with the drop
+        external.action = event.data.l[4] as x11_sys::Atom;
+        external.accepted = event.data.l[1] & (1 << 0) != 0 && external.action != 0;
+        external.waiting_for_status = false;
+        if let Some((x, y, time)) = external.pending_position.take() {
+            external.waiting_for_status = true;
+            let target = external.target;
+            self.send_position(&target, x, y, time);
+        }
+    }
+
+    unsafe fn send_enter(&self, target: &XlibDragTarget) {
+        let more_types = if self.types.len() > 3 {1} else {0};
+        let type_at = | index: usize | self.types.get(index).cloned().unwrap_or(0) as c_long;
+        self.send_message(target, self.atoms.enter, [
+            self.window as c_long,
+            (target.version << 24) | more_types,
+            type_at(0),
+            type_at(1),
+            type_at(2),
+        ]);
+    }
+
+    unsafe fn send_position(&self, target: &XlibDragTarget, x: c_int, y: c_int, time: x11_sys::Time) {
+        self.send_message(target, self.atoms.position, [
+            self.window as c_long,
+            0,
+            ((x as c_long) << 16) | (y as c_long & 0xffff),
+            time as c_long,
+            self.action as c_long,
+        ]);
+    }
+
+    unsafe fn send_message(&self, target: &XlibDragTarget, message_type: x11_sys::Atom, l: [c_long; 5]) {
+        let mut data = mem::zeroed::<x11_sys::XClientMessageEvent__bindgen_ty_1>();
+        data.l = l;
+        let mut message = x11_sys::XClientMessageEvent {
+            type_: x11_sys::ClientMessage as c_int,
+            serial: 0,
+            send_event: 0,
+            display: self.display,
+            window: target.window,
+            message_type,
+            format: 32,
+            data,
+        };
+        x11_sys::XSendEvent(
+            self.display,
+            target.proxy,
+            x11_sys::False as c_int,
+            x11_sys::NoEventMask as c_long,
+            &mut message as *mut x11_sys::XClientMessageEvent as *mut x11_sys::XEvent
+        );
+        x11_sys::XFlush(self.display);
+    }
+}
+
+/// Turns drag items into what other applications understand, file paths become a uri list
+pub fn drag_items_to_clipboard_items(items: &[DragItem]) -> Vec<ClipboardItem> {
+    let mut uris = Vec::new();
+    let mut clipboard_items = Vec::new();
+    for item in items {
+        match item {
+            DragItem::FilePath {path, ..} => if !path.is_empty() {
+                uris.push(file_path_to_uri(path));
+            }
+            DragItem::String {value, ..} => {
+                clipboard_items.push(ClipboardItem::text(value));
+            }
+        }
+    }
+    if !uris.is_empty() {
+        clipboard_items.insert(0, ClipboardItem::uri_list(&uris));
+    }
+    clipboard_items
+}
+
+// the path has to be absolute, other applications don't share our working directory
+fn file_path_to_uri(path: &str) -> String {
+    let mut uri = String::from("file://");
+    for &byte in path.as_bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+pub struct DragSourceAtoms {
+    pub selection: x11_sys::Atom,
+    pub aware: x11_sys::Atom,
+    pub proxy: x11_sys::Atom,
+    pub type_list: x11_sys::Atom,
+    pub enter: x11_sys::Atom,
+    pub position: x11_sys::Atom,
+    pub status: x11_sys::Atom,
+    pub leave: x11_sys::Atom,
+    pub drop: x11_sys::Atom,
+    pub finished: x11_sys::Atom,
+    pub action_copy: x11_sys::Atom,
+    pub action_move: x11_sys::Atom,
+    pub action_link: x11_sys::Atom,
+}
+
+impl DragSourceAtoms {
+    unsafe fn new(display: *mut x11_sys::Display) -> Self {
+        let intern = | name: &str | x11_sys::XInternAtom(display, name.as_ptr() as *const _, 0);
+        Self {
+            selection: intern("XdndSelection\0"),
+            aware: intern("XdndAware\0"),
+            proxy: intern("XdndProxy\0"),
+            type_list: intern("XdndTypeList\0"),
+            enter: intern("XdndEnter\0"),
+            position: intern("XdndPosition\0"),
+            status: intern("XdndStatus\0"),
+            leave: intern("XdndLeave\0"),
+            drop: intern("XdndDrop\0"),
+            finished: intern("XdndFinished\0"),
+            action_copy: intern("XdndActionCopy\0"),
+            action_move: intern("XdndActionMove\0"),
+            action_link: intern("XdndActionLink\0"),
+        }
+    }
+}
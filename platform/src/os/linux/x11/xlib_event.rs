@@ -10,10 +10,13 @@ use {
             WindowCloseRequestedEvent,
             WindowClosedEvent,
             TextInputEvent,
+            TextCompositionEvent,
             KeyEvent,
             DragEvent,
             DropEvent,
             TextClipboardEvent,
+            ClipboardReadEvent,
+            ClipboardPasteEvent,
             TimerEvent,
         },
     }
@@ -35,12 +38,16 @@ pub enum XlibEvent {
     WindowDragQuery(WindowDragQueryEvent),
     WindowCloseRequested(WindowCloseRequestedEvent),
     TextInput(TextInputEvent),
+    TextComposition(TextCompositionEvent),
     Drag(DragEvent),
     Drop(DropEvent),
     DragEnd,
+    DragLeave,
     KeyDown(KeyEvent),
     KeyUp(KeyEvent),
     TextCopy(TextClipboardEvent),
     TextCut(TextClipboardEvent),
+    ClipboardRead(ClipboardReadEvent),
+    ClipboardPaste(ClipboardPasteEvent),
     Timer(TimerEvent),
 }
@@ -3,7 +3,7 @@ use {
         mem,
         cell::Cell,
         rc::Rc,
-        os::raw::{c_ulong, c_long, c_void, c_char},
+        os::raw::{c_ulong, c_long, c_void, c_char, c_int},
         ptr,
         ffi::{CStr,CString}, 
     },
@@ -21,6 +21,83 @@ use {
     },
 };
 
+unsafe extern "C" fn xim_preedit_start(_xic: x11_sys::XIC, client_data: x11_sys::XPointer, _call_data: x11_sys::XPointer) -> c_int {
+    let window = &mut *(client_data as *mut XlibWindow);
+    window.preedit.clear();
+    window.preedit_caret = 0;
+    // no length limit on the preedit
+    -1
+}
+
+unsafe extern "C" fn xim_preedit_done(_xic: x11_sys::XIC, client_data: x11_sys::XPointer, _call_data: x11_sys::XPointer) -> c_int {
+    let window = &mut *(client_data as *mut XlibWindow);
+    window.preedit.clear();
+    window.preedit_caret = 0;
+    window.send_text_composition();
+    0
+}
+
+unsafe extern "C" fn xim_preedit_draw(_xic: x11_sys::XIC, client_data: x11_sys::XPointer, call_data: x11_sys::XPointer) -> c_int {
+    let window = &mut *(client_data as *mut XlibWindow);
+    let draw = &*(call_data as *const x11_sys::XIMPreeditDrawCallbackStruct);
+    let len = window.preedit.len();
+    let first = (draw.chg_first.max(0) as usize).min(len);
+    let end = (first + draw.chg_length.max(0) as usize).min(len);
+    
+    let mut chars = Vec::new();
+    let mut feedback = Vec::new();
+    let mut only_feedback = false;
+    if !draw.text.is_null() {
+        let text = &*draw.text;
+        let length = text.length as usize;
+        if !text.feedback.is_null() {
+            feedback = std::slice::from_raw_parts(text.feedback, length).to_vec();
+        }
+        if text.encoding_is_wchar != 0 {
+            if !text.string.wide_char.is_null() {
+                chars = std::slice::from_raw_parts(text.string.wide_char, length).iter()
+                    .filter_map( | c | char::from_u32(*c as u32)).collect();
+            }
+        }
+        else if !text.string.multi_byte.is_null() {
+            chars = CStr::from_ptr(text.string.multi_byte).to_string_lossy().chars().collect();
+        }
+        // a text without a string only changes the styling of the range
+        only_feedback = chars.is_empty() && !feedback.is_empty();
+    }
+    if only_feedback {
+        for (index, feedback) in feedback.iter().enumerate() {
+            if let Some(c) = window.preedit.get_mut(first + index) {
+                c.1 = *feedback;
+            }
+        }
+    }
+    else {
+        let replace = chars.iter().enumerate().map( | (index, c) | (*c, feedback.get(index).cloned().unwrap_or(0)));
+        window.preedit.splice(first..end, replace);
+    }
+    window.preedit_caret = (draw.caret.max(0) as usize).min(window.preedit.len());
+    window.send_text_composition();
+    0
+}
+
+unsafe extern "C" fn xim_preedit_caret(_xic: x11_sys::XIC, client_data: x11_sys::XPointer, call_data: x11_sys::XPointer) -> c_int {
+    let window = &mut *(client_data as *mut XlibWindow);
+    let caret = &mut *(call_data as *mut x11_sys::XIMPreeditCaretCallbackStruct);
+    let caret_pos = match caret.direction {
+        x11_sys::XIMForwardChar => window.preedit_caret + 1,
+        x11_sys::XIMBackwardChar => window.preedit_caret.saturating_sub(1),
+        x11_sys::XIMAbsolutePosition => caret.position.max(0) as usize,
+        _ => window.preedit_caret
+    }.min(window.preedit.len());
+    caret.position = caret_pos as c_int;
+    if caret_pos != window.preedit_caret {
+        window.preedit_caret = caret_pos;
+        window.send_text_composition();
+    }
+    0
+}
+
 #[derive(Clone)]
 pub struct XlibWindow {
     pub window: Option<c_ulong>,
@@ -34,6 +111,9 @@ pub struct XlibWindow {
     pub last_window_geom: WindowGeom,
     
     pub ime_spot: DVec2,
+    pub ime_visible: bool,
+    pub preedit: Vec<(char, x11_sys::XIMFeedback)>,
+    pub preedit_caret: usize,
     pub current_cursor: MouseCursor,
     pub last_mouse_pos: DVec2,
 }
@@ -62,6 +142,9 @@ impl XlibWindow {
             last_window_geom: WindowGeom::default(),
             last_nc_mode: None,
             ime_spot: DVec2::default(),
+            ime_visible: false,
+            preedit: Vec::new(),
+            preedit_caret: 0,
             current_cursor: MouseCursor::Default,
             last_mouse_pos: DVec2::default(),
         }
@@ -96,6 +179,7 @@ impl XlibWindow {
                     | x11_sys::FocusChangeMask
                     | x11_sys::EnterWindowMask
                     | x11_sys::LeaveWindowMask
+                    | x11_sys::PropertyChangeMask
             ) as c_long;
             
             let dpi_factor = self.get_dpi_factor();
@@ -150,16 +234,7 @@ impl XlibWindow {
             let title_bytes = format!("{}\0", title);
             x11_sys::XStoreName(display, window, title_bytes.as_bytes().as_ptr() as *const c_char);
             
-            let xic = x11_sys::XCreateIC(
-                get_xlib_app_global().xim,
-                x11_sys::XNInputStyle.as_ptr(),
-                (x11_sys::XIMPreeditNothing | x11_sys::XIMStatusNothing) as i32,
-                x11_sys::XNClientWindow.as_ptr(),
-                window,
-                x11_sys::XNFocusWindow.as_ptr(),
-                window,
-                ptr::null_mut() as *mut c_void
-            );
+            let xic = self.create_ic(window);
             
             // Create a window
             get_xlib_app_global().window_map.insert(window, self);
@@ -167,7 +242,7 @@ impl XlibWindow {
             self.attributes = Some(attributes);
             self.visual_info = Some(visual_info);
             self.window = Some(window);
-            self.xic = Some(xic);
+            self.xic = xic;
             self.last_window_geom = self.get_window_geom();
             
             let new_geom = self.get_window_geom();
@@ -220,6 +295,9 @@ impl XlibWindow {
     
     pub fn close_window(&mut self) {
         unsafe {
+            if let Some(xic) = self.xic.take() {
+                x11_sys::XDestroyIC(xic);
+            }
             x11_sys::XDestroyWindow(get_xlib_app_global().display, self.window.unwrap());
             self.window = None;
             // lets remove us from the mapping
@@ -297,8 +375,167 @@ impl XlibWindow {
         maximized
     }
     
+    unsafe fn create_ic(&mut self, window: c_ulong) -> Option<x11_sys::XIC> {
+        let xim = get_xlib_app_global().xim;
+        if xim.is_null() {
+            return None
+        }
+        let style = get_xlib_app_global().xim_style;
+        // the preedit callbacks get a pointer to us, XlibWindow is boxed so it doesn't move
+        let client_data = self as *mut XlibWindow as x11_sys::XPointer;
+        let mut start = x11_sys::XICCallback {client_data, callback: Some(xim_preedit_start)};
+        let mut done = x11_sys::XICCallback {client_data, callback: Some(xim_preedit_done)};
+        let mut draw = x11_sys::XICCallback {client_data, callback: Some(xim_preedit_draw)};
+        let mut caret = x11_sys::XICCallback {client_data, callback: Some(xim_preedit_caret)};
+        let mut spot = x11_sys::XPoint {x: 0, y: 0};
+        
+        let preedit_attributes = if style & x11_sys::XIMPreeditCallbacks as c_ulong != 0 {
+            x11_sys::XVaCreateNestedList(
+                0,
+                x11_sys::XNPreeditStartCallback.as_ptr(),
+                &mut start as *mut x11_sys::XICCallback,
+                x11_sys::XNPreeditDoneCallback.as_ptr(),
+                &mut done as *mut x11_sys::XICCallback,
+                x11_sys::XNPreeditDrawCallback.as_ptr(),
+                &mut draw as *mut x11_sys::XICCallback,
+                x11_sys::XNPreeditCaretCallback.as_ptr(),
+                &mut caret as *mut x11_sys::XICCallback,
+                ptr::null_mut() as *mut c_void
+            )
+        }
+        else if style & x11_sys::XIMPreeditPosition as c_ulong != 0 {
+            x11_sys::XVaCreateNestedList(
+                0,
+                x11_sys::XNSpotLocation.as_ptr(),
+                &mut spot as *mut x11_sys::XPoint,
+                ptr::null_mut() as *mut c_void
+            )
+        }
+        else {
+            ptr::null_mut()
+        };
+        
+        let xic = if preedit_attributes.is_null() {
+            x11_sys::XCreateIC(
+                xim,
+                x11_sys::XNInputStyle.as_ptr(),
+                style,
+                x11_sys::XNClientWindow.as_ptr(),
+                window,
+                x11_sys::XNFocusWindow.as_ptr(),
+                window,
+                ptr::null_mut() as *mut c_void
+            )
+        }
+        else {
+            let xic = x11_sys::XCreateIC(
+                xim,
+                x11_sys::XNInputStyle.as_ptr(),
+                style,
+                x11_sys::XNClientWindow.as_ptr(),
+                window,
+                x11_sys::XNFocusWindow.as_ptr(),
+                window,
+                x11_sys::XNPreeditAttributes.as_ptr(),
+                preedit_attributes,
+                ptr::null_mut() as *mut c_void
+            );
+            x11_sys::XFree(preedit_attributes);
+            xic
+        };
+        if xic.is_null() {
+            crate::log!("Could not create an X input context, input methods won't work");
+            return None
+        }
+        Some(xic)
+    }
+    
     pub fn set_ime_spot(&mut self, spot: DVec2) {
         self.ime_spot = spot;
+        let xic = if let Some(xic) = self.xic {xic} else {return};
+        if get_xlib_app_global().xim_style & x11_sys::XIMPreeditPosition as c_ulong == 0 {
+            return
+        }
+        let dpi_factor = self.get_dpi_factor();
+        let mut spot = x11_sys::XPoint {
+            x: (spot.x * dpi_factor) as i16,
+            y: (spot.y * dpi_factor) as i16,
+        };
+        unsafe {
+            let preedit_attributes = x11_sys::XVaCreateNestedList(
+                0,
+                x11_sys::XNSpotLocation.as_ptr(),
+                &mut spot as *mut x11_sys::XPoint,
+                ptr::null_mut() as *mut c_void
+            );
+            x11_sys::XSetICValues(
+                xic,
+                x11_sys::XNPreeditAttributes.as_ptr(),
+                preedit_attributes,
+                ptr::null_mut() as *mut c_void
+            );
+            x11_sys::XFree(preedit_attributes);
+        }
+    }
+    
+    pub fn show_ime(&mut self, spot: DVec2) {
+        if !self.ime_visible {
+            self.ime_visible = true;
+            self.focus_ime();
+        }
+        if spot != self.ime_spot {
+            self.set_ime_spot(spot);
+        }
+    }
+    
+    pub fn hide_ime(&mut self) {
+        if self.ime_visible {
+            self.ime_visible = false;
+            self.unfocus_ime();
+            if !self.preedit.is_empty() {
+                self.preedit.clear();
+                self.preedit_caret = 0;
+                self.send_text_composition();
+            }
+        }
+    }
+    
+    pub fn focus_ime(&mut self) {
+        if let Some(xic) = self.xic {
+            if self.ime_visible {
+                unsafe {x11_sys::XSetICFocus(xic)};
+            }
+        }
+    }
+    
+    pub fn unfocus_ime(&mut self) {
+        if let Some(xic) = self.xic {
+            unsafe {x11_sys::XUnsetICFocus(xic)};
+        }
+    }
+    
+    pub fn send_text_composition(&mut self) {
+        let mut segments: Vec<TextCompositionSegment> = Vec::new();
+        for (index, (_, feedback)) in self.preedit.iter().enumerate() {
+            let style = if feedback & (x11_sys::XIMReverse | x11_sys::XIMHighlight) as c_ulong != 0 {
+                TextCompositionStyle::Highlight
+            }
+            else if feedback & x11_sys::XIMUnderline as c_ulong != 0 {
+                TextCompositionStyle::Underline
+            }
+            else {
+                continue
+            };
+            match segments.last_mut() {
+                Some(last) if last.end == index && last.style == style => last.end = index + 1,
+                _ => segments.push(TextCompositionSegment {start: index, end: index + 1, style})
+            }
+        }
+        self.do_callback(XlibEvent::TextComposition(TextCompositionEvent {
+            text: self.preedit.iter().map( | (c, _) | *c).collect(),
+            cursor: self.preedit_caret,
+            segments
+        }));
     }
     
     pub fn get_position(&self) -> DVec2 {
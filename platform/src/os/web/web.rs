@@ -354,7 +354,7 @@ impl Cx {
         self.handle_platform_ops();
         self.handle_media_signals();
         
-        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 {
+        if self.any_passes_dirty() || self.need_redrawing() || self.new_next_frames.len() != 0 || !self.texture_readbacks.is_empty() {
             self.os.from_wasm(FromWasmRequestAnimationFrame {});
         }
         
@@ -383,7 +383,8 @@ impl Cx {
                     self.draw_pass_to_texture(*pass_id);
                 }
             }
-        }    
+        }
+        self.call_texture_readback_events();
     }
     
     
@@ -436,6 +437,12 @@ impl Cx {
                 },
                 CxOsOp::ShowClipboardActions(_) =>{
                 }
+                CxOsOp::ClipboardRead{request_id, selection, ..} => {
+                    self.call_clipboard_read_unsupported(request_id, selection);
+                }
+                CxOsOp::ClipboardWrite{selection, ..} => {
+                    Cx::log_clipboard_write_unsupported(selection);
+                }
                 CxOsOp::SetCursor(cursor) => {
                     self.os.from_wasm(FromWasmSetMouseCursor::new(cursor));
                 },
@@ -2,7 +2,10 @@ use {
     crate::{
         makepad_shader_compiler::{
             generate_glsl,
+            DrawShaderDef,
+            DrawShaderConstTable,
         },
+        makepad_live_compiler::LiveRegistry,
         makepad_wasm_bridge::*,
         makepad_math::*,
         os::{
@@ -11,9 +14,10 @@ use {
             }
         },
         draw_vars::DRAW_CALL_TEXTURE_SLOTS,
+        draw_shader::log_draw_shader_error,
         cx::Cx,
         draw_list::DrawListId,
-        texture::TextureFormat,
+        texture::{TextureFormat, TextureUpdated},
         pass::{PassId, PassClearColor, PassClearDepth},
     },
 };
@@ -82,17 +86,20 @@ impl Cx {
                     let cxtexture = &mut self.textures[texture_id];
                     if cxtexture.format.is_vec(){
                         if cxtexture.alloc_vec(){}
-                        if cxtexture.check_updated(){
+                        if cxtexture.check_updated() != TextureUpdated::Empty{
                             match &cxtexture.format{
+                                // there is no partial upload on the web, update_rect sends the whole texture
                                 TextureFormat::VecBGRAu8_32{width, height, data}=>{
                                     self.os.from_wasm(FromWasmAllocTextureImage2D {
                                         texture_id: texture_id.0,
                                         width: *width,
                                         height: *height,
-                                        data: WasmDataU32::new(&data)
+                                        data: WasmDataU32::new(data)
                                     });
                                 }
-                                _=>panic!()
+                                _=>{
+                                    crate::error!("Texture format not supported on the web, only VecBGRAu8_32 is");
+                                }
                             }
                         }
                     }
@@ -323,6 +330,11 @@ impl Cx {
             if let Some(item) = self.draw_shaders.ptr_to_item.get(&draw_shader_ptr) {
                 let cx_shader = &mut self.draw_shaders.shaders[item.draw_shader_id];
                 let draw_shader_def = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr);
+                // shaders that don't pass are left without an os shader and aren't drawn
+                if let Err(err) = generate_glsl::check_draw_shader(draw_shader_def.as_ref().unwrap(), &self.shader_registry) {
+                    log_draw_shader_error(&self.live_registry.borrow(), err);
+                    continue;
+                }
                 
                 let vertex = generate_glsl::generate_vertex_shader(
                     draw_shader_def.as_ref().unwrap(),
@@ -370,6 +382,18 @@ impl Cx {
         }
         self.draw_shaders.compile_set.clear();
     }
+    
+    /// The browser compiles the shaders, so this only runs the checks for what
+    /// GLSL ES doesn't support and generates the source
+    pub (crate) fn os_validate_draw_shader(&self, draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, live_registry: &LiveRegistry) -> bool {
+        if let Err(err) = generate_glsl::check_draw_shader(draw_shader_def, &self.shader_registry) {
+            log_draw_shader_error(live_registry, err);
+            return false
+        }
+        generate_glsl::generate_vertex_shader(draw_shader_def, const_table, &self.shader_registry);
+        generate_glsl::generate_pixel_shader(draw_shader_def, const_table, &self.shader_registry);
+        true
+    }
 }
 
 impl CxOsDrawShader{
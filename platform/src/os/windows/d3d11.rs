@@ -1,5 +1,6 @@
 use crate::{
-    makepad_shader_compiler::generate_hlsl,
+    makepad_shader_compiler::{generate_hlsl, source_map::ShaderSourceMap, DrawShaderDef, DrawShaderConstTable},
+    makepad_live_compiler::LiveRegistry,
     makepad_math::*,
     os::{
         windows::win32_app::{TRUE, FALSE,},
@@ -9,12 +10,13 @@ use crate::{
     draw_list::DrawListId,
     event::WindowGeom,
     cx::Cx,
-    draw_shader::CxDrawShaderMapping,
+    draw_shader::{CxDrawShaderMapping, report_shader_compiler_log},
     pass::{PassClearColor, PassClearDepth, PassId},
     window::WindowId,
     texture::{ 
         TextureFormat,
         TexturePixel,
+        TextureUpdated,
         TextureId,
         CxTexture
     },  
@@ -75,6 +77,7 @@ use crate::{
                     D3D11_MAP_WRITE_DISCARD,
                     D3D11_QUERY_DESC,
                     D3D11_QUERY_EVENT,
+                    D3D11_BOX,
                     ID3D11Device,
                     ID3D11DeviceContext,
                     ID3D11RenderTargetView,
@@ -118,6 +121,7 @@ use crate::{
                         DXGI_SAMPLE_DESC,
                         DXGI_FORMAT_R32G32B32A32_FLOAT,
                         DXGI_FORMAT_R16_FLOAT, 
+                        DXGI_FORMAT_R16_UNORM,
                         //DXGI_FORMAT_D32_FLOAT_S8X 24_UINT,
                         DXGI_FORMAT_D32_FLOAT,
                         DXGI_FORMAT_R32_UINT,
@@ -472,7 +476,7 @@ impl Cx {
             if let Some(item) = self.draw_shaders.ptr_to_item.get(&draw_shader_ptr) {
                 let cx_shader = &mut self.draw_shaders.shaders[item.draw_shader_id];
                 let draw_shader_def = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr);
-                let hlsl = generate_hlsl::generate_shader(
+                let (hlsl, source_map) = generate_hlsl::generate_shader_with_source_map(
                     draw_shader_def.as_ref().unwrap(),
                     &cx_shader.mapping.const_table,
                     &self.shader_registry
@@ -489,7 +493,7 @@ impl Cx {
                     }
                 }
                 if cx_shader.os_shader_id.is_none() {
-                    if let Some(shp) = CxOsDrawShader::new(d3d11_cx, hlsl, &cx_shader.mapping) {
+                    if let Some(shp) = CxOsDrawShader::new(d3d11_cx, hlsl, &source_map, &cx_shader.mapping, &self.live_registry.borrow()) {
                         cx_shader.os_shader_id = Some(self.draw_shaders.os_shaders.len());
                         self.draw_shaders.os_shaders.push(shp);
                     }
@@ -498,6 +502,21 @@ impl Cx {
         }
         self.draw_shaders.compile_set.clear();
     }
+    
+    /// Compiles the hlsl of a shader without a window, D3DCompile doesn't need a device
+    pub (crate) fn os_validate_draw_shader(&self, draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, live_registry: &LiveRegistry) -> bool {
+        let (hlsl, source_map) = generate_hlsl::generate_shader_with_source_map(draw_shader_def, const_table, &self.shader_registry);
+        let mut valid = true;
+        for (target, entry) in [("vs_5_0\0", "vertex_main\0"), ("ps_5_0\0", "pixel_main\0")] {
+            if let Err(msg) = compile_shader(target, entry, &hlsl) {
+                if !report_shader_compiler_log(live_registry, &source_map, &msg, 0) {
+                    crate::error!("{}", msg);
+                }
+                valid = false;
+            }
+        }
+        valid
+    }
 
     pub fn share_texture_for_presentable_image(
         &mut self,
@@ -517,6 +536,7 @@ fn texture_pixel_to_dx11_pixel(pix:&TexturePixel)->DXGI_FORMAT{
         TexturePixel::Ru8  => DXGI_FORMAT_R8_UNORM,
         TexturePixel::RGu8  => DXGI_FORMAT_R8G8_UNORM,
         TexturePixel::Rf32  => DXGI_FORMAT_R32_FLOAT,
+        TexturePixel::Ru16  => DXGI_FORMAT_R16_UNORM,
         TexturePixel::D32 => DXGI_FORMAT_D32_FLOAT,
     }   
 }
@@ -803,9 +823,32 @@ impl CxTexture {
         &mut self,
         d3d11_cx: &D3d11Cx,
     ) {
-        // TODO maybe we can update the data instead of making a new texture?
+        // a full update makes a new texture, a partial one writes into the one we have
         if self.alloc_vec(){}
-        if self.check_updated(){
+        let updated = self.check_updated();
+        if let (TextureUpdated::Partial(rect), Some(texture)) = (updated, &self.os.texture){
+            let (data, bytes_per_pixel, row_length) = self.format.vec_pixel_data().unwrap();
+            let resource: ID3D11Resource = texture.cast().unwrap();
+            for rect in self.format.vec_upload_rects(rect){
+                let dest_box = D3D11_BOX {
+                    left: rect.x as u32,
+                    top: rect.y as u32,
+                    front: 0,
+                    right: (rect.x + rect.width) as u32,
+                    bottom: (rect.y + rect.height) as u32,
+                    back: 1,
+                };
+                unsafe {d3d11_cx.context.UpdateSubresource(
+                    &resource,
+                    0,
+                    Some(&dest_box as *const _),
+                    data.add((rect.y * row_length + rect.x) * bytes_per_pixel) as *const _,
+                    (row_length * bytes_per_pixel) as u32,
+                    0
+                )};
+            }
+        }
+        else if updated != TextureUpdated::Empty{
             fn get_descs(format: DXGI_FORMAT, width: usize, height: usize, bpp: usize, data: *const std::ffi::c_void)->(D3D11_SUBRESOURCE_DATA,D3D11_TEXTURE2D_DESC) {
                 let sub_data = D3D11_SUBRESOURCE_DATA {
                     pSysMem: data,
@@ -847,6 +890,13 @@ impl CxTexture {
                 TextureFormat::VecRf32{width, height, data}=>{
                     get_descs(DXGI_FORMAT_R32_FLOAT, *width, *height, 4, data.as_ptr() as *const _)
                 }
+                TextureFormat::VecRu16{width, height, data}=>{
+                    get_descs(DXGI_FORMAT_R16_UNORM, *width, *height, 2, data.as_ptr() as *const _)
+                }
+                TextureFormat::VecNV12{width, height, data} |
+                TextureFormat::VecI420{width, height, data}=>{
+                    get_descs(DXGI_FORMAT_R8_UNORM, *width, *height + *height / 2, 1, data.as_ptr() as *const _)
+                }
                 _=>panic!()
             };
                                         
@@ -1111,37 +1161,39 @@ pub struct CxOsDrawShader {
     pub input_layout: ID3D11InputLayout
 }
 
+/// Compiles one entry point of the hlsl, the error is the log of the compiler
+fn compile_shader(target: &str, entry: &str, shader: &str) -> Result<ID3DBlob, String> {
+    unsafe {
+        let shader_bytes = shader.as_bytes();
+        let mut blob = None;
+        let mut errors = None;
+        if D3DCompile(
+            shader_bytes.as_ptr() as *const _,
+            shader_bytes.len(),
+            PCSTR("makepad_shader\0".as_ptr()), // sourcename
+            None, // defines
+            None, // include
+            PCSTR(entry.as_ptr()), // entry point
+            PCSTR(target.as_ptr()), // target
+            0, // flags1
+            0, // flags2
+            &mut blob,
+            Some(&mut errors)
+        ).is_ok() {
+            return Ok(blob.unwrap());
+        };
+        let error = errors.unwrap();
+        let pointer = error.GetBufferPointer();
+        let size = error.GetBufferSize();
+        let slice = std::slice::from_raw_parts(pointer as *const u8, size as usize);
+        return Err(String::from_utf8_lossy(slice).into_owned());
+    }
+}
+
 impl CxOsDrawShader {
     
-    fn new(d3d11_cx: &D3d11Cx, hlsl: String, mapping: &CxDrawShaderMapping) -> Option<Self> {
+    fn new(d3d11_cx: &D3d11Cx, hlsl: String, source_map: &ShaderSourceMap, mapping: &CxDrawShaderMapping, live_registry: &LiveRegistry) -> Option<Self> {
         
-        fn compile_shader(target: &str, entry: &str, shader: &str) -> Result<ID3DBlob, String> {
-            unsafe {
-                let shader_bytes = shader.as_bytes();
-                let mut blob = None;
-                let mut errors = None;
-                if D3DCompile(
-                    shader_bytes.as_ptr() as *const _,
-                    shader_bytes.len(),
-                    PCSTR("makepad_shader\0".as_ptr()), // sourcename
-                    None, // defines
-                    None, // include
-                    PCSTR(entry.as_ptr()), // entry point
-                    PCSTR(target.as_ptr()), // target
-                    0, // flags1
-                    0, // flags2
-                    &mut blob,
-                    Some(&mut errors)
-                ).is_ok() {
-                    return Ok(blob.unwrap());
-                };
-                let error = errors.unwrap();
-                let pointer = error.GetBufferPointer();
-                let size = error.GetBufferSize();
-                let slice = std::slice::from_raw_parts(pointer as *const u8, size as usize);
-                return Err(String::from_utf8_lossy(slice).into_owned());
-            }
-        }
         fn split_source(src: &str) -> String {
             let mut r = String::new();
             let split = src.split("\n");
@@ -1167,6 +1219,7 @@ impl CxOsDrawShader {
         
         let vs_blob = match compile_shader("vs_5_0\0", "vertex_main\0", &hlsl) {
             Err(msg) => {
+                report_shader_compiler_log(live_registry, source_map, &msg, 0);
                 println!("Cannot compile vertexshader\n{}\n{}", msg, split_source(&hlsl));
                 return None
             },
@@ -1177,6 +1230,7 @@ impl CxOsDrawShader {
         
         let ps_blob = match compile_shader("ps_5_0\0", "pixel_main\0", &hlsl) {
             Err(msg) => {
+                report_shader_compiler_log(live_registry, source_map, &msg, 0);
                 println!("Cannot compile pixelshader\n{}\n{}", msg, split_source(&hlsl));
                 return None
             },
@@ -6,7 +6,7 @@ pub struct OsWebSocket{
 }
 
 impl OsWebSocket{
-    pub fn send_message(&mut self, _message:WebSocketMessage)->Result<(),()>{
+    pub fn send_message(&mut self, _message:WebSocketMessage)->Result<(),String>{
         todo!("Websockets not implemented yet on windows, we'll get there");
     }
                         
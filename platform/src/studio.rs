@@ -1,16 +1,20 @@
 use crate::makepad_micro_serde::*;
 use crate::log::LogLevel;
 
-// communication enums for studio
+// communication enums for studio. the studio and a running app can be built from different
+// versions of this file, so these use versioned bin: add fields freely but keep their names,
+// or give a renamed field its old id with #[bin_id]
 
-#[derive(SerBin, DeBin, Debug)]
+#[derive(SerBin, DeBin, Debug, Default)]
+#[bin_version = 1]
 pub struct ProfileSampleEvent{
     pub event_u32: u32,
     pub start: f64,
     pub end: f64,
 }
 
-#[derive(SerBin, DeBin, Debug)]
+#[derive(SerBin, DeBin, Debug, Default)]
+#[bin_version = 1]
 pub struct StudioLogItem{
     pub file_name:String,
     pub line_start: u32,
@@ -22,6 +26,7 @@ pub struct StudioLogItem{
 }
 
 #[derive(SerBin, DeBin, Debug)]
+#[bin_version = 1]
 pub enum AppToStudio{
     LogItem(StudioLogItem),
     ProfileEvent(ProfileSampleEvent)
@@ -31,6 +36,7 @@ pub enum AppToStudio{
 pub struct AppToStudioVec(pub Vec<AppToStudio>);
 
 #[derive(SerBin, DeBin)]
+#[bin_version = 1]
 pub enum StudioToApp{
     LiveChange{
         file_name: String,
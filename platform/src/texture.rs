@@ -1,7 +1,9 @@
 use {
     crate::{
+        makepad_live_id::LiveId,
         id_pool::*,
         cx::Cx,
+        pass::PassId,
         os::CxOsTexture,
     },
     std::rc::Rc,
@@ -11,7 +13,7 @@ use {
 #[derive(Clone)]
 pub struct Texture(Rc<PoolId>);
 
-#[derive(Clone, Debug, PartialEq, Copy)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Copy)]
 pub struct TextureId(pub (crate) usize, u64);
 
 impl Texture {
@@ -66,6 +68,30 @@ impl TextureSize{
 }
 
 
+/// An area of a texture in pixels, measured from the top left corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextureRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum TextureReadbackSource {
+    Texture(TextureId),
+    Pass(PassId),
+}
+
+/// A pending readback, the backend fills in the response after the next repaint.
+#[derive(Clone, Debug)]
+pub(crate) struct CxTextureReadback {
+    pub request_id: LiveId,
+    pub source: TextureReadbackSource,
+    pub rect: Option<TextureRect>,
+    pub response: Option<Result<crate::event::TextureReadbackData, String>>,
+}
+
 #[derive(Clone, Debug)]
 pub enum TextureFormat {
     Unknown,
@@ -75,6 +101,20 @@ pub enum TextureFormat {
     VecRu8{width:usize, height:usize, data:Vec<u8>, unpack_row_length:Option<usize>},
     VecRGu8{width:usize, height:usize, data:Vec<u8>, unpack_row_length:Option<usize>},
     VecRf32{width:usize, height:usize, data:Vec<f32>},
+    /// Single channel 16 bit values, the shader reads them as 0.0 to 1.0. On OpenGL ES
+    /// this needs the EXT_texture_norm16 extension, the web doesn't support it.
+    VecRu16{width:usize, height:usize, data:Vec<u16>},
+    /// A luma plane of `width*height` bytes followed by interleaved u,v bytes at half the
+    /// resolution. It is stored as a single channel texture with the chroma rows below the luma,
+    /// `Yuv::sample_nv12` in the shader std turns it into rgb. Width and height have to be even.
+    /// Not supported on the web.
+    VecNV12{width:usize, height:usize, data:Vec<u8>},
+    /// Like `VecNV12` but with separate u and v planes, sampled with `Yuv::sample_i420`.
+    VecI420{width:usize, height:usize, data:Vec<u8>},
+    /// A cube map for a `textureCube` in a shader, the 6 faces of `size*size` pixels follow each
+    /// other in +x -x +y -y +z -z order. Changes are always uploaded whole, `update_rect`
+    /// doesn't take cube maps. Only OpenGL binds them so far.
+    VecCubeBGRAu8_32{size:usize, data:Vec<u32>},
     DepthD32{size:TextureSize},
     RenderBGRAu8{size:TextureSize},
     RenderRGBAf16{size:TextureSize},
@@ -84,6 +124,34 @@ pub enum TextureFormat {
     VideoRGB,
 }
 
+/// The part of a vec texture that changed since it was uploaded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextureUpdated {
+    Empty,
+    Partial(TextureRect),
+    Full,
+}
+
+impl TextureUpdated {
+    // rects marked in the same frame are merged into the rect around them
+    fn add_rect(self, rect: TextureRect) -> Self {
+        match self {
+            Self::Empty => Self::Partial(rect),
+            Self::Partial(old) => {
+                let x = old.x.min(rect.x);
+                let y = old.y.min(rect.y);
+                Self::Partial(TextureRect {
+                    x,
+                    y,
+                    width: (old.x + old.width).max(rect.x + rect.width) - x,
+                    height: (old.y + old.height).max(rect.y + rect.height) - y,
+                })
+            }
+            Self::Full => Self::Full,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct TextureAlloc{
     pub category: TextureCategory,
@@ -95,7 +163,7 @@ pub(crate) struct TextureAlloc{
 #[allow(unused)]    
 #[derive(Clone, Debug)]
 pub enum TextureCategory{
-    Vec{updated:bool},
+    Vec{updated:TextureUpdated},
     Render{initial:bool},
     DepthBuffer{initial:bool},
     Shared{initial:bool},
@@ -123,6 +191,7 @@ pub(crate) enum TexturePixel{
     Ru8,
     RGu8,
     Rf32,
+    Ru16,
     D32,
     #[cfg(any(target_os = "android", target_os = "linux"))]
     VideoRGB
@@ -132,22 +201,47 @@ impl CxTexture{
     pub(crate) fn set_updated(&mut self, up:bool){
         if let Some(alloc) = &mut self.alloc{
             if let TextureCategory::Vec{updated} = &mut alloc.category{
-                *updated = up
+                *updated = if up {TextureUpdated::Full} else {TextureUpdated::Empty}
+            }
+        }
+    }
+    
+    pub(crate) fn set_updated_rect(&mut self, rect:TextureRect){
+        if let Some(alloc) = &mut self.alloc{
+            if let TextureCategory::Vec{updated} = &mut alloc.category{
+                *updated = updated.add_rect(rect)
             }
         }
     }
     
-    pub(crate) fn check_updated(&mut self)->bool{
+    pub(crate) fn check_updated(&mut self)->TextureUpdated{
         if let Some(alloc) = &mut self.alloc{
             if let TextureCategory::Vec{updated} = &mut alloc.category{
                 let u = *updated;
-                *updated = false;
-                if u{ // check our buffer sizes
+                *updated = TextureUpdated::Empty;
+                if u != TextureUpdated::Empty{ // check our buffer sizes
                     match &self.format{
-                        TextureFormat::VecBGRAu8_32{width, height, data}=>{
-                            if width * height != data.len(){
-                                error!("Texture buffer size incorrect {}*{} != {}", width, height, data.len());
-                                return false
+                        TextureFormat::VecBGRAu8_32{width, height, data} if width * height != data.len()=>{
+                            error!("Texture buffer size incorrect {}*{} != {}", width, height, data.len());
+                            return TextureUpdated::Empty
+                        }
+                        TextureFormat::VecRu16{width, height, data} if width * height != data.len()=>{
+                            error!("Texture buffer size incorrect {}*{} != {}", width, height, data.len());
+                            return TextureUpdated::Empty
+                        }
+                        TextureFormat::VecCubeBGRAu8_32{size, data} if size * size * 6 != data.len()=>{
+                            error!("Cube texture buffer size incorrect {}*{}*6 != {}", size, size, data.len());
+                            return TextureUpdated::Empty
+                        }
+                        TextureFormat::VecNV12{width, height, data} |
+                        TextureFormat::VecI420{width, height, data}=>{
+                            if width % 2 != 0 || height % 2 != 0{
+                                error!("Planar yuv texture size has to be even {}x{}", width, height);
+                                return TextureUpdated::Empty
+                            }
+                            if width * height * 3 / 2 != data.len(){
+                                error!("Texture buffer size incorrect {}*{}*3/2 != {}", width, height, data.len());
+                                return TextureUpdated::Empty
                             }
                         }
                         _=>()
@@ -156,7 +250,7 @@ impl CxTexture{
                 return u
             }
         }
-        false
+        TextureUpdated::Empty
     }
     
     pub fn set_initial(&mut self, init:bool){
@@ -259,10 +353,17 @@ impl TextureFormat{
             Self::VecRu8{..}=>true,
             Self::VecRGu8{..}=>true,
             Self::VecRf32{..}=>true,
+            Self::VecRu16{..}=>true,
+            Self::VecNV12{..}=>true,
+            Self::VecI420{..}=>true,
             _=>false
         }
     }
     
+    pub fn is_cube(&self)->bool{
+        matches!(self, Self::VecCubeBGRAu8_32{..})
+    }
+    
     pub fn is_render(&self)->bool{
         match self{
             Self::RenderBGRAu8{..}=>true,
@@ -295,47 +396,114 @@ impl TextureFormat{
             Self::VecRu8{width, height, ..}=>Some((*width,*height)),
             Self::VecRGu8{width, height, ..}=>Some((*width,*height)),
             Self::VecRf32{width, height,..}=>Some((*width,*height)),
+            Self::VecRu16{width, height,..}=>Some((*width,*height)),
+            Self::VecNV12{width, height,..}=>Some((*width,*height)),
+            Self::VecI420{width, height,..}=>Some((*width,*height)),
+            _=>None
+        }
+    }
+    
+    /// The start of the data of a vec texture with the bytes per pixel and the pixels per row.
+    pub(crate) fn vec_pixel_data(&self)->Option<(*const u8, usize, usize)>{
+        match self{
+            Self::VecBGRAu8_32{width, data, ..}=>Some((data.as_ptr() as *const u8, 4, *width)),
+            Self::VecMipBGRAu8_32{width, data, ..}=>Some((data.as_ptr() as *const u8, 4, *width)),
+            Self::VecRGBAf32{width, data, ..}=>Some((data.as_ptr() as *const u8, 16, *width)),
+            Self::VecRu8{width, data, unpack_row_length, ..}=>Some((data.as_ptr(), 1, unpack_row_length.unwrap_or(*width))),
+            Self::VecRGu8{width, data, unpack_row_length, ..}=>Some((data.as_ptr(), 2, unpack_row_length.unwrap_or(*width))),
+            Self::VecRf32{width, data, ..}=>Some((data.as_ptr() as *const u8, 4, *width)),
+            Self::VecRu16{width, data, ..}=>Some((data.as_ptr() as *const u8, 2, *width)),
+            Self::VecNV12{width, data, ..}=>Some((data.as_ptr(), 1, *width)),
+            Self::VecI420{width, data, ..}=>Some((data.as_ptr(), 1, *width)),
             _=>None
         }
     }
     
+    /// The parts of the allocated texture that change with `rect` of the image. Planar yuv
+    /// formats keep their chroma rows below the luma so they need more than one upload.
+    pub(crate) fn vec_upload_rects(&self, rect:TextureRect)->Vec<TextureRect>{
+        // a u,v pair belongs to a 2x2 block of luma pixels
+        let cx = rect.x / 2;
+        let cy = rect.y / 2;
+        let cx_end = (rect.x + rect.width).div_ceil(2);
+        let cy_end = (rect.y + rect.height).div_ceil(2);
+        match self{
+            Self::VecNV12{height, ..}=>vec![
+                rect,
+                TextureRect{x: cx * 2, y: height + cy, width: (cx_end - cx) * 2, height: cy_end - cy}
+            ],
+            Self::VecI420{width, height, ..}=>{
+                // the u and v planes have rows of half the width, so two of them share a texture row
+                let chroma_width = width / 2;
+                let mut rects = vec![rect];
+                for plane in 0..2{
+                    let plane_start = width * height + plane * chroma_width * (height / 2);
+                    let start = plane_start + cy * chroma_width;
+                    let end = plane_start + cy_end * chroma_width;
+                    let y = start / width;
+                    rects.push(TextureRect{x: 0, y, width: *width, height: end.div_ceil(*width) - y});
+                }
+                rects
+            }
+            _=>vec![rect]
+        }
+    }
+    
     pub(crate) fn as_vec_alloc(&self)->Option<TextureAlloc>{
         match self{
             Self::VecBGRAu8_32{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::BGRAu8,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             Self::VecMipBGRAu8_32{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::BGRAu8,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             Self::VecRGBAf32{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::RGBAf32,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             Self::VecRu8{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::Ru8,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             Self::VecRGu8{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::RGu8,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             Self::VecRf32{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::Rf32,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
+            }),
+            Self::VecRu16{width,height,..}=>Some(TextureAlloc{
+                width:*width,
+                height:*height,
+                pixel:TexturePixel::Ru16,
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
+            }),
+            Self::VecNV12{width,height,..} | Self::VecI420{width,height,..}=>Some(TextureAlloc{
+                width:*width,
+                height:*height + *height / 2,
+                pixel:TexturePixel::Ru8,
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
+            }),
+            Self::VecCubeBGRAu8_32{size,..}=>Some(TextureAlloc{
+                width:*size,
+                height:*size * 6,
+                pixel:TexturePixel::BGRAu8,
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             _=>None
         }
@@ -445,7 +613,7 @@ impl Texture {
     pub fn swap_vec_u32(&self, cx: &mut Cx, image: &mut Vec<u32>) {
         let cxtexture = &mut cx.textures[self.texture_id()];
         match &mut cxtexture.format{
-            TextureFormat::VecBGRAu8_32{data,..} => {
+            TextureFormat::VecBGRAu8_32{data,..} | TextureFormat::VecCubeBGRAu8_32{data,..} => {
                 std::mem::swap(data, image);
                 cxtexture.set_updated(true);
             }
@@ -458,7 +626,8 @@ impl Texture {
     pub fn swap_vec_u8(&self, cx: &mut Cx, image: &mut Vec<u8>) {
         let cxtexture = &mut cx.textures[self.texture_id()];
         match &mut cxtexture.format{
-            TextureFormat::VecRu8{data,..} | TextureFormat::VecRGu8 { data, ..} => {
+            TextureFormat::VecRu8{data,..} | TextureFormat::VecRGu8 { data, ..} |
+            TextureFormat::VecNV12{data,..} | TextureFormat::VecI420{data,..} => {
                 std::mem::swap(data, image);
                 cxtexture.set_updated(true);
             },
@@ -484,6 +653,54 @@ impl Texture {
             }
         }
     }
+    
+    pub fn swap_vec_u16(&self, cx: &mut Cx, image: &mut Vec<u16>) {
+        let cxtexture = &mut cx.textures[self.texture_id()];
+        match &mut cxtexture.format{
+            TextureFormat::VecRu16{data,..} => {
+                std::mem::swap(data, image);
+                cxtexture.set_updated(true);
+            }
+            _=>{
+                panic!("Not the correct texture desc for u16 image buffer")
+            }
+        }
+    }
+    
+    /// Marks `rect` of a vec texture as changed after its data was edited through `get_format`,
+    /// only that part is uploaded on the next repaint instead of the whole texture.
+    /// The web has no partial uploads, there the whole texture is sent again.
+    pub fn update_rect(&self, cx: &mut Cx, rect: TextureRect) {
+        let cxtexture = &mut cx.textures[self.texture_id()];
+        let (width, height) = match cxtexture.format.vec_width_height(){
+            Some(size)=>size,
+            None=>panic!("Not a vec texture for update_rect")
+        };
+        let x = rect.x.min(width);
+        let y = rect.y.min(height);
+        let rect = TextureRect{
+            x,
+            y,
+            width: (rect.x + rect.width).min(width) - x,
+            height: (rect.y + rect.height).min(height) - y,
+        };
+        if rect.width > 0 && rect.height > 0{
+            cxtexture.set_updated_rect(rect);
+        }
+    }
+    
+    /// Copies the pixels of the texture, or the part of it in `rect`, back to the cpu. They arrive
+    /// as an `Event::TextureReadback` with the same `request_id` after the next repaint, the event
+    /// loop repaints for it even when nothing else changed.
+    /// BGRA textures come back as `BGRAu8`, float render targets as `RGBAf32`.
+    pub fn read_pixels(&self, cx: &mut Cx, request_id: LiveId, rect: Option<TextureRect>) {
+        cx.texture_readbacks.push(CxTextureReadback {
+            request_id,
+            source: TextureReadbackSource::Texture(self.texture_id()),
+            rect,
+            response: None,
+        });
+    }
 }
 
 #[derive(Default)]
@@ -492,3 +709,57 @@ pub struct CxTexture {
     pub (crate) alloc: Option<TextureAlloc>,
     pub os: CxOsTexture
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn rect(x: usize, y: usize, width: usize, height: usize) -> TextureRect {
+        TextureRect {x, y, width, height}
+    }
+    
+    #[test]
+    fn add_rect_merges_into_the_rect_around() {
+        assert_eq!(TextureUpdated::Empty.add_rect(rect(1, 2, 3, 4)), TextureUpdated::Partial(rect(1, 2, 3, 4)));
+        assert_eq!(
+            TextureUpdated::Partial(rect(1, 2, 3, 4)).add_rect(rect(5, 0, 2, 1)),
+            TextureUpdated::Partial(rect(1, 0, 6, 6))
+        );
+        assert_eq!(TextureUpdated::Full.add_rect(rect(1, 2, 3, 4)), TextureUpdated::Full);
+    }
+    
+    #[test]
+    fn nv12_upload_rects() {
+        let format = TextureFormat::VecNV12 {width: 8, height: 4, data: vec![0; 48]};
+        // the chroma covers the 2x2 blocks the rect touches, the rows are below the luma
+        assert_eq!(format.vec_upload_rects(rect(1, 1, 2, 2)), vec![rect(1, 1, 2, 2), rect(0, 4, 4, 2)]);
+        assert_eq!(format.vec_upload_rects(rect(4, 2, 4, 2)), vec![rect(4, 2, 4, 2), rect(4, 5, 4, 1)]);
+    }
+    
+    #[test]
+    fn i420_upload_rects() {
+        // the 4x2 u and v planes are one texture row each
+        let format = TextureFormat::VecI420 {width: 8, height: 4, data: vec![0; 48]};
+        assert_eq!(format.vec_upload_rects(rect(1, 1, 2, 2)), vec![rect(1, 1, 2, 2), rect(0, 4, 8, 1), rect(0, 5, 8, 1)]);
+        // the 4x4 planes take two rows each, every row holds two chroma rows
+        let format = TextureFormat::VecI420 {width: 8, height: 8, data: vec![0; 96]};
+        assert_eq!(format.vec_upload_rects(rect(0, 2, 8, 2)), vec![rect(0, 2, 8, 2), rect(0, 8, 8, 1), rect(0, 10, 8, 1)]);
+        assert_eq!(format.vec_upload_rects(rect(0, 2, 8, 4)), vec![rect(0, 2, 8, 4), rect(0, 8, 8, 2), rect(0, 10, 8, 2)]);
+    }
+    
+    #[test]
+    fn update_rect_is_clipped_and_merged() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        let texture = Texture::new(&mut cx);
+        texture.set_format(&mut cx, TextureFormat::VecBGRAu8_32 {width: 8, height: 4, data: vec![0; 32]});
+        cx.textures[texture.texture_id()].alloc_vec();
+        assert_eq!(cx.textures[texture.texture_id()].check_updated(), TextureUpdated::Full);
+        
+        texture.update_rect(&mut cx, rect(6, 1, 4, 1));
+        texture.update_rect(&mut cx, rect(1, 2, 1, 1));
+        assert_eq!(cx.textures[texture.texture_id()].check_updated(), TextureUpdated::Partial(rect(1, 1, 7, 2)));
+        
+        texture.update_rect(&mut cx, rect(8, 0, 2, 2));
+        assert_eq!(cx.textures[texture.texture_id()].check_updated(), TextureUpdated::Empty);
+    }
+}
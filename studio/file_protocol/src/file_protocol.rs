@@ -39,7 +39,16 @@ pub enum FileRequest {
     /// Requests the collab server to apply the given delta to the given revision of the file with
     /// the given id.
     SaveFile(String, String, u64),
-
+    /// Requests the collab server to search every text file in its file tree. Matches don't
+    /// span lines. The results arrive as `SearchResults` notifications with the given id, a new
+    /// search cancels the previous one.
+    Search {
+        id: u64,
+        pattern: String,
+        case_sensitive: bool,
+        whole_word: bool,
+        regex: bool,
+    },
 }
 
 /// A type for representing either a response or a notification from the collab server.
@@ -62,6 +71,22 @@ pub enum FileResponse {
     /// The result of requesting the collab server to apply a delta to a revision of the file with
     /// the given id.
     SaveFile(Result<(String,String,String, u64), FileError>),
+    /// The result of requesting the collab server to search its file tree. This only says whether
+    /// the search with the given id was started.
+    Search(Result<u64, FileError>),
+}
+
+/// A type for representing a single match of a search.
+#[derive(Clone, Debug, SerBin, DeBin)]
+pub struct SearchResult {
+    /// The path of the file, relative to the root of the file tree.
+    pub path: String,
+    pub line_index: usize,
+    /// The byte range of the match in the line.
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// The text of the whole line.
+    pub line: String,
 }
 
 /// A type for representing data about a file tree.
@@ -97,6 +122,12 @@ pub struct DirectoryEntry {
 #[derive(Clone, Debug, SerBin, DeBin)]
 pub enum FileNotification {
     FileChangedOnDisk,
+    /// The next batch of results for the search with the given id. `done` is set on the last one.
+    SearchResults {
+        id: u64,
+        results: Vec<SearchResult>,
+        done: bool,
+    },
     // Notifies the client that another client applied the given delta to the file with the given
     // id. This is only sent for files for which the client is a participant.
    // DeltaWasApplied(TextFileId),
@@ -8,14 +8,17 @@ use {
             FileNotification,
             FileRequest,
             FileResponse,
+            SearchResult,
         },
+        makepad_regex::{Regex, SearchOptions},
     },
     std::{
         cmp::Ordering,
         fmt,
         fs,
         path::{Path, PathBuf},
-        sync::{Arc, RwLock},
+        sync::{Arc, RwLock, atomic::{AtomicU64, Ordering as AtomicOrdering}},
+        thread,
     },
 };
 
@@ -47,7 +50,8 @@ impl FileServer {
         FileServerConnection {
             _connection_id:connection_id,
             shared: self.shared.clone(),
-            _notification_sender: notification_sender
+            notification_sender,
+            current_search: Arc::new(AtomicU64::new(0)),
         }
     }
 }
@@ -59,7 +63,9 @@ pub struct FileServerConnection {
     // State is shared between every connection.
     shared: Arc<RwLock<Shared >>,
     // Used to send notifications for this connection.
-    _notification_sender: Box<dyn NotificationSender>,
+    notification_sender: Box<dyn NotificationSender>,
+    // The id of the search that is running, a search thread stops once this changes.
+    current_search: Arc<AtomicU64>,
 }
 
 impl FileServerConnection {
@@ -74,6 +80,9 @@ impl FileServerConnection {
             FileRequest::LoadFileTree {with_data} => FileResponse::LoadFileTree(self.load_file_tree(with_data)),
             FileRequest::OpenFile(path,id) => FileResponse::OpenFile(self.open_file(path, id)),
             FileRequest::SaveFile(path, delta, id) => FileResponse::SaveFile(self.save_file(path, delta, id)),
+            FileRequest::Search {id, pattern, case_sensitive, whole_word, regex} => FileResponse::Search(
+                self.search(id, &pattern, SearchOptions {case_sensitive, whole_word, regex})
+            ),
         }
     }
     
@@ -202,6 +211,127 @@ impl FileServerConnection {
         
         Ok((child_path, old_content, new_content, id))
     }
+    
+    // Handles a `Search` request. The search runs on its own thread so it doesn't hold up the
+    // requests behind it, and sends its results back in batches as notifications.
+    fn search(&self, id: u64, pattern: &str, options: SearchOptions) -> Result<u64, FileError> {
+        // stop collecting once we have this many, nobody scrolls through more
+        const MAX_RESULTS: usize = 10000;
+        // send the results in batches of at least this many
+        const BATCH_SIZE: usize = 256;
+        
+        struct Search {
+            id: u64,
+            regex: Regex,
+            root_path: PathBuf,
+            current_search: Arc<AtomicU64>,
+            notification_sender: Box<dyn NotificationSender>,
+            batch: Vec<SearchResult>,
+            result_count: usize,
+        }
+        
+        impl Search {
+            fn is_cancelled(&self) -> bool {
+                self.current_search.load(AtomicOrdering::Relaxed) != self.id
+            }
+            
+            fn is_done(&self) -> bool {
+                self.result_count >= MAX_RESULTS || self.is_cancelled()
+            }
+            
+            fn flush(&mut self, done: bool) {
+                self.notification_sender.send_notification(FileNotification::SearchResults {
+                    id: self.id,
+                    results: std::mem::take(&mut self.batch),
+                    done,
+                });
+            }
+            
+            fn search_file(&mut self, path: &Path) {
+                let Ok(bytes) = fs::read(path) else {
+                    return
+                };
+                // skip binary files
+                if bytes.contains(&0) {
+                    return;
+                }
+                let text = String::from_utf8_lossy(&bytes);
+                let child_path = path.strip_prefix(&self.root_path).unwrap_or(path);
+                let child_path = child_path.to_string_lossy().replace('\\', "/");
+                for (line_index, line) in text.lines().enumerate() {
+                    for found in self.regex.find_iter(line) {
+                        if found.is_empty() {
+                            continue;
+                        }
+                        self.batch.push(SearchResult {
+                            path: child_path.clone(),
+                            line_index,
+                            start_byte: found.start,
+                            end_byte: found.end,
+                            line: line.to_string(),
+                        });
+                        self.result_count += 1;
+                        if self.result_count >= MAX_RESULTS {
+                            return
+                        }
+                    }
+                }
+                if self.batch.len() >= BATCH_SIZE {
+                    self.flush(false);
+                }
+            }
+            
+            fn search_directory(&mut self, path: &Path) {
+                let Ok(entries) = fs::read_dir(path) else {
+                    return
+                };
+                let mut entries: Vec<_> = entries.filter_map( | entry | entry.ok()).collect();
+                entries.sort_by_key( | entry | entry.file_name());
+                for entry in entries {
+                    if self.is_done() {
+                        return
+                    }
+                    let entry_path = entry.path();
+                    let Ok(name) = entry.file_name().into_string() else {
+                        continue
+                    };
+                    // skip the same entries as load_file_tree
+                    if entry_path.is_dir() && name == "target" || name.starts_with('.') {
+                        continue;
+                    }
+                    if entry_path.is_dir() {
+                        self.search_directory(&entry_path);
+                    }
+                    else if entry_path.is_file() {
+                        self.search_file(&entry_path);
+                    }
+                }
+            }
+        }
+        
+        let regex = Regex::search(pattern, options).map_err(
+            | error | FileError::Unknown(error.to_string())
+        ) ?;
+        // this also cancels the search that is still running
+        self.current_search.store(id, AtomicOrdering::Relaxed);
+        let mut search = Search {
+            id,
+            regex,
+            root_path: self.shared.read().unwrap().root_path.clone(),
+            current_search: self.current_search.clone(),
+            notification_sender: self.notification_sender.clone(),
+            batch: Vec::new(),
+            result_count: 0,
+        };
+        thread::spawn(move || {
+            let root_path = search.root_path.clone();
+            search.search_directory(&root_path);
+            if !search.is_cancelled() {
+                search.flush(true);
+            }
+        });
+        Ok(id)
+    }
 }
 
 /// A trait for sending notifications over a connection.
@@ -185,6 +185,10 @@ impl MatchEvent for App{
                 self.data.build_manager.clear_log(cx, &dock, &mut self.data.file_system);
                 log_list.redraw(cx);
             }
+            FileSystemAction::SearchResults => {
+                self.data.build_manager.show_search_results(&self.data.file_system.search_results);
+                log_list.redraw(cx);
+            }
             FileSystemAction::None=>()
         }
                 
@@ -209,7 +213,10 @@ impl MatchEvent for App{
                     // lets write the file
                     self.data.file_system.request_save_file(action.path.get(0))
                 }
-                CodeEditorAction::None=>{}
+                CodeEditorAction::SearchWorkspace(pattern, options) => {
+                    self.data.file_system.search_workspace(&pattern, options);
+                }
+                CodeEditorAction::Find(_) | CodeEditorAction::Paste(_) | CodeEditorAction::None=>{}
             }
             
             match action.cast(){
@@ -223,8 +230,12 @@ impl MatchEvent for App{
                     self.data.file_system.ensure_unique_tab_names(cx, &dock);
                 }
                 DockAction::ShouldTabStartDrag(tab_id)=>{
+                    // the path lets other applications take the file, the internal id moves the tab within the dock
+                    let path = self.data.file_system.tab_id_to_file_node_id.get(&tab_id)
+                        .map(|file_id| self.data.file_system.file_node_local_path(*file_id))
+                        .unwrap_or_default();
                     dock.tab_start_drag(cx, tab_id, DragItem::FilePath {
-                        path: "".to_string(), //String::from("file://") + &*path.into_unix_string().to_string_lossy(),
+                        path,
                         internal_id: Some(tab_id)
                     });
                 }
@@ -292,7 +303,7 @@ impl MatchEvent for App{
             self.handle_action(cx, action);
         }
         if let Some(file_id) = file_tree.should_file_start_drag(&actions) {
-            let path = self.data.file_system.file_node_path(file_id);
+            let path = self.data.file_system.file_node_local_path(file_id);
             file_tree.file_start_drag(cx, file_id, DragItem::FilePath {
                 path,
                 internal_id: None
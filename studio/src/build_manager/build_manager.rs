@@ -18,6 +18,7 @@ use {
         run_view::*,
         app::AppAction,
         makepad_shell::*,
+        makepad_file_protocol::SearchResult,
     },
     makepad_code_editor::{text, decoration::{Decoration, DecorationType}},
     makepad_http::server::*,
@@ -191,6 +192,20 @@ impl BuildManager {
         self.profile.clear();
     }
     
+    /// Replaces the results of the last workspace search in the log, clicking one jumps to it
+    pub fn show_search_results(&mut self, results: &[SearchResult]) {
+        self.log.retain( | (id, _) | *id != live_id!(search));
+        for result in results {
+            self.log.push((live_id!(search), LogItem::Location(LogItemLocation {
+                level: LogLevel::Log,
+                file_name: result.path.clone(),
+                start: text::Position {line_index: result.line_index, byte_index: result.start_byte},
+                end: text::Position {line_index: result.line_index, byte_index: result.end_byte},
+                message: result.line.trim().to_string(),
+            })));
+        }
+    }
+    
     pub fn start_recompile_timer(&mut self, cx: &mut Cx, ui: &WidgetRef) {
         cx.stop_timer(self.recompile_timer);
         self.recompile_timer = cx.start_timeout(self.recompile_timeout);
@@ -1,8 +1,8 @@
 use {
     std::collections::{HashMap, hash_map},
-    std::path::Path,
+    std::path::{Path, PathBuf},
     crate::{
-        makepad_code_editor::{Document, decoration::{Decoration, DecorationSet}, Session},
+        makepad_code_editor::{Document, decoration::{Decoration, DecorationSet}, language, makepad_regex::SearchOptions, Session},
         makepad_platform::makepad_live_compiler::LiveFileChange,
         makepad_widgets::*,
         makepad_widgets::file_tree::*,
@@ -12,8 +12,10 @@ use {
             FileError,
             FileResponse,
             FileClientMessage,
+            FileNotification,
             FileNodeData,
             FileTreeData,
+            SearchResult,
         },
     },
 };
@@ -22,11 +24,15 @@ use {
 pub struct FileSystem {
     pub file_client: FileClient,
     pub root_path: String,
+    /// The directory the file server serves, on this machine
+    pub local_root: PathBuf,
     pub file_nodes: LiveIdMap<FileNodeId, FileNode>,
     pub path_to_file_node_id: HashMap<String, FileNodeId>,
     pub tab_id_to_file_node_id: HashMap<LiveId, FileNodeId>,
     pub tab_id_to_session: HashMap<LiveId, Session>,
-    pub open_documents: HashMap<FileNodeId, OpenDoc>
+    pub open_documents: HashMap<FileNodeId, OpenDoc>,
+    pub search_results: Vec<SearchResult>,
+    pub search_id: u64,
 }
 
 pub enum OpenDoc {
@@ -59,11 +65,13 @@ pub enum FileSystemAction {
     TreeLoaded,
     RecompileNeeded,
     LiveReloadNeeded(LiveFileChange),
+    SearchResults,
     None
 }
 
 impl FileSystem {
     pub fn init(&mut self, cx: &mut Cx, path:&Path) {
+        self.local_root = path.to_path_buf();
         self.file_client.init(cx, path);
         self.reload_file_tree();
     }
@@ -72,6 +80,19 @@ impl FileSystem {
         self.file_client.send_request(FileRequest::LoadFileTree {with_data: false});
     }
     
+    pub fn search_workspace(&mut self, pattern: &str, options: SearchOptions) {
+        // results for older searches that are still underway are ignored from here on
+        self.search_id += 1;
+        self.search_results.clear();
+        self.file_client.send_request(FileRequest::Search {
+            id: self.search_id,
+            pattern: pattern.to_string(),
+            case_sensitive: options.case_sensitive,
+            whole_word: options.whole_word,
+            regex: options.regex,
+        });
+    }
+    
     pub fn remove_tab(&mut self, tab_id: LiveId) {
         self.tab_id_to_file_node_id.remove(&tab_id);
         self.tab_id_to_session.remove(&tab_id);
@@ -116,7 +137,7 @@ impl FileSystem {
                         }
                         FileResponse::OpenFile(result) => {
                             match result {
-                                Ok((_unix_path, data, id)) => {
+                                Ok((unix_path, data, id)) => {
                                     let file_id = FileNodeId(LiveId(id));
                                     let dock = ui.dock(id!(dock));
                                     for (tab_id, file_id) in &self.tab_id_to_file_node_id {
@@ -126,7 +147,7 @@ impl FileSystem {
                                     }
                                     if let Some(OpenDoc::Decorations(dec)) = self.open_documents.get(&file_id) {
                                         let dec = dec.clone();
-                                        self.open_documents.insert(file_id, OpenDoc::Document(Document::new(data.into(), dec)));
+                                        self.open_documents.insert(file_id, OpenDoc::Document(Document::with_language(data.into(), dec, language::for_path(&unix_path))));
                                     }else {panic!()}
                                     ui.redraw(cx);
                                 }
@@ -175,9 +196,24 @@ impl FileSystem {
                             // to see if we need a recompile
                             
                         }
+                        FileResponse::Search(result) => match result {
+                            Ok(_) => {
+                                // the results arrive as notifications, this clears the previous ones from the log
+                                cx.action(FileSystemAction::SearchResults)
+                            }
+                            Err(err) => {
+                                log!("Search failed {:?}", err);
+                            }
+                        }
                     },
-                    FileClientMessage::Notification(_notification) => {
-                        //self.editors.handle_collab_notification(cx, &mut state.editor_state, notification)
+                    FileClientMessage::Notification(notification) => match notification {
+                        FileNotification::SearchResults {id, results, done: _} => {
+                            if id == self.search_id {
+                                self.search_results.extend(results);
+                                cx.action(FileSystemAction::SearchResults)
+                            }
+                        }
+                        FileNotification::FileChangedOnDisk => {}
                     }
                 }
             }
@@ -312,6 +348,11 @@ impl FileSystem {
         path
     }
     
+    /// The absolute path of a file node, for handing to other applications
+    pub fn file_node_local_path(&self, file_node_id: FileNodeId) -> String {
+        self.local_root.join(self.file_node_path(file_node_id)).to_string_lossy().into_owned()
+    }
+    
     pub fn ensure_unique_tab_names(&self, cx: &mut Cx, dock: &DockRef) {
         let mut min_diff: HashMap<FileNodeId, usize> = HashMap::new();
         let mut outer_path = Vec::new();
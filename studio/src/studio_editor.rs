@@ -1,9 +1,13 @@
-
 use {
     crate::{
         app::{AppData},
         makepad_widgets::*,
-        makepad_code_editor::CodeEditor,
+        makepad_code_editor::{
+            CodeEditor,
+            Session,
+            code_editor::CodeEditorAction,
+            makepad_regex::{RegexError, SearchOptions},
+        },
     },
     std::{
         env,
@@ -11,39 +15,183 @@ use {
 };
 
 live_design!{
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
     import makepad_code_editor::code_editor::CodeEditor;
-    
+
     StudioEditor = {{StudioEditor}}{
+        width: Fill,
+        height: Fill,
+        flow: Down,
+        find_bar: <View> {
+            visible: false,
+            width: Fill,
+            height: Fit,
+            flow: Right,
+            spacing: 5.0,
+            padding: {left: 5.0, right: 5.0, top: 2.0, bottom: 2.0},
+            align: {y: 0.5},
+            show_bg: true,
+            draw_bg: {color: (THEME_COLOR_BG_HEADER)}
+            find_input = <TextInput> {
+                width: 200,
+                empty_message: "Find"
+            }
+            case_sensitive = <CheckBox> {text: "Aa"}
+            whole_word = <CheckBox> {text: "Word"}
+            regex = <CheckBox> {text: ".*"}
+            match_count = <Label> {text: ""}
+            replace_input = <TextInput> {
+                width: 200,
+                empty_message: "Replace"
+            }
+            replace = <Button> {text: "Replace"}
+            replace_all = <Button> {text: "All"}
+            search_workspace = <Button> {text: "Workspace"}
+            close = <Button> {text: "Close"}
+        }
         editor: <CodeEditor>{
         }
     }
-} 
- 
-#[derive(Live, LiveHook, Widget)] 
+}
+
+#[derive(Live, LiveHook, Widget)]
 pub struct StudioEditor{
-    #[wrap] #[live] pub editor: CodeEditor
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+    #[live] #[find] #[redraw] find_bar: WidgetRef,
+    #[live] pub editor: CodeEditor
 }
 
 impl Widget for StudioEditor {
     fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
         // alright we have a scope, and an id, so now we can properly draw the editor.
         let session_id = scope.path.get(0);
+        cx.begin_turtle(walk, self.layout);
+        self.find_bar.draw_all(cx, scope);
         let app_scope = scope.data.get_mut::<AppData>();
         if let Some(session) = app_scope.file_system.get_session_mut(session_id){
+            let walk = self.editor.walk(cx);
             self.editor.draw_walk_editor(cx, session, walk);
         }
+        cx.end_turtle();
         DrawStep::done()
     }
-    
+
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
         let session_id = scope.path.get(0);
-        let data = scope.data.get_mut::<AppData>();
         let uid = self.widget_uid();
+        self.find_bar.handle_event(cx, event, scope);
+        let data = scope.data.get_mut::<AppData>();
         if let Some(session) = data.file_system.get_session_mut(session_id){
             for action in self.editor.handle_event(cx, event, session){
+                match &action {
+                    CodeEditorAction::Find(pattern) => self.open_find_bar(cx, pattern, session),
+                    CodeEditorAction::TextDidChange => self.update_match_count(cx, session),
+                    _ => ()
+                }
                 cx.widget_action(uid, &scope.path, action);
             }
+            if let Event::Actions(actions) = event {
+                self.handle_find_bar_actions(cx, actions, session, uid, &scope.path);
+            }
             data.file_system.handle_sessions();
         }
     }
-}
\ No newline at end of file
+}
+
+impl StudioEditor {
+    fn search_options(&self, cx: &Cx) -> SearchOptions {
+        SearchOptions {
+            case_sensitive: self.find_bar.check_box(id!(case_sensitive)).selected(cx),
+            whole_word: self.find_bar.check_box(id!(whole_word)).selected(cx),
+            regex: self.find_bar.check_box(id!(regex)).selected(cx),
+        }
+    }
+
+    /// Shows the find bar with the given pattern, or the last one if it is empty
+    fn open_find_bar(&mut self, cx: &mut Cx, pattern: &str, session: &Session) {
+        let find_input = self.find_bar.text_input(id!(find_input));
+        if !pattern.is_empty() && !pattern.contains('\n') {
+            find_input.set_text(pattern);
+        }
+        self.find_bar.as_view().set_visible_and_redraw(cx, true);
+        find_input.set_key_focus(cx);
+        self.find(cx, session);
+    }
+
+    fn close_find_bar(&mut self, cx: &mut Cx, session: &Session) {
+        self.find_bar.as_view().set_visible_and_redraw(cx, false);
+        session.clear_search();
+        self.editor.set_key_focus(cx);
+        self.editor.redraw(cx);
+    }
+
+    fn find(&mut self, cx: &mut Cx, session: &Session) {
+        let pattern = self.find_bar.text_input(id!(find_input)).text();
+        let result = session.find(&pattern, self.search_options(cx));
+        self.show_match_count(cx, result);
+        self.editor.redraw(cx);
+    }
+
+    fn update_match_count(&mut self, cx: &mut Cx, session: &Session) {
+        if self.find_bar.as_view().visible() {
+            self.show_match_count(cx, Ok(session.search_matches().len()));
+        }
+    }
+
+    fn show_match_count(&mut self, cx: &mut Cx, result: Result<usize, RegexError>) {
+        let text = match result {
+            Ok(1) => "1 match".to_string(),
+            Ok(count) => format!("{} matches", count),
+            Err(error) => error.to_string(),
+        };
+        self.find_bar.label(id!(match_count)).set_text_and_redraw(cx, &text);
+    }
+
+    fn handle_find_bar_actions(
+        &mut self,
+        cx: &mut Cx,
+        actions: &Actions,
+        session: &Session,
+        uid: WidgetUid,
+        path: &WidgetPath,
+    ) {
+        let find_input = self.find_bar.text_input(id!(find_input));
+        let replace_input = self.find_bar.text_input(id!(replace_input));
+        if find_input.changed(actions).is_some()
+            || self.find_bar.check_box(id!(case_sensitive)).changed(actions).is_some()
+            || self.find_bar.check_box(id!(whole_word)).changed(actions).is_some()
+            || self.find_bar.check_box(id!(regex)).changed(actions).is_some() {
+            self.find(cx, session);
+        }
+        if find_input.returned(actions).is_some() && session.select_next_match() {
+            self.editor.scroll_to_cursor(cx);
+        }
+        if replace_input.returned(actions).is_some()
+            || self.find_bar.button(id!(replace)).clicked(actions) {
+            if session.replace(&replace_input.text()) {
+                cx.widget_action(uid, path, CodeEditorAction::TextDidChange);
+                self.update_match_count(cx, session);
+            }
+            self.editor.scroll_to_cursor(cx);
+        }
+        if self.find_bar.button(id!(replace_all)).clicked(actions)
+            && session.replace_all(&replace_input.text()) > 0 {
+            cx.widget_action(uid, path, CodeEditorAction::TextDidChange);
+            self.update_match_count(cx, session);
+            self.editor.redraw(cx);
+        }
+        if self.find_bar.button(id!(search_workspace)).clicked(actions) {
+            let pattern = find_input.text();
+            if !pattern.is_empty() {
+                cx.widget_action(uid, path, CodeEditorAction::SearchWorkspace(pattern, self.search_options(cx)));
+            }
+        }
+        if find_input.escaped(actions)
+            || replace_input.escaped(actions)
+            || self.find_bar.button(id!(close)).clicked(actions) {
+            self.close_find_bar(cx, session);
+        }
+    }
+}
@@ -3,10 +3,12 @@ mod wasm;
 mod utils;
 mod apple;
 mod check;
+mod shaders;
 use android::*;
 use wasm::*;
 use apple::*;
 use check::*;
+use shaders::*;
 pub use makepad_shell;
 
 fn show_help(err: &str){
@@ -65,6 +67,12 @@ fn show_help(err: &str){
     println!("    android [options] expand-sdk");
     println!("    android [options] remove-sdk-sources");
     println!();
+    println!("Shader commands:");
+    println!();
+    println!("    validate-shaders <cargo args>                Type check every draw shader of an application and generate it for the");
+    println!("                                                 platform without opening a window, on macos and windows the platform");
+    println!("                                                 compiler checks it too. Errors are printed with their file:line");
+    println!();
     println!("Linux commands:");
     println!();
     println!("    linux apt-get-install-makepad-deps           Call apt-get install with all dependencies needed for makepad.");
@@ -103,6 +111,10 @@ fn main() {
         "check" => if let Err(e) = handle_check(&args[1..]){
             println!("Got error: {}", e);
         }
+        "validate-shaders" => if let Err(e) = handle_validate_shaders(&args[1..]){
+            println!("Got error: {}", e);
+            std::process::exit(1);
+        }
         _=> show_help("not implemented yet")
     }
 }
\ No newline at end of file
@@ -0,0 +1,15 @@
+use crate::makepad_shell::*;
+
+pub fn handle_validate_shaders(args: &[String]) -> Result<(), String> {
+    // the application checks its draw shaders and exits before it opens a window
+    let cwd = std::env::current_dir().unwrap();
+    let mut args_out = vec!["run"];
+    for arg in args {
+        args_out.push(arg);
+    }
+    if !args.iter().any( | arg | arg == "--") {
+        args_out.push("--");
+    }
+    args_out.push("--validate-shaders");
+    shell_env(&[], &cwd, "cargo", &args_out).map_err( | _ | "Errors found whilst validating shaders".to_string())
+}
@@ -86,7 +86,7 @@ fn main() {
                                 let msg = DeBin::deserialize_bin(data).unwrap();
                                 tx_receiver.send(msg).unwrap();
                             },
-                            Ok(WebSocketMessage::Close) => {
+                            Ok(WebSocketMessage::Close{..}) => {
                                 println!("Websocket Close message received");
                             }
                             Err(e) => {
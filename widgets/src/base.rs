@@ -18,6 +18,7 @@ live_design!{
     import crate::video::VideoBase;
     import crate::popup_menu::PopupMenuBase;
     import crate::label::LabelBase;
+    import crate::svg::SvgBase;
     import crate::link_label::LinkLabelBase;
     import crate::portal_list::PortalListBase;
     import crate::flat_list::FlatListBase;
@@ -99,6 +100,14 @@ live_design!{
         }
     }
     
+    Svg = <SvgBase> {
+        width: Fit
+        height: Fit
+        draw_svg: {
+            color: #fff
+        }
+    }
+    
     RotatedImage = <RotatedImageBase> {
         
         width: Fit
@@ -439,7 +448,7 @@ live_design!{
         instance border_width: 0.0
         instance border_color: #0000
         instance inset: vec4(0.0, 0.0, 0.0, 0.0)
-        instance radius: 5
+        instance radius: 5.0
         
         fn get_color(self) -> vec4 {
             return self.color
@@ -545,6 +554,7 @@ live_design!{
     RotatedImageBase = <RotatedImageBase> {}
     VideoBase = <VideoBase> {}
     LabelBase = <LabelBase> {}
+    SvgBase = <SvgBase> {}
     LinkLabelBase = <LinkLabelBase> {}
     PortalListBase = <PortalListBase> {}
     FlatListBase = <FlatListBase>{}
@@ -929,7 +929,7 @@ impl Widget for Dock {
             });
         }
         
-        if let Event::DragEnd = event {
+        if let Event::DragEnd | Event::DragLeave = event {
             // end our possible dragstate
             self.drop_state = None;
             self.drop_target_draw_list.redraw(cx);
@@ -4,6 +4,7 @@ use crate::{
     makepad_draw::*,
     widget::*
 };
+use std::rc::Rc;
 
 live_design!{
     ImageBase = {{Image}} {}
@@ -13,12 +14,24 @@ live_design!{
 pub struct Image {
     #[walk] walk: Walk,
     #[redraw] #[live] draw_bg: DrawQuad,
-    #[live] min_width: i64,
-    #[live] min_height: i64,
+    // the size the image is laid out with until its texture is loaded
+    #[live] placeholder_width: i64,
+    #[live] placeholder_height: i64,
     #[live(1.0)] width_scale: f64,
     #[live] fit: ImageFit,
     #[live] source: LiveDependency,
+    // decode the image at the size it is drawn at instead of its full size
+    #[live] downscale_on_decode: bool,
+    // play the frames of an animated GIF or PNG, false pauses on the frame that is showing
+    #[live(true)] animate: bool,
+    #[live] animation_loop: ImageLoop,
     #[rust] texture: Option<Texture>,
+    #[rust] animation: Option<Rc<ImageAnimation>>,
+    #[rust] frame: usize,
+    #[rust] plays: usize,
+    // when the frame that is showing came up, None until the next frame event
+    #[rust] frame_time: Option<f64>,
+    #[rust] next_frame: NextFrame,
 }
 
 impl ImageCacheImpl for Image {
@@ -29,19 +42,52 @@ impl ImageCacheImpl for Image {
     fn set_texture(&mut self, texture: Option<Texture>) {
         self.texture = texture;
     }
+    
+    fn set_animation(&mut self, cx: &mut Cx, animation: Option<Rc<ImageAnimation>>) {
+        let same = match (&self.animation, &animation) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false
+        };
+        if same {
+            return
+        }
+        self.animation = animation;
+        self.frame = 0;
+        self.plays = 0;
+        self.frame_time = None;
+        if self.animation.is_some() && self.animate {
+            self.next_frame = cx.new_next_frame();
+        }
+    }
 }
 
 impl LiveHook for Image{
     fn after_apply(&mut self, cx: &mut Cx, _from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
         self.lazy_create_image_cache(cx);
         let source = self.source.clone();
-        if source.as_str().len()>0 {
+        // with downscale_on_decode the image is loaded once we know its size in draw_walk
+        if source.as_str().len()>0 && !self.downscale_on_decode {
             self.load_image_dep_by_path(cx, source.as_str())
         }
+        // a paused animation shows its current frame for the full delay again when it resumes
+        self.frame_time = None;
+        if self.animation.is_some() && self.animate {
+            self.next_frame = cx.new_next_frame();
+        }
     }
 }
 
 impl Widget for Image {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, _scope: &mut Scope) {
+        if self.handle_image_cache_event(cx, event) {
+            self.redraw(cx);
+        }
+        if let Some(ne) = self.next_frame.is_event(event) {
+            self.play_animation(cx, ne.time);
+        }
+    }
+    
     fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
         self.draw_walk(cx, walk)
     }
@@ -49,19 +95,79 @@ impl Widget for Image {
 
 impl Image {
     
+    fn play_animation(&mut self, cx: &mut Cx, time: f64) {
+        let animation = if let Some(animation) = &self.animation {
+            animation.clone()
+        }
+        else {
+            return
+        };
+        if !self.animate {
+            return
+        }
+        let num_plays = match self.animation_loop {
+            ImageLoop::File => animation.num_plays,
+            ImageLoop::Forever => 0,
+            ImageLoop::Once => 1
+        };
+        let frame_time = *self.frame_time.get_or_insert(time);
+        // after a long stall we pick up at the current frame instead of racing through the ones we missed
+        let duration: f64 = animation.frames.iter().map( | (_, delay) | delay).sum();
+        let mut frame_time = if time - frame_time > duration {time} else {frame_time};
+        let frame = self.frame;
+        while time - frame_time >= animation.frames[self.frame].1 {
+            if num_plays != 0 && self.plays >= num_plays {
+                break
+            }
+            frame_time += animation.frames[self.frame].1;
+            if self.frame + 1 < animation.frames.len() {
+                self.frame += 1;
+            }
+            else {
+                self.plays += 1;
+                if num_plays != 0 && self.plays >= num_plays {
+                    // the animation comes to rest on its last frame
+                    break
+                }
+                self.frame = 0;
+            }
+        }
+        self.frame_time = Some(frame_time);
+        if self.frame != frame {
+            self.redraw(cx);
+        }
+        if num_plays == 0 || self.plays < num_plays {
+            self.next_frame = cx.new_next_frame();
+        }
+    }
+    
     pub fn draw_walk(&mut self, cx: &mut Cx2d, mut walk: Walk) -> DrawStep {
         // alright we get a walk. depending on our aspect ratio
         // we change either nothing, or width or height
         let rect = cx.peek_walk_turtle(walk);
         let dpi = cx.current_dpi_factor();
-        let (width, height) = if let Some(image_texture) = &self.texture {
+        if self.downscale_on_decode && self.source.as_str().len()>0 {
+            let max_size = if rect.size.x > 0.0 && rect.size.y > 0.0 {
+                Some(((rect.size.x * dpi).ceil() as usize, (rect.size.y * dpi).ceil() as usize))
+            }
+            else {
+                None
+            };
+            let source = self.source.clone();
+            self.load_image_dep_by_path_to_fit(cx, source.as_str(), max_size);
+        }
+        // until the image is decoded its texture has no format and we draw the placeholder
+        let frame_texture = self.animation.as_ref().and_then( | animation | animation.frames.get(self.frame)).map( | (texture, _) | texture);
+        let image_texture = frame_texture.or(self.texture.as_ref()).filter( | texture | !matches!(texture.get_format(cx), TextureFormat::Unknown));
+        let is_placeholder = image_texture.is_none();
+        let (width, height) = if let Some(image_texture) = image_texture {
             self.draw_bg.draw_vars.set_texture(0, image_texture);
-            let (width,height) = image_texture.get_format(cx).vec_width_height().unwrap_or((self.min_width as usize, self.min_height as usize));
+            let (width,height) = image_texture.get_format(cx).vec_width_height().unwrap_or((self.placeholder_width as usize, self.placeholder_height as usize));
             (width as f64 * self.width_scale, height as f64)
         }
         else {
             self.draw_bg.draw_vars.empty_texture(0);
-            (self.min_width as f64 / dpi, self.min_height as f64 / dpi)
+            (self.placeholder_width as f64 / dpi, self.placeholder_height as f64 / dpi)
         };
         
         let aspect = width / height;
@@ -96,6 +202,11 @@ impl Image {
         // lets start a turtle and center horizontally
         
         self.draw_bg.draw_walk(cx, walk);
+        if is_placeholder {
+            if let Some(texture) = &self.texture {
+                ImageCache::redraw_when_loaded(cx, texture, self.draw_bg.area());
+            }
+        }
         
         DrawStep::done()
     }
@@ -120,9 +231,22 @@ impl ImageRef {
         }
     }
     
+    pub fn load_image_from_data_with_key(&self, cx: &mut Cx, url: &str, data: &[u8]) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.load_image_from_data_with_key(cx, url, data)
+        }
+    }
+    
+    pub fn load_gif_from_data(&self, cx: &mut Cx, data: &[u8]) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.load_gif_from_data(cx, data)
+        }
+    }
+    
     pub fn set_texture(&self, texture: Option<Texture>) {
         if let Some(mut inner) = self.borrow_mut() {
-            inner.texture = texture
+            inner.texture = texture;
+            inner.animation = None;
         }
     }
     
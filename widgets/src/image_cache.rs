@@ -1,7 +1,13 @@
-use crate::{makepad_draw::*};
-use std::collections::HashMap;
+use crate::{makepad_draw::*, makepad_derive_widget::*};
+use std::{collections::HashMap, fmt, rc::Rc};
+use makepad_image_formats::gif;
 use makepad_zune_jpeg::JpegDecoder;
-use makepad_zune_png::PngDecoder;
+use makepad_zune_png::{
+    BlendOp,
+    DisposeOp,
+    PngDecoder,
+    makepad_zune_core::{bit_depth::BitDepth, options::DecoderOptions},
+};
 
 
 #[derive(Live, LiveHook)]
@@ -14,6 +20,16 @@ pub enum ImageFit{
     Biggest
 }
 
+/// How often an animated image plays before it stops on its last frame.
+#[derive(Live, LiveHook)]
+#[live_ignore]
+pub enum ImageLoop{
+    // as often as the file says
+    #[pick] File,
+    Forever,
+    Once
+}
+
 
 #[derive(Default, Clone)] 
 pub struct ImageBuffer {
@@ -45,8 +61,21 @@ impl ImageBuffer {
                 out[i] = ((a as u32)<<24) | ((r as u32)<<16) | ((g as u32)<<8) | ((b as u32)<<0);
             }
         }
+        else if in_data.len() / pixels == 2{
+            for i in 0..pixels{
+                let l = in_data[i*2] as u32;
+                let a = in_data[i*2+1] as u32;
+                out[i] = (a<<24) | (l<<16) | (l<<8) | l;
+            }
+        }
+        else if in_data.len() / pixels == 1{
+            for i in 0..pixels{
+                let l = in_data[i] as u32;
+                out[i] = 0xff000000 | (l<<16) | (l<<8) | l;
+            }
+        }
         else{
-            return Err("ImageBuffer::new Image buffer pixel alignment not 3 or 4".to_string())
+            return Err("ImageBuffer::new Image buffer pixel alignment not 1 to 4".to_string())
         }
         Ok(ImageBuffer {
             width,
@@ -93,6 +122,17 @@ impl ImageBuffer {
         }
     }
 
+    pub fn from_gif(
+        data: &[u8]
+    ) -> Result<Self, String> {
+        let image = gif::decode(data).map_err( | err | format!("Error decoding GIF: {}", err)) ?;
+        Ok(ImageBuffer {
+            width: image.width,
+            height: image.height,
+            data: image.data
+        })
+    }
+
     pub fn from_jpg(
         data: &[u8]
     ) -> Result<Self, String> {
@@ -108,61 +148,680 @@ impl ImageBuffer {
             }
         }
     }
+    
+    /// Shrinks the image with a box filter until it fits in `max_width` x `max_height`, keeping
+    /// the aspect ratio. Images that already fit are returned as they are.
+    pub fn downscale_to_fit(self, max_width: usize, max_height: usize) -> ImageBuffer {
+        if max_width == 0 || max_height == 0 || self.width <= max_width && self.height <= max_height {
+            return self
+        }
+        let scale = (max_width as f64 / self.width as f64).min(max_height as f64 / self.height as f64);
+        let width = ((self.width as f64 * scale).round() as usize).max(1);
+        let height = ((self.height as f64 * scale).round() as usize).max(1);
+        let mut data = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let y0 = y * self.height / height;
+            let y1 = ((y + 1) * self.height / height).max(y0 + 1);
+            for x in 0..width {
+                let x0 = x * self.width / width;
+                let x1 = ((x + 1) * self.width / width).max(x0 + 1);
+                let mut sum = [0u32; 4];
+                for sy in y0..y1 {
+                    for pixel in &self.data[sy * self.width + x0..sy * self.width + x1] {
+                        sum[0] += pixel >> 24;
+                        sum[1] += (pixel >> 16) & 0xff;
+                        sum[2] += (pixel >> 8) & 0xff;
+                        sum[3] += pixel & 0xff;
+                    }
+                }
+                let count = ((y1 - y0) * (x1 - x0)) as u32;
+                data.push(((sum[0] / count) << 24) | ((sum[1] / count) << 16) | ((sum[2] / count) << 8) | (sum[3] / count));
+            }
+        }
+        ImageBuffer {
+            width,
+            height,
+            data
+        }
+    }
+}
+
+/// What happens to the area of an animation frame before the next frame is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFrameDispose {
+    None,
+    /// clear the area to transparent
+    Background,
+    /// put back what was there before the frame was drawn
+    Previous,
+}
+
+/// How an animation frame is drawn onto what is already there.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFrameBlend {
+    /// replace the pixels, alpha included
+    Source,
+    /// blend over the pixels with the alpha of the frame
+    Over,
+}
+
+/// A frame of an animated GIF or PNG as it is stored in the file, placed at `x`,`y` on the
+/// canvas of the animation.
+pub struct ImageFrame {
+    pub x: usize,
+    pub y: usize,
+    pub image: ImageBuffer,
+    /// how long the frame is shown, in seconds
+    pub delay: f64,
+    pub dispose: ImageFrameDispose,
+    pub blend: ImageFrameBlend,
+}
+
+/// The frames of an animated GIF or PNG.
+pub struct ImageFrames {
+    pub width: usize,
+    pub height: usize,
+    /// how often the animation plays, 0 is forever
+    pub num_plays: usize,
+    pub frames: Vec<ImageFrame>,
+}
+
+// browsers show frames without a delay, or a very short one, for a tenth of a second
+fn frame_delay(delay: f64) -> f64 {
+    if delay <= 0.01 {0.1} else {delay}
+}
+
+// the OVER operator on colors that aren't premultiplied
+fn blend_over(src: u32, dst: u32) -> u32 {
+    let src_alpha = src >> 24;
+    if src_alpha == 255 {
+        return src
+    }
+    if src_alpha == 0 {
+        return dst
+    }
+    let dst_alpha = (dst >> 24) * (255 - src_alpha);
+    let alpha = src_alpha * 255 + dst_alpha;
+    let mut out = (alpha / 255) << 24;
+    for shift in [0, 8, 16] {
+        let src_color = (src >> shift) & 0xff;
+        let dst_color = (dst >> shift) & 0xff;
+        out |= ((src_color * src_alpha * 255 + dst_color * dst_alpha) / alpha) << shift;
+    }
+    out
 }
 
+impl ImageFrames {
+    pub fn from_gif(data: &[u8]) -> Result<Self, String> {
+        let animation = gif::decode_animation(data).map_err( | err | format!("Error decoding GIF: {}", err)) ?;
+        Ok(ImageFrames {
+            width: animation.width,
+            height: animation.height,
+            num_plays: animation.num_plays,
+            frames: animation.frames.into_iter().map( | frame | ImageFrame {
+                x: frame.x,
+                y: frame.y,
+                image: ImageBuffer {
+                    width: frame.image.width,
+                    height: frame.image.height,
+                    data: frame.image.data
+                },
+                delay: frame.delay,
+                dispose: match frame.disposal {
+                    gif::Disposal::None => ImageFrameDispose::None,
+                    gif::Disposal::Background => ImageFrameDispose::Background,
+                    gif::Disposal::Previous => ImageFrameDispose::Previous,
+                },
+                blend: ImageFrameBlend::Over,
+            }).collect()
+        })
+    }
+    
+    /// The frames of an APNG, or None for a PNG that isn't animated.
+    pub fn from_apng(data: &[u8]) -> Result<Option<Self>, String> {
+        let mut decoder = PngDecoder::new_with_options(data, DecoderOptions::default().png_set_add_alpha_channel(true));
+        decoder.decode_headers().map_err( | err | format!("Error decoding PNG: {:?}", err)) ?;
+        let actl = match decoder.get_actl_info() {
+            Some(actl) if actl.num_frames > 1 => actl,
+            _ => return Ok(None)
+        };
+        // 16 bit animations are shown still
+        if decoder.get_depth() != Some(BitDepth::Eight) {
+            return Ok(None)
+        }
+        let (width, height) = decoder.get_dimensions().unwrap();
+        let components = decoder.get_colorspace().unwrap().num_components();
+        // the default image doesn't have to be part of the animation, then the frames come after it
+        decoder.skip_default_image().map_err( | err | format!("Error decoding PNG: {:?}", err)) ?;
+        let mut out = vec![0u8; decoder.output_buffer_size().unwrap()];
+        let mut frames = Vec::new();
+        for _ in 0..actl.num_frames {
+            decoder.decode_into(&mut out).map_err( | err | format!("Error decoding APNG frame: {:?}", err)) ?;
+            let info = decoder.get_frame_info().unwrap();
+            let image = ImageBuffer::new(&out[..info.width * info.height * components], info.width, info.height) ?;
+            frames.push(ImageFrame {
+                x: info.x_offset,
+                y: info.y_offset,
+                image,
+                delay: frame_delay(if info.delay_denom == 0 {
+                    info.delay_num as f64 / 100.0
+                } else {
+                    info.delay_num as f64 / info.delay_denom as f64
+                }),
+                dispose: match info.dispose_op {
+                    DisposeOp::None => ImageFrameDispose::None,
+                    DisposeOp::Background => ImageFrameDispose::Background,
+                    DisposeOp::Previous => ImageFrameDispose::Previous,
+                },
+                blend: match info.blend_op {
+                    BlendOp::Source => ImageFrameBlend::Source,
+                    BlendOp::Over => ImageFrameBlend::Over,
+                },
+            });
+        }
+        Ok(Some(ImageFrames {
+            width,
+            height,
+            num_plays: actl.num_plays as usize,
+            frames
+        }))
+    }
+    
+    /// Draws the frames onto the canvas one after the other, returns what the canvas looks like
+    /// while each frame is shown together with its delay.
+    pub fn compose(&self) -> Vec<(ImageBuffer, f64)> {
+        let mut canvas = vec![0u32; self.width * self.height];
+        let mut composed = Vec::with_capacity(self.frames.len());
+        for frame in &self.frames {
+            let previous = if frame.dispose == ImageFrameDispose::Previous {Some(canvas.clone())} else {None};
+            let width = frame.image.width.min(self.width.saturating_sub(frame.x));
+            let height = frame.image.height.min(self.height.saturating_sub(frame.y));
+            for y in 0..height {
+                let src = &frame.image.data[y * frame.image.width..y * frame.image.width + width];
+                let start = (frame.y + y) * self.width + frame.x;
+                let dst = &mut canvas[start..start + width];
+                match frame.blend {
+                    ImageFrameBlend::Source => dst.copy_from_slice(src),
+                    ImageFrameBlend::Over => for (dst, src) in dst.iter_mut().zip(src) {
+                        *dst = blend_over(*src, *dst);
+                    }
+                }
+            }
+            composed.push((ImageBuffer {
+                width: self.width,
+                height: self.height,
+                data: canvas.clone()
+            }, frame.delay));
+            match frame.dispose {
+                ImageFrameDispose::None => (),
+                ImageFrameDispose::Background => for y in 0..height {
+                    let start = (frame.y + y) * self.width + frame.x;
+                    canvas[start..start + width].fill(0);
+                }
+                ImageFrameDispose::Previous => canvas = previous.unwrap(),
+            }
+        }
+        composed
+    }
+}
+
+// what comes back from the thread pool, animations are composed there as well
+enum DecodedImage {
+    Still(ImageBuffer),
+    Animated {
+        frames: Vec<(ImageBuffer, f64)>,
+        num_plays: usize,
+    },
+}
+
+impl DecodedImage {
+    fn downscale_to_fit(self, max_width: usize, max_height: usize) -> Self {
+        match self {
+            Self::Still(image) => Self::Still(image.downscale_to_fit(max_width, max_height)),
+            Self::Animated {frames, num_plays} => Self::Animated {
+                frames: frames.into_iter().map( | (image, delay) | (image.downscale_to_fit(max_width, max_height), delay)).collect(),
+                num_plays
+            }
+        }
+    }
+    
+    fn animated(frames: ImageFrames) -> Self {
+        Self::Animated {
+            frames: frames.compose(),
+            num_plays: frames.num_plays
+        }
+    }
+}
+
+/// The frames of an animated image in their own textures, the first one is the texture the
+/// image was loaded into.
+pub struct ImageAnimation {
+    /// the texture and delay in seconds of every frame
+    pub frames: Vec<(Texture, f64)>,
+    /// how often the animation plays, 0 is forever
+    pub num_plays: usize,
+}
+
+impl fmt::Debug for ImageAnimation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageAnimation")
+            .field("frames", &self.frames.len())
+            .field("num_plays", &self.num_plays)
+            .finish()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ImageDecoder {
+    Png,
+    Jpg,
+    Gif,
+    Detect,
+}
+
+impl ImageDecoder {
+    fn from_path(path: &str) -> Option<Self> {
+        if path.ends_with(".jpg") || path.ends_with(".jpeg") {
+            Some(Self::Jpg)
+        }
+        else if path.ends_with(".png") || path.ends_with(".apng") {
+            Some(Self::Png)
+        }
+        else if path.ends_with(".gif") {
+            Some(Self::Gif)
+        }
+        else {
+            None
+        }
+    }
+    
+    fn decode(self, data: &[u8]) -> Result<DecodedImage, String> {
+        match self {
+            Self::Png => match ImageFrames::from_apng(data) ? {
+                Some(frames) => Ok(DecodedImage::animated(frames)),
+                None => ImageBuffer::from_png(data).map(DecodedImage::Still)
+            }
+            Self::Jpg => ImageBuffer::from_jpg(data).map(DecodedImage::Still),
+            Self::Gif => {
+                let frames = ImageFrames::from_gif(data) ?;
+                if frames.frames.len() > 1 {
+                    Ok(DecodedImage::animated(frames))
+                }
+                else {
+                    Ok(DecodedImage::Still(frames.compose().remove(0).0))
+                }
+            }
+            Self::Detect => if data.starts_with(&[0x89, b'P', b'N', b'G']) {
+                Self::Png.decode(data)
+            }
+            else if data.starts_with(&[0xff, 0xd8]) {
+                Self::Jpg.decode(data)
+            }
+            else if data.starts_with(b"GIF8") {
+                Self::Gif.decode(data)
+            }
+            else {
+                Err("Image format not supported, expected PNG, JPEG or GIF data".to_string())
+            }
+        }
+    }
+}
+
+/// Sent when an image that was decoding on the thread pool arrives in its texture. An animated
+/// image sends `Animated` with its frames right before `Loaded`.
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ImageCacheAction {
+    None,
+    Loaded(TextureId),
+    Animated(TextureId, Rc<ImageAnimation>),
+    Failed(TextureId, String),
+}
+
+struct ImageCacheEntry {
+    texture: Texture,
+    // size of the decoded pixels, 0 while the image is decoding
+    bytes: usize,
+    last_used: u64,
+    // the bounds the image was downscaled to on decode, None for the full size
+    max_size: Option<(usize, usize)>,
+    // the decode that will fill the texture, older ones are ignored
+    request_id: u64,
+    animation: Option<Rc<ImageAnimation>>,
+}
+
+struct ImageDecodeResult {
+    request_id: u64,
+    result: Result<DecodedImage, String>,
+}
+
+/// Decodes images on a thread pool and keeps the textures of the most recently used ones,
+/// keyed by their path or url, up to a budget of bytes. Evicted textures stay alive for as
+/// long as a widget still holds them.
 pub struct ImageCache {
-    map: HashMap<String, Texture>,
+    map: HashMap<String, ImageCacheEntry>,
+    max_bytes: usize,
+    used_bytes: usize,
+    use_counter: u64,
+    request_id: u64,
+    decoding: HashMap<u64, (Texture, Option<String>)>,
+    decoded: ToUIReceiver<ImageDecodeResult>,
+    thread_pool: Option<TagThreadPool<u64>>,
+    // the areas that drew a placeholder for a texture that is still decoding
+    waiting: HashMap<TextureId, Vec<Area>>,
 }
 
 impl ImageCache {
     pub fn new() -> Self {
         Self {
             map: HashMap::new(),
+            max_bytes: 128 * 1024 * 1024,
+            used_bytes: 0,
+            use_counter: 0,
+            request_id: 0,
+            decoding: HashMap::new(),
+            decoded: Default::default(),
+            thread_pool: None,
+            waiting: HashMap::new(),
         }
     }
+    
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+    
+    pub fn used_bytes(&self) -> usize {
+        self.used_bytes
+    }
+    
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        self.evict(None);
+    }
+    
+    /// The texture cached under `key`, if it is there.
+    pub fn get(&mut self, key: &str) -> Option<Texture> {
+        self.use_counter += 1;
+        let entry = self.map.get_mut(key) ?;
+        entry.last_used = self.use_counter;
+        Some(entry.texture.clone())
+    }
+    
+    /// The frames of the animated image cached under `key`, once it is decoded.
+    pub fn get_animation(&self, key: &str) -> Option<Rc<ImageAnimation>> {
+        self.map.get(key).and_then( | entry | entry.animation.clone())
+    }
+    
+    /// Returns the texture for the PNG, JPEG or GIF in `data` and caches it under `key`, usually the url
+    /// it came from. The texture stays empty until the image is decoded, see `handle_signal`.
+    pub fn load_data(cx: &mut Cx, key: &str, data: &[u8], max_size: Option<(usize, usize)>) -> Texture {
+        Self::load(cx, key, ImageDecoder::Detect, max_size, | _ | Ok(data.to_vec())).unwrap()
+    }
+    
+    /// Like `load_data` for the image dependency at `path`.
+    pub fn load_dep(cx: &mut Cx, path: &str, max_size: Option<(usize, usize)>) -> Result<Texture, String> {
+        let decoder = ImageDecoder::from_path(path).ok_or_else( || format!("Image format not supported {}", path)) ?;
+        Self::load(cx, path, decoder, max_size, | cx | cx.get_dependency(path).map( | data | data.to_vec()))
+    }
+    
+    fn load(cx: &mut Cx, key: &str, decoder: ImageDecoder, max_size: Option<(usize, usize)>, data: impl FnOnce(&mut Cx) -> Result<Vec<u8>, String>) -> Result<Texture, String> {
+        // round up so resizing a widget doesn't decode its image again for every pixel
+        let max_size = max_size.map( | (width, height) | (width.next_power_of_two(), height.next_power_of_two()));
+        let cache = cx.get_global::<ImageCache>();
+        cache.use_counter += 1;
+        let use_counter = cache.use_counter;
+        let existing = cache.map.get_mut(key).map( | entry | {
+            entry.last_used = use_counter;
+            (entry.texture.clone(), Self::fits(entry.max_size, max_size))
+        });
+        let texture = match existing {
+            Some((texture, true)) => return Ok(texture),
+            // decoded too small for this use, decode again into the same texture
+            Some((texture, false)) => texture,
+            None => Texture::new(cx)
+        };
+        let data = data(cx) ?;
+        let request_id = Self::decode(cx, texture.clone(), Some(key.to_string()), decoder, data, max_size);
+        let cache = cx.get_global::<ImageCache>();
+        if let Some(entry) = cache.map.get_mut(key) {
+            entry.max_size = max_size;
+            entry.request_id = request_id;
+        }
+        else {
+            cache.map.insert(key.to_string(), ImageCacheEntry {
+                texture: texture.clone(),
+                bytes: 0,
+                last_used: use_counter,
+                max_size,
+                request_id,
+                animation: None,
+            });
+        }
+        Ok(texture)
+    }
+    
+    // whether an image decoded to fit in `decoded_size` can be shown at `max_size`, None is the full size
+    fn fits(decoded_size: Option<(usize, usize)>, max_size: Option<(usize, usize)>) -> bool {
+        match (decoded_size, max_size) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(decoded_size), Some(max_size)) => max_size.0 <= decoded_size.0 && max_size.1 <= decoded_size.1
+        }
+    }
+    
+    /// Redraws `area` when the image for `texture` is decoded. Widgets call this when they
+    /// draw a placeholder, so they don't have to see the `ImageCacheAction` to show the image.
+    pub fn redraw_when_loaded(cx: &mut Cx, texture: &Texture, area: Area) {
+        if !cx.has_global::<ImageCache>() {
+            return
+        }
+        let areas = cx.get_global::<ImageCache>().waiting.entry(texture.texture_id()).or_default();
+        if !areas.contains(&area) {
+            areas.push(area);
+        }
+    }
+    
+    fn decode(cx: &mut Cx, texture: Texture, key: Option<String>, decoder: ImageDecoder, data: Vec<u8>, max_size: Option<(usize, usize)>) -> u64 {
+        if cx.get_global::<ImageCache>().thread_pool.is_none() {
+            let num_threads = cx.cpu_cores().max(3) - 2;
+            let thread_pool = TagThreadPool::new(cx, num_threads);
+            cx.get_global::<ImageCache>().thread_pool = Some(thread_pool);
+        }
+        let cache = cx.get_global::<ImageCache>();
+        cache.request_id += 1;
+        let request_id = cache.request_id;
+        cache.decoding.insert(request_id, (texture, key));
+        let sender = cache.decoded.sender();
+        // the last request goes first, those are the images that just scrolled into view
+        cache.thread_pool.as_ref().unwrap().execute_rev(request_id, move | request_id | {
+            let result = decoder.decode(&data).map( | image | match max_size {
+                Some((max_width, max_height)) => image.downscale_to_fit(max_width, max_height),
+                None => image
+            });
+            let _ = sender.send(ImageDecodeResult {request_id, result});
+        });
+        request_id
+    }
+    
+    /// Moves the decoded images into their textures, redraws the areas waiting for them and sends
+    /// an `ImageCacheAction` for each. The thread pool wakes up the ui with `Event::Signal` when it
+    /// has something, the `Window` widget calls this on it before the event goes to its children
+    /// so the actions aren't captured by whichever widget happens to see the signal first.
+    pub fn handle_signal(cx: &mut Cx) {
+        if !cx.has_global::<ImageCache>() {
+            return
+        }
+        while let Ok(decoded) = cx.get_global::<ImageCache>().decoded.try_recv() {
+            let cache = cx.get_global::<ImageCache>();
+            let (texture, key) = if let Some(decoding) = cache.decoding.remove(&decoded.request_id) {
+                decoding
+            }
+            else {
+                continue
+            };
+            if let Some(areas) = cache.waiting.remove(&texture.texture_id()) {
+                for area in areas {
+                    cx.redraw_area(area);
+                }
+            }
+            let cache = cx.get_global::<ImageCache>();
+            let entry = key.as_ref().and_then( | key | cache.map.get_mut(key));
+            if let Some(entry) = &entry {
+                if entry.request_id != decoded.request_id {
+                    continue
+                }
+            }
+            match decoded.result {
+                Ok(DecodedImage::Still(image)) => {
+                    if let Some(entry) = entry {
+                        let bytes = image.data.len() * 4;
+                        cache.used_bytes = cache.used_bytes - entry.bytes + bytes;
+                        entry.bytes = bytes;
+                        entry.animation = None;
+                        cache.evict(key.as_deref());
+                    }
+                    image.into_texture(cx, &texture);
+                    cx.action(ImageCacheAction::Loaded(texture.texture_id()));
+                }
+                Ok(DecodedImage::Animated {frames, num_plays}) => {
+                    let bytes = frames.iter().map( | (image, _) | image.data.len() * 4).sum::<usize>();
+                    let frames = frames.into_iter().enumerate().map( | (index, (image, delay)) | {
+                        // the first frame goes in the texture everyone already has
+                        let frame_texture = if index == 0 {texture.clone()} else {Texture::new(cx)};
+                        image.into_texture(cx, &frame_texture);
+                        (frame_texture, delay)
+                    }).collect();
+                    let animation = Rc::new(ImageAnimation {frames, num_plays});
+                    let cache = cx.get_global::<ImageCache>();
+                    if let Some(entry) = key.as_ref().and_then( | key | cache.map.get_mut(key)) {
+                        cache.used_bytes = cache.used_bytes - entry.bytes + bytes;
+                        entry.bytes = bytes;
+                        entry.animation = Some(animation.clone());
+                        cache.evict(key.as_deref());
+                    }
+                    cx.action(ImageCacheAction::Animated(texture.texture_id(), animation));
+                    cx.action(ImageCacheAction::Loaded(texture.texture_id()));
+                }
+                Err(err) => {
+                    error!("ImageCache: Cannot decode image {} {}", key.as_deref().unwrap_or("from data"), err);
+                    // keep the empty texture so we don't try to decode it again on every draw
+                    if let Some(entry) = entry {
+                        entry.max_size = None;
+                    }
+                    cx.action(ImageCacheAction::Failed(texture.texture_id(), err));
+                }
+            }
+        }
+    }
+    
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.map.remove(key) {
+            self.used_bytes -= entry.bytes;
+        }
+    }
+    
+    // drops the least recently used images until the cache fits its budget again
+    fn evict(&mut self, keep: Option<&str>) {
+        while self.used_bytes > self.max_bytes {
+            let oldest = self.map.iter()
+                .filter( | (key, entry) | entry.bytes > 0 && Some(key.as_str()) != keep)
+                .min_by_key( | (_, entry) | entry.last_used)
+                .map( | (key, _) | key.clone());
+            if let Some(oldest) = oldest {
+                self.remove(&oldest);
+            }
+            else {
+                break
+            }
+        }
+    }
+    
+    fn is_cached(&self, texture: &Texture) -> bool {
+        self.map.values().any( | entry | entry.texture.texture_id() == texture.texture_id())
+    }
 }
 
 pub trait ImageCacheImpl {
     fn get_texture(&self) -> &Option<Texture>;
     fn set_texture(&mut self, texture: Option<Texture>);
+    
+    /// Widgets that play animated images take the frames here, the others show the first frame.
+    fn set_animation(&mut self, _cx: &mut Cx, _animation: Option<Rc<ImageAnimation>>) {}
 
     fn lazy_create_image_cache(&mut self,cx: &mut Cx) {
         if !cx.has_global::<ImageCache>() {
             cx.set_global(ImageCache::new());
         }
     }
-
-
-    fn load_png_from_data(&mut self, cx:&mut Cx, data:&[u8]){
-        match ImageBuffer::from_png(&*data){
-            Ok(data)=>{
-                if let Some(texture) = self.get_texture(){
-                    data.into_texture(cx, texture);
+    
+    /// Call this from `handle_event`, returns true when the image of this widget arrived and it needs a redraw.
+    /// The frames of an animated image are passed to `set_animation`. The images are decoded in
+    /// `ImageCache::handle_signal`, which the `Window` calls for all widgets.
+    fn handle_image_cache_event(&mut self, cx: &mut Cx, event: &Event) -> bool {
+        match event {
+            Event::Actions(actions) => {
+                if let Some(texture) = self.get_texture() {
+                    let texture_id = texture.texture_id();
+                    let mut loaded = false;
+                    for action in actions {
+                        match action.downcast_ref::<ImageCacheAction>() {
+                            Some(ImageCacheAction::Loaded(id)) if *id == texture_id => {
+                                loaded = true;
+                            }
+                            Some(ImageCacheAction::Animated(id, animation)) if *id == texture_id => {
+                                self.set_animation(cx, Some(animation.clone()));
+                            }
+                            _ => ()
+                        }
+                    }
+                    loaded
                 }
-                else{
-                    self.set_texture(Some(data.into_new_texture(cx)));
+                else {
+                    false
                 }
             }
-            Err(err)=>{
-                error!("load_png_from_data: Cannot load png image from data {}", err);
-            }
+            _ => false
         }
     }
     
-    fn load_jpg_from_data(&mut self, cx:&mut Cx, data:&[u8]){
-        match ImageBuffer::from_jpg(&*data){
-            Ok(data)=>{
-                if let Some(texture) = self.get_texture(){
-                    data.into_texture(cx, texture);
-                }
-                else{
-                    self.set_texture(Some(data.into_new_texture(cx)));
-                }
-            }
-            Err(err)=>{
-                error!("load_jpg_from_data: Cannot load png image from data {}", err);
+    // the texture the data of a widget is decoded into, one from the cache is shared so it can't be reused
+    fn data_texture(&mut self, cx: &mut Cx) -> Texture {
+        self.lazy_create_image_cache(cx);
+        if let Some(texture) = self.get_texture() {
+            if !cx.get_global::<ImageCache>().is_cached(texture) {
+                return texture.clone()
             }
         }
+        let texture = Texture::new(cx);
+        self.set_texture(Some(texture.clone()));
+        self.set_animation(cx, None);
+        texture
+    }
+
+    /// Decodes the PNG on the thread pool, the widget shows its placeholder until it arrives.
+    fn load_png_from_data(&mut self, cx:&mut Cx, data:&[u8]){
+        let texture = self.data_texture(cx);
+        ImageCache::decode(cx, texture, None, ImageDecoder::Png, data.to_vec(), None);
+    }
+    
+    /// Decodes the JPEG on the thread pool, the widget shows its placeholder until it arrives.
+    fn load_jpg_from_data(&mut self, cx:&mut Cx, data:&[u8]){
+        let texture = self.data_texture(cx);
+        ImageCache::decode(cx, texture, None, ImageDecoder::Jpg, data.to_vec(), None);
+    }
+    
+    /// Decodes the GIF on the thread pool, the widget shows its placeholder until it arrives.
+    fn load_gif_from_data(&mut self, cx:&mut Cx, data:&[u8]){
+        let texture = self.data_texture(cx);
+        ImageCache::decode(cx, texture, None, ImageDecoder::Gif, data.to_vec(), None);
+    }
+    
+    /// Loads a PNG, JPEG or GIF that was fetched from `url`, it is cached under that url.
+    fn load_image_from_data_with_key(&mut self, cx: &mut Cx, url: &str, data: &[u8]) {
+        self.lazy_create_image_cache(cx);
+        let texture = ImageCache::load_data(cx, url, data, None);
+        self.set_texture(Some(texture));
+        let animation = cx.get_global::<ImageCache>().get_animation(url);
+        self.set_animation(cx, animation);
     }
 
     fn load_image_dep_by_path(
@@ -170,42 +829,94 @@ pub trait ImageCacheImpl {
         cx: &mut Cx,
         image_path: &str,
     ) {
-        if let Some(texture) = cx.get_global::<ImageCache>().map.get(image_path){
-            self.set_texture(Some(texture.clone()));
-        }
-        else{
-            match cx.get_dependency(image_path) {
-                Ok(data) => {
-                    if image_path.ends_with(".jpg") {
-                        match ImageBuffer::from_jpg(&*data){
-                            Ok(data)=>{
-                                let texture = data.into_new_texture(cx);
-                                cx.get_global::<ImageCache>().map.insert(image_path.to_string(), texture.clone());
-                                self.set_texture(Some(texture));
-                            }
-                            Err(err)=>{
-                                error!("load_image_dep_by_path: Cannot load jpeg image from path: {} {}",image_path, err);
-                            }
-                        }
-                    } else if image_path.ends_with(".png") {
-                        match ImageBuffer::from_png(&*data){
-                            Ok(data)=>{
-                                let texture = data.into_new_texture(cx);
-                                cx.get_global::<ImageCache>().map.insert(image_path.to_string(), texture.clone());
-                                self.set_texture(Some(texture));
-                            }
-                            Err(err)=>{
-                                error!("load_image_dep_by_path: Cannot load png image from path: {} {}",image_path, err);
-                            }
-                        }
-                    } else {
-                        error!("load_image_dep_by_path: Image format not supported {}",image_path);
-                    }
-                }
-                Err(err) => {
-                    error!("load_image_dep_by_path:  Resource not found {} {}",image_path, err);
-                }
+        self.load_image_dep_by_path_to_fit(cx, image_path, None)
+    }
+    
+    /// Like `load_image_dep_by_path`, downscales the image on decode so it fits in `max_size` pixels.
+    fn load_image_dep_by_path_to_fit(
+        &mut self,
+        cx: &mut Cx,
+        image_path: &str,
+        max_size: Option<(usize, usize)>,
+    ) {
+        self.lazy_create_image_cache(cx);
+        match ImageCache::load_dep(cx, image_path, max_size) {
+            Ok(texture) => {
+                self.set_texture(Some(texture));
+                let animation = cx.get_global::<ImageCache>().get_animation(image_path);
+                self.set_animation(cx, animation);
+            }
+            Err(err) => {
+                error!("load_image_dep_by_path: Cannot load image {} {}", image_path, err);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn insert(cx: &mut Cx, cache: &mut ImageCache, key: &str, bytes: usize) {
+        cache.use_counter += 1;
+        cache.map.insert(key.to_string(), ImageCacheEntry {
+            texture: Texture::new(cx),
+            bytes,
+            last_used: cache.use_counter,
+            max_size: None,
+            request_id: 0,
+            animation: None,
+        });
+        cache.used_bytes += bytes;
+    }
+    
+    fn keys(cache: &ImageCache) -> Vec<&str> {
+        let mut keys: Vec<&str> = cache.map.keys().map( | key | key.as_str()).collect();
+        keys.sort();
+        keys
+    }
+    
+    #[test]
+    fn evict_least_recently_used() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        let mut cache = ImageCache::new();
+        cache.max_bytes = 300;
+        insert(&mut cx, &mut cache, "a", 100);
+        insert(&mut cx, &mut cache, "b", 100);
+        insert(&mut cx, &mut cache, "c", 100);
+        // using a makes b the oldest
+        assert!(cache.get("a").is_some());
+        insert(&mut cx, &mut cache, "d", 100);
+        cache.evict(Some("d"));
+        assert_eq!(keys(&cache), ["a", "c", "d"]);
+        assert_eq!(cache.used_bytes(), 300);
+        
+        cache.set_max_bytes(100);
+        assert_eq!(keys(&cache), ["d"]);
+        assert_eq!(cache.used_bytes(), 100);
+    }
+    
+    #[test]
+    fn evict_keeps_the_new_and_decoding_images() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        let mut cache = ImageCache::new();
+        cache.max_bytes = 100;
+        insert(&mut cx, &mut cache, "decoding", 0);
+        insert(&mut cx, &mut cache, "a", 100);
+        // an image bigger than the whole budget stays until something newer replaces it
+        insert(&mut cx, &mut cache, "big", 200);
+        cache.evict(Some("big"));
+        assert_eq!(keys(&cache), ["big", "decoding"]);
+        assert_eq!(cache.used_bytes(), 200);
+    }
+    
+    #[test]
+    fn fits() {
+        assert!(ImageCache::fits(None, None));
+        assert!(ImageCache::fits(None, Some((64, 64))));
+        assert!(!ImageCache::fits(Some((64, 64)), None));
+        assert!(ImageCache::fits(Some((64, 64)), Some((64, 32))));
+        assert!(!ImageCache::fits(Some((64, 64)), Some((128, 64))));
+        assert!(!ImageCache::fits(Some((64, 64)), Some((64, 128))));
+    }
+}
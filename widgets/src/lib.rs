@@ -7,6 +7,7 @@ pub use makepad_derive_widget::*;
 
 pub mod button;
 pub mod label;
+pub mod svg;
 pub mod image;
 pub mod link_label;
 pub mod drop_down;
@@ -66,6 +67,7 @@ pub use crate::{
     view::*,
     image::*,
     label::*,
+    svg::*,
     slider::*,
     check_box::*,
     drop_down::*,
@@ -123,6 +125,7 @@ pub fn live_design(cx: &mut Cx) {
     crate::theme_desktop_dark::live_design(cx);
     crate::slider::live_design(cx);
     crate::label::live_design(cx);
+    crate::svg::live_design(cx);
     crate::nav_control::live_design(cx);
     crate::image::live_design(cx);
     crate::rotated_image::live_design(cx);
@@ -312,8 +312,9 @@ impl PortalList {
                     cx.begin_turtle(Walk {
                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y + self.first_scroll)),
                         margin: Default::default(),
-                        width: Size::Fill,
-                        height: Size::Fit
+                        width: Size::fill(),
+                        height: Size::Fit,
+                        ..Walk::default()
                     }, Layout::flow_down());
                     return Some(self.first_id)
                 }
@@ -341,8 +342,9 @@ impl PortalList {
                             cx.begin_turtle(Walk {
                                 abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y)),
                                 margin: Default::default(),
-                                width: Size::Fill,
-                                height: Size::Fit
+                                width: Size::fill(),
+                                height: Size::Fit,
+                                ..Walk::default()
                             }, Layout::flow_down());
                             return Some(self.first_id - 1);
                         }
@@ -368,8 +370,9 @@ impl PortalList {
                     cx.begin_turtle(Walk {
                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y + pos + rect.size.index(vi))),
                         margin: Default::default(),
-                        width: Size::Fill,
-                        height: Size::Fit
+                        width: Size::fill(),
+                        height: Size::Fit,
+                        ..Walk::default()
                     }, Layout::flow_down());
                     return Some(index + 1)
                 }
@@ -400,8 +403,9 @@ impl PortalList {
                                 cx.begin_turtle(Walk {
                                     abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y)),
                                     margin: Default::default(),
-                                    width: Size::Fill,
-                                    height: Size::Fit
+                                    width: Size::fill(),
+                                    height: Size::Fit,
+                                    ..Walk::default()
                                 }, Layout::flow_down());
                                 return Some(last_index + 1);
                             }
@@ -425,8 +429,9 @@ impl PortalList {
                     cx.begin_turtle(Walk {
                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y)),
                         margin: Default::default(),
-                        width: Size::Fill,
-                        height: Size::Fit
+                        width: Size::fill(),
+                        height: Size::Fit,
+                        ..Walk::default()
                     }, Layout::flow_down());
                     
                     return Some(index - 1);
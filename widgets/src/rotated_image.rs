@@ -38,6 +38,12 @@ impl LiveHook for RotatedImage {
 }
 
 impl Widget for RotatedImage {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, _scope: &mut Scope) {
+        if self.handle_image_cache_event(cx, event) {
+            self.redraw(cx);
+        }
+    }
+    
     fn draw_walk(&mut self, cx: &mut Cx2d, _scope:&mut Scope, walk: Walk) -> DrawStep {
         self.draw_walk_rotated_image(cx, walk)
     }
@@ -49,6 +55,12 @@ impl RotatedImage {
             self.draw_bg.draw_vars.set_texture(0, image_texture);
         }
         self.draw_bg.draw_walk(cx, walk);
+        if let Some(image_texture) = &self.texture {
+            // until the image is decoded its texture has no format
+            if matches!(image_texture.get_format(cx), TextureFormat::Unknown) {
+                ImageCache::redraw_when_loaded(cx, image_texture, self.draw_bg.area());
+            }
+        }
 
         DrawStep::done()
     }
@@ -203,8 +203,8 @@ impl Splitter {
         self.position = self.align.to_position(self.axis, self.rect);
         
         let walk = match self.axis {
-            SplitterAxis::Horizontal => Walk::size(Size::Fixed(self.position), Size::Fill),
-            SplitterAxis::Vertical => Walk::size(Size::Fill, Size::Fixed(self.position)),
+            SplitterAxis::Horizontal => Walk::size(Size::Fixed(self.position), Size::fill()),
+            SplitterAxis::Vertical => Walk::size(Size::fill(), Size::Fixed(self.position)),
         };
         cx.begin_turtle(walk, Layout::flow_down());
     }
@@ -214,11 +214,11 @@ impl Splitter {
         match self.axis {
             SplitterAxis::Horizontal => {
                 self.draw_splitter.is_vertical = 1.0;
-                self.draw_splitter.draw_walk(cx, Walk::size(Size::Fixed(self.split_bar_size), Size::Fill));
+                self.draw_splitter.draw_walk(cx, Walk::size(Size::Fixed(self.split_bar_size), Size::fill()));
             }
             SplitterAxis::Vertical => {
                 self.draw_splitter.is_vertical = 0.0;
-                self.draw_splitter.draw_walk(cx, Walk::size(Size::Fill, Size::Fixed(self.split_bar_size)));
+                self.draw_splitter.draw_walk(cx, Walk::size(Size::fill(), Size::Fixed(self.split_bar_size)));
             }
         }
         cx.begin_turtle(Walk::default(), Layout::flow_down());
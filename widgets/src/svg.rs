@@ -0,0 +1,27 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        widget::*
+    }
+};
+
+live_design!{
+    SvgBase = {{Svg}} {}
+}
+
+/// Shows an svg file in its own colors. Unlike an icon, which draws the whole file in one color,
+/// every fill and stroke keeps its color, gradient and opacity; `draw_svg.color` is only used for
+/// what the file paints with `currentColor`.
+#[derive(Live, LiveHook, Widget)]
+pub struct Svg {
+    #[redraw] #[live] draw_svg: DrawSvg,
+    #[walk] walk: Walk,
+}
+
+impl Widget for Svg {
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.draw_svg.draw_walk(cx, walk);
+        DrawStep::done()
+    }
+}
@@ -72,7 +72,8 @@ pub struct TextInput {
     #[rust] undo_stack: Vec<UndoItem>,
     #[rust] redo_stack: Vec<UndoItem>,
     #[rust] cursor_tail: usize,
-    #[rust] cursor_head: usize
+    #[rust] cursor_head: usize,
+    #[rust] composition: TextCompositionEvent,
 }
 
 impl Widget for TextInput {
@@ -82,6 +83,7 @@ impl Widget for TextInput {
         match event.hits(cx, self.draw_bg.area()) {
             Hit::KeyFocusLost(_) => {
                 self.animator_play(cx, id!(focus.off));
+                self.composition = TextCompositionEvent::default();
                 cx.hide_text_ime();
                 cx.widget_action(uid, &scope.path, TextInputAction::Return(self.text.clone()));
                 cx.widget_action(uid, &scope.path, TextInputAction::KeyFocusLost);
@@ -96,7 +98,18 @@ impl Widget for TextInput {
                 self.draw_bg.redraw(cx);
                 cx.widget_action(uid, &scope.path, TextInputAction::KeyFocus);
             }
+            Hit::TextComposition(tc) => {
+                // the preedit is only drawn, it becomes part of the text when it arrives as TextInput
+                if !self.read_only && !self.secret {
+                    self.composition = tc;
+                    self.draw_bg.redraw(cx);
+                }
+            }
             Hit::TextInput(te) => {
+                if !self.composition.is_empty() {
+                    self.composition = TextCompositionEvent::default();
+                    self.draw_bg.redraw(cx);
+                }
                 let mut input = String::new();
                 self.filter_input(&te.input, Some(&mut input));
                 if input.len() == 0 {
@@ -131,6 +144,12 @@ impl Widget for TextInput {
                     if self.change(cx, ""){self.push_change_action(uid, scope, cx)}
                 }
             }
+            Hit::ClipboardPaste(cp) => {
+                // images and file lists can't go into the text, let the app decide what to do with them
+                if !self.read_only {
+                    cx.widget_action(uid, &scope.path, TextInputAction::Paste(cp.item));
+                }
+            }
             Hit::KeyDown(ke) => match ke.key_code {
                                 
                 KeyCode::Tab => {
@@ -161,14 +180,15 @@ impl Widget for TextInput {
                     self.undo_id += 1;
                     self.cursor_tail = 0;
                     self.cursor_head = self.text.chars().count();
+                    self.set_primary_selection(cx);
                     self.draw_bg.redraw(cx);
                 }
                 KeyCode::ArrowLeft => if !ke.modifiers.logo {
                                         
                     self.undo_id += 1;
-                    if self.cursor_head>0 {
-                        self.cursor_head -= 1;
-                    }
+                    // the arrows move the cursor visually, which in mixed direction text isn't
+                    // always a step back or forward in the text
+                    self.cursor_head = self.visual_cursor_step(cx, true);
                     if !ke.modifiers.shift {
                         self.cursor_tail = self.cursor_head;
                     }
@@ -176,9 +196,7 @@ impl Widget for TextInput {
                 },
                 KeyCode::ArrowRight => if !ke.modifiers.logo {
                     self.undo_id += 1;
-                    if self.cursor_head < self.text.chars().count() {
-                        self.cursor_head += 1;
-                    }
+                    self.cursor_head = self.visual_cursor_step(cx, false);
                     if !ke.modifiers.shift {
                         self.cursor_tail = self.cursor_head;
                     }
@@ -295,6 +313,9 @@ impl Widget for TextInput {
                 if fe.was_long_press() {
                     cx.show_clipboard_actions(self.selected_text());
                 }
+                else {
+                    self.set_primary_selection(cx);
+                }
                 if fe.is_over && fe.device.has_hovers() {
                     self.animator_play(cx, id!(hover.on));
                 }
@@ -357,6 +378,7 @@ pub enum TextInputAction {
     Escape,
     KeyFocus,
     KeyFocusLost,
+    Paste(ClipboardItem),
     None
 }
 
@@ -371,6 +393,20 @@ impl TextInput {
         }
     }
     
+    // the cursor one step to the left or right on screen, at the end of a line it moves on
+    // through the text in the direction of the layout
+    fn visual_cursor_step(&self, cx: &Cx, left: bool) -> usize {
+        if let Some(pos) = self.draw_text.get_visual_cursor_step(cx, self.cursor_head, left) {
+            return pos.min(self.text.chars().count())
+        }
+        if left != self.layout.rtl {
+            self.cursor_head.saturating_sub(1)
+        }
+        else {
+            (self.cursor_head + 1).min(self.text.chars().count())
+        }
+    }
+    
     pub fn selected_text(&mut self) -> String {
         let mut ret = String::new();
         let (left, right) = self.sorted_cursor();
@@ -514,6 +550,14 @@ impl TextInput {
         true
     }
     
+    /// Offers the selected text as the X11 primary selection, other platforms ignore it
+    pub fn set_primary_selection(&mut self, cx: &mut Cx) {
+        if self.cursor_head != self.cursor_tail && !self.secret {
+            let text = self.selected_text();
+            cx.clipboard_write(ClipboardSelection::Primary, vec![ClipboardItem::text(&text)]);
+        }
+    }
+    
     pub fn set_key_focus(&self, cx: &mut Cx) {
         cx.set_key_focus(self.draw_bg.area());
     }
@@ -558,7 +602,28 @@ impl TextInput {
         // this makes sure selection goes behind the text
         self.draw_select.append_to_draw_call(cx);
         
-        if self.text.len() == 0 {
+        // show the input method preedit in place of the selection
+        let (left, right) = self.sorted_cursor();
+        let composing = !self.composition.is_empty() && !self.secret;
+        let (cursor_head, cursor_tail) = if composing {
+            let head = left + self.composition.cursor;
+            (head, head)
+        }
+        else {
+            (self.cursor_head, self.cursor_tail)
+        };
+        
+        if composing {
+            let mut text: String = self.text.chars().take(left).collect();
+            text.push_str(&self.composition.text);
+            text.extend(self.text.chars().skip(right));
+            self.draw_text.is_empty = 0.0;
+            self.draw_text.draw_walk(cx, Walk::size(
+                self.walk.width,
+                self.walk.height
+            ), self.label_align, &text);
+        }
+        else if self.text.len() == 0 {
             self.draw_text.is_empty = 1.0;
             self.draw_text.draw_walk(cx, Walk::size(self.walk.width, self.walk.height), self.label_align, &self.empty_message);
         }
@@ -584,10 +649,11 @@ impl TextInput {
         // move the IME
         let line_spacing = self.draw_text.get_line_spacing();
         let top_drop = self.draw_text.get_font_size() * 0.2;
-        let head = self.draw_text.get_cursor_pos(cx, 0.0, self.cursor_head)
-            .unwrap_or(dvec2(turtle.pos.x, 0.0));
+        let line_start = if self.layout.rtl {turtle.pos.x + turtle.size.x} else {turtle.pos.x};
+        let head = self.draw_text.get_cursor_pos(cx, 0.0, cursor_head)
+            .unwrap_or(dvec2(line_start, 0.0));
         
-        if !self.read_only && self.cursor_head == self.cursor_tail {
+        if !self.read_only && cursor_head == cursor_tail {
             self.draw_cursor.draw_abs(cx, Rect {
                 pos: dvec2(head.x - 0.5 * self.cursor_size, head.y - top_drop),
                 size: dvec2(self.cursor_size, line_spacing)
@@ -596,7 +662,7 @@ impl TextInput {
         
         // draw selection rects
         
-        if self.cursor_head != self.cursor_tail {
+        if cursor_head != cursor_tail {
             let top_drop = self.draw_text.get_font_size() * 0.3;
             let bottom_drop = self.draw_text.get_font_size() * 0.1;
             
@@ -606,12 +672,34 @@ impl TextInput {
                 self.draw_select.draw_abs(cx, rect);
             }
         }
+        
+        // underline the preedit, and mark the clause the input method is converting
+        if composing {
+            let top_drop = self.draw_text.get_font_size() * 0.3;
+            let bottom_drop = self.draw_text.get_font_size() * 0.1;
+            let len = self.composition.text.chars().count();
+            let rects = self.draw_text.get_selection_rects(cx, left, left + len, dvec2(0.0, -top_drop), dvec2(0.0, bottom_drop));
+            for rect in rects {
+                self.draw_cursor.draw_abs(cx, Rect {
+                    pos: dvec2(rect.pos.x, rect.pos.y + rect.size.y - 1.0),
+                    size: dvec2(rect.size.x, 1.0)
+                });
+            }
+            for segment in &self.composition.segments {
+                if segment.style == TextCompositionStyle::Highlight {
+                    let rects = self.draw_text.get_selection_rects(cx, left + segment.start, left + segment.end, dvec2(0.0, -top_drop), dvec2(0.0, bottom_drop));
+                    for rect in rects {
+                        self.draw_select.draw_abs(cx, rect);
+                    }
+                }
+            }
+        }
         self.draw_bg.end(cx);
         
         if  cx.has_key_focus(self.draw_bg.area()) {
-            // ok so. if we have the IME we should inject a tracking point
-            let ime_x = self.draw_text.get_cursor_pos(cx, 0.5, self.cursor_head)
-                .unwrap_or(dvec2(turtle.pos.x, 0.0)).x;
+            // ok so. if we have the IME we should inject a tracking point, the candidate window sits at the start of the preedit
+            let ime_x = self.draw_text.get_cursor_pos(cx, 0.5, if composing {left} else {self.cursor_head})
+                .unwrap_or(dvec2(line_start, 0.0)).x;
             
             if self.numeric_only {
                 cx.hide_text_ime();
@@ -634,4 +722,20 @@ impl TextInputRef {
         None
     }
     
+    pub fn returned(&self, actions: &Actions) -> Option<String> {
+        if let TextInputAction::Return(val) = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some(val);
+        }
+        None
+    }
+    
+    pub fn escaped(&self, actions: &Actions) -> bool {
+        matches!(actions.find_widget_action_cast(self.widget_uid()), TextInputAction::Escape)
+    }
+    
+    pub fn set_key_focus(&self, cx: &mut Cx) {
+        if let Some(inner) = self.borrow() {
+            inner.set_key_focus(cx);
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use crate::{
     makepad_derive_widget::*,
     debug_view::DebugView,
+    image_cache::ImageCache,
     performance_view::PerformanceView,
     makepad_draw::*,
     nav_control::NavControl,
@@ -180,6 +181,12 @@ impl Widget for Window {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         let uid = self.widget_uid();
         
+        // decoded images arrive on a signal, they're taken in here before any widget
+        // below us can capture the actions that come with them
+        if let Event::Signal = event {
+            ImageCache::handle_signal(cx);
+        }
+        
         self.debug_view.handle_event(cx, event);
         if self.show_performance_view {
             self.performance_view.handle_widget(cx, event);